@@ -124,25 +124,28 @@ impl From<Color> for Hsv {
     }
 }
 
+/// Components are truncated, use `Color::from_hsv` to get rounded ones.
 impl From<Hsv> for Color {
     fn from(hsv: Hsv) -> Self {
-        let hi = ((hsv.hue / 60.0) % 6.0) as i32;
-        let vmin = ((100.0 - hsv.saturation) * hsv.brightness) / 100.0;
-        let a = (hsv.brightness - vmin) * ((hsv.hue % 60.0) / 60.0);
-        let vinc = vmin + a;
-        let vdec = hsv.brightness - a;
-        Self::from(
-            match hi {
-                0 => Vector3::new(hsv.brightness, vinc, vmin),
-                1 => Vector3::new(vdec, hsv.brightness, vmin),
-                2 => Vector3::new(vmin, hsv.brightness, vinc),
-                3 => Vector3::new(vmin, vdec, hsv.brightness),
-                4 => Vector3::new(vinc, vmin, hsv.brightness),
-                5 => Vector3::new(hsv.brightness, vmin, vdec),
-                _ => unreachable!(),
-            }
-            .scale(1.0 / 100.0),
-        )
+        Self::from(hsv_to_rgb(&hsv).scale(1.0 / 100.0))
+    }
+}
+
+/// Returns RGB components of HSV color in [0; 100] range.
+fn hsv_to_rgb(hsv: &Hsv) -> Vector3<f32> {
+    let hi = ((hsv.hue / 60.0) % 6.0) as i32;
+    let vmin = ((100.0 - hsv.saturation) * hsv.brightness) / 100.0;
+    let a = (hsv.brightness - vmin) * ((hsv.hue % 60.0) / 60.0);
+    let vinc = vmin + a;
+    let vdec = hsv.brightness - a;
+    match hi {
+        0 => Vector3::new(hsv.brightness, vinc, vmin),
+        1 => Vector3::new(vdec, hsv.brightness, vmin),
+        2 => Vector3::new(vmin, hsv.brightness, vinc),
+        3 => Vector3::new(vmin, vdec, hsv.brightness),
+        4 => Vector3::new(vinc, vmin, hsv.brightness),
+        5 => Vector3::new(hsv.brightness, vmin, vdec),
+        _ => unreachable!(),
     }
 }
 
@@ -218,6 +221,46 @@ impl Color {
         }
     }
 
+    /// Creates color from hue in [0; 360] range, saturation and brightness in [0; 100] range.
+    /// Components are rounded, so RGB -> HSV -> RGB conversion gives the same color.
+    pub fn from_hsv(hue: f32, saturation: f32, brightness: f32, a: u8) -> Self {
+        let rgb = hsv_to_rgb(&Hsv::new(hue, saturation, brightness));
+        let component = |v: f32| (v * 2.55).round().max(0.0).min(255.0) as u8;
+        Self {
+            r: component(rgb.x),
+            g: component(rgb.y),
+            b: component(rgb.z),
+            a,
+        }
+    }
+
+    pub fn to_hsv(self) -> Hsv {
+        Hsv::from(self)
+    }
+
+    /// Returns same color with given alpha.
+    pub fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Moves brightness towards maximum by given fraction in [0; 1] range, alpha is kept.
+    /// Useful to derive hover shade of some base color.
+    pub fn lighten(self, amount: f32) -> Self {
+        let hsv = self.to_hsv();
+        let amount = amount.max(0.0).min(1.0);
+        let brightness = hsv.brightness + (100.0 - hsv.brightness) * amount;
+        Self::from_hsv(hsv.hue, hsv.saturation, brightness, self.a)
+    }
+
+    /// Moves brightness towards zero by given fraction in [0; 1] range, alpha is kept.
+    /// Useful to derive pressed shade of some base color.
+    pub fn darken(self, amount: f32) -> Self {
+        let hsv = self.to_hsv();
+        let amount = amount.max(0.0).min(1.0);
+        let brightness = hsv.brightness * (1.0 - amount);
+        Self::from_hsv(hsv.hue, hsv.saturation, brightness, self.a)
+    }
+
     pub fn lerp(self, other: Self, t: f32) -> Self {
         let dr = (t * (i32::from(other.r) - i32::from(self.r)) as f32) as i32;
         let dg = (t * (i32::from(other.g) - i32::from(self.g)) as f32) as i32;
@@ -0,0 +1,150 @@
+//! Read-only accessibility view of the UI. It is a simplified version of the visual tree which
+//! contains only meaningful nodes (buttons, texts, etc.) with their labels and states. This is
+//! a base for integration with platform accessibility APIs (screen readers, etc.).
+
+use crate::{
+    core::{math::Rect, pool::Handle},
+    message::MessageData,
+    Control, UINode, UserInterface,
+};
+
+/// Semantic role of a widget.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum AccessibleRole {
+    /// Layout or decoration node without any semantic meaning. Such nodes will be skipped
+    /// in accessibility tree unless they have a name.
+    #[default]
+    Generic,
+    Button,
+    CheckBox,
+    Text,
+    TextBox,
+    Image,
+    List,
+    ListItem,
+    ComboBox,
+    Menu,
+    MenuItem,
+    ScrollBar,
+    Slider,
+    ProgressBar,
+    SpinButton,
+    TabList,
+    Tree,
+    TreeItem,
+    Group,
+    Window,
+    Dialog,
+}
+
+impl AccessibleRole {
+    /// Returns true if a widget with the role does not have its own label and it should
+    /// be taken from its content (for example text of a button).
+    pub fn is_labeled_by_content(self) -> bool {
+        matches!(
+            self,
+            AccessibleRole::Button
+                | AccessibleRole::CheckBox
+                | AccessibleRole::ListItem
+                | AccessibleRole::MenuItem
+                | AccessibleRole::TreeItem
+                | AccessibleRole::Group
+                | AccessibleRole::Window
+                | AccessibleRole::Dialog
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibleNode<M: MessageData, C: Control<M, C>> {
+    /// Handle of widget in the visual tree.
+    pub handle: Handle<UINode<M, C>>,
+    pub role: AccessibleRole,
+    pub name: String,
+    /// Current value or state of widget, for example text of a text box or state of a check box.
+    pub value: Option<String>,
+    pub enabled: bool,
+    pub focused: bool,
+    pub screen_bounds: Rect<f32>,
+    pub children: Vec<AccessibleNode<M, C>>,
+}
+
+/// Searches for a text in the sub-tree, it is used as label of widgets which does not
+/// have labels by themselves, such as buttons.
+pub(in crate) fn find_label<M: MessageData, C: Control<M, C>>(
+    ui: &UserInterface<M, C>,
+    node: Handle<UINode<M, C>>,
+) -> Option<String> {
+    for &child in ui.node(node).children() {
+        let child_ref = ui.node(child);
+        if !child_ref.visibility() {
+            continue;
+        }
+        if child_ref.accessible_role() == AccessibleRole::Text {
+            if let Some(name) = child_ref.accessible_name() {
+                return Some(name);
+            }
+        }
+        if let Some(name) = find_label(ui, child) {
+            return Some(name);
+        }
+    }
+    None
+}
+
+fn build_nodes<M: MessageData, C: Control<M, C>>(
+    ui: &UserInterface<M, C>,
+    handle: Handle<UINode<M, C>>,
+    enabled: bool,
+    output: &mut Vec<AccessibleNode<M, C>>,
+) {
+    let node = ui.node(handle);
+    if !node.is_globally_visible() {
+        return;
+    }
+
+    let enabled = enabled && node.enabled();
+    let role = node.accessible_role();
+    let name = node
+        .accessible_label()
+        .map(ToOwned::to_owned)
+        .or_else(|| node.accessible_name())
+        .or_else(|| {
+            if role.is_labeled_by_content() {
+                find_label(ui, handle)
+            } else {
+                None
+            }
+        });
+
+    let mut children = Vec::new();
+    for &child in node.children() {
+        build_nodes(ui, child, enabled, &mut children);
+    }
+
+    if role == AccessibleRole::Generic && name.is_none() {
+        // Nothing meaningful here, children are moved up on the tree.
+        output.extend(children);
+    } else {
+        output.push(AccessibleNode {
+            handle,
+            role,
+            name: name.unwrap_or_default(),
+            value: node.accessible_value(),
+            enabled,
+            focused: ui.keyboard_focus_node() == handle,
+            screen_bounds: node.screen_bounds(),
+            children,
+        });
+    }
+}
+
+/// Builds accessibility tree starting from given node. Returns top level accessible nodes.
+pub fn build_accessibility_tree<M: MessageData, C: Control<M, C>>(
+    ui: &UserInterface<M, C>,
+    root: Handle<UINode<M, C>>,
+) -> Vec<AccessibleNode<M, C>> {
+    let mut nodes = Vec::new();
+    build_nodes(ui, root, true, &mut nodes);
+    nodes
+}
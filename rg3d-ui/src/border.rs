@@ -1,17 +1,40 @@
 use crate::draw::Draw;
 use crate::{
-    core::{algebra::Vector2, math::Rect, pool::Handle, scope_profile},
-    draw::{CommandTexture, DrawingContext},
-    message::{MessageData, UiMessage},
+    brush::Brush,
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle, scope_profile},
+    draw::{CommandTexture, DrawingContext, SharedTexture},
+    message::{BorderMessage, MessageData, MessageDirection, UiMessage, UiMessageData},
     widget::{Widget, WidgetBuilder},
     BuildContext, Control, Thickness, UINode, UserInterface, BRUSH_PRIMARY,
 };
 use std::ops::{Deref, DerefMut};
 
+/// Amount of segments that approximates each rounded corner.
+const CORNER_SEGMENTS: usize = 6;
+
+/// Defines how background texture of a border fills its bounds.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TextureFill {
+    /// Texture is stretched over the whole bounds.
+    Stretch,
+    /// Texture is split into 9 parts: corners are drawn as is, edges are stretched along
+    /// one axis and center part is stretched along both axes. `margin` defines size of
+    /// corners on screen (in units), `uv_margin` defines size of corners in normalized
+    /// texture coordinates.
+    NineSlice {
+        margin: Thickness,
+        uv_margin: Thickness,
+    },
+}
+
 #[derive(Clone)]
 pub struct Border<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     stroke_thickness: Thickness,
+    /// Inner space between the stroke and content of the border.
+    padding: Thickness,
+    background_texture: Option<(SharedTexture, TextureFill)>,
+    corner_radius: f32,
 }
 
 crate::define_widget_deref!(Border<M, C>);
@@ -24,10 +47,13 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Border<M, C> {
     ) -> Vector2<f32> {
         scope_profile!();
 
-        let margin_x = self.stroke_thickness.left + self.stroke_thickness.right;
-        let margin_y = self.stroke_thickness.top + self.stroke_thickness.bottom;
+        // Padding is applied inside the stroke, so content never overlaps the frame.
+        let margin = (self.stroke_thickness + self.padding).axes_margin();
 
-        let size_for_child = Vector2::new(available_size.x - margin_x, available_size.y - margin_y);
+        let size_for_child = Vector2::new(
+            (available_size.x - margin.x).max(0.0),
+            (available_size.y - margin.y).max(0.0),
+        );
         let mut desired_size = Vector2::default();
 
         for child_handle in self.widget.children() {
@@ -42,21 +68,18 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Border<M, C> {
             }
         }
 
-        desired_size.x += margin_x;
-        desired_size.y += margin_y;
-
-        desired_size
+        desired_size + margin
     }
 
     fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
         scope_profile!();
 
-        let rect_for_child = Rect::new(
-            self.stroke_thickness.left,
-            self.stroke_thickness.top,
-            final_size.x - (self.stroke_thickness.right + self.stroke_thickness.left),
-            final_size.y - (self.stroke_thickness.bottom + self.stroke_thickness.top),
-        );
+        let rect_for_child = (self.stroke_thickness + self.padding).deflate_rect(&Rect::new(
+            0.0,
+            0.0,
+            final_size.x,
+            final_size.y,
+        ));
 
         for child_handle in self.widget.children() {
             ui.node(*child_handle).arrange(ui, &rect_for_child);
@@ -66,22 +89,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Border<M, C> {
     }
 
     fn draw(&self, drawing_context: &mut DrawingContext) {
-        let bounds = self.widget.screen_bounds();
-        DrawingContext::push_rect_filled(drawing_context, &bounds, None);
-        drawing_context.commit(
-            self.clip_bounds(),
-            self.widget.background(),
-            CommandTexture::None,
-            None,
-        );
-
-        drawing_context.push_rect_vary(&bounds, self.stroke_thickness);
-        drawing_context.commit(
-            self.clip_bounds(),
-            self.widget.foreground(),
-            CommandTexture::None,
-            None,
-        );
+        self.draw_with_background(drawing_context, self.widget.background());
     }
 
     fn handle_routed_message(
@@ -90,6 +98,21 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Border<M, C> {
         message: &mut UiMessage<M, C>,
     ) {
         self.widget.handle_routed_message(ui, message);
+
+        if message.destination() == self.handle()
+            && message.direction() == MessageDirection::ToWidget
+        {
+            if let UiMessageData::Border(msg) = message.data() {
+                match *msg {
+                    BorderMessage::StrokeThickness(thickness) => {
+                        self.set_stroke_thickness(thickness);
+                    }
+                    BorderMessage::CornerRadius(radius) => {
+                        self.set_corner_radius(radius);
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -98,6 +121,9 @@ impl<M: MessageData, C: Control<M, C>> Border<M, C> {
         Self {
             widget,
             stroke_thickness: Thickness::uniform(1.0),
+            padding: Thickness::zero(),
+            background_texture: None,
+            corner_radius: 0.0,
         }
     }
 
@@ -108,11 +134,160 @@ impl<M: MessageData, C: Control<M, C>> Border<M, C> {
         }
         self
     }
+
+    pub fn stroke_thickness(&self) -> Thickness {
+        self.stroke_thickness
+    }
+
+    pub fn set_padding(&mut self, padding: Thickness) -> &mut Self {
+        if self.padding != padding {
+            self.padding = padding;
+            self.widget.invalidate_layout();
+        }
+        self
+    }
+
+    pub fn padding(&self) -> Thickness {
+        self.padding
+    }
+
+    /// Sets texture that will be used to fill background of the border instead of the solid
+    /// brush. Stroke is still drawn over the texture. `None` returns solid fill back.
+    pub fn set_background_texture(
+        &mut self,
+        texture: Option<SharedTexture>,
+        fill: TextureFill,
+    ) -> &mut Self {
+        self.background_texture = texture.map(|texture| (texture, fill));
+        self
+    }
+
+    pub fn background_texture(&self) -> Option<&(SharedTexture, TextureFill)> {
+        self.background_texture.as_ref()
+    }
+
+    /// Sets radius of rounded corners, zero gives sharp corners. Stroke of a border with
+    /// rounded corners has uniform thickness, the largest side of stroke thickness is used.
+    pub fn set_corner_radius(&mut self, radius: f32) -> &mut Self {
+        self.corner_radius = radius.max(0.0);
+        self
+    }
+
+    pub fn corner_radius(&self) -> f32 {
+        self.corner_radius
+    }
+
+    /// Draws the border using given brush for its background instead of the brush of the widget.
+    pub fn draw_with_background(&self, drawing_context: &mut DrawingContext, background: Brush) {
+        let bounds = self.widget.screen_bounds();
+        if let Some((texture, fill)) = self.background_texture.as_ref() {
+            match *fill {
+                TextureFill::Stretch => {
+                    drawing_context.push_rect_filled(&bounds, None);
+                }
+                TextureFill::NineSlice { margin, uv_margin } => {
+                    push_nine_slice(drawing_context, &bounds, margin, uv_margin);
+                }
+            }
+            drawing_context.commit(
+                self.clip_bounds(),
+                Brush::Solid(Color::WHITE),
+                CommandTexture::Texture(texture.clone()),
+                None,
+            );
+        } else {
+            if self.corner_radius > 0.0 {
+                drawing_context.push_rounded_rect_filled(
+                    &bounds,
+                    self.corner_radius,
+                    CORNER_SEGMENTS,
+                );
+            } else {
+                DrawingContext::push_rect_filled(drawing_context, &bounds, None);
+            }
+            drawing_context.commit(self.clip_bounds(), background, CommandTexture::None, None);
+        }
+
+        if self.corner_radius > 0.0 {
+            let t = self.stroke_thickness;
+            let thickness = t.left.max(t.top).max(t.right).max(t.bottom);
+            if thickness > 0.0 {
+                drawing_context.push_rounded_rect(
+                    &bounds,
+                    thickness,
+                    self.corner_radius,
+                    CORNER_SEGMENTS,
+                );
+            }
+        } else {
+            drawing_context.push_rect_vary(&bounds, self.stroke_thickness);
+        }
+        drawing_context.commit(
+            self.clip_bounds(),
+            self.widget.foreground(),
+            CommandTexture::None,
+            None,
+        );
+    }
+}
+
+/// Splits bounds into 3x3 grid and emits a quad for each part with respective texture
+/// coordinates.
+pub(in crate) fn push_nine_slice(
+    drawing_context: &mut DrawingContext,
+    bounds: &Rect<f32>,
+    margin: Thickness,
+    uv_margin: Thickness,
+) {
+    // Corners must not overlap when bounds are smaller than margins.
+    let scale_x = (bounds.w() / margin.total_horizontal()).min(1.0);
+    let scale_y = (bounds.h() / margin.total_vertical()).min(1.0);
+
+    let xs = [
+        bounds.x(),
+        bounds.x() + margin.left * scale_x,
+        bounds.x() + bounds.w() - margin.right * scale_x,
+        bounds.x() + bounds.w(),
+    ];
+    let ys = [
+        bounds.y(),
+        bounds.y() + margin.top * scale_y,
+        bounds.y() + bounds.h() - margin.bottom * scale_y,
+        bounds.y() + bounds.h(),
+    ];
+    let us = [0.0, uv_margin.left, 1.0 - uv_margin.right, 1.0];
+    let vs = [0.0, uv_margin.top, 1.0 - uv_margin.bottom, 1.0];
+
+    for row in 0..3 {
+        for column in 0..3 {
+            let rect = Rect::new(
+                xs[column],
+                ys[row],
+                xs[column + 1] - xs[column],
+                ys[row + 1] - ys[row],
+            );
+            if rect.w() <= 0.0 || rect.h() <= 0.0 {
+                continue;
+            }
+            drawing_context.push_rect_filled(
+                &rect,
+                Some(&[
+                    Vector2::new(us[column], vs[row]),
+                    Vector2::new(us[column + 1], vs[row]),
+                    Vector2::new(us[column + 1], vs[row + 1]),
+                    Vector2::new(us[column], vs[row + 1]),
+                ]),
+            );
+        }
+    }
 }
 
 pub struct BorderBuilder<M: MessageData, C: Control<M, C>> {
     pub widget_builder: WidgetBuilder<M, C>,
     pub stroke_thickness: Option<Thickness>,
+    pub padding: Option<Thickness>,
+    pub background_texture: Option<(SharedTexture, TextureFill)>,
+    pub corner_radius: f32,
 }
 
 impl<M: MessageData, C: Control<M, C>> BorderBuilder<M, C> {
@@ -120,6 +295,9 @@ impl<M: MessageData, C: Control<M, C>> BorderBuilder<M, C> {
         Self {
             widget_builder,
             stroke_thickness: None,
+            padding: None,
+            background_texture: None,
+            corner_radius: 0.0,
         }
     }
 
@@ -128,6 +306,21 @@ impl<M: MessageData, C: Control<M, C>> BorderBuilder<M, C> {
         self
     }
 
+    pub fn with_padding(mut self, padding: Thickness) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    pub fn with_background_texture(mut self, texture: SharedTexture, fill: TextureFill) -> Self {
+        self.background_texture = Some((texture, fill));
+        self
+    }
+
+    pub fn with_corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = radius;
+        self
+    }
+
     pub fn build_border(mut self) -> Border<M, C> {
         if self.widget_builder.foreground.is_none() {
             self.widget_builder.foreground = Some(BRUSH_PRIMARY);
@@ -137,6 +330,9 @@ impl<M: MessageData, C: Control<M, C>> BorderBuilder<M, C> {
             stroke_thickness: self
                 .stroke_thickness
                 .unwrap_or_else(|| Thickness::uniform(1.0)),
+            padding: self.padding.unwrap_or_else(Thickness::zero),
+            background_texture: self.background_texture,
+            corner_radius: self.corner_radius.max(0.0),
         }
     }
 
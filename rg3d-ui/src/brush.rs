@@ -20,3 +20,41 @@ pub enum Brush {
         stops: Vec<GradientPoint>,
     },
 }
+
+/// Multiplies every component of a color by corresponding component of a tint.
+pub fn tint_color(color: Color, tint: Color) -> Color {
+    let mul = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+    Color::from_rgba(
+        mul(color.r, tint.r),
+        mul(color.g, tint.g),
+        mul(color.b, tint.b),
+        mul(color.a, tint.a),
+    )
+}
+
+impl Brush {
+    /// Returns brush with every color multiplied by given tint.
+    pub fn tinted(&self, tint: Color) -> Brush {
+        let tint_stops = |stops: &[GradientPoint]| {
+            stops
+                .iter()
+                .map(|point| GradientPoint {
+                    stop: point.stop,
+                    color: tint_color(point.color, tint),
+                })
+                .collect()
+        };
+        match self {
+            Brush::Solid(color) => Brush::Solid(tint_color(*color, tint)),
+            Brush::LinearGradient { from, to, stops } => Brush::LinearGradient {
+                from: *from,
+                to: *to,
+                stops: tint_stops(stops),
+            },
+            Brush::RadialGradient { center, stops } => Brush::RadialGradient {
+                center: *center,
+                stops: tint_stops(stops),
+            },
+        }
+    }
+}
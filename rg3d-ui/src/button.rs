@@ -1,10 +1,12 @@
 use crate::brush::GradientPoint;
-use crate::core::algebra::Vector2;
+use crate::core::algebra::{Matrix3, Vector2};
 use crate::{
-    border::BorderBuilder,
+    accessibility::AccessibleRole,
+    border::{push_nine_slice, BorderBuilder, TextureFill},
     brush::Brush,
-    core::pool::Handle,
-    decorator::DecoratorBuilder,
+    core::{color::Color, pool::Handle},
+    decorator::{DecoratorBuilder, VisualState},
+    draw::{CommandTexture, Draw, DrawingContext, SharedTexture},
     message::{
         ButtonMessage, MessageData, MessageDirection, UiMessage, UiMessageData, WidgetMessage,
     },
@@ -17,11 +19,54 @@ use crate::{
 };
 use std::ops::{Deref, DerefMut};
 
+/// Textures of a skinned button for every visual state. A missing texture of a state is
+/// replaced by the normal texture tinted with a color of the state.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateTextures {
+    pub normal: Option<SharedTexture>,
+    pub hover: Option<SharedTexture>,
+    pub pressed: Option<SharedTexture>,
+    pub disabled: Option<SharedTexture>,
+}
+
+impl StateTextures {
+    /// Returns texture and tint for given state, or `None` if there is no texture at all.
+    pub fn resolve(&self, state: VisualState) -> Option<(&SharedTexture, Color)> {
+        let (texture, fallback_tint) = match state {
+            VisualState::Normal => (&self.normal, Color::WHITE),
+            VisualState::Hover => (&self.hover, Color::opaque(225, 225, 225)),
+            VisualState::Pressed => (&self.pressed, Color::opaque(170, 170, 170)),
+            VisualState::Disabled => (&self.disabled, Color::opaque(120, 120, 120)),
+        };
+        match texture {
+            Some(texture) => Some((texture, Color::WHITE)),
+            None => self.normal.as_ref().map(|normal| (normal, fallback_tint)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.normal.is_none()
+            && self.hover.is_none()
+            && self.pressed.is_none()
+            && self.disabled.is_none()
+    }
+}
+
 #[derive(Clone)]
 pub struct Button<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     decorator: Handle<UINode<M, C>>,
     content: Handle<UINode<M, C>>,
+    /// Back was created by the button (it wasn't set by `ButtonBuilder::with_back`), so it is
+    /// replaced when state textures are set or cleared.
+    default_back: bool,
+    state_textures: StateTextures,
+    texture_fill: TextureFill,
+    is_mouse_over: bool,
+    is_pressed: bool,
+    pressed_content_offset: Vector2<f32>,
+    /// Render transform of the content before it was offset by press.
+    content_transform: Option<Matrix3<f32>>,
 }
 
 crate::define_widget_deref!(Button<M, C>);
@@ -36,6 +81,13 @@ impl<M: MessageData, C: Control<M, C>> Button<M, C> {
             widget,
             decorator: body,
             content,
+            default_back: false,
+            state_textures: Default::default(),
+            texture_fill: TextureFill::Stretch,
+            is_mouse_over: false,
+            is_pressed: false,
+            pressed_content_offset: Vector2::default(),
+            content_transform: None,
         }
     }
 
@@ -47,14 +99,107 @@ impl<M: MessageData, C: Control<M, C>> Button<M, C> {
         self.content = content;
         self
     }
+
+    /// Sets textures for every visual state, see `ButtonBuilder::with_state_textures`.
+    pub fn set_state_textures(
+        &mut self,
+        normal: Option<SharedTexture>,
+        hover: Option<SharedTexture>,
+        pressed: Option<SharedTexture>,
+        disabled: Option<SharedTexture>,
+    ) -> &mut Self {
+        self.state_textures = StateTextures {
+            normal,
+            hover,
+            pressed,
+            disabled,
+        };
+        self
+    }
+
+    pub fn state_textures(&self) -> &StateTextures {
+        &self.state_textures
+    }
+
+    pub fn set_texture_fill(&mut self, fill: TextureFill) -> &mut Self {
+        self.texture_fill = fill;
+        self
+    }
+
+    pub fn texture_fill(&self) -> TextureFill {
+        self.texture_fill
+    }
+
+    pub fn visual_state(&self) -> VisualState {
+        VisualState::resolve(self.is_mouse_over, self.is_pressed, self.enabled())
+    }
+
+    /// Sets offset of content while the button is pressed, see
+    /// `ButtonBuilder::with_pressed_content_offset`.
+    pub fn set_pressed_content_offset(&mut self, offset: Vector2<f32>) -> &mut Self {
+        self.pressed_content_offset = offset;
+        self
+    }
+
+    pub fn pressed_content_offset(&self) -> Vector2<f32> {
+        self.pressed_content_offset
+    }
+
+    fn set_pressed(&mut self, ui: &UserInterface<M, C>, pressed: bool) {
+        self.is_pressed = pressed;
+
+        if !ui.nodes().is_valid_handle(self.content) {
+            return;
+        }
+        if pressed {
+            if self.pressed_content_offset != Vector2::default() && self.content_transform.is_none()
+            {
+                let transform = *ui.node(self.content).render_transform();
+                self.content_transform = Some(transform);
+                ui.send_message(WidgetMessage::render_transform(
+                    self.content,
+                    MessageDirection::ToWidget,
+                    Matrix3::new_translation(&self.pressed_content_offset) * transform,
+                ));
+            }
+        } else if let Some(transform) = self.content_transform.take() {
+            ui.send_message(WidgetMessage::render_transform(
+                self.content,
+                MessageDirection::ToWidget,
+                transform,
+            ));
+        }
+    }
 }
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for Button<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Button
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         node_map.resolve(&mut self.content);
         node_map.resolve(&mut self.decorator);
     }
 
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        if let Some((texture, tint)) = self.state_textures.resolve(self.visual_state()) {
+            let bounds = self.screen_bounds();
+            match self.texture_fill {
+                TextureFill::Stretch => drawing_context.push_rect_filled(&bounds, None),
+                TextureFill::NineSlice { margin, uv_margin } => {
+                    push_nine_slice(drawing_context, &bounds, margin, uv_margin)
+                }
+            }
+            drawing_context.commit(
+                self.clip_bounds(),
+                Brush::Solid(tint),
+                CommandTexture::Texture(texture.clone()),
+                None,
+            );
+        }
+    }
+
     fn handle_routed_message(
         &mut self,
         ui: &mut UserInterface<M, C>,
@@ -68,18 +213,40 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Button<M, C> {
                     || self.has_descendant(message.destination(), ui)
                 {
                     match msg {
+                        WidgetMessage::MouseEnter => self.is_mouse_over = true,
+                        WidgetMessage::MouseLeave => {
+                            self.is_mouse_over = false;
+                            self.set_pressed(ui, false);
+                        }
                         WidgetMessage::MouseUp { .. } => {
-                            ui.send_message(ButtonMessage::click(
-                                self.handle(),
-                                MessageDirection::FromWidget,
-                            ));
+                            self.set_pressed(ui, false);
+                            // Release after a drag is not a click.
+                            if !ui.is_drag_started() {
+                                ui.send_message(ButtonMessage::click(
+                                    self.handle(),
+                                    MessageDirection::FromWidget,
+                                ));
+                            }
                             ui.release_mouse_capture();
                             message.set_handled(true);
                         }
+                        WidgetMessage::MouseCaptureLost => self.set_pressed(ui, false),
                         WidgetMessage::MouseDown { .. } => {
+                            self.set_pressed(ui, true);
                             ui.capture_mouse(message.destination());
                             message.set_handled(true);
                         }
+                        &WidgetMessage::Enabled(enabled)
+                            if message.destination() == self.handle()
+                                && message.direction() == MessageDirection::ToWidget =>
+                        {
+                            // Decorator must know about disabled state to draw itself properly.
+                            ui.send_message(WidgetMessage::enabled(
+                                self.decorator,
+                                MessageDirection::ToWidget,
+                                enabled,
+                            ));
+                        }
                         _ => (),
                     }
                 }
@@ -96,12 +263,20 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Button<M, C> {
                                 ));
                             }
                             self.content = *content;
+                            self.content_transform = None;
                             ui.send_message(WidgetMessage::link(
                                 self.content,
                                 MessageDirection::ToWidget,
                                 self.decorator,
                             ));
                         }
+                        ButtonMessage::StateTextures(textures) => {
+                            let was_empty = self.state_textures.is_empty();
+                            self.state_textures = textures.clone();
+                            if self.default_back && was_empty != textures.is_empty() {
+                                self.replace_back(ui);
+                            }
+                        }
                     }
                 }
             }
@@ -116,6 +291,81 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Button<M, C> {
     }
 }
 
+impl<M: MessageData, C: Control<M, C>> Button<M, C> {
+    /// Replaces default back by the one that matches current state textures, content is
+    /// moved to the new back.
+    fn replace_back(&mut self, ui: &mut UserInterface<M, C>) {
+        let back = make_back(&mut ui.build_ctx(), &self.state_textures);
+        ui.send_message(WidgetMessage::link(
+            back,
+            MessageDirection::ToWidget,
+            self.handle(),
+        ));
+        if self.content.is_some() {
+            ui.send_message(WidgetMessage::link(
+                self.content,
+                MessageDirection::ToWidget,
+                back,
+            ));
+        }
+        if self.decorator.is_some() {
+            ui.send_message(WidgetMessage::remove(
+                self.decorator,
+                MessageDirection::ToWidget,
+            ));
+        }
+        if !self.enabled() {
+            ui.send_message(WidgetMessage::enabled(
+                back,
+                MessageDirection::ToWidget,
+                false,
+            ));
+        }
+        self.decorator = back;
+    }
+}
+
+/// Creates back of a button: a decorator, or transparent host of content for skinned buttons
+/// that draw state textures by themselves.
+fn make_back<M: MessageData, C: Control<M, C>>(
+    ctx: &mut BuildContext<M, C>,
+    state_textures: &StateTextures,
+) -> Handle<UINode<M, C>> {
+    if !state_textures.is_empty() {
+        return BorderBuilder::new(
+            WidgetBuilder::new().with_background(Brush::Solid(Color::TRANSPARENT)),
+        )
+        .with_stroke_thickness(Thickness::zero())
+        .build(ctx);
+    }
+
+    DecoratorBuilder::new(
+        BorderBuilder::new(WidgetBuilder::new().with_foreground(Brush::LinearGradient {
+            from: Vector2::new(0.5, 0.0),
+            to: Vector2::new(0.5, 1.0),
+            stops: vec![
+                GradientPoint {
+                    stop: 0.0,
+                    color: COLOR_LIGHTEST,
+                },
+                GradientPoint {
+                    stop: 0.25,
+                    color: COLOR_LIGHTEST,
+                },
+                GradientPoint {
+                    stop: 1.0,
+                    color: COLOR_DARKEST,
+                },
+            ],
+        }))
+        .with_stroke_thickness(Thickness::uniform(1.0)),
+    )
+    .with_normal_brush(BRUSH_LIGHT)
+    .with_hover_brush(BRUSH_LIGHTER)
+    .with_pressed_brush(BRUSH_LIGHTEST)
+    .build(ctx)
+}
+
 pub enum ButtonContent<M: MessageData, C: Control<M, C>> {
     Text(String),
     Node(Handle<UINode<M, C>>),
@@ -126,6 +376,9 @@ pub struct ButtonBuilder<M: MessageData, C: Control<M, C>> {
     content: Option<ButtonContent<M, C>>,
     font: Option<SharedFont>,
     back: Option<Handle<UINode<M, C>>>,
+    state_textures: StateTextures,
+    texture_fill: TextureFill,
+    pressed_content_offset: Vector2<f32>,
 }
 
 impl<M: MessageData, C: Control<M, C>> ButtonBuilder<M, C> {
@@ -135,6 +388,9 @@ impl<M: MessageData, C: Control<M, C>> ButtonBuilder<M, C> {
             content: None,
             font: None,
             back: None,
+            state_textures: Default::default(),
+            texture_fill: TextureFill::Stretch,
+            pressed_content_offset: Vector2::default(),
         }
     }
 
@@ -158,7 +414,34 @@ impl<M: MessageData, C: Control<M, C>> ButtonBuilder<M, C> {
         self
     }
 
-    pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+    /// Sets textures for visual states of the button (normal, hover, pressed, disabled),
+    /// the button draws texture of current state instead of the default decorator. Missing
+    /// textures are replaced by tinted normal texture.
+    pub fn with_state_textures(mut self, textures: StateTextures) -> Self {
+        self.state_textures = textures;
+        self
+    }
+
+    /// Sets how state textures fill bounds of the button, use `TextureFill::NineSlice` for
+    /// skinned buttons that must stretch without distortion of corners.
+    pub fn with_texture_fill(mut self, fill: TextureFill) -> Self {
+        self.texture_fill = fill;
+        self
+    }
+
+    /// Sets offset that is applied to content while the button is pressed (for example one
+    /// pixel down-right for tactile feedback). Offset is applied with render transform, so it
+    /// does not affect layout and is reverted exactly on release. Default is zero.
+    pub fn with_pressed_content_offset(mut self, offset: Vector2<f32>) -> Self {
+        self.pressed_content_offset = offset;
+        self
+    }
+
+    pub fn build(mut self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        if self.widget_builder.tab_stop.is_none() {
+            self.widget_builder.tab_stop = Some(true);
+        }
+
         let content = if let Some(content) = self.content {
             match content {
                 ButtonContent::Text(txt) => TextBuilder::new(WidgetBuilder::new())
@@ -173,44 +456,237 @@ impl<M: MessageData, C: Control<M, C>> ButtonBuilder<M, C> {
             Handle::NONE
         };
 
-        let back = self.back.unwrap_or_else(|| {
-            DecoratorBuilder::new(
-                BorderBuilder::new(
-                    WidgetBuilder::new()
-                        .with_foreground(Brush::LinearGradient {
-                            from: Vector2::new(0.5, 0.0),
-                            to: Vector2::new(0.5, 1.0),
-                            stops: vec![
-                                GradientPoint {
-                                    stop: 0.0,
-                                    color: COLOR_LIGHTEST,
-                                },
-                                GradientPoint {
-                                    stop: 0.25,
-                                    color: COLOR_LIGHTEST,
-                                },
-                                GradientPoint {
-                                    stop: 1.0,
-                                    color: COLOR_DARKEST,
-                                },
-                            ],
-                        })
-                        .with_child(content),
-                )
-                .with_stroke_thickness(Thickness::uniform(1.0)),
-            )
-            .with_normal_brush(BRUSH_LIGHT)
-            .with_hover_brush(BRUSH_LIGHTER)
-            .with_pressed_brush(BRUSH_LIGHTEST)
-            .build(ctx)
-        });
+        let state_textures = self.state_textures;
+        let default_back = self.back.is_none();
+        let back = self.back.unwrap_or_else(|| make_back(ctx, &state_textures));
         ctx.link(content, back);
 
         let button = Button {
             widget: self.widget_builder.with_child(back).build(),
             decorator: back,
             content,
+            default_back,
+            state_textures,
+            texture_fill: self.texture_fill,
+            is_mouse_over: false,
+            is_pressed: false,
+            pressed_content_offset: self.pressed_content_offset,
+            content_transform: None,
         };
         ctx.add_node(UINode::Button(button))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        button::{ButtonBuilder, StateTextures},
+        core::{
+            algebra::{Matrix3, Vector2},
+            color::Color,
+        },
+        decorator::VisualState,
+        draw::SharedTexture,
+        message::{
+            ButtonMessage, ButtonState, MessageDirection, MouseButton, OsEvent, TouchPhase,
+            UiMessageData,
+        },
+        node::{StubNode, UINode},
+        scroll_viewer::ScrollViewerBuilder,
+        widget::WidgetBuilder,
+        UserInterface,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn pressed_content_offset() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let content = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        ButtonBuilder::new(WidgetBuilder::new())
+            .with_content(content)
+            .with_pressed_content_offset(Vector2::new(1.0, 2.0))
+            .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        ui.draw();
+
+        ui.process_os_event(&OsEvent::CursorMoved {
+            position: ui.node(content).screen_position(),
+        });
+        let press = |ui: &mut UserInterface<(), StubNode>, state| {
+            ui.process_os_event(&OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state,
+            });
+            while ui.poll_message().is_some() {}
+        };
+
+        press(&mut ui, ButtonState::Pressed);
+        assert_eq!(
+            *ui.node(content).render_transform(),
+            Matrix3::new_translation(&Vector2::new(1.0, 2.0))
+        );
+        press(&mut ui, ButtonState::Released);
+        assert_eq!(*ui.node(content).render_transform(), Matrix3::identity());
+    }
+
+    #[test]
+    fn click_after_cursor_move() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let mut clicks = |allow_drag| {
+            let content = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+            let button = ButtonBuilder::new(WidgetBuilder::new().with_allow_drag(allow_drag))
+                .with_content(content)
+                .build(&mut ui.build_ctx());
+            ui.update(screen_size, 0.0);
+            ui.draw();
+
+            let position = ui.node(content).screen_position();
+            let mut clicks = 0;
+            for event in [
+                OsEvent::CursorMoved { position },
+                OsEvent::MouseInput {
+                    button: MouseButton::Left,
+                    state: ButtonState::Pressed,
+                },
+                OsEvent::CursorMoved {
+                    position: position + Vector2::new(50.0, 0.0),
+                },
+                OsEvent::MouseInput {
+                    button: MouseButton::Left,
+                    state: ButtonState::Released,
+                },
+            ]
+            .iter()
+            {
+                ui.process_os_event(event);
+                while let Some(message) = ui.poll_message() {
+                    if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
+                        clicks += (message.destination() == button) as i32;
+                    }
+                }
+            }
+            ui.remove_node(button);
+            clicks
+        };
+
+        // Jitter of the cursor is not a drag for buttons that can't be dragged.
+        assert_eq!(clicks(false), 1);
+        assert_eq!(clicks(true), 0);
+    }
+
+    #[test]
+    fn pan_cancels_press() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let content =
+            BorderBuilder::new(WidgetBuilder::new().with_height(100.0)).build(&mut ui.build_ctx());
+        let button = ButtonBuilder::new(WidgetBuilder::new())
+            .with_content(content)
+            .build(&mut ui.build_ctx());
+        // Scroll bars show their value as text, which can't be used in tests.
+        let v_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        let h_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        ScrollViewerBuilder::new(WidgetBuilder::new())
+            .with_content(button)
+            .with_vertical_scroll_bar(v_scroll_bar)
+            .with_horizontal_scroll_bar(h_scroll_bar)
+            .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        ui.draw();
+
+        let position = ui.node(content).screen_position() + Vector2::new(5.0, 5.0);
+        let mut clicks = 0;
+        for (phase, position) in [
+            (TouchPhase::Started, position),
+            (TouchPhase::Moved, position + Vector2::new(0.0, 50.0)),
+            (TouchPhase::Ended, position + Vector2::new(0.0, 50.0)),
+        ]
+        .iter()
+        {
+            ui.process_os_event(&OsEvent::Touch {
+                id: 0,
+                phase: *phase,
+                position: *position,
+            });
+            while let Some(message) = ui.poll_message() {
+                if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
+                    clicks += 1;
+                }
+            }
+            if *phase == TouchPhase::Moved {
+                // Panning has taken the press away from the button.
+                assert!(ui.captured_node().is_none());
+                if let UINode::Button(button) = ui.node(button) {
+                    assert!(!button.is_pressed);
+                }
+            }
+        }
+        assert_eq!(clicks, 0);
+    }
+
+    #[test]
+    fn state_textures_at_runtime() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let content = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        let button = ButtonBuilder::new(WidgetBuilder::new())
+            .with_content(content)
+            .build(&mut ui.build_ctx());
+
+        // Returns whether content of the button is hosted by a decorator.
+        let is_decorated = |ui: &UserInterface<(), StubNode>| {
+            let back = ui.node(content).parent();
+            assert_eq!(ui.node(back).parent(), button);
+            // Exactly one back is hosted by the button.
+            assert_eq!(ui.node(button).children(), &[back]);
+            matches!(ui.node(back), UINode::Decorator(_))
+        };
+        assert!(is_decorated(&ui));
+
+        let textures = StateTextures {
+            normal: Some(SharedTexture::from(Arc::new(0u8))),
+            ..Default::default()
+        };
+        let set_textures = |ui: &mut UserInterface<(), StubNode>, textures| {
+            ui.send_message(ButtonMessage::state_textures(
+                button,
+                MessageDirection::ToWidget,
+                textures,
+            ));
+            while ui.poll_message().is_some() {}
+        };
+
+        // Skinned button draws textures itself, back only hosts content.
+        set_textures(&mut ui, textures);
+        assert!(!is_decorated(&ui));
+
+        // Decorator is restored when textures are cleared.
+        set_textures(&mut ui, StateTextures::default());
+        assert!(is_decorated(&ui));
+    }
+
+    #[test]
+    fn state_texture_fallback() {
+        let normal = SharedTexture::from(Arc::new(0u8));
+        let pressed = SharedTexture::from(Arc::new(1u8));
+        let textures = StateTextures {
+            normal: Some(normal.clone()),
+            pressed: Some(pressed.clone()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            textures.resolve(VisualState::Pressed),
+            Some((&pressed, Color::WHITE))
+        );
+        let (texture, tint) = textures.resolve(VisualState::Hover).unwrap();
+        assert_eq!(texture, &normal);
+        assert_ne!(tint, Color::WHITE);
+        assert!(StateTextures::default()
+            .resolve(VisualState::Normal)
+            .is_none());
+    }
+}
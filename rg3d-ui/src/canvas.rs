@@ -1,15 +1,39 @@
 use crate::{
-    core::{algebra::Vector2, math::Rect, pool::Handle, scope_profile},
-    message::{MessageData, UiMessage},
+    brush::Brush,
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle, scope_profile},
+    draw::{CommandTexture, Draw, DrawingContext},
+    message::{MessageData, MessageDirection, UiMessage, UiMessageData, WidgetMessage},
     widget::{Widget, WidgetBuilder},
     BuildContext, Control, UINode, UserInterface,
 };
 use std::ops::{Deref, DerefMut};
 
+/// Grid lines are not drawn if their step is less than this value, they'd fill whole canvas.
+const MIN_SNAP_LINE_STEP: f32 = 4.0;
+
 /// Allows user to directly set position and size of a node
 #[derive(Clone)]
 pub struct Canvas<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
+    snap: Option<Vector2<f32>>,
+    snap_grid_visible: bool,
+    /// Canvas takes size of bounding box of its children, see `set_auto_size`.
+    auto_size: bool,
+    /// Child that is dragged by drag and drop system.
+    dragged: Handle<UINode<M, C>>,
+    /// Position of the cursor relative to dragged child.
+    drag_offset: Vector2<f32>,
+}
+
+fn snap_position(position: Vector2<f32>, step: Vector2<f32>) -> Vector2<f32> {
+    let snap = |value: f32, step: f32| {
+        if step > 0.0 {
+            (value / step).round() * step
+        } else {
+            value
+        }
+    };
+    Vector2::new(snap(position.x, step.x), snap(position.y, step.y))
 }
 
 crate::define_widget_deref!(Canvas<M, C>);
@@ -24,11 +48,17 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Canvas<M, C> {
 
         let size_for_child = Vector2::new(std::f32::INFINITY, std::f32::INFINITY);
 
+        let mut size = Vector2::<f32>::default();
         for child_handle in self.widget.children() {
-            ui.node(*child_handle).measure(ui, size_for_child);
+            let child = ui.node(*child_handle);
+            child.measure(ui, size_for_child);
+            if self.auto_size {
+                let max = self.child_position(child) + child.desired_size();
+                size = Vector2::new(size.x.max(max.x), size.y.max(max.y));
+            }
         }
 
-        Vector2::default()
+        size
     }
 
     fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
@@ -36,11 +66,12 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Canvas<M, C> {
 
         for child_handle in self.widget.children() {
             let child = ui.nodes.borrow(*child_handle);
+            let position = self.child_position(child);
             child.arrange(
                 ui,
                 &Rect::new(
-                    child.desired_local_position().x,
-                    child.desired_local_position().y,
+                    position.x,
+                    position.y,
                     child.desired_size().x,
                     child.desired_size().y,
                 ),
@@ -50,34 +81,179 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Canvas<M, C> {
         final_size
     }
 
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        if let Some(step) = self.snap {
+            if self.snap_grid_visible
+                && step.x >= MIN_SNAP_LINE_STEP
+                && step.y >= MIN_SNAP_LINE_STEP
+            {
+                let bounds = self.widget.screen_bounds();
+                let mut x = bounds.x() + step.x;
+                while x < bounds.x() + bounds.w() {
+                    drawing_context.push_line(
+                        Vector2::new(x, bounds.y()),
+                        Vector2::new(x, bounds.y() + bounds.h()),
+                        1.0,
+                        Color::WHITE,
+                    );
+                    x += step.x;
+                }
+                let mut y = bounds.y() + step.y;
+                while y < bounds.y() + bounds.h() {
+                    drawing_context.push_line(
+                        Vector2::new(bounds.x(), y),
+                        Vector2::new(bounds.x() + bounds.w(), y),
+                        1.0,
+                        Color::WHITE,
+                    );
+                    y += step.y;
+                }
+                drawing_context.commit(
+                    self.clip_bounds(),
+                    Brush::Solid(Color::from_rgba(255, 255, 255, 20)),
+                    CommandTexture::None,
+                    None,
+                );
+            }
+        }
+    }
+
     fn handle_routed_message(
         &mut self,
         ui: &mut UserInterface<M, C>,
         message: &mut UiMessage<M, C>,
     ) {
         self.widget.handle_routed_message(ui, message);
+
+        if self.snap.is_none() {
+            return;
+        }
+
+        if let UiMessageData::Widget(msg) = &message.data() {
+            match msg {
+                &WidgetMessage::DragStarted(node) if self.widget.children().contains(&node) => {
+                    self.dragged = node;
+                    self.drag_offset = ui.cursor_position() - ui.node(node).screen_position;
+                }
+                WidgetMessage::DragOver(_) if self.dragged.is_some() => {
+                    let position =
+                        ui.cursor_position() - self.widget.screen_position - self.drag_offset;
+                    ui.send_message(WidgetMessage::desired_position(
+                        self.dragged,
+                        MessageDirection::ToWidget,
+                        position,
+                    ));
+                }
+                WidgetMessage::MouseUp { .. } | WidgetMessage::Drop(_) => {
+                    self.dragged = Handle::NONE;
+                }
+                _ => (),
+            }
+        }
     }
 }
 
 impl<M: MessageData, C: Control<M, C>> Canvas<M, C> {
     pub fn new(widget: Widget<M, C>) -> Self {
-        Self { widget }
+        Self {
+            widget,
+            snap: None,
+            snap_grid_visible: true,
+            auto_size: false,
+            dragged: Handle::NONE,
+            drag_offset: Vector2::default(),
+        }
+    }
+
+    fn child_position(&self, child: &UINode<M, C>) -> Vector2<f32> {
+        match self.snap {
+            Some(step) => snap_position(child.desired_local_position(), step),
+            None => child.desired_local_position(),
+        }
+    }
+
+    /// When enabled, desired size of canvas is a bounding box of its children (max of
+    /// position + size of every child), so canvas in a scroll viewer defines scrollable area
+    /// that matches its content. Otherwise canvas wants no space by itself and just fills
+    /// the space it was given.
+    pub fn set_auto_size(&mut self, auto_size: bool) -> &mut Self {
+        if self.auto_size != auto_size {
+            self.auto_size = auto_size;
+            self.invalidate_layout();
+        }
+        self
+    }
+
+    pub fn is_auto_size(&self) -> bool {
+        self.auto_size
+    }
+
+    /// Sets step of a grid to which positions of children are snapped. Snapping affects only
+    /// arrangement - desired positions of children stay unchanged. When snapping is enabled,
+    /// children that are dragged by drag and drop system (`with_allow_drag`) follow the
+    /// cursor. `None` disables snapping, so children are placed exactly at their positions
+    /// and canvas does not move dragged children.
+    pub fn set_snap(&mut self, grid: Option<Vector2<f32>>) -> &mut Self {
+        if self.snap != grid {
+            self.snap = grid;
+            self.dragged = Handle::NONE;
+            self.invalidate_layout();
+        }
+        self
+    }
+
+    pub fn snap(&self) -> Option<Vector2<f32>> {
+        self.snap
+    }
+
+    /// Enables or disables faint grid lines that are drawn when snapping is enabled.
+    pub fn set_snap_grid_visible(&mut self, visible: bool) -> &mut Self {
+        self.snap_grid_visible = visible;
+        self
+    }
+
+    pub fn is_snap_grid_visible(&self) -> bool {
+        self.snap_grid_visible
     }
 }
 
 pub struct CanvasBuilder<M: MessageData, C: Control<M, C>> {
     widget_builder: WidgetBuilder<M, C>,
+    snap: Option<Vector2<f32>>,
+    snap_grid_visible: bool,
+    auto_size: bool,
 }
 
 impl<M: MessageData, C: Control<M, C>> CanvasBuilder<M, C> {
     pub fn new(widget_builder: WidgetBuilder<M, C>) -> Self {
-        Self { widget_builder }
+        Self {
+            widget_builder,
+            snap: None,
+            snap_grid_visible: true,
+            auto_size: false,
+        }
+    }
+
+    pub fn with_snap(mut self, grid: Option<Vector2<f32>>) -> Self {
+        self.snap = grid;
+        self
+    }
+
+    pub fn with_snap_grid_visible(mut self, visible: bool) -> Self {
+        self.snap_grid_visible = visible;
+        self
+    }
+
+    pub fn with_auto_size(mut self, auto_size: bool) -> Self {
+        self.auto_size = auto_size;
+        self
     }
 
     pub fn build(self, ui: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
-        let canvas = Canvas {
-            widget: self.widget_builder.build(),
-        };
+        let mut canvas = Canvas::new(self.widget_builder.build());
+        canvas.snap = self.snap;
+        canvas.snap_grid_visible = self.snap_grid_visible;
+        canvas.auto_size = self.auto_size;
         ui.add_node(UINode::Canvas(canvas))
     }
 }
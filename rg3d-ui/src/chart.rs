@@ -0,0 +1,419 @@
+//! Simple chart that renders one or more data series as bars or a line graph. Vertical axis
+//! is scaled automatically to fit range of the data.
+
+use crate::{
+    brush::Brush,
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle, scope_profile},
+    draw::{CommandTexture, Draw, DrawingContext},
+    formatted_text::{FormattedText, FormattedTextBuilder},
+    message::{ChartMessage, MessageData, MessageDirection, UiMessage, UiMessageData},
+    ttf::SharedFont,
+    widget::{Widget, WidgetBuilder},
+    BuildContext, Control, UINode, UserInterface, BRUSH_LIGHT, BRUSH_TEXT,
+};
+use std::{
+    cell::{Cell, RefCell},
+    ops::{Deref, DerefMut},
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChartMode {
+    Bar,
+    Line,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartSeries {
+    pub values: Vec<f32>,
+    pub color: Color,
+}
+
+/// Space between vertical axis and labels.
+const LABEL_MARGIN: f32 = 2.0;
+
+#[derive(Clone)]
+pub struct Chart<M: MessageData, C: Control<M, C>> {
+    widget: Widget<M, C>,
+    series: Vec<ChartSeries>,
+    mode: ChartMode,
+    /// Maximum amount of values in each series, oldest values will be dropped when new
+    /// values are pushed. Useful for live data.
+    window_size: Option<usize>,
+    preferred_size: Vector2<f32>,
+    show_labels: bool,
+    max_label: RefCell<FormattedText>,
+    min_label: RefCell<FormattedText>,
+    label_width: Cell<f32>,
+}
+
+crate::define_widget_deref!(Chart<M, C>);
+
+impl<M: MessageData, C: Control<M, C>> Chart<M, C> {
+    pub fn series(&self) -> &[ChartSeries] {
+        &self.series
+    }
+
+    /// Adds new series and returns its index.
+    pub fn add_series(&mut self, values: Vec<f32>, color: Color) -> usize {
+        self.series.push(ChartSeries { values, color });
+        self.apply_window();
+        self.widget.invalidate_layout();
+        self.series.len() - 1
+    }
+
+    /// Appends a value to the end of a series. If rolling window is set, oldest value of the
+    /// series will be removed when the window is full.
+    pub fn push_value(&mut self, series: usize, value: f32) {
+        if let Some(series) = self.series.get_mut(series) {
+            series.values.push(value);
+            self.apply_window();
+            self.widget.invalidate_layout();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.series.clear();
+        self.widget.invalidate_layout();
+    }
+
+    pub fn set_mode(&mut self, mode: ChartMode) -> &mut Self {
+        if self.mode != mode {
+            self.mode = mode;
+            self.widget.invalidate_layout();
+        }
+        self
+    }
+
+    pub fn mode(&self) -> ChartMode {
+        self.mode
+    }
+
+    pub fn set_window_size(&mut self, window_size: Option<usize>) -> &mut Self {
+        self.window_size = window_size;
+        self.apply_window();
+        self.widget.invalidate_layout();
+        self
+    }
+
+    pub fn window_size(&self) -> Option<usize> {
+        self.window_size
+    }
+
+    pub fn set_preferred_size(&mut self, size: Vector2<f32>) -> &mut Self {
+        if self.preferred_size != size {
+            self.preferred_size = size;
+            self.widget.invalidate_layout();
+        }
+        self
+    }
+
+    pub fn preferred_size(&self) -> Vector2<f32> {
+        self.preferred_size
+    }
+
+    fn apply_window(&mut self) {
+        if let Some(window_size) = self.window_size {
+            for series in self.series.iter_mut() {
+                if series.values.len() > window_size {
+                    let excess = series.values.len() - window_size;
+                    series.values.drain(0..excess);
+                }
+            }
+        }
+    }
+
+    /// Returns range of values of every series. Bars always grow from zero so zero is always
+    /// included in the range in bar mode.
+    pub fn value_range(&self) -> (f32, f32) {
+        let mut min = f32::MAX;
+        let mut max = -f32::MAX;
+        for &value in self.series.iter().flat_map(|s| s.values.iter()) {
+            min = min.min(value);
+            max = max.max(value);
+        }
+        if min > max {
+            return (0.0, 1.0);
+        }
+        if self.mode == ChartMode::Bar {
+            min = min.min(0.0);
+            max = max.max(0.0);
+        }
+        if (max - min).abs() <= f32::EPSILON {
+            max = min + 1.0;
+        }
+        (min, max)
+    }
+
+    fn max_len(&self) -> usize {
+        self.series
+            .iter()
+            .map(|s| s.values.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn plot_bounds(&self) -> Rect<f32> {
+        let bounds = self.widget.screen_bounds();
+        let offset = if self.show_labels {
+            self.label_width.get() + LABEL_MARGIN
+        } else {
+            0.0
+        };
+        Rect::new(
+            bounds.x() + offset,
+            bounds.y(),
+            (bounds.w() - offset).max(0.0),
+            bounds.h(),
+        )
+    }
+}
+
+impl<M: MessageData, C: Control<M, C>> Control<M, C> for Chart<M, C> {
+    fn measure_override(
+        &self,
+        ui: &UserInterface<M, C>,
+        available_size: Vector2<f32>,
+    ) -> Vector2<f32> {
+        scope_profile!();
+
+        self.widget.measure_override(ui, available_size);
+
+        if self.show_labels {
+            let (min, max) = self.value_range();
+            let constraint = Vector2::new(f32::INFINITY, f32::INFINITY);
+            let max_size = self
+                .max_label
+                .borrow_mut()
+                .set_text(format!("{:.2}", max))
+                .set_constraint(constraint)
+                .build();
+            let min_size = self
+                .min_label
+                .borrow_mut()
+                .set_text(format!("{:.2}", min))
+                .set_constraint(constraint)
+                .build();
+            self.label_width.set(max_size.x.max(min_size.x));
+        }
+
+        self.preferred_size
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let bounds = self.widget.screen_bounds();
+        let plot = self.plot_bounds();
+        let (min, max) = self.value_range();
+        let range = max - min;
+        let to_screen_y = |value: f32| plot.y() + plot.h() * (1.0 - (value - min) / range);
+
+        // Background.
+        drawing_context.push_rect_filled(&bounds, None);
+        drawing_context.commit(
+            self.clip_bounds(),
+            self.widget.background(),
+            CommandTexture::None,
+            None,
+        );
+
+        // Axes.
+        let origin_y = to_screen_y(min.max(0.0).min(max));
+        drawing_context.push_line(
+            plot.left_top_corner(),
+            plot.left_bottom_corner(),
+            1.0,
+            Color::WHITE,
+        );
+        drawing_context.push_line(
+            Vector2::new(plot.x(), origin_y),
+            Vector2::new(plot.x() + plot.w(), origin_y),
+            1.0,
+            Color::WHITE,
+        );
+        drawing_context.commit(
+            self.clip_bounds(),
+            self.widget.foreground(),
+            CommandTexture::None,
+            None,
+        );
+
+        // Data.
+        let len = self.max_len();
+        if len > 0 {
+            let slot_width = plot.w() / len as f32;
+            match self.mode {
+                ChartMode::Bar => {
+                    let bar_width = slot_width / self.series.len() as f32;
+                    for (series_index, series) in self.series.iter().enumerate() {
+                        for (i, &value) in series.values.iter().enumerate() {
+                            let x =
+                                plot.x() + i as f32 * slot_width + series_index as f32 * bar_width;
+                            let y = to_screen_y(value);
+                            let rect =
+                                Rect::new(x, y.min(origin_y), bar_width, (y - origin_y).abs());
+                            drawing_context.push_rect_multicolor(&rect, [series.color; 4]);
+                        }
+                    }
+                }
+                ChartMode::Line => {
+                    for series in self.series.iter() {
+                        let points = series
+                            .values
+                            .iter()
+                            .enumerate()
+                            .map(|(i, &value)| {
+                                Vector2::new(
+                                    plot.x() + (i as f32 + 0.5) * slot_width,
+                                    to_screen_y(value),
+                                )
+                            })
+                            .collect::<Vec<_>>();
+                        drawing_context.push_polyline(&points, 2.0, series.color, false);
+                    }
+                }
+            }
+            drawing_context.commit(
+                self.clip_bounds(),
+                Brush::Solid(Color::WHITE),
+                CommandTexture::None,
+                None,
+            );
+        }
+
+        if self.show_labels {
+            drawing_context.draw_text(
+                self.clip_bounds(),
+                bounds.position,
+                &self.max_label.borrow(),
+            );
+            let min_label = self.min_label.borrow();
+            let min_label_height = min_label
+                .get_lines()
+                .iter()
+                .map(|line| line.height)
+                .sum::<f32>();
+            drawing_context.draw_text(
+                self.clip_bounds(),
+                Vector2::new(bounds.x(), bounds.y() + bounds.h() - min_label_height),
+                &min_label,
+            );
+        }
+    }
+
+    fn handle_routed_message(
+        &mut self,
+        ui: &mut UserInterface<M, C>,
+        message: &mut UiMessage<M, C>,
+    ) {
+        self.widget.handle_routed_message(ui, message);
+
+        if message.destination() == self.handle()
+            && message.direction() == MessageDirection::ToWidget
+        {
+            if let UiMessageData::Chart(msg) = message.data() {
+                match msg {
+                    &ChartMessage::Mode(mode) => {
+                        self.set_mode(mode);
+                    }
+                    ChartMessage::AddSeries(series) => {
+                        self.add_series(series.values.clone(), series.color);
+                    }
+                    &ChartMessage::PushValue { series, value } => {
+                        self.push_value(series, value);
+                    }
+                    &ChartMessage::WindowSize(window_size) => {
+                        self.set_window_size(window_size);
+                    }
+                    ChartMessage::Clear => {
+                        self.clear();
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct ChartBuilder<M: MessageData, C: Control<M, C>> {
+    widget_builder: WidgetBuilder<M, C>,
+    series: Vec<ChartSeries>,
+    mode: ChartMode,
+    window_size: Option<usize>,
+    preferred_size: Vector2<f32>,
+    show_labels: bool,
+    font: Option<SharedFont>,
+}
+
+impl<M: MessageData, C: Control<M, C>> ChartBuilder<M, C> {
+    pub fn new(widget_builder: WidgetBuilder<M, C>) -> Self {
+        Self {
+            widget_builder,
+            series: Default::default(),
+            mode: ChartMode::Line,
+            window_size: None,
+            preferred_size: Vector2::new(200.0, 100.0),
+            show_labels: true,
+            font: None,
+        }
+    }
+
+    pub fn with_series(mut self, values: Vec<f32>, color: Color) -> Self {
+        self.series.push(ChartSeries { values, color });
+        self
+    }
+
+    pub fn with_mode(mut self, mode: ChartMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_window_size(mut self, window_size: usize) -> Self {
+        self.window_size = Some(window_size);
+        self
+    }
+
+    pub fn with_preferred_size(mut self, preferred_size: Vector2<f32>) -> Self {
+        self.preferred_size = preferred_size;
+        self
+    }
+
+    pub fn with_labels(mut self, show_labels: bool) -> Self {
+        self.show_labels = show_labels;
+        self
+    }
+
+    pub fn with_font(mut self, font: SharedFont) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    pub fn build(mut self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        if self.widget_builder.foreground.is_none() {
+            self.widget_builder.foreground = Some(BRUSH_LIGHT);
+        }
+        if self.widget_builder.background.is_none() {
+            self.widget_builder.background = Some(Brush::Solid(Color::TRANSPARENT));
+        }
+
+        let font = self.font.unwrap_or_else(|| ctx.ui.default_font());
+        let make_label = || {
+            FormattedTextBuilder::new()
+                .with_font(font.clone())
+                .with_brush(BRUSH_TEXT)
+                .build()
+        };
+
+        let mut chart = Chart {
+            widget: self.widget_builder.build(),
+            series: self.series,
+            mode: self.mode,
+            window_size: self.window_size,
+            preferred_size: self.preferred_size,
+            show_labels: self.show_labels,
+            max_label: RefCell::new(make_label()),
+            min_label: RefCell::new(make_label()),
+            label_width: Cell::new(0.0),
+        };
+        chart.apply_window();
+
+        ctx.add_node(UINode::Chart(chart))
+    }
+}
@@ -2,13 +2,16 @@ use crate::grid::{Column, GridBuilder, Row};
 use crate::message::{MessageData, MessageDirection};
 use crate::vector_image::{Primitive, VectorImageBuilder};
 use crate::{
+    accessibility::AccessibleRole,
     border::BorderBuilder,
     brush::Brush,
     core::{color::Color, pool::Handle},
+    decorator::DecoratorBuilder,
     message::{CheckBoxMessage, UiMessage, UiMessageData, WidgetMessage},
     widget::{Widget, WidgetBuilder},
     BuildContext, Control, HorizontalAlignment, NodeHandleMapping, Thickness, UINode,
-    UserInterface, VerticalAlignment, BRUSH_BRIGHT, BRUSH_DARK, BRUSH_LIGHT, BRUSH_TEXT,
+    UserInterface, VerticalAlignment, BRUSH_BRIGHT, BRUSH_DARK, BRUSH_LIGHT, BRUSH_PRIMARY,
+    BRUSH_TEXT,
 };
 use rg3d_core::algebra::Vector2;
 use std::ops::{Deref, DerefMut};
@@ -20,15 +23,32 @@ pub struct CheckBox<M: MessageData, C: Control<M, C>> {
     pub check_mark: Handle<UINode<M, C>>,
     pub uncheck_mark: Handle<UINode<M, C>>,
     pub undefined_mark: Handle<UINode<M, C>>,
+    pub decorator: Handle<UINode<M, C>>,
 }
 
 crate::define_widget_deref!(CheckBox<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for CheckBox<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::CheckBox
+    }
+
+    fn accessible_value(&self) -> Option<String> {
+        Some(
+            match self.checked {
+                Some(true) => "checked",
+                Some(false) => "unchecked",
+                None => "indeterminate",
+            }
+            .to_owned(),
+        )
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         node_map.resolve(&mut self.check_mark);
         node_map.resolve(&mut self.uncheck_mark);
         node_map.resolve(&mut self.undefined_mark);
+        node_map.resolve(&mut self.decorator);
     }
 
     fn handle_routed_message(
@@ -42,17 +62,48 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for CheckBox<M, C> {
             UiMessageData::Widget(ref msg) => {
                 match msg {
                     WidgetMessage::MouseDown { .. } => {
-                        if message.destination() == self.handle()
-                            || self.widget.has_descendant(message.destination(), ui)
+                        if !message.handled()
+                            && (message.destination() == self.handle()
+                                || self.widget.has_descendant(message.destination(), ui))
                         {
                             ui.capture_mouse(self.handle());
+                            message.set_handled(true);
                         }
                     }
-                    WidgetMessage::MouseUp { .. } => {
+                    &WidgetMessage::Enabled(enabled)
                         if message.destination() == self.handle()
-                            || self.widget.has_descendant(message.destination(), ui)
+                            && message.direction() == MessageDirection::ToWidget =>
+                    {
+                        ui.send_message(WidgetMessage::enabled(
+                            self.decorator,
+                            MessageDirection::ToWidget,
+                            enabled,
+                        ));
+                    }
+                    &WidgetMessage::MouseUp { pos, button } => {
+                        // Releases that were handled by children (which could capture mouse by
+                        // themselves) belong to them. Check boxes can be nested (for example in
+                        // a header of an expander), only the innermost one must be switched.
+                        if !message.handled()
+                            && (message.destination() == self.handle()
+                                || self.widget.has_descendant(message.destination(), ui))
                         {
                             ui.release_mouse_capture();
+                            message.set_handled(true);
+
+                            if message.destination() == self.handle() {
+                                // Mouse was captured by the check box, so pressed decorator does
+                                // not receive the release. The copy is marked as handled, so it
+                                // won't switch the check box again.
+                                let release = WidgetMessage::mouse_up(
+                                    self.decorator,
+                                    MessageDirection::FromWidget,
+                                    pos,
+                                    button,
+                                );
+                                release.set_handled(true);
+                                ui.send_message(release);
+                            }
 
                             if let Some(value) = self.checked {
                                 // Invert state if it is defined.
@@ -139,6 +190,9 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for CheckBox<M, C> {
         if self.undefined_mark == handle {
             self.undefined_mark = Handle::NONE;
         }
+        if self.decorator == handle {
+            self.decorator = Handle::NONE;
+        }
     }
 }
 
@@ -188,7 +242,11 @@ impl<M: MessageData, C: Control<M, C>> CheckBoxBuilder<M, C> {
         self
     }
 
-    pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+    pub fn build(mut self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        if self.widget_builder.tab_stop.is_none() {
+            self.widget_builder.tab_stop = Some(true);
+        }
+
         let check_mark = self.check_mark.unwrap_or_else(|| {
             VectorImageBuilder::new(
                 WidgetBuilder::new()
@@ -237,20 +295,24 @@ impl<M: MessageData, C: Control<M, C>> CheckBoxBuilder<M, C> {
             ctx[self.content].set_row(0).set_column(1);
         }
 
+        let decorator = DecoratorBuilder::new(
+            BorderBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(check_mark)
+                    .with_child(uncheck_mark)
+                    .with_child(undefined_mark)
+                    .with_foreground(BRUSH_LIGHT),
+            )
+            .with_stroke_thickness(Thickness::uniform(1.0)),
+        )
+        .with_normal_brush(BRUSH_DARK)
+        .with_hover_brush(BRUSH_PRIMARY)
+        .with_pressed_brush(BRUSH_LIGHT)
+        .build(ctx);
+
         let grid = GridBuilder::new(
             WidgetBuilder::new()
-                .with_child(
-                    BorderBuilder::new(
-                        WidgetBuilder::new()
-                            .with_child(check_mark)
-                            .with_child(uncheck_mark)
-                            .with_child(undefined_mark)
-                            .with_background(BRUSH_DARK)
-                            .with_foreground(BRUSH_LIGHT),
-                    )
-                    .with_stroke_thickness(Thickness::uniform(1.0))
-                    .build(ctx),
-                )
+                .with_child(decorator)
                 .with_child(self.content),
         )
         .add_row(Row::stretch())
@@ -264,6 +326,7 @@ impl<M: MessageData, C: Control<M, C>> CheckBoxBuilder<M, C> {
             check_mark,
             uncheck_mark,
             undefined_mark,
+            decorator,
         };
         ctx.add_node(UINode::CheckBox(cb))
     }
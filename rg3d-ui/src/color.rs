@@ -1220,3 +1220,34 @@ impl<M: MessageData, C: Control<M, C>> ColorFieldBuilder<M, C> {
         ctx.add_node(UINode::ColorField(field))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::core::color::{Color, Hsv};
+
+    #[test]
+    fn hsv_round_trip() {
+        for r in (0..=255).step_by(5) {
+            for g in (0..=255).step_by(5) {
+                for b in (0..=255).step_by(5) {
+                    let color = Color::from_rgba(r, g, b, 100);
+                    let hsv = color.to_hsv();
+                    let restored =
+                        Color::from_hsv(hsv.hue(), hsv.saturation(), hsv.brightness(), 100);
+                    assert_eq!(color, restored);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hsv_conversion_truncates() {
+        // Picker relies on `From<Hsv>`, it truncates components while `from_hsv` rounds them.
+        let hsv = Hsv::new(0.0, 100.0, 50.0);
+        assert_eq!(Color::from(hsv), Color::from_rgba(127, 0, 0, 255));
+        assert_eq!(
+            Color::from_hsv(hsv.hue(), hsv.saturation(), hsv.brightness(), 255),
+            Color::from_rgba(128, 0, 0, 255)
+        );
+    }
+}
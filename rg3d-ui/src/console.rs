@@ -0,0 +1,625 @@
+//! Append-only log view for in-game developer consoles. Lines are stored in a ring buffer
+//! with configurable capacity, only visible lines are laid out and drawn, so the view stays
+//! fast with thousands of lines. The view sticks to last line on append unless user scrolled
+//! up, text can be selected with a mouse and copied with Ctrl+C.
+
+use crate::{
+    brush::Brush,
+    canvas::CanvasBuilder,
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
+    draw::{CommandTexture, Draw, DrawingContext},
+    formatted_text::{FormattedText, FormattedTextBuilder},
+    message::{
+        ButtonState, ConsoleViewMessage, KeyCode, MessageData, MessageDirection, MouseButton,
+        ScrollBarMessage, UiMessage, UiMessageData, WidgetMessage,
+    },
+    scroll_viewer::ScrollViewerBuilder,
+    ttf::SharedFont,
+    widget::{Widget, WidgetBuilder},
+    BuildContext, Control, NodeHandleMapping, UINode, UserInterface, BRUSH_BRIGHT_BLUE, BRUSH_TEXT,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+};
+
+/// Default maximum amount of retained lines.
+pub const DEFAULT_MAX_LINES: usize = 1000;
+
+/// View follows last line if it is scrolled closer than this distance to the bottom.
+const TAIL_TOLERANCE: f32 = 0.5;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsoleLine {
+    pub text: String,
+    /// Color of the line, foreground of the view is used if not set.
+    pub color: Option<Color>,
+}
+
+/// Position of a character in the view, `column` is index of character in its line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ConsolePosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone)]
+pub struct ConsoleView<M: MessageData, C: Control<M, C>> {
+    widget: Widget<M, C>,
+    lines: VecDeque<ConsoleLine>,
+    max_lines: usize,
+    font: SharedFont,
+    /// Text of a single line, it is rebuilt for every visible line when view is drawn.
+    formatted_text: RefCell<FormattedText>,
+    selection_brush: Brush,
+    /// Draws selection with selection color of the UI instead of `selection_brush`, see
+    /// `UserInterface::set_selection_color`.
+    shared_selection_color: bool,
+    scroll_viewer: Handle<UINode<M, C>>,
+    v_scroll_bar: Handle<UINode<M, C>>,
+    /// Empty node with height of all lines, it gives scroll viewer a range to scroll.
+    content: Handle<UINode<M, C>>,
+    scroll_value: f32,
+    scroll_max: f32,
+    /// View is scrolled to the bottom and will stay there when new lines are appended.
+    follow_tail: bool,
+    /// Bounds of visible area in local coordinates of the view.
+    viewport: Cell<Rect<f32>>,
+    /// Anchor and active end of selection, anchor is where dragging has started.
+    selection: Option<(ConsolePosition, ConsolePosition)>,
+    selecting: bool,
+}
+
+crate::define_widget_deref!(ConsoleView<M, C>);
+
+impl<M: MessageData, C: Control<M, C>> ConsoleView<M, C> {
+    pub fn lines(&self) -> &VecDeque<ConsoleLine> {
+        &self.lines
+    }
+
+    /// Appends a line to the end of the view. If capacity of the view is reached, oldest
+    /// line is dropped. Returns amount of dropped lines.
+    fn append_line<P: AsRef<str>>(&mut self, text: P, color: Option<Color>) -> usize {
+        self.lines.push_back(ConsoleLine {
+            text: text.as_ref().to_owned(),
+            color,
+        });
+        self.widget.invalidate_layout();
+        self.apply_max_lines()
+    }
+
+    fn clear(&mut self) {
+        self.lines.clear();
+        self.selection = None;
+        self.selecting = false;
+        self.follow_tail = true;
+        self.widget.invalidate_layout();
+    }
+
+    /// Sets maximum amount of retained lines, oldest lines are dropped first. Returns amount
+    /// of dropped lines.
+    fn set_max_lines(&mut self, max_lines: usize) -> usize {
+        self.max_lines = max_lines.max(1);
+        self.widget.invalidate_layout();
+        self.apply_max_lines()
+    }
+
+    pub fn max_lines(&self) -> usize {
+        self.max_lines
+    }
+
+    /// Returns true if view sticks to last line.
+    pub fn is_following_tail(&self) -> bool {
+        self.follow_tail
+    }
+
+    /// Returns ordered bounds of selection, end is exclusive.
+    pub fn selection(&self) -> Option<(ConsolePosition, ConsolePosition)> {
+        self.selection
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .filter(|(begin, end)| begin != end)
+    }
+
+    pub fn select_all(&mut self) {
+        self.selection = self.lines.back().map(|last| {
+            (
+                ConsolePosition { line: 0, column: 0 },
+                ConsolePosition {
+                    line: self.lines.len() - 1,
+                    column: last.text.chars().count(),
+                },
+            )
+        });
+    }
+
+    /// Returns selected text, lines are separated by `\n`.
+    pub fn selected_text(&self) -> String {
+        let mut text = String::new();
+        if let Some((begin, end)) = self.selection() {
+            for (i, line) in self
+                .lines
+                .iter()
+                .enumerate()
+                .take(end.line + 1)
+                .skip(begin.line)
+            {
+                if i > begin.line {
+                    text.push('\n');
+                }
+                let skip = if i == begin.line { begin.column } else { 0 };
+                let take = if i == end.line {
+                    end.column.saturating_sub(skip)
+                } else {
+                    usize::MAX
+                };
+                text.extend(line.text.chars().skip(skip).take(take));
+            }
+        }
+        text
+    }
+
+    fn apply_max_lines(&mut self) -> usize {
+        let excess = self.lines.len().saturating_sub(self.max_lines);
+        if excess == 0 {
+            return 0;
+        }
+        self.lines.drain(..excess);
+        self.selection = match self.selection {
+            Some((a, b)) if a.line.max(b.line) >= excess => {
+                let shift = |p: ConsolePosition| {
+                    if p.line < excess {
+                        ConsolePosition { line: 0, column: 0 }
+                    } else {
+                        ConsolePosition {
+                            line: p.line - excess,
+                            column: p.column,
+                        }
+                    }
+                };
+                Some((shift(a), shift(b)))
+            }
+            _ => None,
+        };
+        excess
+    }
+
+    /// Gives scroll viewer a range of all lines. If lines were dropped from the front, view
+    /// is scrolled back by their height so visible lines stay in place.
+    fn sync_content(&self, ui: &UserInterface<M, C>, dropped: usize) {
+        let line_height = self.line_height();
+        let height = self.lines.len() as f32 * line_height;
+        if ui.node(self.content).height() != height {
+            ui.send_message(WidgetMessage::height(
+                self.content,
+                MessageDirection::ToWidget,
+                height,
+            ));
+        }
+
+        if dropped > 0 && !self.follow_tail {
+            ui.send_message(ScrollBarMessage::value(
+                self.v_scroll_bar,
+                MessageDirection::ToWidget,
+                self.scroll_value - dropped as f32 * line_height,
+            ));
+        }
+    }
+
+    fn line_height(&self) -> f32 {
+        self.font.0.lock().unwrap().height()
+    }
+
+    /// Returns span of columns in pixels from the start of line.
+    fn columns_width(&self, line: &str, columns: usize) -> f32 {
+        let font = self.font.0.lock().unwrap();
+        line.chars()
+            .take(columns)
+            .map(|c| font.glyph_advance(c as u32))
+            .sum()
+    }
+
+    fn position_at(&self, screen_pos: Vector2<f32>) -> Option<ConsolePosition> {
+        if self.lines.is_empty() {
+            return None;
+        }
+        let viewport = self.viewport.get();
+        let local = screen_pos - self.widget.screen_position() - viewport.position;
+        let line_height = self.line_height();
+        let line = if line_height > 0.0 {
+            (((local.y + self.scroll_value) / line_height).max(0.0) as usize)
+                .min(self.lines.len() - 1)
+        } else {
+            0
+        };
+
+        let font = self.font.0.lock().unwrap();
+        let mut column = 0;
+        let mut x = 0.0;
+        for c in self.lines[line].text.chars() {
+            let advance = font.glyph_advance(c as u32);
+            if local.x < x + advance * 0.5 {
+                break;
+            }
+            x += advance;
+            column += 1;
+        }
+        Some(ConsolePosition { line, column })
+    }
+
+    fn screen_viewport(&self) -> Rect<f32> {
+        let viewport = self.viewport.get();
+        Rect::new(
+            self.widget.screen_position().x + viewport.x(),
+            self.widget.screen_position().y + viewport.y(),
+            viewport.w(),
+            viewport.h(),
+        )
+    }
+}
+
+impl<M: MessageData, C: Control<M, C>> Control<M, C> for ConsoleView<M, C> {
+    fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
+        node_map.resolve(&mut self.scroll_viewer);
+        node_map.resolve(&mut self.v_scroll_bar);
+        node_map.resolve(&mut self.content);
+    }
+
+    fn measure_override(
+        &self,
+        ui: &UserInterface<M, C>,
+        available_size: Vector2<f32>,
+    ) -> Vector2<f32> {
+        // Lines are built on draw, where scale factor is unknown.
+        self.formatted_text
+            .borrow_mut()
+            .set_raster_scale(ui.scale_factor());
+
+        self.widget.measure_override(ui, available_size)
+    }
+
+    fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
+        let size = self.widget.arrange_override(ui, final_size);
+
+        if let UINode::ScrollViewer(scroll_viewer) = ui.node(self.scroll_viewer) {
+            let scroll_panel = scroll_viewer.content_presenter();
+            // Accumulate offsets of content presenter up to the view.
+            let mut position = Vector2::default();
+            let mut handle = scroll_panel;
+            while handle.is_some() && handle != self.handle() {
+                let node = ui.node(handle);
+                position += node.actual_local_position();
+                handle = node.parent();
+            }
+            let size = ui.node(scroll_panel).actual_size();
+            self.viewport
+                .set(Rect::new(position.x, position.y, size.x, size.y));
+        }
+
+        size
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let bounds = self.widget.screen_bounds();
+        drawing_context.push_rect_filled(&bounds, None);
+        drawing_context.commit(
+            self.clip_bounds(),
+            self.widget.background(),
+            CommandTexture::None,
+            None,
+        );
+
+        let line_height = self.line_height();
+        if self.lines.is_empty() || line_height <= 0.0 {
+            return;
+        }
+
+        let viewport = self.screen_viewport();
+        let clip_bounds = viewport.clip_by(self.clip_bounds());
+        let first = ((self.scroll_value / line_height).max(0.0) as usize).min(self.lines.len());
+        let last = (((self.scroll_value + viewport.h()) / line_height)
+            .ceil()
+            .max(0.0) as usize)
+            .min(self.lines.len());
+        let line_y = |i: usize| viewport.y() + i as f32 * line_height - self.scroll_value;
+
+        if let Some((begin, end)) = self.selection() {
+            for i in first.max(begin.line)..last.min(end.line + 1) {
+                let text = &self.lines[i].text;
+                let x0 = if i == begin.line {
+                    self.columns_width(text, begin.column)
+                } else {
+                    0.0
+                };
+                let x1 = if i == end.line {
+                    self.columns_width(text, end.column)
+                } else {
+                    // Selection of a line break is shown as a space.
+                    self.columns_width(text, usize::MAX) + self.columns_width(" ", 1)
+                };
+                drawing_context.push_rect_filled(
+                    &Rect::new(viewport.x() + x0, line_y(i), x1 - x0, line_height),
+                    None,
+                );
+            }
+            drawing_context.commit(
+                clip_bounds,
+                selection_brush(
+                    self.shared_selection_color,
+                    &self.selection_brush,
+                    drawing_context,
+                ),
+                CommandTexture::None,
+                None,
+            );
+        }
+
+        let mut formatted_text = self.formatted_text.borrow_mut();
+        for i in first..last {
+            let line = &self.lines[i];
+            formatted_text
+                .set_text(&line.text)
+                .set_constraint(Vector2::new(f32::MAX, line_height))
+                .set_brush(
+                    line.color
+                        .map(Brush::Solid)
+                        .unwrap_or_else(|| self.widget.foreground()),
+                )
+                .build();
+            drawing_context.draw_text(
+                clip_bounds,
+                Vector2::new(viewport.x(), line_y(i)),
+                &formatted_text,
+            );
+        }
+    }
+
+    fn handle_routed_message(
+        &mut self,
+        ui: &mut UserInterface<M, C>,
+        message: &mut UiMessage<M, C>,
+    ) {
+        self.widget.handle_routed_message(ui, message);
+
+        match message.data() {
+            UiMessageData::ConsoleView(msg)
+                if message.destination() == self.handle()
+                    && message.direction() == MessageDirection::ToWidget =>
+            {
+                let dropped = match msg {
+                    ConsoleViewMessage::AppendLine { text, color } => {
+                        Some(self.append_line(text, *color))
+                    }
+                    ConsoleViewMessage::Clear => {
+                        self.clear();
+                        Some(0)
+                    }
+                    &ConsoleViewMessage::MaxLines(max_lines) => Some(self.set_max_lines(max_lines)),
+                    ConsoleViewMessage::Copy(_) => None,
+                };
+                if let Some(dropped) = dropped {
+                    self.sync_content(ui, dropped);
+                }
+            }
+            UiMessageData::ScrollBar(msg)
+                if message.destination() == self.v_scroll_bar
+                    && message.direction() == MessageDirection::FromWidget =>
+            {
+                match *msg {
+                    ScrollBarMessage::Value(value) => {
+                        self.scroll_value = value;
+                        self.follow_tail = value >= self.scroll_max - TAIL_TOLERANCE;
+                    }
+                    ScrollBarMessage::MaxValue(max) => {
+                        self.scroll_max = max;
+                        if self.follow_tail {
+                            ui.send_message(ScrollBarMessage::value(
+                                self.v_scroll_bar,
+                                MessageDirection::ToWidget,
+                                max,
+                            ));
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            UiMessageData::Widget(msg) => match *msg {
+                WidgetMessage::MouseDown { pos, button }
+                    if button == MouseButton::Left && self.screen_viewport().contains(pos) =>
+                {
+                    if let Some(position) = self.position_at(pos) {
+                        let anchor = match self.selection {
+                            Some((anchor, _)) if ui.keyboard_modifiers().shift => anchor,
+                            _ => position,
+                        };
+                        self.selection = Some((anchor, position));
+                        self.selecting = true;
+                        ui.capture_mouse(self.handle());
+                        message.set_handled(true);
+                    }
+                }
+                WidgetMessage::MouseMove { pos, state } if self.selecting => {
+                    if state.left == ButtonState::Pressed {
+                        if let (Some((anchor, _)), Some(position)) =
+                            (self.selection, self.position_at(pos))
+                        {
+                            self.selection = Some((anchor, position));
+                        }
+                    } else {
+                        self.selecting = false;
+                    }
+                }
+                WidgetMessage::MouseUp { button, .. }
+                    if button == MouseButton::Left && self.selecting =>
+                {
+                    self.selecting = false;
+                    ui.release_mouse_capture();
+                }
+                WidgetMessage::KeyDown(code) if ui.keyboard_modifiers().control => match code {
+                    KeyCode::C if self.selection().is_some() => {
+                        ui.send_message(ConsoleViewMessage::copy(
+                            self.handle(),
+                            MessageDirection::FromWidget,
+                            self.selected_text(),
+                        ));
+                        message.set_handled(true);
+                    }
+                    KeyCode::A => {
+                        self.select_all();
+                        message.set_handled(true);
+                    }
+                    _ => (),
+                },
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    fn remove_ref(&mut self, handle: Handle<UINode<M, C>>) {
+        if self.scroll_viewer == handle {
+            self.scroll_viewer = Handle::NONE;
+        }
+        if self.v_scroll_bar == handle {
+            self.v_scroll_bar = Handle::NONE;
+        }
+        if self.content == handle {
+            self.content = Handle::NONE;
+        }
+    }
+}
+
+fn selection_brush(shared: bool, brush: &Brush, drawing_context: &DrawingContext) -> Brush {
+    if shared {
+        Brush::Solid(drawing_context.selection_color(true))
+    } else {
+        brush.clone()
+    }
+}
+
+pub struct ConsoleViewBuilder<M: MessageData, C: Control<M, C>> {
+    widget_builder: WidgetBuilder<M, C>,
+    lines: Vec<ConsoleLine>,
+    max_lines: usize,
+    font: Option<SharedFont>,
+    selection_brush: Brush,
+    shared_selection_color: bool,
+}
+
+impl<M: MessageData, C: Control<M, C>> ConsoleViewBuilder<M, C> {
+    pub fn new(widget_builder: WidgetBuilder<M, C>) -> Self {
+        Self {
+            widget_builder,
+            lines: Default::default(),
+            max_lines: DEFAULT_MAX_LINES,
+            font: None,
+            selection_brush: BRUSH_BRIGHT_BLUE,
+            shared_selection_color: true,
+        }
+    }
+
+    pub fn with_lines(mut self, lines: Vec<ConsoleLine>) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines;
+        self
+    }
+
+    pub fn with_font(mut self, font: SharedFont) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Sets brush of selection, it is used only if shared selection color is disabled by
+    /// `with_shared_selection_color(false)`.
+    pub fn with_selection_brush(mut self, brush: Brush) -> Self {
+        self.selection_brush = brush;
+        self
+    }
+
+    /// Selection is drawn with selection color of the UI by default, so it matches
+    /// selection of text boxes and list items.
+    pub fn with_shared_selection_color(mut self, shared: bool) -> Self {
+        self.shared_selection_color = shared;
+        self
+    }
+
+    pub fn build(mut self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        if self.widget_builder.foreground.is_none() {
+            self.widget_builder.foreground = Some(BRUSH_TEXT);
+        }
+        if self.widget_builder.background.is_none() {
+            self.widget_builder.background = Some(Brush::Solid(Color::TRANSPARENT));
+        }
+
+        let font = self.font.unwrap_or_else(|| ctx.ui.default_font());
+
+        let content = CanvasBuilder::new(WidgetBuilder::new()).build(ctx);
+        let scroll_viewer = ScrollViewerBuilder::new(WidgetBuilder::new())
+            .with_content(content)
+            .with_stretch_content_height(false)
+            .build(ctx);
+        let v_scroll_bar = ctx[scroll_viewer].as_scroll_viewer().v_scroll_bar;
+
+        let mut console = ConsoleView {
+            widget: self.widget_builder.with_child(scroll_viewer).build(),
+            lines: Default::default(),
+            max_lines: self.max_lines.max(1),
+            formatted_text: RefCell::new(
+                FormattedTextBuilder::new().with_font(font.clone()).build(),
+            ),
+            font,
+            selection_brush: self.selection_brush,
+            shared_selection_color: self.shared_selection_color,
+            scroll_viewer,
+            v_scroll_bar,
+            content,
+            scroll_value: 0.0,
+            scroll_max: 0.0,
+            follow_tail: true,
+            viewport: Default::default(),
+            selection: None,
+            selecting: false,
+        };
+        for line in self.lines {
+            console.append_line(line.text, line.color);
+        }
+        let height = console.lines.len() as f32 * console.line_height();
+        ctx[content].set_height(height);
+
+        ctx.add_node(UINode::ConsoleView(console))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        brush::Brush,
+        console::{selection_brush, ConsoleViewBuilder},
+        core::color::Color,
+        draw::DrawingContext,
+        node::StubNode,
+        widget::WidgetBuilder,
+    };
+
+    #[test]
+    fn shared_selection_color() {
+        let builder = ConsoleViewBuilder::<(), StubNode>::new(WidgetBuilder::new());
+        assert!(builder.shared_selection_color);
+
+        let mut drawing_context = DrawingContext::new();
+        drawing_context.set_selection_colors(Color::RED, Color::GREEN);
+        let own = Brush::Solid(Color::BLUE);
+        assert!(matches!(
+            selection_brush(true, &own, &drawing_context),
+            Brush::Solid(color) if color == Color::RED
+        ));
+        assert!(matches!(
+            selection_brush(false, &own, &drawing_context),
+            Brush::Solid(color) if color == Color::BLUE
+        ));
+    }
+}
@@ -0,0 +1,998 @@
+//! Table of rows with typed columns. Every column has a header, a width mode and optionally
+//! a template that builds content of its cells. Rows can be selected, sorted by a click on a
+//! header of a sortable column and edited in editable columns.
+//!
+//! # Virtualization
+//!
+//! All rows have the same height, so only rows that are visible in the viewport (plus a few
+//! rows around it) have nodes, invisible rows are replaced with empty space. This allows to
+//! show large datasets, but it also means that nodes of cells are rebuilt when grid is
+//! scrolled, so templates must not keep handles of cells.
+
+use crate::{
+    border::BorderBuilder,
+    brush::Brush,
+    button::ButtonBuilder,
+    check_box::CheckBoxBuilder,
+    core::{color::Color, pool::Handle},
+    grid::{Column, GridBuilder, Row},
+    message::{
+        ButtonMessage, CheckBoxMessage, DataGridMessage, MessageData, MessageDirection,
+        MouseButton, ScrollBarMessage, TextBoxMessage, TextMessage, UiMessage, UiMessageData,
+        WidgetMessage,
+    },
+    scroll_viewer::ScrollViewerBuilder,
+    stack_panel::StackPanelBuilder,
+    text::TextBuilder,
+    text_box::{TextBoxBuilder, TextCommitMode},
+    widget::{Widget, WidgetBuilder},
+    BuildContext, Control, HorizontalAlignment, NodeHandleMapping, Thickness, UINode,
+    UserInterface, VerticalAlignment, BRUSH_BRIGHT_BLUE,
+};
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Formatter},
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+/// Default height of a row.
+pub const DEFAULT_ROW_HEIGHT: f32 = 22.0;
+
+/// Amount of invisible rows that have nodes above and below viewport, so short scrolls do
+/// not show empty space.
+const OVERSCAN_ROWS: usize = 2;
+
+/// Value of a cell, type of values defines how a column is sorted and edited.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DataGridValue {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+}
+
+impl DataGridValue {
+    fn kind(&self) -> u8 {
+        match self {
+            DataGridValue::Text(_) => 0,
+            DataGridValue::Number(_) => 1,
+            DataGridValue::Bool(_) => 2,
+        }
+    }
+
+    /// Compares values for sorting, values of different types are ordered by their type.
+    pub fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (DataGridValue::Text(a), DataGridValue::Text(b)) => a.cmp(b),
+            (DataGridValue::Number(a), DataGridValue::Number(b)) => {
+                a.partial_cmp(b).unwrap_or(Ordering::Equal)
+            }
+            (DataGridValue::Bool(a), DataGridValue::Bool(b)) => a.cmp(b),
+            _ => self.kind().cmp(&other.kind()),
+        }
+    }
+}
+
+impl Display for DataGridValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataGridValue::Text(text) => write!(f, "{}", text),
+            DataGridValue::Number(number) => write!(f, "{}", number),
+            DataGridValue::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// Defines how width of a column is calculated. Widths do not depend on content, so every
+/// row and the header have exactly the same columns.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ColumnWidth {
+    /// Column has fixed width.
+    Strict(f32),
+    /// Column takes a share of width that is left from strict columns.
+    Stretch,
+}
+
+/// Builds content of a cell for a given value.
+pub type CellTemplate<M, C> =
+    dyn Fn(&mut BuildContext<M, C>, &DataGridValue) -> Handle<UINode<M, C>>;
+
+#[derive(Clone)]
+pub struct DataGridColumn<M: MessageData, C: Control<M, C>> {
+    pub header: String,
+    pub width: ColumnWidth,
+    /// Cells of editable column are text boxes (or check boxes for boolean values).
+    pub editable: bool,
+    pub sortable: bool,
+    /// Builds content of read-only cells, text is used if not set.
+    pub template: Option<Rc<CellTemplate<M, C>>>,
+}
+
+impl<M: MessageData, C: Control<M, C>> DataGridColumn<M, C> {
+    pub fn new<P: AsRef<str>>(header: P, width: ColumnWidth) -> Self {
+        Self {
+            header: header.as_ref().to_owned(),
+            width,
+            editable: false,
+            sortable: true,
+            template: None,
+        }
+    }
+
+    pub fn with_editable(mut self, editable: bool) -> Self {
+        self.editable = editable;
+        self
+    }
+
+    pub fn with_sortable(mut self, sortable: bool) -> Self {
+        self.sortable = sortable;
+        self
+    }
+
+    pub fn with_template(mut self, template: Rc<CellTemplate<M, C>>) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    fn grid_column(&self) -> Column {
+        match self.width {
+            ColumnWidth::Strict(width) => Column::strict(width),
+            ColumnWidth::Stretch => Column::stretch(),
+        }
+    }
+}
+
+/// Node of a visible row.
+#[derive(Clone)]
+struct RowView<M: MessageData, C: Control<M, C>> {
+    index: usize,
+    node: Handle<UINode<M, C>>,
+    /// Editors of editable cells with index of their column.
+    editors: Vec<(usize, Handle<UINode<M, C>>)>,
+}
+
+#[derive(Clone)]
+pub struct DataGrid<M: MessageData, C: Control<M, C>> {
+    widget: Widget<M, C>,
+    columns: Vec<DataGridColumn<M, C>>,
+    rows: Vec<Vec<DataGridValue>>,
+    row_height: f32,
+    selected_index: Option<usize>,
+    selected_brush: Brush,
+    sort: Option<(usize, SortOrder)>,
+    scroll_viewer: Handle<UINode<M, C>>,
+    v_scroll_bar: Handle<UINode<M, C>>,
+    /// Vertical stack of visible rows between two spacers.
+    body: Handle<UINode<M, C>>,
+    header_buttons: Vec<Handle<UINode<M, C>>>,
+    header_texts: Vec<Handle<UINode<M, C>>>,
+    visible_rows: Vec<RowView<M, C>>,
+    scroll_value: f32,
+    viewport_height: f32,
+    /// Node that is shown only while there are no rows (like "No results").
+    placeholder: Handle<UINode<M, C>>,
+}
+
+crate::define_widget_deref!(DataGrid<M, C>);
+
+impl<M: MessageData, C: Control<M, C>> DataGrid<M, C> {
+    pub fn columns(&self) -> &[DataGridColumn<M, C>] {
+        &self.columns
+    }
+
+    /// Returns rows in current order, indices of rows in messages are indices in this order.
+    pub fn rows(&self) -> &[Vec<DataGridValue>] {
+        &self.rows
+    }
+
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected_index
+    }
+
+    pub fn sort(&self) -> Option<(usize, SortOrder)> {
+        self.sort
+    }
+
+    pub fn row_height(&self) -> f32 {
+        self.row_height
+    }
+
+    pub fn placeholder(&self) -> Handle<UINode<M, C>> {
+        self.placeholder
+    }
+
+    /// Shows placeholder if there are no rows and hides it otherwise.
+    fn sync_placeholder(&self, ui: &UserInterface<M, C>) {
+        if self.placeholder.is_some() {
+            ui.send_message(WidgetMessage::visibility(
+                self.placeholder,
+                MessageDirection::ToWidget,
+                self.rows.is_empty(),
+            ));
+        }
+    }
+
+    fn visible_range(&self) -> (usize, usize) {
+        let first = ((self.scroll_value / self.row_height).max(0.0) as usize)
+            .saturating_sub(OVERSCAN_ROWS)
+            .min(self.rows.len());
+        let last = (((self.scroll_value + self.viewport_height) / self.row_height).ceil() as usize
+            + OVERSCAN_ROWS)
+            .min(self.rows.len());
+        (first, last)
+    }
+
+    fn row_brush(&self, index: usize) -> Brush {
+        if self.selected_index == Some(index) {
+            self.selected_brush.clone()
+        } else {
+            Brush::Solid(Color::TRANSPARENT)
+        }
+    }
+
+    /// Builds nodes of a row with given index.
+    fn build_row(&self, ctx: &mut BuildContext<M, C>, index: usize) -> RowView<M, C> {
+        let mut editors = Vec::new();
+        let mut cells = Vec::new();
+        for (column_index, (column, value)) in
+            self.columns.iter().zip(self.rows[index].iter()).enumerate()
+        {
+            let cell = if column.editable {
+                let editor = match value {
+                    DataGridValue::Bool(value) => CheckBoxBuilder::new(
+                        WidgetBuilder::new().with_vertical_alignment(VerticalAlignment::Center),
+                    )
+                    .checked(Some(*value))
+                    .build(ctx),
+                    _ => TextBoxBuilder::new(WidgetBuilder::new())
+                        .with_text(value.to_string())
+                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                        .with_text_commit_mode(TextCommitMode::LostFocusPlusEnter)
+                        .build(ctx),
+                };
+                editors.push((column_index, editor));
+                editor
+            } else if let Some(template) = column.template.as_ref() {
+                template(ctx, value)
+            } else {
+                TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(2.0)))
+                    .with_text(value.to_string())
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx)
+            };
+            ctx[cell].set_row(0).set_column(column_index);
+            cells.push(cell);
+        }
+
+        let node = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_height(self.row_height)
+                .with_background(self.row_brush(index))
+                .with_child(
+                    GridBuilder::new(WidgetBuilder::new().with_children(&cells))
+                        .add_row(Row::stretch())
+                        .add_columns(self.columns.iter().map(|c| c.grid_column()).collect())
+                        .build(ctx),
+                ),
+        )
+        .with_stroke_thickness(Thickness::zero())
+        .build(ctx);
+
+        RowView {
+            index,
+            node,
+            editors,
+        }
+    }
+
+    /// Builds nodes of visible rows between two spacers, `kept` row is reused instead of
+    /// building a new one. Returns children of the body in their order.
+    fn build_rows(
+        &mut self,
+        ctx: &mut BuildContext<M, C>,
+        mut kept: Option<RowView<M, C>>,
+    ) -> Vec<Handle<UINode<M, C>>> {
+        let (first, last) = self.visible_range();
+        self.visible_rows.clear();
+        let mut children = vec![BorderBuilder::new(
+            WidgetBuilder::new().with_height(first as f32 * self.row_height),
+        )
+        .build(ctx)];
+        for index in first..last {
+            let row = match kept.take() {
+                Some(row) if row.index == index => row,
+                other => {
+                    kept = other;
+                    self.build_row(ctx, index)
+                }
+            };
+            children.push(row.node);
+            self.visible_rows.push(row);
+        }
+        children.push(
+            BorderBuilder::new(
+                WidgetBuilder::new().with_height((self.rows.len() - last) as f32 * self.row_height),
+            )
+            .build(ctx),
+        );
+        children
+    }
+
+    /// Rebuilds nodes of visible rows. A row that contains keyboard focus is kept while it
+    /// is visible, so refresh does not interrupt editing of its cells.
+    fn refresh(&mut self, ui: &mut UserInterface<M, C>) {
+        let (first, last) = self.visible_range();
+        let focus = ui.keyboard_focus_node();
+        let kept = self
+            .visible_rows
+            .iter()
+            .position(|row| {
+                (first..last).contains(&row.index)
+                    && focus.is_some()
+                    && (row.node == focus || ui.is_node_child_of(focus, row.node))
+            })
+            .map(|i| self.visible_rows.remove(i));
+        let kept_node = kept.as_ref().map(|row| row.node);
+        for child in ui.node(self.body).children().to_vec() {
+            if kept_node != Some(child) {
+                ui.send_message(WidgetMessage::remove(child, MessageDirection::ToWidget));
+            }
+        }
+        if let Some(row) = kept.as_ref() {
+            ui.send_message(WidgetMessage::background(
+                row.node,
+                MessageDirection::ToWidget,
+                self.row_brush(row.index),
+            ));
+        }
+
+        let children = self.build_rows(&mut ui.build_ctx(), kept);
+        for child in children {
+            ui.send_message(WidgetMessage::link(
+                child,
+                MessageDirection::ToWidget,
+                self.body,
+            ));
+        }
+    }
+
+    fn sync_headers(&self, ui: &UserInterface<M, C>) {
+        for (i, (&text, column)) in self
+            .header_texts
+            .iter()
+            .zip(self.columns.iter())
+            .enumerate()
+        {
+            let arrow = match self.sort {
+                Some((column, SortOrder::Ascending)) if column == i => " ^",
+                Some((column, SortOrder::Descending)) if column == i => " v",
+                _ => "",
+            };
+            ui.send_message(TextMessage::text(
+                text,
+                MessageDirection::ToWidget,
+                format!("{}{}", column.header, arrow),
+            ));
+        }
+    }
+
+    fn apply_sort(&mut self) {
+        if let Some((column, order)) = self.sort {
+            let selected = self.selected_index;
+            let mut indices = (0..self.rows.len()).collect::<Vec<_>>();
+            indices.sort_by(|&a, &b| {
+                let ordering = match (self.rows[a].get(column), self.rows[b].get(column)) {
+                    (Some(a), Some(b)) => a.compare(b),
+                    _ => Ordering::Equal,
+                };
+                match order {
+                    SortOrder::Ascending => ordering,
+                    SortOrder::Descending => ordering.reverse(),
+                }
+            });
+            let mut rows = std::mem::take(&mut self.rows);
+            self.rows = indices
+                .iter()
+                .map(|&i| std::mem::take(&mut rows[i]))
+                .collect();
+            // Selected row and nodes of visible rows move with their data.
+            let mut positions = vec![0; indices.len()];
+            for (position, &index) in indices.iter().enumerate() {
+                positions[index] = position;
+            }
+            self.selected_index = selected.map(|selected| positions[selected]);
+            for row in self.visible_rows.iter_mut() {
+                row.index = positions[row.index];
+            }
+        }
+    }
+
+    /// Writes edited value of a cell and notifies user about the change.
+    fn commit_cell(
+        &mut self,
+        ui: &mut UserInterface<M, C>,
+        row: usize,
+        column: usize,
+        value: DataGridValue,
+    ) {
+        if let Some(cell) = self.rows.get_mut(row).and_then(|r| r.get_mut(column)) {
+            if *cell != value {
+                *cell = value.clone();
+                ui.send_message(DataGridMessage::cell_changed(
+                    self.handle(),
+                    MessageDirection::FromWidget,
+                    row,
+                    column,
+                    value,
+                ));
+            }
+        }
+    }
+
+    fn find_editor(&self, handle: Handle<UINode<M, C>>) -> Option<(usize, usize)> {
+        self.visible_rows.iter().find_map(|row| {
+            row.editors
+                .iter()
+                .find(|(_, editor)| *editor == handle)
+                .map(|&(column, _)| (row.index, column))
+        })
+    }
+}
+
+impl<M: MessageData, C: Control<M, C>> Control<M, C> for DataGrid<M, C> {
+    fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
+        node_map.resolve(&mut self.scroll_viewer);
+        node_map.resolve(&mut self.v_scroll_bar);
+        node_map.resolve(&mut self.body);
+        node_map.resolve(&mut self.placeholder);
+        for button in self.header_buttons.iter_mut() {
+            node_map.resolve(button);
+        }
+        for text in self.header_texts.iter_mut() {
+            node_map.resolve(text);
+        }
+        // Rows will be rebuilt on next scroll, nodes of a copy must not be shared.
+        self.visible_rows.clear();
+    }
+
+    fn handle_routed_message(
+        &mut self,
+        ui: &mut UserInterface<M, C>,
+        message: &mut UiMessage<M, C>,
+    ) {
+        self.widget.handle_routed_message(ui, message);
+
+        match message.data() {
+            UiMessageData::DataGrid(msg)
+                if message.destination() == self.handle()
+                    && message.direction() == MessageDirection::ToWidget =>
+            {
+                match msg {
+                    DataGridMessage::Rows(rows) => {
+                        self.rows = rows.clone();
+                        self.selected_index = None;
+                        // Every row is new, none of nodes can be kept.
+                        self.visible_rows.clear();
+                        self.apply_sort();
+                        self.refresh(ui);
+                        self.sync_placeholder(ui);
+                    }
+                    &DataGridMessage::SelectionChanged(selection) => {
+                        let selection = selection.filter(|&i| i < self.rows.len());
+                        if self.selected_index != selection {
+                            self.selected_index = selection;
+                            for row in self.visible_rows.iter() {
+                                ui.send_message(WidgetMessage::background(
+                                    row.node,
+                                    MessageDirection::ToWidget,
+                                    self.row_brush(row.index),
+                                ));
+                            }
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    &DataGridMessage::SortChanged { column, order } => {
+                        if column < self.columns.len() && self.sort != Some((column, order)) {
+                            self.sort = Some((column, order));
+                            self.apply_sort();
+                            self.sync_headers(ui);
+                            self.refresh(ui);
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    DataGridMessage::CellChanged { row, column, value } => {
+                        let (row, column) = (*row, *column);
+                        self.commit_cell(ui, row, column, value.clone());
+                        let editor = self
+                            .visible_rows
+                            .iter()
+                            .find(|r| r.index == row)
+                            .and_then(|r| r.editors.iter().find(|(c, _)| *c == column))
+                            .map(|&(_, editor)| editor);
+                        match (editor, value) {
+                            // Editors show new value by themselves, so edited cell is not
+                            // interrupted.
+                            (Some(editor), &DataGridValue::Bool(value)) => {
+                                ui.send_message(CheckBoxMessage::checked(
+                                    editor,
+                                    MessageDirection::ToWidget,
+                                    Some(value),
+                                ));
+                            }
+                            (Some(editor), value) => {
+                                ui.send_message(TextBoxMessage::text(
+                                    editor,
+                                    MessageDirection::ToWidget,
+                                    value.to_string(),
+                                ));
+                            }
+                            (None, _) => {
+                                self.visible_rows.retain(|r| r.index != row);
+                                self.refresh(ui);
+                            }
+                        }
+                    }
+                }
+            }
+            UiMessageData::ScrollBar(msg)
+                if message.destination() == self.v_scroll_bar
+                    && message.direction() == MessageDirection::FromWidget =>
+            {
+                let old_range = self.visible_range();
+                match *msg {
+                    ScrollBarMessage::Value(value) => self.scroll_value = value,
+                    ScrollBarMessage::PageSize(page_size) => self.viewport_height = page_size,
+                    _ => return,
+                }
+                if self.visible_range() != old_range {
+                    self.refresh(ui);
+                }
+            }
+            UiMessageData::Button(ButtonMessage::Click)
+                if message.direction() == MessageDirection::FromWidget =>
+            {
+                if let Some(column) = self
+                    .header_buttons
+                    .iter()
+                    .position(|&b| b == message.destination())
+                {
+                    if self.columns[column].sortable {
+                        let order = match self.sort {
+                            Some((sorted, SortOrder::Ascending)) if sorted == column => {
+                                SortOrder::Descending
+                            }
+                            _ => SortOrder::Ascending,
+                        };
+                        ui.send_message(DataGridMessage::sort_changed(
+                            self.handle(),
+                            MessageDirection::ToWidget,
+                            column,
+                            order,
+                        ));
+                    }
+                }
+            }
+            UiMessageData::TextBox(TextBoxMessage::Text(text))
+                if message.direction() == MessageDirection::FromWidget =>
+            {
+                if let Some((row, column)) = self.find_editor(message.destination()) {
+                    let value = match self.rows[row][column] {
+                        DataGridValue::Number(_) => {
+                            text.trim().parse().ok().map(DataGridValue::Number)
+                        }
+                        _ => Some(DataGridValue::Text(text.clone())),
+                    };
+                    match value {
+                        Some(value) => self.commit_cell(ui, row, column, value),
+                        None => {
+                            // Revert invalid input.
+                            ui.send_message(TextBoxMessage::text(
+                                message.destination(),
+                                MessageDirection::ToWidget,
+                                self.rows[row][column].to_string(),
+                            ));
+                        }
+                    }
+                }
+            }
+            &UiMessageData::CheckBox(CheckBoxMessage::Check(Some(value)))
+                if message.direction() == MessageDirection::FromWidget =>
+            {
+                if let Some((row, column)) = self.find_editor(message.destination()) {
+                    self.commit_cell(ui, row, column, DataGridValue::Bool(value));
+                }
+            }
+            UiMessageData::Widget(WidgetMessage::MouseDown { button, .. })
+                if *button == MouseButton::Left =>
+            {
+                // Find a row that contains the clicked node.
+                let mut handle = message.destination();
+                while handle.is_some() && handle != self.body {
+                    if let Some(row) = self.visible_rows.iter().find(|r| r.node == handle) {
+                        ui.send_message(DataGridMessage::selection(
+                            self.handle(),
+                            MessageDirection::ToWidget,
+                            Some(row.index),
+                        ));
+                        break;
+                    }
+                    handle = ui.node(handle).parent();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn remove_ref(&mut self, handle: Handle<UINode<M, C>>) {
+        if self.scroll_viewer == handle {
+            self.scroll_viewer = Handle::NONE;
+        }
+        if self.v_scroll_bar == handle {
+            self.v_scroll_bar = Handle::NONE;
+        }
+        if self.body == handle {
+            self.body = Handle::NONE;
+        }
+        if self.placeholder == handle {
+            self.placeholder = Handle::NONE;
+        }
+        self.visible_rows.retain(|row| row.node != handle);
+    }
+}
+
+pub struct DataGridBuilder<M: MessageData, C: Control<M, C>> {
+    widget_builder: WidgetBuilder<M, C>,
+    columns: Vec<DataGridColumn<M, C>>,
+    rows: Vec<Vec<DataGridValue>>,
+    row_height: f32,
+    selected_brush: Brush,
+    placeholder: Handle<UINode<M, C>>,
+}
+
+impl<M: MessageData, C: Control<M, C>> DataGridBuilder<M, C> {
+    pub fn new(widget_builder: WidgetBuilder<M, C>) -> Self {
+        Self {
+            widget_builder,
+            columns: Default::default(),
+            rows: Default::default(),
+            row_height: DEFAULT_ROW_HEIGHT,
+            selected_brush: BRUSH_BRIGHT_BLUE,
+            placeholder: Handle::NONE,
+        }
+    }
+
+    pub fn with_columns(mut self, columns: Vec<DataGridColumn<M, C>>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn with_rows(mut self, rows: Vec<Vec<DataGridValue>>) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    pub fn with_row_height(mut self, row_height: f32) -> Self {
+        self.row_height = row_height;
+        self
+    }
+
+    pub fn with_selected_brush(mut self, brush: Brush) -> Self {
+        self.selected_brush = brush;
+        self
+    }
+
+    /// Sets node that is shown over the grid while there are no rows. Placeholder is
+    /// centered in the grid and does not react on mouse.
+    pub fn with_placeholder(mut self, placeholder: Handle<UINode<M, C>>) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        let mut header_buttons = Vec::new();
+        let mut header_texts = Vec::new();
+        for (i, column) in self.columns.iter().enumerate() {
+            let text = TextBuilder::new(WidgetBuilder::new())
+                .with_text(&column.header)
+                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .build(ctx);
+            header_texts.push(text);
+            header_buttons.push(
+                ButtonBuilder::new(
+                    WidgetBuilder::new()
+                        .with_height(self.row_height)
+                        .on_column(i)
+                        .with_tab_stop(false),
+                )
+                .with_content(text)
+                .build(ctx),
+            );
+        }
+        let header = GridBuilder::new(WidgetBuilder::new().with_children(&header_buttons))
+            .add_row(Row::stretch())
+            .add_columns(self.columns.iter().map(|c| c.grid_column()).collect())
+            .build(ctx);
+
+        let mut data_grid = DataGrid {
+            widget: WidgetBuilder::new().build(),
+            columns: self.columns,
+            rows: self.rows,
+            row_height: self.row_height.max(1.0),
+            selected_index: None,
+            selected_brush: self.selected_brush,
+            sort: None,
+            scroll_viewer: Handle::NONE,
+            v_scroll_bar: Handle::NONE,
+            body: Handle::NONE,
+            header_buttons,
+            header_texts,
+            visible_rows: Default::default(),
+            scroll_value: 0.0,
+            viewport_height: 0.0,
+            placeholder: self.placeholder,
+        };
+
+        // Rows that fit before first layout are built right away, the rest are built when
+        // size of viewport becomes known.
+        let rows = data_grid.build_rows(ctx, None);
+        data_grid.body =
+            StackPanelBuilder::new(WidgetBuilder::new().with_children(&rows)).build(ctx);
+        data_grid.scroll_viewer = ScrollViewerBuilder::new(WidgetBuilder::new())
+            .with_header(header)
+            .with_content(data_grid.body)
+            .with_stretch_content_height(false)
+            .build(ctx);
+        data_grid.v_scroll_bar = ctx[data_grid.scroll_viewer].as_scroll_viewer().v_scroll_bar;
+        let mut widget_builder = self.widget_builder.with_child(data_grid.scroll_viewer);
+        if self.placeholder.is_some() {
+            ctx[self.placeholder]
+                .set_hit_test_visibility(false)
+                .set_horizontal_alignment(HorizontalAlignment::Center)
+                .set_vertical_alignment(VerticalAlignment::Center)
+                .set_visibility(data_grid.rows.is_empty());
+            widget_builder = widget_builder.with_child(self.placeholder);
+        }
+        data_grid.widget = widget_builder.build();
+
+        ctx.add_node(UINode::DataGrid(data_grid))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        brush::Brush,
+        core::{algebra::Vector2, color::Color, pool::Handle},
+        data_grid::{ColumnWidth, DataGrid, DataGridColumn, DataGridValue, SortOrder},
+        message::{
+            ButtonState, CheckBoxMessage, DataGridMessage, MessageDirection, MouseButton, OsEvent,
+            UiMessageData,
+        },
+        node::StubNode,
+        stack_panel::StackPanelBuilder,
+        widget::WidgetBuilder,
+        UINode, UserInterface,
+    };
+    use std::rc::Rc;
+
+    // Builder of the grid makes texts for headers, which need a font, so the grid is built
+    // without header and scroll viewer.
+    fn make_grid(ui: &mut UserInterface<(), StubNode>) -> Handle<UINode<(), StubNode>> {
+        let ctx = &mut ui.build_ctx();
+        let name =
+            DataGridColumn::new("Name", ColumnWidth::Stretch).with_template(Rc::new(|ctx, _| {
+                BorderBuilder::new(WidgetBuilder::new()).build(ctx)
+            }));
+        let enabled = DataGridColumn::new("Enabled", ColumnWidth::Strict(20.0)).with_editable(true);
+        let mut grid = DataGrid {
+            widget: WidgetBuilder::new().build(),
+            columns: vec![name, enabled],
+            rows: [3.0, 1.0, 2.0]
+                .iter()
+                .map(|&n| vec![DataGridValue::Number(n), DataGridValue::Bool(false)])
+                .collect(),
+            row_height: 20.0,
+            selected_index: None,
+            selected_brush: Brush::Solid(Color::WHITE),
+            sort: None,
+            scroll_viewer: Handle::NONE,
+            v_scroll_bar: Handle::NONE,
+            body: Handle::NONE,
+            header_buttons: Default::default(),
+            header_texts: Default::default(),
+            visible_rows: Default::default(),
+            scroll_value: 0.0,
+            viewport_height: 100.0,
+            placeholder: BorderBuilder::new(WidgetBuilder::new().with_visibility(false)).build(ctx),
+        };
+        let rows = grid.build_rows(ctx, None);
+        grid.body = StackPanelBuilder::new(WidgetBuilder::new().with_children(&rows)).build(ctx);
+        grid.widget = WidgetBuilder::new()
+            .with_width(100.0)
+            .with_child(grid.body)
+            .with_child(grid.placeholder)
+            .build();
+        ctx.add_node(UINode::DataGrid(grid))
+    }
+
+    fn data_grid(
+        ui: &UserInterface<(), StubNode>,
+        handle: Handle<UINode<(), StubNode>>,
+    ) -> &DataGrid<(), StubNode> {
+        if let UINode::DataGrid(grid) = ui.node(handle) {
+            grid
+        } else {
+            unreachable!()
+        }
+    }
+
+    fn numbers(grid: &DataGrid<(), StubNode>) -> Vec<DataGridValue> {
+        grid.rows().iter().map(|row| row[0].clone()).collect()
+    }
+
+    fn editor(grid: &DataGrid<(), StubNode>, row: usize) -> Handle<UINode<(), StubNode>> {
+        grid.visible_rows
+            .iter()
+            .find(|r| r.index == row)
+            .unwrap()
+            .editors[0]
+            .1
+    }
+
+    fn flush(ui: &mut UserInterface<(), StubNode>) -> Vec<DataGridMessage> {
+        let mut messages = Vec::new();
+        while let Some(message) = ui.poll_message() {
+            if let UiMessageData::DataGrid(msg) = message.data() {
+                if message.direction() == MessageDirection::FromWidget {
+                    messages.push(msg.clone());
+                }
+            }
+        }
+        messages
+    }
+
+    #[test]
+    fn sort() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let grid = make_grid(&mut ui);
+        ui.send_message(DataGridMessage::selection(
+            grid,
+            MessageDirection::ToWidget,
+            Some(0),
+        ));
+        ui.send_message(DataGridMessage::sort_changed(
+            grid,
+            MessageDirection::ToWidget,
+            0,
+            SortOrder::Ascending,
+        ));
+        assert_eq!(
+            flush(&mut ui),
+            vec![
+                DataGridMessage::SelectionChanged(Some(0)),
+                DataGridMessage::SortChanged {
+                    column: 0,
+                    order: SortOrder::Ascending
+                }
+            ]
+        );
+        let grid_ref = data_grid(&ui, grid);
+        assert_eq!(
+            numbers(grid_ref),
+            [1.0, 2.0, 3.0]
+                .iter()
+                .map(|&n| DataGridValue::Number(n))
+                .collect::<Vec<_>>()
+        );
+        // Selection moves with its row.
+        assert_eq!(grid_ref.selected_index(), Some(2));
+        // Rows are rebuilt in new order, between two spacers.
+        assert_eq!(ui.node(grid_ref.body).children().len(), 5);
+        let indices = grid_ref
+            .visible_rows
+            .iter()
+            .map(|r| r.index)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn select_by_click() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let grid = make_grid(&mut ui);
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        flush(&mut ui);
+
+        for event in [
+            OsEvent::CursorMoved {
+                position: Vector2::new(10.0, 30.0),
+            },
+            OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ButtonState::Pressed,
+            },
+        ]
+        .iter()
+        {
+            ui.process_os_event(event);
+        }
+        assert_eq!(
+            flush(&mut ui),
+            vec![DataGridMessage::SelectionChanged(Some(1))]
+        );
+        assert_eq!(data_grid(&ui, grid).selected_index(), Some(1));
+    }
+
+    #[test]
+    fn edit_commit() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let grid = make_grid(&mut ui);
+        flush(&mut ui);
+
+        let editor_of_first = editor(data_grid(&ui, grid), 0);
+        ui.send_message(CheckBoxMessage::checked(
+            editor_of_first,
+            MessageDirection::ToWidget,
+            Some(true),
+        ));
+        assert_eq!(
+            flush(&mut ui),
+            vec![DataGridMessage::CellChanged {
+                row: 0,
+                column: 1,
+                value: DataGridValue::Bool(true)
+            }]
+        );
+        assert_eq!(data_grid(&ui, grid).rows()[0][1], DataGridValue::Bool(true));
+
+        // Edited cell survives refresh caused by sorting, it moves with its row.
+        ui.set_keyboard_focus_node(editor_of_first);
+        ui.send_message(DataGridMessage::sort_changed(
+            grid,
+            MessageDirection::ToWidget,
+            0,
+            SortOrder::Ascending,
+        ));
+        flush(&mut ui);
+        let grid_ref = data_grid(&ui, grid);
+        assert_eq!(editor(grid_ref, 2), editor_of_first);
+        assert!(ui.is_node_child_of(editor_of_first, grid_ref.body));
+        assert_eq!(ui.keyboard_focus_node(), editor_of_first);
+    }
+
+    #[test]
+    fn placeholder() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let grid = make_grid(&mut ui);
+        let placeholder = data_grid(&ui, grid).placeholder();
+
+        ui.send_message(DataGridMessage::rows(
+            grid,
+            MessageDirection::ToWidget,
+            vec![],
+        ));
+        flush(&mut ui);
+        assert!(ui.node(placeholder).visibility());
+
+        ui.send_message(DataGridMessage::rows(
+            grid,
+            MessageDirection::ToWidget,
+            vec![vec![DataGridValue::Number(1.0), DataGridValue::Bool(true)]],
+        ));
+        flush(&mut ui);
+        assert!(!ui.node(placeholder).visibility());
+    }
+}
@@ -13,12 +13,73 @@ use crate::{
 };
 use std::ops::{Deref, DerefMut};
 
+/// Interaction state of a widget, it defines which brush should be used to draw the widget.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VisualState {
+    Normal,
+    Hover,
+    Pressed,
+    Disabled,
+}
+
+impl VisualState {
+    /// Resolves current state of a widget. Disabled state always wins, then pressed, then hover.
+    pub fn resolve(is_mouse_over: bool, is_pressed: bool, enabled: bool) -> Self {
+        if !enabled {
+            VisualState::Disabled
+        } else if is_pressed {
+            VisualState::Pressed
+        } else if is_mouse_over {
+            VisualState::Hover
+        } else {
+            VisualState::Normal
+        }
+    }
+}
+
+/// Set of brushes for every visual state of a widget.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VisualStates {
+    pub normal: Brush,
+    pub hover: Brush,
+    pub pressed: Brush,
+    pub disabled: Brush,
+}
+
+impl Default for VisualStates {
+    fn default() -> Self {
+        Self {
+            normal: BRUSH_LIGHT,
+            hover: BRUSH_LIGHTER,
+            pressed: BRUSH_LIGHTEST,
+            disabled: Brush::Solid(Color::opaque(50, 50, 50)),
+        }
+    }
+}
+
+impl VisualStates {
+    pub fn brush(&self, state: VisualState) -> &Brush {
+        match state {
+            VisualState::Normal => &self.normal,
+            VisualState::Hover => &self.hover,
+            VisualState::Pressed => &self.pressed,
+            VisualState::Disabled => &self.disabled,
+        }
+    }
+
+    /// Returns brush that should be used to draw a widget in given conditions.
+    pub fn resolve(&self, is_mouse_over: bool, is_pressed: bool, enabled: bool) -> &Brush {
+        self.brush(VisualState::resolve(is_mouse_over, is_pressed, enabled))
+    }
+}
+
 /// A visual element that changes its appearance by listening specific events.
-/// It can has "pressed", "hover", "selected" or normal appearance:
+/// It can has "pressed", "hover", "selected", "disabled" or normal appearance:
 ///
 /// `Pressed` - enables on mouse down message.
 /// `Selected` - whether decorator selected or not.
 /// `Hovered` - mouse is over decorator.
+/// `Disabled` - decorator is disabled, it beats any other state.
 /// `Normal` - not selected, pressed, hovered.
 ///
 /// This element is widely used to provide some generic visual behaviour for various
@@ -26,12 +87,15 @@ use std::ops::{Deref, DerefMut};
 #[derive(Clone)]
 pub struct Decorator<M: MessageData, C: Control<M, C>> {
     border: Border<M, C>,
-    normal_brush: Brush,
-    hover_brush: Brush,
-    pressed_brush: Brush,
+    states: VisualStates,
     selected_brush: Brush,
-    disabled_brush: Brush,
+    /// Draws selection with selection color of the UI instead of `selected_brush`, see
+    /// `UserInterface::set_selection_color`.
+    shared_selection_color: bool,
     is_selected: bool,
+    is_selection_active: bool,
+    is_mouse_over: bool,
+    is_pressed: bool,
     pressable: bool,
 }
 
@@ -49,6 +113,58 @@ impl<M: MessageData, C: Control<M, C>> DerefMut for Decorator<M, C> {
     }
 }
 
+impl<M: MessageData, C: Control<M, C>> Decorator<M, C> {
+    pub fn visual_states(&self) -> &VisualStates {
+        &self.states
+    }
+
+    pub fn set_normal_brush(&mut self, brush: Brush) -> &mut Self {
+        self.states.normal = brush;
+        self
+    }
+
+    pub fn set_hover_brush(&mut self, brush: Brush) -> &mut Self {
+        self.states.hover = brush;
+        self
+    }
+
+    pub fn set_pressed_brush(&mut self, brush: Brush) -> &mut Self {
+        self.states.pressed = brush;
+        self
+    }
+
+    pub fn set_disabled_brush(&mut self, brush: Brush) -> &mut Self {
+        self.states.disabled = brush;
+        self
+    }
+
+    pub fn set_selected_brush(&mut self, brush: Brush) -> &mut Self {
+        self.selected_brush = brush;
+        self
+    }
+
+    pub fn is_selected(&self) -> bool {
+        self.is_selected
+    }
+
+    pub fn is_selection_active(&self) -> bool {
+        self.is_selection_active
+    }
+
+    pub fn is_shared_selection_color(&self) -> bool {
+        self.shared_selection_color
+    }
+
+    /// Returns brush that matches current state of the decorator. Selected brush is replaced
+    /// by selection color of the UI when drawing if the decorator uses shared selection color.
+    pub fn current_brush(&self) -> &Brush {
+        match VisualState::resolve(self.is_mouse_over, self.is_pressed, self.enabled()) {
+            VisualState::Normal if self.is_selected => &self.selected_brush,
+            state => self.states.brush(state),
+        }
+    }
+}
+
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for Decorator<M, C> {
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         self.border.resolve(node_map)
@@ -83,7 +199,14 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Decorator<M, C> {
     }
 
     fn draw(&self, drawing_context: &mut DrawingContext) {
-        self.border.draw(drawing_context)
+        let brush = match VisualState::resolve(self.is_mouse_over, self.is_pressed, self.enabled())
+        {
+            VisualState::Normal if self.is_selected && self.shared_selection_color => {
+                Brush::Solid(drawing_context.selection_color(self.is_selection_active))
+            }
+            _ => self.current_brush().clone(),
+        };
+        self.border.draw_with_background(drawing_context, brush)
     }
 
     fn update(&mut self, dt: f32) {
@@ -98,107 +221,53 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Decorator<M, C> {
         self.border.handle_routed_message(ui, message);
 
         match &message.data() {
-            UiMessageData::Decorator(msg) => match msg {
+            UiMessageData::Decorator(msg) if message.destination() == self.handle() => match msg {
                 &DecoratorMessage::Select(value) => {
-                    if self.is_selected != value {
-                        self.is_selected = value;
-                        if self.is_selected {
-                            ui.send_message(WidgetMessage::background(
-                                self.handle(),
-                                MessageDirection::ToWidget,
-                                self.selected_brush.clone(),
-                            ));
-                        } else {
-                            ui.send_message(WidgetMessage::background(
-                                self.handle(),
-                                MessageDirection::ToWidget,
-                                self.normal_brush.clone(),
-                            ));
-                        }
-                    }
+                    self.is_selected = value;
+                }
+                &DecoratorMessage::SelectionActive(value) => {
+                    self.is_selection_active = value;
                 }
                 DecoratorMessage::HoverBrush(brush) => {
-                    self.hover_brush = brush.clone();
-                    if self.is_mouse_directly_over {
-                        ui.send_message(WidgetMessage::background(
-                            self.handle(),
-                            MessageDirection::ToWidget,
-                            self.hover_brush.clone(),
-                        ));
-                    }
+                    self.set_hover_brush(brush.clone());
                 }
                 DecoratorMessage::NormalBrush(brush) => {
-                    self.normal_brush = brush.clone();
-                    if !self.is_selected && !self.is_mouse_directly_over {
-                        ui.send_message(WidgetMessage::background(
-                            self.handle(),
-                            MessageDirection::ToWidget,
-                            self.normal_brush.clone(),
-                        ));
-                    }
+                    self.set_normal_brush(brush.clone());
                 }
                 DecoratorMessage::PressedBrush(brush) => {
-                    self.pressed_brush = brush.clone();
+                    self.set_pressed_brush(brush.clone());
                 }
                 DecoratorMessage::SelectedBrush(brush) => {
-                    self.selected_brush = brush.clone();
-                    if self.is_selected {
-                        ui.send_message(WidgetMessage::background(
-                            self.handle(),
-                            MessageDirection::ToWidget,
-                            self.selected_brush.clone(),
-                        ));
-                    }
+                    self.set_selected_brush(brush.clone());
+                }
+                DecoratorMessage::DisabledBrush(brush) => {
+                    self.set_disabled_brush(brush.clone());
                 }
             },
+            // Background is drawn by state brushes, so a new background replaces normal brush.
+            UiMessageData::Widget(WidgetMessage::Background(brush))
+                if message.destination() == self.handle()
+                    && message.direction() == MessageDirection::ToWidget =>
+            {
+                self.set_normal_brush(brush.clone());
+            }
             UiMessageData::Widget(msg) => {
                 if message.destination() == self.handle()
                     || self.has_descendant(message.destination(), ui)
                 {
                     match msg {
                         WidgetMessage::MouseLeave => {
-                            if self.is_selected {
-                                ui.send_message(WidgetMessage::background(
-                                    self.handle(),
-                                    MessageDirection::ToWidget,
-                                    self.selected_brush.clone(),
-                                ));
-                            } else {
-                                ui.send_message(WidgetMessage::background(
-                                    self.handle(),
-                                    MessageDirection::ToWidget,
-                                    self.normal_brush.clone(),
-                                ));
-                            }
+                            self.is_mouse_over = false;
+                            self.is_pressed = false;
                         }
                         WidgetMessage::MouseEnter => {
-                            ui.send_message(WidgetMessage::background(
-                                self.handle(),
-                                MessageDirection::ToWidget,
-                                self.hover_brush.clone(),
-                            ));
+                            self.is_mouse_over = true;
                         }
                         WidgetMessage::MouseDown { .. } if self.pressable => {
-                            ui.send_message(WidgetMessage::background(
-                                self.handle(),
-                                MessageDirection::ToWidget,
-                                self.pressed_brush.clone(),
-                            ));
+                            self.is_pressed = true;
                         }
-                        WidgetMessage::MouseUp { .. } => {
-                            if self.is_selected {
-                                ui.send_message(WidgetMessage::background(
-                                    self.handle(),
-                                    MessageDirection::ToWidget,
-                                    self.selected_brush.clone(),
-                                ));
-                            } else {
-                                ui.send_message(WidgetMessage::background(
-                                    self.handle(),
-                                    MessageDirection::ToWidget,
-                                    self.normal_brush.clone(),
-                                ));
-                            }
+                        WidgetMessage::MouseUp { .. } | WidgetMessage::MouseCaptureLost => {
+                            self.is_pressed = false;
                         }
                         _ => {}
                     }
@@ -220,6 +289,7 @@ pub struct DecoratorBuilder<M: MessageData, C: Control<M, C>> {
     pressed_brush: Option<Brush>,
     selected_brush: Option<Brush>,
     disabled_brush: Option<Brush>,
+    shared_selection_color: bool,
     pressable: bool,
 }
 
@@ -232,6 +302,7 @@ impl<M: MessageData, C: Control<M, C>> DecoratorBuilder<M, C> {
             pressed_brush: None,
             selected_brush: None,
             disabled_brush: None,
+            shared_selection_color: false,
             pressable: true,
         }
     }
@@ -261,6 +332,13 @@ impl<M: MessageData, C: Control<M, C>> DecoratorBuilder<M, C> {
         self
     }
 
+    /// Makes selected decorator drawn with selection color of the UI (active or inactive, see
+    /// `DecoratorMessage::SelectionActive`) instead of selected brush. Disabled by default.
+    pub fn with_shared_selection_color(mut self, shared: bool) -> Self {
+        self.shared_selection_color = shared;
+        self
+    }
+
     pub fn with_pressable(mut self, pressable: bool) -> Self {
         self.pressable = pressable;
         self
@@ -296,16 +374,56 @@ impl<M: MessageData, C: Control<M, C>> DecoratorBuilder<M, C> {
 
         let node = UINode::Decorator(Decorator {
             border,
-            normal_brush,
-            hover_brush: self.hover_brush.unwrap_or(BRUSH_LIGHTER),
-            pressed_brush: self.pressed_brush.unwrap_or(BRUSH_LIGHTEST),
+            states: VisualStates {
+                normal: normal_brush,
+                hover: self.hover_brush.unwrap_or(BRUSH_LIGHTER),
+                pressed: self.pressed_brush.unwrap_or(BRUSH_LIGHTEST),
+                disabled: self
+                    .disabled_brush
+                    .unwrap_or_else(|| Brush::Solid(Color::opaque(50, 50, 50))),
+            },
             selected_brush: self.selected_brush.unwrap_or(BRUSH_BRIGHT),
-            disabled_brush: self
-                .disabled_brush
-                .unwrap_or_else(|| Brush::Solid(Color::opaque(50, 50, 50))),
+            shared_selection_color: self.shared_selection_color,
             is_selected: false,
+            is_selection_active: true,
+            is_mouse_over: false,
+            is_pressed: false,
             pressable: self.pressable,
         });
         ui.add_node(node)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        brush::Brush,
+        core::{algebra::Vector2, color::Color},
+        decorator::DecoratorBuilder,
+        message::{MessageDirection, WidgetMessage},
+        node::{StubNode, UINode},
+        widget::WidgetBuilder,
+        UserInterface,
+    };
+
+    #[test]
+    fn background_changes_normal_brush() {
+        let mut ui = UserInterface::<(), StubNode>::new(Vector2::new(1000.0, 1000.0));
+        let decorator = DecoratorBuilder::new(BorderBuilder::new(WidgetBuilder::new()))
+            .build(&mut ui.build_ctx());
+        let brush = Brush::Solid(Color::RED);
+        ui.send_message(WidgetMessage::background(
+            decorator,
+            MessageDirection::ToWidget,
+            brush.clone(),
+        ));
+        while ui.poll_message().is_some() {}
+
+        if let UINode::Decorator(decorator) = ui.node(decorator) {
+            assert_eq!(decorator.current_brush(), &brush);
+        } else {
+            unreachable!()
+        }
+    }
+}
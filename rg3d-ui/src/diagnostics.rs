@@ -0,0 +1,136 @@
+//! Layout diagnostics. It is a developer tool that helps to find typical layout bugs, such as
+//! widgets that got no space or widgets that are partially clipped by their parents. It does
+//! not change any behavior of the UI.
+
+use crate::{
+    core::{math::Rect, pool::Handle},
+    message::MessageData,
+    Control, UINode, UserInterface,
+};
+use std::fmt::{Display, Formatter};
+
+/// Distances (in units) smaller than this value are ignored, so rounding errors of layout
+/// won't produce warnings.
+const TOLERANCE: f32 = 0.5;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LayoutWarningKind<M: MessageData, C: Control<M, C>> {
+    /// Node wants some space for its content, but its final size is zero.
+    ZeroSize,
+    /// Node goes beyond bounds of its parent, so it is partially clipped.
+    Overflow { parent: Handle<UINode<M, C>> },
+    /// Node overlaps its sibling in a canvas.
+    Overlap { sibling: Handle<UINode<M, C>> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutWarning<M: MessageData, C: Control<M, C>> {
+    pub node: Handle<UINode<M, C>>,
+    pub kind: LayoutWarningKind<M, C>,
+}
+
+impl<M: MessageData, C: Control<M, C>> Display for LayoutWarning<M, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            LayoutWarningKind::ZeroSize => {
+                write!(f, "{:?} has content, but its size is zero", self.node)
+            }
+            LayoutWarningKind::Overflow { parent } => {
+                write!(
+                    f,
+                    "{:?} overflows bounds of its parent {:?}",
+                    self.node, parent
+                )
+            }
+            LayoutWarningKind::Overlap { sibling } => {
+                write!(
+                    f,
+                    "{:?} overlaps its sibling {:?} in canvas",
+                    self.node, sibling
+                )
+            }
+        }
+    }
+}
+
+fn is_inside(inner: &Rect<f32>, outer: &Rect<f32>) -> bool {
+    inner.x() >= outer.x() - TOLERANCE
+        && inner.y() >= outer.y() - TOLERANCE
+        && inner.x() + inner.w() <= outer.x() + outer.w() + TOLERANCE
+        && inner.y() + inner.h() <= outer.y() + outer.h() + TOLERANCE
+}
+
+fn is_overlapping(a: &Rect<f32>, b: &Rect<f32>) -> bool {
+    let w = (a.x() + a.w()).min(b.x() + b.w()) - a.x().max(b.x());
+    let h = (a.y() + a.h()).min(b.y() + b.h()) - a.y().max(b.y());
+    w > TOLERANCE && h > TOLERANCE
+}
+
+fn is_zero_size(bounds: &Rect<f32>) -> bool {
+    bounds.w() <= f32::EPSILON || bounds.h() <= f32::EPSILON
+}
+
+/// Checks arranged geometry of given sub-tree. Invisible nodes are skipped together with
+/// their descendants. Children of scroll panels are not checked for overflow, because they
+/// are meant to be larger than the panel, children of root canvas are not checked for
+/// overlap, because it contains floating windows and popups.
+pub fn validate_layout<M: MessageData, C: Control<M, C>>(
+    ui: &UserInterface<M, C>,
+    root: Handle<UINode<M, C>>,
+) -> Vec<LayoutWarning<M, C>> {
+    let mut warnings = Vec::new();
+    let mut stack = vec![root];
+    while let Some(handle) = stack.pop() {
+        let node = ui.node(handle);
+        if !node.is_globally_visible() {
+            continue;
+        }
+
+        let bounds = node.screen_bounds();
+        let desired_size = node.desired_size();
+        let zero_size = is_zero_size(&bounds);
+        if zero_size && desired_size.x > TOLERANCE && desired_size.y > TOLERANCE {
+            warnings.push(LayoutWarning {
+                node: handle,
+                kind: LayoutWarningKind::ZeroSize,
+            });
+        }
+
+        let parent = node.parent();
+        if !zero_size && parent.is_some() && !node.is_draw_on_top() {
+            let parent_node = ui.node(parent);
+            if !matches!(parent_node, UINode::ScrollPanel(_))
+                && !is_inside(&bounds, &parent_node.screen_bounds())
+            {
+                warnings.push(LayoutWarning {
+                    node: handle,
+                    kind: LayoutWarningKind::Overflow { parent },
+                });
+            }
+        }
+
+        if let UINode::Canvas(_) = node {
+            if handle != ui.root() {
+                let children = node
+                    .children()
+                    .iter()
+                    .filter(|&&child| ui.node(child).is_globally_visible())
+                    .map(|&child| (child, ui.node(child).screen_bounds()))
+                    .collect::<Vec<_>>();
+                for (i, (a, a_bounds)) in children.iter().enumerate() {
+                    for (b, b_bounds) in children[i + 1..].iter() {
+                        if is_overlapping(a_bounds, b_bounds) {
+                            warnings.push(LayoutWarning {
+                                node: *b,
+                                kind: LayoutWarningKind::Overlap { sibling: *a },
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        stack.extend_from_slice(node.children());
+    }
+    warnings
+}
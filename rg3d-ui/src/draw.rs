@@ -1,16 +1,16 @@
 // Don't know how to correctly fix this so lower priority for now.
 #![warn(clippy::vtable_address_comparisons)]
 
-use crate::core::algebra::Vector2;
+use crate::core::algebra::{Matrix3, Vector2};
 use crate::{
-    brush::Brush,
+    brush::{tint_color, Brush},
     core::{
         color::Color,
         math::{self, Rect, TriangleDefinition},
     },
-    formatted_text::FormattedText,
+    formatted_text::{FormattedText, TextGlyph},
     ttf::SharedFont,
-    Thickness,
+    Thickness, COLOR_BRIGHT_BLUE, COLOR_INACTIVE_SELECTION,
 };
 use std::{any::Any, ops::Deref, ops::Range, sync::Arc};
 
@@ -54,6 +54,11 @@ pub enum CommandTexture {
     None,
     Texture(SharedTexture),
     Font(SharedFont),
+    /// Blurred copy of everything that was drawn behind bounds of the command, radius of blur
+    /// is in the same units as geometry. See `Backdrop::Blur`.
+    Backdrop {
+        blur_radius: f32,
+    },
 }
 
 /// A set of triangles that will be used for clipping.
@@ -109,6 +114,33 @@ impl ClippingGeometry {
     }
 }
 
+/// Background of a panel (window, popup) that is drawn behind the panel.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum Backdrop {
+    #[default]
+    None,
+    /// Blurs everything behind the panel with given radius and draws `BLUR_TINT` over it.
+    /// Blur is done by renderer (see `CommandTexture::Backdrop`), a custom renderer must
+    /// handle that texture kind too.
+    Blur(f32),
+    /// Fills bounds of the panel with given color and alpha in [0; 1] range. It is a cheap
+    /// alternative to blur.
+    Tint(Color, f32),
+}
+
+/// Tint that is drawn over blurred backdrop, it keeps content of the panel readable.
+pub const BLUR_TINT: Color = Color::from_rgba(20, 20, 20, 120);
+
+/// User-defined command, built-in renderer skips it, so a custom renderer must handle it.
+/// For example it could bind some shader for next `count` commands to blur region behind a
+/// panel.
+#[derive(Clone)]
+pub struct CustomCommand {
+    pub data: Arc<dyn Any + Send + Sync>,
+    /// Amount of following commands affected by this command.
+    pub count: usize,
+}
+
 #[derive(Clone)]
 pub struct Command {
     /// Clipping bounds, should be used for scissor-test. Screen-space.
@@ -122,6 +154,11 @@ pub struct Command {
     pub opacity: f32,
     /// A set of triangles that defines clipping region.
     pub clipping_geometry: Option<ClippingGeometry>,
+    /// Visual transform of the geometry in screen space. Vertices, bounds and clipping
+    /// geometry are not transformed, so renderer must apply it.
+    pub transform: Matrix3<f32>,
+    /// Custom commands have no geometry, see `DrawingContext::push_custom_command`.
+    pub custom: Option<CustomCommand>,
 }
 
 pub trait Draw {
@@ -156,13 +193,21 @@ pub trait Draw {
         self.push_triangle(index, index + 1, index + 2);
     }
 
-    fn push_line(&mut self, a: Vector2<f32>, b: Vector2<f32>, thickness: f32) {
+    fn push_line(&mut self, a: Vector2<f32>, b: Vector2<f32>, thickness: f32, color: Color) {
         let index = self.last_vertex_index();
         let perp = get_line_thickness_vector(a, b, thickness);
-        self.push_vertex(a - perp, Vector2::new(0.0, 0.0));
-        self.push_vertex(b - perp, Vector2::new(1.0, 0.0));
-        self.push_vertex(a + perp, Vector2::new(1.0, 1.0));
-        self.push_vertex(b + perp, Vector2::new(0.0, 1.0));
+        for &(pos, tex_coord) in &[
+            (a - perp, Vector2::new(0.0, 0.0)),
+            (b - perp, Vector2::new(1.0, 0.0)),
+            (a + perp, Vector2::new(1.0, 1.0)),
+            (b + perp, Vector2::new(0.0, 1.0)),
+        ] {
+            self.push_vertex_raw(Vertex {
+                pos,
+                tex_coord,
+                color,
+            });
+        }
 
         self.push_triangle(index, index + 1, index + 2);
         self.push_triangle(index + 2, index + 1, index + 3);
@@ -184,12 +229,12 @@ pub trait Draw {
         let left_bottom_off = Vector2::new(rect.x(), rect.y() + rect.h() - offset);
 
         // Horizontal lines
-        self.push_line(left_top_off, right_top_off, thickness);
-        self.push_line(right_bottom_off, left_bottom_off, thickness);
+        self.push_line(left_top_off, right_top_off, thickness, Color::WHITE);
+        self.push_line(right_bottom_off, left_bottom_off, thickness, Color::WHITE);
 
         // Vertical line
-        self.push_line(right_top, right_bottom, thickness);
-        self.push_line(left_bottom, left_top, thickness);
+        self.push_line(right_top, right_bottom, thickness, Color::WHITE);
+        self.push_line(left_bottom, left_top, thickness, Color::WHITE);
     }
 
     fn push_rect_vary(&mut self, rect: &Rect<f32>, thickness: Thickness) {
@@ -215,12 +260,17 @@ pub trait Draw {
         let left_bottom_off = Vector2::new(rect.x(), rect.y() + rect.h() - thickness.bottom * 0.5);
 
         // Horizontal lines
-        self.push_line(left_top_off, right_top_off, thickness.top);
-        self.push_line(right_bottom_off, left_bottom_off, thickness.bottom);
+        self.push_line(left_top_off, right_top_off, thickness.top, Color::WHITE);
+        self.push_line(
+            right_bottom_off,
+            left_bottom_off,
+            thickness.bottom,
+            Color::WHITE,
+        );
 
         // Vertical lines
-        self.push_line(right_top, right_bottom, thickness.right);
-        self.push_line(left_bottom, left_top, thickness.left);
+        self.push_line(right_top, right_bottom, thickness.right, Color::WHITE);
+        self.push_line(left_bottom, left_top, thickness.left, Color::WHITE);
     }
 
     fn push_rect_filled(&mut self, rect: &Rect<f32>, tex_coords: Option<&[Vector2<f32>; 4]>) {
@@ -246,26 +296,39 @@ pub trait Draw {
         self.push_triangle(index, index + 2, index + 3);
     }
 
+    /// Colors are given in clockwise order starting from left top corner.
     fn push_rect_multicolor(&mut self, rect: &Rect<f32>, colors: [Color; 4]) {
+        self.push_rect_filled_multicolor(rect, None, colors);
+    }
+
+    /// Same as `push_rect_filled`, but every corner has its own color, colors are interpolated
+    /// across the rect and multiplied with the brush. Colors are given in clockwise order
+    /// starting from left top corner.
+    fn push_rect_filled_multicolor(
+        &mut self,
+        rect: &Rect<f32>,
+        tex_coords: Option<&[Vector2<f32>; 4]>,
+        colors: [Color; 4],
+    ) {
         let index = self.last_vertex_index();
         self.push_vertex_raw(Vertex {
             pos: rect.left_top_corner(),
-            tex_coord: Vector2::new(0.0, 0.0),
+            tex_coord: tex_coords.map_or(Vector2::new(0.0, 0.0), |t| t[0]),
             color: colors[0],
         });
         self.push_vertex_raw(Vertex {
             pos: rect.right_top_corner(),
-            tex_coord: Vector2::new(1.0, 0.0),
+            tex_coord: tex_coords.map_or(Vector2::new(1.0, 0.0), |t| t[1]),
             color: colors[1],
         });
         self.push_vertex_raw(Vertex {
             pos: rect.right_bottom_corner(),
-            tex_coord: Vector2::new(1.0, 1.0),
+            tex_coord: tex_coords.map_or(Vector2::new(1.0, 1.0), |t| t[2]),
             color: colors[2],
         });
         self.push_vertex_raw(Vertex {
             pos: rect.left_bottom_corner(),
-            tex_coord: Vector2::new(0.0, 1.0),
+            tex_coord: tex_coords.map_or(Vector2::new(0.0, 1.0), |t| t[3]),
             color: colors[3],
         });
 
@@ -273,6 +336,23 @@ pub trait Draw {
         self.push_triangle(index, index + 2, index + 3);
     }
 
+    /// Pushes rect with colors that are interpolated between its corners. Use it with white
+    /// solid brush to get the colors as is.
+    fn push_quad_gradient(
+        &mut self,
+        rect: &Rect<f32>,
+        top_left: Color,
+        top_right: Color,
+        bottom_left: Color,
+        bottom_right: Color,
+    ) {
+        self.push_rect_filled_multicolor(
+            rect,
+            None,
+            [top_left, top_right, bottom_right, bottom_left],
+        );
+    }
+
     fn push_circle(&mut self, origin: Vector2<f32>, radius: f32, segments: usize, color: Color) {
         if segments >= 3 {
             let center_index = self.last_vertex_index();
@@ -307,6 +387,251 @@ pub trait Draw {
             }
         }
     }
+
+    /// Pushes a filled rectangle with rounded corners, each corner is approximated with
+    /// given amount of segments.
+    fn push_rounded_rect_filled(&mut self, rect: &Rect<f32>, radius: f32, segments: usize) {
+        let points = rounded_rect_points(rect, radius, segments);
+        let tex_coord = |pos: Vector2<f32>| {
+            Vector2::new(
+                (pos.x - rect.x()) / rect.w().max(f32::EPSILON),
+                (pos.y - rect.y()) / rect.h().max(f32::EPSILON),
+            )
+        };
+
+        let center_index = self.last_vertex_index();
+        let center = rect.position + rect.size.scale(0.5);
+        self.push_vertex(center, tex_coord(center));
+        for &pos in points.iter() {
+            self.push_vertex(pos, tex_coord(pos));
+        }
+
+        let first_vertex = center_index + 1;
+        let count = points.len() as u32;
+        for i in 0..count {
+            self.push_triangle(
+                center_index,
+                first_vertex + i,
+                first_vertex + (i + 1) % count,
+            );
+        }
+    }
+
+    /// Pushes outline of a rectangle with rounded corners, outline is placed inside of the
+    /// rectangle.
+    fn push_rounded_rect(
+        &mut self,
+        rect: &Rect<f32>,
+        thickness: f32,
+        radius: f32,
+        segments: usize,
+    ) {
+        let half_thickness = thickness * 0.5;
+        let inner = Rect::new(
+            rect.x() + half_thickness,
+            rect.y() + half_thickness,
+            (rect.w() - thickness).max(0.0),
+            (rect.h() - thickness).max(0.0),
+        );
+        let points = rounded_rect_points(&inner, (radius - half_thickness).max(0.0), segments);
+        self.push_polyline(&points, thickness, Color::WHITE, true);
+    }
+
+    /// Pushes a set of connected lines with miter joins. Edges of the polyline are smoothly
+    /// faded out to give it anti-aliased look. If `closed` is true, last point will be connected
+    /// with the first one.
+    fn push_polyline(
+        &mut self,
+        points: &[Vector2<f32>],
+        thickness: f32,
+        color: Color,
+        closed: bool,
+    ) {
+        let count = points.len();
+        if count < 2 {
+            return;
+        }
+        let closed = closed && count > 2;
+        let half_thickness = thickness * 0.5;
+        let edge_color = Color::from_rgba(color.r, color.g, color.b, 0);
+
+        // Every point produces four vertices across the line: outer faded edge, two solid
+        // vertices and another faded edge.
+        let first = self.last_vertex_index();
+        for i in 0..count {
+            let normal = polyline_joint_normal(points, i, closed);
+            for &(offset, color) in &[
+                (-half_thickness - ANTI_ALIASING_FRINGE, edge_color),
+                (-half_thickness, color),
+                (half_thickness, color),
+                (half_thickness + ANTI_ALIASING_FRINGE, edge_color),
+            ] {
+                self.push_vertex_raw(Vertex {
+                    pos: points[i] + normal.scale(offset),
+                    tex_coord: Vector2::default(),
+                    color,
+                });
+            }
+        }
+
+        let segments = if closed { count } else { count - 1 };
+        for segment in 0..segments {
+            let a = first + 4 * segment as u32;
+            let b = first + 4 * ((segment + 1) % count) as u32;
+            for k in 0..3 {
+                self.push_triangle(a + k, b + k, a + k + 1);
+                self.push_triangle(a + k + 1, b + k, b + k + 1);
+            }
+        }
+    }
+
+    /// Tessellates cubic bezier curve into given amount of segments and pushes it as a
+    /// polyline.
+    #[allow(clippy::too_many_arguments)]
+    fn push_bezier(
+        &mut self,
+        p0: Vector2<f32>,
+        p1: Vector2<f32>,
+        p2: Vector2<f32>,
+        p3: Vector2<f32>,
+        segments: usize,
+        thickness: f32,
+        color: Color,
+    ) {
+        let points = tessellate_cubic_bezier(p0, p1, p2, p3, segments);
+        self.push_polyline(&points, thickness, color, false);
+    }
+}
+
+/// Returns axis-aligned bounds of given rectangle transformed by given 2D affine transform.
+pub fn transform_bounds(transform: &Matrix3<f32>, bounds: &Rect<f32>) -> Rect<f32> {
+    if *transform == Matrix3::identity() {
+        return *bounds;
+    }
+
+    let corners = [
+        bounds.left_top_corner(),
+        bounds.right_top_corner(),
+        bounds.right_bottom_corner(),
+        bounds.left_bottom_corner(),
+    ];
+    let mut min = Vector2::new(f32::MAX, f32::MAX);
+    let mut max = Vector2::new(-f32::MAX, -f32::MAX);
+    for corner in corners.iter() {
+        let p = transform.transform_point(&(*corner).into());
+        min = min.inf(&p.coords);
+        max = max.sup(&p.coords);
+    }
+    Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+}
+
+/// Returns outline of a rectangle with rounded corners. Points go clockwise starting from
+/// the top edge, radius is limited by half of the smallest side of the rectangle.
+pub fn rounded_rect_points(rect: &Rect<f32>, radius: f32, segments: usize) -> Vec<Vector2<f32>> {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    let radius = radius.min(rect.w() * 0.5).min(rect.h() * 0.5).max(0.0);
+    let segments = segments.max(1);
+    let corners = [
+        (
+            rect.right_top_corner() + Vector2::new(-radius, radius),
+            -FRAC_PI_2,
+        ),
+        (
+            rect.right_bottom_corner() + Vector2::new(-radius, -radius),
+            0.0,
+        ),
+        (
+            rect.left_bottom_corner() + Vector2::new(radius, -radius),
+            FRAC_PI_2,
+        ),
+        (rect.left_top_corner() + Vector2::new(radius, radius), PI),
+    ];
+
+    let mut points = Vec::with_capacity(4 * (segments + 1));
+    for &(center, start_angle) in corners.iter() {
+        for i in 0..=segments {
+            let angle = start_angle + FRAC_PI_2 * i as f32 / segments as f32;
+            points.push(center + Vector2::new(angle.cos(), angle.sin()).scale(radius));
+        }
+    }
+    points
+}
+
+/// Width of faded edges of polylines.
+const ANTI_ALIASING_FRINGE: f32 = 1.0;
+
+/// Limits length of miter on sharp corners, so they won't produce long spikes.
+const MITER_LIMIT: f32 = 4.0;
+
+fn line_normal(a: Vector2<f32>, b: Vector2<f32>) -> Option<Vector2<f32>> {
+    (b - a)
+        .try_normalize(f32::EPSILON)
+        .map(|dir| Vector2::new(dir.y, -dir.x))
+}
+
+/// Calculates miter vector for a point of polyline. Length of the vector is chosen so that
+/// offset along it gives correct thickness for both adjacent segments.
+fn polyline_joint_normal(points: &[Vector2<f32>], i: usize, closed: bool) -> Vector2<f32> {
+    let count = points.len();
+    let prev = if i > 0 {
+        Some(points[i - 1])
+    } else if closed {
+        Some(points[count - 1])
+    } else {
+        None
+    };
+    let next = if i + 1 < count {
+        Some(points[i + 1])
+    } else if closed {
+        Some(points[0])
+    } else {
+        None
+    };
+
+    let n0 = prev.and_then(|prev| line_normal(prev, points[i]));
+    let n1 = next.and_then(|next| line_normal(points[i], next));
+
+    match (n0, n1) {
+        (Some(n0), Some(n1)) => match (n0 + n1).try_normalize(f32::EPSILON) {
+            Some(miter) => {
+                let cos = miter.dot(&n0).max(1.0 / MITER_LIMIT);
+                miter.scale(1.0 / cos)
+            }
+            None => n0,
+        },
+        (Some(n), None) | (None, Some(n)) => n,
+        (None, None) => Vector2::default(),
+    }
+}
+
+/// Calculates a point on cubic bezier curve at given parameter `t` in [0; 1] range.
+pub fn cubic_bezier(
+    p0: Vector2<f32>,
+    p1: Vector2<f32>,
+    p2: Vector2<f32>,
+    p3: Vector2<f32>,
+    t: f32,
+) -> Vector2<f32> {
+    let it = 1.0 - t;
+    p0.scale(it * it * it)
+        + p1.scale(3.0 * it * it * t)
+        + p2.scale(3.0 * it * t * t)
+        + p3.scale(t * t * t)
+}
+
+/// Splits cubic bezier curve into `segments` straight segments, returns `segments + 1` points.
+pub fn tessellate_cubic_bezier(
+    p0: Vector2<f32>,
+    p1: Vector2<f32>,
+    p2: Vector2<f32>,
+    p3: Vector2<f32>,
+    segments: usize,
+) -> Vec<Vector2<f32>> {
+    let segments = segments.max(1);
+    (0..=segments)
+        .map(|i| cubic_bezier(p0, p1, p2, p3, i as f32 / segments as f32))
+        .collect()
 }
 
 pub struct DrawingContext {
@@ -314,7 +639,14 @@ pub struct DrawingContext {
     triangle_buffer: Vec<TriangleDefinition>,
     command_buffer: Vec<Command>,
     opacity_stack: Vec<f32>,
+    transform_stack: Vec<Matrix3<f32>>,
+    tint_stack: Vec<Color>,
+    /// Tint of a node is multiplied by tint of its parent, see
+    /// `UserInterface::set_tint_inheritance`.
+    inherit_tint: bool,
     triangles_to_commit: usize,
+    selection_color: Color,
+    inactive_selection_color: Color,
 }
 
 fn get_line_thickness_vector(a: Vector2<f32>, b: Vector2<f32>, thickness: f32) -> Vector2<f32> {
@@ -362,9 +694,39 @@ impl DrawingContext {
             command_buffer: Vec::new(),
             triangles_to_commit: 0,
             opacity_stack: vec![1.0],
+            transform_stack: vec![Matrix3::identity()],
+            tint_stack: vec![Color::WHITE],
+            inherit_tint: false,
+            selection_color: COLOR_BRIGHT_BLUE,
+            inactive_selection_color: COLOR_INACTIVE_SELECTION,
         }
     }
 
+    /// Returns color of selected text or items, inactive color is used when owner of the
+    /// selection is not focused. See `UserInterface::set_selection_color`.
+    #[inline]
+    pub fn selection_color(&self, active: bool) -> Color {
+        if active {
+            self.selection_color
+        } else {
+            self.inactive_selection_color
+        }
+    }
+
+    pub(in crate) fn set_selection_colors(&mut self, active: Color, inactive: Color) {
+        self.selection_color = active;
+        self.inactive_selection_color = inactive;
+    }
+
+    #[inline]
+    pub fn is_tint_inherited(&self) -> bool {
+        self.inherit_tint
+    }
+
+    pub(in crate) fn set_tint_inheritance(&mut self, inherit: bool) {
+        self.inherit_tint = inherit;
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         self.vertex_buffer.clear();
@@ -372,6 +734,10 @@ impl DrawingContext {
         self.command_buffer.clear();
         self.opacity_stack.clear();
         self.opacity_stack.push(1.0);
+        self.transform_stack.clear();
+        self.transform_stack.push(Matrix3::identity());
+        self.tint_stack.clear();
+        self.tint_stack.push(Color::WHITE);
         self.triangles_to_commit = 0;
     }
 
@@ -390,6 +756,63 @@ impl DrawingContext {
         &self.command_buffer
     }
 
+    #[inline]
+    pub fn commands(&self) -> &[Command] {
+        &self.command_buffer
+    }
+
+    /// Gives mutable access to commands, so they can be changed, reordered or new commands
+    /// can be inserted between them. Triangle ranges of commands must stay valid.
+    #[inline]
+    pub fn commands_mut(&mut self) -> &mut Vec<Command> {
+        &mut self.command_buffer
+    }
+
+    /// Draws given backdrop within bounds, see `Backdrop`.
+    pub fn draw_backdrop(
+        &mut self,
+        backdrop: Backdrop,
+        bounds: &Rect<f32>,
+        clip_bounds: Rect<f32>,
+    ) {
+        let color = match backdrop {
+            Backdrop::None => return,
+            Backdrop::Blur(radius) => {
+                self.push_rect_filled(bounds, None);
+                self.commit(
+                    clip_bounds,
+                    Brush::Solid(Color::WHITE),
+                    CommandTexture::Backdrop {
+                        blur_radius: radius.max(0.0),
+                    },
+                    None,
+                );
+                BLUR_TINT
+            }
+            Backdrop::Tint(color, alpha) => color.with_alpha((alpha.clamp(0.0, 1.0) * 255.0) as u8),
+        };
+        self.push_rect_filled(bounds, None);
+        self.commit(clip_bounds, Brush::Solid(color), CommandTexture::None, None);
+    }
+
+    /// Adds custom command with no geometry, it will be placed between commands that were
+    /// committed before and after this call. Current opacity and transform are stored in
+    /// the command.
+    pub fn push_custom_command(&mut self, data: Arc<dyn Any + Send + Sync>, count: usize) {
+        let end = self.triangle_buffer.len() - self.triangles_to_commit;
+        self.command_buffer.push(Command {
+            clip_bounds: Default::default(),
+            bounds: Default::default(),
+            brush: Brush::Solid(Color::TRANSPARENT),
+            texture: CommandTexture::None,
+            triangles: end..end,
+            opacity: *self.opacity_stack.last().unwrap(),
+            clipping_geometry: None,
+            transform: *self.transform_stack.last().unwrap(),
+            custom: Some(CustomCommand { data, count }),
+        });
+    }
+
     pub fn push_opacity(&mut self, opacity: f32) {
         self.opacity_stack.push(opacity);
     }
@@ -398,6 +821,32 @@ impl DrawingContext {
         self.opacity_stack.pop().unwrap();
     }
 
+    /// Pushes tint that multiplies brushes of every command that will be committed until the
+    /// tint is popped. If tint inheritance is enabled, it is combined with current tint.
+    pub fn push_tint(&mut self, tint: Color) {
+        let tint = if self.inherit_tint {
+            tint_color(*self.tint_stack.last().unwrap(), tint)
+        } else {
+            tint
+        };
+        self.tint_stack.push(tint);
+    }
+
+    pub fn pop_tint(&mut self) {
+        self.tint_stack.pop().unwrap();
+    }
+
+    /// Pushes screen-space transform, it is combined with current one and applied to every
+    /// command that will be committed until the transform is popped.
+    pub fn push_transform(&mut self, transform: Matrix3<f32>) {
+        let current = *self.transform_stack.last().unwrap();
+        self.transform_stack.push(current * transform);
+    }
+
+    pub fn pop_transform(&mut self) {
+        self.transform_stack.pop().unwrap();
+    }
+
     pub fn triangle_points(
         &self,
         triangle: &TriangleDefinition,
@@ -452,6 +901,13 @@ impl DrawingContext {
             let bounds = self.bounds_of(triangles.clone());
 
             let opacity = *self.opacity_stack.last().unwrap();
+            let transform = *self.transform_stack.last().unwrap();
+            let tint = *self.tint_stack.last().unwrap();
+            let brush = if tint == Color::WHITE {
+                brush
+            } else {
+                brush.tinted(tint)
+            };
             self.command_buffer.push(Command {
                 clip_bounds,
                 bounds,
@@ -460,42 +916,173 @@ impl DrawingContext {
                 triangles,
                 opacity,
                 clipping_geometry,
+                transform,
+                custom: None,
             });
             self.triangles_to_commit = 0;
         }
     }
 
+    /// Scales every vertex, bounds and clipping geometry of every command by given factor.
+    /// Used to convert geometry from logical units to physical.
+    pub fn apply_scale(&mut self, scale: f32) {
+        self.apply_transform(Vector2::new(scale, scale), Vector2::default());
+    }
+
+    /// Scales every vertex, bounds and clipping geometry of every command by given per-axis
+    /// factors and then moves it by given offset. Used to convert geometry from logical units
+    /// to physical when the UI is fitted into the screen (see
+    /// `UserInterface::set_reference_resolution`).
+    pub fn apply_transform(&mut self, scale: Vector2<f32>, offset: Vector2<f32>) {
+        if (scale.x - 1.0).abs() <= f32::EPSILON
+            && (scale.y - 1.0).abs() <= f32::EPSILON
+            && offset == Vector2::default()
+        {
+            return;
+        }
+
+        let transform_point =
+            |p: Vector2<f32>| Vector2::new(p.x * scale.x + offset.x, p.y * scale.y + offset.y);
+        let transform_rect = |rect: &mut Rect<f32>| {
+            rect.position = transform_point(rect.position);
+            rect.size = rect.size.component_mul(&scale);
+        };
+        let units = Matrix3::new_translation(&offset) * Matrix3::new_nonuniform_scaling(&scale);
+        let inv_units = units.try_inverse().unwrap_or_else(Matrix3::identity);
+
+        for vertex in self.vertex_buffer.iter_mut() {
+            vertex.pos = transform_point(vertex.pos);
+        }
+
+        for command in self.command_buffer.iter_mut() {
+            transform_rect(&mut command.clip_bounds);
+            transform_rect(&mut command.bounds);
+            // Transform is defined in logical units, so it is converted to the new space.
+            if command.transform != Matrix3::identity() {
+                command.transform = units * command.transform * inv_units;
+            }
+            if let Some(geometry) = command.clipping_geometry.as_mut() {
+                for vertex in geometry.vertex_buffer.iter_mut() {
+                    vertex.pos = transform_point(vertex.pos);
+                }
+            }
+            if let CommandTexture::Backdrop { blur_radius } = &mut command.texture {
+                *blur_radius *= scale.x.max(scale.y);
+            }
+        }
+    }
+
     pub fn draw_text(
         &mut self,
         clip_bounds: Rect<f32>,
         position: Vector2<f32>,
         formatted_text: &FormattedText,
     ) {
-        let font = if let Some(font) = formatted_text.get_font() {
-            font
-        } else {
+        if formatted_text.get_font().is_none() {
             println!("Trying to draw text without font!");
             return;
-        };
-
-        for element in formatted_text.get_glyphs() {
-            let bounds = element.get_bounds();
+        }
 
-            let final_bounds = Rect::new(
-                position.x + bounds.x(),
-                position.y + bounds.y(),
-                bounds.w(),
-                bounds.h(),
+        let runs = formatted_text.runs();
+        if runs.is_empty() {
+            self.push_glyphs(
+                clip_bounds,
+                position,
+                formatted_text,
+                formatted_text.get_glyphs(),
+                formatted_text.brush(),
+                false,
             );
+        } else {
+            // Glyphs are sorted by index of symbol, so every run is a continuous sequence of
+            // glyphs and it is drawn by separate command with its own color.
+            let glyphs = formatted_text.get_glyphs();
+            let mut begin = 0;
+            for run in runs {
+                let end = begin
+                    + glyphs[begin..]
+                        .iter()
+                        .take_while(|g| g.char_index() < run.range.end)
+                        .count();
+                self.push_glyphs(
+                    clip_bounds,
+                    position,
+                    formatted_text,
+                    &glyphs[begin..end],
+                    Brush::Solid(run.color),
+                    run.bold,
+                );
+                begin = end;
+            }
+        }
+    }
 
-            self.push_rect_filled(&final_bounds, Some(element.get_tex_coords()));
+    /// Draws given glyphs with given brush, glyphs from each font (primary and fallback ones)
+    /// are drawn by a separate command with texture of the font.
+    fn push_glyphs(
+        &mut self,
+        clip_bounds: Rect<f32>,
+        position: Vector2<f32>,
+        formatted_text: &FormattedText,
+        glyphs: &[TextGlyph],
+        brush: Brush,
+        bold: bool,
+    ) {
+        let font_count = formatted_text.fallback_fonts().len() + 1;
+        for font_index in 0..font_count {
+            let font = match formatted_text.glyph_font(font_index) {
+                Some(font) => font,
+                None => continue,
+            };
+            for glyph in glyphs.iter().filter(|g| g.font_index() == font_index) {
+                self.push_glyph(position, glyph);
+                if bold {
+                    self.push_glyph(position + Vector2::new(1.0, 0.0), glyph);
+                }
+            }
+            self.commit(clip_bounds, brush.clone(), CommandTexture::Font(font), None);
         }
+    }
 
-        self.commit(
-            clip_bounds,
-            formatted_text.brush(),
-            CommandTexture::Font(font),
-            None,
-        )
+    fn push_glyph(&mut self, position: Vector2<f32>, glyph: &TextGlyph) {
+        let bounds = glyph.get_bounds();
+
+        let final_bounds = Rect::new(
+            position.x + bounds.x(),
+            position.y + bounds.y(),
+            bounds.w(),
+            bounds.h(),
+        );
+
+        self.push_rect_filled(&final_bounds, Some(glyph.get_tex_coords()));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        brush::Brush,
+        core::math::Rect,
+        draw::{Backdrop, CommandTexture, DrawingContext, BLUR_TINT},
+    };
+
+    #[test]
+    fn blurred_backdrop() {
+        let mut drawing_context = DrawingContext::new();
+        let bounds = Rect::new(10.0, 10.0, 100.0, 50.0);
+        drawing_context.draw_backdrop(Backdrop::Blur(4.0), &bounds, bounds);
+        drawing_context.apply_scale(2.0);
+
+        let commands = drawing_context.get_commands();
+        assert_eq!(commands.len(), 2);
+        // Blurred copy of region behind the panel is scaled with geometry, tint is over it.
+        assert!(matches!(
+            commands[0].texture,
+            CommandTexture::Backdrop { blur_radius } if blur_radius == 8.0
+        ));
+        assert!(matches!(
+            (&commands[1].texture, &commands[1].brush),
+            (CommandTexture::None, Brush::Solid(color)) if *color == BLUR_TINT
+        ));
     }
 }
@@ -0,0 +1,94 @@
+//! Free drawing surface. It is an escape hatch for custom visuals (node graph connections,
+//! curves, etc.) which does not require writing a full user-defined widget.
+
+use crate::{
+    core::{math::Rect, pool::Handle},
+    draw::{CommandTexture, DrawingContext},
+    message::{MessageData, UiMessage},
+    widget::{Widget, WidgetBuilder},
+    BuildContext, Control, UINode, UserInterface,
+};
+use std::{
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+/// Callback that emits custom geometry. Second argument is screen bounds of the surface.
+pub type DrawCallback = dyn Fn(&mut DrawingContext, Rect<f32>);
+
+#[derive(Clone)]
+pub struct DrawSurface<M: MessageData, C: Control<M, C>> {
+    widget: Widget<M, C>,
+    callback: Option<Rc<DrawCallback>>,
+}
+
+crate::define_widget_deref!(DrawSurface<M, C>);
+
+impl<M: MessageData, C: Control<M, C>> DrawSurface<M, C> {
+    pub fn new(widget: Widget<M, C>) -> Self {
+        Self {
+            widget,
+            callback: None,
+        }
+    }
+
+    pub fn set_draw_callback(&mut self, callback: Option<Rc<DrawCallback>>) -> &mut Self {
+        self.callback = callback;
+        self
+    }
+
+    pub fn draw_callback(&self) -> Option<Rc<DrawCallback>> {
+        self.callback.clone()
+    }
+}
+
+impl<M: MessageData, C: Control<M, C>> Control<M, C> for DrawSurface<M, C> {
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        if let Some(callback) = self.callback.as_ref() {
+            callback(drawing_context, self.widget.screen_bounds());
+            // Callback may commit its geometry by itself, everything that is left will be
+            // drawn using foreground brush of the surface.
+            drawing_context.commit(
+                self.clip_bounds(),
+                self.widget.foreground(),
+                CommandTexture::None,
+                None,
+            );
+        }
+    }
+
+    fn handle_routed_message(
+        &mut self,
+        ui: &mut UserInterface<M, C>,
+        message: &mut UiMessage<M, C>,
+    ) {
+        self.widget.handle_routed_message(ui, message);
+    }
+}
+
+pub struct DrawSurfaceBuilder<M: MessageData, C: Control<M, C>> {
+    widget_builder: WidgetBuilder<M, C>,
+    callback: Option<Rc<DrawCallback>>,
+}
+
+impl<M: MessageData, C: Control<M, C>> DrawSurfaceBuilder<M, C> {
+    pub fn new(widget_builder: WidgetBuilder<M, C>) -> Self {
+        Self {
+            widget_builder,
+            callback: None,
+        }
+    }
+
+    pub fn with_draw_callback(mut self, callback: Rc<DrawCallback>) -> Self {
+        self.callback = Some(callback);
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        let surface = DrawSurface {
+            widget: self.widget_builder.build(),
+            callback: self.callback,
+        };
+        ctx.add_node(UINode::DrawSurface(surface))
+    }
+}
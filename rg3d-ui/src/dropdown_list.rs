@@ -1,16 +1,23 @@
 //! Drop-down list. This is control which shows currently selected item and provides drop-down
 //! list to select its current item. It is build using composition with standard list view.
+//!
+//! Focused drop-down list supports type-ahead: typed characters are accumulated and the first
+//! item whose text starts with them (case-insensitively) is selected. Accumulated characters
+//! are dropped after some time of inactivity, see `DropdownList::set_type_ahead_timeout`.
 
 use crate::core::algebra::Vector2;
 use crate::grid::{Column, GridBuilder, Row};
 use crate::message::{MessageData, MessageDirection};
 use crate::utils::{make_arrow, ArrowDirection};
 use crate::{
+    accessibility::{self, AccessibleRole},
     border::BorderBuilder,
     core::pool::Handle,
     list_view::ListViewBuilder,
     message::PopupMessage,
-    message::{DropdownListMessage, ListViewMessage, UiMessage, UiMessageData, WidgetMessage},
+    message::{
+        DropdownListMessage, KeyCode, ListViewMessage, UiMessage, UiMessageData, WidgetMessage,
+    },
     node::UINode,
     popup::{Placement, PopupBuilder},
     widget::Widget,
@@ -29,11 +36,20 @@ pub struct DropdownList<M: MessageData, C: Control<M, C>> {
     selection: Option<usize>,
     close_on_selection: bool,
     main_grid: Handle<UINode<M, C>>,
+    /// Characters typed by user since last pause, in lower case.
+    type_ahead: String,
+    /// Time since last typed character.
+    type_ahead_idle: f32,
+    type_ahead_timeout: f32,
 }
 
 crate::define_widget_deref!(DropdownList<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for DropdownList<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::ComboBox
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         node_map.resolve(&mut self.popup);
         node_map.resolve(&mut self.list_view);
@@ -42,6 +58,10 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for DropdownList<M, C> {
         node_map.resolve_slice(&mut self.items);
     }
 
+    fn update(&mut self, dt: f32) {
+        self.type_ahead_idle += dt;
+    }
+
     fn handle_routed_message(
         &mut self,
         ui: &mut UserInterface<M, C>,
@@ -69,6 +89,58 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for DropdownList<M, C> {
                     ui.send_message(PopupMessage::open(self.popup, MessageDirection::ToWidget));
                 }
             }
+            UiMessageData::Widget(WidgetMessage::Text(symbol))
+                if !symbol.is_control()
+                    && !ui.keyboard_modifiers().control
+                    && !ui.keyboard_modifiers().alt
+                    && (message.destination() == self.handle()
+                        || self.widget.has_descendant(message.destination(), ui)) =>
+            {
+                if self.type_ahead_idle > self.type_ahead_timeout {
+                    self.type_ahead.clear();
+                }
+                self.type_ahead_idle = 0.0;
+                self.type_ahead.extend(symbol.to_lowercase());
+
+                let found = self.items.iter().position(|&item| {
+                    matches!(item_text(ui, item), Some(text)
+                        if text.to_lowercase().starts_with(&self.type_ahead))
+                });
+                if let Some(index) = found {
+                    if Some(index) != self.selection {
+                        ui.send_message(DropdownListMessage::selection(
+                            self.handle,
+                            MessageDirection::ToWidget,
+                            Some(index),
+                        ));
+                    }
+                }
+                message.set_handled(true);
+            }
+            UiMessageData::Widget(WidgetMessage::KeyDown(code))
+                if (*code == KeyCode::Up || *code == KeyCode::Down)
+                    && !message.handled()
+                    && (message.destination() == self.handle()
+                        || self.widget.has_descendant(message.destination(), ui)) =>
+            {
+                if !self.items.is_empty() {
+                    let last = self.items.len() - 1;
+                    let index = match (self.selection, *code) {
+                        (Some(index), KeyCode::Up) => index.saturating_sub(1),
+                        (Some(index), _) => (index + 1).min(last),
+                        (None, KeyCode::Up) => last,
+                        (None, _) => 0,
+                    };
+                    if Some(index) != self.selection {
+                        ui.send_message(DropdownListMessage::selection(
+                            self.handle,
+                            MessageDirection::ToWidget,
+                            Some(index),
+                        ));
+                    }
+                }
+                message.set_handled(true);
+            }
             UiMessageData::DropdownList(msg)
                 if message.destination() == self.handle()
                     && message.direction() == MessageDirection::ToWidget =>
@@ -83,7 +155,11 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for DropdownList<M, C> {
                         self.items = items.clone();
                     }
                     &DropdownListMessage::AddItem(item) => {
-                        ListViewMessage::add_item(self.list_view, MessageDirection::ToWidget, item);
+                        ui.send_message(ListViewMessage::add_item(
+                            self.list_view,
+                            MessageDirection::ToWidget,
+                            item,
+                        ));
                         self.items.push(item);
                     }
                     &DropdownListMessage::SelectionChanged(selection) => {
@@ -167,6 +243,30 @@ impl<M: MessageData, C: Control<M, C>> DropdownList<M, C> {
     pub fn items(&self) -> &[Handle<UINode<M, C>>] {
         &self.items
     }
+
+    /// Sets time (in seconds) of inactivity after which typed characters are dropped and
+    /// type-ahead starts from scratch.
+    pub fn set_type_ahead_timeout(&mut self, timeout: f32) -> &mut Self {
+        self.type_ahead_timeout = timeout;
+        self
+    }
+
+    pub fn type_ahead_timeout(&self) -> f32 {
+        self.type_ahead_timeout
+    }
+}
+
+/// Returns text of an item, it is either the item itself or first text in its sub-tree.
+fn item_text<M: MessageData, C: Control<M, C>>(
+    ui: &UserInterface<M, C>,
+    item: Handle<UINode<M, C>>,
+) -> Option<String> {
+    let node = ui.node(item);
+    if node.accessible_role() == AccessibleRole::Text {
+        node.accessible_name()
+    } else {
+        accessibility::find_label(ui, item)
+    }
 }
 
 pub struct DropdownListBuilder<M: MessageData, C: Control<M, C>> {
@@ -174,6 +274,7 @@ pub struct DropdownListBuilder<M: MessageData, C: Control<M, C>> {
     items: Vec<Handle<UINode<M, C>>>,
     selected: Option<usize>,
     close_on_selection: bool,
+    type_ahead_timeout: f32,
 }
 
 impl<M: MessageData, C: Control<M, C>> DropdownListBuilder<M, C> {
@@ -183,6 +284,7 @@ impl<M: MessageData, C: Control<M, C>> DropdownListBuilder<M, C> {
             items: Default::default(),
             selected: None,
             close_on_selection: false,
+            type_ahead_timeout: 1.0,
         }
     }
 
@@ -201,10 +303,19 @@ impl<M: MessageData, C: Control<M, C>> DropdownListBuilder<M, C> {
         self
     }
 
-    pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>>
+    pub fn with_type_ahead_timeout(mut self, timeout: f32) -> Self {
+        self.type_ahead_timeout = timeout;
+        self
+    }
+
+    pub fn build(mut self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>>
     where
         Self: Sized,
     {
+        if self.widget_builder.tab_stop.is_none() {
+            self.widget_builder.tab_stop = Some(true);
+        }
+
         let items_control = ListViewBuilder::new(
             WidgetBuilder::new().with_max_size(Vector2::new(std::f32::INFINITY, 200.0)),
         )
@@ -252,6 +363,9 @@ impl<M: MessageData, C: Control<M, C>> DropdownListBuilder<M, C> {
             selection: self.selected,
             close_on_selection: self.close_on_selection,
             main_grid,
+            type_ahead: Default::default(),
+            type_ahead_idle: 0.0,
+            type_ahead_timeout: self.type_ahead_timeout,
         });
 
         ctx.add_node(dropdown_list)
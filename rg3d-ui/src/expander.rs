@@ -1,57 +1,173 @@
 use crate::{
+    accessibility::AccessibleRole,
+    border::BorderBuilder,
+    brush::Brush,
     check_box::CheckBoxBuilder,
-    core::pool::Handle,
+    core::{algebra::Vector2, color::Color, pool::Handle},
     grid::{Column, GridBuilder, Row},
     message::{
         CheckBoxMessage, ExpanderMessage, MessageData, MessageDirection, UiMessage, UiMessageData,
         WidgetMessage,
     },
+    text::TextBuilder,
     utils::{make_arrow, ArrowDirection},
     widget::{Widget, WidgetBuilder},
-    BuildContext, Control, UINode, UserInterface, VerticalAlignment,
+    BuildContext, Control, NodeHandleMapping, UINode, UserInterface, VerticalAlignment,
 };
 use std::ops::{Deref, DerefMut};
 
+/// Expander shows or hides its content by click on a toggle arrow. Header of expander is
+/// placed next to the arrow and can be any node - a text, an icon with a text, a check box
+/// and so on. By default click on header toggles expander too, unless the click was handled
+/// by interactive content of header (a button or a check box).
 #[derive(Clone)]
 pub struct Expander<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     header: Handle<UINode<M, C>>,
+    header_host: Handle<UINode<M, C>>,
     content: Handle<UINode<M, C>>,
     expander: Handle<UINode<M, C>>,
     is_expanded: bool,
+    toggle_on_header: bool,
 }
 
 crate::define_widget_deref!(Expander<M, C>);
 
+impl<M: MessageData, C: Control<M, C>> Expander<M, C> {
+    pub fn header(&self) -> Handle<UINode<M, C>> {
+        self.header
+    }
+
+    pub fn content(&self) -> Handle<UINode<M, C>> {
+        self.content
+    }
+
+    pub fn is_expanded(&self) -> bool {
+        self.is_expanded
+    }
+
+    pub fn is_toggle_on_header(&self) -> bool {
+        self.toggle_on_header
+    }
+
+    fn is_header_part(&self, node: Handle<UINode<M, C>>, ui: &UserInterface<M, C>) -> bool {
+        node == self.header_host || ui.node(self.header_host).has_descendant(node, ui)
+    }
+
+    fn set_header(&mut self, header: Handle<UINode<M, C>>, ui: &mut UserInterface<M, C>) {
+        if self.header != header {
+            if self.header.is_some() {
+                ui.send_message(WidgetMessage::remove(
+                    self.header,
+                    MessageDirection::ToWidget,
+                ));
+            }
+            self.header = header;
+            if header.is_some() {
+                ui.send_message(WidgetMessage::link(
+                    header,
+                    MessageDirection::ToWidget,
+                    self.header_host,
+                ));
+            }
+        }
+    }
+}
+
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for Expander<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Group
+    }
+
+    fn accessible_value(&self) -> Option<String> {
+        Some(
+            if self.is_expanded {
+                "expanded"
+            } else {
+                "collapsed"
+            }
+            .to_owned(),
+        )
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
+        node_map.resolve(&mut self.header);
+        node_map.resolve(&mut self.header_host);
+        node_map.resolve(&mut self.content);
+        node_map.resolve(&mut self.expander);
+    }
+
+    fn remove_ref(&mut self, handle: Handle<UINode<M, C>>) {
+        if self.header == handle {
+            self.header = Handle::NONE;
+        }
+    }
+
     fn handle_routed_message(
         &mut self,
         ui: &mut UserInterface<M, C>,
         message: &mut UiMessage<M, C>,
     ) {
         match message.data() {
-            UiMessageData::Expander(msg) => {
-                if let ExpanderMessage::Expand(expand) = *msg {
-                    if message.destination() == self.handle()
-                        && message.direction() == MessageDirection::ToWidget
-                        && self.is_expanded != expand
-                    {
-                        // Switch state of expander.
-                        ui.send_message(CheckBoxMessage::checked(
-                            self.expander,
-                            MessageDirection::ToWidget,
-                            Some(expand),
-                        ));
-                        // Show or hide content.
-                        ui.send_message(WidgetMessage::visibility(
-                            self.content,
-                            MessageDirection::ToWidget,
-                            expand,
-                        ));
-                        self.is_expanded = expand;
+            UiMessageData::Expander(msg)
+                if message.destination() == self.handle()
+                    && message.direction() == MessageDirection::ToWidget =>
+            {
+                match msg {
+                    &ExpanderMessage::Expand(expand) => {
+                        if self.is_expanded != expand {
+                            // Switch state of expander.
+                            ui.send_message(CheckBoxMessage::checked(
+                                self.expander,
+                                MessageDirection::ToWidget,
+                                Some(expand),
+                            ));
+                            // Show or hide content.
+                            ui.send_message(WidgetMessage::visibility(
+                                self.content,
+                                MessageDirection::ToWidget,
+                                expand,
+                            ));
+                            self.is_expanded = expand;
+                        }
+                    }
+                    &ExpanderMessage::Header(header) => {
+                        self.set_header(header, ui);
+                    }
+                    ExpanderMessage::HeaderText(text) => {
+                        let header = TextBuilder::new(
+                            WidgetBuilder::new().with_vertical_alignment(VerticalAlignment::Center),
+                        )
+                        .with_text(text)
+                        .build(&mut ui.build_ctx());
+                        self.set_header(header, ui);
+                    }
+                    &ExpanderMessage::ToggleOnHeader(toggle_on_header) => {
+                        self.toggle_on_header = toggle_on_header;
                     }
                 }
             }
+            UiMessageData::Widget(WidgetMessage::MouseDown { .. })
+                if self.toggle_on_header
+                    && !message.handled()
+                    && self.is_header_part(message.destination(), ui) =>
+            {
+                ui.capture_mouse(self.header_host);
+                message.set_handled(true);
+            }
+            UiMessageData::Widget(WidgetMessage::MouseUp { .. })
+                if self.toggle_on_header
+                    && !message.handled()
+                    && self.is_header_part(message.destination(), ui) =>
+            {
+                ui.release_mouse_capture();
+                ui.send_message(ExpanderMessage::expand(
+                    self.handle,
+                    MessageDirection::ToWidget,
+                    !self.is_expanded,
+                ));
+                message.set_handled(true);
+            }
             UiMessageData::CheckBox(msg) => {
                 if let CheckBoxMessage::Check(value) = *msg {
                     if message.destination() == self.expander
@@ -75,8 +191,10 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Expander<M, C> {
 pub struct ExpanderBuilder<M: MessageData, C: Control<M, C>> {
     pub widget_builder: WidgetBuilder<M, C>,
     header: Handle<UINode<M, C>>,
+    header_text: Option<String>,
     content: Handle<UINode<M, C>>,
     is_expanded: bool,
+    toggle_on_header: bool,
 }
 
 impl<M: MessageData, C: Control<M, C>> ExpanderBuilder<M, C> {
@@ -84,8 +202,10 @@ impl<M: MessageData, C: Control<M, C>> ExpanderBuilder<M, C> {
         Self {
             widget_builder,
             header: Handle::NONE,
+            header_text: None,
             content: Handle::NONE,
             is_expanded: true,
+            toggle_on_header: true,
         }
     }
 
@@ -94,6 +214,18 @@ impl<M: MessageData, C: Control<M, C>> ExpanderBuilder<M, C> {
         self
     }
 
+    /// Creates text header, it is used only if there is no header set by `with_header`.
+    pub fn with_header_text<P: AsRef<str>>(mut self, text: P) -> Self {
+        self.header_text = Some(text.as_ref().to_owned());
+        self
+    }
+
+    /// Sets whether click on header toggles expander (default) or only click on arrow does.
+    pub fn with_toggle_on_header(mut self, toggle_on_header: bool) -> Self {
+        self.toggle_on_header = toggle_on_header;
+        self
+    }
+
     pub fn with_content(mut self, content: Handle<UINode<M, C>>) -> Self {
         self.content = content;
         self
@@ -110,10 +242,28 @@ impl<M: MessageData, C: Control<M, C>> ExpanderBuilder<M, C> {
         )
         .with_check_mark(make_arrow(ctx, ArrowDirection::Bottom, 8.0))
         .with_uncheck_mark(make_arrow(ctx, ArrowDirection::Right, 8.0))
-        .with_content(self.header)
         .checked(Some(self.is_expanded))
         .build(ctx);
 
+        let header = match self.header_text {
+            Some(text) if self.header.is_none() => TextBuilder::new(
+                WidgetBuilder::new().with_vertical_alignment(VerticalAlignment::Center),
+            )
+            .with_text(text)
+            .build(ctx),
+            _ => self.header,
+        };
+
+        // Transparent background makes whole header area clickable.
+        let header_host = BorderBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_background(Brush::Solid(Color::TRANSPARENT))
+                .with_child(header),
+        )
+        .with_stroke_thickness(Default::default())
+        .build(ctx);
+
         if self.content.is_some() {
             ctx[self.content]
                 .set_row(1)
@@ -127,20 +277,115 @@ impl<M: MessageData, C: Control<M, C>> ExpanderBuilder<M, C> {
                 .with_child(
                     GridBuilder::new(
                         WidgetBuilder::new()
-                            .with_child(expander)
+                            .with_child(
+                                GridBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_min_size(Vector2::new(0.0, 24.0))
+                                        .with_child(expander)
+                                        .with_child(header_host),
+                                )
+                                .add_column(Column::auto())
+                                .add_column(Column::stretch())
+                                .add_row(Row::stretch())
+                                .build(ctx),
+                            )
                             .with_child(self.content),
                     )
                     .add_column(Column::auto())
-                    .add_row(Row::strict(24.0))
+                    .add_row(Row::auto())
                     .add_row(Row::stretch())
                     .build(ctx),
                 )
                 .build(),
-            header: self.header,
+            header,
+            header_host,
             content: self.content,
             expander,
             is_expanded: self.is_expanded,
+            toggle_on_header: self.toggle_on_header,
         });
         ctx.add_node(e)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        check_box::CheckBoxBuilder,
+        core::algebra::Vector2,
+        expander::ExpanderBuilder,
+        message::{ButtonState, MouseButton, OsEvent},
+        node::StubNode,
+        widget::WidgetBuilder,
+        HorizontalAlignment, UINode, UserInterface, VerticalAlignment,
+    };
+
+    #[test]
+    fn interactive_header() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let check_box = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .with_width(20.0)
+                .with_height(20.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left),
+        )
+        .checked(Some(false))
+        .build(ctx);
+        let header =
+            BorderBuilder::new(WidgetBuilder::new().with_width(100.0).with_child(check_box))
+                .build(ctx);
+        let content = BorderBuilder::new(WidgetBuilder::new().with_height(50.0)).build(ctx);
+        let expander = ExpanderBuilder::new(
+            WidgetBuilder::new()
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .with_header(header)
+        .with_content(content)
+        .build(ctx);
+        ui.update(screen_size, 0.0);
+        ui.draw();
+
+        let click = |ui: &mut UserInterface<(), StubNode>, position| {
+            ui.process_os_event(&OsEvent::CursorMoved { position });
+            for &state in [ButtonState::Pressed, ButtonState::Released].iter() {
+                ui.process_os_event(&OsEvent::MouseInput {
+                    button: MouseButton::Left,
+                    state,
+                });
+            }
+            while ui.poll_message().is_some() {}
+            ui.update(screen_size, 0.0);
+            ui.draw();
+        };
+        let is_expanded = |ui: &UserInterface<(), StubNode>| {
+            if let UINode::Expander(expander) = ui.node(expander) {
+                expander.is_expanded()
+            } else {
+                unreachable!()
+            }
+        };
+        let is_checked = |ui: &UserInterface<(), StubNode>| {
+            if let UINode::CheckBox(check_box) = ui.node(check_box) {
+                check_box.checked
+            } else {
+                unreachable!()
+            }
+        };
+
+        // Click on check box in header switches the check box only.
+        let bounds = ui.node(check_box).screen_bounds();
+        click(&mut ui, bounds.position + bounds.size.scale(0.5));
+        assert_eq!(is_checked(&ui), Some(true));
+        assert!(is_expanded(&ui));
+
+        // Click on the rest of header toggles expander.
+        let bounds = ui.node(header).screen_bounds();
+        click(&mut ui, bounds.right_top_corner() + Vector2::new(-5.0, 5.0));
+        assert_eq!(is_checked(&ui), Some(true));
+        assert!(!is_expanded(&ui));
+    }
+}
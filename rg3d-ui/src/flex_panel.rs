@@ -0,0 +1,360 @@
+//! Panel that arranges children in a line like CSS flexbox: children start with their basis
+//! size, then free space of the line is distributed between them by grow factors, or overflow
+//! is removed by shrink factors. Grow, shrink and basis are attached properties of children,
+//! see `Widget::set_flex_grow`, `Widget::set_flex_shrink` and `Widget::set_flex_basis`.
+
+use crate::{
+    core::{algebra::Vector2, math::Rect, pool::Handle, scope_profile},
+    message::{MessageData, UiMessage},
+    widget::{Widget, WidgetBuilder},
+    BuildContext, Control, Orientation, UINode, UserInterface,
+};
+use std::ops::{Deref, DerefMut};
+
+/// Defines how space that is left after growing or shrinking of children is distributed
+/// along orientation of a flex panel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JustifyContent {
+    /// Children are packed to the start of the line.
+    Start,
+    /// Children are packed to the end of the line.
+    End,
+    /// Children are packed to the center of the line.
+    Center,
+    /// First child is at the start, last child is at the end, the rest of space is divided
+    /// evenly between children.
+    SpaceBetween,
+    /// Every child has the same space on both sides, so space at the edges is half of space
+    /// between children.
+    SpaceAround,
+    /// Space at the edges and between children is the same.
+    SpaceEvenly,
+}
+
+/// Defines how children are placed across orientation of a flex panel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AlignItems {
+    Start,
+    End,
+    Center,
+    /// Children are stretched to fill the panel.
+    Stretch,
+}
+
+#[derive(Clone)]
+pub struct FlexPanel<M: MessageData, C: Control<M, C>> {
+    widget: Widget<M, C>,
+    orientation: Orientation,
+    justify_content: JustifyContent,
+    align_items: AlignItems,
+}
+
+crate::define_widget_deref!(FlexPanel<M, C>);
+
+impl<M: MessageData, C: Control<M, C>> FlexPanel<M, C> {
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        if self.orientation != orientation {
+            self.orientation = orientation;
+            self.widget.invalidate_layout();
+        }
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn set_justify_content(&mut self, justify_content: JustifyContent) {
+        if self.justify_content != justify_content {
+            self.justify_content = justify_content;
+            self.widget.invalidate_layout();
+        }
+    }
+
+    pub fn justify_content(&self) -> JustifyContent {
+        self.justify_content
+    }
+
+    pub fn set_align_items(&mut self, align_items: AlignItems) {
+        if self.align_items != align_items {
+            self.align_items = align_items;
+            self.widget.invalidate_layout();
+        }
+    }
+
+    pub fn align_items(&self) -> AlignItems {
+        self.align_items
+    }
+
+    fn main(&self, v: Vector2<f32>) -> f32 {
+        match self.orientation {
+            Orientation::Horizontal => v.x,
+            Orientation::Vertical => v.y,
+        }
+    }
+
+    fn cross(&self, v: Vector2<f32>) -> f32 {
+        match self.orientation {
+            Orientation::Horizontal => v.y,
+            Orientation::Vertical => v.x,
+        }
+    }
+
+    fn make_vector(&self, main: f32, cross: f32) -> Vector2<f32> {
+        match self.orientation {
+            Orientation::Horizontal => Vector2::new(main, cross),
+            Orientation::Vertical => Vector2::new(cross, main),
+        }
+    }
+
+    fn basis(&self, child: &UINode<M, C>) -> f32 {
+        child
+            .flex_basis()
+            .unwrap_or_else(|| self.main(child.desired_size()))
+    }
+}
+
+impl<M: MessageData, C: Control<M, C>> Control<M, C> for FlexPanel<M, C> {
+    fn measure_override(
+        &self,
+        ui: &UserInterface<M, C>,
+        available_size: Vector2<f32>,
+    ) -> Vector2<f32> {
+        scope_profile!();
+
+        let available_cross = self.cross(available_size);
+        let mut main = 0.0f32;
+        let mut cross = 0.0f32;
+
+        for child_handle in self.widget.children() {
+            let child = ui.node(*child_handle);
+            let constraint =
+                self.make_vector(child.flex_basis().unwrap_or(f32::INFINITY), available_cross);
+            child.measure(ui, constraint);
+
+            if !child.visibility() {
+                continue;
+            }
+            main += self.basis(child);
+            cross = cross.max(self.cross(child.desired_size()));
+        }
+
+        self.make_vector(main, cross)
+    }
+
+    fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
+        scope_profile!();
+
+        let final_main = self.main(final_size);
+        let final_cross = self.cross(final_size);
+
+        let children = self
+            .widget
+            .children()
+            .iter()
+            .cloned()
+            .filter(|&handle| ui.node(handle).visibility())
+            .collect::<Vec<_>>();
+        let mut sizes = children
+            .iter()
+            .map(|&handle| self.basis(ui.node(handle)))
+            .collect::<Vec<_>>();
+
+        let free = final_main - sizes.iter().sum::<f32>();
+        if free > 0.0 {
+            let total_grow = children
+                .iter()
+                .map(|&handle| ui.node(handle).flex_grow())
+                .sum::<f32>();
+            if total_grow > 0.0 {
+                for (size, &handle) in sizes.iter_mut().zip(children.iter()) {
+                    *size += free * ui.node(handle).flex_grow() / total_grow;
+                }
+            }
+        } else if free < 0.0 {
+            // Like in CSS, shrink factors are weighted by basis, so large children shrink more.
+            let total_shrink = children
+                .iter()
+                .zip(sizes.iter())
+                .map(|(&handle, size)| ui.node(handle).flex_shrink() * size)
+                .sum::<f32>();
+            if total_shrink > 0.0 {
+                for (size, &handle) in sizes.iter_mut().zip(children.iter()) {
+                    let weight = ui.node(handle).flex_shrink() * *size;
+                    *size = (*size + free * weight / total_shrink).max(0.0);
+                }
+            }
+        }
+
+        let free = (final_main - sizes.iter().sum::<f32>()).max(0.0);
+        let count = children.len() as f32;
+        let (mut offset, gap) = match self.justify_content {
+            JustifyContent::Start => (0.0, 0.0),
+            JustifyContent::End => (free, 0.0),
+            JustifyContent::Center => (free * 0.5, 0.0),
+            JustifyContent::SpaceBetween if count > 1.0 => (0.0, free / (count - 1.0)),
+            JustifyContent::SpaceBetween => (0.0, 0.0),
+            JustifyContent::SpaceAround if count > 0.0 => (free / count * 0.5, free / count),
+            JustifyContent::SpaceAround => (0.0, 0.0),
+            JustifyContent::SpaceEvenly => (free / (count + 1.0), free / (count + 1.0)),
+        };
+
+        for (&handle, &size) in children.iter().zip(sizes.iter()) {
+            let child = ui.node(handle);
+            let child_cross = self.cross(child.desired_size()).min(final_cross);
+            let (cross_position, cross_size) = match self.align_items {
+                AlignItems::Start => (0.0, child_cross),
+                AlignItems::End => (final_cross - child_cross, child_cross),
+                AlignItems::Center => ((final_cross - child_cross) * 0.5, child_cross),
+                AlignItems::Stretch => (0.0, final_cross),
+            };
+            let position = self.make_vector(offset, cross_position);
+            let size_vector = self.make_vector(size, cross_size);
+            child.arrange(
+                ui,
+                &Rect::new(position.x, position.y, size_vector.x, size_vector.y),
+            );
+            offset += size + gap;
+        }
+
+        final_size
+    }
+
+    fn handle_routed_message(
+        &mut self,
+        ui: &mut UserInterface<M, C>,
+        message: &mut UiMessage<M, C>,
+    ) {
+        self.widget.handle_routed_message(ui, message);
+    }
+}
+
+pub struct FlexPanelBuilder<M: MessageData, C: Control<M, C>> {
+    widget_builder: WidgetBuilder<M, C>,
+    orientation: Orientation,
+    justify_content: JustifyContent,
+    align_items: AlignItems,
+}
+
+impl<M: MessageData, C: Control<M, C>> FlexPanelBuilder<M, C> {
+    pub fn new(widget_builder: WidgetBuilder<M, C>) -> Self {
+        Self {
+            widget_builder,
+            orientation: Orientation::Horizontal,
+            justify_content: JustifyContent::Start,
+            align_items: AlignItems::Stretch,
+        }
+    }
+
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn with_justify_content(mut self, justify_content: JustifyContent) -> Self {
+        self.justify_content = justify_content;
+        self
+    }
+
+    pub fn with_align_items(mut self, align_items: AlignItems) -> Self {
+        self.align_items = align_items;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        let flex_panel = FlexPanel {
+            widget: self.widget_builder.build(),
+            orientation: self.orientation,
+            justify_content: self.justify_content,
+            align_items: self.align_items,
+        };
+
+        ctx.add_node(UINode::FlexPanel(flex_panel))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        core::algebra::Vector2,
+        flex_panel::{AlignItems, FlexPanelBuilder, JustifyContent},
+        node::StubNode,
+        widget::WidgetBuilder,
+        UserInterface, VerticalAlignment,
+    };
+
+    #[test]
+    fn grow_shrink_justify() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let item = |ctx: &mut _, builder: WidgetBuilder<(), StubNode>| {
+            BorderBuilder::new(builder.with_height(10.0)).build(ctx)
+        };
+
+        // Free space of 60 is divided 1:2, fixed child keeps its size.
+        let a = item(
+            ctx,
+            WidgetBuilder::new()
+                .with_flex_basis(100.0)
+                .with_flex_grow(1.0),
+        );
+        let b = item(
+            ctx,
+            WidgetBuilder::new()
+                .with_flex_basis(100.0)
+                .with_flex_grow(2.0),
+        );
+        let c = item(ctx, WidgetBuilder::new().with_flex_basis(40.0));
+        FlexPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_child(a)
+                .with_child(b)
+                .with_child(c),
+        )
+        .build(ctx);
+
+        // Overflow of 50 is removed by shrink weighted by basis.
+        let d = item(ctx, WidgetBuilder::new().with_flex_basis(100.0));
+        let e = item(ctx, WidgetBuilder::new().with_flex_basis(50.0));
+        FlexPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_desired_position(Vector2::new(0.0, 100.0))
+                .with_child(d)
+                .with_child(e),
+        )
+        .build(ctx);
+
+        // Children without grow are centered and aligned to the end across the panel.
+        let f = item(ctx, WidgetBuilder::new().with_flex_basis(50.0));
+        let g = item(ctx, WidgetBuilder::new().with_flex_basis(50.0));
+        FlexPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(30.0)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_desired_position(Vector2::new(0.0, 200.0))
+                .with_child(f)
+                .with_child(g),
+        )
+        .with_justify_content(JustifyContent::Center)
+        .with_align_items(AlignItems::End)
+        .build(ctx);
+
+        ui.update(screen_size, 0.0);
+
+        let width = |ui: &UserInterface<(), StubNode>, handle| ui.node(handle).actual_size().x;
+        assert_eq!(width(&ui, a), 120.0);
+        assert_eq!(width(&ui, b), 140.0);
+        assert_eq!(width(&ui, c), 40.0);
+        assert_eq!(ui.node(c).screen_position().x, 260.0);
+
+        assert!((width(&ui, d) - 200.0 / 3.0).abs() < 0.001);
+        assert!((width(&ui, e) - 100.0 / 3.0).abs() < 0.001);
+
+        assert_eq!(ui.node(f).screen_position(), Vector2::new(100.0, 220.0));
+        assert_eq!(ui.node(g).screen_position(), Vector2::new(150.0, 220.0));
+    }
+}
@@ -1,18 +1,32 @@
 use crate::{
     brush::Brush,
     core::{algebra::Vector2, color::Color, math::Rect},
-    ttf::SharedFont,
+    ttf::{Font, FontGlyph, SharedFont},
     HorizontalAlignment, VerticalAlignment,
 };
-use std::ops::Range;
+use std::{cell::RefCell, collections::HashMap, ops::Range, sync::MutexGuard};
 
 #[derive(Debug, Clone)]
 pub struct TextGlyph {
     bounds: Rect<f32>,
     tex_coords: [Vector2<f32>; 4],
+    /// Index of symbol in text array.
+    char_index: usize,
+    /// Index of font of the glyph, see `FormattedText::glyph_font`.
+    font: usize,
 }
 
 impl TextGlyph {
+    pub fn char_index(&self) -> usize {
+        self.char_index
+    }
+
+    /// Returns index of font from which the glyph was taken: zero for primary font, `n` for
+    /// `n`-th font from fallback list (counting from one).
+    pub fn font_index(&self) -> usize {
+        self.font
+    }
+
     pub fn get_bounds(&self) -> Rect<f32> {
         self.bounds
     }
@@ -59,6 +73,130 @@ impl TextLine {
     }
 }
 
+/// Defines how text is split on lines when a line does not fit into width of constraint.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum WrapMode {
+    /// Text is not wrapped, only explicit line breaks start new lines.
+    #[default]
+    NoWrap,
+    /// Line is broken at a symbol that does not fit.
+    Letter,
+    /// Line is broken after last whitespace before a symbol that does not fit, so words are
+    /// kept whole. A word that is longer than whole line is broken as in `Letter` mode.
+    Word,
+}
+
+/// Defines how a line of text that does not fit into its constraint is shortened. Trimming
+/// is done only if wrapping is disabled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum TextTrimming {
+    /// Line is not trimmed, it is just clipped by bounds of its widget.
+    #[default]
+    None,
+    /// End of line is replaced with ellipsis.
+    CharacterEllipsis,
+    /// End of line is replaced with ellipsis, line is cut at word boundary if possible.
+    WordEllipsis,
+    /// Middle of line is replaced with ellipsis, so both start and end of the line stay
+    /// visible. Useful for file paths.
+    MiddleEllipsis,
+}
+
+/// Unicode horizontal ellipsis, it is replaced with three dots if font has no such glyph.
+const ELLIPSIS: u32 = 0x2026;
+
+/// Returns symbols (code and index in text) that are left of given line after trimming
+/// together with their width. If even ellipsis alone does not fit, only ellipsis is left.
+fn trim_line(
+    line: &[u32],
+    begin: usize,
+    mode: TextTrimming,
+    max_width: f32,
+    ellipsis: &[u32],
+    advance_of: &dyn Fn(u32) -> f32,
+) -> (Vec<(u32, usize)>, f32) {
+    let len = line.len();
+    let ellipsis_width = ellipsis.iter().map(|&c| advance_of(c)).sum::<f32>();
+    let available = max_width - ellipsis_width;
+
+    // Amount of leading and trailing symbols that are kept.
+    let mut head = 0;
+    let mut tail = 0;
+    let mut width = 0.0;
+    if available > 0.0 {
+        if mode == TextTrimming::MiddleEllipsis {
+            // Half of space for start of the line, everything that left is for its end.
+            while head < len && width + advance_of(line[head]) <= available * 0.5 {
+                width += advance_of(line[head]);
+                head += 1;
+            }
+            while tail < len - head && width + advance_of(line[len - 1 - tail]) <= available {
+                width += advance_of(line[len - 1 - tail]);
+                tail += 1;
+            }
+            while head < len - tail && width + advance_of(line[head]) <= available {
+                width += advance_of(line[head]);
+                head += 1;
+            }
+        } else {
+            while head < len && width + advance_of(line[head]) <= available {
+                width += advance_of(line[head]);
+                head += 1;
+            }
+            if mode == TextTrimming::WordEllipsis {
+                if let Some(p) = line[..head].iter().rposition(|&c| is_whitespace(c)) {
+                    if p > 0 {
+                        head = p;
+                    }
+                }
+                while head > 0 && is_whitespace(line[head - 1]) {
+                    head -= 1;
+                }
+                width = line[..head].iter().map(|&c| advance_of(c)).sum();
+            }
+        }
+    }
+
+    let mut symbols = Vec::with_capacity(head + ellipsis.len() + tail);
+    symbols.extend((0..head).map(|i| (line[i], begin + i)));
+    symbols.extend(ellipsis.iter().map(|&c| (c, begin + head)));
+    symbols.extend((len - tail..len).map(|i| (line[i], begin + i)));
+    (symbols, width + ellipsis_width)
+}
+
+/// Piece of text with its own style, see `FormattedText::set_runs`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub color: Color,
+    /// There are no bold fonts, so bold runs are drawn with synthetic emboldening, every
+    /// glyph is drawn twice with one unit offset.
+    pub bold: bool,
+}
+
+impl TextRun {
+    pub fn new<P: AsRef<str>>(text: P, color: Color) -> Self {
+        Self {
+            text: text.as_ref().to_owned(),
+            color,
+            bold: false,
+        }
+    }
+
+    pub fn with_bold(mut self, bold: bool) -> Self {
+        self.bold = bold;
+        self
+    }
+}
+
+/// Style of a range of symbols, made from a `TextRun`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunStyle {
+    pub range: Range<usize>,
+    pub color: Color,
+    pub bold: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct FormattedText {
     font: Option<SharedFont>,
@@ -74,7 +212,68 @@ pub struct FormattedText {
     horizontal_alignment: HorizontalAlignment,
     brush: Brush,
     constraint: Vector2<f32>,
-    wrap: bool,
+    wrap: WrapMode,
+    trimming: TextTrimming,
+    /// Styles of runs, empty if text was set as plain string.
+    runs: Vec<RunStyle>,
+    /// Fonts that are used for symbols that are missing in primary font, in order of priority.
+    fallback_fonts: Vec<SharedFont>,
+    /// Cached index of font (see `TextGlyph::font_index`) that has a glyph for a symbol,
+    /// `None` if no font has it.
+    glyph_sources: HashMap<u32, Option<usize>>,
+    /// Scale at which fonts are rasterized, see `Font::set_raster_scale`.
+    raster_scale: f32,
+}
+
+fn is_whitespace(code: u32) -> bool {
+    matches!(std::char::from_u32(code), Some(c) if c.is_whitespace())
+}
+
+/// Locks fallback fonts. Fonts that are the same as the primary font or that are repeated are
+/// not locked (and not used), because they are already locked.
+fn lock_fallbacks<'a>(
+    primary: &SharedFont,
+    fallbacks: &'a [SharedFont],
+) -> Vec<Option<MutexGuard<'a, Font>>> {
+    fallbacks
+        .iter()
+        .enumerate()
+        .map(|(i, font)| {
+            if font == primary || fallbacks[..i].contains(font) {
+                None
+            } else {
+                Some(font.0.lock().unwrap())
+            }
+        })
+        .collect()
+}
+
+/// Finds first font that has a glyph for given symbol, see `TextGlyph::font_index`.
+fn find_glyph_source(
+    font: &Font,
+    fallbacks: &[Option<MutexGuard<Font>>],
+    code: u32,
+) -> Option<usize> {
+    if font.glyph(code).is_some() {
+        Some(0)
+    } else {
+        fallbacks
+            .iter()
+            .position(|fallback| matches!(fallback, Some(f) if f.glyph(code).is_some()))
+            .map(|index| index + 1)
+    }
+}
+
+fn source_glyph<'a>(
+    font: &'a Font,
+    fallbacks: &'a [Option<MutexGuard<Font>>],
+    source: Option<usize>,
+    code: u32,
+) -> Option<&'a FontGlyph> {
+    match source? {
+        0 => font.glyph(code),
+        index => fallbacks.get(index - 1)?.as_ref()?.glyph(code),
+    }
 }
 
 impl FormattedText {
@@ -88,13 +287,58 @@ impl FormattedText {
 
     pub fn set_font(&mut self, font: SharedFont) -> &mut Self {
         self.font = Some(font);
+        self.glyph_sources.clear();
+        self
+    }
+
+    /// Sets fonts that are used (in given order) for symbols that are missing in the primary
+    /// font. Symbols that are missing in every font are drawn as boxes.
+    pub fn set_fallback_fonts(&mut self, fonts: &[SharedFont]) -> &mut Self {
+        if self.fallback_fonts != fonts {
+            self.fallback_fonts = fonts.to_vec();
+            self.glyph_sources.clear();
+        }
         self
     }
 
+    /// Sets scale at which fonts of the text will be rasterized on next build, usually it is
+    /// scale factor of the UI.
+    pub fn set_raster_scale(&mut self, scale: f32) -> &mut Self {
+        self.raster_scale = scale;
+        self
+    }
+
+    pub fn raster_scale(&self) -> f32 {
+        self.raster_scale
+    }
+
+    pub fn fallback_fonts(&self) -> &[SharedFont] {
+        &self.fallback_fonts
+    }
+
+    /// Returns font by its index from `TextGlyph::font_index`.
+    pub fn glyph_font(&self, index: usize) -> Option<SharedFont> {
+        if index == 0 {
+            self.font.clone()
+        } else {
+            self.fallback_fonts.get(index - 1).cloned()
+        }
+    }
+
     pub fn get_lines(&self) -> &[TextLine] {
         &self.lines
     }
 
+    /// Returns distance from top of text to baseline of its first line, it is valid after
+    /// `build`.
+    pub fn baseline(&self) -> f32 {
+        let ascender = self
+            .font
+            .as_ref()
+            .map_or(0.0, |font| font.0.lock().unwrap().ascender().floor());
+        self.lines.first().map_or(0.0, |line| line.y_offset) + ascender
+    }
+
     pub fn set_vertical_alignment(&mut self, vertical_alignment: VerticalAlignment) -> &mut Self {
         self.vertical_alignment = vertical_alignment;
         self
@@ -142,34 +386,125 @@ impl FormattedText {
     }
 
     pub fn get_range_width<T: IntoIterator<Item = usize>>(&self, range: T) -> f32 {
+        let text = &self.text;
+        self.symbols_width(range.into_iter().map(|index| text[index]))
+    }
+
+    /// Returns advance of given symbol, font of the symbol is taken from the fallback chain
+    /// the same way as on `build`, so it can be used for symbols that are not in the text.
+    pub fn symbol_advance(&self, code: u32) -> f32 {
+        self.symbols_width(std::iter::once(code))
+    }
+
+    fn symbols_width<T: Iterator<Item = u32>>(&self, symbols: T) -> f32 {
         let mut width = 0.0;
-        if let Some(ref font) = self.font {
-            let font = font.0.lock().unwrap();
-            for index in range {
-                width += font.glyph_advance(self.text[index]);
+        if let Some(ref primary) = self.font {
+            let font = primary.0.lock().unwrap();
+            let fallbacks = lock_fallbacks(primary, &self.fallback_fonts);
+            for code in symbols {
+                let source = match self.glyph_sources.get(&code) {
+                    Some(source) => *source,
+                    None => find_glyph_source(&font, &fallbacks, code),
+                };
+                width += source_glyph(&font, &fallbacks, source, code)
+                    .map_or(font.height(), |glyph| glyph.advance);
             }
         }
         width
     }
 
+    /// Returns index of caret position (in `0..=text.len()`) that is nearest to given point in
+    /// local coordinates of the text, it is valid after `build`. Line is picked by vertical
+    /// position (points above or below the text go to first or last line), then the nearest
+    /// boundary between glyphs is found, so points past the end of a line give the end of it.
+    pub fn hit_test_char(&self, point: Vector2<f32>) -> usize {
+        let line = match self
+            .lines
+            .iter()
+            .find(|line| point.y < line.y_offset + line.height)
+            .or_else(|| self.lines.last())
+        {
+            Some(line) => line,
+            None => return 0,
+        };
+
+        let mut x = line.x_offset;
+        for index in line.begin..line.end {
+            let advance = self.get_range_width(index..index + 1);
+            if point.x < x + advance * 0.5 {
+                return index;
+            }
+            x += advance;
+        }
+        line.end
+    }
+
     pub fn set_text<P: AsRef<str>>(&mut self, text: P) -> &mut Self {
         // Convert text to UTF32.
         self.text.clear();
         for code in text.as_ref().chars().map(|c| c as u32) {
             self.text.push(code);
         }
+        self.runs.clear();
         self
     }
 
+    /// Sets text as a sequence of runs with individual styles. Text of runs is concatenated,
+    /// so lines are wrapped across runs the same way as plain text. Any further edit of
+    /// text (`set_text`, `insert_char`, etc.) drops the styles.
+    pub fn set_runs(&mut self, runs: &[TextRun]) -> &mut Self {
+        self.text.clear();
+        self.runs.clear();
+        for run in runs {
+            let begin = self.text.len();
+            self.text.extend(run.text.chars().map(|c| c as u32));
+            if self.text.len() > begin {
+                self.runs.push(RunStyle {
+                    range: begin..self.text.len(),
+                    color: run.color,
+                    bold: run.bold,
+                });
+            }
+        }
+        self
+    }
+
+    pub fn runs(&self) -> &[RunStyle] {
+        &self.runs
+    }
+
+    /// Enables or disables wrapping by letters, see `set_wrap_mode` for other modes.
     pub fn set_wrap(&mut self, wrap: bool) -> &mut Self {
-        self.wrap = wrap;
+        self.wrap = if wrap {
+            WrapMode::Letter
+        } else {
+            WrapMode::NoWrap
+        };
         self
     }
 
     pub fn is_wrap(&self) -> bool {
+        self.wrap != WrapMode::NoWrap
+    }
+
+    pub fn set_wrap_mode(&mut self, mode: WrapMode) -> &mut Self {
+        self.wrap = mode;
+        self
+    }
+
+    pub fn wrap_mode(&self) -> WrapMode {
         self.wrap
     }
 
+    pub fn set_trimming(&mut self, trimming: TextTrimming) -> &mut Self {
+        self.trimming = trimming;
+        self
+    }
+
+    pub fn trimming(&self) -> TextTrimming {
+        self.trimming
+    }
+
     pub fn insert_char(&mut self, c: char, index: usize) -> &mut Self {
         let c = c as u32;
         if index == self.text.len() {
@@ -177,60 +512,80 @@ impl FormattedText {
         } else {
             self.text.insert(index, c);
         }
+        self.runs.clear();
         self
     }
 
     pub fn remove_range(&mut self, range: Range<usize>) -> &mut Self {
         self.text.drain(range);
+        self.runs.clear();
         self
     }
 
     pub fn remove_at(&mut self, index: usize) -> &mut Self {
         self.text.remove(index);
+        self.runs.clear();
         self
     }
 
     pub fn build(&mut self) -> Vector2<f32> {
-        let font = if let Some(font) = &self.font {
-            font.0.lock().unwrap()
+        let (mut font, mut fallbacks) = if let Some(font) = &self.font {
+            (
+                font.0.lock().unwrap(),
+                lock_fallbacks(font, &self.fallback_fonts),
+            )
         } else {
             return Vector2::default();
         };
 
+        font.set_raster_scale(self.raster_scale);
+        for fallback in fallbacks.iter_mut().flatten() {
+            fallback.set_raster_scale(self.raster_scale);
+        }
+
+        let glyph_sources = RefCell::new(std::mem::take(&mut self.glyph_sources));
+        let source_of = |code: u32| {
+            *glyph_sources
+                .borrow_mut()
+                .entry(code)
+                .or_insert_with(|| find_glyph_source(&font, &fallbacks, code))
+        };
+        let advance_of = |code: u32| match source_glyph(&font, &fallbacks, source_of(code), code) {
+            Some(glyph) => glyph.advance,
+            None => font.height(),
+        };
+
         // Split on lines.
-        let mut total_height = 0.0;
-        let mut current_line = TextLine::new();
-        self.lines.clear();
-        for (i, code) in self.text.iter().enumerate() {
-            let advance = match font.glyph(*code) {
-                Some(glyph) => glyph.advance,
-                None => font.height(),
-            };
-            let is_new_line = *code == u32::from(b'\n') || *code == u32::from(b'\r');
-            let new_width = current_line.width + advance;
-            if self.wrap && new_width > self.constraint.x || is_new_line {
-                self.lines.push(current_line);
-                current_line.begin = if is_new_line { i + 1 } else { i };
-                current_line.end = current_line.begin + 1;
-                current_line.width = advance;
-                total_height += font.ascender();
+        self.lines = split_lines(&self.text, self.constraint.x, self.wrap, &advance_of);
+        let total_height = self.lines.len() as f32 * font.ascender();
+
+        // Shorten lines that do not fit into constraint.
+        let mut trimmed_lines = Vec::new();
+        if self.trimming != TextTrimming::None
+            && self.wrap == WrapMode::NoWrap
+            && self.constraint.x.is_finite()
+        {
+            let ellipsis = if source_of(ELLIPSIS).is_some() {
+                vec![ELLIPSIS]
             } else {
-                current_line.width = new_width;
-                current_line.end += 1;
-            }
-        }
-        // Commit rest of text.
-        if current_line.begin != current_line.end {
-            for code in self.text.iter().skip(current_line.end) {
-                let advance = match font.glyph(*code) {
-                    Some(glyph) => glyph.advance,
-                    None => font.height(),
-                };
-                current_line.width += advance;
+                vec![u32::from(b'.'); 3]
+            };
+            for line in self.lines.iter_mut() {
+                trimmed_lines.push(if line.width > self.constraint.x {
+                    let (symbols, width) = trim_line(
+                        &self.text[line.begin..line.end],
+                        line.begin,
+                        self.trimming,
+                        self.constraint.x,
+                        &ellipsis,
+                        &advance_of,
+                    );
+                    line.width = width;
+                    Some(symbols)
+                } else {
+                    None
+                });
             }
-            current_line.end = self.text.len();
-            self.lines.push(current_line);
-            total_height += font.ascender();
         }
 
         // Align lines according to desired alignment.
@@ -259,7 +614,7 @@ impl FormattedText {
         self.glyphs.clear();
 
         let cursor_y_start = match self.vertical_alignment {
-            VerticalAlignment::Top => 0.0,
+            VerticalAlignment::Top | VerticalAlignment::Baseline => 0.0,
             VerticalAlignment::Center => {
                 if self.constraint.y.is_infinite() {
                     0.0
@@ -284,26 +639,35 @@ impl FormattedText {
         };
 
         let mut cursor = Vector2::new(cursor_x_start, cursor_y_start);
-        for line in self.lines.iter_mut() {
+        let mut symbols = Vec::new();
+        for (line_index, line) in self.lines.iter_mut().enumerate() {
             cursor.x = line.x_offset;
 
-            for code_index in line.begin..line.end {
-                let code = self.text[code_index];
+            symbols.clear();
+            match trimmed_lines.get(line_index) {
+                Some(Some(trimmed)) => symbols.extend_from_slice(trimmed),
+                _ => {
+                    let text = &self.text;
+                    symbols.extend((line.begin..line.end).map(|i| (text[i], i)))
+                }
+            }
 
-                match font.glyph(code) {
+            for &(code, code_index) in symbols.iter() {
+                let source = source_of(code);
+                match source_glyph(&font, &fallbacks, source, code) {
                     Some(glyph) => {
                         // Insert glyph
                         let rect = Rect::new(
                             cursor.x + glyph.left.floor(),
-                            cursor.y + font.ascender().floor()
-                                - glyph.top.floor()
-                                - glyph.bitmap_height as f32,
-                            glyph.bitmap_width as f32,
-                            glyph.bitmap_height as f32,
+                            cursor.y + font.ascender().floor() - glyph.top.floor() - glyph.height,
+                            glyph.width,
+                            glyph.height,
                         );
                         let text_glyph = TextGlyph {
                             bounds: rect,
                             tex_coords: glyph.tex_coords,
+                            char_index: code_index,
+                            font: source.unwrap_or_default(),
                         };
                         self.glyphs.push(text_glyph);
 
@@ -320,6 +684,8 @@ impl FormattedText {
                         self.glyphs.push(TextGlyph {
                             bounds: rect,
                             tex_coords: [Vector2::default(); 4],
+                            char_index: code_index,
+                            font: 0,
                         });
                         cursor.x += rect.w();
                     }
@@ -335,10 +701,70 @@ impl FormattedText {
         for line in self.lines.iter() {
             full_size.x = line.width.max(full_size.x);
         }
+
+        self.glyph_sources = glyph_sources.into_inner();
+
         full_size
     }
 }
 
+/// Splits text on lines, lines are broken by line break symbols and, if wrapping is enabled,
+/// where width of a line exceeds given width.
+fn split_lines(
+    text: &[u32],
+    width: f32,
+    wrap: WrapMode,
+    advance_of: &dyn Fn(u32) -> f32,
+) -> Vec<TextLine> {
+    let mut lines = Vec::new();
+    let mut current_line = TextLine::new();
+    for (i, code) in text.iter().enumerate() {
+        let advance = advance_of(*code);
+        let is_new_line = *code == u32::from(b'\n') || *code == u32::from(b'\r');
+        let new_width = current_line.width + advance;
+        let overflow = wrap != WrapMode::NoWrap && new_width > width;
+        // Try to break line after last whitespace, so words won't be split. Word that is
+        // longer than whole line is split anyway.
+        let word_break =
+            if overflow && wrap == WrapMode::Word && !is_new_line && !is_whitespace(*code) {
+                text[current_line.begin..i]
+                    .iter()
+                    .rposition(|&c| is_whitespace(c))
+                    .map(|p| current_line.begin + p + 1)
+                    .filter(|&p| p < i)
+            } else {
+                None
+            };
+        if let Some(break_index) = word_break {
+            let width_of =
+                |range: Range<usize>| text[range].iter().map(|&c| advance_of(c)).sum::<f32>();
+            current_line.end = break_index;
+            current_line.width = width_of(current_line.begin..break_index);
+            lines.push(current_line);
+            current_line.begin = break_index;
+            current_line.end = i + 1;
+            current_line.width = width_of(break_index..i) + advance;
+        } else if overflow || is_new_line {
+            lines.push(current_line);
+            current_line.begin = if is_new_line { i + 1 } else { i };
+            current_line.end = current_line.begin + 1;
+            current_line.width = advance;
+        } else {
+            current_line.width = new_width;
+            current_line.end += 1;
+        }
+    }
+    // Commit rest of text.
+    if current_line.begin != current_line.end {
+        for code in text.iter().skip(current_line.end) {
+            current_line.width += advance_of(*code);
+        }
+        current_line.end = text.len();
+        lines.push(current_line);
+    }
+    lines
+}
+
 pub struct FormattedTextBuilder {
     font: Option<SharedFont>,
     brush: Brush,
@@ -346,7 +772,7 @@ pub struct FormattedTextBuilder {
     text: String,
     vertical_alignment: VerticalAlignment,
     horizontal_alignment: HorizontalAlignment,
-    wrap: bool,
+    wrap: WrapMode,
 }
 
 impl Default for FormattedTextBuilder {
@@ -365,7 +791,7 @@ impl FormattedTextBuilder {
             vertical_alignment: VerticalAlignment::Top,
             brush: Brush::Solid(Color::WHITE),
             constraint: Vector2::new(128.0, 128.0),
-            wrap: false,
+            wrap: WrapMode::NoWrap,
         }
     }
 
@@ -379,8 +805,18 @@ impl FormattedTextBuilder {
         self
     }
 
+    /// Enables or disables wrapping by letters, see `with_wrap_mode` for other modes.
     pub fn with_wrap(mut self, wrap: bool) -> Self {
-        self.wrap = wrap;
+        self.wrap = if wrap {
+            WrapMode::Letter
+        } else {
+            WrapMode::NoWrap
+        };
+        self
+    }
+
+    pub fn with_wrap_mode(mut self, mode: WrapMode) -> Self {
+        self.wrap = mode;
         self
     }
 
@@ -415,6 +851,44 @@ impl FormattedTextBuilder {
             brush: self.brush,
             constraint: self.constraint,
             wrap: self.wrap,
+            trimming: TextTrimming::None,
+            runs: Vec::new(),
+            fallback_fonts: Vec::new(),
+            raster_scale: 1.0,
+            glyph_sources: Default::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::formatted_text::{split_lines, WrapMode};
+
+    fn lines(text: &str, mode: WrapMode) -> Vec<String> {
+        let text = text.chars().map(|c| c as u32).collect::<Vec<_>>();
+        // Every symbol is 10 units wide, so a line fits 8 symbols.
+        split_lines(&text, 80.0, mode, &|_| 10.0)
+            .iter()
+            .map(|line| {
+                text[line.begin..line.end]
+                    .iter()
+                    .filter_map(|&c| std::char::from_u32(c))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn line_breaks() {
+        let text = "one two three\nfour";
+        assert_eq!(lines(text, WrapMode::NoWrap), ["one two three", "four"]);
+        assert_eq!(lines(text, WrapMode::Letter), ["one two ", "three", "four"]);
+        assert_eq!(
+            lines("one twothree", WrapMode::Letter),
+            ["one twot", "hree"]
+        );
+        assert_eq!(lines("one twothree", WrapMode::Word), ["one ", "twothree"]);
+        // Word that is longer than a line is broken by letters.
+        assert_eq!(lines("abcdefghijkl", WrapMode::Word), ["abcdefgh", "ijkl"]);
+    }
+}
@@ -1,17 +1,46 @@
 use crate::draw::Draw;
 use crate::{
-    core::{algebra::Vector2, math::Rect, pool::Handle, scope_profile},
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle, scope_profile},
     draw::{CommandTexture, DrawingContext},
     message::MessageData,
     message::UiMessage,
     widget::{Widget, WidgetBuilder},
-    BuildContext, Control, UINode, UserInterface,
+    BaselineGroup, BuildContext, Control, MeasurePass, UINode, UserInterface, VerticalAlignment,
 };
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     ops::{Deref, DerefMut},
+    sync::Mutex,
 };
 
+lazy_static! {
+    static ref SHARED_SIZE_GROUP_NAMES: Mutex<Vec<String>> = Default::default();
+}
+
+/// Shared size group of grid columns or rows. Names of groups are stored globally, the group
+/// itself is just an index of its name, so columns and rows stay lightweight and `Copy`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SharedSizeGroup(usize);
+
+impl SharedSizeGroup {
+    /// Returns group with given name, every call with the same name gives the same group.
+    pub fn new<S: AsRef<str>>(name: S) -> Self {
+        let name = name.as_ref();
+        let mut names = SHARED_SIZE_GROUP_NAMES.lock().unwrap();
+        match names.iter().position(|n| n == name) {
+            Some(index) => Self(index),
+            None => {
+                names.push(name.to_owned());
+                Self(names.len() - 1)
+            }
+        }
+    }
+
+    pub fn name(self) -> String {
+        SHARED_SIZE_GROUP_NAMES.lock().unwrap()[self.0].clone()
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum SizeMode {
     Strict,
@@ -25,6 +54,10 @@ pub struct Column {
     desired_width: f32,
     actual_width: f32,
     x: f32,
+    /// Auto-sized columns in the same group share the same width across every grid.
+    shared_size_group: Option<SharedSizeGroup>,
+    /// Width of the content of the column before applying shared size.
+    content_width: f32,
 }
 
 impl Column {
@@ -34,6 +67,8 @@ impl Column {
             desired_width,
             actual_width: 0.0,
             x: 0.0,
+            shared_size_group: None,
+            content_width: 0.0,
         }
     }
 
@@ -43,6 +78,8 @@ impl Column {
             desired_width,
             actual_width: 0.0,
             x: 0.0,
+            shared_size_group: None,
+            content_width: 0.0,
         }
     }
 
@@ -52,6 +89,8 @@ impl Column {
             desired_width: 0.0,
             actual_width: 0.0,
             x: 0.0,
+            shared_size_group: None,
+            content_width: 0.0,
         }
     }
 
@@ -61,8 +100,21 @@ impl Column {
             desired_width: 0.0,
             actual_width: 0.0,
             x: 0.0,
+            shared_size_group: None,
+            content_width: 0.0,
         }
     }
+
+    /// Sets name of shared size group. Auto-sized columns from the same group will have the
+    /// same width (the maximum of content widths) even if they are in different grids.
+    pub fn with_shared_size_group<S: AsRef<str>>(mut self, group: S) -> Self {
+        self.shared_size_group = Some(SharedSizeGroup::new(group));
+        self
+    }
+
+    pub fn shared_size_group(&self) -> Option<SharedSizeGroup> {
+        self.shared_size_group
+    }
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -71,6 +123,10 @@ pub struct Row {
     desired_height: f32,
     actual_height: f32,
     y: f32,
+    /// Auto-sized rows in the same group share the same height across every grid.
+    shared_size_group: Option<SharedSizeGroup>,
+    /// Height of the content of the row before applying shared size.
+    content_height: f32,
 }
 
 impl Row {
@@ -80,6 +136,8 @@ impl Row {
             desired_height,
             actual_height: 0.0,
             y: 0.0,
+            shared_size_group: None,
+            content_height: 0.0,
         }
     }
 
@@ -89,6 +147,8 @@ impl Row {
             desired_height,
             actual_height: 0.0,
             y: 0.0,
+            shared_size_group: None,
+            content_height: 0.0,
         }
     }
 
@@ -98,6 +158,8 @@ impl Row {
             desired_height: 0.0,
             actual_height: 0.0,
             y: 0.0,
+            shared_size_group: None,
+            content_height: 0.0,
         }
     }
 
@@ -107,8 +169,32 @@ impl Row {
             desired_height: 0.0,
             actual_height: 0.0,
             y: 0.0,
+            shared_size_group: None,
+            content_height: 0.0,
         }
     }
+
+    /// Sets name of shared size group. Auto-sized rows from the same group will have the
+    /// same height (the maximum of content heights) even if they are in different grids.
+    pub fn with_shared_size_group<S: AsRef<str>>(mut self, group: S) -> Self {
+        self.shared_size_group = Some(SharedSizeGroup::new(group));
+        self
+    }
+
+    pub fn shared_size_group(&self) -> Option<SharedSizeGroup> {
+        self.shared_size_group
+    }
+}
+
+/// Defines how children of a grid are placed into cells automatically, in order of children.
+/// Explicit row and column of children are ignored when auto-flow is used. Rows (or columns)
+/// are added as needed, added ones are auto-sized.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AutoFlow {
+    /// Children fill rows of given amount of columns one by one.
+    Rows(usize),
+    /// Children fill columns of given amount of rows one by one.
+    Columns(usize),
 }
 
 /// Automatically arranges children by rows and columns
@@ -119,6 +205,12 @@ pub struct Grid<M: MessageData, C: Control<M, C>> {
     columns: RefCell<Vec<Column>>,
     draw_border: bool,
     border_thickness: f32,
+    auto_flow: Option<AutoFlow>,
+    /// Row and column of every child, in order of children.
+    cells: RefCell<Vec<(usize, usize)>>,
+    /// Amount of rows and columns that were added at the end by auto-flow.
+    implicit_rows: Cell<usize>,
+    implicit_columns: Cell<usize>,
 }
 
 crate::define_widget_deref!(Grid<M, C>);
@@ -131,15 +223,25 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Grid<M, C> {
     ) -> Vector2<f32> {
         scope_profile!();
 
+        self.assign_cells(ui);
+
         // In case of no rows or columns, grid acts like default panel.
         if self.columns.borrow().is_empty() || self.rows.borrow().is_empty() {
             return self.widget.measure_override(ui, available_size);
         }
 
+        // Children of star-sized grid are measured twice, results of both passes are cached
+        // separately.
+        let (content_pass, constrained_pass) = if self.has_stretch_sized_cells() {
+            (MeasurePass::Content, MeasurePass::Constrained)
+        } else {
+            (MeasurePass::Normal, MeasurePass::Normal)
+        };
+
         let mut desired_size = Vector2::default();
         // Step 1. Measure every children with relaxed constraints (size of grid).
         for child_handle in self.widget.children() {
-            ui.node(*child_handle).measure(ui, available_size);
+            ui.measure_node_in_pass(*child_handle, available_size, content_pass);
         }
 
         // Step 2. Calculate width of columns and heights of rows.
@@ -150,16 +252,20 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Grid<M, C> {
         self.fit_stretch_sized_rows(ui, available_size, preset_height);
 
         // Step 3. Re-measure children with new constraints.
-        for child_handle in self.widget.children() {
-            let child = ui.nodes.borrow(*child_handle);
-            if let Some(column) = self.columns.borrow().get(child.column()) {
-                if let Some(row) = self.rows.borrow().get(child.row()) {
+        for (child_handle, &(row, column)) in self
+            .widget
+            .children()
+            .iter()
+            .zip(self.cells.borrow().iter())
+        {
+            if let Some(column) = self.columns.borrow().get(column) {
+                if let Some(row) = self.rows.borrow().get(row) {
                     // Contents of Auto cells must *not* be measured twice: Auto cells will be
                     // fit to content size anyways, this check saves millions of calls on nested
                     // grids.
                     if column.size_mode != SizeMode::Auto && row.size_mode != SizeMode::Auto {
                         let cell_size = Vector2::new(column.actual_width, row.actual_height);
-                        ui.node(*child_handle).measure(ui, cell_size);
+                        ui.measure_node_in_pass(*child_handle, cell_size, constrained_pass);
                     }
                 }
             }
@@ -179,6 +285,8 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Grid<M, C> {
     fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
         scope_profile!();
 
+        self.assign_cells(ui);
+
         if self.columns.borrow().is_empty() || self.rows.borrow().is_empty() {
             let rect = Rect::new(0.0, 0.0, final_size.x, final_size.y);
             for child_handle in self.widget.children() {
@@ -196,14 +304,42 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Grid<M, C> {
         self.arrange_rows();
         self.arrange_columns();
 
-        for child_handle in self.widget.children() {
+        // Children aligned by baseline share common baseline within a row.
+        let mut baseline_groups = vec![BaselineGroup::default(); self.rows.borrow().len()];
+        for (child_handle, &(row, _)) in self
+            .widget
+            .children()
+            .iter()
+            .zip(self.cells.borrow().iter())
+        {
             let child = ui.nodes.borrow(*child_handle);
-            if let Some(column) = self.columns.borrow().get(child.column()) {
-                if let Some(row) = self.rows.borrow().get(child.row()) {
-                    ui.nodes.borrow(*child_handle).arrange(
-                        ui,
-                        &Rect::new(column.x, row.y, column.actual_width, row.actual_height),
-                    );
+            if child.visibility() && child.vertical_alignment() == VerticalAlignment::Baseline {
+                if let Some(group) = baseline_groups.get_mut(row) {
+                    group.add(child, ui);
+                }
+            }
+        }
+
+        for (child_handle, &(row_index, column)) in self
+            .widget
+            .children()
+            .iter()
+            .zip(self.cells.borrow().iter())
+        {
+            if let Some(column) = self.columns.borrow().get(column) {
+                if let Some(row) = self.rows.borrow().get(row_index) {
+                    let child = ui.nodes.borrow(*child_handle);
+                    let rect = if child.vertical_alignment() == VerticalAlignment::Baseline {
+                        Rect::new(
+                            column.x,
+                            row.y + baseline_groups[row_index].offset(child, ui),
+                            column.actual_width,
+                            child.desired_size().y,
+                        )
+                    } else {
+                        Rect::new(column.x, row.y, column.actual_width, row.actual_height)
+                    };
+                    child.arrange(ui, &rect);
                 }
             }
         }
@@ -220,20 +356,25 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Grid<M, C> {
             let right_bottom = Vector2::new(bounds.x() + bounds.w(), bounds.y() + bounds.h());
             let left_bottom = Vector2::new(bounds.x(), bounds.y() + bounds.h());
 
-            drawing_context.push_line(left_top, right_top, self.border_thickness);
-            drawing_context.push_line(right_top, right_bottom, self.border_thickness);
-            drawing_context.push_line(right_bottom, left_bottom, self.border_thickness);
-            drawing_context.push_line(left_bottom, left_top, self.border_thickness);
+            drawing_context.push_line(left_top, right_top, self.border_thickness, Color::WHITE);
+            drawing_context.push_line(right_top, right_bottom, self.border_thickness, Color::WHITE);
+            drawing_context.push_line(
+                right_bottom,
+                left_bottom,
+                self.border_thickness,
+                Color::WHITE,
+            );
+            drawing_context.push_line(left_bottom, left_top, self.border_thickness, Color::WHITE);
 
             for column in self.columns.borrow().iter() {
                 let a = Vector2::new(bounds.x() + column.x, bounds.y());
                 let b = Vector2::new(bounds.x() + column.x, bounds.y() + bounds.h());
-                drawing_context.push_line(a, b, self.border_thickness);
+                drawing_context.push_line(a, b, self.border_thickness, Color::WHITE);
             }
             for row in self.rows.borrow().iter() {
                 let a = Vector2::new(bounds.x(), bounds.y() + row.y);
                 let b = Vector2::new(bounds.x() + bounds.w(), bounds.y() + row.y);
-                drawing_context.push_line(a, b, self.border_thickness);
+                drawing_context.push_line(a, b, self.border_thickness, Color::WHITE);
             }
 
             drawing_context.commit(
@@ -260,6 +401,7 @@ pub struct GridBuilder<M: MessageData, C: Control<M, C>> {
     columns: Vec<Column>,
     draw_border: bool,
     border_thickness: f32,
+    auto_flow: Option<AutoFlow>,
 }
 
 impl<M: MessageData, C: Control<M, C>> GridBuilder<M, C> {
@@ -270,6 +412,7 @@ impl<M: MessageData, C: Control<M, C>> GridBuilder<M, C> {
             columns: Vec::new(),
             draw_border: false,
             border_thickness: 1.0,
+            auto_flow: None,
         }
     }
 
@@ -303,6 +446,12 @@ impl<M: MessageData, C: Control<M, C>> GridBuilder<M, C> {
         self
     }
 
+    /// See `Grid::set_auto_flow`.
+    pub fn with_auto_flow(mut self, auto_flow: AutoFlow) -> Self {
+        self.auto_flow = Some(auto_flow);
+        self
+    }
+
     pub fn build(self, ui: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
         let grid = Grid {
             widget: self.widget_builder.build(),
@@ -310,6 +459,10 @@ impl<M: MessageData, C: Control<M, C>> GridBuilder<M, C> {
             columns: RefCell::new(self.columns),
             draw_border: self.draw_border,
             border_thickness: self.border_thickness,
+            auto_flow: self.auto_flow,
+            cells: Default::default(),
+            implicit_rows: Cell::new(0),
+            implicit_columns: Cell::new(0),
         };
         ui.add_node(UINode::Grid(grid))
     }
@@ -323,6 +476,10 @@ impl<M: MessageData, C: Control<M, C>> Grid<M, C> {
             columns: Default::default(),
             draw_border: false,
             border_thickness: 1.0,
+            auto_flow: None,
+            cells: Default::default(),
+            implicit_rows: Cell::new(0),
+            implicit_columns: Cell::new(0),
         }
     }
 
@@ -352,6 +509,92 @@ impl<M: MessageData, C: Control<M, C>> Grid<M, C> {
         self.rows = RefCell::new(rows);
     }
 
+    /// Places children into cells automatically in order of children, explicit row and column
+    /// of children are ignored. `None` restores explicit placement. Useful when amount of
+    /// children is not known beforehand, for example in icon grids.
+    pub fn set_auto_flow(&mut self, auto_flow: Option<AutoFlow>) -> &mut Self {
+        if self.auto_flow != auto_flow {
+            self.auto_flow = auto_flow;
+            self.invalidate_layout();
+        }
+        self
+    }
+
+    pub fn auto_flow(&self) -> Option<AutoFlow> {
+        self.auto_flow
+    }
+
+    /// Calculates cells of children and adds (or removes) rows and columns required by
+    /// auto-flow.
+    fn assign_cells(&self, ui: &UserInterface<M, C>) {
+        let mut cells = self.cells.borrow_mut();
+        cells.clear();
+
+        let mut rows = self.rows.borrow_mut();
+        let mut columns = self.columns.borrow_mut();
+        let explicit_rows = rows.len() - self.implicit_rows.get();
+        let explicit_columns = columns.len() - self.implicit_columns.get();
+        rows.truncate(explicit_rows);
+        columns.truncate(explicit_columns);
+
+        let (major, line_length) = match self.auto_flow {
+            None => {
+                for child_handle in self.widget.children() {
+                    let child = ui.nodes.borrow(*child_handle);
+                    cells.push((child.row(), child.column()));
+                }
+                self.implicit_rows.set(0);
+                self.implicit_columns.set(0);
+                return;
+            }
+            Some(AutoFlow::Rows(columns)) => (true, columns.max(1)),
+            Some(AutoFlow::Columns(rows)) => (false, rows.max(1)),
+        };
+
+        // Invisible children do not occupy cells.
+        let mut index = 0;
+        for child_handle in self.widget.children() {
+            if ui.nodes.borrow(*child_handle).visibility() {
+                let (line, position) = (index / line_length, index % line_length);
+                cells.push(if major {
+                    (line, position)
+                } else {
+                    (position, line)
+                });
+                index += 1;
+            } else {
+                cells.push((usize::MAX, usize::MAX));
+            }
+        }
+        let lines = index.div_ceil(line_length);
+        let (row_count, column_count) = if major {
+            (lines, line_length)
+        } else {
+            (line_length, lines)
+        };
+
+        while rows.len() < row_count {
+            rows.push(Row::auto());
+        }
+        while columns.len() < column_count {
+            columns.push(Column::auto());
+        }
+        self.implicit_rows.set(rows.len() - explicit_rows);
+        self.implicit_columns.set(columns.len() - explicit_columns);
+    }
+
+    fn has_stretch_sized_cells(&self) -> bool {
+        self.columns
+            .borrow()
+            .iter()
+            .any(|column| column.size_mode == SizeMode::Stretch)
+            || self
+                .rows
+                .borrow()
+                .iter()
+                .any(|row| row.size_mode == SizeMode::Stretch)
+    }
+
     fn calculate_preset_width(&self, ui: &UserInterface<M, C>) -> f32 {
         let mut preset_width = 0.0;
 
@@ -362,15 +605,26 @@ impl<M: MessageData, C: Control<M, C>> Grid<M, C> {
                 preset_width += col.actual_width;
             } else if col.size_mode == SizeMode::Auto {
                 col.actual_width = col.desired_width;
-                for child_handle in self.widget.children() {
+                for (child_handle, &(_, column)) in self
+                    .widget
+                    .children()
+                    .iter()
+                    .zip(self.cells.borrow().iter())
+                {
                     let child = ui.nodes.borrow(*child_handle);
-                    if child.column() == i
+                    if column == i
                         && child.visibility()
                         && child.desired_size().x > col.actual_width
                     {
                         col.actual_width = child.desired_size().x;
                     }
                 }
+                col.content_width = col.actual_width;
+                if let Some(group) = col.shared_size_group {
+                    if let Some(shared) = ui.shared_size(group) {
+                        col.actual_width = col.actual_width.max(shared);
+                    }
+                }
                 preset_width += col.actual_width;
             }
         }
@@ -388,15 +642,42 @@ impl<M: MessageData, C: Control<M, C>> Grid<M, C> {
                 preset_height += row.actual_height;
             } else if row.size_mode == SizeMode::Auto {
                 row.actual_height = row.desired_height;
-                for child_handle in self.widget.children() {
+                for (child_handle, &(row_index, _)) in self
+                    .widget
+                    .children()
+                    .iter()
+                    .zip(self.cells.borrow().iter())
+                {
                     let child = ui.nodes.borrow(*child_handle);
-                    if child.row() == i
+                    if row_index == i
                         && child.visibility()
                         && child.desired_size().y > row.actual_height
                     {
                         row.actual_height = child.desired_size().y;
                     }
                 }
+                let mut baseline_group = BaselineGroup::default();
+                for (child_handle, &(row_index, _)) in self
+                    .widget
+                    .children()
+                    .iter()
+                    .zip(self.cells.borrow().iter())
+                {
+                    let child = ui.nodes.borrow(*child_handle);
+                    if row_index == i
+                        && child.visibility()
+                        && child.vertical_alignment() == VerticalAlignment::Baseline
+                    {
+                        baseline_group.add(child, ui);
+                    }
+                }
+                row.actual_height = row.actual_height.max(baseline_group.height());
+                row.content_height = row.actual_height;
+                if let Some(group) = row.shared_size_group {
+                    if let Some(shared) = ui.shared_size(group) {
+                        row.actual_height = row.actual_height.max(shared);
+                    }
+                }
                 preset_height += row.actual_height;
             }
         }
@@ -412,9 +693,14 @@ impl<M: MessageData, C: Control<M, C>> Grid<M, C> {
     ) {
         let mut rest_width = 0.0;
         if available_size.x.is_infinite() {
-            for child_handle in self.widget.children() {
+            for (child_handle, &(_, column)) in self
+                .widget
+                .children()
+                .iter()
+                .zip(self.cells.borrow().iter())
+            {
                 let child = ui.nodes.borrow(*child_handle);
-                if let Some(column) = self.columns.borrow().get(child.column()) {
+                if let Some(column) = self.columns.borrow().get(column) {
                     if column.size_mode == SizeMode::Stretch {
                         rest_width += child.desired_size().x;
                     }
@@ -450,9 +736,14 @@ impl<M: MessageData, C: Control<M, C>> Grid<M, C> {
         let mut stretch_sized_rows = 0;
         let mut rest_height = 0.0;
         if available_size.y.is_infinite() {
-            for child_handle in self.widget.children() {
+            for (child_handle, &(row, _)) in self
+                .widget
+                .children()
+                .iter()
+                .zip(self.cells.borrow().iter())
+            {
                 let child = ui.nodes.borrow(*child_handle);
-                if let Some(row) = self.rows.borrow().get(child.row()) {
+                if let Some(row) = self.rows.borrow().get(row) {
                     if row.size_mode == SizeMode::Stretch {
                         rest_height += child.desired_size().y;
                     }
@@ -493,6 +784,21 @@ impl<M: MessageData, C: Control<M, C>> Grid<M, C> {
         }
     }
 
+    /// Passes content size of every auto-sized column and row that is in a shared size group
+    /// into given closure.
+    pub(in crate) fn visit_shared_size_groups<F: FnMut(SharedSizeGroup, f32)>(&self, mut func: F) {
+        for column in self.columns.borrow().iter() {
+            if let (SizeMode::Auto, Some(group)) = (column.size_mode, column.shared_size_group()) {
+                func(group, column.content_width);
+            }
+        }
+        for row in self.rows.borrow().iter() {
+            if let (SizeMode::Auto, Some(group)) = (row.size_mode, row.shared_size_group()) {
+                func(group, row.content_height);
+            }
+        }
+    }
+
     pub fn set_draw_border(&mut self, value: bool) -> &mut Self {
         self.draw_border = value;
         self
@@ -511,3 +817,48 @@ impl<M: MessageData, C: Control<M, C>> Grid<M, C> {
         self.border_thickness
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        core::algebra::Vector2,
+        grid::{AutoFlow, GridBuilder},
+        node::{StubNode, UINode},
+        widget::WidgetBuilder,
+        UserInterface,
+    };
+
+    #[test]
+    fn auto_flow_line_count() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        for &(children, auto_flow, expected) in [
+            (0, AutoFlow::Rows(2), (0, 2)),
+            (4, AutoFlow::Rows(2), (2, 2)),
+            (5, AutoFlow::Rows(2), (3, 2)),
+            (1, AutoFlow::Columns(3), (3, 1)),
+            (7, AutoFlow::Columns(3), (3, 3)),
+        ]
+        .iter()
+        {
+            let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+            let ctx = &mut ui.build_ctx();
+            let children = (0..children)
+                .map(|_| BorderBuilder::new(WidgetBuilder::new()).build(ctx))
+                .collect::<Vec<_>>();
+            let grid = GridBuilder::new(WidgetBuilder::new().with_children(&children))
+                .with_auto_flow(auto_flow)
+                .build(ctx);
+            ui.update(screen_size, 0.0);
+
+            if let UINode::Grid(grid) = ui.node(grid) {
+                assert_eq!(
+                    (grid.rows.borrow().len(), grid.columns.borrow().len()),
+                    expected
+                );
+            } else {
+                unreachable!();
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::draw::Draw;
 use crate::{
+    accessibility::AccessibleRole,
     brush::Brush,
     core::{algebra::Vector2, color::Color, pool::Handle},
     draw::{CommandTexture, DrawingContext, SharedTexture},
@@ -7,15 +8,33 @@ use crate::{
     widget::{Widget, WidgetBuilder},
     BuildContext, Control, UINode, UserInterface,
 };
-use std::ops::{Deref, DerefMut};
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
 
+/// Image shows a texture. Image without explicit size is measured to size of its texture (in
+/// logical units, so it depends on scale factor of the UI), if only one axis of size is set,
+/// another one is derived from aspect ratio of the texture. Size of textures is provided by
+/// `UserInterface::set_texture_size_provider`, while a texture is not loaded the image is
+/// measured by its children and it will be measured again as soon as the texture is loaded.
 #[derive(Clone)]
 pub struct Image<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     texture: Option<SharedTexture>,
     flip: bool,
+    /// Colors that modulate corners of the image, clockwise from left top corner.
+    corner_colors: Option<[Color; 4]>,
+    /// Size of texture in pixels, it is requested from the UI once per texture and stays
+    /// `None` while the texture is loading.
+    texture_size: Cell<Option<Vector2<f32>>>,
+    alpha_hit_test: bool,
+    alpha_hit_threshold: f32,
 }
 
+/// Pixels with alpha below this value are not hit by default, see `Image::set_alpha_hit_test`.
+pub const DEFAULT_ALPHA_HIT_THRESHOLD: f32 = 0.5;
+
 crate::define_widget_deref!(Image<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Image<M, C> {
@@ -24,15 +43,125 @@ impl<M: MessageData, C: Control<M, C>> Image<M, C> {
             widget,
             texture: None,
             flip: false,
+            corner_colors: None,
+            texture_size: Cell::new(None),
+            alpha_hit_test: false,
+            alpha_hit_threshold: DEFAULT_ALPHA_HIT_THRESHOLD,
         }
     }
 
     pub fn set_texture(&mut self, texture: SharedTexture) {
         self.texture = Some(texture);
+        self.texture_size.set(None);
+        self.widget.invalidate_layout();
+    }
+
+    pub fn texture(&self) -> Option<&SharedTexture> {
+        self.texture.as_ref()
+    }
+
+    pub(in crate) fn is_awaiting_texture_size(&self) -> bool {
+        self.texture.is_some() && self.texture_size.get().is_none()
+    }
+
+    /// Stores size of texture, size is measured with it on next layout pass.
+    pub(in crate) fn set_texture_size(&self, size: Option<Vector2<f32>>) {
+        self.texture_size.set(size);
+        self.widget.invalidate_layout();
+    }
+
+    /// Sets colors that modulate each corner of the image (left top, right top, right bottom,
+    /// left bottom), colors are interpolated across the image. It is useful for vignette or
+    /// gradient tinting. `None` draws the image as is.
+    pub fn set_corner_colors(&mut self, colors: Option<[Color; 4]>) {
+        self.corner_colors = colors;
+    }
+
+    pub fn corner_colors(&self) -> Option<[Color; 4]> {
+        self.corner_colors
+    }
+
+    /// Enables or disables alpha hit test. When enabled, clicks on pixels of the texture with
+    /// alpha below threshold pass through the image, it is useful for icons of irregular
+    /// shape. Alpha is read with callback set by `UserInterface::set_texture_alpha_provider`,
+    /// so it is off by default to avoid cost of texture reads.
+    pub fn set_alpha_hit_test(&mut self, enabled: bool) {
+        self.alpha_hit_test = enabled;
+    }
+
+    pub fn is_alpha_hit_test(&self) -> bool {
+        self.alpha_hit_test
+    }
+
+    /// Sets minimal alpha of pixels that are hit, `DEFAULT_ALPHA_HIT_THRESHOLD` by default.
+    pub fn set_alpha_hit_threshold(&mut self, threshold: f32) {
+        self.alpha_hit_threshold = threshold;
+    }
+
+    pub fn alpha_hit_threshold(&self) -> f32 {
+        self.alpha_hit_threshold
+    }
+
+    /// Checks whether pixel of the texture at given point (in screen coordinates) is opaque
+    /// enough to be hit. Image without alpha hit test is opaque everywhere.
+    pub(in crate) fn is_opaque_at(&self, ui: &UserInterface<M, C>, pt: Vector2<f32>) -> bool {
+        if !self.alpha_hit_test {
+            return true;
+        }
+        let texture = match self.texture.as_ref() {
+            Some(texture) => texture,
+            None => return true,
+        };
+        let bounds = self.widget.screen_bounds();
+        if bounds.w() <= 0.0 || bounds.h() <= 0.0 {
+            return true;
+        }
+        let u = (pt.x - bounds.x()) / bounds.w();
+        let v = (pt.y - bounds.y()) / bounds.h();
+        let tex_coord = Vector2::new(u, if self.flip { 1.0 - v } else { v });
+        // Unknown alpha is treated as opaque.
+        !matches!(ui.texture_alpha(texture, tex_coord), Some(alpha) if alpha < self.alpha_hit_threshold)
     }
 }
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for Image<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Image
+    }
+
+    fn measure_override(
+        &self,
+        ui: &UserInterface<M, C>,
+        available_size: Vector2<f32>,
+    ) -> Vector2<f32> {
+        let size = self.widget.measure_override(ui, available_size);
+
+        let texture = match self.texture.as_ref() {
+            Some(texture) => texture,
+            None => return size,
+        };
+        if self.texture_size.get().is_none() {
+            self.texture_size.set(ui.texture_size(texture));
+        }
+        let texture_size = match self.texture_size.get() {
+            Some(texture_size) => texture_size,
+            None => {
+                ui.await_texture_size(self.handle());
+                return size;
+            }
+        };
+
+        let natural_size = texture_size.scale(1.0 / ui.scale_factor());
+        let aspect = natural_size.x / natural_size.y;
+        let natural_size = match (self.width().is_nan(), self.height().is_nan()) {
+            (false, true) => Vector2::new(self.width(), self.width() / aspect),
+            (true, false) => Vector2::new(self.height() * aspect, self.height()),
+            _ => natural_size,
+        };
+
+        Vector2::new(size.x.max(natural_size.x), size.y.max(natural_size.y))
+    }
+
     fn draw(&self, drawing_context: &mut DrawingContext) {
         let bounds = self.widget.screen_bounds();
         let tex_coords = if self.flip {
@@ -45,7 +174,11 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Image<M, C> {
         } else {
             None
         };
-        drawing_context.push_rect_filled(&bounds, tex_coords.as_ref());
+        if let Some(colors) = self.corner_colors {
+            drawing_context.push_rect_filled_multicolor(&bounds, tex_coords.as_ref(), colors);
+        } else {
+            drawing_context.push_rect_filled(&bounds, tex_coords.as_ref());
+        }
         let texture = self
             .texture
             .as_ref()
@@ -65,10 +198,21 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Image<M, C> {
                 match msg {
                     ImageMessage::Texture(tex) => {
                         self.texture = tex.clone();
+                        self.texture_size.set(None);
+                        self.widget.invalidate_layout();
                     }
                     &ImageMessage::Flip(flip) => {
                         self.flip = flip;
                     }
+                    &ImageMessage::CornerColors(colors) => {
+                        self.corner_colors = colors;
+                    }
+                    &ImageMessage::AlphaHitTest(enabled) => {
+                        self.alpha_hit_test = enabled;
+                    }
+                    &ImageMessage::AlphaHitThreshold(threshold) => {
+                        self.alpha_hit_threshold = threshold;
+                    }
                 }
             }
         }
@@ -79,6 +223,9 @@ pub struct ImageBuilder<M: MessageData, C: Control<M, C>> {
     widget_builder: WidgetBuilder<M, C>,
     texture: Option<SharedTexture>,
     flip: bool,
+    corner_colors: Option<[Color; 4]>,
+    alpha_hit_test: bool,
+    alpha_hit_threshold: f32,
 }
 
 impl<M: MessageData, C: Control<M, C>> ImageBuilder<M, C> {
@@ -87,14 +234,35 @@ impl<M: MessageData, C: Control<M, C>> ImageBuilder<M, C> {
             widget_builder,
             texture: None,
             flip: false,
+            corner_colors: None,
+            alpha_hit_test: false,
+            alpha_hit_threshold: DEFAULT_ALPHA_HIT_THRESHOLD,
         }
     }
 
+    /// See `Image::set_alpha_hit_test`.
+    pub fn with_alpha_hit_test(mut self, enabled: bool) -> Self {
+        self.alpha_hit_test = enabled;
+        self
+    }
+
+    /// See `Image::set_alpha_hit_threshold`.
+    pub fn with_alpha_hit_threshold(mut self, threshold: f32) -> Self {
+        self.alpha_hit_threshold = threshold;
+        self
+    }
+
     pub fn with_flip(mut self, flip: bool) -> Self {
         self.flip = flip;
         self
     }
 
+    /// See `Image::set_corner_colors`.
+    pub fn with_corner_colors(mut self, colors: [Color; 4]) -> Self {
+        self.corner_colors = Some(colors);
+        self
+    }
+
     pub fn with_texture(mut self, texture: SharedTexture) -> Self {
         self.texture = Some(texture);
         self
@@ -114,6 +282,10 @@ impl<M: MessageData, C: Control<M, C>> ImageBuilder<M, C> {
             widget: self.widget_builder.build(),
             texture: self.texture,
             flip: self.flip,
+            corner_colors: self.corner_colors,
+            texture_size: Cell::new(None),
+            alpha_hit_test: self.alpha_hit_test,
+            alpha_hit_threshold: self.alpha_hit_threshold,
         };
         UINode::Image(image)
     }
@@ -122,3 +294,110 @@ impl<M: MessageData, C: Control<M, C>> ImageBuilder<M, C> {
         ctx.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        draw::SharedTexture,
+        image::ImageBuilder,
+        message::{ImageMessage, MessageDirection},
+        node::StubNode,
+        widget::WidgetBuilder,
+        HorizontalAlignment, UserInterface, VerticalAlignment,
+    };
+    use std::{cell::Cell, rc::Rc, sync::Arc};
+
+    #[test]
+    fn alpha_hit_test() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        // Left half of the texture is transparent.
+        ui.set_texture_alpha_provider(Some(Box::new(|_, tex_coord| {
+            Some(if tex_coord.x < 0.5 { 0.0 } else { 1.0 })
+        })));
+        let texture = SharedTexture::from(Arc::new(0u8));
+        let image = ImageBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(100.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .with_texture(texture)
+        .with_alpha_hit_test(true)
+        .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        ui.draw();
+
+        assert!(ui.hit_test(Vector2::new(25.0, 50.0)).is_none());
+        assert_eq!(ui.hit_test(Vector2::new(75.0, 50.0)), image);
+
+        // Zero threshold makes every pixel hittable.
+        ui.send_message(ImageMessage::alpha_hit_threshold(
+            image,
+            MessageDirection::ToWidget,
+            0.0,
+        ));
+        while ui.poll_message().is_some() {}
+        assert_eq!(ui.hit_test(Vector2::new(25.0, 50.0)), image);
+    }
+
+    #[test]
+    fn measure_by_texture_size() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        // Texture is "loaded" after first two requests.
+        let requests = Rc::new(Cell::new(0));
+        ui.set_texture_size_provider(Some(Box::new({
+            let requests = requests.clone();
+            move |_| {
+                requests.set(requests.get() + 1);
+                if requests.get() > 2 {
+                    Some(Vector2::new(64.0, 32.0))
+                } else {
+                    None
+                }
+            }
+        })));
+        let image = ImageBuilder::new(
+            WidgetBuilder::new()
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .with_texture(SharedTexture::from(Arc::new(0u8)))
+        .build(&mut ui.build_ctx());
+        let scaled = ImageBuilder::new(
+            WidgetBuilder::new()
+                .with_width(32.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .build(&mut ui.build_ctx());
+
+        ui.update(screen_size, 0.0);
+        assert_eq!(ui.node(image).actual_size(), Vector2::new(0.0, 0.0));
+
+        ui.update(screen_size, 0.0);
+        ui.update(screen_size, 0.0);
+        assert_eq!(ui.node(image).actual_size(), Vector2::new(64.0, 32.0));
+
+        // Known size is not requested again.
+        let count = requests.get();
+        for _ in 0..3 {
+            ui.node(image).invalidate_layout();
+            ui.update(screen_size, 0.0);
+        }
+        assert_eq!(requests.get(), count);
+
+        ui.send_message(ImageMessage::texture(
+            scaled,
+            MessageDirection::ToWidget,
+            Some(SharedTexture::from(Arc::new(1u8))),
+        ));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+        assert_eq!(requests.get(), count + 1);
+        assert_eq!(ui.node(scaled).actual_size(), Vector2::new(32.0, 16.0));
+    }
+}
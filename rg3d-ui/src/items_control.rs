@@ -0,0 +1,266 @@
+//! Common item management for list-like widgets: list view, tree, tree root, menu and menu
+//! item use it directly, dropdown list builds on list view. It is not a widget by itself,
+//! instead it is embedded into widgets that manage a collection of items placed on some
+//! items panel.
+
+use crate::{
+    core::pool::Handle,
+    message::{MessageData, MessageDirection, WidgetMessage},
+    BuildContext, Control, HorizontalAlignment, NodeHandleMapping, UINode, UserInterface,
+    VerticalAlignment,
+};
+
+#[derive(Debug, Clone)]
+pub struct ItemsControl<M: MessageData, C: Control<M, C>> {
+    panel: Handle<UINode<M, C>>,
+    items: Vec<Handle<UINode<M, C>>>,
+    /// Nodes that are actually linked to items panel. Container could be the item itself
+    /// or some wrapper around it (like ListViewItem).
+    item_containers: Vec<Handle<UINode<M, C>>>,
+    /// Node that is shown only while there are no items (like "No results").
+    placeholder: Handle<UINode<M, C>>,
+}
+
+impl<M: MessageData, C: Control<M, C>> ItemsControl<M, C> {
+    /// Creates new items control where every item is its own container.
+    pub fn new(panel: Handle<UINode<M, C>>, items: Vec<Handle<UINode<M, C>>>) -> Self {
+        Self {
+            panel,
+            item_containers: items.clone(),
+            items,
+            placeholder: Handle::NONE,
+        }
+    }
+
+    /// Creates new items control where every item is wrapped into a container. Items and
+    /// containers are matched by index, items (or containers) without a pair are ignored.
+    pub fn with_containers(
+        panel: Handle<UINode<M, C>>,
+        mut items: Vec<Handle<UINode<M, C>>>,
+        mut item_containers: Vec<Handle<UINode<M, C>>>,
+    ) -> Self {
+        if items.len() != item_containers.len() {
+            println!(
+                "ItemsControl: {} items does not match {} containers, extra ones will be ignored!",
+                items.len(),
+                item_containers.len()
+            );
+            let len = items.len().min(item_containers.len());
+            items.truncate(len);
+            item_containers.truncate(len);
+        }
+        Self {
+            panel,
+            items,
+            item_containers,
+            placeholder: Handle::NONE,
+        }
+    }
+
+    /// Sets placeholder on widget build stage, placeholder must be linked to the widget
+    /// manually. Placeholder is centered in the widget and does not react on mouse.
+    pub fn with_placeholder(
+        mut self,
+        ctx: &mut BuildContext<M, C>,
+        placeholder: Handle<UINode<M, C>>,
+    ) -> Self {
+        if placeholder.is_some() {
+            ctx[placeholder]
+                .set_hit_test_visibility(false)
+                .set_horizontal_alignment(HorizontalAlignment::Center)
+                .set_vertical_alignment(VerticalAlignment::Center)
+                .set_visibility(self.items.is_empty());
+        }
+        self.placeholder = placeholder;
+        self
+    }
+
+    pub fn placeholder(&self) -> Handle<UINode<M, C>> {
+        self.placeholder
+    }
+
+    /// Replaces placeholder of the items control, previous placeholder is removed and new
+    /// one is linked to given `owner` widget. `Handle::NONE` removes placeholder.
+    pub fn set_placeholder(
+        &mut self,
+        ui: &UserInterface<M, C>,
+        owner: Handle<UINode<M, C>>,
+        placeholder: Handle<UINode<M, C>>,
+    ) {
+        if self.placeholder == placeholder {
+            return;
+        }
+        if self.placeholder.is_some() {
+            ui.send_message(WidgetMessage::remove(
+                self.placeholder,
+                MessageDirection::ToWidget,
+            ));
+        }
+        self.placeholder = placeholder;
+        if placeholder.is_some() {
+            ui.send_message(WidgetMessage::link(
+                placeholder,
+                MessageDirection::ToWidget,
+                owner,
+            ));
+            ui.send_message(WidgetMessage::hit_test_visibility(
+                placeholder,
+                MessageDirection::ToWidget,
+                false,
+            ));
+            ui.send_message(WidgetMessage::horizontal_alignment(
+                placeholder,
+                MessageDirection::ToWidget,
+                HorizontalAlignment::Center,
+            ));
+            ui.send_message(WidgetMessage::vertical_alignment(
+                placeholder,
+                MessageDirection::ToWidget,
+                VerticalAlignment::Center,
+            ));
+            self.sync_placeholder(ui);
+        }
+    }
+
+    /// Shows placeholder if there are no items and hides it otherwise.
+    fn sync_placeholder(&self, ui: &UserInterface<M, C>) {
+        if self.placeholder.is_some() {
+            ui.send_message(WidgetMessage::visibility(
+                self.placeholder,
+                MessageDirection::ToWidget,
+                self.items.is_empty(),
+            ));
+        }
+    }
+
+    /// Shows or hides placeholder if its visibility does not match current items. It is used
+    /// after removal of nodes, `remove_ref` can't send messages.
+    pub(in crate) fn refresh_placeholder(&self, ui: &UserInterface<M, C>) {
+        if let Some(placeholder) = ui.nodes.try_borrow(self.placeholder) {
+            if placeholder.visibility() != self.items.is_empty() {
+                self.sync_placeholder(ui);
+            }
+        }
+    }
+
+    pub fn items(&self) -> &[Handle<UINode<M, C>>] {
+        &self.items
+    }
+
+    pub fn item_containers(&self) -> &[Handle<UINode<M, C>>] {
+        &self.item_containers
+    }
+
+    pub fn items_panel(&self) -> Handle<UINode<M, C>> {
+        self.panel
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns index of an item. Given handle can be either an item or its container.
+    pub fn index_of(&self, handle: Handle<UINode<M, C>>) -> Option<usize> {
+        self.items
+            .iter()
+            .position(|&i| i == handle)
+            .or_else(|| self.item_containers.iter().position(|&c| c == handle))
+    }
+
+    pub fn add_item(&mut self, ui: &UserInterface<M, C>, item: Handle<UINode<M, C>>) {
+        self.add_item_with_container(ui, item, item);
+    }
+
+    pub fn add_item_with_container(
+        &mut self,
+        ui: &UserInterface<M, C>,
+        item: Handle<UINode<M, C>>,
+        container: Handle<UINode<M, C>>,
+    ) {
+        ui.send_message(WidgetMessage::link(
+            container,
+            MessageDirection::ToWidget,
+            self.panel,
+        ));
+
+        self.items.push(item);
+        self.item_containers.push(container);
+        self.sync_placeholder(ui);
+    }
+
+    /// Adds new item without linking its container to items panel. This method is meant to be
+    /// used only on widget build stage, when container is linked manually.
+    pub fn push_item(&mut self, item: Handle<UINode<M, C>>, container: Handle<UINode<M, C>>) {
+        self.items.push(item);
+        self.item_containers.push(container);
+    }
+
+    /// Removes an item at given index, its container will be removed from UI. Returns
+    /// handle of removed item.
+    pub fn remove_item(
+        &mut self,
+        ui: &UserInterface<M, C>,
+        index: usize,
+    ) -> Option<Handle<UINode<M, C>>> {
+        if index < self.items.len() {
+            let container = self.item_containers.remove(index);
+            ui.send_message(WidgetMessage::remove(container, MessageDirection::ToWidget));
+            let item = self.items.remove(index);
+            self.sync_placeholder(ui);
+            Some(item)
+        } else {
+            None
+        }
+    }
+
+    /// Removes every item from UI.
+    pub fn clear(&mut self, ui: &UserInterface<M, C>) {
+        for &container in self.item_containers.iter() {
+            ui.send_message(WidgetMessage::remove(container, MessageDirection::ToWidget));
+        }
+        self.items.clear();
+        self.item_containers.clear();
+        self.sync_placeholder(ui);
+    }
+
+    /// Moves every item container to a new items panel and returns previous panel. Caller
+    /// is responsible for placing new panel in the tree and removing the old one.
+    pub fn set_items_panel(
+        &mut self,
+        ui: &UserInterface<M, C>,
+        panel: Handle<UINode<M, C>>,
+    ) -> Handle<UINode<M, C>> {
+        for &container in self.item_containers.iter() {
+            ui.send_message(WidgetMessage::link(
+                container,
+                MessageDirection::ToWidget,
+                panel,
+            ));
+        }
+        std::mem::replace(&mut self.panel, panel)
+    }
+
+    pub fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
+        node_map.resolve(&mut self.panel);
+        node_map.resolve(&mut self.placeholder);
+        node_map.resolve_slice(&mut self.items);
+        node_map.resolve_slice(&mut self.item_containers);
+    }
+
+    pub fn remove_ref(&mut self, handle: Handle<UINode<M, C>>) {
+        if self.panel == handle {
+            self.panel = Handle::NONE;
+        }
+        if self.placeholder == handle {
+            self.placeholder = Handle::NONE;
+        }
+        if let Some(index) = self.index_of(handle) {
+            self.item_containers.remove(index);
+            self.items.remove(index);
+        }
+    }
+}
@@ -13,21 +13,29 @@ extern crate sysinfo;
 
 pub use rg3d_core as core;
 
+pub mod accessibility;
 pub mod border;
 pub mod brush;
 pub mod button;
 pub mod canvas;
+pub mod chart;
 pub mod check_box;
 pub mod color;
+pub mod console;
+pub mod data_grid;
 pub mod decorator;
+pub mod diagnostics;
 pub mod dock;
 pub mod draw;
+pub mod draw_surface;
 pub mod dropdown_list;
 pub mod expander;
 pub mod file_browser;
+pub mod flex_panel;
 pub mod formatted_text;
 pub mod grid;
 pub mod image;
+pub mod items_control;
 pub mod list_view;
 pub mod menu;
 pub mod message;
@@ -36,6 +44,8 @@ pub mod node;
 pub mod numeric;
 pub mod popup;
 pub mod progress_bar;
+pub mod range_slider;
+pub mod replay;
 pub mod scroll_bar;
 pub mod scroll_panel;
 pub mod scroll_viewer;
@@ -43,6 +53,7 @@ pub mod stack_panel;
 pub mod tab_control;
 pub mod text;
 pub mod text_box;
+pub mod touch;
 pub mod tree;
 pub mod ttf;
 pub mod utils;
@@ -52,9 +63,10 @@ pub mod widget;
 pub mod window;
 pub mod wrap_panel;
 
-use crate::core::algebra::Vector2;
+use crate::core::algebra::{Matrix3, Point2, Vector2};
 use crate::draw::Draw;
 use crate::{
+    accessibility::{AccessibleNode, AccessibleRole},
     brush::Brush,
     canvas::Canvas,
     core::{
@@ -63,21 +75,27 @@ use crate::{
         pool::{Handle, Pool},
         scope_profile,
     },
-    draw::{CommandTexture, DrawingContext},
+    diagnostics::LayoutWarning,
+    draw::{transform_bounds, CommandTexture, DrawingContext, SharedTexture},
+    formatted_text::FormattedTextBuilder,
+    grid::SharedSizeGroup,
     message::{
-        ButtonState, CursorIcon, KeyboardModifiers, MessageData, MessageDirection, MouseButton,
-        OsEvent, UiMessage, UiMessageData, WidgetMessage,
+        ButtonState, CursorIcon, KeyCode, KeyboardModifiers, MessageData, MessageDirection,
+        MouseButton, OsEvent, PopupMessage, TouchPhase, UiMessage, UiMessageData, WidgetMessage,
     },
     node::UINode,
+    replay::{EventRecording, RecordedEvent},
+    scroll_viewer::ScrollViewer,
+    touch::{TouchState, LONG_PRESS_TIME, PAN_THRESHOLD, TAP_TIME},
     ttf::{Font, SharedFont},
-    widget::{Widget, WidgetBuilder},
+    widget::{NavMode, TooltipPlacement, Widget, WidgetBuilder},
 };
 use rg3d_core::math::clampf;
 use std::{
-    cell::Cell,
+    cell::{Cell, RefCell},
     collections::{HashMap, VecDeque},
     fmt::Debug,
-    ops::{Deref, DerefMut, Index, IndexMut},
+    ops::{Add, Deref, DerefMut, Index, IndexMut},
     sync::{
         mpsc::{self, Receiver, Sender, TryRecvError},
         Arc, Mutex,
@@ -94,6 +112,7 @@ pub const COLOR_LIGHTER: Color = Color::opaque(80, 80, 80);
 pub const COLOR_LIGHTEST: Color = Color::opaque(95, 95, 95);
 pub const COLOR_BRIGHT: Color = Color::opaque(130, 130, 130);
 pub const COLOR_BRIGHT_BLUE: Color = Color::opaque(80, 118, 178);
+pub const COLOR_INACTIVE_SELECTION: Color = Color::opaque(100, 100, 100);
 pub const COLOR_TEXT: Color = Color::opaque(220, 220, 220);
 pub const COLOR_FOREGROUND: Color = Color::WHITE;
 
@@ -123,6 +142,55 @@ pub enum VerticalAlignment {
     Top,
     Center,
     Bottom,
+    /// Aligns text baselines of nodes in a row of a horizontal stack panel or a grid, see
+    /// `Control::baseline_offset`. Elsewhere it works as `Top`.
+    Baseline,
+}
+
+/// Common baseline of nodes with `VerticalAlignment::Baseline` in a row of a panel.
+#[derive(Copy, Clone, Default, Debug)]
+pub(in crate) struct BaselineGroup {
+    /// Largest distance from top of a node (including margin) to its baseline.
+    ascent: f32,
+    /// Largest distance from baseline to bottom of a node (including margin).
+    descent: f32,
+}
+
+impl BaselineGroup {
+    fn baseline_of<M: MessageData, C: Control<M, C>>(
+        node: &UINode<M, C>,
+        ui: &UserInterface<M, C>,
+    ) -> f32 {
+        let margin = node.margin();
+        let height = node.desired_size().y - margin.total_vertical();
+        // Nodes without text stand on the baseline.
+        margin.top + node.baseline_offset(ui).unwrap_or(height).min(height)
+    }
+
+    pub(in crate) fn add<M: MessageData, C: Control<M, C>>(
+        &mut self,
+        node: &UINode<M, C>,
+        ui: &UserInterface<M, C>,
+    ) {
+        let baseline = Self::baseline_of(node, ui);
+        self.ascent = self.ascent.max(baseline);
+        self.descent = self.descent.max(node.desired_size().y - baseline);
+    }
+
+    /// Height that is enough to fit every node of the group.
+    pub(in crate) fn height(&self) -> f32 {
+        self.ascent + self.descent
+    }
+
+    /// Vertical offset of a node from top of the row that puts baseline of the node on the
+    /// common baseline.
+    pub(in crate) fn offset<M: MessageData, C: Control<M, C>>(
+        &self,
+        node: &UINode<M, C>,
+        ui: &UserInterface<M, C>,
+    ) -> f32 {
+        self.ascent - Self::baseline_of(node, ui)
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -200,13 +268,76 @@ impl Thickness {
         }
     }
 
+    /// Creates thickness with given left and right sides.
+    pub fn horizontal(v: f32) -> Self {
+        Self {
+            left: v,
+            top: 0.0,
+            right: v,
+            bottom: 0.0,
+        }
+    }
+
+    /// Creates thickness with given top and bottom sides.
+    pub fn vertical(v: f32) -> Self {
+        Self {
+            left: 0.0,
+            top: v,
+            right: 0.0,
+            bottom: v,
+        }
+    }
+
     pub fn offset(&self) -> Vector2<f32> {
         Vector2::new(self.left, self.top)
     }
 
+    /// Returns sum of left and right sides.
+    pub fn total_horizontal(&self) -> f32 {
+        self.left + self.right
+    }
+
+    /// Returns sum of top and bottom sides.
+    pub fn total_vertical(&self) -> f32 {
+        self.top + self.bottom
+    }
+
     /// Returns margin for each axis.
     pub fn axes_margin(&self) -> Vector2<f32> {
-        Vector2::new(self.left + self.right, self.top + self.bottom)
+        Vector2::new(self.total_horizontal(), self.total_vertical())
+    }
+
+    /// Shrinks rectangle by the thickness on each side, size of the result is never negative.
+    pub fn deflate_rect(&self, rect: &Rect<f32>) -> Rect<f32> {
+        Rect::new(
+            rect.x() + self.left,
+            rect.y() + self.top,
+            (rect.w() - self.total_horizontal()).max(0.0),
+            (rect.h() - self.total_vertical()).max(0.0),
+        )
+    }
+
+    /// Grows rectangle by the thickness on each side.
+    pub fn inflate_rect(&self, rect: &Rect<f32>) -> Rect<f32> {
+        Rect::new(
+            rect.x() - self.left,
+            rect.y() - self.top,
+            rect.w() + self.total_horizontal(),
+            rect.h() + self.total_vertical(),
+        )
+    }
+}
+
+impl Add for Thickness {
+    type Output = Thickness;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            left: self.left + rhs.left,
+            top: self.top + rhs.top,
+            right: self.right + rhs.right,
+            bottom: self.bottom + rhs.bottom,
+        }
     }
 }
 
@@ -229,17 +360,19 @@ impl<M: MessageData, C: Control<M, C>> NodeHandleMapping<M, C> {
         self.hash_map.insert(old, new);
     }
 
+    /// Replaces handle with its new value. Handles that weren't mapped (for example handles
+    /// to nodes outside of copied sub-tree) are left untouched.
     pub fn resolve(&self, old: &mut Handle<UINode<M, C>>) {
         // None handles aren't mapped.
-        if old.is_some() {
-            *old = *self.hash_map.get(old).unwrap()
+        if let Some(new) = self.hash_map.get(old) {
+            *old = *new;
         }
     }
 
     pub fn resolve_cell(&self, old: &mut Cell<Handle<UINode<M, C>>>) {
         // None handles aren't mapped.
-        if old.get().is_some() {
-            old.set(*self.hash_map.get(&old.get()).unwrap())
+        if let Some(new) = self.hash_map.get(&old.get()) {
+            old.set(*new);
         }
     }
 
@@ -258,6 +391,12 @@ where
 {
     fn resolve(&mut self, _node_map: &NodeHandleMapping<M, C>) {}
 
+    /// Returns desired size of the control for given available size. Default implementation
+    /// measures children and returns size of the largest of them. This is called for every
+    /// kind of node including user nodes (`UINode::User`), so custom widgets can have their
+    /// own size without faking it with children. Handle of the node is available through
+    /// `self.handle()`, children must be measured with `ui.node(child).measure(ui, size)`
+    /// to have their desired sizes.
     fn measure_override(
         &self,
         ui: &UserInterface<M, C>,
@@ -268,6 +407,10 @@ where
         self.deref().measure_override(ui, available_size)
     }
 
+    /// Places children of the control into given final size and returns actual size of the
+    /// control. Default implementation stretches every child to the final size. As well as
+    /// `measure_override` it is called for user nodes too, children must be placed with
+    /// `ui.node(child).arrange(ui, &rect)`.
     fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
         scope_profile!();
 
@@ -277,6 +420,11 @@ where
     fn arrange(&self, ui: &UserInterface<M, C>, final_rect: &Rect<f32>) {
         scope_profile!();
 
+        let _depth_guard = match ui.enter_layout_level(self.handle()) {
+            Some(guard) => guard,
+            None => return,
+        };
+
         if self.is_arrange_valid(ui) && self.prev_arrange.get() == *final_rect {
             return;
         }
@@ -285,13 +433,10 @@ where
             self.prev_arrange.set(*final_rect);
 
             let margin = self.margin().axes_margin();
+            let available_rect = self.margin().deflate_rect(final_rect);
+            let available_size = available_rect.size;
 
-            let mut size = Vector2::new(
-                (final_rect.w() - margin.x).max(0.0),
-                (final_rect.h() - margin.y).max(0.0),
-            );
-
-            let available_size = size;
+            let mut size = available_size;
 
             if self.horizontal_alignment() != HorizontalAlignment::Stretch {
                 size.x = size.x.min(self.desired_size().x - margin.x);
@@ -309,10 +454,11 @@ where
 
             size = self.arrange_override(ui, size);
 
-            size.x = size.x.min(final_rect.w());
-            size.y = size.y.min(final_rect.h());
+            // Margin is outside of the node, so the node must fit into the rest of the rect.
+            size.x = size.x.min(available_size.x);
+            size.y = size.y.min(available_size.y);
 
-            let mut origin = final_rect.position + self.margin().offset();
+            let mut origin = available_rect.position;
 
             match self.horizontal_alignment() {
                 HorizontalAlignment::Center | HorizontalAlignment::Stretch => {
@@ -365,13 +511,34 @@ where
     fn measure(&self, ui: &UserInterface<M, C>, available_size: Vector2<f32>) {
         scope_profile!();
 
-        if self.is_measure_valid(ui) && self.prev_measure.get() == available_size {
-            return;
+        let _depth_guard = match ui.enter_layout_level(self.handle()) {
+            Some(guard) => guard,
+            None => return,
+        };
+
+        // Pass is consumed here, so descendants of this node are measured in a normal pass
+        // unless their parent says otherwise.
+        let pass = ui.measure_pass.replace(MeasurePass::Normal);
+
+        let mut cache = self.measure_cache.get();
+        if self.is_measure_valid(ui) {
+            if let Some((input, desired_size)) = cache[pass as usize] {
+                if is_same_measure_input(input, available_size) {
+                    ui.measure_cache_hits.set(ui.measure_cache_hits.get() + 1);
+                    // Node could be measured in another pass after this result was cached.
+                    self.commit_measure(desired_size);
+                    return;
+                }
+            }
+        } else {
+            // Results of every pass are outdated.
+            cache = Default::default();
         }
 
-        if self.visibility() {
-            self.prev_measure.set(available_size);
+        ui.measure_cache_misses
+            .set(ui.measure_cache_misses.get() + 1);
 
+        if self.visibility() {
             let axes_margin = self.margin().axes_margin();
             let mut inner_size = available_size - axes_margin;
             inner_size.x = inner_size.x.max(0.0);
@@ -393,7 +560,16 @@ where
             size.x = clampf(size.x, self.min_size().x, self.max_size().x);
             size.y = clampf(size.y, self.min_size().y, self.max_size().y);
 
-            let mut desired_size = self.measure_override(ui, size);
+            let mut desired_size = match self.measure_override_size() {
+                Some(forced_size) => {
+                    // Children must have desired size to be arranged, but it is ignored.
+                    for &child in self.children() {
+                        ui.node(child).measure(ui, forced_size);
+                    }
+                    forced_size
+                }
+                None => self.measure_override(ui, size),
+            };
 
             if !self.width().is_nan() {
                 desired_size.x = self.width();
@@ -411,13 +587,24 @@ where
             desired_size.y = desired_size.y.min(available_size.y);
 
             self.commit_measure(desired_size);
+            cache[pass as usize] = Some((available_size, desired_size));
+            self.measure_cache.set(cache);
         } else {
             self.commit_measure(Vector2::new(0.0, 0.0));
+            self.measure_cache.set(Default::default());
         }
     }
 
+    /// Emits geometry of the control, it is drawn below children of the control. Like layout
+    /// methods it is called for user nodes too, screen bounds of the node are available
+    /// through `self.screen_bounds()`.
     fn draw(&self, _drawing_context: &mut DrawingContext) {}
 
+    /// Draws additional geometry on top of children of the widget. It is useful for effects
+    /// that must overlap content (fading, highlighting, etc). Geometry emitted here does not
+    /// participate in hit testing.
+    fn draw_overlay(&self, _drawing_context: &mut DrawingContext) {}
+
     fn update(&mut self, _dt: f32) {}
 
     /// Performs event-specific actions. Must call widget.handle_message()!
@@ -427,6 +614,42 @@ where
     /// Do *not* try to borrow node by `self_handle` in UI - at this moment node has been moved
     /// out of pool and attempt of borrowing will cause panic! `self_handle` should be used only
     /// to check if event came from/for this node or to capture input on node.
+    ///
+    /// Changes of other nodes or of the tree structure (for example closing a window that
+    /// contains this node) must be deferred by [`UserInterface::post`](struct.UserInterface.html#method.post)
+    /// or requested by sending a message through `ui.send_message`.
+    ///
+    /// There is no separate user-data argument: state that a handler needs is kept in fields
+    /// of the custom control (`C`) or sent to game code in messages of type `M`. Passing
+    /// arbitrary user data into handlers is out of scope.
+    ///
+    /// # Migration
+    ///
+    /// Code that changed the UI directly from a message handler, like removing a dialog when
+    /// its button is clicked, should post a command instead:
+    ///
+    /// ```no_run
+    /// use rg3d_ui::{
+    ///     core::pool::Handle,
+    ///     message::{ButtonMessage, UiMessage, UiMessageData},
+    ///     node::{StubNode, UINode},
+    ///     UiCommand, UserInterface,
+    /// };
+    ///
+    /// fn on_message(
+    ///     ui: &mut UserInterface<(), StubNode>,
+    ///     message: &UiMessage<(), StubNode>,
+    ///     ok: Handle<UINode<(), StubNode>>,
+    ///     dialog: Handle<UINode<(), StubNode>>,
+    /// ) {
+    ///     if message.destination() == ok {
+    ///         if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
+    ///             // Button is a part of the dialog, so the dialog is removed later.
+    ///             ui.post(UiCommand::Remove(dialog));
+    ///         }
+    ///     }
+    /// }
+    /// ```
     fn handle_routed_message(
         &mut self,
         ui: &mut UserInterface<M, C>,
@@ -467,12 +690,64 @@ where
     /// Called when a node is deleted from container thus giving a chance to remove dangling
     /// handles which may cause panic.
     fn remove_ref(&mut self, _handle: Handle<UINode<M, C>>) {}
+
+    /// Returns distance from top of the node (without margin) to baseline of its text, it is
+    /// used to align nodes with `VerticalAlignment::Baseline`. Nodes without text return `None`
+    /// and their bottom edge is used as baseline.
+    fn baseline_offset(&self, _ui: &UserInterface<M, C>) -> Option<f32> {
+        None
+    }
+
+    /// Semantic role of the widget that will be used in accessibility tree.
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Generic
+    }
+
+    /// Label of the widget for accessibility tree. Widgets which are labeled by their
+    /// content (buttons, etc.) may return `None`, in this case label will be taken from
+    /// content.
+    fn accessible_name(&self) -> Option<String> {
+        None
+    }
+
+    /// Current value or state of the widget for accessibility tree.
+    fn accessible_value(&self) -> Option<String> {
+        None
+    }
+}
+
+pub type CommandFn<M, C> = dyn FnOnce(&mut UserInterface<M, C>);
+
+/// Intercepts input messages of a sub-tree, see
+/// [`UserInterface::set_input_filter`](struct.UserInterface.html#method.set_input_filter).
+pub type InputFilter<M, C> = dyn FnMut(&UiMessage<M, C>) -> bool;
+
+type BoxedInputFilter<M, C> = Box<InputFilter<M, C>>;
+
+/// Deferred change of the UI, see [`UserInterface::post`](struct.UserInterface.html#method.post).
+pub enum UiCommand<M: MessageData, C: Control<M, C>> {
+    /// Removes a node with all its descendants.
+    Remove(Handle<UINode<M, C>>),
+    /// Attaches a node to a new parent.
+    Link {
+        child: Handle<UINode<M, C>>,
+        parent: Handle<UINode<M, C>>,
+        in_front: bool,
+    },
+    /// Detaches a node from its parent and attaches it to the root canvas.
+    Unlink(Handle<UINode<M, C>>),
+    /// Arbitrary change of the UI (properties of nodes, etc).
+    Apply(Box<CommandFn<M, C>>),
 }
 
 pub struct DragContext<M: MessageData, C: Control<M, C>> {
     is_dragging: bool,
     drag_node: Handle<UINode<M, C>>,
     click_pos: Vector2<f32>,
+    /// Cursor went farther than drag threshold since last press of left mouse button.
+    threshold_exceeded: bool,
+    /// Drag of some node was started since last press of left mouse button.
+    drag_started: bool,
 }
 
 impl<M: MessageData, C: Control<M, C>> Default for DragContext<M, C> {
@@ -481,6 +756,8 @@ impl<M: MessageData, C: Control<M, C>> Default for DragContext<M, C> {
             is_dragging: false,
             drag_node: Default::default(),
             click_pos: Vector2::new(0.0, 0.0),
+            threshold_exceeded: false,
+            drag_started: false,
         }
     }
 }
@@ -552,11 +829,31 @@ pub struct RestrictionEntry<M: MessageData, C: Control<M, C>> {
     pub stop: bool,
 }
 
+/// Outline that is drawn around focused widget when focus was changed by keyboard.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FocusVisual {
+    pub color: Color,
+    pub thickness: f32,
+}
+
+/// Distance between bounds of focused widget and its focus outline.
+const FOCUS_VISUAL_OUTSET: f32 = 2.0;
+
+/// Time (in seconds) that mouse should hover a widget before its tooltip will be shown.
+const TOOLTIP_DELAY: f32 = 0.55;
+
+/// Distance between the cursor and a tooltip, so the tooltip won't overlap the cursor.
+const TOOLTIP_CURSOR_OFFSET: f32 = 16.0;
+
 pub struct UserInterface<M: MessageData, C: Control<M, C>> {
     screen_size: Vector2<f32>,
     nodes: Pool<UINode<M, C>>,
     drawing_context: DrawingContext,
     visual_debug: bool,
+    /// See `set_inspect_mode`.
+    inspect_mode: bool,
+    /// Node under cursor in inspect mode.
+    inspected_node: Handle<UINode<M, C>>,
     root_canvas: Handle<UINode<M, C>>,
     picked_node: Handle<UINode<M, C>>,
     prev_picked_node: Handle<UINode<M, C>>,
@@ -566,12 +863,264 @@ pub struct UserInterface<M: MessageData, C: Control<M, C>> {
     receiver: Receiver<UiMessage<M, C>>,
     sender: Sender<UiMessage<M, C>>,
     stack: Vec<Handle<UINode<M, C>>>,
+    /// Roots of sub-trees drawn in separate layers sorted by layer, see
+    /// `WidgetBuilder::with_render_layer`. Collected again when hierarchy, z-index or render
+    /// layer of some node has changed.
+    layer_roots: Vec<Handle<UINode<M, C>>>,
+    layer_roots_dirty: bool,
     picking_stack: Vec<RestrictionEntry<M, C>>,
     bubble_queue: VecDeque<Handle<UINode<M, C>>>,
     drag_context: DragContext<M, C>,
     mouse_state: MouseState,
     keyboard_modifiers: KeyboardModifiers,
     cursor_icon: CursorIcon,
+    cursor_visible: bool,
+    scale_factor: f32,
+    /// Size of the screen in physical units, as it was passed to `update`.
+    physical_screen_size: Vector2<f32>,
+    /// See `set_reference_resolution`.
+    reference_resolution: Option<(Vector2<f32>, ScaleMode)>,
+    /// Transform from logical units to physical: physical = logical * scale + offset.
+    screen_scale: Vector2<f32>,
+    screen_offset: Vector2<f32>,
+    focus_visual: Option<FocusVisual>,
+    /// Whether keyboard focus was changed by keyboard navigation last time.
+    keyboard_navigation: bool,
+    /// See `set_focus_follows_mouse`.
+    focus_follows_mouse: bool,
+    unfocus_on_mouse_leave: bool,
+    /// Focusable node under the cursor, used by focus-follows-mouse mode.
+    hovered_focusable: Handle<UINode<M, C>>,
+    /// Widget under the cursor which has a tooltip.
+    tooltip_owner: Handle<UINode<M, C>>,
+    /// Tooltip that is currently on screen.
+    shown_tooltip: Handle<UINode<M, C>>,
+    tooltip_time: f32,
+    commands: VecDeque<UiCommand<M, C>>,
+    /// Sizes of shared size groups of grids, see `Column::with_shared_size_group`.
+    shared_sizes: HashMap<SharedSizeGroup, f32>,
+    touch_state: TouchState<M, C>,
+    /// Area that was changed during last `draw`, see `dirty_region`.
+    dirty_region: Option<Rect<f32>>,
+    /// Area that was changed by removed nodes or screen resize before next `draw`.
+    pending_dirty_region: Option<Rect<f32>>,
+    /// Whether `WidgetMessage::NodeAdded` and `WidgetMessage::NodeRemoved` are emitted, see
+    /// `set_structure_notifications`.
+    structure_notifications: bool,
+    /// Last value change notification of every node, see `Widget::set_event_coalescing`.
+    last_value_changes: HashMap<Handle<UINode<M, C>>, UiMessageData<M, C>>,
+    /// Sub-trees that are drawn separately, see `draw_subtree`.
+    offscreen: HashMap<Handle<UINode<M, C>>, OffscreenSubtree>,
+    /// Active focus scopes in order of activation, see `push_focus_scope`.
+    focus_scopes: Vec<FocusScopeEntry<M, C>>,
+    /// Open popups in order of opening, see `open_popup`.
+    popup_stack: Vec<OpenPopup<M, C>>,
+    /// See `set_input_filter`.
+    input_filters: HashMap<Handle<UINode<M, C>>, BoxedInputFilter<M, C>>,
+    /// Events recorded since `record_events(true)`.
+    recording: EventRecording,
+    /// Time passed since recording was started, `None` if recording is not active.
+    recording_time: Option<f32>,
+    /// Events left to replay with time passed since replay was started.
+    replay: VecDeque<RecordedEvent>,
+    replay_time: f32,
+    /// Nodes that are continuously rotated, see `start_spin`.
+    spins: HashMap<Handle<UINode<M, C>>, Spin>,
+    /// See `add_draw_hook`.
+    draw_hooks: Vec<Box<DrawHook>>,
+    texture_size_provider: Option<Box<TextureSizeProvider>>,
+    /// Images whose texture size was unknown at last measure, see `update_image_sizes`.
+    awaiting_texture_size: RefCell<Vec<Handle<UINode<M, C>>>>,
+    texture_alpha_provider: Option<Box<TextureAlphaProvider>>,
+    /// Last pressed key that is still held, only this key is repeated.
+    held_key: Option<HeldKey>,
+    key_repeat_delay: f32,
+    key_repeat_rate: f32,
+    /// See `set_drag_threshold`.
+    drag_threshold: f32,
+    /// Nodes that follow the cursor, see `attach_to_cursor`.
+    cursor_attachments: HashMap<Handle<UINode<M, C>>, CursorAttachment>,
+    /// See `set_input_enabled`.
+    input_enabled: bool,
+    /// Whether the cursor was moved at least once, hover is not refreshed until then.
+    cursor_position_known: bool,
+    /// See `consumed_input_this_frame`.
+    input_consumed: bool,
+    /// Localized strings by their keys, see `set_localization`.
+    localization: HashMap<String, String>,
+    /// Font of text widgets that are built without explicit font, `DEFAULT_FONT` if not set.
+    default_font: Option<SharedFont>,
+    /// Fonts for symbols that are missing in font of a text, see `add_fallback_font`.
+    fallback_fonts: Vec<SharedFont>,
+    /// Pass in which next call of `Control::measure` is made, see `MeasurePass`.
+    measure_pass: Cell<MeasurePass>,
+    measure_cache_hits: Cell<usize>,
+    measure_cache_misses: Cell<usize>,
+    /// Current depth of measure or arrange recursion, see `set_max_layout_depth`.
+    layout_depth: Cell<usize>,
+    max_layout_depth: usize,
+    /// Depth limit was hit during current layout update, it is reported only once per update.
+    layout_depth_exceeded: Cell<bool>,
+    /// Count of nested batches, see `begin_batch`.
+    batch_depth: usize,
+    /// Messages that need settled layout, they're sent again when the batch is finished.
+    batch_deferred: RefCell<Vec<UiMessage<M, C>>>,
+    /// Amount of layout updates made after handling of messages since last update, see
+    /// `poll_message`.
+    message_layout_passes: usize,
+}
+
+/// Defines how the UI with reference resolution is fitted into the screen, see
+/// `UserInterface::set_reference_resolution`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Whole UI is visible, aspect ratio is preserved. Free space at the sides (or at the top
+    /// and bottom) is left empty (letterbox bars).
+    FitInside,
+    /// Whole screen is covered, aspect ratio is preserved. Parts of the UI that do not fit
+    /// are cropped.
+    FitOutside,
+    /// Whole screen is covered, aspect ratio is not preserved.
+    Stretch,
+}
+
+/// Kind of measurement pass that produced cached desired size of a node. Star-sized grids
+/// measure their children twice: first with relaxed constraints to find out size of content,
+/// then with actual size of cells. Results of these passes must not be mixed even if the
+/// available size happens to be the same.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum MeasurePass {
+    #[default]
+    Normal,
+    /// Measurement of content with relaxed constraints.
+    Content,
+    /// Measurement with constraints of a cell with known size.
+    Constrained,
+}
+
+impl MeasurePass {
+    /// Amount of kinds of passes, every kind has its own slot in measure cache of a node.
+    pub(in crate) const COUNT: usize = 3;
+}
+
+/// Available and desired sizes of last measurement of a node in every kind of pass, indexed
+/// by `MeasurePass`.
+pub(in crate) type MeasureCache = [Option<(Vector2<f32>, Vector2<f32>)>; MeasurePass::COUNT];
+
+/// Maximum amount of measure and arrange passes per update, see `UserInterface::update_layout`.
+const MAX_LAYOUT_ITERATIONS: usize = 4;
+
+/// Default maximum depth of measure and arrange recursion, see
+/// `UserInterface::set_max_layout_depth`.
+pub const DEFAULT_MAX_LAYOUT_DEPTH: usize = 256;
+
+/// Decreases depth of layout recursion when measure or arrange of a node is finished.
+struct LayoutDepthGuard<'a>(&'a Cell<usize>);
+
+impl<'a> Drop for LayoutDepthGuard<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
+/// Available sizes that differ less than this value are considered equal, so cached
+/// measurement results survive rounding errors.
+const MEASURE_EPSILON: f32 = 0.001;
+
+fn is_same_measure_input(a: Vector2<f32>, b: Vector2<f32>) -> bool {
+    // Exact comparison is required for infinite constraints.
+    (a.x == b.x || (a.x - b.x).abs() <= MEASURE_EPSILON)
+        && (a.y == b.y || (a.y - b.y).abs() <= MEASURE_EPSILON)
+}
+
+/// Returns smallest rectangle that contains both rectangles.
+fn union_rects(a: Rect<f32>, b: Rect<f32>) -> Rect<f32> {
+    let left = a.x().min(b.x());
+    let top = a.y().min(b.y());
+    let right = (a.x() + a.w()).max(b.x() + b.w());
+    let bottom = (a.y() + a.h()).max(b.y() + b.h());
+    Rect::new(left, top, right - left, bottom - top)
+}
+
+/// Returns common part of given rectangles or `None` if they do not intersect.
+fn intersect_rects(a: Rect<f32>, b: Rect<f32>) -> Option<Rect<f32>> {
+    let left = a.x().max(b.x());
+    let top = a.y().max(b.y());
+    let right = (a.x() + a.w()).min(b.x() + b.w());
+    let bottom = (a.y() + a.h()).min(b.y() + b.h());
+    if right > left && bottom > top {
+        Some(Rect::new(left, top, right - left, bottom - top))
+    } else {
+        None
+    }
+}
+
+/// Continuous rotation of a node, see `UserInterface::start_spin`.
+#[derive(Copy, Clone, Debug)]
+struct Spin {
+    /// Radians per second.
+    speed: f32,
+    angle: f32,
+}
+
+/// Callback that is called with drawing context at the end of `UserInterface::draw`, see
+/// `UserInterface::add_draw_hook`.
+pub type DrawHook = dyn FnMut(&mut DrawingContext);
+
+/// Returns size of a texture in pixels or `None` if the texture is not loaded yet, see
+/// `UserInterface::set_texture_size_provider`.
+pub type TextureSizeProvider = dyn Fn(&SharedTexture) -> Option<Vector2<f32>>;
+
+/// Returns alpha (in `[0; 1]` range) of a texture at given texture coordinates or `None` if
+/// it is unknown, see `UserInterface::set_texture_alpha_provider`.
+pub type TextureAlphaProvider = dyn Fn(&SharedTexture, Vector2<f32>) -> Option<f32>;
+
+/// Default delay before first repeat of held key, in seconds.
+pub const DEFAULT_KEY_REPEAT_DELAY: f32 = 0.4;
+/// Default amount of repeats of held key per second.
+pub const DEFAULT_KEY_REPEAT_RATE: f32 = 30.0;
+
+/// Default distance (in logical units) that cursor must pass with pressed button to start
+/// dragging, see `UserInterface::set_drag_threshold`.
+pub const DEFAULT_DRAG_THRESHOLD: f32 = 4.0;
+
+/// Key that is held down and repeated, see `UserInterface::set_key_repeat`.
+#[derive(Copy, Clone)]
+struct HeldKey {
+    key: KeyCode,
+    /// Time left until next repeat.
+    timer: f32,
+}
+
+/// Node that follows the cursor, see `UserInterface::attach_to_cursor`.
+struct CursorAttachment {
+    offset: Vector2<f32>,
+    /// Hit test visibility of the node before it was attached.
+    was_hit_test_visible: bool,
+}
+
+/// Focus scope that was activated by `UserInterface::push_focus_scope`.
+#[derive(Copy, Clone, Debug)]
+struct FocusScopeEntry<M: MessageData, C: Control<M, C>> {
+    handle: Handle<UINode<M, C>>,
+    /// Node that had keyboard focus when the scope was activated.
+    prev_focus: Handle<UINode<M, C>>,
+}
+
+/// Popup that is tracked by popup manager of the UI.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OpenPopup<M: MessageData, C: Control<M, C>> {
+    pub handle: Handle<UINode<M, C>>,
+    /// Popup will be closed by a click outside of it or by Escape key.
+    pub auto_close: bool,
+}
+
+/// Sub-tree that is drawn into its own drawing context, see `UserInterface::draw_subtree`.
+struct OffscreenSubtree {
+    size: Vector2<f32>,
+    /// Sub-tree has changed since it was drawn last time.
+    dirty: bool,
+    drawing_context: DrawingContext,
 }
 
 lazy_static! {
@@ -582,9 +1131,52 @@ lazy_static! {
     };
 }
 
+/// Returns true if child with given render layer must be skipped while drawing (or picking)
+/// contents of `layer`, because it starts its own layer. `None` layer means "all layers".
+fn is_other_layer(layer: Option<u8>, child_layer: u8) -> bool {
+    match layer {
+        Some(layer) => child_layer != 0 && child_layer != layer,
+        None => false,
+    }
+}
+
+/// Returns layer in which node is drawn - first non-zero render layer up on tree.
+fn effective_render_layer<M: MessageData, C: Control<M, C>>(
+    nodes: &Pool<UINode<M, C>>,
+    mut handle: Handle<UINode<M, C>>,
+) -> u8 {
+    while let Some(node) = nodes.try_borrow(handle) {
+        if node.render_layer() != 0 {
+            return node.render_layer();
+        }
+        handle = node.parent();
+    }
+    0
+}
+
+/// Collects roots of sub-trees that are drawn in a layer other than layer of their parent,
+/// in tree order.
+fn collect_layer_roots<M: MessageData, C: Control<M, C>>(
+    nodes: &Pool<UINode<M, C>>,
+    node_handle: Handle<UINode<M, C>>,
+    layer: u8,
+    layer_roots: &mut Vec<Handle<UINode<M, C>>>,
+) {
+    for &child in nodes[node_handle].children() {
+        let child_layer = nodes[child].render_layer();
+        if is_other_layer(Some(layer), child_layer) {
+            layer_roots.push(child);
+            collect_layer_roots(nodes, child, child_layer, layer_roots);
+        } else {
+            collect_layer_roots(nodes, child, layer, layer_roots);
+        }
+    }
+}
+
 fn draw_node<M: MessageData, C: Control<M, C>>(
     nodes: &Pool<UINode<M, C>>,
     node_handle: Handle<UINode<M, C>>,
+    layer: Option<u8>,
     drawing_context: &mut DrawingContext,
 ) {
     scope_profile!();
@@ -615,6 +1207,12 @@ fn draw_node<M: MessageData, C: Control<M, C>>(
     } else {
         0.4
     });
+    drawing_context.push_tint(node.tint());
+
+    let transformed = *node.render_transform() != Matrix3::identity();
+    if transformed {
+        drawing_context.push_transform(local_render_transform(node));
+    }
 
     node.draw(drawing_context);
 
@@ -625,15 +1223,109 @@ fn draw_node<M: MessageData, C: Control<M, C>>(
 
     // Continue on children
     for &child_node in node.children().iter() {
-        // Do not continue render of top-most nodes - they'll be rendered in separate pass.
-        if !nodes[child_node].is_draw_on_top() {
-            draw_node(nodes, child_node, drawing_context);
+        // Do not continue render of top-most nodes and nodes of other layers - they'll be
+        // rendered in separate passes.
+        let child = &nodes[child_node];
+        if !child.is_draw_on_top() && !is_other_layer(layer, child.render_layer()) {
+            draw_node(nodes, child_node, layer, drawing_context);
         }
     }
 
+    node.draw_overlay(drawing_context);
+
+    if transformed {
+        drawing_context.pop_transform();
+    }
+    drawing_context.pop_tint();
     drawing_context.pop_opacity();
 }
 
+/// Draws root of a sub-tree of other layer. The sub-tree is drawn in a separate pass, so state
+/// that is inherited from ancestors (render transform, tint and opacity) is pushed first.
+fn draw_layer_root<M: MessageData, C: Control<M, C>>(
+    nodes: &Pool<UINode<M, C>>,
+    layer_root: Handle<UINode<M, C>>,
+    drawing_context: &mut DrawingContext,
+) {
+    let mut ancestors = Vec::new();
+    let mut handle = nodes[layer_root].parent();
+    while let Some(node) = nodes.try_borrow(handle) {
+        ancestors.push(handle);
+        handle = node.parent();
+    }
+
+    for &ancestor in ancestors.iter().rev() {
+        let node = &nodes[ancestor];
+        drawing_context.push_opacity(if is_node_enabled(nodes, ancestor) {
+            node.opacity()
+        } else {
+            0.4
+        });
+        drawing_context.push_tint(node.tint());
+        drawing_context.push_transform(local_render_transform(node));
+    }
+
+    let layer = nodes[layer_root].render_layer();
+    draw_node(nodes, layer_root, Some(layer), drawing_context);
+
+    for _ in ancestors.iter() {
+        drawing_context.pop_transform();
+        drawing_context.pop_tint();
+        drawing_context.pop_opacity();
+    }
+}
+
+/// Returns render transform of given node in screen space, it is relative to center of the node.
+fn local_render_transform<M: MessageData, C: Control<M, C>>(node: &UINode<M, C>) -> Matrix3<f32> {
+    let center = node.screen_position + node.actual_size().scale(0.5);
+    Matrix3::new_translation(&center) * node.render_transform() * Matrix3::new_translation(&-center)
+}
+
+/// Returns transform that is used to draw given node, it combines render transforms of the node
+/// and all its ancestors.
+fn visual_transform<M: MessageData, C: Control<M, C>>(
+    nodes: &Pool<UINode<M, C>>,
+    node_handle: Handle<UINode<M, C>>,
+) -> Matrix3<f32> {
+    let mut transform = Matrix3::identity();
+    let mut handle = node_handle;
+    while let Some(node) = nodes.try_borrow(handle) {
+        if *node.render_transform() != Matrix3::identity() {
+            transform = local_render_transform(node) * transform;
+        }
+        handle = node.parent();
+    }
+    transform
+}
+
+/// Moves a point from screen coordinates to untransformed space of given node, geometry of
+/// nodes is stored without render transforms. Degenerate transform (zero scale) collapses the
+/// node to nothing, so there is no such point.
+fn to_node_space<M: MessageData, C: Control<M, C>>(
+    nodes: &Pool<UINode<M, C>>,
+    node_handle: Handle<UINode<M, C>>,
+    pt: Vector2<f32>,
+) -> Option<Vector2<f32>> {
+    visual_transform(nodes, node_handle)
+        .try_inverse()
+        .map(|inverse| inverse.transform_point(&Point2::from(pt)).coords)
+}
+
+fn is_repeatable(key: KeyCode) -> bool {
+    !matches!(
+        key,
+        KeyCode::LShift
+            | KeyCode::RShift
+            | KeyCode::LControl
+            | KeyCode::RControl
+            | KeyCode::LAlt
+            | KeyCode::RAlt
+            | KeyCode::LWin
+            | KeyCode::RWin
+            | KeyCode::Capital
+    )
+}
+
 fn is_node_enabled<M: MessageData, C: Control<M, C>>(
     nodes: &Pool<UINode<M, C>>,
     handle: Handle<UINode<M, C>>,
@@ -660,6 +1352,8 @@ impl<M: MessageData, C: Control<M, C>> UserInterface<M, C> {
             sender,
             receiver,
             visual_debug: false,
+            inspect_mode: false,
+            inspected_node: Handle::NONE,
             captured_node: Handle::NONE,
             root_canvas: Handle::NONE,
             nodes: Pool::new(),
@@ -668,13 +1362,69 @@ impl<M: MessageData, C: Control<M, C>> UserInterface<M, C> {
             picked_node: Handle::NONE,
             prev_picked_node: Handle::NONE,
             keyboard_focus_node: Handle::NONE,
+            focus_visual: None,
+            keyboard_navigation: false,
+            focus_follows_mouse: false,
+            unfocus_on_mouse_leave: false,
+            hovered_focusable: Handle::NONE,
+            tooltip_owner: Handle::NONE,
+            shown_tooltip: Handle::NONE,
+            tooltip_time: 0.0,
+            commands: Default::default(),
+            shared_sizes: Default::default(),
+            touch_state: Default::default(),
+            dirty_region: None,
+            pending_dirty_region: None,
+            structure_notifications: false,
+            last_value_changes: Default::default(),
+            offscreen: Default::default(),
+            focus_scopes: Default::default(),
+            popup_stack: Default::default(),
+            input_filters: Default::default(),
+            recording: Default::default(),
+            recording_time: None,
+            replay: Default::default(),
+            replay_time: 0.0,
+            spins: Default::default(),
+            cursor_attachments: Default::default(),
+            draw_hooks: Default::default(),
+            texture_size_provider: None,
+            awaiting_texture_size: Default::default(),
+            texture_alpha_provider: None,
+            held_key: None,
+            key_repeat_delay: DEFAULT_KEY_REPEAT_DELAY,
+            key_repeat_rate: DEFAULT_KEY_REPEAT_RATE,
+            drag_threshold: DEFAULT_DRAG_THRESHOLD,
+            input_enabled: true,
+            cursor_position_known: false,
+            input_consumed: false,
+            localization: Default::default(),
+            default_font: None,
+            fallback_fonts: Default::default(),
+            measure_pass: Cell::new(MeasurePass::Normal),
+            measure_cache_hits: Cell::new(0),
+            measure_cache_misses: Cell::new(0),
+            layout_depth: Cell::new(0),
+            max_layout_depth: DEFAULT_MAX_LAYOUT_DEPTH,
+            layout_depth_exceeded: Cell::new(false),
+            batch_depth: 0,
+            batch_deferred: Default::default(),
+            message_layout_passes: 0,
             stack: Default::default(),
+            layer_roots: Default::default(),
+            layer_roots_dirty: true,
             picking_stack: Default::default(),
             bubble_queue: Default::default(),
             drag_context: Default::default(),
             mouse_state: Default::default(),
             keyboard_modifiers: Default::default(),
             cursor_icon: Default::default(),
+            cursor_visible: true,
+            scale_factor: 1.0,
+            physical_screen_size: screen_size,
+            reference_resolution: None,
+            screen_scale: Vector2::new(1.0, 1.0),
+            screen_offset: Vector2::default(),
         };
         ui.root_canvas = ui.add_node(UINode::Canvas(Canvas::new(WidgetBuilder::new().build())));
         ui
@@ -703,6 +1453,25 @@ impl<M: MessageData, C: Control<M, C>> UserInterface<M, C> {
         self.captured_node = Handle::NONE;
     }
 
+    /// Takes mouse capture away from captured widget (or from `pressed` widget if nothing is
+    /// captured) and notifies it by `WidgetMessage::MouseCaptureLost`, so the widget will reset
+    /// its pressed state without performing any action. Used by widgets that intercept input of
+    /// their children, for example when scroll viewer starts to scroll by dragging.
+    pub fn cancel_mouse_capture(&mut self, pressed: Handle<UINode<M, C>>) {
+        let target = if self.captured_node.is_some() {
+            self.captured_node
+        } else {
+            pressed
+        };
+        self.captured_node = Handle::NONE;
+        if self.nodes.is_valid_handle(target) {
+            self.send_message(WidgetMessage::mouse_capture_lost(
+                target,
+                MessageDirection::FromWidget,
+            ));
+        }
+    }
+
     #[inline]
     pub fn get_drawing_context(&self) -> &DrawingContext {
         &self.drawing_context
@@ -718,10 +1487,14 @@ impl<M: MessageData, C: Control<M, C>> UserInterface<M, C> {
     }
 
     fn update_visibility(&mut self) {
+        self.update_visibility_from(self.root_canvas);
+    }
+
+    fn update_visibility_from(&mut self, root: Handle<UINode<M, C>>) {
         scope_profile!();
 
         self.stack.clear();
-        self.stack.push(self.root_canvas);
+        self.stack.push(root);
         while let Some(node_handle) = self.stack.pop() {
             let (widget, parent) = self
                 .nodes
@@ -742,10 +1515,15 @@ impl<M: MessageData, C: Control<M, C>> UserInterface<M, C> {
     }
 
     fn update_transform(&mut self) {
+        self.update_transform_from(self.root_canvas);
+    }
+
+    /// Calculates screen positions of every node in given sub-tree.
+    fn update_transform_from(&mut self, root: Handle<UINode<M, C>>) {
         scope_profile!();
 
         self.stack.clear();
-        self.stack.push(self.root_canvas);
+        self.stack.push(root);
         while let Some(node_handle) = self.stack.pop() {
             let (widget, parent) = self
                 .nodes
@@ -767,32 +1545,201 @@ impl<M: MessageData, C: Control<M, C>> UserInterface<M, C> {
         }
     }
 
+    /// Runs measure pass for a sub-tree starting from given node and returns desired size of
+    /// the node. It allows to measure a node before placing it (popups, tooltips, etc).
+    /// Measured node must be visible, otherwise its desired size will be zero.
+    pub fn measure_node(
+        &self,
+        handle: Handle<UINode<M, C>>,
+        available_size: Vector2<f32>,
+    ) -> Vector2<f32> {
+        let node = self.node(handle);
+        node.measure(self, available_size);
+        node.desired_size()
+    }
+
+    /// Runs arrange pass for a sub-tree starting from given node. `final_rect` is in local
+    /// coordinates of parent of the node. Node must be measured first.
+    pub fn arrange_node(&mut self, handle: Handle<UINode<M, C>>, final_rect: &Rect<f32>) {
+        self.node(handle).arrange(self, final_rect);
+        self.update_transform_from(handle);
+    }
+
+    /// Returns final screen bounds of a node (in logical units) after layout. Returns `None`
+    /// if there is no such node or its layout is not valid (it was never arranged or it was
+    /// invalidated after last update).
+    pub fn node_screen_rect(&self, handle: Handle<UINode<M, C>>) -> Option<Rect<f32>> {
+        self.nodes
+            .try_borrow(handle)
+            .filter(|node| Widget::is_arrange_valid(node))
+            .map(|node| node.screen_bounds())
+    }
+
+    /// Transforms a point from local space of a node to screen space. Widgets are translated
+    /// during layout and then drawn with render transforms of the node and its ancestors (see
+    /// `Widget::set_render_transform`), the result is where the point is drawn.
+    pub fn node_to_screen(
+        &self,
+        handle: Handle<UINode<M, C>>,
+        local: Vector2<f32>,
+    ) -> Option<Vector2<f32>> {
+        let bounds = self.node_screen_rect(handle)?;
+        let transform = visual_transform(&self.nodes, handle);
+        Some(
+            transform
+                .transform_point(&Point2::from(bounds.position + local))
+                .coords,
+        )
+    }
+
+    /// Transforms a point from screen space to local space of a node, it is inverse of
+    /// `node_to_screen`. Returns `None` if render transforms of the node collapse it (it has
+    /// zero scale).
+    pub fn screen_to_node(
+        &self,
+        handle: Handle<UINode<M, C>>,
+        screen: Vector2<f32>,
+    ) -> Option<Vector2<f32>> {
+        let bounds = self.node_screen_rect(handle)?;
+        let inverse = visual_transform(&self.nodes, handle).try_inverse()?;
+        Some(inverse.transform_point(&Point2::from(screen)).coords - bounds.position)
+    }
+
+    /// Returns screen size in logical units, which is physical size of the screen divided by
+    /// current scale factor, or the reference resolution if it is set.
     pub fn screen_size(&self) -> Vector2<f32> {
         self.screen_size
     }
 
-    pub fn update(&mut self, screen_size: Vector2<f32>, dt: f32) {
-        scope_profile!();
+    /// Sets new scale factor of the whole UI. Every node will be measured and arranged in
+    /// logical units, and the final geometry will be scaled by given factor, so a node with
+    /// logical size of 100 px will occupy 200 px on screen with scale factor of 2.0. This is
+    /// useful on high-DPI displays. Changing scale factor invalidates layout of every node.
+    /// Scale factor is ignored while reference resolution is set.
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        let scale_factor = scale_factor.max(f32::EPSILON);
+        if (self.scale_factor - scale_factor).abs() > f32::EPSILON {
+            self.scale_factor = scale_factor;
+            self.update_screen_transform();
+        }
+    }
 
-        self.screen_size = screen_size;
-        self.update_visibility();
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
 
-        for n in self.nodes.iter() {
-            if !n.is_globally_visible() && n.prev_global_visibility == n.is_globally_visible() {
-                n.commit_measure(Vector2::default());
-                n.commit_arrange(Vector2::new(0.0, 0.0), Vector2::default());
+    /// Makes the UI to be laid out at given resolution (for example, 1920x1080) regardless of
+    /// size of the screen, the UI is then scaled and centered on the screen using given mode.
+    /// It lets UIs authored at a design resolution look the same at any window size. Cursor
+    /// and touch positions are mapped back to the reference resolution automatically.
+    pub fn set_reference_resolution(&mut self, resolution: Vector2<f32>, mode: ScaleMode) {
+        let resolution = Vector2::new(resolution.x.max(1.0), resolution.y.max(1.0));
+        self.reference_resolution = Some((resolution, mode));
+        self.update_screen_transform();
+    }
+
+    /// Returns the UI to the usual behavior when its logical size is defined by size of the
+    /// screen and scale factor.
+    pub fn clear_reference_resolution(&mut self) {
+        if self.reference_resolution.take().is_some() {
+            self.update_screen_transform();
+        }
+    }
+
+    pub fn reference_resolution(&self) -> Option<(Vector2<f32>, ScaleMode)> {
+        self.reference_resolution
+    }
+
+    /// Converts a point from logical units (used by layout) to physical units of the screen.
+    pub fn logical_to_physical(&self, point: Vector2<f32>) -> Vector2<f32> {
+        point.component_mul(&self.screen_scale) + self.screen_offset
+    }
+
+    /// Converts a point from physical units of the screen (cursor position, etc) to logical
+    /// units.
+    pub fn physical_to_logical(&self, point: Vector2<f32>) -> Vector2<f32> {
+        (point - self.screen_offset).component_div(&self.screen_scale)
+    }
+
+    /// Recalculates logical size of the screen and transform to physical units from physical
+    /// size of the screen, scale factor and reference resolution.
+    fn update_screen_transform(&mut self) {
+        let physical_cursor = self.logical_to_physical(self.cursor_position);
+        let size = self.physical_screen_size;
+
+        let (screen_size, scale, offset) = match self.reference_resolution {
+            Some((resolution, mode)) => {
+                let fit = size.component_div(&resolution);
+                let scale = match mode {
+                    ScaleMode::FitInside => Vector2::repeat(fit.x.min(fit.y)),
+                    ScaleMode::FitOutside => Vector2::repeat(fit.x.max(fit.y)),
+                    ScaleMode::Stretch => fit,
+                };
+                let scale = scale.map(|s| s.max(f32::EPSILON));
+                let offset = (size - resolution.component_mul(&scale)).scale(0.5);
+                (resolution, scale, offset)
+            }
+            None => (
+                size.scale(1.0 / self.scale_factor),
+                Vector2::repeat(self.scale_factor),
+                Vector2::default(),
+            ),
+        };
+
+        let changed = self.screen_scale != scale || self.screen_offset != offset;
+        self.screen_scale = scale;
+        self.screen_offset = offset;
+        self.cursor_position = self.physical_to_logical(physical_cursor);
+        if self.screen_size != screen_size {
+            self.mark_dirty(Rect::new(0.0, 0.0, screen_size.x, screen_size.y));
+        }
+        self.screen_size = screen_size;
+        if changed {
+            for node in self.nodes.iter() {
+                node.invalidate_layout();
             }
         }
+    }
 
-        self.node(self.root_canvas).measure(self, screen_size);
-        self.node(self.root_canvas)
-            .arrange(self, &Rect::new(0.0, 0.0, screen_size.x, screen_size.y));
-        self.update_transform();
+    /// Updates layout of the whole UI. `screen_size` is a physical size of the screen.
+    pub fn update(&mut self, screen_size: Vector2<f32>, dt: f32) {
+        scope_profile!();
+
+        if self.physical_screen_size != screen_size {
+            self.physical_screen_size = screen_size;
+            self.update_screen_transform();
+        }
+        let screen_size = self.screen_size;
+        self.input_consumed = false;
+        self.message_layout_passes = 0;
+        self.update_replay(dt);
+        self.apply_commands();
+        self.update_touch(dt);
+        self.update_kinetic_scrolling(dt);
+        self.update_drag_edge_scrolling(dt);
+        self.update_scroll_bar_repeat(dt);
+        self.update_spins(dt);
+        self.update_key_repeat(dt);
+        self.update_cursor_attachments();
+        self.update_image_sizes();
+        self.update_tooltip(dt);
+        self.update_localization();
+        self.settle_layout();
 
         for node in self.nodes.iter_mut() {
             node.update(dt)
         }
 
+        // Nodes could change their content while updating, the frame must be displayed with
+        // settled layout.
+        if !self.is_layout_valid() {
+            self.update_layout(screen_size);
+        }
+
+        // Layout could move nodes under stationary cursor, they're picked by geometry of
+        // the last drawn frame.
+        self.refresh_hover();
+
         if !self.drag_context.is_dragging {
             // Try to fetch new cursor icon starting from current picked node. Traverse
             // tree up until cursor with different value is found.
@@ -807,947 +1754,4326 @@ impl<M: MessageData, C: Control<M, C>> UserInterface<M, C> {
                 handle = node.parent();
             }
         }
-    }
 
-    pub fn cursor(&self) -> CursorIcon {
-        self.cursor_icon
+        self.cursor_visible = !self
+            .nodes
+            .try_borrow(self.captured_node)
+            .is_some_and(|node| node.hides_cursor());
     }
 
-    pub fn draw(&mut self) -> &DrawingContext {
-        scope_profile!();
+    /// Updates visibility and layout of the whole tree, unlike `update` it does not apply
+    /// deferred commands, run timers or update nodes. It is used when a message needs
+    /// up-to-date layout in the middle of message dispatch.
+    fn settle_layout(&mut self) {
+        self.update_visibility();
+        self.update_shared_size_groups();
 
-        self.calculate_clip_bounds(
-            self.root_canvas,
-            Rect::new(0.0, 0.0, self.screen_size.x, self.screen_size.y),
-        );
-        self.drawing_context.clear();
-
-        for node in self.nodes.iter_mut() {
-            node.command_indices.borrow_mut().clear();
+        for n in self.nodes.iter() {
+            if !n.is_globally_visible() && n.prev_global_visibility == n.is_globally_visible() {
+                n.commit_measure(Vector2::default());
+                n.commit_arrange(Vector2::new(0.0, 0.0), Vector2::default());
+            }
         }
 
-        // Draw everything except top-most nodes.
-        draw_node(&self.nodes, self.root_canvas, &mut self.drawing_context);
+        self.update_layout(self.screen_size);
+        self.update_layer_roots();
+    }
 
-        // Render top-most nodes in separate pass.
-        // TODO: This may give weird results because of invalid nesting.
-        self.stack.clear();
-        self.stack.push(self.root());
-        while let Some(node_handle) = self.stack.pop() {
-            let node = &self.nodes[node_handle];
-            if node.is_draw_on_top() {
-                draw_node(&self.nodes, node_handle, &mut self.drawing_context);
+    /// Checks if every visible node has valid layout. Unlike `Control::is_measure_valid` it
+    /// does not treat change of visibility as invalidation, because it is applied only once
+    /// per update.
+    fn is_layout_valid(&self) -> bool {
+        let mut stack = vec![self.root_canvas];
+        while let Some(handle) = stack.pop() {
+            let widget = self.nodes[handle].deref();
+            if !widget.is_globally_visible() {
+                continue;
             }
-            for &child in node.children() {
-                self.stack.push(child);
+            if !widget.is_measure_valid() || !widget.is_arrange_valid() {
+                return false;
             }
+            stack.extend_from_slice(widget.children());
         }
+        true
+    }
 
-        // Debug info rendered on top of other.
-        if self.visual_debug {
-            if self.picked_node.is_some() {
-                let bounds = self.nodes.borrow(self.picked_node).screen_bounds();
-                self.drawing_context.push_rect(&bounds, 1.0);
-                self.drawing_context.commit(
-                    bounds,
-                    Brush::Solid(Color::WHITE),
-                    CommandTexture::None,
-                    None,
-                );
+    /// Performs measure and arrange passes until layout stops changing. Nodes may invalidate
+    /// layout while it is being calculated (content that resizes in response to its own
+    /// layout, tooltip placement, etc.), without repeating the passes such changes would be
+    /// visible only in the next frame.
+    fn update_layout(&mut self, screen_size: Vector2<f32>) {
+        self.layout_depth.set(0);
+        self.layout_depth_exceeded.set(false);
+        let mut iteration = 0;
+        loop {
+            self.node(self.root_canvas).measure(self, screen_size);
+            // Tooltip must be measured first, its placement depends on its size.
+            self.place_tooltip();
+            self.node(self.root_canvas)
+                .arrange(self, &Rect::new(0.0, 0.0, screen_size.x, screen_size.y));
+
+            if self.is_layout_valid() {
+                break;
             }
 
-            if self.keyboard_focus_node.is_some() {
-                let bounds = self.nodes.borrow(self.keyboard_focus_node).screen_bounds();
-                self.drawing_context.push_rect(&bounds, 1.0);
-                self.drawing_context.commit(
-                    bounds,
-                    Brush::Solid(Color::GREEN),
-                    CommandTexture::None,
-                    None,
+            iteration += 1;
+            if iteration >= MAX_LAYOUT_ITERATIONS {
+                println!(
+                    "Layout did not settle in {} iterations, some node constantly invalidates it!",
+                    MAX_LAYOUT_ITERATIONS
                 );
+                break;
             }
         }
-
-        &self.drawing_context
+        self.update_transform();
     }
 
-    fn is_node_clipped(&self, node_handle: Handle<UINode<M, C>>, pt: Vector2<f32>) -> bool {
-        scope_profile!();
+    pub fn cursor(&self) -> CursorIcon {
+        self.cursor_icon
+    }
 
-        let mut clipped = true;
+    /// Returns whether OS cursor should be visible, it is hidden while mouse is captured by a
+    /// widget that hides cursor (see `Widget::set_hides_cursor`). Visibility is computed on
+    /// each update, so the cursor is shown again when the widget releases capture or is
+    /// removed. The UI only computes the flag, it must be applied to the window by the engine.
+    pub fn is_cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
 
-        let widget = self.nodes.borrow(node_handle);
+    pub fn touch_state(&self) -> &TouchState<M, C> {
+        &self.touch_state
+    }
 
-        if widget.is_globally_visible() {
-            clipped = !widget.screen_bounds().contains(pt);
+    fn update_touch(&mut self, dt: f32) {
+        self.touch_state.time += dt;
 
-            if !clipped {
-                for command_index in widget.command_indices.borrow().iter() {
-                    if let Some(command) = self.drawing_context.get_commands().get(*command_index) {
-                        if let Some(geometry) = command.clipping_geometry.as_ref() {
-                            if geometry.is_contains_point(pt) {
-                                clipped = false;
-                                break;
-                            }
-                        }
-                    }
+        if let Some(touch) = self.touch_state.primary_touch().cloned() {
+            if !self.touch_state.is_panning
+                && !self.touch_state.long_press_fired
+                && self.touch_state.time - touch.start_time >= LONG_PRESS_TIME
+                && touch.distance() < PAN_THRESHOLD
+            {
+                self.touch_state.long_press_fired = true;
+                if self.picked_node.is_some() {
+                    self.send_message(WidgetMessage::long_press(
+                        self.picked_node,
+                        MessageDirection::FromWidget,
+                        touch.position,
+                    ));
                 }
             }
+        }
+    }
 
-            // Point can be clipped by parent's clipping geometry.
-            if !widget.parent().is_none() && !clipped {
-                clipped |= self.is_node_clipped(widget.parent(), pt);
+    /// Moves content of scroll viewers that were released after panning, see
+    /// `ScrollViewer::set_inertia_enabled`.
+    fn update_kinetic_scrolling(&mut self, dt: f32) {
+        for node in self.nodes.iter_mut() {
+            if let UINode::ScrollViewer(scroll_viewer) = node {
+                scroll_viewer.update_kinetics(&self.sender, dt);
             }
         }
-
-        clipped
     }
 
-    fn is_node_contains_point(&self, node_handle: Handle<UINode<M, C>>, pt: Vector2<f32>) -> bool {
-        scope_profile!();
-
-        let widget = self.nodes.borrow(node_handle);
-
-        if !widget.is_globally_visible() {
-            return false;
+    /// Scrolls scroll viewers while an item is dragged near their edges.
+    fn update_drag_edge_scrolling(&mut self, dt: f32) {
+        if !self.drag_context.is_dragging {
+            return;
         }
-
-        if !self.is_node_clipped(node_handle, pt) {
-            for command_index in widget.command_indices.borrow().iter() {
-                if let Some(command) = self.drawing_context.get_commands().get(*command_index) {
-                    if self.drawing_context.is_command_contains_point(command, pt) {
-                        return true;
-                    }
+        // Only innermost scroll viewer under cursor is scrolled, otherwise nested viewers
+        // sharing an edge would be scrolled all at once.
+        let mut handle = self.picked_node;
+        while handle.is_some() {
+            let node = &self.nodes[handle];
+            if let UINode::ScrollViewer(scroll_viewer) = node {
+                if scroll_viewer.drag_edge_margin() > 0.0 {
+                    break;
                 }
             }
+            handle = node.parent();
         }
+        if let Some(UINode::ScrollViewer(scroll_viewer)) = self.nodes.try_borrow_mut(handle) {
+            scroll_viewer.update_drag_edge_scroll(&self.sender, self.cursor_position, dt);
+        }
+    }
 
-        false
+    fn update_scroll_bar_repeat(&mut self, dt: f32) {
+        for node in self.nodes.iter_mut() {
+            if let UINode::ScrollBar(scroll_bar) = node {
+                scroll_bar.update_auto_repeat(&self.sender, dt);
+            }
+        }
     }
 
-    fn pick_node(
-        &self,
-        node_handle: Handle<UINode<M, C>>,
-        pt: Vector2<f32>,
-        level: &mut i32,
-    ) -> Handle<UINode<M, C>> {
-        scope_profile!();
+    /// Enables or disables notifications about changes of the tree structure
+    /// (`WidgetMessage::NodeAdded` and `WidgetMessage::NodeRemoved`). Disabled by default,
+    /// because every link (including links made while building widgets) produces a message.
+    pub fn set_structure_notifications(&mut self, enabled: bool) {
+        self.structure_notifications = enabled;
+    }
 
-        let widget = self.nodes.borrow(node_handle);
+    pub fn is_structure_notifications_enabled(&self) -> bool {
+        self.structure_notifications
+    }
 
-        if !widget.is_hit_test_visible() || !widget.enabled() {
-            return Handle::NONE;
-        }
+    /// Sets color of selection that is shared by widgets which opt in to it, see
+    /// `DecoratorBuilder::with_shared_selection_color`,
+    /// `TextBoxBuilder::with_shared_selection_color` and
+    /// `ConsoleViewBuilder::with_shared_selection_color`. Other widgets use their own brushes.
+    pub fn set_selection_color(&mut self, color: Color) {
+        let inactive = self.inactive_selection_color();
+        self.drawing_context.set_selection_colors(color, inactive);
+    }
 
-        let (mut picked, mut topmost_picked_level) = if self.is_node_contains_point(node_handle, pt)
-        {
-            (node_handle, *level)
-        } else {
-            (Handle::NONE, 0)
-        };
+    pub fn selection_color(&self) -> Color {
+        self.drawing_context.selection_color(true)
+    }
 
-        for child_handle in widget.children() {
-            *level += 1;
-            let picked_child = self.pick_node(*child_handle, pt, level);
-            if picked_child.is_some() && *level > topmost_picked_level {
-                topmost_picked_level = *level;
-                picked = picked_child;
-            }
-        }
+    /// Sets color of selection that is used when owner of the selection is not focused, it
+    /// should be less noticeable than normal selection color.
+    pub fn set_inactive_selection_color(&mut self, color: Color) {
+        let active = self.selection_color();
+        self.drawing_context.set_selection_colors(active, color);
+    }
 
-        picked
+    pub fn inactive_selection_color(&self) -> Color {
+        self.drawing_context.selection_color(false)
     }
 
-    pub fn cursor_position(&self) -> Vector2<f32> {
-        self.cursor_position
+    /// Enables or disables inheritance of tints (see `Widget::set_tint`). When enabled, tint
+    /// of a node is multiplied by tints of its ancestors, so a whole panel can be dimmed by
+    /// tinting the panel only. Disabled by default, every node is tinted by its own tint.
+    pub fn set_tint_inheritance(&mut self, inherit: bool) {
+        self.drawing_context.set_tint_inheritance(inherit);
     }
 
-    pub fn hit_test(&self, pt: Vector2<f32>) -> Handle<UINode<M, C>> {
-        scope_profile!();
+    pub fn is_tint_inherited(&self) -> bool {
+        self.drawing_context.is_tint_inherited()
+    }
 
-        if self.nodes.is_valid_handle(self.captured_node) {
-            self.captured_node
-        } else if self.picking_stack.is_empty() {
-            // We're not restricted to any node, just start from root.
-            let mut level = 0;
-            self.pick_node(self.root_canvas, pt, &mut level)
-        } else {
-            // We have some picking restriction chain.
-            // Go over picking stack and try each entry. This will help with picking
-            // in a series of popups, especially in menus where may be many open popups
-            // at the same time.
-            for root in self.picking_stack.iter().rev() {
-                if self.nodes.is_valid_handle(root.handle) {
-                    let mut level = 0;
-                    let picked = self.pick_node(root.handle, pt, &mut level);
-                    if picked.is_some() {
-                        return picked;
-                    }
-                }
-                if root.stop {
-                    break;
-                }
-            }
-            Handle::NONE
-        }
+    /// Sets font that will be used by text widgets (text, text box, etc.) that are built
+    /// without explicit font after this call. Existing widgets keep their fonts.
+    pub fn set_default_font(&mut self, font: SharedFont) {
+        self.default_font = Some(font);
     }
 
-    /// Searches a node down on tree starting from give root that matches a criteria
-    /// defined by a given func.
-    pub fn find_by_criteria_down<Func>(
-        &self,
-        node_handle: Handle<UINode<M, C>>,
-        func: &Func,
-    ) -> Handle<UINode<M, C>>
-    where
-        Func: Fn(&UINode<M, C>) -> bool,
-    {
-        let node = self.nodes.borrow(node_handle);
+    pub fn default_font(&self) -> SharedFont {
+        self.default_font
+            .clone()
+            .unwrap_or_else(|| DEFAULT_FONT.clone())
+    }
 
-        if func(node) {
-            return node_handle;
+    /// Adds a font to the end of fallback list. When font of a text widget has no glyph for
+    /// some symbol, the glyph is taken from first font of the list that has it. Symbols that
+    /// are missing in every font are drawn as boxes. Layout of every text is updated.
+    pub fn add_fallback_font(&mut self, font: SharedFont) {
+        self.fallback_fonts.push(font);
+        for node in self.nodes.iter() {
+            node.invalidate_layout();
         }
+    }
 
-        for child_handle in node.children() {
-            let result = self.find_by_criteria_down(*child_handle, func);
+    pub fn clear_fallback_fonts(&mut self) {
+        self.fallback_fonts.clear();
+        for node in self.nodes.iter() {
+            node.invalidate_layout();
+        }
+    }
 
-            if result.is_some() {
-                return result;
+    pub fn fallback_fonts(&self) -> &[SharedFont] {
+        &self.fallback_fonts
+    }
+
+    /// Replaces localization table (key -> text). Every localized text (see
+    /// `Text::set_localized`) is updated immediately and will be re-measured on next update.
+    pub fn set_localization(&mut self, table: HashMap<String, String>) {
+        self.localization = table;
+        for node in self.nodes.iter_mut() {
+            if let UINode::Text(text) = node {
+                text.localize(&self.localization);
             }
         }
+    }
 
-        Handle::NONE
+    pub fn localization(&self) -> &HashMap<String, String> {
+        &self.localization
     }
 
-    /// Searches a node up on tree starting from given root that matches a criteria
-    /// defined by a given func.
-    pub fn find_by_criteria_up<Func>(
-        &self,
-        node_handle: Handle<UINode<M, C>>,
-        func: Func,
-    ) -> Handle<UINode<M, C>>
-    where
-        Func: Fn(&UINode<M, C>) -> bool,
-    {
-        let node = self.nodes.borrow(node_handle);
+    /// Returns localized text by its key or the key itself if there is no such key in the
+    /// localization table.
+    pub fn localize<'a>(&'a self, key: &'a str) -> &'a str {
+        self.localization.get(key).map_or(key, |text| text.as_str())
+    }
 
-        if func(node) {
-            return node_handle;
+    fn update_localization(&mut self) {
+        for node in self.nodes.iter_mut() {
+            if let UINode::Text(text) = node {
+                if text.is_localization_pending() {
+                    text.localize(&self.localization);
+                }
+            }
         }
+    }
 
-        if node.parent().is_some() {
-            self.find_by_criteria_up(node.parent(), func)
-        } else {
-            Handle::NONE
+    /// Starts continuous rotation of given node around its center. Rotation is done by render
+    /// transform of the node, so it does not affect layout and hit testing. Calling this
+    /// method for a node that is already spinning only changes the speed.
+    pub fn start_spin(&mut self, handle: Handle<UINode<M, C>>, degrees_per_second: f32) {
+        let speed = degrees_per_second.to_radians();
+        self.spins
+            .entry(handle)
+            .and_modify(|spin| spin.speed = speed)
+            .or_insert(Spin { speed, angle: 0.0 });
+    }
+
+    /// Stops rotation of given node and resets its render transform.
+    pub fn stop_spin(&mut self, handle: Handle<UINode<M, C>>) {
+        if self.spins.remove(&handle).is_some() {
+            if let Some(node) = self.nodes.try_borrow_mut(handle) {
+                node.set_render_transform(Matrix3::identity());
+            }
         }
     }
 
-    /// Checks if specified node is a child of some other node on `root_handle`. This method
-    /// is useful to understand if some event came from some node down by tree.
-    pub fn is_node_child_of(
-        &self,
-        node_handle: Handle<UINode<M, C>>,
-        root_handle: Handle<UINode<M, C>>,
-    ) -> bool {
-        self.nodes
-            .borrow(root_handle)
-            .has_descendant(node_handle, self)
+    pub fn is_spinning(&self, handle: Handle<UINode<M, C>>) -> bool {
+        self.spins.contains_key(&handle)
     }
 
-    /// Recursively calculates clipping bounds for every node.
-    fn calculate_clip_bounds(&self, node: Handle<UINode<M, C>>, parent_bounds: Rect<f32>) {
-        let node = &self.nodes[node];
-        node.clip_bounds
-            .set(node.screen_bounds().clip_by(parent_bounds));
-        for &child in node.children() {
-            self.calculate_clip_bounds(child, node.clip_bounds.get());
+    /// Makes given node follow the cursor, its top-left corner is placed at cursor position
+    /// plus given offset every frame. The node is moved to the root canvas on top of other
+    /// nodes and excluded from hit testing, so it does not block nodes under the cursor. It
+    /// is useful for custom drag previews, tooltips-like overlays, etc. Calling this method
+    /// for already attached node only changes the offset, invalid handle is ignored.
+    pub fn attach_to_cursor(&mut self, handle: Handle<UINode<M, C>>, offset: Vector2<f32>) {
+        if let Some(attachment) = self.cursor_attachments.get_mut(&handle) {
+            attachment.offset = offset;
+            return;
         }
+
+        let parent = match self.nodes.try_borrow(handle) {
+            Some(node) => node.parent(),
+            None => return,
+        };
+        if parent != self.root_canvas {
+            self.link_nodes_internal(handle, self.root_canvas, false);
+        }
+        self.make_topmost(handle);
+
+        let node = self.nodes.borrow_mut(handle);
+        let was_hit_test_visible = node.is_hit_test_visible();
+        node.set_hit_test_visibility(false);
+        self.cursor_attachments.insert(
+            handle,
+            CursorAttachment {
+                offset,
+                was_hit_test_visible,
+            },
+        );
+        self.update_cursor_attachments();
     }
 
-    /// Checks if specified node is a direct child of some other node on `root_handle`.
-    pub fn is_node_direct_child_of(
-        &self,
-        node_handle: Handle<UINode<M, C>>,
-        root_handle: Handle<UINode<M, C>>,
-    ) -> bool {
-        for child_handle in self.nodes.borrow(root_handle).children() {
-            if *child_handle == node_handle {
-                return true;
+    /// Stops following of the cursor and restores hit test visibility of given node, the
+    /// node stays at its last position.
+    pub fn detach_from_cursor(&mut self, handle: Handle<UINode<M, C>>) {
+        if let Some(attachment) = self.cursor_attachments.remove(&handle) {
+            if let Some(node) = self.nodes.try_borrow_mut(handle) {
+                node.set_hit_test_visibility(attachment.was_hit_test_visible);
             }
         }
-        false
     }
 
-    /// Searches a node by name up on tree starting from given root node.
-    pub fn find_by_name_up(
-        &self,
-        node_handle: Handle<UINode<M, C>>,
-        name: &str,
-    ) -> Handle<UINode<M, C>> {
-        self.find_by_criteria_up(node_handle, |node| node.name() == name)
+    pub fn is_attached_to_cursor(&self, handle: Handle<UINode<M, C>>) -> bool {
+        self.cursor_attachments.contains_key(&handle)
     }
 
-    /// Searches a node by name down on tree starting from given root node.
-    pub fn find_by_name_down(
-        &self,
-        node_handle: Handle<UINode<M, C>>,
-        name: &str,
-    ) -> Handle<UINode<M, C>> {
-        self.find_by_criteria_down(node_handle, &|node| node.name() == name)
+    fn update_cursor_attachments(&mut self) {
+        let nodes = &mut self.nodes;
+        let cursor_position = self.cursor_position;
+        self.cursor_attachments.retain(|&handle, attachment| {
+            if let Some(node) = nodes.try_borrow_mut(handle) {
+                let position = cursor_position + attachment.offset;
+                if node.desired_local_position() != position {
+                    node.set_desired_local_position(position);
+                    node.invalidate_layout();
+                }
+                true
+            } else {
+                false
+            }
+        });
     }
 
-    /// Searches a node by name up on tree starting from given root node and tries to borrow it if exists.
-    pub fn borrow_by_name_up(
-        &self,
-        start_node_handle: Handle<UINode<M, C>>,
-        name: &str,
-    ) -> &UINode<M, C> {
-        self.nodes
-            .borrow(self.find_by_name_up(start_node_handle, name))
+    fn update_spins(&mut self, dt: f32) {
+        let nodes = &mut self.nodes;
+        self.spins.retain(|&handle, spin| {
+            if let Some(node) = nodes.try_borrow_mut(handle) {
+                spin.angle = (spin.angle + spin.speed * dt) % std::f32::consts::TAU;
+                node.set_render_transform(Matrix3::new_rotation(spin.angle));
+                true
+            } else {
+                false
+            }
+        });
     }
 
-    /// Searches a node by name down on tree starting from given root node and tries to borrow it if exists.
-    pub fn borrow_by_name_down(
-        &self,
-        start_node_handle: Handle<UINode<M, C>>,
-        name: &str,
-    ) -> &UINode<M, C> {
-        self.nodes
-            .borrow(self.find_by_name_down(start_node_handle, name))
+    fn pan_target_mut(&mut self) -> Option<&mut ScrollViewer<M, C>> {
+        match self.nodes.try_borrow_mut(self.touch_state.pan_target) {
+            Some(UINode::ScrollViewer(scroll_viewer)) => Some(scroll_viewer),
+            _ => None,
+        }
     }
 
-    /// Searches for a node up on tree that satisfies some criteria and then borrows
-    /// shared reference.
-    ///
-    /// # Panics
-    ///
-    /// It will panic if there no node that satisfies given criteria.
-    pub fn borrow_by_criteria_up<Func>(
-        &self,
-        start_node_handle: Handle<UINode<M, C>>,
-        func: Func,
-    ) -> &UINode<M, C>
-    where
-        Func: Fn(&UINode<M, C>) -> bool,
-    {
-        self.nodes
-            .borrow(self.find_by_criteria_up(start_node_handle, func))
+    /// Releases emulated mouse press when panning has started. Pressed widget receives
+    /// `MouseCaptureLost` instead of `MouseUp`, so it won't be clicked but won't stay pressed.
+    fn cancel_touch_press(&mut self) {
+        self.mouse_state.left = ButtonState::Released;
+        self.drag_context.is_dragging = false;
+        self.drag_context.drag_node = Handle::NONE;
+        self.cancel_mouse_capture(self.picked_node);
     }
 
-    pub fn try_borrow_by_criteria_up<Func>(
-        &self,
-        start_node_handle: Handle<UINode<M, C>>,
-        func: Func,
-    ) -> Option<&UINode<M, C>>
-    where
-        Func: Fn(&UINode<M, C>) -> bool,
-    {
-        self.nodes
-            .try_borrow(self.find_by_criteria_up(start_node_handle, func))
-    }
+    fn process_touch(&mut self, id: u64, phase: TouchPhase, position: Vector2<f32>) {
+        // Touch position is in physical units, convert it to logical.
+        let logical_position = self.physical_to_logical(position);
+        match phase {
+            TouchPhase::Started => {
+                if self.touch_state.begin(id, logical_position) {
+                    self.process_os_event(&OsEvent::CursorMoved { position });
+                    self.process_os_event(&OsEvent::MouseInput {
+                        button: MouseButton::Left,
+                        state: ButtonState::Pressed,
+                    });
+                }
+            }
+            TouchPhase::Moved => {
+                if let Some(delta) = self.touch_state.move_to(id, logical_position) {
+                    let distance = self
+                        .touch_state
+                        .primary_touch()
+                        .map_or(0.0, |t| t.distance());
+                    if !self.touch_state.is_panning
+                        && distance > PAN_THRESHOLD
+                        && self.picked_node.is_some()
+                    {
+                        let scroll_viewer = self.find_by_criteria_up(self.picked_node, |n| {
+                            matches!(n, UINode::ScrollViewer(_))
+                        });
+                        if scroll_viewer.is_some() {
+                            self.touch_state.is_panning = true;
+                            self.touch_state.pan_target = scroll_viewer;
+                            self.cancel_touch_press();
+                            if let Some(scroll_viewer) = self.pan_target_mut() {
+                                scroll_viewer.begin_drag();
+                            }
+                        }
+                    }
 
-    pub fn try_borrow_by_criteria_up_mut<Func>(
-        &mut self,
-        start_node_handle: Handle<UINode<M, C>>,
-        func: Func,
-    ) -> Option<&mut UINode<M, C>>
-    where
-        Func: Fn(&UINode<M, C>) -> bool,
-    {
-        self.nodes
-            .try_borrow_mut(self.find_by_criteria_up(start_node_handle, func))
+                    if self.touch_state.is_panning {
+                        let sender = self.sender.clone();
+                        if let Some(scroll_viewer) = self.pan_target_mut() {
+                            scroll_viewer.drag_by(&sender, delta);
+                        }
+                    } else {
+                        self.process_os_event(&OsEvent::CursorMoved { position });
+                    }
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if let Some(touch) = self.touch_state.end(id) {
+                    if self.touch_state.is_panning {
+                        if let Some(scroll_viewer) = self.pan_target_mut() {
+                            scroll_viewer.end_drag(phase == TouchPhase::Ended);
+                        }
+                        self.touch_state.is_panning = false;
+                        self.touch_state.pan_target = Handle::NONE;
+                    } else {
+                        self.process_os_event(&OsEvent::MouseInput {
+                            button: MouseButton::Left,
+                            state: ButtonState::Released,
+                        });
+
+                        if phase == TouchPhase::Ended
+                            && !self.touch_state.long_press_fired
+                            && self.touch_state.time - touch.start_time <= TAP_TIME
+                            && touch.distance() < PAN_THRESHOLD
+                            && self.picked_node.is_some()
+                        {
+                            self.send_message(WidgetMessage::tap(
+                                self.picked_node,
+                                MessageDirection::FromWidget,
+                                logical_position,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
     }
 
-    /// Returns instance of message sender which can be used to push messages into queue
-    /// from other threads.
-    pub fn sender(&self) -> Sender<UiMessage<M, C>> {
-        self.sender.clone()
+    /// Returns current size of a shared size group of grids.
+    pub fn shared_size(&self, group: SharedSizeGroup) -> Option<f32> {
+        self.shared_sizes.get(&group).cloned()
     }
 
-    pub fn send_message(&self, message: UiMessage<M, C>) {
-        self.sender.send(message).unwrap()
-    }
+    /// Collects content sizes of every member of shared size groups (measured at previous
+    /// frame) and invalidates layout of grids whose groups have changed size.
+    fn update_shared_size_groups(&mut self) {
+        let mut sizes = HashMap::<SharedSizeGroup, f32>::new();
+        for node in self.nodes.iter() {
+            if let UINode::Grid(grid) = node {
+                if grid.is_globally_visible() {
+                    grid.visit_shared_size_groups(|group, size| {
+                        let entry = sizes.entry(group).or_insert(0.0);
+                        *entry = entry.max(size);
+                    });
+                }
+            }
+        }
 
-    // Puts node at the end of children list of a parent node.
-    //
-    // # Notes
-    //
-    // Node will be topmost *only* on same hierarchy level! So if you have a floating
-    // window (for example) and a window embedded into some other control (yes this is
-    // possible) then floating window won't be the topmost.
-    fn make_topmost(&mut self, node: Handle<UINode<M, C>>) {
-        let parent = self.node(node).parent();
-        if parent.is_some() {
-            let parent = &mut self.nodes[parent];
-            parent.remove_child(node);
-            parent.add_child(node, false);
+        if sizes != self.shared_sizes {
+            let old_sizes = std::mem::replace(&mut self.shared_sizes, sizes);
+            let shared_sizes = &self.shared_sizes;
+            for node in self.nodes.iter_mut() {
+                if let UINode::Grid(grid) = node {
+                    let mut changed = false;
+                    grid.visit_shared_size_groups(|group, _| {
+                        changed |= old_sizes.get(&group) != shared_sizes.get(&group);
+                    });
+                    if changed {
+                        grid.invalidate_layout();
+                    }
+                }
+            }
         }
     }
 
-    fn preview_message(&mut self, message: &mut UiMessage<M, C>) {
-        scope_profile!();
+    /// Puts a command into deferred queue. It is safe to call from any message handler,
+    /// even if a command removes a node that is currently handling a message (like a
+    /// button that closes its own window).
+    ///
+    /// # Ordering
+    ///
+    /// Commands are applied at the beginning of next [`update`](#method.update) call, after
+    /// every message was dispatched and before layout pass. Commands are applied in the same
+    /// order as they were posted. Commands posted while the queue is being applied will be
+    /// applied at next update. Commands that refer to already removed nodes are ignored.
+    pub fn post(&mut self, command: UiCommand<M, C>) {
+        self.commands.push_back(command);
+    }
 
-        // Fire preview handler first. This will allow controls to do some actions before
-        // message will begin bubble routing.
-        for node in self.nodes.iter() {
-            node.preview_message(self, message);
+    fn apply_commands(&mut self) {
+        let commands = std::mem::take(&mut self.commands);
+        for command in commands {
+            match command {
+                UiCommand::Remove(node) => {
+                    if self.nodes.is_valid_handle(node) {
+                        self.remove_node(node);
+                    }
+                }
+                UiCommand::Link {
+                    child,
+                    parent,
+                    in_front,
+                } => {
+                    if self.nodes.is_valid_handle(child) && self.nodes.is_valid_handle(parent) {
+                        self.link_nodes_internal(child, parent, in_front);
+                    }
+                }
+                UiCommand::Unlink(node) => {
+                    if self.nodes.is_valid_handle(node) {
+                        self.unlink_node(node);
+                    }
+                }
+                UiCommand::Apply(func) => func(self),
+            }
         }
     }
 
-    fn bubble_message(&mut self, message: &mut UiMessage<M, C>) {
-        scope_profile!();
+    /// Builds read-only accessibility view of the whole UI. Layout and decoration nodes
+    /// are skipped, only nodes with meaningful roles are present in the tree.
+    pub fn accessibility_tree(&self) -> Vec<AccessibleNode<M, C>> {
+        accessibility::build_accessibility_tree(self, self.root_canvas)
+    }
 
-        // Dispatch event using bubble strategy. Bubble routing means that message will go
-        // from specified destination up on tree to tree root.
-        // Gather chain of nodes from source to root.
-        self.bubble_queue.clear();
-        self.bubble_queue.push_back(message.destination());
-        let mut parent = self.nodes[message.destination()].parent();
-        while parent.is_some() && self.nodes.is_valid_handle(parent) {
-            self.bubble_queue.push_back(parent);
-            parent = self.nodes[parent].parent();
-        }
+    /// Checks arranged geometry of the whole UI and reports nodes that got zero size while
+    /// having content, nodes that overflow bounds of their parents and overlapping siblings
+    /// in canvases. It should be called after `update`, it is meant to be used as a developer
+    /// tool, see `diagnostics` module for details.
+    pub fn validate_layout(&self) -> Vec<LayoutWarning<M, C>> {
+        diagnostics::validate_layout(self, self.root_canvas)
+    }
 
-        while let Some(handle) = self.bubble_queue.pop_front() {
-            let (ticket, mut node) = self.nodes.take_reserve(handle);
-            node.handle_routed_message(self, message);
-            self.nodes.put_back(ticket, node);
-        }
+    /// Returns tooltip that is currently shown (if any).
+    pub fn shown_tooltip(&self) -> Handle<UINode<M, C>> {
+        self.shown_tooltip
     }
 
-    /// Extracts UI event one-by-one from common queue. Each extracted event will go to *all*
-    /// available nodes first and only then will be moved outside of this method. This is one
-    /// of most important methods which must be called each frame of your game loop, otherwise
-    /// UI will not respond to any kind of events and simply speaking will just not work.
-    pub fn poll_message(&mut self) -> Option<UiMessage<M, C>> {
-        match self.receiver.try_recv() {
-            Ok(mut message) => {
-                // Destination node may be destroyed at the time we receive message,
-                // we have to discard such messages.
-                if !self.nodes.is_valid_handle(message.destination()) {
-                    return None;
+    fn update_tooltip(&mut self, dt: f32) {
+        if self.tooltip_owner.is_some() && self.shown_tooltip.is_none() {
+            self.tooltip_time += dt;
+            if self.tooltip_time >= TOOLTIP_DELAY {
+                let tooltip = self.nodes[self.tooltip_owner].tooltip();
+                if self.nodes.is_valid_handle(tooltip) {
+                    self.nodes[tooltip].set_visibility(true);
+                    self.shown_tooltip = tooltip;
                 }
+            }
+        }
+    }
 
-                if message.need_perform_layout() {
-                    self.update(self.screen_size, 0.0);
-                }
+    fn hide_tooltip(&mut self) {
+        if self.nodes.is_valid_handle(self.shown_tooltip) {
+            self.nodes[self.shown_tooltip].set_visibility(false);
+        }
+        self.shown_tooltip = Handle::NONE;
+        self.tooltip_time = 0.0;
+    }
 
-                self.preview_message(&mut message);
-                self.bubble_message(&mut message);
+    /// Tooltips are hidden by default, drawn on top of everything else and must not
+    /// intercept mouse input of their owners.
+    fn prepare_tooltip(&mut self, tooltip: Handle<UINode<M, C>>) {
+        if self.nodes.is_valid_handle(tooltip) && tooltip != self.shown_tooltip {
+            self.nodes[tooltip]
+                .set_visibility(false)
+                .set_draw_on_top(true)
+                .set_hit_test_visibility(false);
+        }
+    }
 
-                if let UiMessageData::Widget(msg) = &message.data() {
-                    match msg {
-                        WidgetMessage::ZIndex(_) => {
-                            // Keep order of children of a parent node of a node that changed z-index
-                            // the same as z-index of children.
-                            let parent = self.node(message.destination()).parent();
-                            if parent.is_some() {
-                                self.stack.clear();
-                                for child in self.nodes.borrow(parent).children() {
-                                    self.stack.push(*child);
-                                }
+    fn set_tooltip_owner(&mut self, owner: Handle<UINode<M, C>>) {
+        if self.tooltip_owner != owner {
+            self.hide_tooltip();
+            self.tooltip_owner = owner;
+        }
+    }
 
-                                let nodes = &mut self.nodes;
-                                self.stack.sort_by(|a, b| {
-                                    let z_a = nodes.borrow(*a).z_index();
-                                    let z_b = nodes.borrow(*b).z_index();
-                                    z_a.cmp(&z_b)
-                                });
+    /// Calculates position of shown tooltip according to placement mode of its owner. If
+    /// the tooltip does not fit on screen, it is flipped to the opposite side and then
+    /// clamped to the screen bounds.
+    fn place_tooltip(&mut self) {
+        if !self.nodes.is_valid_handle(self.shown_tooltip)
+            || !self.nodes.is_valid_handle(self.tooltip_owner)
+        {
+            return;
+        }
 
-                                let parent = self.nodes.borrow_mut(parent);
-                                parent.clear_children();
-                                for child in self.stack.iter() {
-                                    parent.add_child(*child, false);
-                                }
-                            }
-                        }
-                        WidgetMessage::TopMost => {
-                            if message.destination().is_some() {
-                                self.make_topmost(message.destination());
-                            }
-                        }
-                        WidgetMessage::Unlink => {
-                            if message.destination().is_some() {
-                                self.unlink_node(message.destination());
+        let size = self.nodes[self.shown_tooltip].desired_size();
+        let owner = &self.nodes[self.tooltip_owner];
+        let bounds = owner.screen_bounds();
+        let cursor = self.cursor_position;
+        let screen = self.screen_size;
+
+        let mut position = match owner.tooltip_placement() {
+            TooltipPlacement::Cursor => {
+                let mut position =
+                    cursor + Vector2::new(TOOLTIP_CURSOR_OFFSET, TOOLTIP_CURSOR_OFFSET);
+                if position.x + size.x > screen.x {
+                    position.x = cursor.x - TOOLTIP_CURSOR_OFFSET - size.x;
+                }
+                if position.y + size.y > screen.y {
+                    position.y = cursor.y - TOOLTIP_CURSOR_OFFSET - size.y;
+                }
+                position
+            }
+            TooltipPlacement::Below => {
+                let mut position = Vector2::new(bounds.x(), bounds.y() + bounds.h());
+                if position.y + size.y > screen.y {
+                    position.y = bounds.y() - size.y;
+                }
+                position
+            }
+            TooltipPlacement::Above => {
+                let mut position = Vector2::new(bounds.x(), bounds.y() - size.y);
+                if position.y < 0.0 {
+                    position.y = bounds.y() + bounds.h();
+                }
+                position
+            }
+            TooltipPlacement::Right => {
+                let mut position = Vector2::new(bounds.x() + bounds.w(), bounds.y());
+                if position.x + size.x > screen.x {
+                    position.x = bounds.x() - size.x;
+                }
+                position
+            }
+        };
 
-                                let node = &self.nodes[message.destination()];
-                                let new_position = node.screen_position;
-                                self.send_message(WidgetMessage::desired_position(
-                                    message.destination(),
-                                    MessageDirection::ToWidget,
-                                    new_position,
-                                ));
-                            }
-                        }
-                        &WidgetMessage::LinkWith(parent) => {
-                            if message.destination().is_some() {
-                                self.link_nodes_internal(message.destination(), parent, false);
-                            }
-                        }
-                        &WidgetMessage::LinkWithReverse(parent) => {
-                            if message.destination().is_some() {
-                                self.link_nodes_internal(message.destination(), parent, true);
-                            }
-                        }
-                        WidgetMessage::Remove => {
-                            if message.destination().is_some() {
-                                self.remove_node(message.destination());
-                            }
-                        }
-                        WidgetMessage::Center => {
-                            if message.destination().is_some() {
-                                let node = self.node(message.destination());
-                                let size = node.actual_size();
-                                let parent = node.parent();
-                                let parent_size = if parent.is_some() {
-                                    if parent == self.root_canvas {
-                                        self.screen_size
-                                    } else {
-                                        self.node(parent).actual_size()
-                                    }
-                                } else {
-                                    self.screen_size
-                                };
+        position.x = position.x.min(screen.x - size.x).max(0.0);
+        position.y = position.y.min(screen.y - size.y).max(0.0);
 
-                                self.send_message(WidgetMessage::desired_position(
-                                    message.destination(),
-                                    MessageDirection::ToWidget,
-                                    (parent_size - size).scale(0.5),
-                                ));
-                            }
-                        }
-                        _ => {}
-                    }
-                }
+        self.nodes[self.shown_tooltip].set_desired_local_position(position);
+    }
 
-                Some(message)
+    /// Searches for a widget with tooltip starting from picked node up on the tree.
+    fn find_tooltip_owner(&self, picked: Handle<UINode<M, C>>) -> Handle<UINode<M, C>> {
+        let mut handle = picked;
+        while handle.is_some() {
+            // Hovering the tooltip itself keeps it open.
+            if handle == self.shown_tooltip {
+                return self.tooltip_owner;
             }
-            Err(e) => match e {
-                TryRecvError::Empty => None,
-                TryRecvError::Disconnected => unreachable!(),
-            },
+            let node = &self.nodes[handle];
+            if node.tooltip().is_some() {
+                return handle;
+            }
+            handle = node.parent();
         }
+        Handle::NONE
     }
 
-    pub fn captured_node(&self) -> Handle<UINode<M, C>> {
-        self.captured_node
-    }
+    pub fn draw(&mut self) -> &DrawingContext {
+        scope_profile!();
+
+        self.calculate_clip_bounds(
+            self.root_canvas,
+            Rect::new(0.0, 0.0, self.screen_size.x, self.screen_size.y),
+        );
+        self.collect_dirty_region();
+        self.drawing_context.clear();
+
+        for node in self.nodes.iter_mut() {
+            node.command_indices.borrow_mut().clear();
+        }
+
+        self.update_layer_roots();
+
+        // Draw everything except top-most nodes and nodes of other layers.
+        draw_node(
+            &self.nodes,
+            self.root_canvas,
+            Some(0),
+            &mut self.drawing_context,
+        );
+
+        // Render top-most nodes in separate pass.
+        // TODO: This may give weird results because of invalid nesting.
+        self.stack.clear();
+        self.stack.push(self.root());
+        while let Some(node_handle) = self.stack.pop() {
+            let node = &self.nodes[node_handle];
+            if node.is_draw_on_top() {
+                let layer = effective_render_layer(&self.nodes, node.parent());
+                // Roots of layers are drawn in layer passes.
+                if !is_other_layer(Some(layer), node.render_layer()) {
+                    draw_node(
+                        &self.nodes,
+                        node_handle,
+                        Some(layer),
+                        &mut self.drawing_context,
+                    );
+                }
+            }
+            for &child in node.children() {
+                self.stack.push(child);
+            }
+        }
+
+        // Render layers in ascending order, so higher layers are on top of everything.
+        for &layer_root in self.layer_roots.iter() {
+            draw_layer_root(&self.nodes, layer_root, &mut self.drawing_context);
+        }
+
+        if let Some(focus_visual) = self.focus_visual {
+            if self.keyboard_navigation && self.nodes.is_valid_handle(self.keyboard_focus_node) {
+                let node = self.nodes.borrow(self.keyboard_focus_node);
+                if node.is_globally_visible() {
+                    let outset = FOCUS_VISUAL_OUTSET + focus_visual.thickness;
+                    let bounds = node
+                        .screen_bounds()
+                        .inflate(FOCUS_VISUAL_OUTSET, FOCUS_VISUAL_OUTSET);
+                    self.drawing_context
+                        .push_rect(&bounds, focus_visual.thickness);
+                    self.drawing_context.commit(
+                        node.clip_bounds().inflate(outset, outset),
+                        Brush::Solid(focus_visual.color),
+                        CommandTexture::None,
+                        None,
+                    );
+                }
+            }
+        }
+
+        if self.inspect_mode {
+            self.draw_inspector();
+        }
+
+        // Debug info rendered on top of other.
+        if self.visual_debug {
+            if self.picked_node.is_some() {
+                let bounds = self.nodes.borrow(self.picked_node).screen_bounds();
+                self.drawing_context.push_rect(&bounds, 1.0);
+                self.drawing_context.commit(
+                    bounds,
+                    Brush::Solid(Color::WHITE),
+                    CommandTexture::None,
+                    None,
+                );
+            }
+
+            if self.keyboard_focus_node.is_some() {
+                let bounds = self.nodes.borrow(self.keyboard_focus_node).screen_bounds();
+                self.drawing_context.push_rect(&bounds, 1.0);
+                self.drawing_context.commit(
+                    bounds,
+                    Brush::Solid(Color::GREEN),
+                    CommandTexture::None,
+                    None,
+                );
+            }
+        }
+
+        // Everything was drawn in logical units, convert to physical.
+        self.drawing_context
+            .apply_transform(self.screen_scale, self.screen_offset);
+
+        if !self.draw_hooks.is_empty() {
+            let ranges = self
+                .drawing_context
+                .get_commands()
+                .iter()
+                .map(|command| command.triangles.clone())
+                .collect::<Vec<_>>();
+
+            for hook in self.draw_hooks.iter_mut() {
+                hook(&mut self.drawing_context);
+            }
+
+            self.remap_command_indices(&ranges);
+        }
+
+        &self.drawing_context
+    }
+
+    /// Hooks could insert, remove or reorder commands, so indices of commands of nodes (which
+    /// are used for picking) are restored by triangle ranges of commands: every command with
+    /// geometry has its own range. `ranges` are ranges of commands before hooks were called.
+    fn remap_command_indices(&mut self, ranges: &[std::ops::Range<usize>]) {
+        let new_indices = self
+            .drawing_context
+            .get_commands()
+            .iter()
+            .enumerate()
+            .filter(|(_, command)| !command.triangles.is_empty())
+            .map(|(index, command)| (command.triangles.clone(), index))
+            .collect::<HashMap<_, _>>();
+
+        for node in self.nodes.iter() {
+            let mut indices = node.command_indices.borrow_mut();
+            let remapped = indices
+                .iter()
+                .filter_map(|&index| new_indices.get(ranges.get(index)?).cloned())
+                .collect();
+            *indices = remapped;
+        }
+    }
+
+    /// Adds a callback that is called at the end of every `draw` with final drawing context,
+    /// its coordinates are in physical units. The callback can inspect and change commands or
+    /// insert custom ones (see `DrawingContext::push_custom_command`) to apply effects that
+    /// the UI does not support, like shaders for some regions of the screen. Hooks are called
+    /// in order of adding. Picking keeps working after hooks, as long as triangle ranges of
+    /// commands with geometry stay unique.
+    pub fn add_draw_hook(&mut self, hook: Box<DrawHook>) {
+        self.draw_hooks.push(hook);
+    }
+
+    pub fn clear_draw_hooks(&mut self) {
+        self.draw_hooks.clear();
+    }
+
+    /// Sets a callback that tells size of textures, UI knows nothing about textures so it is
+    /// up to user (or engine) to provide it. Images without explicit size are measured to
+    /// size of their textures, see `Image`.
+    pub fn set_texture_size_provider(&mut self, provider: Option<Box<TextureSizeProvider>>) {
+        self.texture_size_provider = provider;
+        for node in self.nodes.iter() {
+            if let UINode::Image(image) = node {
+                image.set_texture_size(None);
+            }
+        }
+    }
+
+    /// Returns size of a texture in pixels or `None` if it is unknown (or empty).
+    pub fn texture_size(&self, texture: &SharedTexture) -> Option<Vector2<f32>> {
+        self.texture_size_provider
+            .as_ref()
+            .and_then(|provider| provider(texture))
+            .filter(|size| size.x > 0.0 && size.y > 0.0)
+    }
+
+    /// Sets a callback that reads alpha of textures, it is used by images with alpha hit test
+    /// (see `Image::set_alpha_hit_test`). Reading of texture data is up to user (or engine),
+    /// usually it is a lookup in a precomputed mask. Without the callback images are hit
+    /// tested by their rectangles.
+    pub fn set_texture_alpha_provider(&mut self, provider: Option<Box<TextureAlphaProvider>>) {
+        self.texture_alpha_provider = provider;
+    }
+
+    /// Returns alpha of a texture at given texture coordinates or `None` if it is unknown.
+    pub fn texture_alpha(&self, texture: &SharedTexture, tex_coord: Vector2<f32>) -> Option<f32> {
+        self.texture_alpha_provider
+            .as_ref()
+            .and_then(|provider| provider(texture, tex_coord))
+    }
+
+    /// Remembers an image whose texture is not loaded yet, so it will be measured again as
+    /// soon as size of its texture is known.
+    pub(in crate) fn await_texture_size(&self, image: Handle<UINode<M, C>>) {
+        let mut awaiting = self.awaiting_texture_size.borrow_mut();
+        if !awaiting.contains(&image) {
+            awaiting.push(image);
+        }
+    }
+
+    /// Updates sizes of images whose textures have been loaded since last measure, only
+    /// images that are waiting for their textures are checked.
+    fn update_image_sizes(&mut self) {
+        if self.texture_size_provider.is_none() {
+            return;
+        }
+        self.awaiting_texture_size.borrow_mut().retain(|&handle| {
+            match self.nodes.try_borrow(handle) {
+                Some(UINode::Image(image)) if image.is_awaiting_texture_size() => {
+                    match image
+                        .texture()
+                        .and_then(|texture| self.texture_size(texture))
+                    {
+                        Some(size) => {
+                            image.set_texture_size(Some(size));
+                            false
+                        }
+                        None => true,
+                    }
+                }
+                _ => false,
+            }
+        });
+    }
+
+    /// Enables or disables inspect mode, which is a developer tool. In inspect mode the node
+    /// under cursor is outlined and its kind, name and bounds are shown next to it. Click
+    /// prints chain of ancestors of the node with their desired and actual sizes to stdout.
+    /// Mouse input is not passed to widgets while inspect mode is active, keyboard input
+    /// works as usual. Entering inspect mode releases mouse capture and cancels drag and
+    /// drop, so a widget that was pressed won't stay pressed (it receives `MouseCaptureLost`
+    /// instead of `MouseUp`).
+    pub fn set_inspect_mode(&mut self, enabled: bool) {
+        if enabled && !self.inspect_mode {
+            if self.captured_node.is_some() || self.mouse_state.left == ButtonState::Pressed {
+                self.cancel_mouse_capture(self.picked_node);
+            }
+            self.mouse_state = Default::default();
+            self.drag_context.is_dragging = false;
+            self.drag_context.drag_node = Handle::NONE;
+        }
+        self.inspect_mode = enabled;
+        self.inspected_node = if enabled {
+            self.hit_test(self.cursor_position)
+        } else {
+            Handle::NONE
+        };
+    }
+
+    pub fn is_inspect_mode(&self) -> bool {
+        self.inspect_mode
+    }
+
+    /// Handles mouse input in inspect mode, returns false if the event is not consumed.
+    fn process_inspector_event(&mut self, event: &OsEvent) -> bool {
+        match *event {
+            OsEvent::CursorMoved { position } => {
+                self.cursor_position = self.physical_to_logical(position);
+                self.inspected_node = self.hit_test(self.cursor_position);
+            }
+            OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ButtonState::Pressed,
+            } => {
+                let mut handle = self.inspected_node;
+                while let Some(node) = self.nodes.try_borrow(handle) {
+                    println!(
+                        "{} '{}' {:?}: desired size {:?}, actual size {:?}, position {:?}",
+                        node.kind(),
+                        node.name(),
+                        handle,
+                        node.desired_size(),
+                        node.actual_size(),
+                        node.screen_position
+                    );
+                    handle = node.parent();
+                }
+            }
+            OsEvent::MouseInput { .. } | OsEvent::MouseWheel(..) | OsEvent::Touch { .. } => {}
+            _ => return false,
+        }
+        true
+    }
+
+    fn draw_inspector(&mut self) {
+        let node = match self.nodes.try_borrow(self.inspected_node) {
+            Some(node) if node.is_globally_visible() => node,
+            _ => return,
+        };
+
+        let screen_bounds = Rect::new(0.0, 0.0, self.screen_size.x, self.screen_size.y);
+        let bounds = node.screen_bounds();
+        self.drawing_context.push_rect(&bounds, 1.0);
+        self.drawing_context.commit(
+            screen_bounds,
+            Brush::Solid(Color::opaque(255, 0, 255)),
+            CommandTexture::None,
+            None,
+        );
+
+        let mut info = FormattedTextBuilder::new()
+            .with_font(self.default_font())
+            .with_constraint(self.screen_size)
+            .with_text(format!(
+                "{} '{}'\n{:.1}, {:.1} - {:.1} x {:.1}",
+                node.kind(),
+                node.name(),
+                bounds.x(),
+                bounds.y(),
+                bounds.w(),
+                bounds.h()
+            ))
+            .build();
+        let size = info.build();
+        // Keep the label on screen, below the node if possible.
+        let position = Vector2::new(
+            bounds.x().min(self.screen_size.x - size.x).max(0.0),
+            if bounds.y() + bounds.h() + size.y <= self.screen_size.y {
+                bounds.y() + bounds.h()
+            } else {
+                (bounds.y() - size.y).max(0.0)
+            },
+        );
+        self.drawing_context
+            .push_rect_filled(&Rect::new(position.x, position.y, size.x, size.y), None);
+        self.drawing_context.commit(
+            screen_bounds,
+            Brush::Solid(Color::from_rgba(0, 0, 0, 200)),
+            CommandTexture::None,
+            None,
+        );
+        self.drawing_context
+            .draw_text(screen_bounds, position, &info);
+    }
+
+    /// Returns area of the screen (in physical units, as the drawing context) that was changed
+    /// since previous `draw`. It is a union of old and new bounds of every node that was moved,
+    /// resized, shown, hidden, removed, had its layout invalidated or received a message. The
+    /// renderer can scissor to the area and keep the rest of the last frame. `None` means that
+    /// nothing has changed and the last frame can be reused as is.
+    ///
+    /// # Notes
+    ///
+    /// Nodes that were modified directly (without messages) must have their layout or visual
+    /// invalidated (see `Widget::invalidate_visual`), otherwise their changes won't be in the
+    /// area. Changes of opacity, tint and render transform are tracked automatically.
+    ///
+    /// `Renderer::render_ui_to_texture` uses the area to redraw only changed part of the
+    /// texture.
+    pub fn dirty_region(&self) -> Option<Rect<f32>> {
+        self.dirty_region.map(|region| {
+            let position = self.logical_to_physical(region.position);
+            let size = region.size.component_mul(&self.screen_scale);
+            Rect::new(position.x, position.y, size.x, size.y)
+        })
+    }
+
+    fn mark_dirty(&mut self, rect: Rect<f32>) {
+        self.pending_dirty_region = Some(match self.pending_dirty_region {
+            Some(region) => union_rects(region, rect),
+            None => rect,
+        });
+    }
+
+    /// Returns true if opacity, tint or render transform of given node or any of its ancestors
+    /// was changed since last draw.
+    fn is_inherited_visual_changed(&self, node_handle: Handle<UINode<M, C>>) -> bool {
+        let mut handle = node_handle;
+        while let Some(node) = self.nodes.try_borrow(handle) {
+            if node.subtree_visual_changed.get() {
+                return true;
+            }
+            handle = node.parent();
+        }
+        false
+    }
+
+    /// Compares bounds of every node with its bounds at last draw and collects changed area.
+    /// Bounds are taken with render transforms, as nodes are drawn.
+    fn collect_dirty_region(&mut self) {
+        let mut region = self.pending_dirty_region.take();
+        for (handle, node) in self.nodes.pair_iter() {
+            let bounds = if node.is_globally_visible() {
+                Some(transform_bounds(
+                    &visual_transform(&self.nodes, handle),
+                    &node.screen_bounds(),
+                ))
+            } else {
+                None
+            };
+            let old_bounds = node.drawn_bounds.replace(bounds);
+            if node.visual_changed.get()
+                || old_bounds != bounds
+                || self.is_inherited_visual_changed(handle)
+            {
+                for rect in old_bounds.iter().chain(bounds.iter()) {
+                    region = Some(match region {
+                        Some(region) => union_rects(region, *rect),
+                        None => *rect,
+                    });
+                }
+            }
+        }
+        for node in self.nodes.iter() {
+            node.visual_changed.set(false);
+            node.subtree_visual_changed.set(false);
+        }
+        let screen = Rect::new(0.0, 0.0, self.screen_size.x, self.screen_size.y);
+        self.dirty_region = region.and_then(|region| intersect_rects(region, screen));
+    }
+
+    /// Lays out given sub-tree in a rectangle of given size and draws it into its own drawing
+    /// context, which then can be rendered into a texture (for example to show UI in 3D space
+    /// or to cache an expensive panel). Root of the sub-tree must have no parent (see
+    /// `detach_subtree`), so it isn't drawn with the rest of the UI and it doesn't receive any
+    /// input, input must be forwarded to it explicitly. Root that is still linked to a parent
+    /// is not drawn and `None` is returned.
+    ///
+    /// Returns `None` if the sub-tree has not changed since last call, so previously rendered
+    /// texture can be reused. Sub-tree is considered changed if its layout was invalidated or
+    /// if any of its nodes has received a message, use `invalidate_subtree` if nodes were
+    /// modified directly.
+    ///
+    /// # Notes
+    ///
+    /// Size is in the units of the target, scale factor of the UI is not applied.
+    pub fn draw_subtree(
+        &mut self,
+        root: Handle<UINode<M, C>>,
+        size: Vector2<f32>,
+    ) -> Option<&DrawingContext> {
+        scope_profile!();
+
+        if self.nodes[root].parent().is_some() {
+            println!(
+                "Sub-tree {:?} can't be drawn separately, it is linked to a parent!",
+                root
+            );
+            return None;
+        }
+
+        let mut nodes = Vec::new();
+        self.stack.clear();
+        self.stack.push(root);
+        while let Some(handle) = self.stack.pop() {
+            let node = &self.nodes[handle];
+            self.stack.extend_from_slice(node.children());
+            nodes.push(handle);
+        }
+        let layout_valid = nodes.iter().all(|&handle| {
+            let node = &self.nodes[handle];
+            Widget::is_measure_valid(node) && Widget::is_arrange_valid(node)
+        });
+
+        let selection_color = self.selection_color();
+        let inactive_selection_color = self.inactive_selection_color();
+        let inherit_tint = self.is_tint_inherited();
+        let subtree = self
+            .offscreen
+            .entry(root)
+            .or_insert_with(|| OffscreenSubtree {
+                size,
+                dirty: true,
+                drawing_context: DrawingContext::new(),
+            });
+        subtree
+            .drawing_context
+            .set_selection_colors(selection_color, inactive_selection_color);
+        subtree.drawing_context.set_tint_inheritance(inherit_tint);
+        if subtree.size != size || !layout_valid {
+            subtree.size = size;
+            subtree.dirty = true;
+        }
+        if !subtree.dirty {
+            return None;
+        }
+
+        let bounds = Rect::new(0.0, 0.0, size.x, size.y);
+        self.update_visibility_from(root);
+        self.node(root).measure(self, size);
+        self.node(root).arrange(self, &bounds);
+        self.update_transform_from(root);
+        self.calculate_clip_bounds(root, bounds);
+
+        for &handle in nodes.iter() {
+            self.nodes[handle].command_indices.borrow_mut().clear();
+        }
+
+        let subtree = self.offscreen.get_mut(&root).unwrap();
+        subtree.dirty = false;
+        subtree.drawing_context.clear();
+        draw_node(&self.nodes, root, None, &mut subtree.drawing_context);
+
+        Some(&subtree.drawing_context)
+    }
+
+    /// Unlinks given node from its parent, so its sub-tree can be drawn by `draw_subtree`.
+    pub fn detach_subtree(&mut self, root: Handle<UINode<M, C>>) {
+        if self.nodes[root].parent().is_some() {
+            self.unlink_node_internal(root);
+        }
+    }
+
+    /// Forces sub-tree that is drawn by `draw_subtree` to be redrawn on next call.
+    pub fn invalidate_subtree(&mut self, root: Handle<UINode<M, C>>) {
+        if let Some(subtree) = self.offscreen.get_mut(&root) {
+            subtree.dirty = true;
+        }
+    }
+
+    /// Stops tracking changes of sub-tree that was drawn by `draw_subtree` and frees its
+    /// drawing context. The sub-tree remains unlinked, link it back to show it with the rest
+    /// of the UI.
+    pub fn release_subtree(&mut self, root: Handle<UINode<M, C>>) {
+        self.offscreen.remove(&root);
+    }
+
+    /// Returns true if given node is root of a sub-tree that is drawn by `draw_subtree`.
+    pub fn is_offscreen_root(&self, node: Handle<UINode<M, C>>) -> bool {
+        self.offscreen.contains_key(&node)
+    }
+
+    /// Marks off-screen sub-tree that contains given node as changed.
+    fn invalidate_offscreen_of(&mut self, node: Handle<UINode<M, C>>) {
+        if self.offscreen.is_empty() {
+            return;
+        }
+        let mut root = node;
+        while let Some(parent_node) = self.nodes.try_borrow(root) {
+            if parent_node.parent().is_none() {
+                break;
+            }
+            root = parent_node.parent();
+        }
+        self.invalidate_subtree(root);
+    }
+
+    /// Checks whether given point (in screen coordinates) is clipped by the node or by any of
+    /// its ancestors. Every node is tested in its own untransformed space.
+    fn is_node_clipped(&self, node_handle: Handle<UINode<M, C>>, screen_pt: Vector2<f32>) -> bool {
+        scope_profile!();
+
+        let mut clipped = true;
+
+        let widget = self.nodes.borrow(node_handle);
+
+        if widget.is_globally_visible() {
+            let pt = match to_node_space(&self.nodes, node_handle, screen_pt) {
+                Some(pt) => pt,
+                None => return true,
+            };
+
+            clipped = !widget.screen_bounds().contains(pt);
+
+            if !clipped {
+                for command_index in widget.command_indices.borrow().iter() {
+                    if let Some(command) = self.drawing_context.get_commands().get(*command_index) {
+                        if let Some(geometry) = command.clipping_geometry.as_ref() {
+                            // Geometry of commands is in physical units.
+                            if geometry.is_contains_point(self.logical_to_physical(pt)) {
+                                clipped = false;
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Point can be clipped by parent's clipping geometry.
+            if !widget.parent().is_none() && !clipped {
+                clipped |= self.is_node_clipped(widget.parent(), screen_pt);
+            }
+        }
+
+        clipped
+    }
+
+    fn is_node_contains_point(
+        &self,
+        node_handle: Handle<UINode<M, C>>,
+        screen_pt: Vector2<f32>,
+    ) -> bool {
+        scope_profile!();
+
+        let widget = self.nodes.borrow(node_handle);
+
+        if !widget.is_globally_visible() {
+            return false;
+        }
+
+        let pt = match to_node_space(&self.nodes, node_handle, screen_pt) {
+            Some(pt) => pt,
+            None => return false,
+        };
+
+        if let UINode::Image(image) = widget {
+            if !image.is_opaque_at(self, pt) {
+                return false;
+            }
+        }
+
+        if !self.is_node_clipped(node_handle, screen_pt) {
+            for command_index in widget.command_indices.borrow().iter() {
+                if let Some(command) = self.drawing_context.get_commands().get(*command_index) {
+                    if self
+                        .drawing_context
+                        .is_command_contains_point(command, self.logical_to_physical(pt))
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        let padding = widget.hit_test_padding();
+        if padding != Thickness::zero() && !widget.command_indices.borrow().is_empty() {
+            let expanded = padding.inflate_rect(&widget.screen_bounds());
+            // Expanded region is outside of bounds of the node, so it can be clipped only by
+            // parents.
+            if expanded.contains(pt)
+                && (widget.parent().is_none() || !self.is_node_clipped(widget.parent(), screen_pt))
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn pick_node(
+        &self,
+        node_handle: Handle<UINode<M, C>>,
+        pt: Vector2<f32>,
+        layer: u8,
+        level: &mut i32,
+    ) -> Handle<UINode<M, C>> {
+        scope_profile!();
+
+        let widget = self.nodes.borrow(node_handle);
+
+        if !widget.is_hit_test_visible() || !widget.enabled() {
+            return Handle::NONE;
+        }
+
+        let (mut picked, mut topmost_picked_level) =
+            if !widget.is_click_through() && self.is_node_contains_point(node_handle, pt) {
+                (node_handle, *level)
+            } else {
+                (Handle::NONE, 0)
+            };
+
+        for child_handle in widget.children() {
+            if is_other_layer(Some(layer), self.nodes[*child_handle].render_layer()) {
+                continue;
+            }
+            *level += 1;
+            let picked_child = self.pick_node(*child_handle, pt, layer, level);
+            if picked_child.is_some() && *level > topmost_picked_level {
+                topmost_picked_level = *level;
+                picked = picked_child;
+            }
+        }
+
+        picked
+    }
+
+    fn update_layer_roots(&mut self) {
+        if !self.layer_roots_dirty {
+            return;
+        }
+        self.layer_roots_dirty = false;
+        self.layer_roots.clear();
+        collect_layer_roots(&self.nodes, self.root_canvas, 0, &mut self.layer_roots);
+        // Stable sort keeps tree order (and so z-index order) within a layer.
+        let nodes = &self.nodes;
+        self.layer_roots
+            .sort_by_key(|handle| nodes[*handle].render_layer());
+    }
+
+    /// Picks node in sub-tree of `root` in order inverse to drawing order of layers: sub-trees
+    /// of higher layers are tested first.
+    fn pick_layered(&self, root: Handle<UINode<M, C>>, pt: Vector2<f32>) -> Handle<UINode<M, C>> {
+        for &layer_root in self.layer_roots.iter().rev() {
+            if layer_root == root || !self.nodes.is_valid_handle(layer_root) {
+                continue;
+            }
+            // Only layers inside of the sub-tree are allowed.
+            let mut parent = self.nodes[layer_root].parent();
+            while parent.is_some() && parent != root {
+                parent = self.nodes[parent].parent();
+            }
+            if parent == root {
+                let mut level = 0;
+                let layer = self.nodes[layer_root].render_layer();
+                let picked = self.pick_node(layer_root, pt, layer, &mut level);
+                if picked.is_some() {
+                    return picked;
+                }
+            }
+        }
+
+        let mut level = 0;
+        let layer = effective_render_layer(&self.nodes, root);
+        self.pick_node(root, pt, layer, &mut level)
+    }
+
+    pub fn cursor_position(&self) -> Vector2<f32> {
+        self.cursor_position
+    }
+
+    pub fn hit_test(&self, pt: Vector2<f32>) -> Handle<UINode<M, C>> {
+        scope_profile!();
+
+        if self.nodes.is_valid_handle(self.captured_node) {
+            self.captured_node
+        } else if self.picking_stack.is_empty() {
+            // We're not restricted to any node, just start from root.
+            self.pick_layered(self.root_canvas, pt)
+        } else {
+            // We have some picking restriction chain.
+            // Go over picking stack and try each entry. This will help with picking
+            // in a series of popups, especially in menus where may be many open popups
+            // at the same time.
+            for root in self.picking_stack.iter().rev() {
+                if self.nodes.is_valid_handle(root.handle) {
+                    let picked = self.pick_layered(root.handle, pt);
+                    if picked.is_some() {
+                        return picked;
+                    }
+                }
+                if root.stop {
+                    break;
+                }
+            }
+            Handle::NONE
+        }
+    }
+
+    /// Searches a node down on tree starting from give root that matches a criteria
+    /// defined by a given func.
+    pub fn find_by_criteria_down<Func>(
+        &self,
+        node_handle: Handle<UINode<M, C>>,
+        func: &Func,
+    ) -> Handle<UINode<M, C>>
+    where
+        Func: Fn(&UINode<M, C>) -> bool,
+    {
+        let node = self.nodes.borrow(node_handle);
+
+        if func(node) {
+            return node_handle;
+        }
+
+        for child_handle in node.children() {
+            let result = self.find_by_criteria_down(*child_handle, func);
+
+            if result.is_some() {
+                return result;
+            }
+        }
+
+        Handle::NONE
+    }
+
+    /// Searches a node up on tree starting from given root that matches a criteria
+    /// defined by a given func.
+    pub fn find_by_criteria_up<Func>(
+        &self,
+        node_handle: Handle<UINode<M, C>>,
+        func: Func,
+    ) -> Handle<UINode<M, C>>
+    where
+        Func: Fn(&UINode<M, C>) -> bool,
+    {
+        let node = self.nodes.borrow(node_handle);
+
+        if func(node) {
+            return node_handle;
+        }
+
+        if node.parent().is_some() {
+            self.find_by_criteria_up(node.parent(), func)
+        } else {
+            Handle::NONE
+        }
+    }
+
+    /// Checks if specified node is a child of some other node on `root_handle`. This method
+    /// is useful to understand if some event came from some node down by tree.
+    pub fn is_node_child_of(
+        &self,
+        node_handle: Handle<UINode<M, C>>,
+        root_handle: Handle<UINode<M, C>>,
+    ) -> bool {
+        self.nodes
+            .borrow(root_handle)
+            .has_descendant(node_handle, self)
+    }
+
+    /// Recursively calculates clipping bounds for every node.
+    fn calculate_clip_bounds(&self, node: Handle<UINode<M, C>>, parent_bounds: Rect<f32>) {
+        let node = &self.nodes[node];
+        node.clip_bounds
+            .set(node.screen_bounds().clip_by(parent_bounds));
+        for &child in node.children() {
+            self.calculate_clip_bounds(child, node.clip_bounds.get());
+        }
+    }
+
+    /// Checks if specified node is a direct child of some other node on `root_handle`.
+    pub fn is_node_direct_child_of(
+        &self,
+        node_handle: Handle<UINode<M, C>>,
+        root_handle: Handle<UINode<M, C>>,
+    ) -> bool {
+        for child_handle in self.nodes.borrow(root_handle).children() {
+            if *child_handle == node_handle {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Searches a node by name up on tree starting from given root node.
+    pub fn find_by_name_up(
+        &self,
+        node_handle: Handle<UINode<M, C>>,
+        name: &str,
+    ) -> Handle<UINode<M, C>> {
+        self.find_by_criteria_up(node_handle, |node| node.name() == name)
+    }
+
+    /// Searches a node by name down on tree starting from given root node.
+    pub fn find_by_name_down(
+        &self,
+        node_handle: Handle<UINode<M, C>>,
+        name: &str,
+    ) -> Handle<UINode<M, C>> {
+        self.find_by_criteria_down(node_handle, &|node| node.name() == name)
+    }
+
+    /// Searches a node by name up on tree starting from given root node and tries to borrow it if exists.
+    pub fn borrow_by_name_up(
+        &self,
+        start_node_handle: Handle<UINode<M, C>>,
+        name: &str,
+    ) -> &UINode<M, C> {
+        self.nodes
+            .borrow(self.find_by_name_up(start_node_handle, name))
+    }
+
+    /// Searches a node by name down on tree starting from given root node and tries to borrow it if exists.
+    pub fn borrow_by_name_down(
+        &self,
+        start_node_handle: Handle<UINode<M, C>>,
+        name: &str,
+    ) -> &UINode<M, C> {
+        self.nodes
+            .borrow(self.find_by_name_down(start_node_handle, name))
+    }
+
+    /// Searches for a node up on tree that satisfies some criteria and then borrows
+    /// shared reference.
+    ///
+    /// # Panics
+    ///
+    /// It will panic if there no node that satisfies given criteria.
+    pub fn borrow_by_criteria_up<Func>(
+        &self,
+        start_node_handle: Handle<UINode<M, C>>,
+        func: Func,
+    ) -> &UINode<M, C>
+    where
+        Func: Fn(&UINode<M, C>) -> bool,
+    {
+        self.nodes
+            .borrow(self.find_by_criteria_up(start_node_handle, func))
+    }
+
+    pub fn try_borrow_by_criteria_up<Func>(
+        &self,
+        start_node_handle: Handle<UINode<M, C>>,
+        func: Func,
+    ) -> Option<&UINode<M, C>>
+    where
+        Func: Fn(&UINode<M, C>) -> bool,
+    {
+        self.nodes
+            .try_borrow(self.find_by_criteria_up(start_node_handle, func))
+    }
+
+    pub fn try_borrow_by_criteria_up_mut<Func>(
+        &mut self,
+        start_node_handle: Handle<UINode<M, C>>,
+        func: Func,
+    ) -> Option<&mut UINode<M, C>>
+    where
+        Func: Fn(&UINode<M, C>) -> bool,
+    {
+        self.nodes
+            .try_borrow_mut(self.find_by_criteria_up(start_node_handle, func))
+    }
+
+    /// Returns instance of message sender which can be used to push messages into queue
+    /// from other threads.
+    pub fn sender(&self) -> Sender<UiMessage<M, C>> {
+        self.sender.clone()
+    }
+
+    pub fn send_message(&self, message: UiMessage<M, C>) {
+        if self.batch_depth > 0 && message.need_perform_layout() {
+            self.batch_deferred.borrow_mut().push(message);
+        } else {
+            self.sender.send(message).unwrap()
+        }
+    }
+
+    /// Starts a batch of bulk changes of the tree. Messages that need up-to-date layout (like
+    /// `WidgetMessage::center`) normally update the whole UI before they're handled, in a batch
+    /// they are deferred instead and layout is updated only once in `end_batch`. Batches can
+    /// be nested, only the outermost `end_batch` finishes the batch.
+    pub fn begin_batch(&mut self) {
+        self.batch_depth += 1;
+    }
+
+    /// Finishes a batch started by `begin_batch`. At the end of the outermost batch layout is
+    /// updated once and deferred messages are sent again, so they're handled with settled
+    /// layout. Unlike `update`, it does not apply deferred commands or advance timers.
+    pub fn end_batch(&mut self) {
+        if self.batch_depth == 0 {
+            return;
+        }
+        self.batch_depth -= 1;
+        if self.batch_depth == 0 {
+            self.settle_layout();
+            for message in self.batch_deferred.take() {
+                message.set_perform_layout(false);
+                self.send_message(message);
+            }
+        }
+    }
+
+    /// Performs given bulk changes of the tree in a batch, see `begin_batch`.
+    pub fn batch<F, R>(&mut self, func: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        self.begin_batch();
+        let result = func(self);
+        self.end_batch();
+        result
+    }
+
+    pub fn is_in_batch(&self) -> bool {
+        self.batch_depth > 0
+    }
+
+    /// Moves child to given position in children list of its parent, index is clamped to
+    /// valid range. Order of children defines draw order (last child is drawn on top) and
+    /// position of children in panels such as stack panel. `WidgetMessage::ChildrenReordered`
+    /// is sent to the parent if order has changed.
+    pub fn set_child_index(
+        &mut self,
+        parent: Handle<UINode<M, C>>,
+        child: Handle<UINode<M, C>>,
+        new_index: usize,
+    ) {
+        if let Some(parent_node) = self.nodes.try_borrow_mut(parent) {
+            if parent_node.set_child_index(child, new_index) {
+                self.send_message(WidgetMessage::children_reordered(
+                    parent,
+                    MessageDirection::FromWidget,
+                ));
+            }
+        }
+    }
+
+    /// Moves child to the end of children list of its parent, so it is drawn above its
+    /// siblings. Unlike `WidgetMessage::TopMost` it does not keep modal windows on top.
+    pub fn bring_to_front(&mut self, child: Handle<UINode<M, C>>) {
+        let parent = self.node(child).parent();
+        self.set_child_index(parent, child, usize::MAX);
+    }
+
+    /// Moves child to the beginning of children list of its parent, so it is drawn below its
+    /// siblings.
+    pub fn send_to_back(&mut self, child: Handle<UINode<M, C>>) {
+        let parent = self.node(child).parent();
+        self.set_child_index(parent, child, 0);
+    }
+
+    // Puts node at the end of children list of a parent node.
+    //
+    // # Notes
+    //
+    // Node will be topmost *only* on same hierarchy level! So if you have a floating
+    // window (for example) and a window embedded into some other control (yes this is
+    // possible) then floating window won't be the topmost.
+    //
+    // Modal windows always stay above any other nodes on same hierarchy level.
+    fn make_topmost(&mut self, node: Handle<UINode<M, C>>) {
+        let parent = self.node(node).parent();
+        if parent.is_some() {
+            self.stack.clear();
+            for &child in self.nodes[parent].children() {
+                if child != node {
+                    if let UINode::Window(window) = &self.nodes[child] {
+                        if window.is_modal() {
+                            self.stack.push(child);
+                        }
+                    }
+                }
+            }
+
+            let parent = &mut self.nodes[parent];
+            parent.remove_child(node);
+            parent.add_child(node, false);
+
+            for &modal in self.stack.iter() {
+                parent.remove_child(modal);
+                parent.add_child(modal, false);
+            }
+        }
+    }
+
+    fn preview_message(&mut self, message: &mut UiMessage<M, C>) {
+        scope_profile!();
+
+        // Fire preview handler first. This will allow controls to do some actions before
+        // message will begin bubble routing.
+        for node in self.nodes.iter() {
+            node.preview_message(self, message);
+        }
+    }
+
+    fn bubble_message(&mut self, message: &mut UiMessage<M, C>) {
+        scope_profile!();
+
+        // Dispatch event using bubble strategy. Bubble routing means that message will go
+        // from specified destination up on tree to tree root.
+        // Gather chain of nodes from source to root.
+        self.bubble_queue.clear();
+        self.bubble_queue.push_back(message.destination());
+        let mut parent = self.nodes[message.destination()].parent();
+        while parent.is_some() && self.nodes.is_valid_handle(parent) {
+            self.bubble_queue.push_back(parent);
+            parent = self.nodes[parent].parent();
+        }
+
+        while let Some(handle) = self.bubble_queue.pop_front() {
+            let (ticket, mut node) = self.nodes.take_reserve(handle);
+            node.handle_routed_message(self, message);
+            self.nodes.put_back(ticket, node);
+        }
+    }
+
+    /// Extracts UI event one-by-one from common queue. Each extracted event will go to *all*
+    /// available nodes first and only then will be moved outside of this method. This is one
+    /// of most important methods which must be called each frame of your game loop, otherwise
+    /// UI will not respond to any kind of events and simply speaking will just not work.
+    pub fn poll_message(&mut self) -> Option<UiMessage<M, C>> {
+        match self.receive_message() {
+            Ok(mut message) => {
+                // Removal notifications are passed outside as is, their destination is
+                // already destroyed.
+                if !self.nodes.is_valid_handle(message.destination()) {
+                    return Some(message);
+                }
+
+                self.invalidate_offscreen_of(message.destination());
+                self.nodes[message.destination()].visual_changed.set(true);
+
+                if self.is_input_filtered(&message) {
+                    message.set_handled(true);
+                    return Some(message);
+                }
+
+                // Messages sent through a copy of the sender could not be deferred by a batch,
+                // they're handled with current layout.
+                if message.need_perform_layout() && self.batch_depth == 0 {
+                    self.settle_layout();
+                }
+
+                self.preview_message(&mut message);
+                self.bubble_message(&mut message);
+
+                if let UiMessageData::Widget(msg) = &message.data() {
+                    match msg {
+                        WidgetMessage::RenderLayer(_) => self.layer_roots_dirty = true,
+                        WidgetMessage::ZIndex(_) => {
+                            self.layer_roots_dirty = true;
+                            // Keep order of children of a parent node of a node that changed z-index
+                            // the same as z-index of children.
+                            let parent = self.node(message.destination()).parent();
+                            if parent.is_some() {
+                                self.stack.clear();
+                                for child in self.nodes.borrow(parent).children() {
+                                    self.stack.push(*child);
+                                }
+
+                                let nodes = &mut self.nodes;
+                                self.stack.sort_by(|a, b| {
+                                    let z_a = nodes.borrow(*a).z_index();
+                                    let z_b = nodes.borrow(*b).z_index();
+                                    z_a.cmp(&z_b)
+                                });
+
+                                let parent = self.nodes.borrow_mut(parent);
+                                parent.clear_children();
+                                for child in self.stack.iter() {
+                                    parent.add_child(*child, false);
+                                }
+                            }
+                        }
+                        WidgetMessage::TopMost => {
+                            if message.destination().is_some() {
+                                self.make_topmost(message.destination());
+                            }
+                        }
+                        WidgetMessage::Unlink => {
+                            if message.destination().is_some() {
+                                self.unlink_node(message.destination());
+
+                                let node = &self.nodes[message.destination()];
+                                let new_position = node.screen_position;
+                                self.send_message(WidgetMessage::desired_position(
+                                    message.destination(),
+                                    MessageDirection::ToWidget,
+                                    new_position,
+                                ));
+                            }
+                        }
+                        &WidgetMessage::LinkWith(parent) => {
+                            if message.destination().is_some() {
+                                self.link_nodes_internal(message.destination(), parent, false);
+                            }
+                        }
+                        &WidgetMessage::LinkWithReverse(parent) => {
+                            if message.destination().is_some() {
+                                self.link_nodes_internal(message.destination(), parent, true);
+                            }
+                        }
+                        WidgetMessage::Remove => {
+                            if message.destination().is_some() {
+                                self.remove_node(message.destination());
+                            }
+                        }
+                        &WidgetMessage::Tooltip(tooltip) => {
+                            if message.destination() == self.tooltip_owner {
+                                self.hide_tooltip();
+                            }
+                            self.prepare_tooltip(tooltip);
+                        }
+                        WidgetMessage::Center => {
+                            if message.destination().is_some() {
+                                let node = self.node(message.destination());
+                                let size = node.actual_size();
+                                let parent = node.parent();
+                                let parent_size = if parent.is_some() {
+                                    if parent == self.root_canvas {
+                                        self.screen_size
+                                    } else {
+                                        self.node(parent).actual_size()
+                                    }
+                                } else {
+                                    self.screen_size
+                                };
+
+                                self.send_message(WidgetMessage::desired_position(
+                                    message.destination(),
+                                    MessageDirection::ToWidget,
+                                    (parent_size - size).scale(0.5),
+                                ));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                Some(message)
+            }
+            Err(e) => match e {
+                TryRecvError::Empty => {
+                    // Handled messages could invalidate layout, it is updated right away so
+                    // the frame is drawn with settled layout. Layout could send new messages,
+                    // they're handled in the same frame too.
+                    if self.batch_depth == 0
+                        && self.message_layout_passes < MAX_LAYOUT_ITERATIONS
+                        && !self.is_layout_valid()
+                    {
+                        self.message_layout_passes += 1;
+                        self.update_layout(self.screen_size);
+                        self.poll_message()
+                    } else {
+                        None
+                    }
+                }
+                TryRecvError::Disconnected => unreachable!(),
+            },
+        }
+    }
+
+    /// Destination node may be destroyed at the time we receive message, we have to discard
+    /// such messages. The only exception is removal notification.
+    fn receive_message(&mut self) -> Result<UiMessage<M, C>, TryRecvError> {
+        loop {
+            let message = self.receiver.try_recv()?;
+            if self.is_repeated_value_change(&message) {
+                continue;
+            }
+            if self.nodes.is_valid_handle(message.destination())
+                || matches!(
+                    message.data(),
+                    UiMessageData::Widget(WidgetMessage::NodeRemoved { .. })
+                )
+            {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// Sets a callback that sees every input message (mouse, keyboard, touch) for the node
+    /// or any of its descendants before the message is dispatched. If the callback returns
+    /// true, the message is swallowed: it is marked as handled and neither the node nor its
+    /// descendants or ancestors will receive it, however it is still returned from
+    /// `poll_message`. This is more targeted than disabling a node, for example a game view
+    /// can grab all keys while it is focused.
+    ///
+    /// # Ordering
+    ///
+    /// Filters are called from the root down to the destination of a message (tunneling),
+    /// so filter of an ancestor is called before filter of a descendant. All filters are
+    /// called before `Control::preview_message` and `Control::handle_routed_message`.
+    pub fn set_input_filter(&mut self, node: Handle<UINode<M, C>>, filter: Box<InputFilter<M, C>>) {
+        self.input_filters.insert(node, filter);
+    }
+
+    pub fn remove_input_filter(
+        &mut self,
+        node: Handle<UINode<M, C>>,
+    ) -> Option<Box<InputFilter<M, C>>> {
+        self.input_filters.remove(&node)
+    }
+
+    fn is_input_filtered(&mut self, message: &UiMessage<M, C>) -> bool {
+        if self.input_filters.is_empty()
+            || message.direction() != MessageDirection::FromWidget
+            || !matches!(
+                message.data(),
+                UiMessageData::Widget(
+                    WidgetMessage::MouseDown { .. }
+                        | WidgetMessage::MouseUp { .. }
+                        | WidgetMessage::MouseMove { .. }
+                        | WidgetMessage::MouseWheel { .. }
+                        | WidgetMessage::Tap { .. }
+                        | WidgetMessage::LongPress { .. }
+                        | WidgetMessage::Text(_)
+                        | WidgetMessage::KeyDown(_)
+                        | WidgetMessage::KeyUp(_)
+                )
+            )
+        {
+            return false;
+        }
+
+        let mut chain = Vec::new();
+        let mut handle = message.destination();
+        while let Some(node) = self.nodes.try_borrow(handle) {
+            chain.push(handle);
+            handle = node.parent();
+        }
+        for handle in chain.iter().rev() {
+            if let Some(filter) = self.input_filters.get_mut(handle) {
+                if filter(message) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Checks if the message is a value change notification with the same value as previous
+    /// notification from the same node and remembers the value otherwise.
+    fn is_repeated_value_change(&mut self, message: &UiMessage<M, C>) -> bool {
+        if message.direction() != MessageDirection::FromWidget || !message.data().is_value_change()
+        {
+            return false;
+        }
+        match self.nodes.try_borrow(message.destination()) {
+            Some(node) if node.is_event_coalescing_enabled() => {}
+            _ => return false,
+        }
+        if let Some(last) = self.last_value_changes.get(&message.destination()) {
+            if last.is_same_value_change(message.data()) {
+                return true;
+            }
+        }
+        self.last_value_changes
+            .insert(message.destination(), message.data().clone());
+        false
+    }
+
+    fn process_key(&mut self, button: KeyCode, state: ButtonState) -> bool {
+        // Tab is consumed only if it moved focus, otherwise it is delivered to focused widget
+        // as usual key, so text boxes can accept it as input.
+        if state == ButtonState::Pressed
+            && ((button == KeyCode::Tab
+                && !self.is_tab_accepted_by_focused()
+                && self.move_focus_by_tab(!self.keyboard_modifiers.shift))
+                || self.move_focus_by_arrow(button)
+                || (button == KeyCode::Escape && self.close_topmost_popup()))
+        {
+            true
+        } else if self.keyboard_focus_node.is_some() {
+            self.send_message(match state {
+                ButtonState::Pressed => WidgetMessage::key_down(
+                    self.keyboard_focus_node,
+                    MessageDirection::FromWidget,
+                    button,
+                ),
+                ButtonState::Released => WidgetMessage::key_up(
+                    self.keyboard_focus_node,
+                    MessageDirection::FromWidget,
+                    button,
+                ),
+            });
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets timing of repeats of held keys: first repeat happens after `delay` seconds, then
+    /// keys are repeated `rate` times per second. Only last pressed key is repeated, modifier
+    /// keys are never repeated. Repeats are delivered as usual key presses, so they move caret
+    /// in text boxes, change values of numeric fields, move focus in lists, etc. Repeats stop
+    /// when focus is moved by mouse or code and when window loses focus (see
+    /// `OsEvent::WindowFocused`). Zero rate disables repeats. Defaults are `DEFAULT_KEY_REPEAT_DELAY` and
+    /// `DEFAULT_KEY_REPEAT_RATE`.
+    pub fn set_key_repeat(&mut self, delay: f32, rate: f32) {
+        self.key_repeat_delay = delay.max(0.0);
+        self.key_repeat_rate = rate.max(0.0);
+    }
+
+    /// Returns delay and rate of key repeats, see `set_key_repeat`.
+    pub fn key_repeat(&self) -> (f32, f32) {
+        (self.key_repeat_delay, self.key_repeat_rate)
+    }
+
+    /// Sets distance (in logical units) that cursor must pass with pressed left mouse button
+    /// to turn a click into a drag. Smaller movement between press and release is still a
+    /// click, larger one starts drag and drop of draggable nodes or moving of windows and
+    /// suppresses click of buttons on release. Default is `DEFAULT_DRAG_THRESHOLD`.
+    pub fn set_drag_threshold(&mut self, threshold: f32) {
+        self.drag_threshold = threshold.max(0.0);
+    }
+
+    pub fn drag_threshold(&self) -> f32 {
+        self.drag_threshold
+    }
+
+    /// Returns true if cursor went farther than drag threshold since last press of left
+    /// mouse button, so current (or just finished) gesture is a drag and not a click.
+    pub fn is_drag_threshold_exceeded(&self) -> bool {
+        self.drag_context.threshold_exceeded
+    }
+
+    /// Returns true if drag of some node was started since last press of left mouse button.
+    /// Unlike `is_drag_threshold_exceeded` it is false for moves of the cursor over nodes that
+    /// do not allow drag.
+    pub fn is_drag_started(&self) -> bool {
+        self.drag_context.drag_started
+    }
+
+    fn update_key_repeat(&mut self, dt: f32) {
+        if !self.input_enabled || self.key_repeat_rate <= 0.0 {
+            self.held_key = None;
+            return;
+        }
+
+        if let Some(mut held) = self.held_key {
+            held.timer -= dt;
+            if held.timer <= 0.0 {
+                // At most one repeat per update, so a long frame does not produce a burst of
+                // repeats.
+                let period = 1.0 / self.key_repeat_rate;
+                held.timer += period;
+                if held.timer <= 0.0 {
+                    held.timer = period;
+                }
+                self.held_key = Some(held);
+                self.process_key(held.key, ButtonState::Pressed);
+            } else {
+                self.held_key = Some(held);
+            }
+        }
+    }
+
+    pub fn captured_node(&self) -> Handle<UINode<M, C>> {
+        self.captured_node
+    }
+
+    /// Starts (or stops) recording of every event passed to `process_os_event`. Starting
+    /// discards previous recording, stopping keeps it until `take_recorded_events`. Positions
+    /// are recorded in logical units, see [replay](replay/index.html) module docs.
+    pub fn record_events(&mut self, record: bool) {
+        if record {
+            self.recording = Default::default();
+            self.recording_time = Some(0.0);
+        } else {
+            self.recording_time = None;
+        }
+    }
+
+    pub fn is_recording_events(&self) -> bool {
+        self.recording_time.is_some()
+    }
+
+    /// Returns events recorded so far, recording continues with empty buffer if it is active.
+    pub fn take_recorded_events(&mut self) -> EventRecording {
+        std::mem::take(&mut self.recording)
+    }
+
+    /// Feeds recorded events back to `process_os_event` with relative timing of recording,
+    /// time is advanced by `update`. Events that were left from previous replay are
+    /// discarded.
+    pub fn replay_events(&mut self, recording: EventRecording) {
+        self.replay = recording.events.into();
+        self.replay_time = 0.0;
+    }
+
+    pub fn is_replaying_events(&self) -> bool {
+        !self.replay.is_empty()
+    }
+
+    fn update_replay(&mut self, dt: f32) {
+        if let Some(time) = self.recording_time.as_mut() {
+            *time += dt;
+        }
+
+        if self.replay.is_empty() {
+            return;
+        }
+        self.replay_time += dt;
+        while let Some(recorded) = self.replay.front() {
+            if recorded.time > self.replay_time {
+                break;
+            }
+            let mut event = self.replay.pop_front().unwrap().event;
+            match &mut event {
+                OsEvent::CursorMoved { position } | OsEvent::Touch { position, .. } => {
+                    *position = self.logical_to_physical(*position)
+                }
+                _ => (),
+            }
+            self.process_os_event(&event);
+        }
+    }
+
+    fn record_event(&mut self, event: &OsEvent, time: f32) {
+        let mut event = event.clone();
+        match &mut event {
+            OsEvent::CursorMoved { position } | OsEvent::Touch { position, .. } => {
+                *position = self.physical_to_logical(*position)
+            }
+            _ => (),
+        }
+        self.recording.events.push(RecordedEvent { time, event });
+    }
+
+    /// Translates raw window event into some specific UI message. This is one of the
+    /// most important methods of UI. You must call it each time you received a message
+    /// from a window.
+    pub fn process_os_event(&mut self, event: &OsEvent) -> bool {
+        if let Some(time) = self.recording_time {
+            self.record_event(event, time);
+        }
+
+        // Hierarchy could change since the last update, picking must respect layers of
+        // new nodes.
+        self.update_layer_roots();
+
+        if !self.input_enabled {
+            // Only track state of input devices, so hover state can be restored when input
+            // will be enabled again.
+            match *event {
+                OsEvent::CursorMoved { position } => {
+                    self.cursor_position = self.physical_to_logical(position);
+                    self.cursor_position_known = true;
+                }
+                OsEvent::KeyboardModifiers(modifiers) => self.keyboard_modifiers = modifiers,
+                _ => (),
+            }
+            return false;
+        }
+
+        if self.inspect_mode && self.process_inspector_event(event) {
+            return true;
+        }
+
+        let mut event_processed = false;
+
+        match event {
+            &OsEvent::MouseInput { button, state, .. } => {
+                match button {
+                    MouseButton::Left => self.mouse_state.left = state,
+                    MouseButton::Right => self.mouse_state.right = state,
+                    MouseButton::Middle => self.mouse_state.middle = state,
+                    _ => {}
+                }
+
+                match state {
+                    ButtonState::Pressed => {
+                        self.hide_tooltip();
+                        self.close_popups_outside(self.cursor_position);
+
+                        self.picked_node = self.hit_test(self.cursor_position);
+
+                        if button == MouseButton::Left {
+                            self.drag_context.click_pos = self.cursor_position;
+                            self.drag_context.threshold_exceeded = false;
+                            self.drag_context.drag_started = false;
+                        }
+
+                        // Try to find draggable node in hierarchy starting from picked node.
+                        if self.picked_node.is_some() {
+                            self.stack.clear();
+                            self.stack.push(self.picked_node);
+                            while let Some(handle) = self.stack.pop() {
+                                let node = &self.nodes[handle];
+                                if node.is_drag_allowed() {
+                                    self.drag_context.drag_node = handle;
+                                    self.stack.clear();
+                                    break;
+                                } else if node.parent().is_some() {
+                                    self.stack.push(node.parent());
+                                }
+                            }
+                        }
+
+                        self.set_keyboard_focus(self.picked_node, false);
+
+                        if self.picked_node.is_some() {
+                            self.send_message(WidgetMessage::mouse_down(
+                                self.picked_node,
+                                MessageDirection::FromWidget,
+                                self.cursor_position,
+                                button,
+                            ));
+                            event_processed = true;
+                        }
+                    }
+                    ButtonState::Released => {
+                        // Mouse capture could be taken by another widget since the last move
+                        // of the cursor (for example by scroll viewer which scrolls by drag).
+                        if self.nodes.is_valid_handle(self.captured_node) {
+                            self.picked_node = self.captured_node;
+                        }
+                        if self.picked_node.is_some() {
+                            if self.drag_context.is_dragging {
+                                self.drag_context.is_dragging = false;
+                                self.cursor_icon = CursorIcon::Default;
+
+                                // Try to find node with drop allowed in hierarchy starting from picked node.
+                                self.stack.clear();
+                                self.stack.push(self.picked_node);
+                                while let Some(handle) = self.stack.pop() {
+                                    let node = &self.nodes[handle];
+                                    if node.is_drop_allowed() {
+                                        self.send_message(WidgetMessage::drop(
+                                            handle,
+                                            MessageDirection::FromWidget,
+                                            self.drag_context.drag_node,
+                                        ));
+                                        self.stack.clear();
+                                        break;
+                                    } else if node.parent().is_some() {
+                                        self.stack.push(node.parent());
+                                    }
+                                }
+                            }
+                            self.drag_context.drag_node = Handle::NONE;
+
+                            self.send_message(WidgetMessage::mouse_up(
+                                self.picked_node,
+                                MessageDirection::FromWidget,
+                                self.cursor_position,
+                                button,
+                            ));
+                            event_processed = true;
+                        }
+                    }
+                }
+            }
+            OsEvent::CursorMoved { position } => {
+                // Cursor position is in physical units, convert it to logical.
+                self.cursor_position = self.physical_to_logical(*position);
+                self.cursor_position_known = true;
+                self.picked_node = self.hit_test(self.cursor_position);
+
+                let tooltip_owner = self.find_tooltip_owner(self.picked_node);
+                self.set_tooltip_owner(tooltip_owner);
+
+                if self.focus_follows_mouse {
+                    self.update_hover_focus();
+                }
+
+                if self.mouse_state.left == ButtonState::Pressed
+                    && (self.drag_context.click_pos - self.cursor_position).norm()
+                        > self.drag_threshold
+                {
+                    self.drag_context.threshold_exceeded = true;
+                }
+
+                if !self.drag_context.is_dragging
+                    && self.drag_context.threshold_exceeded
+                    && self.mouse_state.left == ButtonState::Pressed
+                    && self.picked_node.is_some()
+                    && self.drag_context.drag_node.is_some()
+                {
+                    self.drag_context.is_dragging = true;
+                    self.drag_context.drag_started = true;
+
+                    self.send_message(WidgetMessage::drag_started(
+                        self.picked_node,
+                        MessageDirection::FromWidget,
+                        self.drag_context.drag_node,
+                    ));
+
+                    self.cursor_icon = CursorIcon::Crosshair;
+                }
+
+                self.update_hover_state();
+
+                if self.picked_node.is_some() {
+                    // Fire mouse move
+                    self.send_message(WidgetMessage::mouse_move(
+                        self.picked_node,
+                        MessageDirection::FromWidget,
+                        self.cursor_position,
+                        self.mouse_state,
+                    ));
+
+                    if self.drag_context.is_dragging {
+                        self.send_message(WidgetMessage::drag_over(
+                            self.picked_node,
+                            MessageDirection::FromWidget,
+                            self.drag_context.drag_node,
+                        ));
+                    }
+
+                    event_processed = true;
+                }
+            }
+            &OsEvent::Touch {
+                id,
+                phase,
+                position,
+            } => {
+                self.process_touch(id, phase, position);
+                event_processed = true;
+            }
+            OsEvent::MouseWheel(_, y) => {
+                if self.picked_node.is_some() {
+                    self.send_message(WidgetMessage::mouse_wheel(
+                        self.picked_node,
+                        MessageDirection::FromWidget,
+                        self.cursor_position,
+                        *y,
+                    ));
+
+                    event_processed = true;
+                }
+            }
+            &OsEvent::KeyboardInput { button, state } => {
+                // Repeats are made by the UI, so repeats of OS are ignored.
+                let is_os_repeat = state == ButtonState::Pressed
+                    && matches!(self.held_key, Some(held) if held.key == button);
+                match state {
+                    ButtonState::Pressed if !is_os_repeat && is_repeatable(button) => {
+                        self.held_key = Some(HeldKey {
+                            key: button,
+                            timer: self.key_repeat_delay,
+                        });
+                    }
+                    ButtonState::Released if matches!(self.held_key, Some(held) if held.key == button) =>
+                    {
+                        self.held_key = None;
+                    }
+                    _ => (),
+                }
+
+                event_processed = is_os_repeat || self.process_key(button, state);
+            }
+            OsEvent::Character(unicode) => {
+                if self.keyboard_focus_node.is_some() {
+                    self.send_message(WidgetMessage::text(
+                        self.keyboard_focus_node,
+                        MessageDirection::FromWidget,
+                        *unicode,
+                    ));
+
+                    event_processed = true;
+                }
+            }
+            &OsEvent::KeyboardModifiers(modifiers) => {
+                // TODO: Is message needed for focused node?
+                self.keyboard_modifiers = modifiers;
+            }
+            &OsEvent::WindowFocused(focused) => {
+                // Release of a key is not reported to inactive window, so repeats must stop.
+                if !focused {
+                    self.held_key = None;
+                }
+            }
+        }
+
+        // Hovering is not a consumption of input, game still must receive it.
+        if event_processed && !matches!(event, OsEvent::CursorMoved { .. }) {
+            self.input_consumed = true;
+        }
+
+        self.prev_picked_node = self.picked_node;
+
+        for i in 0..self.nodes.get_capacity() {
+            let handle = self.nodes.handle_from_index(i);
+
+            if self.nodes.is_valid_handle(handle) {
+                let (ticket, mut node) = self.nodes.take_reserve(handle);
+
+                node.handle_os_event(handle, self, event);
+
+                self.nodes.put_back(ticket, node);
+            }
+        }
+
+        event_processed
+    }
+
+    /// Fires mouse leave for previously picked node and mouse enter for currently picked one.
+    fn update_hover_state(&mut self) {
+        if self.picked_node != self.prev_picked_node && self.prev_picked_node.is_some() {
+            let prev_picked_node = self.nodes.borrow_mut(self.prev_picked_node);
+            if prev_picked_node.is_mouse_directly_over {
+                prev_picked_node.is_mouse_directly_over = false;
+                self.send_message(WidgetMessage::mouse_leave(
+                    self.prev_picked_node,
+                    MessageDirection::FromWidget,
+                ));
+            }
+        }
+
+        if self.picked_node.is_some() {
+            let picked_node = self.nodes.borrow_mut(self.picked_node);
+            if !picked_node.is_mouse_directly_over {
+                picked_node.is_mouse_directly_over = true;
+                self.send_message(WidgetMessage::mouse_enter(
+                    self.picked_node,
+                    MessageDirection::FromWidget,
+                ));
+            }
+        }
+    }
+
+    /// Picks node under current cursor position and updates hover state (mouse enter and
+    /// leave messages, tooltip owner and focus if `set_focus_follows_mouse` is enabled) as if
+    /// the cursor was moved. It is called at the end of every `update`, after layout has
+    /// settled, so a widget that was scrolled or shown under stationary cursor becomes hovered
+    /// without any mouse movement. Picking is done by drawing commands of the last frame, so
+    /// a widget that has just appeared is hovered at the update after it was drawn. Does
+    /// nothing if input is disabled or if the cursor was never moved.
+    pub fn refresh_hover(&mut self) {
+        if !self.input_enabled || !self.cursor_position_known {
+            return;
+        }
+
+        self.picked_node = self.hit_test(self.cursor_position);
+
+        let tooltip_owner = self.find_tooltip_owner(self.picked_node);
+        self.set_tooltip_owner(tooltip_owner);
+
+        if self.focus_follows_mouse {
+            self.update_hover_focus();
+        }
+
+        self.update_hover_state();
+
+        self.prev_picked_node = self.picked_node;
+    }
+
+    /// Enables or disables processing of every input event. When input is disabled, the UI
+    /// is still updated and drawn, but `process_os_event` ignores every event and returns
+    /// `false`, so game can use its own shortcuts (for example to skip a cutscene).
+    ///
+    /// Disabling input releases mouse capture, cancels drag and drop and touch gestures,
+    /// so widgets that were pressed won't receive mouse up. Enabling input picks a node
+    /// under the last known cursor position and updates hover state, see `refresh_hover`.
+    pub fn set_input_enabled(&mut self, enabled: bool) {
+        if self.input_enabled == enabled {
+            return;
+        }
+
+        self.input_enabled = enabled;
+
+        if enabled {
+            self.refresh_hover();
+        } else {
+            // Content that is held by a finger or a mouse must not stay attached to pointer.
+            for node in self.nodes.iter_mut() {
+                if let UINode::ScrollViewer(scroll_viewer) = node {
+                    scroll_viewer.cancel_drag();
+                }
+            }
+            self.release_mouse_capture();
+            self.mouse_state = Default::default();
+            self.drag_context.is_dragging = false;
+            self.drag_context.drag_node = Handle::NONE;
+            self.cursor_icon = CursorIcon::Default;
+            self.touch_state = Default::default();
+            self.set_tooltip_owner(Handle::NONE);
+        }
+    }
+
+    pub fn is_input_enabled(&self) -> bool {
+        self.input_enabled
+    }
+
+    /// Returns true if some mouse button, wheel, touch or keyboard event was handled by the UI
+    /// since last call of `update`, so the game must not react on it. Clicks on areas without
+    /// any widget and on click-through widgets (see `Widget::set_click_through`) are not
+    /// handled. The flag is reset by `update`, so check it before updating the UI.
+    pub fn consumed_input_this_frame(&self) -> bool {
+        self.input_consumed
+    }
+
+    /// Measures given node in given pass, the pass becomes a part of the key of measurement
+    /// cache of the node. It is used by panels that measure their children more than once.
+    pub fn measure_node_in_pass(
+        &self,
+        node: Handle<UINode<M, C>>,
+        available_size: Vector2<f32>,
+        pass: MeasurePass,
+    ) {
+        self.measure_pass.set(pass);
+        self.node(node).measure(self, available_size);
+    }
+
+    /// Returns count of measurements that were taken from cache and count of measurements
+    /// that were actually performed, `(hits, misses)`.
+    pub fn measure_cache_stats(&self) -> (usize, usize) {
+        (
+            self.measure_cache_hits.get(),
+            self.measure_cache_misses.get(),
+        )
+    }
+
+    pub fn reset_measure_cache_stats(&self) {
+        self.measure_cache_hits.set(0);
+        self.measure_cache_misses.set(0);
+    }
+
+    /// Sets maximum depth of measure and arrange recursion. Nodes that are deeper in the tree
+    /// are not measured and arranged at all, chain of nodes that hit the limit is logged. It
+    /// protects from stack overflow on malformed or extremely deep trees. Default is
+    /// `DEFAULT_MAX_LAYOUT_DEPTH`.
+    pub fn set_max_layout_depth(&mut self, depth: usize) {
+        self.max_layout_depth = depth.max(1);
+    }
+
+    pub fn max_layout_depth(&self) -> usize {
+        self.max_layout_depth
+    }
+
+    /// Enters next level of layout recursion for given node, returns `None` if the node is too
+    /// deep. Level is left when the guard is dropped.
+    fn enter_layout_level(&self, node: Handle<UINode<M, C>>) -> Option<LayoutDepthGuard<'_>> {
+        let depth = self.layout_depth.get() + 1;
+        if depth > self.max_layout_depth {
+            if !self.layout_depth_exceeded.replace(true) {
+                let mut chain = Vec::new();
+                let mut handle = node;
+                while let Some(node) = self.nodes.try_borrow(handle) {
+                    // Chain is limited, so it is safe to print even if the tree has a cycle.
+                    if chain.len() > self.max_layout_depth {
+                        break;
+                    }
+                    chain.push(format!("{} '{}' {:?}", node.kind(), node.name(), handle));
+                    handle = node.parent();
+                }
+                chain.reverse();
+                println!(
+                    "Layout is deeper than {} levels, layout of node is skipped: {}",
+                    self.max_layout_depth,
+                    chain.join(" -> ")
+                );
+            }
+            return None;
+        }
+        self.layout_depth.set(depth);
+        Some(LayoutDepthGuard(&self.layout_depth))
+    }
+
+    pub fn nodes(&self) -> &Pool<UINode<M, C>> {
+        &self.nodes
+    }
+
+    pub fn root(&self) -> Handle<UINode<M, C>> {
+        self.root_canvas
+    }
+
+    pub fn add_node(&mut self, mut node: UINode<M, C>) -> Handle<UINode<M, C>> {
+        let children = node.children().to_vec();
+        node.clear_children();
+        let node_handle = self.nodes.spawn(node);
+        // Node must know its handle before it is linked to anything.
+        self.nodes[node_handle].deref_mut().handle = node_handle;
+        if self.root_canvas.is_some() {
+            self.link_nodes_internal(node_handle, self.root_canvas, false);
+        }
+        for child in children {
+            self.link_nodes_internal(child, node_handle, false)
+        }
+        let tooltip = self.nodes[node_handle].tooltip();
+        self.prepare_tooltip(tooltip);
+        node_handle
+    }
+
+    pub fn push_picking_restriction(&mut self, restriction: RestrictionEntry<M, C>) {
+        if let Some(top) = self.top_picking_restriction() {
+            assert_ne!(top.handle, restriction.handle);
+        }
+        self.picking_stack.push(restriction);
+    }
+
+    pub fn remove_picking_restriction(&mut self, node: Handle<UINode<M, C>>) {
+        if let Some(pos) = self.picking_stack.iter().position(|h| h.handle == node) {
+            self.picking_stack.remove(pos);
+        }
+    }
+
+    pub fn picking_restriction_stack(&self) -> &[RestrictionEntry<M, C>] {
+        &self.picking_stack
+    }
+
+    /// Removes all picking restrictions.
+    pub fn drop_picking_restrictions(&mut self) {
+        self.picking_stack.clear();
+    }
+
+    /// Opens given popup and puts it on top of popup stack. Auto-close popup is closed when
+    /// user presses Escape or clicks outside of it and outside of every popup that was opened
+    /// after it, so a click on nested popup (submenu) does not close its parent.
+    pub fn open_popup(&mut self, popup: Handle<UINode<M, C>>, auto_close: bool) {
+        self.register_popup(popup, auto_close);
+        self.send_message(PopupMessage::open(popup, MessageDirection::ToWidget));
+    }
+
+    /// Closes given popup together with every popup that was opened after it, topmost first.
+    pub fn close_popup(&mut self, popup: Handle<UINode<M, C>>) {
+        if let Some(index) = self.popup_stack.iter().position(|p| p.handle == popup) {
+            for entry in self.popup_stack[index..].iter().rev() {
+                self.send_message(PopupMessage::close(
+                    entry.handle,
+                    MessageDirection::ToWidget,
+                ));
+            }
+        } else {
+            self.send_message(PopupMessage::close(popup, MessageDirection::ToWidget));
+        }
+    }
+
+    /// Returns open popups, bottom first.
+    pub fn popup_stack(&self) -> &[OpenPopup<M, C>] {
+        &self.popup_stack
+    }
+
+    /// Adds the popup on top of popup stack, does nothing if it is already there.
+    pub(in crate) fn register_popup(&mut self, popup: Handle<UINode<M, C>>, auto_close: bool) {
+        if !self.popup_stack.iter().any(|p| p.handle == popup) {
+            self.popup_stack.push(OpenPopup {
+                handle: popup,
+                auto_close,
+            });
+        }
+    }
+
+    pub(in crate) fn unregister_popup(&mut self, popup: Handle<UINode<M, C>>) {
+        self.popup_stack.retain(|p| p.handle != popup);
+    }
+
+    /// Closes every auto-close popup that was opened after the topmost popup that contains
+    /// given point. If no popup contains the point, every auto-close popup is closed.
+    fn close_popups_outside(&mut self, pos: Vector2<f32>) {
+        let nodes = &self.nodes;
+        let first = self
+            .popup_stack
+            .iter()
+            .rposition(|p| {
+                nodes.is_valid_handle(p.handle) && nodes[p.handle].screen_bounds().contains(pos)
+            })
+            .map_or(0, |index| index + 1);
+        for entry in self.popup_stack[first..].iter().rev() {
+            if entry.auto_close {
+                self.send_message(PopupMessage::close(
+                    entry.handle,
+                    MessageDirection::ToWidget,
+                ));
+            }
+        }
+    }
+
+    /// Closes topmost auto-close popup, returns true if there was such popup.
+    fn close_topmost_popup(&mut self) -> bool {
+        match self.popup_stack.iter().rev().find(|p| p.auto_close) {
+            Some(entry) => {
+                self.send_message(PopupMessage::close(
+                    entry.handle,
+                    MessageDirection::ToWidget,
+                ));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Enables or disables outline around focused widget. Outline is shown only when focus
+    /// was changed by keyboard (Tab), not by mouse click.
+    pub fn set_focus_visual(&mut self, enabled: bool, color: Color, thickness: f32) {
+        self.focus_visual = if enabled {
+            Some(FocusVisual { color, thickness })
+        } else {
+            None
+        };
+    }
+
+    pub fn focus_visual(&self) -> Option<FocusVisual> {
+        self.focus_visual
+    }
+
+    pub fn keyboard_focus_node(&self) -> Handle<UINode<M, C>> {
+        self.keyboard_focus_node
+    }
+
+    /// Moves keyboard focus to given node, `Handle::NONE` removes focus from any node.
+    pub fn set_keyboard_focus_node(&mut self, node: Handle<UINode<M, C>>) {
+        self.set_keyboard_focus(node, false);
+    }
+
+    /// Enables focus-follows-mouse mode (disabled by default): when the cursor enters a
+    /// focusable (tab stop) node, the node receives keyboard focus, as if it was clicked. It
+    /// is useful for kiosk or arcade-like UIs.
+    ///
+    /// Focus changes only when the cursor enters another focusable node, so focus that was
+    /// moved by keyboard navigation (Tab, arrows) stays until the mouse is moved to another
+    /// node. Text boxes are excluded: hover never focuses a text box and never takes focus
+    /// away from a focused text box, so typed text won't go to another widget because of an
+    /// accidental mouse movement. Click a text box to focus it.
+    pub fn set_focus_follows_mouse(&mut self, enabled: bool) {
+        self.focus_follows_mouse = enabled;
+        self.hovered_focusable = Handle::NONE;
+    }
+
+    pub fn is_focus_follows_mouse(&self) -> bool {
+        self.focus_follows_mouse
+    }
+
+    /// Whether leaving a focusable node without entering another one clears keyboard focus in
+    /// focus-follows-mouse mode, disabled by default.
+    pub fn set_unfocus_on_mouse_leave(&mut self, enabled: bool) {
+        self.unfocus_on_mouse_leave = enabled;
+    }
+
+    pub fn is_unfocus_on_mouse_leave(&self) -> bool {
+        self.unfocus_on_mouse_leave
+    }
+
+    fn update_hover_focus(&mut self) {
+        let focusable = if self.picked_node.is_some() {
+            self.find_by_criteria_up(self.picked_node, |n| n.is_tab_stop())
+        } else {
+            Handle::NONE
+        };
+        let focusable = if focusable.is_some() && self.is_node_enabled(focusable) {
+            focusable
+        } else {
+            Handle::NONE
+        };
+        if focusable == self.hovered_focusable {
+            return;
+        }
+        self.hovered_focusable = focusable;
+
+        if let Some(UINode::TextBox(_)) = self.nodes.try_borrow(self.keyboard_focus_node) {
+            return;
+        }
+        if focusable.is_some() {
+            if !matches!(self.nodes[focusable], UINode::TextBox(_)) {
+                self.set_keyboard_focus(focusable, false);
+            }
+        } else if self.unfocus_on_mouse_leave {
+            self.set_keyboard_focus(Handle::NONE, false);
+        }
+    }
+
+    fn set_keyboard_focus(&mut self, node: Handle<UINode<M, C>>, by_keyboard: bool) {
+        self.keyboard_navigation = by_keyboard;
+
+        if self.keyboard_focus_node != node {
+            // Focus moved by keyboard (Tab, arrows) is a result of the held key, so it keeps
+            // repeating, any other focus change stops repeats.
+            if !by_keyboard {
+                self.held_key = None;
+            }
+
+            if self.keyboard_focus_node.is_some() {
+                self.send_message(WidgetMessage::lost_focus(
+                    self.keyboard_focus_node,
+                    MessageDirection::FromWidget,
+                ));
+            }
+
+            self.keyboard_focus_node = node;
+
+            if self.keyboard_focus_node.is_some() {
+                self.send_message(WidgetMessage::got_focus(
+                    self.keyboard_focus_node,
+                    MessageDirection::FromWidget,
+                ));
+            }
+        }
+    }
+
+    /// Makes given node a focus scope and activates it: Tab navigation will be trapped inside
+    /// the node until the scope is removed by `remove_focus_scope`. Current keyboard focus is
+    /// remembered and restored when the scope is removed. Modal windows do this automatically.
+    pub fn push_focus_scope(&mut self, scope: Handle<UINode<M, C>>) {
+        if self.focus_scopes.iter().any(|s| s.handle == scope) {
+            return;
+        }
+        // Node can be taken out of the pool if the scope is pushed by the node itself while
+        // it handles a message, in this case the node must set the flag itself.
+        if let Some(node) = self.nodes.try_borrow_mut(scope) {
+            node.set_focus_scope(true);
+        }
+        self.focus_scopes.push(FocusScopeEntry {
+            handle: scope,
+            prev_focus: self.keyboard_focus_node,
+        });
+    }
+
+    /// Deactivates given focus scope. If the scope was the active one, previous scope becomes
+    /// active and keyboard focus returns to the node that had it before the scope was pushed.
+    pub fn remove_focus_scope(&mut self, scope: Handle<UINode<M, C>>) {
+        if let Some(index) = self.focus_scopes.iter().position(|s| s.handle == scope) {
+            let entry = self.focus_scopes.remove(index);
+            if index == self.focus_scopes.len() {
+                let prev_focus = if self.nodes.is_valid_handle(entry.prev_focus) {
+                    entry.prev_focus
+                } else {
+                    Handle::NONE
+                };
+                let by_keyboard = self.keyboard_navigation;
+                self.set_keyboard_focus(prev_focus, by_keyboard);
+            }
+        }
+    }
+
+    /// Returns active focus scope or `Handle::NONE` if there is no active scope.
+    pub fn active_focus_scope(&self) -> Handle<UINode<M, C>> {
+        self.focus_scopes.last().map_or(Handle::NONE, |s| s.handle)
+    }
+
+    /// Returns root of the region in which Tab navigation happens: nearest focus scope that
+    /// contains focused node, if it is inside active scope, otherwise active scope itself.
+    /// Without any scopes, navigation is limited by top picking restriction (modal window,
+    /// etc.) if any.
+    fn tab_navigation_root(&self) -> Handle<UINode<M, C>> {
+        let active = self.active_focus_scope();
+        if self.nodes.is_valid_handle(self.keyboard_focus_node) {
+            let scope = self.find_by_criteria_up(self.keyboard_focus_node, |n| n.is_focus_scope());
+            if scope.is_some()
+                && (active.is_none() || scope == active || self.is_node_child_of(scope, active))
+            {
+                return scope;
+            }
+        }
+        if self.nodes.is_valid_handle(active) {
+            active
+        } else {
+            self.top_picking_restriction()
+                .map_or(self.root_canvas, |r| r.handle)
+        }
+    }
+
+    /// Collects every widget that can be focused by Tab navigation in depth-first order.
+    fn tab_stops(&self) -> Vec<Handle<UINode<M, C>>> {
+        self.tab_stops_of(self.tab_navigation_root())
+    }
+
+    fn tab_stops_of(&self, root: Handle<UINode<M, C>>) -> Vec<Handle<UINode<M, C>>> {
+        let mut tab_stops = Vec::new();
+        let mut stack = vec![root];
+        while let Some(handle) = stack.pop() {
+            let node = self.nodes.borrow(handle);
+            if !node.is_globally_visible() {
+                continue;
+            }
+            if node.is_tab_stop() && is_node_enabled(&self.nodes, handle) {
+                tab_stops.push(handle);
+            }
+            // Nested focus scopes are closed for navigation from outside.
+            if handle != root && node.is_focus_scope() {
+                continue;
+            }
+            // Reversed to visit children in their order.
+            stack.extend(node.children().iter().rev());
+        }
+        tab_stops
+    }
+
+    /// Moves keyboard focus to next (or previous) tab stop, wraps around at the ends.
+    /// Returns true if focused widget uses Tab as input, see `TextBox::set_accepts_tab`.
+    fn is_tab_accepted_by_focused(&self) -> bool {
+        matches!(self.nodes.try_borrow(self.keyboard_focus_node),
+            Some(UINode::TextBox(text_box)) if text_box.is_accepts_tab())
+    }
+
+    /// Moves keyboard focus to next (or previous) tab stop, returns true if focus has changed.
+    fn move_focus_by_tab(&mut self, forward: bool) -> bool {
+        let tab_stops = self.tab_stops();
+        if tab_stops.is_empty() {
+            return false;
+        }
+
+        // Focus may be on some inner part of a tab stop (text of a button, etc.).
+        let current = if self.nodes.is_valid_handle(self.keyboard_focus_node) {
+            self.find_by_criteria_up(self.keyboard_focus_node, |n| n.is_tab_stop())
+        } else {
+            Handle::NONE
+        };
+
+        let next = match tab_stops.iter().position(|&h| h == current) {
+            Some(index) if forward => (index + 1) % tab_stops.len(),
+            Some(index) => (index + tab_stops.len() - 1) % tab_stops.len(),
+            None if forward => 0,
+            None => tab_stops.len() - 1,
+        };
+
+        if tab_stops[next] == current {
+            return false;
+        }
+        self.set_keyboard_focus(tab_stops[next], true);
+        true
+    }
+
+    /// Orientation of a container for arrow navigation, it is taken from the container itself
+    /// or from first panel inside it (items panel of a list view, etc.).
+    pub(in crate) fn navigation_orientation(&self, container: Handle<UINode<M, C>>) -> Orientation {
+        let mut stack = vec![container];
+        while let Some(handle) = stack.pop() {
+            match self.nodes.try_borrow(handle) {
+                Some(UINode::StackPanel(panel)) => return panel.orientation(),
+                Some(UINode::WrapPanel(panel)) => return panel.orientation(),
+                Some(node) => stack.extend(node.children().iter().rev()),
+                None => (),
+            }
+        }
+        Orientation::Vertical
+    }
+
+    /// Moves keyboard focus among focusable items of closest container with enabled keyboard
+    /// navigation. Returns true if the key was used. If orientation of a container does not
+    /// match the key, outer containers will be checked.
+    fn move_focus_by_arrow(&mut self, key: KeyCode) -> bool {
+        let (orientation, forward) = match key {
+            KeyCode::Up => (Orientation::Vertical, false),
+            KeyCode::Down => (Orientation::Vertical, true),
+            KeyCode::Left => (Orientation::Horizontal, false),
+            KeyCode::Right => (Orientation::Horizontal, true),
+            _ => return false,
+        };
+
+        // Text box uses arrows to move caret, item containers use them to move selection.
+        match self.nodes.try_borrow(self.keyboard_focus_node) {
+            Some(UINode::TextBox(_))
+            | Some(UINode::ListView(_))
+            | Some(UINode::TreeRoot(_))
+            | None => return false,
+            _ => (),
+        }
+
+        let mut container = self.nodes[self.keyboard_focus_node].parent();
+        while container.is_some() {
+            let node = &self.nodes[container];
+            let mode = node.keyboard_navigation();
+            // Navigation does not leave focus scope.
+            let parent = if node.is_focus_scope() {
+                Handle::NONE
+            } else {
+                node.parent()
+            };
+
+            if mode != NavMode::None && self.navigation_orientation(container) == orientation {
+                let mut items = self.tab_stops_of(container);
+                items.retain(|&item| item != container);
+                let current = items.iter().position(|&item| {
+                    item == self.keyboard_focus_node
+                        || self.is_node_child_of(self.keyboard_focus_node, item)
+                });
+                if let Some(current) = current {
+                    let next = if forward {
+                        if current + 1 < items.len() {
+                            Some(current + 1)
+                        } else if mode == NavMode::Cycle {
+                            Some(0)
+                        } else {
+                            None
+                        }
+                    } else if current > 0 {
+                        Some(current - 1)
+                    } else if mode == NavMode::Cycle {
+                        Some(items.len() - 1)
+                    } else {
+                        None
+                    };
+                    if let Some(next) = next {
+                        self.set_keyboard_focus(items[next], true);
+                    }
+                    return true;
+                }
+            }
+
+            container = parent;
+        }
+
+        false
+    }
+
+    pub fn top_picking_restriction(&self) -> Option<RestrictionEntry<M, C>> {
+        self.picking_stack.last().cloned()
+    }
+
+    /// Use WidgetMessage::remove(...) to remove node.
+    fn remove_node(&mut self, node: Handle<UINode<M, C>>) {
+        let root_parent = self.detach_node(node);
+
+        let mut removed_nodes = Vec::new();
+        let mut removed_parents = Vec::new();
+        let mut tooltips = Vec::new();
+        let mut stack = vec![node];
+        while let Some(handle) = stack.pop() {
+            removed_nodes.push(handle);
+
+            if self.prev_picked_node == handle {
+                self.prev_picked_node = Handle::NONE;
+            }
+            if self.hovered_focusable == handle {
+                self.hovered_focusable = Handle::NONE;
+            }
+            if self.picked_node == handle {
+                self.picked_node = Handle::NONE;
+            }
+            if self.captured_node == handle {
+                self.captured_node = Handle::NONE;
+            }
+            if self.keyboard_focus_node == handle {
+                self.keyboard_focus_node = Handle::NONE;
+                self.held_key = None;
+            }
+            if self.tooltip_owner == handle {
+                self.tooltip_owner = Handle::NONE;
+                self.hide_tooltip();
+            }
+            if self.shown_tooltip == handle {
+                self.shown_tooltip = Handle::NONE;
+            }
+            self.offscreen.remove(&handle);
+            if let Some(bounds) = self.nodes[handle].drawn_bounds.get() {
+                self.mark_dirty(bounds);
+            }
+            if self.touch_state.pan_target == handle {
+                self.touch_state.pan_target = Handle::NONE;
+                self.touch_state.is_panning = false;
+            }
+            self.remove_picking_restriction(handle);
+            self.unregister_popup(handle);
+            self.focus_scopes.retain(|s| s.handle != handle);
+            self.spins.remove(&handle);
+            self.cursor_attachments.remove(&handle);
+            self.last_value_changes.remove(&handle);
+            self.input_filters.remove(&handle);
+
+            let removed = self.nodes.borrow(handle);
+            // Root of removed sub-tree is already detached from its parent.
+            let parent = if handle == node {
+                root_parent
+            } else {
+                removed.parent()
+            };
+            removed_parents.push((handle, parent));
+            tooltips.push(removed.tooltip());
+            for child in removed.children().iter() {
+                stack.push(*child);
+            }
+            self.nodes.free(handle);
+        }
+
+        for node in self.nodes.iter_mut() {
+            for removed_node in removed_nodes.iter() {
+                node.remove_ref(*removed_node);
+            }
+            if removed_nodes.contains(&node.tooltip()) {
+                node.set_tooltip(Handle::NONE);
+            }
+        }
+
+        // Removed nodes could be the last items of list-like widgets, their placeholders must
+        // be shown.
+        for node in self.nodes.iter() {
+            if let Some(items) = node.items_control() {
+                items.refresh_placeholder(self);
+            }
+        }
+
+        // Tooltips are owned by their widgets, so they must be removed too.
+        for tooltip in tooltips {
+            if self.nodes.is_valid_handle(tooltip) {
+                self.remove_node(tooltip);
+            }
+        }
+
+        // Nodes were collected in depth-first order, so reversed order gives children
+        // before their parents.
+        if self.structure_notifications {
+            for &(handle, parent) in removed_parents.iter().rev() {
+                self.send_message(WidgetMessage::node_removed(
+                    handle,
+                    MessageDirection::FromWidget,
+                    parent,
+                ));
+            }
+        }
+    }
+
+    /// Links specified child with specified parent. Link that would make a cycle (parent is the
+    /// child itself or one of its descendants) is refused.
+    #[inline]
+    fn link_nodes_internal(
+        &mut self,
+        child_handle: Handle<UINode<M, C>>,
+        parent_handle: Handle<UINode<M, C>>,
+        in_front: bool,
+    ) {
+        let mut ancestor = parent_handle;
+        while ancestor.is_some() {
+            if ancestor == child_handle {
+                println!(
+                    "Unable to link node {:?} to {:?}, it would make a cycle!",
+                    child_handle, parent_handle
+                );
+                return;
+            }
+            ancestor = self.nodes[ancestor].parent();
+        }
+
+        self.unlink_node_internal(child_handle);
+        self.nodes[child_handle].set_parent(parent_handle);
+        self.nodes[parent_handle].add_child(child_handle, in_front);
+        self.layer_roots_dirty = true;
+        if self.structure_notifications {
+            self.send_message(WidgetMessage::node_added(
+                child_handle,
+                MessageDirection::FromWidget,
+                parent_handle,
+            ));
+        }
+    }
+
+    /// Unlinks specified node from its parent, so node will become root.
+    #[inline]
+    fn unlink_node_internal(&mut self, node_handle: Handle<UINode<M, C>>) {
+        let parent_handle = self.detach_node(node_handle);
+        if self.structure_notifications && parent_handle.is_some() {
+            self.send_message(WidgetMessage::node_removed(
+                node_handle,
+                MessageDirection::FromWidget,
+                parent_handle,
+            ));
+        }
+    }
+
+    /// Detaches node from its parent without any notifications, returns handle of former parent.
+    fn detach_node(&mut self, node_handle: Handle<UINode<M, C>>) -> Handle<UINode<M, C>> {
+        // Replace parent handle of child
+        let node = self.nodes.borrow_mut(node_handle);
+        let parent_handle = node.parent();
+        if parent_handle.is_some() {
+            node.set_parent(Handle::NONE);
+
+            // Remove child from parent's children list
+            self.nodes[parent_handle].remove_child(node_handle);
+            self.layer_roots_dirty = true;
+        }
+        parent_handle
+    }
+
+    /// Unlinks specified node from its parent and attaches back to root canvas.
+    ///
+    /// Use [WidgetMessage::remove](enum.WidgetMessage.html#method.remove) to unlink
+    /// a node at runtime!
+    #[inline]
+    fn unlink_node(&mut self, node_handle: Handle<UINode<M, C>>) {
+        self.unlink_node_internal(node_handle);
+        self.link_nodes_internal(node_handle, self.root_canvas, false);
+    }
+
+    #[inline]
+    pub fn node(&self, node_handle: Handle<UINode<M, C>>) -> &UINode<M, C> {
+        self.nodes().borrow(node_handle)
+    }
+
+    /// Makes a deep copy of a node and all its descendants. Handles between copied nodes are
+    /// remapped to the copies, handles to nodes outside of the sub-tree still point to original
+    /// nodes. Copy is attached to the root canvas, just like any newly added node. Callbacks
+    /// (filters, draw callbacks, etc.) are shared between original and copy.
+    pub fn copy_node(&mut self, node: Handle<UINode<M, C>>) -> Handle<UINode<M, C>> {
+        let mut map = NodeHandleMapping::default();
+
+        let root = self.copy_node_recursive(node, &mut map);
+
+        for &node_handle in map.hash_map.values() {
+            self.nodes[node_handle].resolve(&map);
+        }
+
+        root
+    }
+
+    fn copy_node_recursive(
+        &mut self,
+        node_handle: Handle<UINode<M, C>>,
+        map: &mut NodeHandleMapping<M, C>,
+    ) -> Handle<UINode<M, C>> {
+        let node = self.nodes.borrow(node_handle);
+        let mut cloned = node.clone();
+
+        let mut cloned_children = Vec::new();
+        for child in node.children().to_vec() {
+            cloned_children.push(self.copy_node_recursive(child, map));
+        }
+
+        cloned.set_children(cloned_children);
+        let copy_handle = self.add_node(cloned);
+        map.add_mapping(node_handle, copy_handle);
+        copy_handle
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        brush::Brush,
+        core::{
+            algebra::{Matrix3, Vector2},
+            color::Color,
+            math::Rect,
+            pool::Handle,
+        },
+        draw::{CommandTexture, Draw, DrawingContext},
+        grid::{Column, GridBuilder, Row},
+        message::{
+            ButtonState, KeyCode, MessageDirection, MouseButton, OsEvent, ScrollBarMessage,
+            UiMessage, UiMessageData, WidgetMessage,
+        },
+        node::{StubNode, UINode},
+        stack_panel::StackPanelBuilder,
+        widget::{Widget, WidgetBuilder},
+        BuildContext, Control, HorizontalAlignment, Orientation, Thickness, UiCommand,
+        UserInterface, VerticalAlignment,
+    };
+    use std::ops::{Deref, DerefMut};
+
+    #[test]
+    fn center() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let widget_size = Vector2::new(100.0, 100.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let widget = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(widget_size.x)
+                .with_height(widget_size.y),
+        )
+        .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0); // Make sure layout was calculated.
+        ui.send_message(WidgetMessage::center(widget, MessageDirection::ToWidget));
+        while let Some(_) = ui.poll_message() {}
+        ui.update(screen_size, 0.0);
+        let expected_position = (screen_size - widget_size).scale(0.5);
+        let actual_position = ui.node(widget).actual_local_position();
+        assert_eq!(actual_position, expected_position);
+    }
+
+    #[test]
+    fn thickness_rect() {
+        let rect = Rect::new(10.0, 20.0, 100.0, 50.0);
+        let thickness = Thickness {
+            left: 1.0,
+            top: 2.0,
+            right: 3.0,
+            bottom: 4.0,
+        };
+        let deflated = thickness.deflate_rect(&rect);
+        assert_eq!(deflated, Rect::new(11.0, 22.0, 96.0, 44.0));
+        assert_eq!(thickness.inflate_rect(&deflated), rect);
+        assert_eq!(
+            thickness.inflate_rect(&rect),
+            Rect::new(9.0, 18.0, 104.0, 56.0)
+        );
+        // Size of deflated rect never goes negative.
+        assert_eq!(
+            Thickness::uniform(10.0).deflate_rect(&Rect::new(0.0, 0.0, 5.0, 30.0)),
+            Rect::new(10.0, 10.0, 0.0, 10.0)
+        );
+        assert_eq!(Thickness::horizontal(2.0).total_horizontal(), 4.0);
+        assert_eq!(Thickness::horizontal(2.0).total_vertical(), 0.0);
+        assert_eq!(
+            Thickness::vertical(3.0).axes_margin(),
+            Vector2::new(0.0, 6.0)
+        );
+    }
 
-    /// Translates raw window event into some specific UI message. This is one of the
-    /// most important methods of UI. You must call it each time you received a message
-    /// from a window.
-    pub fn process_os_event(&mut self, event: &OsEvent) -> bool {
-        let mut event_processed = false;
+    #[test]
+    fn copy_node() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let child = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let original = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(100.0)
+                .with_child(child),
+        )
+        .build(ctx);
+        let copy = ui.copy_node(original);
+        ui.update(screen_size, 0.0);
 
-        match event {
-            &OsEvent::MouseInput { button, state, .. } => {
-                match button {
-                    MouseButton::Left => self.mouse_state.left = state,
-                    MouseButton::Right => self.mouse_state.right = state,
-                    MouseButton::Middle => self.mouse_state.middle = state,
-                    _ => {}
-                }
+        assert_ne!(copy, original);
+        assert_eq!(ui.node(copy).parent(), ui.root());
+        let copy_child = ui.node(copy).children()[0];
+        assert_ne!(copy_child, child);
+        assert_eq!(ui.node(copy_child).parent(), copy);
 
-                match state {
-                    ButtonState::Pressed => {
-                        self.picked_node = self.hit_test(self.cursor_position);
+        ui.send_message(WidgetMessage::width(copy, MessageDirection::ToWidget, 50.0));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
 
-                        // Try to find draggable node in hierarchy starting from picked node.
-                        if self.picked_node.is_some() {
-                            self.stack.clear();
-                            self.stack.push(self.picked_node);
-                            while let Some(handle) = self.stack.pop() {
-                                let node = &self.nodes[handle];
-                                if node.is_drag_allowed() {
-                                    self.drag_context.drag_node = handle;
-                                    self.stack.clear();
-                                    break;
-                                } else if node.parent().is_some() {
-                                    self.stack.push(node.parent());
-                                }
-                            }
-                            self.drag_context.click_pos = self.cursor_position;
-                        }
+        assert_eq!(ui.node(original).actual_size(), Vector2::new(100.0, 100.0));
+        assert_eq!(ui.node(copy).actual_size(), Vector2::new(50.0, 100.0));
+        assert_eq!(ui.node(child).actual_size(), Vector2::new(98.0, 98.0));
+        assert_eq!(ui.node(copy_child).actual_size(), Vector2::new(48.0, 98.0));
+    }
 
-                        if self.keyboard_focus_node != self.picked_node {
-                            if self.keyboard_focus_node.is_some() {
-                                self.send_message(WidgetMessage::lost_focus(
-                                    self.keyboard_focus_node,
-                                    MessageDirection::FromWidget,
-                                ));
-                            }
+    #[test]
+    fn click_through_hud() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let button = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(100.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .build(ctx);
+        BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_click_through(true)
+                .with_background(Brush::Solid(Color::TRANSPARENT))
+                .with_child(button),
+        )
+        .build(ctx);
+        ui.update(screen_size, 0.0);
+        ui.draw();
+
+        let click = |ui: &mut UserInterface<(), StubNode>, position| {
+            ui.process_os_event(&OsEvent::CursorMoved { position });
+            for &state in [ButtonState::Pressed, ButtonState::Released].iter() {
+                ui.process_os_event(&OsEvent::MouseInput {
+                    button: MouseButton::Left,
+                    state,
+                });
+            }
+            while ui.poll_message().is_some() {}
+            let consumed = ui.consumed_input_this_frame();
+            ui.update(screen_size, 0.0);
+            consumed
+        };
 
-                            self.keyboard_focus_node = self.picked_node;
+        assert!(!click(&mut ui, Vector2::new(500.0, 500.0)));
+        assert!(click(&mut ui, Vector2::new(50.0, 50.0)));
+        assert!(!click(&mut ui, Vector2::new(500.0, 500.0)));
+    }
 
-                            if self.keyboard_focus_node.is_some() {
-                                self.send_message(WidgetMessage::got_focus(
-                                    self.keyboard_focus_node,
-                                    MessageDirection::FromWidget,
-                                ));
-                            }
-                        }
+    #[test]
+    fn drag_threshold() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        ui.set_drag_threshold(4.0);
 
-                        if self.picked_node.is_some() {
-                            self.send_message(WidgetMessage::mouse_down(
-                                self.picked_node,
-                                MessageDirection::FromWidget,
-                                self.cursor_position,
-                                button,
-                            ));
-                            event_processed = true;
-                        }
-                    }
-                    ButtonState::Released => {
-                        if self.picked_node.is_some() {
-                            if self.drag_context.is_dragging {
-                                self.drag_context.is_dragging = false;
-                                self.cursor_icon = CursorIcon::Default;
+        let mouse = |ui: &mut UserInterface<(), StubNode>, state| {
+            ui.process_os_event(&OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state,
+            });
+        };
+        let move_to = |ui: &mut UserInterface<(), StubNode>, x, y| {
+            ui.process_os_event(&OsEvent::CursorMoved {
+                position: Vector2::new(x, y),
+            });
+        };
 
-                                // Try to find node with drop allowed in hierarchy starting from picked node.
-                                self.stack.clear();
-                                self.stack.push(self.picked_node);
-                                while let Some(handle) = self.stack.pop() {
-                                    let node = &self.nodes[handle];
-                                    if node.is_drop_allowed() {
-                                        self.send_message(WidgetMessage::drop(
-                                            handle,
-                                            MessageDirection::FromWidget,
-                                            self.drag_context.drag_node,
-                                        ));
-                                        self.stack.clear();
-                                        break;
-                                    } else if node.parent().is_some() {
-                                        self.stack.push(node.parent());
-                                    }
-                                }
-                            }
-                            self.drag_context.drag_node = Handle::NONE;
+        move_to(&mut ui, 50.0, 50.0);
+        mouse(&mut ui, ButtonState::Pressed);
+        move_to(&mut ui, 52.0, 51.0);
+        assert!(!ui.is_drag_threshold_exceeded());
+        move_to(&mut ui, 60.0, 50.0);
+        assert!(ui.is_drag_threshold_exceeded());
+        mouse(&mut ui, ButtonState::Released);
+        // Stays set after release, so handlers of mouse up could skip clicks.
+        assert!(ui.is_drag_threshold_exceeded());
+
+        mouse(&mut ui, ButtonState::Pressed);
+        assert!(!ui.is_drag_threshold_exceeded());
+        mouse(&mut ui, ButtonState::Released);
+        move_to(&mut ui, 100.0, 100.0);
+        assert!(!ui.is_drag_threshold_exceeded());
+    }
 
-                            self.send_message(WidgetMessage::mouse_up(
-                                self.picked_node,
-                                MessageDirection::FromWidget,
-                                self.cursor_position,
-                                button,
-                            ));
-                            event_processed = true;
-                        }
-                    }
+    #[test]
+    fn key_repeat() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        ui.set_key_repeat(0.5, 10.0);
+        let border = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        ui.set_keyboard_focus_node(border);
+
+        let key_downs = |ui: &mut UserInterface<(), StubNode>, dt| {
+            ui.update(screen_size, dt);
+            let mut count = 0;
+            while let Some(message) = ui.poll_message() {
+                if let UiMessageData::Widget(WidgetMessage::KeyDown(KeyCode::A)) = message.data() {
+                    count += 1;
                 }
             }
-            OsEvent::CursorMoved { position } => {
-                self.cursor_position = *position;
-                self.picked_node = self.hit_test(self.cursor_position);
+            count
+        };
 
-                if !self.drag_context.is_dragging
-                    && self.mouse_state.left == ButtonState::Pressed
-                    && self.picked_node.is_some()
-                    && self.drag_context.drag_node.is_some()
-                    && (self.drag_context.click_pos - *position).norm() > 5.0
-                {
-                    self.drag_context.is_dragging = true;
+        ui.process_os_event(&OsEvent::KeyboardInput {
+            button: KeyCode::A,
+            state: ButtonState::Pressed,
+        });
+        assert_eq!(key_downs(&mut ui, 0.0), 1);
+        assert_eq!(key_downs(&mut ui, 0.4), 0);
+        // Long frame makes a single repeat.
+        assert_eq!(key_downs(&mut ui, 2.0), 1);
+        assert_eq!(key_downs(&mut ui, 0.1), 1);
+
+        // Inactive window does not receive release of the key.
+        ui.process_os_event(&OsEvent::WindowFocused(false));
+        assert_eq!(key_downs(&mut ui, 1.0), 0);
+
+        ui.process_os_event(&OsEvent::KeyboardInput {
+            button: KeyCode::A,
+            state: ButtonState::Pressed,
+        });
+        assert_eq!(key_downs(&mut ui, 1.0), 2);
+        ui.set_keyboard_focus_node(Handle::NONE);
+        assert_eq!(key_downs(&mut ui, 1.0), 0);
+    }
 
-                    self.send_message(WidgetMessage::drag_started(
-                        self.picked_node,
-                        MessageDirection::FromWidget,
-                        self.drag_context.drag_node,
-                    ));
+    #[test]
+    fn cursor_visibility() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let hiding = BorderBuilder::new(WidgetBuilder::new().with_hides_cursor(true)).build(ctx);
+        let other = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        ui.update(screen_size, 0.0);
+        assert!(ui.is_cursor_visible());
 
-                    self.cursor_icon = CursorIcon::Crosshair;
-                }
+        ui.capture_mouse(other);
+        ui.update(screen_size, 0.0);
+        assert!(ui.is_cursor_visible());
+        ui.release_mouse_capture();
 
-                // Fire mouse leave for previously picked node
-                if self.picked_node != self.prev_picked_node && self.prev_picked_node.is_some() {
-                    let prev_picked_node = self.nodes.borrow_mut(self.prev_picked_node);
-                    if prev_picked_node.is_mouse_directly_over {
-                        prev_picked_node.is_mouse_directly_over = false;
-                        self.send_message(WidgetMessage::mouse_leave(
-                            self.prev_picked_node,
-                            MessageDirection::FromWidget,
-                        ));
-                    }
-                }
+        ui.capture_mouse(hiding);
+        ui.update(screen_size, 0.0);
+        assert!(!ui.is_cursor_visible());
 
-                if self.picked_node.is_some() {
-                    let picked_node = self.nodes.borrow_mut(self.picked_node);
-                    if !picked_node.is_mouse_directly_over {
-                        picked_node.is_mouse_directly_over = true;
-                        self.send_message(WidgetMessage::mouse_enter(
-                            self.picked_node,
-                            MessageDirection::FromWidget,
-                        ));
-                    }
+        // Removed node can't show the cursor by itself.
+        ui.send_message(WidgetMessage::remove(hiding, MessageDirection::ToWidget));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+        assert!(ui.is_cursor_visible());
+    }
 
-                    // Fire mouse move
-                    self.send_message(WidgetMessage::mouse_move(
-                        self.picked_node,
-                        MessageDirection::FromWidget,
-                        self.cursor_position,
-                        self.mouse_state,
-                    ));
+    #[test]
+    fn inspect_mode_releases_press() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let content = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        crate::button::ButtonBuilder::new(
+            WidgetBuilder::new().with_width(100.0).with_height(100.0),
+        )
+        .with_content(content)
+        .build(ctx);
+        ui.update(screen_size, 0.0);
+        ui.draw();
 
-                    if self.drag_context.is_dragging {
-                        self.send_message(WidgetMessage::drag_over(
-                            self.picked_node,
-                            MessageDirection::FromWidget,
-                            self.drag_context.drag_node,
-                        ));
+        let mut capture_lost = false;
+        let mut process = |ui: &mut UserInterface<(), StubNode>, event: Option<OsEvent>| {
+            if let Some(event) = event {
+                ui.process_os_event(&event);
+            }
+            while let Some(message) = ui.poll_message() {
+                match message.data() {
+                    UiMessageData::Button(crate::message::ButtonMessage::Click) => {
+                        panic!("button must not be clicked")
                     }
-
-                    event_processed = true;
+                    UiMessageData::Widget(WidgetMessage::MouseCaptureLost) => {
+                        capture_lost |= message.destination() == content;
+                    }
+                    _ => (),
                 }
             }
-            OsEvent::MouseWheel(_, y) => {
-                if self.picked_node.is_some() {
-                    self.send_message(WidgetMessage::mouse_wheel(
-                        self.picked_node,
-                        MessageDirection::FromWidget,
-                        self.cursor_position,
-                        *y,
-                    ));
+        };
+        process(
+            &mut ui,
+            Some(OsEvent::CursorMoved {
+                position: Vector2::new(50.0, 50.0),
+            }),
+        );
+        process(
+            &mut ui,
+            Some(OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ButtonState::Pressed,
+            }),
+        );
+        // Button captures mouse by its content.
+        assert_eq!(ui.captured_node, content);
+
+        ui.set_inspect_mode(true);
+        process(
+            &mut ui,
+            Some(OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ButtonState::Released,
+            }),
+        );
+        ui.set_inspect_mode(false);
+        process(&mut ui, None);
 
-                    event_processed = true;
-                }
-            }
-            OsEvent::KeyboardInput { button, state } => {
-                if self.keyboard_focus_node.is_some() {
-                    self.send_message(match state {
-                        ButtonState::Pressed => WidgetMessage::key_down(
-                            self.keyboard_focus_node,
-                            MessageDirection::FromWidget,
-                            *button,
-                        ),
-                        ButtonState::Released => WidgetMessage::key_up(
-                            self.keyboard_focus_node,
-                            MessageDirection::FromWidget,
-                            *button,
-                        ),
-                    });
+        assert!(capture_lost);
+        assert!(ui.captured_node.is_none());
+    }
 
-                    event_processed = true;
+    #[test]
+    fn layout_after_messages() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let border = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left),
+        )
+        .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        assert_eq!(ui.node(border).actual_size().x, 100.0);
+
+        ui.send_message(WidgetMessage::width(
+            border,
+            MessageDirection::ToWidget,
+            50.0,
+        ));
+        while ui.poll_message().is_some() {}
+        // No update is needed to get settled layout.
+        assert_eq!(ui.node(border).actual_size().x, 50.0);
+    }
+
+    #[test]
+    fn value_change_coalescing() {
+        let mut ui = UserInterface::<(), StubNode>::new(Vector2::new(1000.0, 1000.0));
+        let ctx = &mut ui.build_ctx();
+        let plain = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let coalescing =
+            BorderBuilder::new(WidgetBuilder::new().with_event_coalescing(true)).build(ctx);
+
+        let notifications = |ui: &mut UserInterface<(), StubNode>, node| {
+            for &value in [1.0, 1.0, 2.0].iter() {
+                ui.send_message(ScrollBarMessage::value(
+                    node,
+                    MessageDirection::FromWidget,
+                    value,
+                ));
+            }
+            let mut count = 0;
+            while let Some(message) = ui.poll_message() {
+                if let UiMessageData::ScrollBar(ScrollBarMessage::Value(_)) = message.data() {
+                    count += 1;
                 }
             }
-            OsEvent::Character(unicode) => {
-                if self.keyboard_focus_node.is_some() {
-                    self.send_message(WidgetMessage::text(
-                        self.keyboard_focus_node,
-                        MessageDirection::FromWidget,
-                        *unicode,
-                    ));
+            count
+        };
 
-                    event_processed = true;
+        assert_eq!(notifications(&mut ui, plain), 3);
+        assert_eq!(notifications(&mut ui, coalescing), 2);
+    }
+
+    #[test]
+    fn structure_notifications() {
+        let mut ui = UserInterface::<(), StubNode>::new(Vector2::new(1000.0, 1000.0));
+
+        let build = |ui: &mut UserInterface<(), StubNode>| {
+            let ctx = &mut ui.build_ctx();
+            let child = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+            let parent = BorderBuilder::new(WidgetBuilder::new().with_child(child)).build(ctx);
+            (parent, child)
+        };
+        let notifications = |ui: &mut UserInterface<(), StubNode>| {
+            let mut result = Vec::new();
+            while let Some(message) = ui.poll_message() {
+                match message.data() {
+                    UiMessageData::Widget(WidgetMessage::NodeAdded { parent }) => {
+                        result.push((true, message.destination(), *parent))
+                    }
+                    UiMessageData::Widget(WidgetMessage::NodeRemoved { parent }) => {
+                        result.push((false, message.destination(), *parent))
+                    }
+                    _ => (),
                 }
             }
-            &OsEvent::KeyboardModifiers(modifiers) => {
-                // TODO: Is message needed for focused node?
-                self.keyboard_modifiers = modifiers;
+            result
+        };
+
+        let (parent, _) = build(&mut ui);
+        ui.send_message(WidgetMessage::remove(parent, MessageDirection::ToWidget));
+        assert!(notifications(&mut ui).is_empty());
+
+        ui.set_structure_notifications(true);
+        let (parent, child) = build(&mut ui);
+        let root = ui.root();
+        assert!(!notifications(&mut ui).is_empty());
+        ui.send_message(WidgetMessage::link(child, MessageDirection::ToWidget, root));
+        assert_eq!(
+            notifications(&mut ui),
+            [(false, child, parent), (true, child, root)]
+        );
+        ui.send_message(WidgetMessage::link(
+            child,
+            MessageDirection::ToWidget,
+            parent,
+        ));
+        notifications(&mut ui);
+        ui.send_message(WidgetMessage::remove(parent, MessageDirection::ToWidget));
+        assert_eq!(
+            notifications(&mut ui),
+            [(false, child, parent), (false, parent, root)]
+        );
+    }
+
+    #[test]
+    fn render_layer() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let square = |ctx: &mut BuildContext<(), StubNode>, render_layer| {
+            BorderBuilder::new(
+                WidgetBuilder::new()
+                    .with_width(100.0)
+                    .with_height(100.0)
+                    .with_render_layer(render_layer)
+                    .with_horizontal_alignment(HorizontalAlignment::Left)
+                    .with_vertical_alignment(VerticalAlignment::Top),
+            )
+            .build(ctx)
+        };
+        // Layered square and its child are earlier in tree, but must be on top of the last one.
+        let reticle = square(ctx, 1);
+        let reticle_child = square(ctx, 0);
+        ctx.link(reticle_child, reticle);
+        let panel = square(ctx, 0);
+        // Layered node is drawn in a separate pass, but it must be moved with its parent.
+        let shift = Matrix3::new_translation(&Vector2::new(500.0, 0.0));
+        let moved = square(ctx, 0);
+        ctx[moved].set_render_transform(shift);
+        let moved_child = square(ctx, 2);
+        ctx.link(moved_child, moved);
+        ui.update(screen_size, 0.0);
+        ui.draw();
+
+        let first_command =
+            |ui: &UserInterface<(), StubNode>, handle| ui.node(handle).command_indices.borrow()[0];
+        assert!(first_command(&ui, reticle) > first_command(&ui, panel));
+        assert!(first_command(&ui, reticle_child) > first_command(&ui, reticle));
+        assert_eq!(ui.hit_test(Vector2::new(50.0, 50.0)), reticle_child);
+        let commands = ui.get_drawing_context().get_commands();
+        assert_eq!(commands[first_command(&ui, moved_child)].transform, shift);
+
+        ui.send_message(WidgetMessage::render_layer(
+            reticle,
+            MessageDirection::ToWidget,
+            0,
+        ));
+        while ui.poll_message().is_some() {}
+        // Layers are collected again before the next draw, so picking is correct right away.
+        ui.update(screen_size, 0.0);
+        assert_eq!(ui.hit_test(Vector2::new(50.0, 50.0)), panel);
+    }
+
+    #[test]
+    fn reject_cycles() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let child = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let parent = BorderBuilder::new(WidgetBuilder::new().with_child(child)).build(ctx);
+
+        for &(node, new_parent) in [(parent, child), (parent, parent)].iter() {
+            ui.send_message(WidgetMessage::link(
+                node,
+                MessageDirection::ToWidget,
+                new_parent,
+            ));
+        }
+        while ui.poll_message().is_some() {}
+
+        assert_eq!(ui.node(child).parent(), parent);
+        assert_eq!(ui.node(parent).parent(), ui.root());
+        ui.update(screen_size, 0.0);
+    }
+
+    #[test]
+    fn layout_depth_guard() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        ui.set_max_layout_depth(3);
+        let ctx = &mut ui.build_ctx();
+        let deepest = BorderBuilder::new(WidgetBuilder::new().with_width(10.0)).build(ctx);
+        let mut parent = deepest;
+        for _ in 0..3 {
+            parent = BorderBuilder::new(WidgetBuilder::new().with_child(parent)).build(ctx);
+        }
+        ui.update(screen_size, 0.0);
+
+        // Root canvas is the first level, so only two outer borders fit into the limit.
+        assert_eq!(ui.node(deepest).desired_size(), Vector2::default());
+        assert_ne!(ui.node(parent).desired_size(), Vector2::default());
+    }
+
+    #[test]
+    fn refresh_hover() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let border = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(100.0)
+                .with_visibility(false),
+        )
+        .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        ui.process_os_event(&OsEvent::CursorMoved {
+            position: Vector2::new(50.0, 50.0),
+        });
+        while ui.poll_message().is_some() {}
+        assert!(ui.picked_node.is_none());
+
+        // Border appears under stationary cursor.
+        ui.send_message(WidgetMessage::visibility(
+            border,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        // Drawing alone changes nothing, border is picked by next update.
+        assert!(ui.picked_node.is_none());
+        ui.update(screen_size, 0.0);
+
+        assert_eq!(ui.picked_node, border);
+        let mut entered = false;
+        while let Some(message) = ui.poll_message() {
+            if let UiMessageData::Widget(WidgetMessage::MouseEnter) = message.data() {
+                entered |= message.destination() == border;
             }
         }
+        assert!(entered);
+    }
 
-        self.prev_picked_node = self.picked_node;
+    #[test]
+    fn enabling_input_refreshes_hover() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let border = BorderBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+            .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        while ui.poll_message().is_some() {}
+
+        let has_mouse_move = |ui: &mut UserInterface<(), StubNode>| {
+            let mut moved = false;
+            while let Some(message) = ui.poll_message() {
+                moved |= matches!(
+                    message.data(),
+                    UiMessageData::Widget(WidgetMessage::MouseMove { .. })
+                );
+            }
+            moved
+        };
 
-        for i in 0..self.nodes.get_capacity() {
-            let handle = self.nodes.handle_from_index(i);
+        // Cursor was never moved, so nothing is picked at the origin.
+        ui.set_input_enabled(false);
+        ui.set_input_enabled(true);
+        assert!(ui.picked_node.is_none());
+        assert!(!has_mouse_move(&mut ui));
+
+        ui.process_os_event(&OsEvent::CursorMoved {
+            position: Vector2::new(50.0, 50.0),
+        });
+        while ui.poll_message().is_some() {}
+        ui.set_input_enabled(false);
+        ui.set_input_enabled(true);
+        assert_eq!(ui.picked_node, border);
+        assert!(!has_mouse_move(&mut ui));
+    }
 
-            if self.nodes.is_valid_handle(handle) {
-                let (ticket, mut node) = self.nodes.take_reserve(handle);
+    #[test]
+    fn refresh_hover_focus() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        ui.set_focus_follows_mouse(true);
+        let border = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(100.0)
+                .with_tab_stop(true)
+                .with_visibility(false),
+        )
+        .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        ui.process_os_event(&OsEvent::CursorMoved {
+            position: Vector2::new(50.0, 50.0),
+        });
+        while ui.poll_message().is_some() {}
+        assert!(ui.keyboard_focus_node.is_none());
+
+        // Focusable border appears under stationary cursor and takes focus.
+        ui.send_message(WidgetMessage::visibility(
+            border,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        ui.update(screen_size, 0.0);
 
-                node.handle_os_event(handle, self, event);
+        assert_eq!(ui.keyboard_focus_node, border);
+    }
 
-                self.nodes.put_back(ticket, node);
+    #[test]
+    fn pick_after_draw_hook() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let a = BorderBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+            .build(ctx);
+        let b = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(100.0)
+                .with_desired_position(Vector2::new(200.0, 0.0)),
+        )
+        .build(ctx);
+        // Moves every command two indices further, a border has two commands.
+        ui.add_draw_hook(Box::new(|ctx: &mut DrawingContext| {
+            for _ in 0..2 {
+                ctx.push_custom_command(std::sync::Arc::new(()), 0);
+                let custom = ctx.commands_mut().pop().unwrap();
+                ctx.commands_mut().insert(0, custom);
             }
-        }
+        }));
+        ui.update(screen_size, 0.0);
+        ui.draw();
 
-        event_processed
+        assert_eq!(ui.hit_test(Vector2::new(50.0, 50.0)), a);
+        assert_eq!(ui.hit_test(Vector2::new(250.0, 50.0)), b);
     }
 
-    pub fn nodes(&self) -> &Pool<UINode<M, C>> {
-        &self.nodes
+    #[test]
+    fn dirty_region() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let border = BorderBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+            .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        ui.draw();
+        assert_eq!(ui.dirty_region(), None);
+
+        // Scale around center of the border.
+        ui.nodes
+            .borrow_mut(border)
+            .set_render_transform(Matrix3::new_scaling(2.0));
+        ui.draw();
+        assert_eq!(ui.dirty_region(), Some(Rect::new(0.0, 0.0, 150.0, 150.0)));
+        ui.draw();
+        assert_eq!(ui.dirty_region(), None);
+
+        ui.nodes.borrow_mut(border).set_opacity(0.5);
+        ui.draw();
+        assert_eq!(ui.dirty_region(), Some(Rect::new(0.0, 0.0, 150.0, 150.0)));
     }
 
-    pub fn root(&self) -> Handle<UINode<M, C>> {
-        self.root_canvas
+    #[test]
+    fn node_to_screen_with_render_transform() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let child = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(10.0)
+                .with_height(10.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .build(ctx);
+        let parent = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(100.0)
+                .with_child(child),
+        )
+        .build(ctx);
+        ui.update(screen_size, 0.0);
+        // Parent is scaled around its center at (50, 50).
+        ui.nodes
+            .borrow_mut(parent)
+            .set_render_transform(Matrix3::new_scaling(2.0));
+
+        // Child is at (1, 1) because of stroke of the parent.
+        let local = Vector2::new(10.0, 10.0);
+        let screen = Vector2::new(-28.0, -28.0);
+        assert_eq!(ui.node_to_screen(child, local), Some(screen));
+        assert_eq!(ui.screen_to_node(child, screen), Some(local));
+
+        ui.nodes
+            .borrow_mut(parent)
+            .set_render_transform(Matrix3::new_scaling(0.0));
+        assert_eq!(ui.screen_to_node(child, screen), None);
     }
 
-    pub fn add_node(&mut self, mut node: UINode<M, C>) -> Handle<UINode<M, C>> {
-        let children = node.children().to_vec();
-        node.clear_children();
-        let node_handle = self.nodes.spawn(node);
-        if self.root_canvas.is_some() {
-            self.link_nodes_internal(node_handle, self.root_canvas, false);
-        }
-        for child in children {
-            self.link_nodes_internal(child, node_handle, false)
-        }
-        let node = self.nodes[node_handle].deref_mut();
-        node.handle = node_handle;
-        node_handle
-    }
+    #[test]
+    fn pick_with_render_transform() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let square = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(100.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .build(ctx);
+        ctx[square].set_render_transform(Matrix3::new_translation(&Vector2::new(500.0, 0.0)));
+        ui.update(screen_size, 0.0);
+        ui.draw();
 
-    pub fn push_picking_restriction(&mut self, restriction: RestrictionEntry<M, C>) {
-        if let Some(top) = self.top_picking_restriction() {
-            assert_ne!(top.handle, restriction.handle);
-        }
-        self.picking_stack.push(restriction);
+        assert_eq!(ui.hit_test(Vector2::new(550.0, 50.0)), square);
+        assert_ne!(ui.hit_test(Vector2::new(50.0, 50.0)), square);
     }
 
-    pub fn remove_picking_restriction(&mut self, node: Handle<UINode<M, C>>) {
-        if let Some(pos) = self.picking_stack.iter().position(|h| h.handle == node) {
-            self.picking_stack.remove(pos);
-        }
-    }
+    #[test]
+    fn pick_transformed_child_clipped_by_parent() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let child = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(100.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .build(ctx);
+        ctx[child].set_render_transform(Matrix3::new_translation(&Vector2::new(350.0, 0.0)));
+        let parent = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(400.0)
+                .with_height(100.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_child(child),
+        )
+        .build(ctx);
+        ui.update(screen_size, 0.0);
+        ui.draw();
 
-    pub fn picking_restriction_stack(&self) -> &[RestrictionEntry<M, C>] {
-        &self.picking_stack
+        assert_eq!(ui.hit_test(Vector2::new(375.0, 50.0)), child);
+        // Part of the child that is moved out of the parent is clipped.
+        assert_ne!(ui.hit_test(Vector2::new(425.0, 50.0)), child);
+        assert_ne!(ui.hit_test(Vector2::new(425.0, 50.0)), parent);
     }
 
-    /// Removes all picking restrictions.
-    pub fn drop_picking_restrictions(&mut self) {
-        self.picking_stack.clear();
+    #[test]
+    fn draw_detached_subtree() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let root = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+
+        // Drawing does not change the tree, linked root is rejected.
+        assert!(ui.draw_subtree(root, Vector2::new(100.0, 100.0)).is_none());
+        assert_eq!(ui.node(root).parent(), ui.root_canvas);
+
+        ui.detach_subtree(root);
+        assert!(ui.node(root).parent().is_none());
+        assert!(ui.draw_subtree(root, Vector2::new(100.0, 100.0)).is_some());
+        assert!(ui.draw_subtree(root, Vector2::new(100.0, 100.0)).is_none());
     }
 
-    pub fn top_picking_restriction(&self) -> Option<RestrictionEntry<M, C>> {
-        self.picking_stack.last().cloned()
+    #[test]
+    fn measure_cache_per_pass() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        // Children of star-sized grid are measured with size of the grid, then with size of
+        // their cells.
+        let child = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let grid = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_width(200.0)
+                .with_height(100.0)
+                .with_child(child),
+        )
+        .add_column(Column::stretch())
+        .add_column(Column::stretch())
+        .add_row(Row::stretch())
+        .build(ctx);
+        ui.update(screen_size, 0.0);
+
+        ui.reset_measure_cache_stats();
+        ui.node(grid).invalidate_layout();
+        ui.update(screen_size, 0.0);
+        // Root canvas and the grid are measured, both passes of the child are cached.
+        assert_eq!(ui.measure_cache_stats(), (2, 2));
+        assert_eq!(ui.node(child).actual_size(), Vector2::new(100.0, 100.0));
     }
 
-    /// Use WidgetMessage::remove(...) to remove node.
-    fn remove_node(&mut self, node: Handle<UINode<M, C>>) {
-        self.unlink_node_internal(node);
+    #[test]
+    fn measure_override_size() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let child = BorderBuilder::new(WidgetBuilder::new().with_width(200.0)).build(ctx);
+        let parent = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_measure_override_size(Vector2::new(50.0, 40.0))
+                .with_child(child),
+        )
+        .build(ctx);
+        ui.update(screen_size, 0.0);
 
-        let mut removed_nodes = Vec::new();
-        let mut stack = vec![node];
-        while let Some(handle) = stack.pop() {
-            removed_nodes.push(handle);
+        assert_eq!(ui.node(parent).desired_size(), Vector2::new(50.0, 40.0));
+        // Child is still measured, but within the forced size.
+        assert_eq!(ui.node(child).desired_size().x, 50.0);
 
-            if self.prev_picked_node == handle {
-                self.prev_picked_node = Handle::NONE;
-            }
-            if self.picked_node == handle {
-                self.picked_node = Handle::NONE;
+        ui.nodes.borrow_mut(parent).set_measure_override_size(None);
+        ui.update(screen_size, 0.0);
+        assert_eq!(ui.node(child).desired_size().x, 200.0);
+    }
+
+    #[test]
+    fn batch_layout() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let build = |batch: bool| {
+            let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+            ui.update(screen_size, 0.0);
+            if batch {
+                ui.begin_batch();
+                // Nested batch must not finish the outer one.
+                ui.begin_batch();
             }
-            if self.captured_node == handle {
-                self.captured_node = Handle::NONE;
+            let panel = StackPanelBuilder::new(WidgetBuilder::new().with_width(200.0))
+                .build(&mut ui.build_ctx());
+            let mut nodes = Vec::new();
+            for _ in 0..20 {
+                let child = BorderBuilder::new(WidgetBuilder::new().with_height(10.0))
+                    .build(&mut ui.build_ctx());
+                ui.send_message(WidgetMessage::link(
+                    child,
+                    MessageDirection::ToWidget,
+                    panel,
+                ));
+                nodes.push(child);
             }
-            if self.keyboard_focus_node == handle {
-                self.keyboard_focus_node = Handle::NONE;
+            let centered = BorderBuilder::new(WidgetBuilder::new().with_width(100.0))
+                .build(&mut ui.build_ctx());
+            ui.send_message(WidgetMessage::center(centered, MessageDirection::ToWidget));
+            nodes.push(centered);
+            while ui.poll_message().is_some() {}
+            if batch {
+                ui.end_batch();
+                assert!(ui.is_in_batch());
+                ui.end_batch();
+                assert!(!ui.is_in_batch());
+                while ui.poll_message().is_some() {}
             }
-            self.remove_picking_restriction(handle);
+            ui.update(screen_size, 0.0);
+            nodes
+                .iter()
+                .map(|n| ui.node(*n).screen_position)
+                .collect::<Vec<_>>()
+        };
 
-            for child in self.nodes().borrow(handle).children().iter() {
-                stack.push(*child);
-            }
-            self.nodes.free(handle);
-        }
+        let batched = build(true);
+        assert_eq!(batched, build(false));
+        assert_eq!(batched.last().unwrap().x, 450.0);
+    }
 
-        for node in self.nodes.iter_mut() {
-            for removed_node in removed_nodes.iter() {
-                node.remove_ref(*removed_node);
-            }
-        }
+    #[test]
+    fn commands_wait_for_update() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let removed = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        let centered =
+            BorderBuilder::new(WidgetBuilder::new().with_width(100.0)).build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        ui.post(UiCommand::Remove(removed));
+
+        // Neither layout that is needed by a message nor end of a batch applies commands.
+        ui.send_message(WidgetMessage::center(centered, MessageDirection::ToWidget));
+        while ui.poll_message().is_some() {}
+        ui.batch(|ui| {
+            ui.send_message(WidgetMessage::center(centered, MessageDirection::ToWidget));
+        });
+        while ui.poll_message().is_some() {}
+        assert!(ui.nodes.is_valid_handle(removed));
+
+        ui.update(screen_size, 0.0);
+        assert!(!ui.nodes.is_valid_handle(removed));
     }
 
-    /// Links specified child with specified parent.
-    #[inline]
-    fn link_nodes_internal(
-        &mut self,
-        child_handle: Handle<UINode<M, C>>,
-        parent_handle: Handle<UINode<M, C>>,
-        in_front: bool,
-    ) {
-        assert_ne!(child_handle, parent_handle);
-        self.unlink_node_internal(child_handle);
-        self.nodes[child_handle].set_parent(parent_handle);
-        self.nodes[parent_handle].add_child(child_handle, in_front);
+    /// Example of a custom node, it is drawn as a colored triangle.
+    #[derive(Clone)]
+    struct Triangle {
+        widget: Widget<(), Triangle>,
+        color: Color,
     }
 
-    /// Unlinks specified node from its parent, so node will become root.
-    #[inline]
-    fn unlink_node_internal(&mut self, node_handle: Handle<UINode<M, C>>) {
-        // Replace parent handle of child
-        let node = self.nodes.borrow_mut(node_handle);
-        let parent_handle = node.parent();
-        if parent_handle.is_some() {
-            node.set_parent(Handle::NONE);
+    impl Deref for Triangle {
+        type Target = Widget<(), Triangle>;
 
-            // Remove child from parent's children list
-            self.nodes[parent_handle].remove_child(node_handle);
+        fn deref(&self) -> &Self::Target {
+            &self.widget
         }
     }
 
-    /// Unlinks specified node from its parent and attaches back to root canvas.
-    ///
-    /// Use [WidgetMessage::remove](enum.WidgetMessage.html#method.remove) to unlink
-    /// a node at runtime!
-    #[inline]
-    fn unlink_node(&mut self, node_handle: Handle<UINode<M, C>>) {
-        self.unlink_node_internal(node_handle);
-        self.link_nodes_internal(node_handle, self.root_canvas, false);
+    impl DerefMut for Triangle {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.widget
+        }
     }
 
-    #[inline]
-    pub fn node(&self, node_handle: Handle<UINode<M, C>>) -> &UINode<M, C> {
-        self.nodes().borrow(node_handle)
-    }
+    impl Control<(), Triangle> for Triangle {
+        fn draw(&self, drawing_context: &mut DrawingContext) {
+            let bounds = self.screen_bounds();
+            drawing_context.push_triangle_multicolor([
+                (bounds.left_bottom_corner(), self.color),
+                (
+                    Vector2::new(bounds.x() + bounds.w() * 0.5, bounds.y()),
+                    self.color,
+                ),
+                (bounds.right_bottom_corner(), self.color),
+            ]);
+            drawing_context.commit(
+                self.clip_bounds(),
+                Brush::Solid(Color::WHITE),
+                CommandTexture::None,
+                None,
+            );
+        }
 
-    pub fn copy_node(&mut self, node: Handle<UINode<M, C>>) -> Handle<UINode<M, C>> {
-        let mut map = NodeHandleMapping::default();
+        fn handle_routed_message(
+            &mut self,
+            ui: &mut UserInterface<(), Triangle>,
+            message: &mut UiMessage<(), Triangle>,
+        ) {
+            self.widget.handle_routed_message(ui, message);
+        }
+    }
 
-        let root = self.copy_node_recursive(node, &mut map);
+    #[test]
+    fn draw_user_node() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), Triangle>::new(screen_size);
+        let triangle = Triangle {
+            widget: WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(50.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .build(),
+            color: Color::RED,
+        };
+        ui.build_ctx().add_node(UINode::User(triangle));
+        ui.update(screen_size, 0.0);
 
-        for &node_handle in map.hash_map.values() {
-            self.nodes[node_handle].resolve(&map);
+        let drawing_context = ui.draw();
+        let command = drawing_context
+            .commands()
+            .iter()
+            .find(|c| c.triangles.len() == 1)
+            .expect("user node must be drawn");
+        let vertices = drawing_context.get_vertices();
+        let triangle = &drawing_context.get_triangles()[command.triangles.start];
+        for &index in triangle.0.iter() {
+            assert_eq!(vertices[index as usize].color, Color::RED);
         }
+        assert_eq!(command.bounds.w(), 100.0);
+        assert_eq!(command.bounds.h(), 50.0);
+    }
 
-        root
+    #[test]
+    fn tint_inheritance() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let half = Color::from_rgba(128, 128, 128, 255);
+        let white = || Brush::Solid(Color::WHITE);
+        let child = BorderBuilder::new(WidgetBuilder::new().with_background(white()))
+            .build(&mut ui.build_ctx());
+        let parent = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_background(white())
+                .with_tint(half)
+                .with_child(child),
+        )
+        .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+
+        let brush_of = |ui: &mut UserInterface<(), StubNode>, node| {
+            ui.draw();
+            let index = ui.node(node).command_indices.borrow()[0];
+            ui.draw().commands()[index].brush.clone()
+        };
+
+        assert_eq!(brush_of(&mut ui, parent), Brush::Solid(half));
+        assert_eq!(brush_of(&mut ui, child), white());
+
+        ui.set_tint_inheritance(true);
+        assert_eq!(brush_of(&mut ui, child), Brush::Solid(half));
     }
 
-    fn copy_node_recursive(
-        &mut self,
-        node_handle: Handle<UINode<M, C>>,
-        map: &mut NodeHandleMapping<M, C>,
-    ) -> Handle<UINode<M, C>> {
-        let node = self.nodes.borrow(node_handle);
-        let mut cloned = node.clone();
+    /// Stand-in for a text of some font size, actual fonts cannot be loaded in tests.
+    #[derive(Clone)]
+    struct Label {
+        widget: Widget<(), Label>,
+        baseline: f32,
+    }
 
-        let mut cloned_children = Vec::new();
-        for child in node.children().to_vec() {
-            cloned_children.push(self.copy_node_recursive(child, map));
+    impl Deref for Label {
+        type Target = Widget<(), Label>;
+
+        fn deref(&self) -> &Self::Target {
+            &self.widget
         }
+    }
 
-        cloned.set_children(cloned_children);
-        let copy_handle = self.add_node(cloned);
-        map.add_mapping(node_handle, copy_handle);
-        copy_handle
+    impl DerefMut for Label {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.widget
+        }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::{
-        border::BorderBuilder,
-        core::algebra::Vector2,
-        message::{MessageDirection, WidgetMessage},
-        node::StubNode,
-        widget::WidgetBuilder,
-        UserInterface,
-    };
+    impl Control<(), Label> for Label {
+        fn baseline_offset(&self, _ui: &UserInterface<(), Label>) -> Option<f32> {
+            Some(self.baseline)
+        }
+
+        fn handle_routed_message(
+            &mut self,
+            ui: &mut UserInterface<(), Label>,
+            message: &mut UiMessage<(), Label>,
+        ) {
+            self.widget.handle_routed_message(ui, message);
+        }
+    }
 
     #[test]
-    fn center() {
+    fn baseline_alignment() {
         let screen_size = Vector2::new(1000.0, 1000.0);
-        let widget_size = Vector2::new(100.0, 100.0);
-        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
-        let widget = BorderBuilder::new(
+        let mut ui = UserInterface::<(), Label>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let mut label = |height: f32, baseline: f32| {
+            ctx.add_node(UINode::User(Label {
+                widget: WidgetBuilder::new()
+                    .with_width(50.0)
+                    .with_height(height)
+                    .with_vertical_alignment(VerticalAlignment::Baseline)
+                    .build(),
+                baseline,
+            }))
+        };
+        let small = label(20.0, 16.0);
+        let large = label(40.0, 32.0);
+        let icon = BorderBuilder::new(
             WidgetBuilder::new()
-                .with_width(widget_size.x)
-                .with_height(widget_size.y),
+                .with_width(10.0)
+                .with_height(10.0)
+                .with_vertical_alignment(VerticalAlignment::Baseline),
         )
-        .build(&mut ui.build_ctx());
-        ui.update(screen_size, 0.0); // Make sure layout was calculated.
-        ui.send_message(WidgetMessage::center(widget, MessageDirection::ToWidget));
-        while let Some(_) = ui.poll_message() {}
+        .build(ctx);
+        let panel = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_child(small)
+                .with_child(icon)
+                .with_child(large),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
         ui.update(screen_size, 0.0);
-        let expected_position = (screen_size - widget_size).scale(0.5);
-        let actual_position = ui.node(widget).actual_local_position();
-        assert_eq!(actual_position, expected_position);
+
+        let top = |ui: &UserInterface<(), Label>, node| ui.node(node).screen_position().y;
+        assert_eq!(top(&ui, small) + 16.0, 32.0);
+        assert_eq!(top(&ui, large) + 32.0, 32.0);
+        // Nodes without text stand on the baseline.
+        assert_eq!(top(&ui, icon) + 10.0, 32.0);
+        // Large label has longest descent (8 units).
+        assert_eq!(ui.node(panel).actual_size().y, 40.0);
     }
 }
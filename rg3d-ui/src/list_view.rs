@@ -1,18 +1,20 @@
 use crate::border::BorderBuilder;
 use crate::draw::Draw;
 use crate::{
+    accessibility::AccessibleRole,
     brush::Brush,
     core::{color::Color, pool::Handle},
     draw::{CommandTexture, DrawingContext},
+    items_control::ItemsControl,
     message::{
-        DecoratorMessage, ListViewMessage, MessageData, MessageDirection, UiMessage, UiMessageData,
-        WidgetMessage,
+        DecoratorMessage, KeyCode, ListViewMessage, MessageData, MessageDirection,
+        ScrollViewerMessage, UiMessage, UiMessageData, WidgetMessage,
     },
     scroll_viewer::ScrollViewerBuilder,
     stack_panel::StackPanelBuilder,
-    widget::{Widget, WidgetBuilder},
-    BuildContext, Control, NodeHandleMapping, Thickness, UINode, UserInterface, BRUSH_DARK,
-    BRUSH_LIGHT,
+    widget::{NavMode, Widget, WidgetBuilder},
+    BuildContext, Control, NodeHandleMapping, Orientation, Thickness, UINode, UserInterface,
+    BRUSH_DARK, BRUSH_LIGHT,
 };
 use std::ops::{Deref, DerefMut};
 
@@ -20,9 +22,11 @@ use std::ops::{Deref, DerefMut};
 pub struct ListView<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     selected_index: Option<usize>,
-    item_containers: Vec<Handle<UINode<M, C>>>,
-    panel: Handle<UINode<M, C>>,
-    items: Vec<Handle<UINode<M, C>>>,
+    items: ItemsControl<M, C>,
+    scroll_viewer: Handle<UINode<M, C>>,
+    /// Whether the list or any of its descendants is focused, selection of unfocused list is
+    /// drawn with inactive selection color.
+    has_focus: bool,
 }
 
 crate::define_widget_deref!(ListView<M, C>);
@@ -32,9 +36,9 @@ impl<M: MessageData, C: Control<M, C>> ListView<M, C> {
         Self {
             widget,
             selected_index: None,
-            item_containers: items,
-            panel: Default::default(),
-            items: Default::default(),
+            items: ItemsControl::new(Default::default(), items),
+            scroll_viewer: Default::default(),
+            has_focus: false,
         }
     }
 
@@ -43,12 +47,95 @@ impl<M: MessageData, C: Control<M, C>> ListView<M, C> {
     }
 
     pub fn item_containers(&self) -> &[Handle<UINode<M, C>>] {
-        &self.item_containers
+        self.items.item_containers()
     }
 
     pub fn items(&self) -> &[Handle<UINode<M, C>>] {
+        self.items.items()
+    }
+
+    pub fn items_panel(&self) -> Handle<UINode<M, C>> {
+        self.items.items_panel()
+    }
+
+    pub fn items_control(&self) -> &ItemsControl<M, C> {
         &self.items
     }
+
+    pub fn placeholder(&self) -> Handle<UINode<M, C>> {
+        self.items.placeholder()
+    }
+
+    /// Moves selection to adjacent item by an arrow key that matches orientation of items
+    /// panel, it is done while the list is focused. Returns true if the key was used.
+    fn move_selection_by_arrow(&self, ui: &UserInterface<M, C>, key: KeyCode) -> bool {
+        let mode = self.keyboard_navigation();
+        let forward = match (ui.navigation_orientation(self.items.items_panel()), key) {
+            _ if mode == NavMode::None => return false,
+            (Orientation::Vertical, KeyCode::Up) | (Orientation::Horizontal, KeyCode::Left) => {
+                false
+            }
+            (Orientation::Vertical, KeyCode::Down) | (Orientation::Horizontal, KeyCode::Right) => {
+                true
+            }
+            _ => return false,
+        };
+
+        let count = self.items.len();
+        if count > 0 {
+            let cycle = mode == NavMode::Cycle;
+            let index = match self.selected_index {
+                None if forward => 0,
+                None => count - 1,
+                Some(index) if forward && index + 1 < count => index + 1,
+                Some(index) if !forward && index > 0 => index - 1,
+                Some(_) if cycle && forward => 0,
+                Some(_) if cycle => count - 1,
+                Some(index) => index,
+            };
+            if self.selected_index != Some(index) {
+                ui.send_message(ListViewMessage::selection(
+                    self.handle(),
+                    MessageDirection::ToWidget,
+                    Some(index),
+                ));
+                ui.send_message(ScrollViewerMessage::bring_into_view(
+                    self.scroll_viewer,
+                    MessageDirection::ToWidget,
+                    self.items.item_containers()[index],
+                ));
+            }
+        }
+        true
+    }
+
+    fn sync_selection(&self, ui: &UserInterface<M, C>) {
+        for (i, &container) in self.items.item_containers().iter().enumerate() {
+            let select = self.selected_index.map_or(false, |k| k == i);
+            if let UINode::ListViewItem(container) = ui.node(container) {
+                let mut stack = container.children().to_vec();
+                while let Some(handle) = stack.pop() {
+                    let node = ui.node(handle);
+                    match node {
+                        UINode::ListView(_) => {}
+                        UINode::Decorator(_) => {
+                            ui.send_message(DecoratorMessage::select(
+                                handle,
+                                MessageDirection::ToWidget,
+                                select,
+                            ));
+                            ui.send_message(DecoratorMessage::selection_active(
+                                handle,
+                                MessageDirection::ToWidget,
+                                self.has_focus,
+                            ));
+                        }
+                        _ => stack.extend_from_slice(node.children()),
+                    }
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -66,6 +153,10 @@ impl<M: MessageData, C: Control<M, C>> ListViewItem<M, C> {
 }
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for ListViewItem<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::ListItem
+    }
+
     fn draw(&self, drawing_context: &mut DrawingContext) {
         // Emit transparent geometry so item container can be picked by hit test.
         drawing_context.push_rect_filled(&self.widget.screen_bounds(), None);
@@ -103,10 +194,13 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ListViewItem<M, C> {
 }
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for ListView<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::List
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
-        node_map.resolve(&mut self.panel);
-        node_map.resolve_slice(&mut self.items);
-        node_map.resolve_slice(&mut self.item_containers);
+        self.items.resolve(node_map);
+        node_map.resolve(&mut self.scroll_viewer);
     }
 
     fn handle_routed_message(
@@ -116,6 +210,27 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ListView<M, C> {
     ) {
         self.widget.handle_routed_message(ui, message);
 
+        // Focus messages of descendants bubble up here.
+        match &message.data() {
+            UiMessageData::Widget(WidgetMessage::GotFocus) if !self.has_focus => {
+                self.has_focus = true;
+                self.sync_selection(ui);
+            }
+            UiMessageData::Widget(WidgetMessage::LostFocus) if self.has_focus => {
+                self.has_focus = false;
+                self.sync_selection(ui);
+            }
+            // The list is a single tab stop, arrow keys move selection inside it.
+            UiMessageData::Widget(WidgetMessage::KeyDown(key))
+                if message.destination() == self.handle()
+                    && !message.handled()
+                    && self.move_selection_by_arrow(ui, *key) =>
+            {
+                message.set_handled(true);
+            }
+            _ => (),
+        }
+
         if let UiMessageData::ListView(msg) = &message.data() {
             if message.destination() == self.handle()
                 && message.direction() == MessageDirection::ToWidget
@@ -123,65 +238,65 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ListView<M, C> {
                 match msg {
                     ListViewMessage::Items(items) => {
                         // Remove previous items.
-                        for child in ui.node(self.panel).children().to_vec() {
-                            ui.send_message(WidgetMessage::remove(
-                                child,
-                                MessageDirection::ToWidget,
-                            ));
-                        }
+                        self.items.clear(ui);
 
                         // Generate new items.
                         let item_containers = generate_item_containers(&mut ui.build_ctx(), items);
 
-                        for item_container in item_containers.iter() {
-                            ui.send_message(WidgetMessage::link(
-                                *item_container,
-                                MessageDirection::ToWidget,
-                                self.panel,
-                            ));
+                        for (&item, item_container) in items.iter().zip(item_containers) {
+                            self.items.add_item_with_container(ui, item, item_container);
                         }
-
-                        self.item_containers = item_containers;
-                        self.items = items.clone();
                     }
                     &ListViewMessage::AddItem(item) => {
                         let item_container =
                             generate_item_container(&mut ui.build_ctx(), item, self.items.len());
 
-                        ui.send_message(WidgetMessage::link(
-                            item,
-                            MessageDirection::ToWidget,
-                            self.panel,
-                        ));
+                        self.items.add_item_with_container(ui, item, item_container);
+                    }
+                    &ListViewMessage::RemoveItem(index) => {
+                        if self.items.remove_item(ui, index).is_some() {
+                            // Containers store their indices, so shift indices of every container
+                            // after removed one.
+                            for (i, &container) in
+                                self.items.item_containers().iter().enumerate().skip(index)
+                            {
+                                if let UINode::ListViewItem(container) = &mut ui.nodes[container] {
+                                    container.index = i;
+                                }
+                            }
 
-                        self.item_containers.push(item_container);
-                        self.items.push(item);
+                            let selection = match self.selected_index {
+                                Some(selected) if selected == index => None,
+                                Some(selected) if selected > index => Some(selected - 1),
+                                selection => selection,
+                            };
+                            if selection != self.selected_index {
+                                ui.send_message(ListViewMessage::selection(
+                                    self.handle(),
+                                    MessageDirection::ToWidget,
+                                    selection,
+                                ));
+                            }
+                        }
+                    }
+                    &ListViewMessage::ItemsPanel(panel) => {
+                        if panel != self.items.items_panel() {
+                            self.items.set_items_panel(ui, panel);
+                            // Scroll viewer will remove previous panel.
+                            ui.send_message(ScrollViewerMessage::content(
+                                self.scroll_viewer,
+                                MessageDirection::ToWidget,
+                                panel,
+                            ));
+                        }
+                    }
+                    &ListViewMessage::Placeholder(placeholder) => {
+                        self.items.set_placeholder(ui, self.handle(), placeholder);
                     }
                     &ListViewMessage::SelectionChanged(selection) => {
                         if self.selected_index != selection {
                             self.selected_index = selection;
-
-                            for (i, &container) in self.item_containers.iter().enumerate() {
-                                let select = selection.map_or(false, |k| k == i);
-                                if let UINode::ListViewItem(container) = ui.node(container) {
-                                    let mut stack = container.children().to_vec();
-                                    while let Some(handle) = stack.pop() {
-                                        let node = ui.node(handle);
-                                        match node {
-                                            UINode::ListView(_) => {}
-                                            UINode::Decorator(_) => {
-                                                ui.send_message(DecoratorMessage::select(
-                                                    handle,
-                                                    MessageDirection::ToWidget,
-                                                    select,
-                                                ));
-                                            }
-                                            _ => stack.extend_from_slice(node.children()),
-                                        }
-                                    }
-                                }
-                            }
-
+                            self.sync_selection(ui);
                             ui.send_message(message.reverse());
                         }
                     }
@@ -191,7 +306,10 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ListView<M, C> {
     }
 
     fn remove_ref(&mut self, handle: Handle<UINode<M, C>>) {
-        self.item_containers.retain(|i| *i != handle);
+        self.items.remove_ref(handle);
+        if self.scroll_viewer == handle {
+            self.scroll_viewer = Handle::NONE;
+        }
     }
 }
 
@@ -200,6 +318,7 @@ pub struct ListViewBuilder<M: MessageData, C: Control<M, C>> {
     items: Vec<Handle<UINode<M, C>>>,
     panel: Option<Handle<UINode<M, C>>>,
     scroll_viewer: Option<Handle<UINode<M, C>>>,
+    placeholder: Handle<UINode<M, C>>,
 }
 
 impl<M: MessageData, C: Control<M, C>> ListViewBuilder<M, C> {
@@ -209,9 +328,17 @@ impl<M: MessageData, C: Control<M, C>> ListViewBuilder<M, C> {
             items: Vec::new(),
             panel: None,
             scroll_viewer: None,
+            placeholder: Handle::NONE,
         }
     }
 
+    /// Sets a node (like "No results" text) that is shown in the center of the list only while
+    /// the list has no items.
+    pub fn with_placeholder(mut self, placeholder: Handle<UINode<M, C>>) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
     pub fn with_items(mut self, items: Vec<Handle<UINode<M, C>>>) -> Self {
         self.items = items;
         self
@@ -227,12 +354,24 @@ impl<M: MessageData, C: Control<M, C>> ListViewBuilder<M, C> {
         self
     }
 
-    pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+    pub fn build(mut self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        if self.widget_builder.keyboard_navigation.is_none() {
+            self.widget_builder.keyboard_navigation = Some(NavMode::Contained);
+        }
+        if self.widget_builder.tab_stop.is_none() {
+            self.widget_builder.tab_stop = Some(true);
+        }
+
         let item_containers = generate_item_containers(ctx, &self.items);
 
-        let panel = self.panel.unwrap_or_else(|| {
+        let panel = if let Some(panel) = self.panel {
+            for &item_container in item_containers.iter() {
+                ctx.link(item_container, panel);
+            }
+            panel
+        } else {
             StackPanelBuilder::new(WidgetBuilder::new().with_children(&item_containers)).build(ctx)
-        });
+        };
 
         let back = BorderBuilder::new(
             WidgetBuilder::new()
@@ -257,11 +396,16 @@ impl<M: MessageData, C: Control<M, C>> ListViewBuilder<M, C> {
         ctx.link(scroll_viewer, back);
 
         let list_box = ListView {
-            widget: self.widget_builder.with_child(back).build(),
+            widget: self
+                .widget_builder
+                .with_child(back)
+                .with_child(self.placeholder)
+                .build(),
             selected_index: None,
-            item_containers,
-            items: self.items,
-            panel,
+            items: ItemsControl::with_containers(panel, self.items, item_containers)
+                .with_placeholder(ctx, self.placeholder),
+            scroll_viewer,
+            has_focus: false,
         };
 
         ctx.add_node(UINode::ListView(list_box))
@@ -291,3 +435,69 @@ fn generate_item_containers<M: MessageData, C: Control<M, C>>(
         .map(|(index, &item)| generate_item_container(ctx, item, index))
         .collect()
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        core::algebra::Vector2,
+        list_view::ListViewBuilder,
+        message::{ButtonState, KeyCode, OsEvent},
+        node::StubNode,
+        scroll_viewer::ScrollViewerBuilder,
+        widget::WidgetBuilder,
+        UINode, UserInterface,
+    };
+
+    #[test]
+    fn single_tab_stop() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let items = (0..3)
+            .map(|_| BorderBuilder::new(WidgetBuilder::new().with_height(20.0)).build(ctx))
+            .collect::<Vec<_>>();
+        // Real scroll bars need a font.
+        let h_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let v_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let scroll_viewer = ScrollViewerBuilder::new(WidgetBuilder::new())
+            .with_horizontal_scroll_bar(h_scroll_bar)
+            .with_vertical_scroll_bar(v_scroll_bar)
+            .build(ctx);
+        let list = ListViewBuilder::new(WidgetBuilder::new())
+            .with_items(items)
+            .with_scroll_viewer(scroll_viewer)
+            .build(ctx);
+        ui.update(screen_size, 0.0);
+
+        let press = |ui: &mut UserInterface<(), StubNode>, button| {
+            for &state in [ButtonState::Pressed, ButtonState::Released].iter() {
+                ui.process_os_event(&OsEvent::KeyboardInput { button, state });
+            }
+            while ui.poll_message().is_some() {}
+        };
+        let selected = |ui: &UserInterface<(), StubNode>| {
+            if let UINode::ListView(list) = ui.node(list) {
+                list.selected()
+            } else {
+                unreachable!()
+            }
+        };
+
+        press(&mut ui, KeyCode::Tab);
+        assert_eq!(ui.keyboard_focus_node(), list);
+        // Items are not tab stops, so focus stays on the list.
+        press(&mut ui, KeyCode::Tab);
+        assert_eq!(ui.keyboard_focus_node(), list);
+
+        press(&mut ui, KeyCode::Down);
+        assert_eq!(selected(&ui), Some(0));
+        press(&mut ui, KeyCode::Down);
+        press(&mut ui, KeyCode::Down);
+        press(&mut ui, KeyCode::Down);
+        assert_eq!(selected(&ui), Some(2));
+        press(&mut ui, KeyCode::Up);
+        assert_eq!(selected(&ui), Some(1));
+        assert_eq!(ui.keyboard_focus_node(), list);
+    }
+}
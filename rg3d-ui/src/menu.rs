@@ -1,10 +1,12 @@
 use crate::core::algebra::Vector2;
 use crate::{
+    accessibility::AccessibleRole,
     border::BorderBuilder,
     brush::Brush,
     core::{color::Color, pool::Handle},
     decorator::DecoratorBuilder,
     grid::{Column, GridBuilder, Row},
+    items_control::ItemsControl,
     message::{
         ButtonState, MenuItemMessage, MenuMessage, OsEvent, PopupMessage, UiMessage, UiMessageData,
         WidgetMessage,
@@ -27,11 +29,24 @@ use std::{
 pub struct Menu<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     active: bool,
+    items: ItemsControl<M, C>,
 }
 
 crate::define_widget_deref!(Menu<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for Menu<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Menu
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
+        self.items.resolve(node_map);
+    }
+
+    fn remove_ref(&mut self, handle: Handle<UINode<M, C>>) {
+        self.items.remove_ref(handle);
+    }
+
     fn handle_routed_message(
         &mut self,
         ui: &mut UserInterface<M, C>,
@@ -126,6 +141,16 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Menu<M, C> {
     }
 }
 
+impl<M: MessageData, C: Control<M, C>> Menu<M, C> {
+    pub fn items(&self) -> &[Handle<UINode<M, C>>] {
+        self.items.items()
+    }
+
+    pub fn items_control(&self) -> &ItemsControl<M, C> {
+        &self.items
+    }
+}
+
 #[derive(Copy, Clone, PartialOrd, PartialEq, Hash)]
 enum MenuItemPlacement {
     Bottom,
@@ -135,13 +160,24 @@ enum MenuItemPlacement {
 #[derive(Clone)]
 pub struct MenuItem<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
-    items: Vec<Handle<UINode<M, C>>>,
+    items: ItemsControl<M, C>,
     popup: Handle<UINode<M, C>>,
+    back: Handle<UINode<M, C>>,
     placement: MenuItemPlacement,
 }
 
 crate::define_widget_deref!(MenuItem<M, C>);
 
+impl<M: MessageData, C: Control<M, C>> MenuItem<M, C> {
+    pub fn items(&self) -> &[Handle<UINode<M, C>>] {
+        self.items.items()
+    }
+
+    pub fn items_control(&self) -> &ItemsControl<M, C> {
+        &self.items
+    }
+}
+
 // MenuItem uses popup to show its content, popup can be top-most only if it is
 // direct child of root canvas of UI. This fact adds some complications to search
 // of parent menu - we can't just traverse the tree because popup is not a child
@@ -169,9 +205,18 @@ fn find_menu<M: MessageData, C: Control<M, C>>(
 }
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for MenuItem<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::MenuItem
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
-        node_map.resolve_slice(&mut self.items);
+        self.items.resolve(node_map);
         node_map.resolve(&mut self.popup);
+        node_map.resolve(&mut self.back);
+    }
+
+    fn remove_ref(&mut self, handle: Handle<UINode<M, C>>) {
+        self.items.remove_ref(handle);
     }
 
     fn handle_routed_message(
@@ -218,6 +263,16 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for MenuItem<M, C> {
                             message.set_handled(true);
                         }
                     }
+                    &WidgetMessage::Enabled(enabled)
+                        if message.destination() == self.handle()
+                            && message.direction() == MessageDirection::ToWidget =>
+                    {
+                        ui.send_message(WidgetMessage::enabled(
+                            self.back,
+                            MessageDirection::ToWidget,
+                            enabled,
+                        ));
+                    }
                     WidgetMessage::MouseEnter => {
                         // While parent menu active it is possible to open submenus
                         // by simple mouse hover.
@@ -333,20 +388,21 @@ impl<M: MessageData, C: Control<M, C>> MenuBuilder<M, C> {
             }
         }
 
+        let panel = StackPanelBuilder::new(WidgetBuilder::new().with_children(&self.items))
+            .with_orientation(Orientation::Horizontal)
+            .build(ctx);
+
         let back = BorderBuilder::new(
             WidgetBuilder::new()
                 .with_background(BRUSH_PRIMARY)
-                .with_child(
-                    StackPanelBuilder::new(WidgetBuilder::new().with_children(&self.items))
-                        .with_orientation(Orientation::Horizontal)
-                        .build(ctx),
-                ),
+                .with_child(panel),
         )
         .build(ctx);
 
         let menu = Menu {
             widget: self.widget_builder.with_child(back).build(),
             active: false,
+            items: ItemsControl::new(panel, self.items),
         };
 
         ctx.add_node(UINode::Menu(menu))
@@ -478,10 +534,11 @@ impl<'a, 'b, M: MessageData, C: Control<M, C>> MenuItemBuilder<'a, 'b, M, C> {
 
         ctx.link(content, back);
 
+        let panel =
+            StackPanelBuilder::new(WidgetBuilder::new().with_children(&self.items)).build(ctx);
+
         let popup = PopupBuilder::new(WidgetBuilder::new().with_min_size(Vector2::new(10.0, 10.0)))
-            .with_content(
-                StackPanelBuilder::new(WidgetBuilder::new().with_children(&self.items)).build(ctx),
-            )
+            .with_content(panel)
             // We'll manually control if popup is either open or closed.
             .stays_open(true)
             .build(ctx);
@@ -489,7 +546,8 @@ impl<'a, 'b, M: MessageData, C: Control<M, C>> MenuItemBuilder<'a, 'b, M, C> {
         let menu = MenuItem {
             widget: self.widget_builder.with_child(back).build(),
             popup,
-            items: self.items,
+            items: ItemsControl::new(panel, self.items),
+            back,
             placement: MenuItemPlacement::Right,
         };
 
@@ -13,20 +13,27 @@
 //! However [WidgetMessage::GotFocus](enum.WidgetMessage.html) has "Direction: From UI" which means that only
 //! internal library code can send such messages without a risk of breaking anything.
 
-use crate::core::algebra::{Vector2, Vector3};
+use crate::core::algebra::{Matrix3, Vector2, Vector3};
 use crate::dock::SplitDirection;
 use crate::{
     brush::Brush,
+    button::StateTextures,
+    chart::{ChartMode, ChartSeries},
     core::{
         color::{Color, Hsv},
+        math::Rect,
         pool::Handle,
     },
+    data_grid::{DataGridValue, SortOrder},
     dock::TileContent,
-    draw::SharedTexture,
+    draw::{Backdrop, SharedTexture},
+    formatted_text::{TextRun, TextTrimming, WrapMode},
     messagebox::MessageBoxResult,
     popup::Placement,
+    scroll_bar::ScrollBarStyle,
     ttf::SharedFont,
-    window::WindowTitle,
+    widget::TooltipPlacement,
+    window::{SizeToContent, WindowTitle},
     Control, HorizontalAlignment, MouseState, Orientation, Thickness, UINode, VerticalAlignment,
 };
 use std::{cell::Cell, fmt::Debug, path::PathBuf};
@@ -157,6 +164,29 @@ pub enum WidgetMessage<M: MessageData, C: Control<M, C>> {
         amount: f32,
     },
 
+    /// Initiated when a widget was tapped by a finger on touch screen.
+    ///
+    /// Direction: **From UI**.
+    Tap {
+        /// Position of the touch.
+        pos: Vector2<f32>,
+    },
+
+    /// Initiated when a finger was held on a widget for some time without moving.
+    ///
+    /// Direction: **From UI**.
+    LongPress {
+        /// Position of the touch.
+        pos: Vector2<f32>,
+    },
+
+    /// Initiated when mouse capture (or mouse press without capture) of a widget was taken away
+    /// by the UI, for example when a touch press has turned into panning. Widget must forget its
+    /// pressed or dragged state without performing any action (no clicks, no selection).
+    ///
+    /// Direction: **From UI**.
+    MouseCaptureLost,
+
     /// Initiated when cursor leaves geometry of a widget.
     ///
     /// Direction: **From UI**.
@@ -214,6 +244,12 @@ pub enum WidgetMessage<M: MessageData, C: Control<M, C>> {
     /// Direction: **From/To UI**.
     TopMost,
 
+    /// Initiated when order of children of widget was changed by `UserInterface::set_child_index`
+    /// and friends.
+    ///
+    /// Direction: **From UI**.
+    ChildrenReordered,
+
     /// A request to detach widget from its current parent and attach to root canvas.
     ///
     /// Direction: **From/To UI**.
@@ -225,6 +261,27 @@ pub enum WidgetMessage<M: MessageData, C: Control<M, C>> {
     /// Direction: **From/To UI**.
     Remove,
 
+    /// Initiated when a widget was attached to a parent. Emitted for every link, including
+    /// links made while building a tree, only if enabled by
+    /// `UserInterface::set_structure_notifications`.
+    ///
+    /// Direction: **From UI**.
+    NodeAdded {
+        /// New parent of the widget.
+        parent: Handle<UINode<M, C>>,
+    },
+
+    /// Initiated when a widget was detached from its parent or removed. When a widget is
+    /// removed, every descendant widget emits this message too, children go before parents.
+    /// Handle of a removed widget is already invalid when this message is received. Emitted
+    /// only if enabled by `UserInterface::set_structure_notifications`.
+    ///
+    /// Direction: **From UI**.
+    NodeRemoved {
+        /// Former parent of the widget.
+        parent: Handle<UINode<M, C>>,
+    },
+
     /// A request to link initiator with specified widget.
     ///
     /// Direction: **From/To UI**.
@@ -356,16 +413,61 @@ pub enum WidgetMessage<M: MessageData, C: Control<M, C>> {
     /// Direction: **From/To UI**
     Cursor(Option<CursorIcon>),
 
+    /// A request to set new tooltip for widget. Tooltip could be any widget.
+    ///
+    /// Direction: **From/To UI**
+    Tooltip(Handle<UINode<M, C>>),
+
+    /// A request to change placement of tooltip of widget.
+    ///
+    /// Direction: **From/To UI**
+    TooltipPlacement(TooltipPlacement),
+
     /// A request to set new opacity for widget.
     ///
     /// Direction: **From/To UI**
     Opacity(f32),
+
+    /// A request to set new tint for widget, see `Widget::set_tint`.
+    ///
+    /// Direction: **From/To UI**
+    Tint(Color),
+
+    /// A request to set new render layer for widget, see `WidgetBuilder::with_render_layer`.
+    ///
+    /// Direction: **From/To UI**
+    RenderLayer(u8),
+
+    /// A request to set new render transform for widget, see `Widget::set_render_transform`.
+    ///
+    /// Direction: **From/To UI**
+    RenderTransform(Matrix3<f32>),
+
+    /// Sent by a text editing widget (like a text box) when it gets keyboard focus, so host
+    /// application can show soft (on-screen) keyboard on touch devices. Bounds of the widget
+    /// are in physical units of the screen, they allow to place the keyboard (or scroll the
+    /// UI) so the widget is not covered by it.
+    ///
+    /// Direction: **From UI**
+    ShowSoftKeyboard { bounds: Rect<f32> },
+
+    /// Sent by a text editing widget when it loses keyboard focus, host application may hide
+    /// soft keyboard. If focus moves to another text editing widget, it is followed by
+    /// `ShowSoftKeyboard` from that widget.
+    ///
+    /// Direction: **From UI**
+    HideSoftKeyboard,
 }
 
 impl<M: MessageData, C: Control<M, C>> WidgetMessage<M, C> {
     define_constructor!(Widget(WidgetMessage:Remove) => fn remove(), layout: false);
+    define_constructor!(Widget(WidgetMessage:ShowSoftKeyboard) => fn show_soft_keyboard(bounds: Rect<f32>), layout: false);
+    define_constructor!(Widget(WidgetMessage:HideSoftKeyboard) => fn hide_soft_keyboard(), layout: false);
     define_constructor!(Widget(WidgetMessage:Unlink) => fn unlink(), layout: false);
+    define_constructor!(Widget(WidgetMessage:ChildrenReordered) => fn children_reordered(), layout: false);
     define_constructor!(Widget(WidgetMessage:LinkWith) => fn link(Handle<UINode<M, C>>), layout: false);
+    define_constructor!(Widget(WidgetMessage:NodeAdded) => fn node_added(parent: Handle<UINode<M, C>>), layout: false);
+    define_constructor!(Widget(WidgetMessage:NodeRemoved) => fn node_removed(parent: Handle<UINode<M, C>>), layout: false);
     define_constructor!(Widget(WidgetMessage:LinkWithReverse) => fn link_reverse(Handle<UINode<M, C>>), layout: false);
     define_constructor!(Widget(WidgetMessage:Background) => fn background(Brush), layout: false);
     define_constructor!(Widget(WidgetMessage:Foreground) => fn foreground(Brush), layout: false);
@@ -380,6 +482,8 @@ impl<M: MessageData, C: Control<M, C>> WidgetMessage<M, C> {
     define_constructor!(Widget(WidgetMessage:Row) => fn row(usize), layout: false);
     define_constructor!(Widget(WidgetMessage:Column) => fn column(usize), layout: false);
     define_constructor!(Widget(WidgetMessage:Cursor) => fn cursor(Option<CursorIcon>), layout: false);
+    define_constructor!(Widget(WidgetMessage:Tooltip) => fn tooltip(Handle<UINode<M, C>>), layout: false);
+    define_constructor!(Widget(WidgetMessage:TooltipPlacement) => fn tooltip_placement(TooltipPlacement), layout: false);
     define_constructor!(Widget(WidgetMessage:ZIndex) => fn z_index(usize), layout: false);
     define_constructor!(Widget(WidgetMessage:HitTestVisibility) => fn hit_test_visibility(bool), layout: false);
     define_constructor!(Widget(WidgetMessage:Margin) => fn margin(Thickness), layout: false);
@@ -388,6 +492,9 @@ impl<M: MessageData, C: Control<M, C>> WidgetMessage<M, C> {
     define_constructor!(Widget(WidgetMessage:HorizontalAlignment) => fn horizontal_alignment(HorizontalAlignment), layout: false);
     define_constructor!(Widget(WidgetMessage:VerticalAlignment) => fn vertical_alignment(VerticalAlignment), layout: false);
     define_constructor!(Widget(WidgetMessage:Opacity) => fn opacity(f32), layout: false);
+    define_constructor!(Widget(WidgetMessage:Tint) => fn tint(Color), layout: false);
+    define_constructor!(Widget(WidgetMessage:RenderLayer) => fn render_layer(u8), layout: false);
+    define_constructor!(Widget(WidgetMessage:RenderTransform) => fn render_transform(Matrix3<f32>), layout: false);
 
     // Internal messages. Do not use.
     define_constructor!(Widget(WidgetMessage:GotFocus) => fn got_focus(), layout: false);
@@ -396,6 +503,9 @@ impl<M: MessageData, C: Control<M, C>> WidgetMessage<M, C> {
     define_constructor!(Widget(WidgetMessage:MouseUp) => fn mouse_up(pos: Vector2<f32>, button: MouseButton), layout: false);
     define_constructor!(Widget(WidgetMessage:MouseMove) => fn mouse_move(pos: Vector2<f32>, state: MouseState), layout: false);
     define_constructor!(Widget(WidgetMessage:MouseWheel) => fn mouse_wheel(pos: Vector2<f32>, amount: f32), layout: false);
+    define_constructor!(Widget(WidgetMessage:Tap) => fn tap(pos: Vector2<f32>), layout: false);
+    define_constructor!(Widget(WidgetMessage:LongPress) => fn long_press(pos: Vector2<f32>), layout: false);
+    define_constructor!(Widget(WidgetMessage:MouseCaptureLost) => fn mouse_capture_lost(), layout: false);
     define_constructor!(Widget(WidgetMessage:MouseLeave) => fn mouse_leave(), layout: false);
     define_constructor!(Widget(WidgetMessage:MouseEnter) => fn mouse_enter(), layout: false);
     define_constructor!(Widget(WidgetMessage:Text) => fn text(char), layout: false);
@@ -410,11 +520,14 @@ impl<M: MessageData, C: Control<M, C>> WidgetMessage<M, C> {
 pub enum ButtonMessage<M: MessageData, C: Control<M, C>> {
     Click,
     Content(Handle<UINode<M, C>>),
+    /// Sets textures of a skinned button, see `ButtonBuilder::with_state_textures`.
+    StateTextures(StateTextures),
 }
 
 impl<M: MessageData, C: Control<M, C>> ButtonMessage<M, C> {
     define_constructor!(Button(ButtonMessage:Click) => fn click(), layout: false);
     define_constructor!(Button(ButtonMessage:Content) => fn content(Handle<UINode<M, C>>), layout: false);
+    define_constructor!(Button(ButtonMessage:StateTextures) => fn state_textures(StateTextures), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -422,12 +535,39 @@ pub enum ScrollBarMessage {
     Value(f32),
     MinValue(f32),
     MaxValue(f32),
+    /// Amount by which the value is changed by a click on the track.
+    PageSize(f32),
+    /// Applies style to the scroll bar and its parts.
+    Style(ScrollBarStyle),
 }
 
 impl ScrollBarMessage {
     define_constructor_unbound!(ScrollBar(ScrollBarMessage:Value) => fn value(f32), layout: false);
     define_constructor_unbound!(ScrollBar(ScrollBarMessage:MaxValue) => fn max_value(f32), layout: false);
     define_constructor_unbound!(ScrollBar(ScrollBarMessage:MinValue) => fn min_value(f32), layout: false);
+    define_constructor_unbound!(ScrollBar(ScrollBarMessage:PageSize) => fn page_size(f32), layout: false);
+    define_constructor_unbound!(ScrollBar(ScrollBarMessage:Style) => fn style(ScrollBarStyle), layout: false);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RangeSliderMessage {
+    /// Sets both ends of the selected range. Values are clamped to `[min, max]` and snapped to
+    /// the step, response is sent only if the range has changed.
+    Range {
+        low: f32,
+        high: f32,
+    },
+    MinValue(f32),
+    MaxValue(f32),
+    /// Step to which values are snapped, zero disables snapping.
+    Step(f32),
+}
+
+impl RangeSliderMessage {
+    define_constructor_unbound!(RangeSlider(RangeSliderMessage:Range) => fn range(low: f32, high: f32), layout: false);
+    define_constructor_unbound!(RangeSlider(RangeSliderMessage:MinValue) => fn min_value(f32), layout: false);
+    define_constructor_unbound!(RangeSlider(RangeSliderMessage:MaxValue) => fn max_value(f32), layout: false);
+    define_constructor_unbound!(RangeSlider(RangeSliderMessage:Step) => fn step(f32), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -440,12 +580,105 @@ impl CheckBoxMessage {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum ExpanderMessage {
+pub enum ExpanderMessage<M: MessageData, C: Control<M, C>> {
     Expand(bool),
+    /// Replaces header of expander, previous header is removed. Header can be any node, it
+    /// is placed next to the toggle arrow.
+    Header(Handle<UINode<M, C>>),
+    /// Replaces header of expander with a text.
+    HeaderText(String),
+    /// Whether click on header toggles expander or only click on arrow does.
+    ToggleOnHeader(bool),
 }
 
-impl ExpanderMessage {
-    define_constructor_unbound!(Expander(ExpanderMessage:Expand) => fn expand(bool), layout: false);
+impl<M: MessageData, C: Control<M, C>> ExpanderMessage<M, C> {
+    define_constructor!(Expander(ExpanderMessage:Expand) => fn expand(bool), layout: false);
+    define_constructor!(Expander(ExpanderMessage:Header) => fn header(Handle<UINode<M, C>>), layout: false);
+    define_constructor!(Expander(ExpanderMessage:HeaderText) => fn header_text(String), layout: false);
+    define_constructor!(Expander(ExpanderMessage:ToggleOnHeader) => fn toggle_on_header(bool), layout: false);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartMessage {
+    /// Switches chart between bars and line graph.
+    Mode(ChartMode),
+
+    /// Adds new data series.
+    AddSeries(ChartSeries),
+
+    /// Appends a value to a series with given index.
+    PushValue { series: usize, value: f32 },
+
+    /// Sets maximum amount of values in each series, `None` means unlimited.
+    WindowSize(Option<usize>),
+
+    /// Removes every series.
+    Clear,
+}
+
+impl ChartMessage {
+    define_constructor_unbound!(Chart(ChartMessage:Mode) => fn mode(ChartMode), layout: true);
+    define_constructor_unbound!(Chart(ChartMessage:AddSeries) => fn add_series(ChartSeries), layout: true);
+    define_constructor_unbound!(Chart(ChartMessage:PushValue) => fn push_value(series: usize, value: f32), layout: true);
+    define_constructor_unbound!(Chart(ChartMessage:WindowSize) => fn window_size(Option<usize>), layout: true);
+    define_constructor_unbound!(Chart(ChartMessage:Clear) => fn clear(), layout: true);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleViewMessage {
+    /// Appends a line to the end of the view, `None` color means foreground of the view.
+    AppendLine { text: String, color: Option<Color> },
+
+    /// Removes every line.
+    Clear,
+
+    /// Sets maximum amount of retained lines, oldest lines are dropped first.
+    MaxLines(usize),
+
+    /// Sent by the view (with FromWidget direction) when user presses Ctrl+C, contains
+    /// selected text. UI does not have access to system clipboard, so it is up to user to
+    /// put the text there.
+    Copy(String),
+}
+
+impl ConsoleViewMessage {
+    define_constructor_unbound!(ConsoleView(ConsoleViewMessage:AppendLine) => fn append_line(text: String, color: Option<Color>), layout: false);
+    define_constructor_unbound!(ConsoleView(ConsoleViewMessage:Clear) => fn clear(), layout: false);
+    define_constructor_unbound!(ConsoleView(ConsoleViewMessage:MaxLines) => fn max_lines(usize), layout: false);
+    define_constructor_unbound!(ConsoleView(ConsoleViewMessage:Copy) => fn copy(String), layout: false);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataGridMessage {
+    /// Replaces every row, current sorting is applied to new rows.
+    Rows(Vec<Vec<DataGridValue>>),
+
+    /// Selects a row with given index (in current order).
+    ///
+    /// Direction: **To/From UI**.
+    SelectionChanged(Option<usize>),
+
+    /// Sorts rows by a column. Sent by the grid when user clicks a header of sortable column,
+    /// first click sorts in ascending order, next clicks toggle the order.
+    ///
+    /// Direction: **To/From UI**.
+    SortChanged { column: usize, order: SortOrder },
+
+    /// Sets value of a cell. Sent by the grid when user has edited a cell.
+    ///
+    /// Direction: **To/From UI**.
+    CellChanged {
+        row: usize,
+        column: usize,
+        value: DataGridValue,
+    },
+}
+
+impl DataGridMessage {
+    define_constructor_unbound!(DataGrid(DataGridMessage:Rows) => fn rows(Vec<Vec<DataGridValue>>), layout: false);
+    define_constructor_unbound!(DataGrid(DataGridMessage:SelectionChanged) => fn selection(Option<usize>), layout: false);
+    define_constructor_unbound!(DataGrid(DataGridMessage:SortChanged) => fn sort_changed(column: usize, order: SortOrder), layout: false);
+    define_constructor_unbound!(DataGrid(DataGridMessage:CellChanged) => fn cell_changed(row: usize, column: usize, value: DataGridValue), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -474,6 +707,10 @@ pub enum WindowMessage<M: MessageData, C: Control<M, C>> {
     /// Whether or not window can be resized by resize grips.
     CanResize(bool),
 
+    /// Sets thickness of resize zone along edges of window, see
+    /// `Window::set_resize_border_thickness`.
+    ResizeBorderThickness(f32),
+
     /// Indicates that move has been started. You should never send this message by hand.
     MoveStart,
 
@@ -485,6 +722,17 @@ pub enum WindowMessage<M: MessageData, C: Control<M, C>> {
 
     /// Sets new window title.
     Title(WindowTitle<M, C>),
+
+    /// Sets axes on which window will be sized to its content.
+    SizeToContent(SizeToContent),
+
+    /// Brings window on top of other windows on the same hierarchy level. Modal windows always
+    /// stay above non-modal ones. Sent automatically on mouse click anywhere in a window, the
+    /// window then responds with the same message with FromWidget direction.
+    Activate,
+
+    /// Sets backdrop that is drawn behind the window.
+    Backdrop(Backdrop),
 }
 
 impl<M: MessageData, C: Control<M, C>> WindowMessage<M, C> {
@@ -495,23 +743,45 @@ impl<M: MessageData, C: Control<M, C>> WindowMessage<M, C> {
     define_constructor!(Window(WindowMessage:CanMinimize) => fn can_minimize(bool), layout: false);
     define_constructor!(Window(WindowMessage:CanClose) => fn can_close(bool), layout: false);
     define_constructor!(Window(WindowMessage:CanResize) => fn can_resize(bool), layout: false);
+    define_constructor!(Window(WindowMessage:ResizeBorderThickness) => fn resize_border_thickness(f32), layout: false);
     define_constructor!(Window(WindowMessage:MoveStart) => fn move_start(), layout: false);
     define_constructor!(Window(WindowMessage:Move) => fn move_to(Vector2<f32>), layout: false);
     define_constructor!(Window(WindowMessage:MoveEnd) => fn move_end(), layout: false);
     define_constructor!(Window(WindowMessage:Title) => fn title(WindowTitle<M, C>), layout: false);
+    define_constructor!(Window(WindowMessage:SizeToContent) => fn size_to_content(SizeToContent), layout: false);
+    define_constructor!(Window(WindowMessage:Activate) => fn activate(), layout: false);
+    define_constructor!(Window(WindowMessage:Backdrop) => fn backdrop(Backdrop), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ScrollViewerMessage<M: MessageData, C: Control<M, C>> {
     Content(Handle<UINode<M, C>>),
+    /// Replaces header of scroll viewer, previous header is removed. Header stays above
+    /// content while it is scrolled vertically and follows horizontal scroll of content, it
+    /// is useful for column headers of tables. `Handle::NONE` removes the header. Header can
+    /// be replaced only in a scroll viewer that was built with a header.
+    Header(Handle<UINode<M, C>>),
     /// Adjusts vertical and horizontal scroll values so given node will be in "view box"
     /// of scroll viewer.
     BringIntoView(Handle<UINode<M, C>>),
+    /// Allows or forbids horizontal scrolling of content.
+    HorizontalScroll(bool),
+    /// Allows or forbids vertical scrolling of content.
+    VerticalScroll(bool),
+    /// Stretches content to width of viewport, otherwise content has its own width.
+    StretchContentWidth(bool),
+    /// Stretches content to height of viewport, otherwise content has its own height.
+    StretchContentHeight(bool),
 }
 
 impl<M: MessageData, C: Control<M, C>> ScrollViewerMessage<M, C> {
     define_constructor!(ScrollViewer(ScrollViewerMessage:Content) => fn content(Handle<UINode<M, C>>), layout: false);
+    define_constructor!(ScrollViewer(ScrollViewerMessage:Header) => fn header(Handle<UINode<M, C>>), layout: false);
     define_constructor!(ScrollViewer(ScrollViewerMessage:BringIntoView) => fn bring_into_view(Handle<UINode<M, C>>), layout: true);
+    define_constructor!(ScrollViewer(ScrollViewerMessage:HorizontalScroll) => fn horizontal_scroll(bool), layout: false);
+    define_constructor!(ScrollViewer(ScrollViewerMessage:VerticalScroll) => fn vertical_scroll(bool), layout: false);
+    define_constructor!(ScrollViewer(ScrollViewerMessage:StretchContentWidth) => fn stretch_content_width(bool), layout: false);
+    define_constructor!(ScrollViewer(ScrollViewerMessage:StretchContentHeight) => fn stretch_content_height(bool), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -519,12 +789,22 @@ pub enum ListViewMessage<M: MessageData, C: Control<M, C>> {
     SelectionChanged(Option<usize>),
     Items(Vec<Handle<UINode<M, C>>>),
     AddItem(Handle<UINode<M, C>>),
+    /// Removes an item at given index.
+    RemoveItem(usize),
+    /// Replaces items panel (a panel which arranges items, StackPanel by default).
+    ItemsPanel(Handle<UINode<M, C>>),
+    /// Replaces node that is shown while the list has no items, see
+    /// `ListViewBuilder::with_placeholder`.
+    Placeholder(Handle<UINode<M, C>>),
 }
 
 impl<M: MessageData, C: Control<M, C>> ListViewMessage<M, C> {
     define_constructor!(ListView(ListViewMessage:SelectionChanged) => fn selection(Option<usize>), layout: false);
     define_constructor!(ListView(ListViewMessage:Items) => fn items(Vec<Handle<UINode<M, C>>>), layout: false);
     define_constructor!(ListView(ListViewMessage:AddItem) => fn add_item(Handle<UINode<M, C>>), layout: false);
+    define_constructor!(ListView(ListViewMessage:RemoveItem) => fn remove_item(usize), layout: false);
+    define_constructor!(ListView(ListViewMessage:ItemsPanel) => fn items_panel(Handle<UINode<M, C>>), layout: false);
+    define_constructor!(ListView(ListViewMessage:Placeholder) => fn placeholder(Handle<UINode<M, C>>), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -546,6 +826,8 @@ pub enum PopupMessage<M: MessageData, C: Control<M, C>> {
     Close,
     Content(Handle<UINode<M, C>>),
     Placement(Placement),
+    /// Sets backdrop that is drawn behind the popup.
+    Backdrop(Backdrop),
 }
 
 impl<M: MessageData, C: Control<M, C>> PopupMessage<M, C> {
@@ -553,6 +835,7 @@ impl<M: MessageData, C: Control<M, C>> PopupMessage<M, C> {
     define_constructor!(Popup(PopupMessage:Close) => fn close(), layout: false);
     define_constructor!(Popup(PopupMessage:Content) => fn content(Handle<UINode<M, C>>), layout: false);
     define_constructor!(Popup(PopupMessage:Placement) => fn placement(Placement), layout: false);
+    define_constructor!(Popup(PopupMessage:Backdrop) => fn backdrop(Backdrop), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -611,6 +894,9 @@ pub enum TreeRootMessage<M: MessageData, C: Control<M, C>> {
     RemoveItem(Handle<UINode<M, C>>),
     Items(Vec<Handle<UINode<M, C>>>),
     Selected(Vec<Handle<UINode<M, C>>>),
+    /// Replaces node that is shown while the tree has no items, see
+    /// `TreeRootBuilder::with_placeholder`.
+    Placeholder(Handle<UINode<M, C>>),
 }
 
 impl<M: MessageData, C: Control<M, C>> TreeRootMessage<M, C> {
@@ -618,6 +904,7 @@ impl<M: MessageData, C: Control<M, C>> TreeRootMessage<M, C> {
     define_constructor!(TreeRoot(TreeRootMessage:RemoveItem) => fn remove_item(Handle<UINode<M, C>>), layout: false);
     define_constructor!(TreeRoot(TreeRootMessage:Items) => fn items(Vec<Handle<UINode<M, C>>>), layout: false);
     define_constructor!(TreeRoot(TreeRootMessage:Selected) => fn select(Vec<Handle<UINode<M, C>>>), layout: false);
+    define_constructor!(TreeRoot(TreeRootMessage:Placeholder) => fn placeholder(Handle<UINode<M, C>>), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -634,16 +921,50 @@ impl FileBrowserMessage {
 #[derive(Debug, Clone, PartialEq)]
 pub enum TextBoxMessage {
     Text(String),
+    /// Inserts given text at caret position, replacing current selection. Text is passed
+    /// through the filter of a text box, so every rejected character will be skipped. UI
+    /// does not have access to system clipboard, so it is up to user to fetch clipboard
+    /// content and send this message to a focused text box. Line breaks are kept only by
+    /// multiline text boxes and tabs only by text boxes that accept them.
+    ///
+    /// Numeric fields (`NumericUpDown`) handle it differently: pasted text replaces the whole
+    /// value if it is a valid number and is ignored otherwise.
+    Paste(String),
+    /// Reverts last edit of text. Consecutive typing of a word is reverted at once.
+    Undo,
+    /// Re-applies last reverted edit. Any new edit clears redo history.
+    Redo,
+    /// See `TextBox::set_caret_brush`.
+    CaretBrush(Option<Brush>),
+    /// See `TextBox::set_caret_width`.
+    CaretWidth(f32),
+    /// See `TextBox::set_max_length`.
+    MaxLength(Option<usize>),
 }
 
 impl TextBoxMessage {
     define_constructor_unbound!(TextBox(TextBoxMessage:Text) => fn text(String), layout: false);
+    define_constructor_unbound!(TextBox(TextBoxMessage:Paste) => fn paste(String), layout: false);
+    define_constructor_unbound!(TextBox(TextBoxMessage:Undo) => fn undo(), layout: false);
+    define_constructor_unbound!(TextBox(TextBoxMessage:Redo) => fn redo(), layout: false);
+    define_constructor_unbound!(TextBox(TextBoxMessage:CaretBrush) => fn caret_brush(Option<Brush>), layout: false);
+    define_constructor_unbound!(TextBox(TextBoxMessage:CaretWidth) => fn caret_width(f32), layout: false);
+    define_constructor_unbound!(TextBox(TextBoxMessage:MaxLength) => fn max_length(Option<usize>), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TextMessage {
     Text(String),
+    /// Replaces text with a sequence of styled runs.
+    Runs(Vec<TextRun>),
+    /// Makes the text localized by given key, see `Text::set_localized`.
+    Localized(String),
+    /// Enables or disables wrapping by letters.
     Wrap(bool),
+    /// Sets the way of wrapping, see `WrapMode`.
+    WrapMode(WrapMode),
+    /// Sets the way of shortening of lines that do not fit, works only without wrapping.
+    Trimming(TextTrimming),
     Font(SharedFont),
     VerticalAlignment(VerticalAlignment),
     HorizontalAlignment(HorizontalAlignment),
@@ -651,7 +972,11 @@ pub enum TextMessage {
 
 impl TextMessage {
     define_constructor_unbound!(Text(TextMessage:Text) => fn text(String), layout: false);
+    define_constructor_unbound!(Text(TextMessage:Runs) => fn runs(Vec<TextRun>), layout: false);
+    define_constructor_unbound!(Text(TextMessage:Localized) => fn localized(String), layout: false);
     define_constructor_unbound!(Text(TextMessage:Wrap) => fn wrap(bool), layout: false);
+    define_constructor_unbound!(Text(TextMessage:WrapMode) => fn wrap_mode(WrapMode), layout: false);
+    define_constructor_unbound!(Text(TextMessage:Trimming) => fn trimming(TextTrimming), layout: false);
     define_constructor_unbound!(Text(TextMessage:Font) => fn font(SharedFont), layout: false);
     define_constructor_unbound!(Text(TextMessage:VerticalAlignment) => fn vertical_alignment(VerticalAlignment), layout: false);
     define_constructor_unbound!(Text(TextMessage:HorizontalAlignment) => fn horizontal_alignment(HorizontalAlignment), layout: false);
@@ -661,11 +986,20 @@ impl TextMessage {
 pub enum ImageMessage {
     Texture(Option<SharedTexture>),
     Flip(bool),
+    /// See `Image::set_corner_colors`.
+    CornerColors(Option<[Color; 4]>),
+    /// See `Image::set_alpha_hit_test`.
+    AlphaHitTest(bool),
+    /// See `Image::set_alpha_hit_threshold`.
+    AlphaHitThreshold(f32),
 }
 
 impl ImageMessage {
     define_constructor_unbound!(Image(ImageMessage:Texture) => fn texture(Option<SharedTexture>), layout: false);
     define_constructor_unbound!(Image(ImageMessage:Flip) => fn flip(bool), layout: false);
+    define_constructor_unbound!(Image(ImageMessage:CornerColors) => fn corner_colors(Option<[Color; 4]>), layout: false);
+    define_constructor_unbound!(Image(ImageMessage:AlphaHitTest) => fn alpha_hit_test(bool), layout: false);
+    define_constructor_unbound!(Image(ImageMessage:AlphaHitThreshold) => fn alpha_hit_threshold(f32), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -724,17 +1058,33 @@ impl Vec3EditorMessage {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ScrollPanelMessage<M: MessageData, C: Control<M, C>> {
+    /// Sets vertical offset of content. Panel clamps offset when content shrinks, the message
+    /// with new offset is sent from widget in this case. Offset past the end of content is
+    /// kept otherwise, scroll viewer uses it for overscroll.
     VerticalScroll(f32),
+    /// Sets horizontal offset of content, see `VerticalScroll`.
     HorizontalScroll(f32),
     /// Adjusts vertical and horizontal scroll values so given node will be in "view box"
     /// of scroll panel.
     BringIntoView(Handle<UINode<M, C>>),
+    /// See `ScrollViewerMessage::HorizontalScroll`.
+    HorizontalScrollAllowed(bool),
+    /// See `ScrollViewerMessage::VerticalScroll`.
+    VerticalScrollAllowed(bool),
+    /// See `ScrollViewerMessage::StretchContentWidth`.
+    StretchContentWidth(bool),
+    /// See `ScrollViewerMessage::StretchContentHeight`.
+    StretchContentHeight(bool),
 }
 
 impl<M: MessageData, C: Control<M, C>> ScrollPanelMessage<M, C> {
     define_constructor!(ScrollPanel(ScrollPanelMessage:VerticalScroll) => fn vertical_scroll(f32), layout: false);
     define_constructor!(ScrollPanel(ScrollPanelMessage:HorizontalScroll) => fn horizontal_scroll(f32), layout: false);
     define_constructor!(ScrollPanel(ScrollPanelMessage:BringIntoView) => fn bring_into_view(Handle<UINode<M, C>>), layout: true);
+    define_constructor!(ScrollPanel(ScrollPanelMessage:HorizontalScrollAllowed) => fn horizontal_scroll_allowed(bool), layout: false);
+    define_constructor!(ScrollPanel(ScrollPanelMessage:VerticalScrollAllowed) => fn vertical_scroll_allowed(bool), layout: false);
+    define_constructor!(ScrollPanel(ScrollPanelMessage:StretchContentWidth) => fn stretch_content_width(bool), layout: false);
+    define_constructor!(ScrollPanel(ScrollPanelMessage:StretchContentHeight) => fn stretch_content_height(bool), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -775,6 +1125,18 @@ impl MessageBoxMessage {
     define_constructor_unbound!(MessageBox(MessageBoxMessage:Close) => fn close(MessageBoxResult), layout: false);
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorderMessage {
+    StrokeThickness(Thickness),
+    /// Radius of rounded corners, zero means sharp corners.
+    CornerRadius(f32),
+}
+
+impl BorderMessage {
+    define_constructor_unbound!(Border(BorderMessage:StrokeThickness) => fn stroke_thickness(Thickness), layout: false);
+    define_constructor_unbound!(Border(BorderMessage:CornerRadius) => fn corner_radius(f32), layout: false);
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DecoratorMessage {
     Select(bool),
@@ -782,14 +1144,20 @@ pub enum DecoratorMessage {
     NormalBrush(Brush),
     PressedBrush(Brush),
     SelectedBrush(Brush),
+    DisabledBrush(Brush),
+    /// Whether owner of the selection (list, tree, etc.) is focused, inactive selection is
+    /// drawn with inactive selection color of the UI.
+    SelectionActive(bool),
 }
 
 impl DecoratorMessage {
     define_constructor_unbound!(Decorator(DecoratorMessage:Select) => fn select(bool), layout: false);
+    define_constructor_unbound!(Decorator(DecoratorMessage:SelectionActive) => fn selection_active(bool), layout: false);
     define_constructor_unbound!(Decorator(DecoratorMessage:HoverBrush) => fn hover_brush(Brush), layout: false);
     define_constructor_unbound!(Decorator(DecoratorMessage:NormalBrush) => fn normal_brush(Brush), layout: false);
     define_constructor_unbound!(Decorator(DecoratorMessage:PressedBrush) => fn pressed_brush(Brush), layout: false);
     define_constructor_unbound!(Decorator(DecoratorMessage:SelectedBrush) => fn selected_brush(Brush), layout: false);
+    define_constructor_unbound!(Decorator(DecoratorMessage:DisabledBrush) => fn disabled_brush(Brush), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -879,6 +1247,7 @@ pub enum UiMessageData<M: MessageData, C: Control<M, C>> {
     Widget(WidgetMessage<M, C>),
     Button(ButtonMessage<M, C>),
     ScrollBar(ScrollBarMessage),
+    RangeSlider(RangeSliderMessage),
     CheckBox(CheckBoxMessage),
     Window(WindowMessage<M, C>),
     ListView(ListViewMessage<M, C>),
@@ -895,6 +1264,7 @@ pub enum UiMessageData<M: MessageData, C: Control<M, C>> {
     Menu(MenuMessage),
     MenuItem(MenuItemMessage),
     MessageBox(MessageBoxMessage),
+    Border(BorderMessage),
     Decorator(DecoratorMessage),
     Text(TextMessage),
     ScrollPanel(ScrollPanelMessage<M, C>),
@@ -905,11 +1275,83 @@ pub enum UiMessageData<M: MessageData, C: Control<M, C>> {
     AlphaBar(AlphaBarMessage),
     ColorPicker(ColorPickerMessage),
     ColorField(ColorFieldMessage),
-    Expander(ExpanderMessage),
+    Expander(ExpanderMessage<M, C>),
     SaturationBrightnessField(SaturationBrightnessFieldMessage),
+    Chart(ChartMessage),
+    ConsoleView(ConsoleViewMessage),
+    DataGrid(DataGridMessage),
     User(M),
 }
 
+impl<M: MessageData, C: Control<M, C>> UiMessageData<M, C> {
+    /// Returns true if the message is a notification about new value of a widget (scroll
+    /// value, check state, selection, color, etc). Widgets that enable coalescing do not
+    /// repeat such notifications with the same value, see `Widget::set_event_coalescing`.
+    pub fn is_value_change(&self) -> bool {
+        self.is_same_value_change(self)
+    }
+
+    /// Returns true if both messages are value change notifications of the same kind with
+    /// the same value.
+    pub fn is_same_value_change(&self, other: &Self) -> bool {
+        use UiMessageData as D;
+        match (self, other) {
+            (
+                D::ScrollBar(ScrollBarMessage::Value(a)),
+                D::ScrollBar(ScrollBarMessage::Value(b)),
+            )
+            | (
+                D::NumericUpDown(NumericUpDownMessage::Value(a)),
+                D::NumericUpDown(NumericUpDownMessage::Value(b)),
+            )
+            | (
+                D::ProgressBar(ProgressBarMessage::Progress(a)),
+                D::ProgressBar(ProgressBarMessage::Progress(b)),
+            )
+            | (D::HueBar(HueBarMessage::Hue(a)), D::HueBar(HueBarMessage::Hue(b)))
+            | (D::AlphaBar(AlphaBarMessage::Alpha(a)), D::AlphaBar(AlphaBarMessage::Alpha(b))) => {
+                a == b
+            }
+            (
+                D::RangeSlider(RangeSliderMessage::Range { low: a, high: b }),
+                D::RangeSlider(RangeSliderMessage::Range { low: c, high: d }),
+            ) => a == c && b == d,
+            (D::CheckBox(CheckBoxMessage::Check(a)), D::CheckBox(CheckBoxMessage::Check(b))) => {
+                a == b
+            }
+            (
+                D::ListView(ListViewMessage::SelectionChanged(a)),
+                D::ListView(ListViewMessage::SelectionChanged(b)),
+            )
+            | (
+                D::DropdownList(DropdownListMessage::SelectionChanged(a)),
+                D::DropdownList(DropdownListMessage::SelectionChanged(b)),
+            ) => a == b,
+            (
+                D::TreeRoot(TreeRootMessage::Selected(a)),
+                D::TreeRoot(TreeRootMessage::Selected(b)),
+            ) => a == b,
+            (D::TextBox(TextBoxMessage::Text(a)), D::TextBox(TextBoxMessage::Text(b))) => a == b,
+            (
+                D::Vec3Editor(Vec3EditorMessage::Value(a)),
+                D::Vec3Editor(Vec3EditorMessage::Value(b)),
+            ) => a == b,
+            (
+                D::ColorPicker(ColorPickerMessage::Color(a)),
+                D::ColorPicker(ColorPickerMessage::Color(b)),
+            )
+            | (
+                D::ColorField(ColorFieldMessage::Color(a)),
+                D::ColorField(ColorFieldMessage::Color(b)),
+            ) => a == b,
+            (D::Expander(ExpanderMessage::Expand(a)), D::Expander(ExpanderMessage::Expand(b))) => {
+                a == b
+            }
+            _ => false,
+        }
+    }
+}
+
 /// Message direction allows you to distinguish from where message has came from.
 /// Often there is a need to find out who created a message to respond properly.
 /// Imagine that we have a NumericUpDown input field for a property and we using
@@ -1060,6 +1502,7 @@ pub enum MouseButton {
     Other(u16),
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum OsEvent {
     MouseInput {
         button: MouseButton,
@@ -1075,6 +1518,24 @@ pub enum OsEvent {
     Character(char),
     KeyboardModifiers(KeyboardModifiers),
     MouseWheel(f32, f32),
+    /// Touch screen event, see [touch](../touch/index.html) module docs for details.
+    Touch {
+        /// Unique identifier of a finger.
+        id: u64,
+        phase: TouchPhase,
+        /// Position in physical units, same as cursor position.
+        position: Vector2<f32>,
+    },
+    /// Window gained (true) or lost (false) focus of OS.
+    WindowFocused(bool),
+}
+
+#[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy)]
+pub enum TouchPhase {
+    Started,
+    Moved,
+    Ended,
+    Cancelled,
 }
 
 #[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy)]
@@ -1280,6 +1741,178 @@ pub enum KeyCode {
     Plus,
 }
 
+impl KeyCode {
+    /// Restores key code from its numeric id (`key as u32`).
+    pub fn from_id(id: u32) -> Option<Self> {
+        match id {
+            0 => Some(KeyCode::Key1),
+            1 => Some(KeyCode::Key2),
+            2 => Some(KeyCode::Key3),
+            3 => Some(KeyCode::Key4),
+            4 => Some(KeyCode::Key5),
+            5 => Some(KeyCode::Key6),
+            6 => Some(KeyCode::Key7),
+            7 => Some(KeyCode::Key8),
+            8 => Some(KeyCode::Key9),
+            9 => Some(KeyCode::Key0),
+            10 => Some(KeyCode::A),
+            11 => Some(KeyCode::B),
+            12 => Some(KeyCode::C),
+            13 => Some(KeyCode::D),
+            14 => Some(KeyCode::E),
+            15 => Some(KeyCode::F),
+            16 => Some(KeyCode::G),
+            17 => Some(KeyCode::H),
+            18 => Some(KeyCode::I),
+            19 => Some(KeyCode::J),
+            20 => Some(KeyCode::K),
+            21 => Some(KeyCode::L),
+            22 => Some(KeyCode::M),
+            23 => Some(KeyCode::N),
+            24 => Some(KeyCode::O),
+            25 => Some(KeyCode::P),
+            26 => Some(KeyCode::Q),
+            27 => Some(KeyCode::R),
+            28 => Some(KeyCode::S),
+            29 => Some(KeyCode::T),
+            30 => Some(KeyCode::U),
+            31 => Some(KeyCode::V),
+            32 => Some(KeyCode::W),
+            33 => Some(KeyCode::X),
+            34 => Some(KeyCode::Y),
+            35 => Some(KeyCode::Z),
+            36 => Some(KeyCode::Escape),
+            37 => Some(KeyCode::F1),
+            38 => Some(KeyCode::F2),
+            39 => Some(KeyCode::F3),
+            40 => Some(KeyCode::F4),
+            41 => Some(KeyCode::F5),
+            42 => Some(KeyCode::F6),
+            43 => Some(KeyCode::F7),
+            44 => Some(KeyCode::F8),
+            45 => Some(KeyCode::F9),
+            46 => Some(KeyCode::F10),
+            47 => Some(KeyCode::F11),
+            48 => Some(KeyCode::F12),
+            49 => Some(KeyCode::F13),
+            50 => Some(KeyCode::F14),
+            51 => Some(KeyCode::F15),
+            52 => Some(KeyCode::F16),
+            53 => Some(KeyCode::F17),
+            54 => Some(KeyCode::F18),
+            55 => Some(KeyCode::F19),
+            56 => Some(KeyCode::F20),
+            57 => Some(KeyCode::F21),
+            58 => Some(KeyCode::F22),
+            59 => Some(KeyCode::F23),
+            60 => Some(KeyCode::F24),
+            61 => Some(KeyCode::Snapshot),
+            62 => Some(KeyCode::Scroll),
+            63 => Some(KeyCode::Pause),
+            64 => Some(KeyCode::Insert),
+            65 => Some(KeyCode::Home),
+            66 => Some(KeyCode::Delete),
+            67 => Some(KeyCode::End),
+            68 => Some(KeyCode::PageDown),
+            69 => Some(KeyCode::PageUp),
+            70 => Some(KeyCode::Left),
+            71 => Some(KeyCode::Up),
+            72 => Some(KeyCode::Right),
+            73 => Some(KeyCode::Down),
+            74 => Some(KeyCode::Backspace),
+            75 => Some(KeyCode::Return),
+            76 => Some(KeyCode::Space),
+            77 => Some(KeyCode::Compose),
+            78 => Some(KeyCode::Caret),
+            79 => Some(KeyCode::Numlock),
+            80 => Some(KeyCode::Numpad0),
+            81 => Some(KeyCode::Numpad1),
+            82 => Some(KeyCode::Numpad2),
+            83 => Some(KeyCode::Numpad3),
+            84 => Some(KeyCode::Numpad4),
+            85 => Some(KeyCode::Numpad5),
+            86 => Some(KeyCode::Numpad6),
+            87 => Some(KeyCode::Numpad7),
+            88 => Some(KeyCode::Numpad8),
+            89 => Some(KeyCode::Numpad9),
+            90 => Some(KeyCode::AbntC1),
+            91 => Some(KeyCode::AbntC2),
+            92 => Some(KeyCode::NumpadAdd),
+            93 => Some(KeyCode::Apostrophe),
+            94 => Some(KeyCode::Apps),
+            95 => Some(KeyCode::At),
+            96 => Some(KeyCode::Ax),
+            97 => Some(KeyCode::Backslash),
+            98 => Some(KeyCode::Calculator),
+            99 => Some(KeyCode::Capital),
+            100 => Some(KeyCode::Colon),
+            101 => Some(KeyCode::Comma),
+            102 => Some(KeyCode::Convert),
+            103 => Some(KeyCode::NumpadDecimal),
+            104 => Some(KeyCode::NumpadDivide),
+            105 => Some(KeyCode::Equals),
+            106 => Some(KeyCode::Grave),
+            107 => Some(KeyCode::Kana),
+            108 => Some(KeyCode::Kanji),
+            109 => Some(KeyCode::LAlt),
+            110 => Some(KeyCode::LBracket),
+            111 => Some(KeyCode::LControl),
+            112 => Some(KeyCode::LShift),
+            113 => Some(KeyCode::LWin),
+            114 => Some(KeyCode::Mail),
+            115 => Some(KeyCode::MediaSelect),
+            116 => Some(KeyCode::MediaStop),
+            117 => Some(KeyCode::Minus),
+            118 => Some(KeyCode::NumpadMultiply),
+            119 => Some(KeyCode::Mute),
+            120 => Some(KeyCode::MyComputer),
+            121 => Some(KeyCode::NavigateForward),
+            122 => Some(KeyCode::NavigateBackward),
+            123 => Some(KeyCode::NextTrack),
+            124 => Some(KeyCode::NoConvert),
+            125 => Some(KeyCode::NumpadComma),
+            126 => Some(KeyCode::NumpadEnter),
+            127 => Some(KeyCode::NumpadEquals),
+            128 => Some(KeyCode::OEM102),
+            129 => Some(KeyCode::Period),
+            130 => Some(KeyCode::PlayPause),
+            131 => Some(KeyCode::Power),
+            132 => Some(KeyCode::PrevTrack),
+            133 => Some(KeyCode::RAlt),
+            134 => Some(KeyCode::RBracket),
+            135 => Some(KeyCode::RControl),
+            136 => Some(KeyCode::RShift),
+            137 => Some(KeyCode::RWin),
+            138 => Some(KeyCode::Semicolon),
+            139 => Some(KeyCode::Slash),
+            140 => Some(KeyCode::Sleep),
+            141 => Some(KeyCode::Stop),
+            142 => Some(KeyCode::NumpadSubtract),
+            143 => Some(KeyCode::Sysrq),
+            144 => Some(KeyCode::Tab),
+            145 => Some(KeyCode::Underline),
+            146 => Some(KeyCode::Unlabeled),
+            147 => Some(KeyCode::VolumeDown),
+            148 => Some(KeyCode::VolumeUp),
+            149 => Some(KeyCode::Wake),
+            150 => Some(KeyCode::WebBack),
+            151 => Some(KeyCode::WebFavorites),
+            152 => Some(KeyCode::WebForward),
+            153 => Some(KeyCode::WebHome),
+            154 => Some(KeyCode::WebRefresh),
+            155 => Some(KeyCode::WebSearch),
+            156 => Some(KeyCode::WebStop),
+            157 => Some(KeyCode::Yen),
+            158 => Some(KeyCode::Copy),
+            159 => Some(KeyCode::Paste),
+            160 => Some(KeyCode::Cut),
+            161 => Some(KeyCode::Asterisk),
+            162 => Some(KeyCode::Plus),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum CursorIcon {
     Default,
@@ -1,4 +1,5 @@
 use crate::{
+    accessibility::AccessibleRole,
     button::ButtonBuilder,
     core::{algebra::Vector2, math::Rect, pool::Handle},
     draw::DrawingContext,
@@ -59,6 +60,10 @@ impl<M: MessageData, C: Control<M, C>> DerefMut for MessageBox<M, C> {
 // Message box extends Window widget so it delegates most of calls
 // to inner window.
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for MessageBox<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Dialog
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         self.window.resolve(node_map);
         node_map.resolve(&mut self.ok_yes);
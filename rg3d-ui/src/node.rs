@@ -1,18 +1,25 @@
 use crate::{
+    accessibility::AccessibleRole,
     border::Border,
     button::Button,
     canvas::Canvas,
+    chart::Chart,
     check_box::CheckBox,
     color::{AlphaBar, ColorField, ColorPicker, HueBar, SaturationBrightnessField},
+    console::ConsoleView,
     core::{algebra::Vector2, define_is_as, math::Rect, pool::Handle},
+    data_grid::DataGrid,
     decorator::Decorator,
     dock::{DockingManager, Tile},
     draw::DrawingContext,
+    draw_surface::DrawSurface,
     dropdown_list::DropdownList,
     expander::Expander,
     file_browser::{FileBrowser, FileSelector},
+    flex_panel::FlexPanel,
     grid::Grid,
     image::Image,
+    items_control::ItemsControl,
     list_view::{ListView, ListViewItem},
     menu::{Menu, MenuItem},
     message::{MessageData, OsEvent, UiMessage},
@@ -20,6 +27,7 @@ use crate::{
     numeric::NumericUpDown,
     popup::Popup,
     progress_bar::ProgressBar,
+    range_slider::RangeSlider,
     scroll_bar::ScrollBar,
     scroll_panel::ScrollPanel,
     scroll_viewer::ScrollViewer,
@@ -78,6 +86,12 @@ pub enum UINode<M: MessageData, C: Control<M, C>> {
     WrapPanel(WrapPanel<M, C>),
     VectorImage(VectorImage<M, C>),
     Expander(Expander<M, C>),
+    DrawSurface(DrawSurface<M, C>),
+    Chart(Chart<M, C>),
+    ConsoleView(ConsoleView<M, C>),
+    DataGrid(DataGrid<M, C>),
+    RangeSlider(RangeSlider<M, C>),
+    FlexPanel(FlexPanel<M, C>),
     User(C),
 }
 
@@ -123,6 +137,12 @@ macro_rules! static_dispatch {
             UINode::WrapPanel(v) => v.$func($($args),*),
             UINode::VectorImage(v) => v.$func($($args),*),
             UINode::Expander(v) => v.$func($($args),*),
+            UINode::DrawSurface(v) => v.$func($($args),*),
+            UINode::Chart(v) => v.$func($($args),*),
+            UINode::ConsoleView(v) => v.$func($($args),*),
+            UINode::DataGrid(v) => v.$func($($args),*),
+            UINode::RangeSlider(v) => v.$func($($args),*),
+            UINode::FlexPanel(v) => v.$func($($args),*),
             UINode::User(v) => v.$func($($args),*),
         }
     };
@@ -180,7 +200,75 @@ impl<M: MessageData, C: Control<M, C>> UINode<M, C> {
     define_is_as!(UINode : WrapPanel -> ref WrapPanel<M, C> => fn is_wrap_panel, fn as_wrap_panel, fn as_wrap_panel_mut);
     define_is_as!(UINode : VectorImage -> ref VectorImage<M, C> => fn is_vector_image, fn as_vector_image, fn as_vector_image_mut);
     define_is_as!(UINode : Expander -> ref Expander<M, C> => fn is_expander, fn as_expander, fn as_expander_mut);
+    define_is_as!(UINode : DrawSurface -> ref DrawSurface<M, C> => fn is_draw_surface, fn as_draw_surface, fn as_draw_surface_mut);
+    define_is_as!(UINode : Chart -> ref Chart<M, C> => fn is_chart, fn as_chart, fn as_chart_mut);
+    define_is_as!(UINode : ConsoleView -> ref ConsoleView<M, C> => fn is_console_view, fn as_console_view, fn as_console_view_mut);
+    define_is_as!(UINode : DataGrid -> ref DataGrid<M, C> => fn is_data_grid, fn as_data_grid, fn as_data_grid_mut);
+    define_is_as!(UINode : RangeSlider -> ref RangeSlider<M, C> => fn is_range_slider, fn as_range_slider, fn as_range_slider_mut);
+    define_is_as!(UINode : FlexPanel -> ref FlexPanel<M, C> => fn is_flex_panel, fn as_flex_panel, fn as_flex_panel_mut);
     define_is_as!(UINode : User -> ref C => fn is_user, fn as_user, fn as_user_mut);
+
+    /// Returns items control of list-like widgets that can show a placeholder.
+    pub(in crate) fn items_control(&self) -> Option<&ItemsControl<M, C>> {
+        match self {
+            UINode::ListView(list_view) => Some(list_view.items_control()),
+            UINode::TreeRoot(tree_root) => Some(tree_root.items_control()),
+            _ => None,
+        }
+    }
+
+    /// Returns name of the kind of the node (`"Button"`, `"Text"`, etc), user nodes are
+    /// reported as `"User"`. Useful for debugging.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            UINode::Border(_) => "Border",
+            UINode::Button(_) => "Button",
+            UINode::Canvas(_) => "Canvas",
+            UINode::ColorPicker(_) => "ColorPicker",
+            UINode::ColorField(_) => "ColorField",
+            UINode::HueBar(_) => "HueBar",
+            UINode::AlphaBar(_) => "AlphaBar",
+            UINode::SaturationBrightnessField(_) => "SaturationBrightnessField",
+            UINode::CheckBox(_) => "CheckBox",
+            UINode::Grid(_) => "Grid",
+            UINode::Image(_) => "Image",
+            UINode::ScrollBar(_) => "ScrollBar",
+            UINode::ScrollPanel(_) => "ScrollPanel",
+            UINode::ScrollViewer(_) => "ScrollViewer",
+            UINode::StackPanel(_) => "StackPanel",
+            UINode::TabControl(_) => "TabControl",
+            UINode::Text(_) => "Text",
+            UINode::TextBox(_) => "TextBox",
+            UINode::Window(_) => "Window",
+            UINode::Popup(_) => "Popup",
+            UINode::DropdownList(_) => "DropdownList",
+            UINode::ListView(_) => "ListView",
+            UINode::ListViewItem(_) => "ListViewItem",
+            UINode::ProgressBar(_) => "ProgressBar",
+            UINode::Decorator(_) => "Decorator",
+            UINode::Tree(_) => "Tree",
+            UINode::TreeRoot(_) => "TreeRoot",
+            UINode::FileBrowser(_) => "FileBrowser",
+            UINode::FileSelector(_) => "FileSelector",
+            UINode::DockingManager(_) => "DockingManager",
+            UINode::Tile(_) => "Tile",
+            UINode::Vec3Editor(_) => "Vec3Editor",
+            UINode::NumericUpDown(_) => "NumericUpDown",
+            UINode::Menu(_) => "Menu",
+            UINode::MenuItem(_) => "MenuItem",
+            UINode::MessageBox(_) => "MessageBox",
+            UINode::WrapPanel(_) => "WrapPanel",
+            UINode::VectorImage(_) => "VectorImage",
+            UINode::Expander(_) => "Expander",
+            UINode::DrawSurface(_) => "DrawSurface",
+            UINode::Chart(_) => "Chart",
+            UINode::ConsoleView(_) => "ConsoleView",
+            UINode::DataGrid(_) => "DataGrid",
+            UINode::RangeSlider(_) => "RangeSlider",
+            UINode::FlexPanel(_) => "FlexPanel",
+            UINode::User(_) => "User",
+        }
+    }
 }
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for UINode<M, C> {
@@ -212,6 +300,10 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for UINode<M, C> {
         static_dispatch!(self, draw, drawing_context)
     }
 
+    fn draw_overlay(&self, drawing_context: &mut DrawingContext) {
+        static_dispatch!(self, draw_overlay, drawing_context)
+    }
+
     fn update(&mut self, dt: f32) {
         static_dispatch!(self, update, dt)
     }
@@ -240,6 +332,22 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for UINode<M, C> {
     fn remove_ref(&mut self, handle: Handle<UINode<M, C>>) {
         static_dispatch!(self, remove_ref, handle)
     }
+
+    fn baseline_offset(&self, ui: &UserInterface<M, C>) -> Option<f32> {
+        static_dispatch!(self, baseline_offset, ui)
+    }
+
+    fn accessible_role(&self) -> AccessibleRole {
+        static_dispatch!(self, accessible_role,)
+    }
+
+    fn accessible_name(&self) -> Option<String> {
+        static_dispatch!(self, accessible_name,)
+    }
+
+    fn accessible_value(&self) -> Option<String> {
+        static_dispatch!(self, accessible_value,)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -4,11 +4,12 @@ use crate::core::color::Color;
 use crate::decorator::DecoratorBuilder;
 use crate::utils::{make_arrow, ArrowDirection};
 use crate::{
+    accessibility::AccessibleRole,
     button::ButtonBuilder,
     core::pool::Handle,
     grid::{Column, GridBuilder, Row},
     message::{
-        ButtonMessage, KeyCode, MessageData, MessageDirection, NumericUpDownMessage,
+        ButtonMessage, KeyCode, MessageData, MessageDirection, MouseButton, NumericUpDownMessage,
         TextBoxMessage, UiMessage, UiMessageData, WidgetMessage,
     },
     node::UINode,
@@ -19,6 +20,19 @@ use crate::{
 };
 use std::ops::{Deref, DerefMut};
 
+/// Multiplier of drag sensitivity when Shift or Control is held.
+const FINE_DRAG_FACTOR: f32 = 0.1;
+/// Maximum time between two clicks of a double click.
+const DOUBLE_CLICK_TIME: f32 = 0.5;
+/// Press and release are treated as a click if cursor moved less than this distance.
+const CLICK_DISTANCE: f32 = 2.0;
+
+#[derive(Copy, Clone, Debug)]
+struct DragState {
+    last_x: f32,
+    distance: f32,
+}
+
 #[derive(Clone)]
 pub struct NumericUpDown<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
@@ -30,6 +44,14 @@ pub struct NumericUpDown<M: MessageData, C: Control<M, C>> {
     min_value: f32,
     max_value: f32,
     precision: usize,
+    /// Change of value per unit of horizontal cursor movement over the field, `None` if
+    /// the value can't be changed by dragging.
+    drag_sensitivity: Option<f32>,
+    drag: Option<DragState>,
+    /// Field accepts text input (after double click), only used when dragging is enabled.
+    editing: bool,
+    /// Time left to make second click of a double click.
+    click_timer: f32,
 }
 
 crate::define_widget_deref!(NumericUpDown<M, C>);
@@ -48,9 +70,97 @@ impl<M: MessageData, C: Control<M, C>> NumericUpDown<M, C> {
             }
         }
     }
+
+    pub fn set_drag_sensitivity(&mut self, sensitivity: Option<f32>) {
+        self.drag_sensitivity = sensitivity;
+    }
+
+    pub fn drag_sensitivity(&self) -> Option<f32> {
+        self.drag_sensitivity
+    }
+
+    fn set_editing(&mut self, ui: &mut UserInterface<M, C>, editing: bool) {
+        self.editing = editing;
+        ui.send_message(WidgetMessage::hit_test_visibility(
+            self.field,
+            MessageDirection::ToWidget,
+            editing,
+        ));
+        if editing {
+            ui.set_keyboard_focus_node(self.field);
+        }
+    }
+
+    fn handle_drag(&mut self, ui: &mut UserInterface<M, C>, message: &mut UiMessage<M, C>) {
+        let sensitivity = match self.drag_sensitivity {
+            Some(sensitivity) if !self.editing => sensitivity,
+            _ => return,
+        };
+
+        if let UiMessageData::Widget(msg) = &message.data() {
+            match *msg {
+                WidgetMessage::MouseDown {
+                    pos,
+                    button: MouseButton::Left,
+                } if ui.node(self.field).screen_bounds().contains(pos) => {
+                    ui.capture_mouse(self.handle());
+                    self.drag = Some(DragState {
+                        last_x: pos.x,
+                        distance: 0.0,
+                    });
+                    message.set_handled(true);
+                }
+                WidgetMessage::MouseMove { pos, .. } => {
+                    if let Some(drag) = self.drag.as_mut() {
+                        let dx = pos.x - drag.last_x;
+                        drag.last_x = pos.x;
+                        drag.distance += dx.abs();
+                        let modifiers = ui.keyboard_modifiers();
+                        let factor = if modifiers.shift || modifiers.control {
+                            FINE_DRAG_FACTOR
+                        } else {
+                            1.0
+                        };
+                        if dx != 0.0 {
+                            ui.send_message(NumericUpDownMessage::value(
+                                self.handle(),
+                                MessageDirection::ToWidget,
+                                self.value + sensitivity * factor * dx,
+                            ));
+                        }
+                    }
+                }
+                WidgetMessage::MouseUp {
+                    button: MouseButton::Left,
+                    ..
+                } => {
+                    if let Some(drag) = self.drag.take() {
+                        ui.release_mouse_capture();
+                        if drag.distance < CLICK_DISTANCE {
+                            if self.click_timer > 0.0 {
+                                self.click_timer = 0.0;
+                                self.set_editing(ui, true);
+                            } else {
+                                self.click_timer = DOUBLE_CLICK_TIME;
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
 }
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for NumericUpDown<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::SpinButton
+    }
+
+    fn accessible_value(&self) -> Option<String> {
+        Some(format!("{:.1$}", self.value, self.precision))
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         node_map.resolve(&mut self.field);
         node_map.resolve(&mut self.increase);
@@ -63,16 +173,17 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for NumericUpDown<M, C> {
         message: &mut UiMessage<M, C>,
     ) {
         self.widget.handle_routed_message(ui, message);
+        self.handle_drag(ui, message);
 
         match &message.data() {
             UiMessageData::Widget(msg) => {
                 if message.destination() == self.field {
                     match msg {
-                        WidgetMessage::LostFocus => {
-                            self.try_parse_value(ui);
-                        }
-                        WidgetMessage::KeyDown(KeyCode::Return) => {
+                        WidgetMessage::LostFocus | WidgetMessage::KeyDown(KeyCode::Return) => {
                             self.try_parse_value(ui);
+                            if self.editing {
+                                self.set_editing(ui, false);
+                            }
                         }
                         _ => {}
                     }
@@ -128,6 +239,29 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for NumericUpDown<M, C> {
             _ => {}
         }
     }
+
+    fn update(&mut self, dt: f32) {
+        self.click_timer = (self.click_timer - dt).max(0.0);
+    }
+
+    fn preview_message(&self, ui: &UserInterface<M, C>, message: &mut UiMessage<M, C>) {
+        if let UiMessageData::TextBox(TextBoxMessage::Paste(text)) = &message.data() {
+            if message.destination() == self.field
+                && message.direction() == MessageDirection::ToWidget
+            {
+                // Pasted text must be a valid number, otherwise it is ignored. Text field
+                // will be synced with new value.
+                if let Ok(value) = text.trim().parse::<f32>() {
+                    ui.send_message(NumericUpDownMessage::value(
+                        self.handle(),
+                        MessageDirection::ToWidget,
+                        value,
+                    ));
+                }
+                message.set_handled(true);
+            }
+        }
+    }
 }
 
 pub struct NumericUpDownBuilder<M: MessageData, C: Control<M, C>> {
@@ -137,6 +271,7 @@ pub struct NumericUpDownBuilder<M: MessageData, C: Control<M, C>> {
     min_value: f32,
     max_value: f32,
     precision: usize,
+    drag_sensitivity: Option<f32>,
 }
 
 pub fn make_button<M: MessageData, C: Control<M, C>>(
@@ -171,6 +306,7 @@ impl<M: MessageData, C: Control<M, C>> NumericUpDownBuilder<M, C> {
             min_value: -std::f32::MAX,
             max_value: std::f32::MAX,
             precision: 3,
+            drag_sensitivity: None,
         }
     }
 
@@ -206,6 +342,15 @@ impl<M: MessageData, C: Control<M, C>> NumericUpDownBuilder<M, C> {
         self
     }
 
+    /// Allows to change the value by dragging cursor left or right over the text field, value
+    /// changes by `sensitivity` per unit of cursor movement (ten times slower when Shift or
+    /// Control is held). Double click on the field switches it to text input until it loses
+    /// focus or Enter is pressed.
+    pub fn with_drag_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.drag_sensitivity = Some(sensitivity);
+        self
+    }
+
     pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
         let increase;
         let decrease;
@@ -221,12 +366,17 @@ impl<M: MessageData, C: Control<M, C>> NumericUpDownBuilder<M, C> {
         let grid = GridBuilder::new(
             WidgetBuilder::new()
                 .with_child({
-                    field = TextBoxBuilder::new(WidgetBuilder::new().on_row(0).on_column(0))
-                        .with_vertical_text_alignment(VerticalAlignment::Center)
-                        .with_horizontal_text_alignment(HorizontalAlignment::Left)
-                        .with_wrap(true)
-                        .with_text(self.value.to_string())
-                        .build(ctx);
+                    field = TextBoxBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(0)
+                            .on_column(0)
+                            .with_hit_test_visibility(self.drag_sensitivity.is_none()),
+                    )
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .with_horizontal_text_alignment(HorizontalAlignment::Left)
+                    .with_wrap(true)
+                    .with_text(self.value.to_string())
+                    .build(ctx);
                     field
                 })
                 .with_child(
@@ -256,7 +406,12 @@ impl<M: MessageData, C: Control<M, C>> NumericUpDownBuilder<M, C> {
         ctx.link(grid, back);
 
         let node = NumericUpDown {
-            widget: self.widget_builder.with_child(back).build(),
+            // Mouse is captured only while a value is dragged.
+            widget: self
+                .widget_builder
+                .with_hides_cursor(true)
+                .with_child(back)
+                .build(),
             increase,
             decrease,
             field,
@@ -265,6 +420,10 @@ impl<M: MessageData, C: Control<M, C>> NumericUpDownBuilder<M, C> {
             min_value: self.min_value,
             max_value: self.max_value,
             precision: self.precision,
+            drag_sensitivity: self.drag_sensitivity,
+            drag: None,
+            editing: false,
+            click_timer: 0.0,
         };
 
         ctx.add_node(UINode::NumericUpDown(node))
@@ -3,7 +3,8 @@ use crate::message::{MessageData, MessageDirection};
 use crate::{
     border::BorderBuilder,
     core::pool::Handle,
-    message::{ButtonState, OsEvent, PopupMessage, UiMessage, UiMessageData, WidgetMessage},
+    draw::{Backdrop, DrawingContext},
+    message::{PopupMessage, UiMessage, UiMessageData, WidgetMessage},
     node::UINode,
     widget::{Widget, WidgetBuilder},
     BuildContext, Control, NodeHandleMapping, RestrictionEntry, Thickness, UserInterface,
@@ -26,20 +27,32 @@ pub enum Placement {
 pub struct Popup<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     placement: Placement,
+    /// Popup is not closed by popup manager of the UI, see `UserInterface::open_popup`.
     stays_open: bool,
     is_open: bool,
     content: Handle<UINode<M, C>>,
     body: Handle<UINode<M, C>>,
+    backdrop: Backdrop,
 }
 
 crate::define_widget_deref!(Popup<M, C>);
 
+impl<M: MessageData, C: Control<M, C>> Popup<M, C> {
+    pub fn backdrop(&self) -> Backdrop {
+        self.backdrop
+    }
+}
+
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for Popup<M, C> {
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         node_map.resolve(&mut self.content);
         node_map.resolve(&mut self.body);
     }
 
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        drawing_context.draw_backdrop(self.backdrop, &self.screen_bounds(), self.clip_bounds());
+    }
+
     fn handle_routed_message(
         &mut self,
         ui: &mut UserInterface<M, C>,
@@ -61,6 +74,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Popup<M, C> {
                             handle: self.handle(),
                             stop: false,
                         });
+                        ui.register_popup(self.handle(), !self.stays_open);
                         ui.send_message(WidgetMessage::topmost(
                             self.handle(),
                             MessageDirection::ToWidget,
@@ -106,6 +120,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Popup<M, C> {
                             false,
                         ));
                         ui.remove_picking_restriction(self.handle());
+                        ui.unregister_popup(self.handle());
                         if ui.captured_node() == self.handle() {
                             ui.release_mouse_capture();
                         }
@@ -130,34 +145,13 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Popup<M, C> {
                     self.placement = placement;
                     self.invalidate_layout();
                 }
+                &PopupMessage::Backdrop(backdrop) => {
+                    self.backdrop = backdrop;
+                }
             },
             _ => {}
         }
     }
-
-    fn handle_os_event(
-        &mut self,
-        self_handle: Handle<UINode<M, C>>,
-        ui: &mut UserInterface<M, C>,
-        event: &OsEvent,
-    ) {
-        if let OsEvent::MouseInput { state, .. } = event {
-            if let Some(top_restriction) = ui.top_picking_restriction() {
-                if *state == ButtonState::Pressed
-                    && top_restriction.handle == self_handle
-                    && self.is_open
-                {
-                    let pos = ui.cursor_position();
-                    if !self.widget.screen_bounds().contains(pos) && !self.stays_open {
-                        ui.send_message(PopupMessage::close(
-                            self.handle(),
-                            MessageDirection::ToWidget,
-                        ));
-                    }
-                }
-            }
-        }
-    }
 }
 
 pub struct PopupBuilder<M: MessageData, C: Control<M, C>> {
@@ -165,6 +159,7 @@ pub struct PopupBuilder<M: MessageData, C: Control<M, C>> {
     placement: Placement,
     stays_open: bool,
     content: Handle<UINode<M, C>>,
+    backdrop: Backdrop,
 }
 
 impl<M: MessageData, C: Control<M, C>> PopupBuilder<M, C> {
@@ -174,6 +169,7 @@ impl<M: MessageData, C: Control<M, C>> PopupBuilder<M, C> {
             placement: Placement::Cursor,
             stays_open: false,
             content: Default::default(),
+            backdrop: Backdrop::None,
         }
     }
 
@@ -192,6 +188,13 @@ impl<M: MessageData, C: Control<M, C>> PopupBuilder<M, C> {
         self
     }
 
+    /// Sets backdrop that is drawn behind the popup, body of the popup should have
+    /// translucent background to make the backdrop visible.
+    pub fn with_backdrop(mut self, backdrop: Backdrop) -> Self {
+        self.backdrop = backdrop;
+        self
+    }
+
     pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
         let body = BorderBuilder::new(
             WidgetBuilder::new()
@@ -213,6 +216,7 @@ impl<M: MessageData, C: Control<M, C>> PopupBuilder<M, C> {
             is_open: false,
             content: self.content,
             body,
+            backdrop: self.backdrop,
         };
 
         ctx.add_node(UINode::Popup(popup))
@@ -1,4 +1,5 @@
 use crate::{
+    accessibility::AccessibleRole,
     border::BorderBuilder,
     brush::Brush,
     canvas::CanvasBuilder,
@@ -23,6 +24,14 @@ pub struct ProgressBar<M: MessageData, C: Control<M, C>> {
 crate::define_widget_deref!(ProgressBar<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for ProgressBar<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::ProgressBar
+    }
+
+    fn accessible_value(&self) -> Option<String> {
+        Some(format!("{:.0}%", self.progress * 100.0))
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         node_map.resolve(&mut self.indicator);
         node_map.resolve(&mut self.body);
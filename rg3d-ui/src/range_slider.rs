@@ -0,0 +1,689 @@
+//! Slider with two thumbs that select a range of values (for example for min/max filters).
+//! Thumbs can't cross each other, so `low` is always less or equal to `high`.
+
+use crate::{
+    accessibility::AccessibleRole,
+    border::BorderBuilder,
+    brush::Brush,
+    canvas::CanvasBuilder,
+    core::{
+        algebra::Vector2,
+        color::Color,
+        math::{self},
+        pool::Handle,
+    },
+    decorator::DecoratorBuilder,
+    message::{
+        KeyCode, MessageData, MessageDirection, MouseButton, RangeSliderMessage, UiMessage,
+        UiMessageData, WidgetMessage,
+    },
+    widget::{Widget, WidgetBuilder},
+    BuildContext, Control, NodeHandleMapping, Orientation, Thickness, UINode, UserInterface,
+    BRUSH_BRIGHT_BLUE, BRUSH_LIGHT, BRUSH_LIGHTER, BRUSH_LIGHTEST,
+};
+use std::ops::{Deref, DerefMut};
+
+/// Length of default thumbs along the slider.
+const THUMB_LENGTH: f32 = 12.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RangeThumb {
+    Low,
+    High,
+}
+
+#[derive(Clone)]
+pub struct RangeSlider<M: MessageData, C: Control<M, C>> {
+    widget: Widget<M, C>,
+    min: f32,
+    max: f32,
+    low: f32,
+    high: f32,
+    /// Values are snapped to multiples of the step (counting from `min`), zero disables
+    /// snapping.
+    step: f32,
+    orientation: Orientation,
+    low_thumb: Handle<UINode<M, C>>,
+    high_thumb: Handle<UINode<M, C>>,
+    /// Highlighted part of the track between thumbs.
+    selection: Handle<UINode<M, C>>,
+    field: Handle<UINode<M, C>>,
+    /// Thumb that is being dragged.
+    dragged: Option<RangeThumb>,
+    /// Thumbs were on top of each other when the drag started, so dragged thumb is chosen by
+    /// direction of the first move: only the low thumb can go down and only the high one up.
+    overlapped: bool,
+    offset: Vector2<f32>,
+}
+
+crate::define_widget_deref!(RangeSlider<M, C>);
+
+impl<M: MessageData, C: Control<M, C>> Control<M, C> for RangeSlider<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Slider
+    }
+
+    fn accessible_value(&self) -> Option<String> {
+        Some(format!("{}..{}", self.low, self.high))
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
+        node_map.resolve(&mut self.low_thumb);
+        node_map.resolve(&mut self.high_thumb);
+        node_map.resolve(&mut self.selection);
+        node_map.resolve(&mut self.field);
+    }
+
+    fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
+        let size = self.widget.arrange_override(ui, final_size);
+
+        if self.field.is_none() {
+            return size;
+        }
+
+        let field_size = ui.node(self.field).actual_size();
+        let (field_length, field_thickness) = match self.orientation {
+            Orientation::Horizontal => (field_size.x, field_size.y),
+            Orientation::Vertical => (field_size.y, field_size.x),
+        };
+        let along = |length: f32, thickness: f32| match self.orientation {
+            Orientation::Horizontal => Vector2::new(length, thickness),
+            Orientation::Vertical => Vector2::new(thickness, length),
+        };
+
+        let mut centers = [0.0; 2];
+        for (center, (thumb, value)) in centers.iter_mut().zip(
+            [(self.low_thumb, self.low), (self.high_thumb, self.high)]
+                .iter()
+                .cloned(),
+        ) {
+            if thumb.is_none() {
+                continue;
+            }
+            let thumb_size = ui.node(thumb).actual_size();
+            let thumb_length = match self.orientation {
+                Orientation::Horizontal => thumb_size.x,
+                Orientation::Vertical => thumb_size.y,
+            };
+            let position = self.percent(value) * (field_length - thumb_length).max(0.0);
+            *center = position + thumb_length * 0.5;
+            ui.send_message(WidgetMessage::desired_position(
+                thumb,
+                MessageDirection::ToWidget,
+                along(position, 0.0),
+            ));
+            let (width, height) = match self.orientation {
+                Orientation::Horizontal => (thumb_size.x, field_thickness),
+                Orientation::Vertical => (field_thickness, thumb_size.y),
+            };
+            ui.send_message(WidgetMessage::width(
+                thumb,
+                MessageDirection::ToWidget,
+                width,
+            ));
+            ui.send_message(WidgetMessage::height(
+                thumb,
+                MessageDirection::ToWidget,
+                height,
+            ));
+        }
+
+        if self.selection.is_some() {
+            let selection_size = along(centers[1] - centers[0], field_thickness);
+            ui.send_message(WidgetMessage::desired_position(
+                self.selection,
+                MessageDirection::ToWidget,
+                along(centers[0], 0.0),
+            ));
+            ui.send_message(WidgetMessage::width(
+                self.selection,
+                MessageDirection::ToWidget,
+                selection_size.x,
+            ));
+            ui.send_message(WidgetMessage::height(
+                self.selection,
+                MessageDirection::ToWidget,
+                selection_size.y,
+            ));
+        }
+
+        size
+    }
+
+    fn handle_routed_message(
+        &mut self,
+        ui: &mut UserInterface<M, C>,
+        message: &mut UiMessage<M, C>,
+    ) {
+        self.widget.handle_routed_message(ui, message);
+
+        match &message.data() {
+            UiMessageData::RangeSlider(msg)
+                if message.destination() == self.handle()
+                    && message.direction() == MessageDirection::ToWidget =>
+            {
+                match *msg {
+                    RangeSliderMessage::Range { low, high } => {
+                        self.set_range(ui, low, high, message.handled());
+                    }
+                    RangeSliderMessage::MinValue(min) => {
+                        if self.min != min {
+                            self.min = min;
+                            if self.min > self.max {
+                                std::mem::swap(&mut self.min, &mut self.max);
+                            }
+                            self.set_range(ui, self.low, self.high, message.handled());
+                            self.invalidate_layout();
+                        }
+                    }
+                    RangeSliderMessage::MaxValue(max) => {
+                        if self.max != max {
+                            self.max = max;
+                            if self.max < self.min {
+                                std::mem::swap(&mut self.min, &mut self.max);
+                            }
+                            self.set_range(ui, self.low, self.high, message.handled());
+                            self.invalidate_layout();
+                        }
+                    }
+                    RangeSliderMessage::Step(step) => {
+                        self.step = step.max(0.0);
+                        self.set_range(ui, self.low, self.high, message.handled());
+                    }
+                }
+            }
+            UiMessageData::Widget(WidgetMessage::KeyDown(code))
+                if !message.handled() && self.thumb_of(message.destination()).is_some() =>
+            {
+                let delta = if self.step > 0.0 {
+                    self.step
+                } else {
+                    (self.max - self.min) * 0.01
+                };
+                let thumb = self.thumb_of(message.destination()).unwrap();
+                let value = self.thumb_value(thumb);
+                let new_value = match code {
+                    KeyCode::Left | KeyCode::Down => value - delta,
+                    KeyCode::Right | KeyCode::Up => value + delta,
+                    KeyCode::Home => self.min,
+                    KeyCode::End => self.max,
+                    _ => return,
+                };
+                self.send_thumb_value(ui, thumb, new_value);
+                message.set_handled(true);
+            }
+            UiMessageData::Widget(msg) => {
+                if let Some(thumb) = self.thumb_of(message.destination()) {
+                    match *msg {
+                        WidgetMessage::MouseDown {
+                            pos,
+                            button: MouseButton::Left,
+                        } => {
+                            let thumb_pos = ui.node(message.destination()).screen_position();
+                            self.dragged = Some(thumb);
+                            self.overlapped = self.low == self.high;
+                            self.offset = thumb_pos - pos;
+                            ui.capture_mouse(message.destination());
+                            message.set_handled(true);
+                        }
+                        WidgetMessage::MouseUp { .. } if self.dragged.is_some() => {
+                            self.dragged = None;
+                            self.overlapped = false;
+                            ui.release_mouse_capture();
+                            message.set_handled(true);
+                        }
+                        WidgetMessage::MouseCaptureLost => {
+                            self.dragged = None;
+                            self.overlapped = false;
+                        }
+                        WidgetMessage::MouseMove { pos, .. } if self.dragged.is_some() => {
+                            let field = ui.node(self.field);
+                            let thumb_size = ui.node(message.destination()).actual_size();
+                            let (span, offset) = match self.orientation {
+                                Orientation::Horizontal => (
+                                    field.actual_size().x - thumb_size.x,
+                                    pos.x - field.screen_position().x + self.offset.x,
+                                ),
+                                Orientation::Vertical => (
+                                    field.actual_size().y - thumb_size.y,
+                                    pos.y - field.screen_position().y + self.offset.y,
+                                ),
+                            };
+                            let percent = if span > 0.0 {
+                                math::clampf(offset / span, 0.0, 1.0)
+                            } else {
+                                0.0
+                            };
+                            let value = self.min + percent * (self.max - self.min);
+                            // Captured thumb could be replaced by the other one, see `overlapped`.
+                            let mut dragged = self.dragged.unwrap_or(thumb);
+                            if self.overlapped {
+                                if value < self.low {
+                                    dragged = RangeThumb::Low;
+                                } else if value > self.high {
+                                    dragged = RangeThumb::High;
+                                }
+                                self.overlapped = value == self.low;
+                                self.dragged = Some(dragged);
+                            }
+                            self.send_thumb_value(ui, dragged, value);
+                            message.set_handled(true);
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn remove_ref(&mut self, handle: Handle<UINode<M, C>>) {
+        if self.low_thumb == handle {
+            self.low_thumb = Handle::NONE;
+        }
+        if self.high_thumb == handle {
+            self.high_thumb = Handle::NONE;
+        }
+        if self.selection == handle {
+            self.selection = Handle::NONE;
+        }
+        if self.field == handle {
+            self.field = Handle::NONE;
+        }
+    }
+}
+
+impl<M: MessageData, C: Control<M, C>> RangeSlider<M, C> {
+    pub const PART_CANVAS: &'static str = "PART_Canvas";
+
+    pub fn low(&self) -> f32 {
+        self.low
+    }
+
+    pub fn high(&self) -> f32 {
+        self.high
+    }
+
+    pub fn min_value(&self) -> f32 {
+        self.min
+    }
+
+    pub fn max_value(&self) -> f32 {
+        self.max
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn low_thumb(&self) -> Handle<UINode<M, C>> {
+        self.low_thumb
+    }
+
+    pub fn high_thumb(&self) -> Handle<UINode<M, C>> {
+        self.high_thumb
+    }
+
+    /// Returns thumb which is dragged at the moment.
+    pub fn dragged_thumb(&self) -> Option<RangeThumb> {
+        self.dragged
+    }
+
+    fn thumb_of(&self, handle: Handle<UINode<M, C>>) -> Option<RangeThumb> {
+        if handle.is_none() {
+            None
+        } else if handle == self.low_thumb {
+            Some(RangeThumb::Low)
+        } else if handle == self.high_thumb {
+            Some(RangeThumb::High)
+        } else {
+            None
+        }
+    }
+
+    fn thumb_value(&self, thumb: RangeThumb) -> f32 {
+        match thumb {
+            RangeThumb::Low => self.low,
+            RangeThumb::High => self.high,
+        }
+    }
+
+    fn percent(&self, value: f32) -> f32 {
+        let range = self.max - self.min;
+        if range > 0.0 {
+            (value - self.min) / range
+        } else {
+            0.0
+        }
+    }
+
+    fn snap(&self, value: f32) -> f32 {
+        let value = if self.step > 0.0 {
+            self.min + ((value - self.min) / self.step).round() * self.step
+        } else {
+            value
+        };
+        math::clampf(value, self.min, self.max)
+    }
+
+    fn send_thumb_value(&self, ui: &UserInterface<M, C>, thumb: RangeThumb, value: f32) {
+        let (low, high) = match thumb {
+            RangeThumb::Low => (value, self.high),
+            RangeThumb::High => (self.low, value),
+        };
+        ui.send_message(RangeSliderMessage::range(
+            self.handle(),
+            MessageDirection::ToWidget,
+            low,
+            high,
+        ));
+    }
+
+    /// Clamps and snaps new values and sends response if the range has changed. A thumb that
+    /// was moved over the other one stops at the value of the other thumb.
+    fn set_range(&mut self, ui: &UserInterface<M, C>, low: f32, high: f32, handled: bool) {
+        let mut low = self.snap(low);
+        let mut high = self.snap(high);
+        if low > high {
+            if low != self.low {
+                low = high;
+            } else {
+                high = low;
+            }
+        }
+
+        if low != self.low || high != self.high {
+            self.low = low;
+            self.high = high;
+            self.invalidate_layout();
+
+            let response = RangeSliderMessage::range(
+                self.handle,
+                MessageDirection::FromWidget,
+                self.low,
+                self.high,
+            );
+            response.set_handled(handled);
+            ui.send_message(response);
+        }
+    }
+}
+
+pub struct RangeSliderBuilder<M: MessageData, C: Control<M, C>> {
+    widget_builder: WidgetBuilder<M, C>,
+    min: f32,
+    max: f32,
+    low: f32,
+    high: f32,
+    step: f32,
+    orientation: Orientation,
+    low_thumb: Option<Handle<UINode<M, C>>>,
+    high_thumb: Option<Handle<UINode<M, C>>>,
+    track_brush: Brush,
+    selection_brush: Brush,
+}
+
+impl<M: MessageData, C: Control<M, C>> RangeSliderBuilder<M, C> {
+    pub fn new(widget_builder: WidgetBuilder<M, C>) -> Self {
+        Self {
+            widget_builder,
+            min: 0.0,
+            max: 100.0,
+            low: 0.0,
+            high: 100.0,
+            step: 0.0,
+            orientation: Orientation::Horizontal,
+            low_thumb: None,
+            high_thumb: None,
+            track_brush: Brush::Solid(Color::opaque(60, 60, 60)),
+            selection_brush: BRUSH_BRIGHT_BLUE,
+        }
+    }
+
+    pub fn with_min(mut self, min: f32) -> Self {
+        self.min = min;
+        self
+    }
+
+    pub fn with_max(mut self, max: f32) -> Self {
+        self.max = max;
+        self
+    }
+
+    pub fn with_range(mut self, low: f32, high: f32) -> Self {
+        self.low = low;
+        self.high = high;
+        self
+    }
+
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn with_low_thumb(mut self, thumb: Handle<UINode<M, C>>) -> Self {
+        self.low_thumb = Some(thumb);
+        self
+    }
+
+    pub fn with_high_thumb(mut self, thumb: Handle<UINode<M, C>>) -> Self {
+        self.high_thumb = Some(thumb);
+        self
+    }
+
+    pub fn with_track_brush(mut self, brush: Brush) -> Self {
+        self.track_brush = brush;
+        self
+    }
+
+    pub fn with_selection_brush(mut self, brush: Brush) -> Self {
+        self.selection_brush = brush;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        let orientation = self.orientation;
+        let mut make_thumb = |thumb: Option<Handle<UINode<M, C>>>| {
+            let thumb = thumb.unwrap_or_else(|| {
+                DecoratorBuilder::new(
+                    BorderBuilder::new(WidgetBuilder::new().with_tab_stop(true))
+                        .with_stroke_thickness(Thickness::uniform(1.0)),
+                )
+                .with_normal_brush(BRUSH_LIGHT)
+                .with_hover_brush(BRUSH_LIGHTER)
+                .with_pressed_brush(BRUSH_LIGHTEST)
+                .build(ctx)
+            });
+            match orientation {
+                Orientation::Horizontal => ctx[thumb].set_width(THUMB_LENGTH),
+                Orientation::Vertical => ctx[thumb].set_height(THUMB_LENGTH),
+            };
+            thumb
+        };
+        let low_thumb = make_thumb(self.low_thumb);
+        let high_thumb = make_thumb(self.high_thumb);
+
+        let selection = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_hit_test_visibility(false)
+                .with_background(self.selection_brush),
+        )
+        .with_stroke_thickness(Thickness::zero())
+        .build(ctx);
+
+        let field = CanvasBuilder::new(
+            WidgetBuilder::new()
+                .with_name(RangeSlider::<M, C>::PART_CANVAS)
+                .with_child(selection)
+                .with_child(low_thumb)
+                .with_child(high_thumb),
+        )
+        .build(ctx);
+
+        let body = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_background(self.track_brush)
+                .with_child(field),
+        )
+        .with_stroke_thickness(Thickness::uniform(1.0))
+        .build(ctx);
+
+        let (min, max) = if self.min > self.max {
+            (self.max, self.min)
+        } else {
+            (self.min, self.max)
+        };
+        let mut slider = RangeSlider {
+            widget: self.widget_builder.with_child(body).build(),
+            min,
+            max,
+            low: min,
+            high: max,
+            step: self.step.max(0.0),
+            orientation,
+            low_thumb,
+            high_thumb,
+            selection,
+            field,
+            dragged: None,
+            overlapped: false,
+            offset: Vector2::default(),
+        };
+        slider.low = slider.snap(self.low.min(self.high));
+        slider.high = slider.snap(self.high.max(self.low));
+
+        ctx.add_node(UINode::RangeSlider(slider))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        core::algebra::Vector2,
+        message::{
+            KeyCode, MessageDirection, MouseButton, RangeSliderMessage, UiMessageData,
+            WidgetMessage,
+        },
+        node::StubNode,
+        range_slider::{RangeSliderBuilder, RangeThumb},
+        widget::WidgetBuilder,
+        UINode, UserInterface,
+    };
+
+    #[test]
+    fn thumbs_do_not_cross() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let low_thumb = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let slider = RangeSliderBuilder::new(WidgetBuilder::new().with_width(200.0))
+            .with_range(20.0, 60.0)
+            .with_step(5.0)
+            .with_low_thumb(low_thumb)
+            .build(ctx);
+
+        let range = |ui: &UserInterface<(), StubNode>| {
+            if let UINode::RangeSlider(slider) = ui.node(slider) {
+                (slider.low(), slider.high())
+            } else {
+                unreachable!()
+            }
+        };
+        let mut changes = Vec::new();
+        let pump = |ui: &mut UserInterface<(), StubNode>, changes: &mut Vec<(f32, f32)>| {
+            while let Some(message) = ui.poll_message() {
+                if let UiMessageData::RangeSlider(RangeSliderMessage::Range { low, high }) =
+                    message.data()
+                {
+                    if message.direction() == MessageDirection::FromWidget {
+                        changes.push((*low, *high));
+                    }
+                }
+            }
+        };
+
+        // Moving the low thumb over the high one stops it at the high value.
+        ui.send_message(RangeSliderMessage::range(
+            slider,
+            MessageDirection::ToWidget,
+            90.0,
+            60.0,
+        ));
+        pump(&mut ui, &mut changes);
+        assert_eq!(range(&ui), (60.0, 60.0));
+
+        // Values are snapped to the step.
+        ui.send_message(RangeSliderMessage::range(
+            slider,
+            MessageDirection::ToWidget,
+            12.0,
+            83.0,
+        ));
+        pump(&mut ui, &mut changes);
+        assert_eq!(range(&ui), (10.0, 85.0));
+
+        ui.send_message(WidgetMessage::key_down(
+            low_thumb,
+            MessageDirection::FromWidget,
+            KeyCode::Right,
+        ));
+        pump(&mut ui, &mut changes);
+        assert_eq!(range(&ui), (15.0, 85.0));
+
+        assert_eq!(changes, vec![(60.0, 60.0), (10.0, 85.0), (15.0, 85.0)]);
+    }
+
+    #[test]
+    fn drag_overlapped_thumbs() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let slider = RangeSliderBuilder::new(WidgetBuilder::new().with_width(200.0))
+            .with_range(50.0, 50.0)
+            .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+
+        let (low_thumb, high_thumb) = if let UINode::RangeSlider(slider) = ui.node(slider) {
+            (slider.low_thumb(), slider.high_thumb())
+        } else {
+            unreachable!()
+        };
+        assert_eq!(
+            ui.node(low_thumb).screen_position(),
+            ui.node(high_thumb).screen_position()
+        );
+
+        // High thumb is on top, but it can't go down - the low one must be dragged instead.
+        let pos = ui.node(high_thumb).screen_position();
+        ui.send_message(WidgetMessage::mouse_down(
+            high_thumb,
+            MessageDirection::FromWidget,
+            pos,
+            MouseButton::Left,
+        ));
+        ui.send_message(WidgetMessage::mouse_move(
+            high_thumb,
+            MessageDirection::FromWidget,
+            pos - Vector2::new(50.0, 0.0),
+            Default::default(),
+        ));
+        while ui.poll_message().is_some() {}
+
+        if let UINode::RangeSlider(slider) = ui.node(slider) {
+            assert_eq!(slider.dragged_thumb(), Some(RangeThumb::Low));
+            assert!(slider.low() < 50.0);
+            assert_eq!(slider.high(), 50.0);
+        } else {
+            unreachable!()
+        }
+    }
+}
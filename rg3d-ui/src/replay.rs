@@ -0,0 +1,313 @@
+//! Recording and replaying of input events. It allows to write deterministic tests of UI, like
+//! "click here, type this, check that": record input once with
+//! `UserInterface::record_events`, save recording using `Visitor` and feed it back with
+//! `UserInterface::replay_events`.
+//!
+//! # Coordinates
+//!
+//! Positions of recorded events are stored in logical units (see
+//! `UserInterface::set_reference_resolution`) and converted back to physical units on replay,
+//! so a recording made on one resolution can be replayed on any other.
+
+use crate::{
+    core::{
+        algebra::Vector2,
+        visitor::{Visit, VisitError, VisitResult, Visitor},
+    },
+    message::{ButtonState, KeyCode, KeyboardModifiers, MouseButton, OsEvent, TouchPhase},
+};
+
+/// Input event with time (in seconds) passed since recording was started.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedEvent {
+    pub time: f32,
+    pub event: OsEvent,
+}
+
+impl Default for RecordedEvent {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            event: OsEvent::CursorMoved {
+                position: Vector2::default(),
+            },
+        }
+    }
+}
+
+/// Sequence of input events in order of arrival.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EventRecording {
+    pub events: Vec<RecordedEvent>,
+}
+
+impl EventRecording {
+    /// Returns time of last event.
+    pub fn duration(&self) -> f32 {
+        self.events.last().map_or(0.0, |e| e.time)
+    }
+}
+
+impl Visit for EventRecording {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.events.visit("Events", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+fn visit_button_state(state: &mut ButtonState, name: &str, visitor: &mut Visitor) -> VisitResult {
+    let mut id = *state as u8;
+    id.visit(name, visitor)?;
+    if visitor.is_reading() {
+        *state = match id {
+            0 => ButtonState::Pressed,
+            1 => ButtonState::Released,
+            _ => return Err(VisitError::User(format!("Invalid button state {}!", id))),
+        };
+    }
+    Ok(())
+}
+
+fn visit_mouse_button(button: &mut MouseButton, name: &str, visitor: &mut Visitor) -> VisitResult {
+    visitor.enter_region(name)?;
+
+    let (mut id, mut other) = match *button {
+        MouseButton::Left => (0u8, 0u16),
+        MouseButton::Right => (1, 0),
+        MouseButton::Middle => (2, 0),
+        MouseButton::Other(other) => (3, other),
+    };
+    id.visit("Id", visitor)?;
+    other.visit("Other", visitor)?;
+    if visitor.is_reading() {
+        *button = match id {
+            0 => MouseButton::Left,
+            1 => MouseButton::Right,
+            2 => MouseButton::Middle,
+            3 => MouseButton::Other(other),
+            _ => return Err(VisitError::User(format!("Invalid mouse button {}!", id))),
+        };
+    }
+
+    visitor.leave_region()
+}
+
+fn visit_touch_phase(phase: &mut TouchPhase, name: &str, visitor: &mut Visitor) -> VisitResult {
+    let mut id = *phase as u8;
+    id.visit(name, visitor)?;
+    if visitor.is_reading() {
+        *phase = match id {
+            0 => TouchPhase::Started,
+            1 => TouchPhase::Moved,
+            2 => TouchPhase::Ended,
+            3 => TouchPhase::Cancelled,
+            _ => return Err(VisitError::User(format!("Invalid touch phase {}!", id))),
+        };
+    }
+    Ok(())
+}
+
+fn visit_modifiers(
+    modifiers: &mut KeyboardModifiers,
+    name: &str,
+    visitor: &mut Visitor,
+) -> VisitResult {
+    visitor.enter_region(name)?;
+
+    modifiers.alt.visit("Alt", visitor)?;
+    modifiers.shift.visit("Shift", visitor)?;
+    modifiers.control.visit("Control", visitor)?;
+    modifiers.system.visit("System", visitor)?;
+
+    visitor.leave_region()
+}
+
+impl Visit for RecordedEvent {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.time.visit("Time", visitor)?;
+
+        let mut id: u8 = match self.event {
+            OsEvent::MouseInput { .. } => 0,
+            OsEvent::CursorMoved { .. } => 1,
+            OsEvent::KeyboardInput { .. } => 2,
+            OsEvent::Character(_) => 3,
+            OsEvent::KeyboardModifiers(_) => 4,
+            OsEvent::MouseWheel(..) => 5,
+            OsEvent::Touch { .. } => 6,
+            OsEvent::WindowFocused(_) => 7,
+        };
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            self.event = match id {
+                0 => OsEvent::MouseInput {
+                    button: MouseButton::Left,
+                    state: ButtonState::Pressed,
+                },
+                1 => OsEvent::CursorMoved {
+                    position: Vector2::default(),
+                },
+                2 => OsEvent::KeyboardInput {
+                    button: KeyCode::Key1,
+                    state: ButtonState::Pressed,
+                },
+                3 => OsEvent::Character('\0'),
+                4 => OsEvent::KeyboardModifiers(Default::default()),
+                5 => OsEvent::MouseWheel(0.0, 0.0),
+                6 => OsEvent::Touch {
+                    id: 0,
+                    phase: TouchPhase::Started,
+                    position: Vector2::default(),
+                },
+                7 => OsEvent::WindowFocused(false),
+                _ => return Err(VisitError::User(format!("Invalid event id {}!", id))),
+            };
+        }
+
+        match &mut self.event {
+            OsEvent::MouseInput { button, state } => {
+                visit_mouse_button(button, "Button", visitor)?;
+                visit_button_state(state, "State", visitor)?;
+            }
+            OsEvent::CursorMoved { position } => position.visit("Position", visitor)?,
+            OsEvent::KeyboardInput { button, state } => {
+                let mut key = *button as u32;
+                key.visit("Key", visitor)?;
+                *button = KeyCode::from_id(key)
+                    .ok_or_else(|| VisitError::User(format!("Invalid key code {}!", key)))?;
+                visit_button_state(state, "State", visitor)?;
+            }
+            OsEvent::Character(character) => {
+                let mut code = *character as u32;
+                code.visit("Code", visitor)?;
+                *character = std::char::from_u32(code)
+                    .ok_or_else(|| VisitError::User(format!("Invalid character {}!", code)))?;
+            }
+            OsEvent::KeyboardModifiers(modifiers) => {
+                visit_modifiers(modifiers, "Modifiers", visitor)?
+            }
+            OsEvent::MouseWheel(x, y) => {
+                x.visit("X", visitor)?;
+                y.visit("Y", visitor)?;
+            }
+            OsEvent::Touch {
+                id,
+                phase,
+                position,
+            } => {
+                id.visit("Finger", visitor)?;
+                visit_touch_phase(phase, "Phase", visitor)?;
+                position.visit("Position", visitor)?;
+            }
+            OsEvent::WindowFocused(focused) => focused.visit("Focused", visitor)?,
+        }
+
+        visitor.leave_region()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        button::ButtonBuilder,
+        core::{
+            algebra::Vector2,
+            visitor::{Visit, Visitor},
+        },
+        message::{ButtonMessage, ButtonState, KeyCode, MouseButton, OsEvent, UiMessageData},
+        node::StubNode,
+        replay::EventRecording,
+        widget::WidgetBuilder,
+        UserInterface,
+    };
+
+    fn make_ui(screen_size: Vector2<f32>) -> UserInterface<(), StubNode> {
+        let mut ui = UserInterface::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let content = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        ButtonBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+            .with_content(content)
+            .build(ctx);
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        ui
+    }
+
+    fn count_clicks(ui: &mut UserInterface<(), StubNode>) -> usize {
+        let mut clicks = 0;
+        while let Some(message) = ui.poll_message() {
+            if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
+                clicks += 1;
+            }
+        }
+        clicks
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = make_ui(screen_size);
+
+        ui.record_events(true);
+        let mut clicks = 0;
+        for event in [
+            OsEvent::CursorMoved {
+                position: Vector2::new(50.0, 50.0),
+            },
+            OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ButtonState::Pressed,
+            },
+            OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ButtonState::Released,
+            },
+            OsEvent::KeyboardInput {
+                button: KeyCode::A,
+                state: ButtonState::Pressed,
+            },
+            OsEvent::Character('a'),
+            OsEvent::MouseWheel(0.0, 1.0),
+            OsEvent::WindowFocused(true),
+        ]
+        .iter()
+        {
+            ui.process_os_event(event);
+            clicks += count_clicks(&mut ui);
+            ui.update(screen_size, 0.1);
+        }
+        ui.record_events(false);
+        assert_eq!(clicks, 1);
+
+        let recording = ui.take_recorded_events();
+        assert_eq!(recording.events.len(), 7);
+        assert!(recording.duration() > 0.5);
+
+        // Recording survives save and load.
+        let path = std::env::temp_dir().join("rg3d_ui_event_recording.bin");
+        let mut visitor = Visitor::new();
+        let mut saved = recording.clone();
+        saved.visit("Recording", &mut visitor).unwrap();
+        visitor.save_binary(&path).unwrap();
+        let mut visitor = Visitor::load_binary(&path).unwrap();
+        let mut loaded = EventRecording::default();
+        loaded.visit("Recording", &mut visitor).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(loaded, recording);
+
+        // Replay on a fresh UI gives the same result.
+        let mut ui = make_ui(screen_size);
+        ui.replay_events(loaded);
+        let mut clicks = 0;
+        while ui.is_replaying_events() {
+            ui.update(screen_size, 0.1);
+            clicks += count_clicks(&mut ui);
+        }
+        assert_eq!(clicks, 1);
+    }
+}
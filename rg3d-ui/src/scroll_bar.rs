@@ -1,4 +1,5 @@
 use crate::{
+    accessibility::AccessibleRole,
     border::BorderBuilder,
     brush::{Brush, GradientPoint},
     button::ButtonBuilder,
@@ -12,8 +13,8 @@ use crate::{
     decorator::DecoratorBuilder,
     grid::{Column, GridBuilder, Row},
     message::{
-        ButtonMessage, MessageData, MessageDirection, ScrollBarMessage, TextMessage, UiMessage,
-        UiMessageData, WidgetMessage,
+        BorderMessage, ButtonMessage, DecoratorMessage, MessageData, MessageDirection, MouseButton,
+        ScrollBarMessage, TextMessage, UiMessage, UiMessageData, WidgetMessage,
     },
     text::TextBuilder,
     utils::{make_arrow, ArrowDirection},
@@ -22,7 +23,72 @@ use crate::{
     UserInterface, VerticalAlignment, BRUSH_LIGHT, BRUSH_LIGHTER, BRUSH_LIGHTEST, COLOR_DARKEST,
     COLOR_LIGHTEST,
 };
-use std::ops::{Deref, DerefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::mpsc::Sender,
+};
+
+/// Time (in seconds) after last use before auto-hidden scroll bar starts to fade out.
+const AUTO_HIDE_DELAY: f32 = 1.0;
+
+/// How fast auto-hidden scroll bar fades in and out, opacity change per second.
+const AUTO_HIDE_FADE_SPEED: f32 = 4.0;
+
+/// Time (in seconds) an arrow or the track must be held down before auto-repeat starts.
+const REPEAT_DELAY: f32 = 0.4;
+
+/// Time (in seconds) between repeated movements.
+const REPEAT_INTERVAL: f32 = 0.05;
+
+/// Thumb can be picked at this distance outside of its bounds, so thin scroll bars are easier
+/// to grab by finger.
+const THUMB_HIT_TEST_PADDING: f32 = 4.0;
+
+/// Repeated movement of the value while an arrow or the track is held down.
+#[derive(Copy, Clone, Debug)]
+struct AutoRepeat {
+    /// Change of the value per movement.
+    delta: f32,
+    /// Movement stops when the value reaches the limit: end of the range for arrows, the
+    /// value under cursor for the track.
+    limit: f32,
+    /// Time until next movement.
+    timer: f32,
+    /// Whether the value was moved by the repeat at least once.
+    repeated: bool,
+}
+
+/// Visual style of a scroll bar. Default style matches default look of the scroll bar.
+/// Brushes of the thumb are applied only if the thumb is a decorator and the corner radius
+/// only if it is a decorator or a border, so the style works with default parts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScrollBarStyle {
+    pub track_brush: Brush,
+    pub thumb_normal_brush: Brush,
+    pub thumb_hover_brush: Brush,
+    pub thumb_pressed_brush: Brush,
+    /// Radius of corners of the thumb, zero gives sharp corners.
+    pub thumb_corner_radius: f32,
+    /// Minimal length of the thumb along the scroll bar, it prevents the thumb from becoming
+    /// too small to grab.
+    pub thumb_min_length: f32,
+    /// Fade scroll bar out when it is not hovered and not used (overlay scroll bar).
+    pub auto_hide: bool,
+}
+
+impl Default for ScrollBarStyle {
+    fn default() -> Self {
+        Self {
+            track_brush: Brush::Solid(Color::opaque(60, 60, 60)),
+            thumb_normal_brush: BRUSH_LIGHT,
+            thumb_hover_brush: BRUSH_LIGHTER,
+            thumb_pressed_brush: BRUSH_LIGHTEST,
+            thumb_corner_radius: 0.0,
+            thumb_min_length: 30.0,
+            auto_hide: false,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct ScrollBar<M: MessageData, C: Control<M, C>> {
@@ -31,6 +97,8 @@ pub struct ScrollBar<M: MessageData, C: Control<M, C>> {
     pub max: f32,
     pub value: f32,
     pub step: f32,
+    /// Amount by which the value is changed by a click on the track.
+    pub page_size: f32,
     pub orientation: Orientation,
     pub is_dragging: bool,
     pub offset: Vector2<f32>,
@@ -40,17 +108,35 @@ pub struct ScrollBar<M: MessageData, C: Control<M, C>> {
     pub field: Handle<UINode<M, C>>,
     pub value_text: Handle<UINode<M, C>>,
     pub value_precision: usize,
+    /// Body of the scroll bar on which the thumb moves.
+    pub track: Handle<UINode<M, C>>,
+    style: ScrollBarStyle,
+    is_hovered: bool,
+    /// Time since scroll bar was used, used for auto-hide.
+    idle_time: f32,
+    repeat: Option<AutoRepeat>,
+    /// Click of an arrow button that was auto-repeated must not move the value once more.
+    skip_click: bool,
 }
 
 crate::define_widget_deref!(ScrollBar<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollBar<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::ScrollBar
+    }
+
+    fn accessible_value(&self) -> Option<String> {
+        Some(self.value.to_string())
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         node_map.resolve(&mut self.increase);
         node_map.resolve(&mut self.decrease);
         node_map.resolve(&mut self.indicator);
         node_map.resolve(&mut self.value_text);
         node_map.resolve(&mut self.field);
+        node_map.resolve(&mut self.track);
     }
 
     fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
@@ -130,6 +216,13 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollBar<M, C> {
         self.widget.handle_routed_message(ui, message);
 
         match &message.data() {
+            UiMessageData::Button(ButtonMessage::Click)
+                if self.skip_click
+                    && (message.destination() == self.increase
+                        || message.destination() == self.decrease) =>
+            {
+                self.skip_click = false;
+            }
             UiMessageData::Button(ButtonMessage::Click) => {
                 if message.destination() == self.increase {
                     ui.send_message(ScrollBarMessage::value(
@@ -155,6 +248,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollBar<M, C> {
                         let new_value = math::clampf(value, self.min, self.max);
                         if (new_value - old_value).abs() > std::f32::EPSILON {
                             self.value = new_value;
+                            self.idle_time = 0.0;
                             self.invalidate_layout();
 
                             if self.value_text.is_some() {
@@ -224,8 +318,37 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollBar<M, C> {
                             ui.send_message(response);
                         }
                     }
+                    ScrollBarMessage::PageSize(page_size) => {
+                        let page_size = page_size.max(0.0);
+                        if self.page_size != page_size {
+                            self.page_size = page_size;
+
+                            let response = ScrollBarMessage::page_size(
+                                self.handle,
+                                MessageDirection::FromWidget,
+                                self.page_size,
+                            );
+                            response.set_handled(message.handled());
+                            ui.send_message(response);
+                        }
+                    }
+                    ScrollBarMessage::Style(ref style) => {
+                        self.apply_style(ui, style.clone());
+                    }
                 }
             }
+            UiMessageData::Widget(WidgetMessage::MouseEnter)
+                if message.destination() == self.handle()
+                    || self.has_descendant(message.destination(), ui) =>
+            {
+                self.is_hovered = true;
+            }
+            UiMessageData::Widget(WidgetMessage::MouseLeave)
+                if message.destination() == self.handle()
+                    || self.has_descendant(message.destination(), ui) =>
+            {
+                self.is_hovered = false;
+            }
             UiMessageData::Widget(msg) => {
                 if message.destination() == self.indicator {
                     match msg {
@@ -243,6 +366,9 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollBar<M, C> {
                             ui.release_mouse_capture();
                             message.set_handled(true);
                         }
+                        WidgetMessage::MouseCaptureLost => {
+                            self.is_dragging = false;
+                        }
                         WidgetMessage::MouseMove { pos: mouse_pos, .. } => {
                             if self.indicator.is_some() {
                                 let canvas = ui.borrow_by_name_up(
@@ -284,6 +410,25 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollBar<M, C> {
                         }
                         _ => (),
                     }
+                } else {
+                    match *msg {
+                        WidgetMessage::MouseDown {
+                            pos,
+                            button: MouseButton::Left,
+                        } => {
+                            self.begin_repeat(ui, message.destination(), pos);
+                        }
+                        WidgetMessage::MouseUp { .. } => {
+                            // Click of this release (if any) follows, so flag is set anew on
+                            // every release and never leaks into next clicks.
+                            let repeat = self.repeat.take();
+                            self.skip_click = repeat.is_some_and(|r| r.repeated);
+                            if repeat.is_some() && ui.captured_node() == self.track {
+                                ui.release_mouse_capture();
+                            }
+                        }
+                        _ => (),
+                    }
                 }
             }
             _ => {}
@@ -306,6 +451,35 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollBar<M, C> {
         if self.field == handle {
             self.field = Handle::NONE;
         }
+        if self.track == handle {
+            self.track = Handle::NONE;
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        if !self.style.auto_hide {
+            return;
+        }
+
+        let in_use = self.is_hovered || self.is_dragging;
+        if in_use {
+            self.idle_time = 0.0;
+        } else {
+            self.idle_time += dt;
+        }
+        let target = if in_use || self.idle_time < AUTO_HIDE_DELAY {
+            1.0
+        } else {
+            0.0
+        };
+        let opacity = self.opacity();
+        let step = AUTO_HIDE_FADE_SPEED * dt;
+        let new_opacity = if opacity < target {
+            (opacity + step).min(target)
+        } else {
+            (opacity - step).max(target)
+        };
+        self.widget.set_opacity(new_opacity);
     }
 }
 
@@ -326,6 +500,7 @@ impl<M: MessageData, C: Control<M, C>> ScrollBar<M, C> {
             max: 100.0,
             value: 0.0,
             step: 1.0,
+            page_size: 10.0,
             orientation: Orientation::Vertical,
             is_dragging: false,
             offset: Default::default(),
@@ -335,6 +510,12 @@ impl<M: MessageData, C: Control<M, C>> ScrollBar<M, C> {
             field,
             value_text,
             value_precision: 3,
+            track: Handle::NONE,
+            style: Default::default(),
+            is_hovered: false,
+            idle_time: 0.0,
+            repeat: None,
+            skip_click: false,
         }
     }
 
@@ -358,6 +539,202 @@ impl<M: MessageData, C: Control<M, C>> ScrollBar<M, C> {
     pub fn step(&self) -> f32 {
         self.step
     }
+
+    /// Sets amount by which the value is changed by a click on the track above or below the
+    /// thumb. Scroll viewer sets page size of its scroll bars to size of its viewport.
+    pub fn set_page_size(&mut self, page_size: f32) -> &mut Self {
+        self.page_size = page_size.max(0.0);
+        self
+    }
+
+    pub fn page_size(&self) -> f32 {
+        self.page_size
+    }
+
+    /// Starts movement of the value if an arrow button or the track is pressed: the value is
+    /// moved by a page at once for the track (arrows move it by click), and then repeatedly
+    /// while the mouse button is held down.
+    fn begin_repeat(
+        &mut self,
+        ui: &mut UserInterface<M, C>,
+        pressed: Handle<UINode<M, C>>,
+        pos: Vector2<f32>,
+    ) {
+        let is_part_of = |part: Handle<UINode<M, C>>| {
+            part.is_some() && (pressed == part || ui.node(part).has_descendant(pressed, ui))
+        };
+
+        let repeat = if is_part_of(self.increase) {
+            AutoRepeat {
+                delta: self.step,
+                limit: self.max,
+                timer: REPEAT_DELAY,
+                repeated: false,
+            }
+        } else if is_part_of(self.decrease) {
+            AutoRepeat {
+                delta: -self.step,
+                limit: self.min,
+                timer: REPEAT_DELAY,
+                repeated: false,
+            }
+        } else if (pressed == self.track || pressed == self.field)
+            && self.field.is_some()
+            && self.indicator.is_some()
+        {
+            let field = ui.node(self.field);
+            let indicator = ui.node(self.indicator);
+            let (cursor, field_position, field_size, thumb_position, thumb_size) =
+                match self.orientation {
+                    Orientation::Horizontal => (
+                        pos.x,
+                        field.screen_position().x,
+                        field.actual_size().x,
+                        indicator.screen_position().x,
+                        indicator.actual_size().x,
+                    ),
+                    Orientation::Vertical => (
+                        pos.y,
+                        field.screen_position().y,
+                        field.actual_size().y,
+                        indicator.screen_position().y,
+                        indicator.actual_size().y,
+                    ),
+                };
+
+            let delta = if cursor < thumb_position {
+                -self.page_size
+            } else if cursor > thumb_position + thumb_size {
+                self.page_size
+            } else {
+                return;
+            };
+
+            // Value at which center of the thumb is under cursor.
+            let span = field_size - thumb_size;
+            let percent = if span > 0.0 {
+                math::clampf(
+                    (cursor - field_position - thumb_size * 0.5) / span,
+                    0.0,
+                    1.0,
+                )
+            } else {
+                0.0
+            };
+
+            ui.send_message(ScrollBarMessage::value(
+                self.handle(),
+                MessageDirection::ToWidget,
+                self.value + delta,
+            ));
+            ui.capture_mouse(self.track);
+
+            AutoRepeat {
+                delta,
+                limit: self.min + percent * (self.max - self.min),
+                timer: REPEAT_DELAY,
+                repeated: false,
+            }
+        } else {
+            return;
+        };
+
+        self.repeat = Some(repeat);
+        self.skip_click = false;
+    }
+
+    pub(in crate) fn update_auto_repeat(&mut self, sender: &Sender<UiMessage<M, C>>, dt: f32) {
+        let handle = self.handle();
+        let repeat = match self.repeat.as_mut() {
+            Some(repeat) => repeat,
+            None => return,
+        };
+
+        repeat.timer -= dt;
+        if repeat.timer > 0.0 {
+            return;
+        }
+        repeat.timer += REPEAT_INTERVAL;
+
+        let reached = if repeat.delta > 0.0 {
+            self.value >= repeat.limit
+        } else {
+            self.value <= repeat.limit
+        };
+        if reached || repeat.delta == 0.0 {
+            return;
+        }
+
+        repeat.repeated = true;
+        sender
+            .send(ScrollBarMessage::value(
+                handle,
+                MessageDirection::ToWidget,
+                self.value + repeat.delta,
+            ))
+            .unwrap();
+    }
+
+    pub fn style(&self) -> &ScrollBarStyle {
+        &self.style
+    }
+
+    fn apply_style(&mut self, ui: &UserInterface<M, C>, style: ScrollBarStyle) {
+        if self.track.is_some() {
+            ui.send_message(WidgetMessage::background(
+                self.track,
+                MessageDirection::ToWidget,
+                style.track_brush.clone(),
+            ));
+        }
+
+        if self.indicator.is_some() {
+            for message in [
+                DecoratorMessage::normal_brush(
+                    self.indicator,
+                    MessageDirection::ToWidget,
+                    style.thumb_normal_brush.clone(),
+                ),
+                DecoratorMessage::hover_brush(
+                    self.indicator,
+                    MessageDirection::ToWidget,
+                    style.thumb_hover_brush.clone(),
+                ),
+                DecoratorMessage::pressed_brush(
+                    self.indicator,
+                    MessageDirection::ToWidget,
+                    style.thumb_pressed_brush.clone(),
+                ),
+                BorderMessage::corner_radius(
+                    self.indicator,
+                    MessageDirection::ToWidget,
+                    style.thumb_corner_radius,
+                ),
+                WidgetMessage::min_size(
+                    self.indicator,
+                    MessageDirection::ToWidget,
+                    thumb_min_size(self.orientation, style.thumb_min_length),
+                ),
+            ]
+            .iter()
+            {
+                ui.send_message(message.clone());
+            }
+        }
+
+        if !style.auto_hide {
+            self.widget.set_opacity(1.0);
+        }
+        self.idle_time = 0.0;
+        self.style = style;
+    }
+}
+
+fn thumb_min_size(orientation: Orientation, min_length: f32) -> Vector2<f32> {
+    match orientation {
+        Orientation::Vertical => Vector2::new(0.0, min_length),
+        Orientation::Horizontal => Vector2::new(min_length, 0.0),
+    }
 }
 
 pub struct ScrollBarBuilder<M: MessageData, C: Control<M, C>> {
@@ -366,6 +743,7 @@ pub struct ScrollBarBuilder<M: MessageData, C: Control<M, C>> {
     max: Option<f32>,
     value: Option<f32>,
     step: Option<f32>,
+    page_size: Option<f32>,
     orientation: Option<Orientation>,
     increase: Option<Handle<UINode<M, C>>>,
     decrease: Option<Handle<UINode<M, C>>>,
@@ -373,6 +751,7 @@ pub struct ScrollBarBuilder<M: MessageData, C: Control<M, C>> {
     body: Option<Handle<UINode<M, C>>>,
     show_value: bool,
     value_precision: usize,
+    style: ScrollBarStyle,
 }
 
 impl<M: MessageData, C: Control<M, C>> ScrollBarBuilder<M, C> {
@@ -383,6 +762,7 @@ impl<M: MessageData, C: Control<M, C>> ScrollBarBuilder<M, C> {
             max: None,
             value: None,
             step: None,
+            page_size: None,
             orientation: None,
             increase: None,
             decrease: None,
@@ -390,6 +770,7 @@ impl<M: MessageData, C: Control<M, C>> ScrollBarBuilder<M, C> {
             body: None,
             show_value: false,
             value_precision: 3,
+            style: Default::default(),
         }
     }
 
@@ -418,6 +799,11 @@ impl<M: MessageData, C: Control<M, C>> ScrollBarBuilder<M, C> {
         self
     }
 
+    pub fn with_page_size(mut self, page_size: f32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
     pub fn with_increase(mut self, increase: Handle<UINode<M, C>>) -> Self {
         self.increase = Some(increase);
         self
@@ -448,7 +834,40 @@ impl<M: MessageData, C: Control<M, C>> ScrollBarBuilder<M, C> {
         self
     }
 
+    pub fn with_style(mut self, style: ScrollBarStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_track_brush(mut self, brush: Brush) -> Self {
+        self.style.track_brush = brush;
+        self
+    }
+
+    pub fn with_thumb_brushes(mut self, normal: Brush, hover: Brush, pressed: Brush) -> Self {
+        self.style.thumb_normal_brush = normal;
+        self.style.thumb_hover_brush = hover;
+        self.style.thumb_pressed_brush = pressed;
+        self
+    }
+
+    pub fn with_thumb_corner_radius(mut self, radius: f32) -> Self {
+        self.style.thumb_corner_radius = radius;
+        self
+    }
+
+    pub fn with_thumb_min_length(mut self, length: f32) -> Self {
+        self.style.thumb_min_length = length;
+        self
+    }
+
+    pub fn with_auto_hide(mut self, auto_hide: bool) -> Self {
+        self.style.auto_hide = auto_hide;
+        self
+    }
+
     pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        let style = self.style;
         let orientation = self.orientation.unwrap_or(Orientation::Horizontal);
 
         let increase = self.increase.unwrap_or_else(|| {
@@ -509,24 +928,24 @@ impl<M: MessageData, C: Control<M, C>> ScrollBarBuilder<M, C> {
                         },
                     ],
                 }))
-                .with_stroke_thickness(Thickness::uniform(1.0)),
+                .with_stroke_thickness(Thickness::uniform(1.0))
+                .with_corner_radius(style.thumb_corner_radius),
             )
-            .with_normal_brush(BRUSH_LIGHT)
-            .with_hover_brush(BRUSH_LIGHTER)
-            .with_pressed_brush(BRUSH_LIGHTEST)
+            .with_normal_brush(style.thumb_normal_brush.clone())
+            .with_hover_brush(style.thumb_hover_brush.clone())
+            .with_pressed_brush(style.thumb_pressed_brush.clone())
             .build(ctx)
         });
 
+        ctx[indicator]
+            .set_min_size(thumb_min_size(orientation, style.thumb_min_length))
+            .set_hit_test_padding(Thickness::uniform(THUMB_HIT_TEST_PADDING));
         match orientation {
             Orientation::Vertical => {
-                ctx[indicator]
-                    .set_min_size(Vector2::new(0.0, 30.0))
-                    .set_width(30.0);
+                ctx[indicator].set_width(30.0);
             }
             Orientation::Horizontal => {
-                ctx[indicator]
-                    .set_min_size(Vector2::new(30.0, 0.0))
-                    .set_height(30.0);
+                ctx[indicator].set_height(30.0);
             }
         }
 
@@ -587,20 +1006,23 @@ impl<M: MessageData, C: Control<M, C>> ScrollBarBuilder<M, C> {
         .build(ctx);
 
         let body = self.body.unwrap_or_else(|| {
-            BorderBuilder::new(
-                WidgetBuilder::new().with_background(Brush::Solid(Color::opaque(60, 60, 60))),
-            )
-            .with_stroke_thickness(Thickness::uniform(1.0))
-            .build(ctx)
+            BorderBuilder::new(WidgetBuilder::new().with_background(style.track_brush.clone()))
+                .with_stroke_thickness(Thickness::uniform(1.0))
+                .build(ctx)
         });
         ctx.link(grid, body);
 
+        let mut widget_builder = self.widget_builder.with_child(body);
+        if style.auto_hide {
+            widget_builder.opacity = 0.0;
+        }
         let node = UINode::ScrollBar(ScrollBar {
-            widget: self.widget_builder.with_child(body).build(),
+            widget: widget_builder.build(),
             min,
             max,
             value,
             step: self.step.unwrap_or(1.0),
+            page_size: self.page_size.unwrap_or(10.0).max(0.0),
             orientation,
             is_dragging: false,
             offset: Vector2::default(),
@@ -610,7 +1032,94 @@ impl<M: MessageData, C: Control<M, C>> ScrollBarBuilder<M, C> {
             field,
             value_text,
             value_precision: self.value_precision,
+            track: body,
+            style,
+            is_hovered: false,
+            idle_time: 0.0,
+            repeat: None,
+            skip_click: false,
         });
         ctx.add_node(node)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        core::{algebra::Vector2, pool::Handle},
+        message::{ButtonMessage, MessageDirection, MouseButton, WidgetMessage},
+        node::{StubNode, UINode},
+        scroll_bar::{AutoRepeat, ScrollBar},
+        widget::WidgetBuilder,
+        Orientation, UserInterface,
+    };
+
+    #[test]
+    fn click_after_repeat() {
+        let mut ui = UserInterface::<(), StubNode>::new(Vector2::new(1000.0, 1000.0));
+        let ctx = &mut ui.build_ctx();
+        let increase = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let indicator = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let field = BorderBuilder::new(WidgetBuilder::new().with_child(indicator)).build(ctx);
+        let scroll_bar = ctx.add_node(UINode::ScrollBar(ScrollBar {
+            widget: WidgetBuilder::new()
+                .with_child(increase)
+                .with_child(field)
+                .build(),
+            min: 0.0,
+            max: 100.0,
+            value: 0.0,
+            step: 1.0,
+            page_size: 10.0,
+            orientation: Orientation::Horizontal,
+            is_dragging: false,
+            offset: Default::default(),
+            increase,
+            decrease: Handle::NONE,
+            indicator,
+            field,
+            value_text: Handle::NONE,
+            value_precision: 0,
+            track: Handle::NONE,
+            style: Default::default(),
+            is_hovered: false,
+            idle_time: 0.0,
+            repeat: None,
+            skip_click: false,
+        }));
+
+        let release = |ui: &mut UserInterface<(), StubNode>, repeated: Option<bool>, click| {
+            if let UINode::ScrollBar(scroll_bar) = ui.nodes.borrow_mut(scroll_bar) {
+                scroll_bar.repeat = repeated.map(|repeated| AutoRepeat {
+                    delta: 1.0,
+                    limit: 100.0,
+                    timer: 0.0,
+                    repeated,
+                });
+            }
+            ui.send_message(WidgetMessage::mouse_up(
+                increase,
+                MessageDirection::FromWidget,
+                Vector2::default(),
+                MouseButton::Left,
+            ));
+            if click {
+                ui.send_message(ButtonMessage::click(increase, MessageDirection::FromWidget));
+            }
+            while ui.poll_message().is_some() {}
+            if let UINode::ScrollBar(scroll_bar) = ui.node(scroll_bar) {
+                scroll_bar.value
+            } else {
+                unreachable!()
+            }
+        };
+
+        // Click of repeated arrow does not move the value once more.
+        assert_eq!(release(&mut ui, Some(true), true), 0.0);
+        assert_eq!(release(&mut ui, Some(false), true), 1.0);
+        // Repeated arrow was released outside of the arrow, so there is no click to skip.
+        assert_eq!(release(&mut ui, Some(true), false), 1.0);
+        assert_eq!(release(&mut ui, None, true), 2.0);
+    }
+}
@@ -7,15 +7,38 @@ use crate::{
     widget::{Widget, WidgetBuilder},
     BuildContext, Control, UINode, UserInterface,
 };
-use std::ops::{Deref, DerefMut};
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
+
+/// Returns size of content along one axis after arrangement.
+fn arranged_content_size(desired: f32, available: f32, scroll_allowed: bool, stretch: bool) -> f32 {
+    if !stretch {
+        desired
+    } else if scroll_allowed {
+        desired.max(available)
+    } else {
+        available
+    }
+}
 
 /// Allows user to scroll content
 #[derive(Clone)]
 pub struct ScrollPanel<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
-    scroll: Vector2<f32>,
+    /// Scroll offset, it is clamped on arrangement when content shrinks, so blank space won't
+    /// be shown past its end. Offset past the end that was set explicitly (overscroll of a
+    /// scroll viewer) is kept.
+    scroll: Cell<Vector2<f32>>,
+    /// Maximum scroll offset at previous arrangement.
+    max_scroll: Cell<Vector2<f32>>,
     vertical_scroll_allowed: bool,
     horizontal_scroll_allowed: bool,
+    /// Content is stretched to width of the panel, otherwise it has its desired width.
+    stretch_content_width: bool,
+    /// Content is stretched to height of the panel, otherwise it has its desired height.
+    stretch_content_height: bool,
 }
 
 crate::define_widget_deref!(ScrollPanel<M, C>);
@@ -28,13 +51,15 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollPanel<M, C> {
     ) -> Vector2<f32> {
         scope_profile!();
 
+        // Content measured with infinite size gets its natural size, which is either
+        // scrolled or (if scrolling is not allowed) clipped.
         let size_for_child = Vector2::new(
-            if self.horizontal_scroll_allowed {
+            if self.horizontal_scroll_allowed || !self.stretch_content_width {
                 std::f32::INFINITY
             } else {
                 available_size.x
             },
-            if self.vertical_scroll_allowed {
+            if self.vertical_scroll_allowed || !self.stretch_content_height {
                 std::f32::INFINITY
             } else {
                 available_size.y
@@ -69,21 +94,51 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollPanel<M, C> {
             children_size.y = children_size.y.max(desired_size.y);
         }
 
-        let child_rect = Rect::new(
-            -self.scroll.x,
-            -self.scroll.y,
-            if self.horizontal_scroll_allowed {
-                children_size.x.max(final_size.x)
-            } else {
-                final_size.x
-            },
-            if self.vertical_scroll_allowed {
-                children_size.y.max(final_size.y)
-            } else {
-                final_size.y
-            },
+        let content_size = Vector2::new(
+            arranged_content_size(
+                children_size.x,
+                final_size.x,
+                self.horizontal_scroll_allowed,
+                self.stretch_content_width,
+            ),
+            arranged_content_size(
+                children_size.y,
+                final_size.y,
+                self.vertical_scroll_allowed,
+                self.stretch_content_height,
+            ),
         );
 
+        // Content could shrink (for example when items were removed from a list), in this case
+        // offset must be clamped, otherwise blank space will be shown past end of content.
+        // Offset is not clamped otherwise, because scroll viewer moves content past its end
+        // when it is dragged or springs back from overscroll.
+        let mut scroll = self.scroll.get();
+        let max_scroll = Vector2::new(
+            (content_size.x - final_size.x).max(0.0),
+            (content_size.y - final_size.y).max(0.0),
+        );
+        let prev_max_scroll = self.max_scroll.replace(max_scroll);
+        if max_scroll.x < prev_max_scroll.x && scroll.x > max_scroll.x {
+            scroll.x = max_scroll.x;
+            ui.send_message(ScrollPanelMessage::horizontal_scroll(
+                self.handle,
+                MessageDirection::FromWidget,
+                max_scroll.x,
+            ));
+        }
+        if max_scroll.y < prev_max_scroll.y && scroll.y > max_scroll.y {
+            scroll.y = max_scroll.y;
+            ui.send_message(ScrollPanelMessage::vertical_scroll(
+                self.handle,
+                MessageDirection::FromWidget,
+                max_scroll.y,
+            ));
+        }
+        self.scroll.set(scroll);
+
+        let child_rect = Rect::new(-scroll.x, -scroll.y, content_size.x, content_size.y);
+
         for child_handle in self.widget.children() {
             ui.node(*child_handle).arrange(ui, &child_rect);
         }
@@ -112,14 +167,32 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollPanel<M, C> {
         if message.destination() == self.handle() {
             if let UiMessageData::ScrollPanel(msg) = &message.data() {
                 match *msg {
-                    ScrollPanelMessage::VerticalScroll(scroll) => {
-                        self.scroll.y = scroll;
+                    ScrollPanelMessage::VerticalScroll(scroll)
+                        if message.direction() == MessageDirection::ToWidget =>
+                    {
+                        self.scroll.set(Vector2::new(self.scroll.get().x, scroll));
                         self.invalidate_layout();
                     }
-                    ScrollPanelMessage::HorizontalScroll(scroll) => {
-                        self.scroll.x = scroll;
+                    ScrollPanelMessage::HorizontalScroll(scroll)
+                        if message.direction() == MessageDirection::ToWidget =>
+                    {
+                        self.scroll.set(Vector2::new(scroll, self.scroll.get().y));
                         self.invalidate_layout();
                     }
+                    ScrollPanelMessage::VerticalScroll(_)
+                    | ScrollPanelMessage::HorizontalScroll(_) => {}
+                    ScrollPanelMessage::HorizontalScrollAllowed(state) => {
+                        self.set_horizontal_scroll_allowed(state);
+                    }
+                    ScrollPanelMessage::VerticalScrollAllowed(state) => {
+                        self.set_vertical_scroll_allowed(state);
+                    }
+                    ScrollPanelMessage::StretchContentWidth(state) => {
+                        self.set_stretch_content_width(state);
+                    }
+                    ScrollPanelMessage::StretchContentHeight(state) => {
+                        self.set_stretch_content_height(state);
+                    }
                     ScrollPanelMessage::BringIntoView(handle) => {
                         let mut parent = handle;
                         let mut relative_position = Vector2::default();
@@ -135,7 +208,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollPanel<M, C> {
                             || relative_position.x > self.actual_size().x
                             || relative_position.y > self.actual_size().y
                         {
-                            relative_position += self.scroll;
+                            relative_position += self.scroll.get();
                             // This check is needed because it possible that given handle is not in
                             // sub-tree of current scroll panel.
                             if parent == self.handle {
@@ -166,9 +239,12 @@ impl<M: MessageData, C: Control<M, C>> ScrollPanel<M, C> {
     pub fn new(widget: Widget<M, C>) -> Self {
         Self {
             widget,
-            scroll: Default::default(),
+            scroll: Cell::new(Default::default()),
+            max_scroll: Cell::new(Default::default()),
             vertical_scroll_allowed: true,
             horizontal_scroll_allowed: false,
+            stretch_content_width: true,
+            stretch_content_height: true,
         }
     }
 
@@ -185,12 +261,47 @@ impl<M: MessageData, C: Control<M, C>> ScrollPanel<M, C> {
             self.widget.invalidate_layout();
         }
     }
+
+    pub fn is_vertical_scroll_allowed(&self) -> bool {
+        self.vertical_scroll_allowed
+    }
+
+    pub fn is_horizontal_scroll_allowed(&self) -> bool {
+        self.horizontal_scroll_allowed
+    }
+
+    /// Stretches content to width of the panel (common for vertical lists), otherwise
+    /// content keeps its own width (2D canvases, images, etc).
+    pub fn set_stretch_content_width(&mut self, state: bool) {
+        if self.stretch_content_width != state {
+            self.stretch_content_width = state;
+            self.widget.invalidate_layout();
+        }
+    }
+
+    pub fn stretch_content_width(&self) -> bool {
+        self.stretch_content_width
+    }
+
+    /// Stretches content to height of the panel, otherwise content keeps its own height.
+    pub fn set_stretch_content_height(&mut self, state: bool) {
+        if self.stretch_content_height != state {
+            self.stretch_content_height = state;
+            self.widget.invalidate_layout();
+        }
+    }
+
+    pub fn stretch_content_height(&self) -> bool {
+        self.stretch_content_height
+    }
 }
 
 pub struct ScrollPanelBuilder<M: MessageData, C: Control<M, C>> {
     widget_builder: WidgetBuilder<M, C>,
     vertical_scroll_allowed: Option<bool>,
     horizontal_scroll_allowed: Option<bool>,
+    stretch_content_width: bool,
+    stretch_content_height: bool,
 }
 
 impl<M: MessageData, C: Control<M, C>> ScrollPanelBuilder<M, C> {
@@ -199,6 +310,8 @@ impl<M: MessageData, C: Control<M, C>> ScrollPanelBuilder<M, C> {
             widget_builder,
             vertical_scroll_allowed: None,
             horizontal_scroll_allowed: None,
+            stretch_content_width: true,
+            stretch_content_height: true,
         }
     }
 
@@ -212,12 +325,145 @@ impl<M: MessageData, C: Control<M, C>> ScrollPanelBuilder<M, C> {
         self
     }
 
+    pub fn with_stretch_content_width(mut self, value: bool) -> Self {
+        self.stretch_content_width = value;
+        self
+    }
+
+    pub fn with_stretch_content_height(mut self, value: bool) -> Self {
+        self.stretch_content_height = value;
+        self
+    }
+
     pub fn build(self, ui: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
         ui.add_node(UINode::ScrollPanel(ScrollPanel {
             widget: self.widget_builder.build(),
-            scroll: Vector2::default(),
+            scroll: Cell::new(Vector2::default()),
+            max_scroll: Cell::new(Vector2::default()),
             vertical_scroll_allowed: self.vertical_scroll_allowed.unwrap_or(true),
             horizontal_scroll_allowed: self.horizontal_scroll_allowed.unwrap_or(false),
+            stretch_content_width: self.stretch_content_width,
+            stretch_content_height: self.stretch_content_height,
         }))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        core::algebra::Vector2,
+        message::{MessageDirection, ScrollPanelMessage, UiMessageData, WidgetMessage},
+        node::StubNode,
+        scroll_panel::ScrollPanelBuilder,
+        widget::WidgetBuilder,
+        UserInterface,
+    };
+
+    #[test]
+    fn stretch_content() {
+        let mut ui = UserInterface::<(), StubNode>::new(Vector2::new(1000.0, 1000.0));
+
+        // Vertical list fills width of the panel.
+        let list =
+            BorderBuilder::new(WidgetBuilder::new().with_height(500.0)).build(&mut ui.build_ctx());
+        ScrollPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(200.0)
+                .with_height(100.0)
+                .with_child(list),
+        )
+        .build(&mut ui.build_ctx());
+
+        // Canvas uses its own extent in both directions.
+        let canvas = BorderBuilder::new(WidgetBuilder::new().with_width(50.0).with_height(60.0))
+            .build(&mut ui.build_ctx());
+        ScrollPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(200.0)
+                .with_height(100.0)
+                .with_child(canvas),
+        )
+        .with_horizontal_scroll_allowed(true)
+        .with_stretch_content_width(false)
+        .with_stretch_content_height(false)
+        .build(&mut ui.build_ctx());
+
+        ui.update(Vector2::new(1000.0, 1000.0), 0.0);
+
+        assert_eq!(ui.node(list).actual_size(), Vector2::new(200.0, 500.0));
+        assert_eq!(ui.node(canvas).actual_size(), Vector2::new(50.0, 60.0));
+    }
+
+    #[test]
+    fn clamp_scroll_of_shrunk_content() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+
+        let list =
+            BorderBuilder::new(WidgetBuilder::new().with_height(500.0)).build(&mut ui.build_ctx());
+        let panel = ScrollPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(200.0)
+                .with_height(100.0)
+                .with_child(list),
+        )
+        .build(&mut ui.build_ctx());
+        ui.send_message(ScrollPanelMessage::vertical_scroll(
+            panel,
+            MessageDirection::ToWidget,
+            400.0,
+        ));
+        ui.update(screen_size, 0.0);
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+        assert_eq!(ui.node(list).actual_local_position().y, -400.0);
+
+        ui.send_message(WidgetMessage::height(
+            list,
+            MessageDirection::ToWidget,
+            150.0,
+        ));
+        // Layout is updated right after the messages are handled, so the clamp is reported
+        // in the same frame.
+        let mut clamped = false;
+        while let Some(message) = ui.poll_message() {
+            if message.destination() == panel && message.direction() == MessageDirection::FromWidget
+            {
+                clamped |= matches!(
+                    message.data(),
+                    UiMessageData::ScrollPanel(ScrollPanelMessage::VerticalScroll(y)) if *y == 50.0
+                );
+            }
+        }
+        assert!(clamped);
+        assert_eq!(ui.node(list).actual_local_position().y, -50.0);
+    }
+
+    #[test]
+    fn keep_overscroll() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+
+        let list =
+            BorderBuilder::new(WidgetBuilder::new().with_height(500.0)).build(&mut ui.build_ctx());
+        let panel = ScrollPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(200.0)
+                .with_height(100.0)
+                .with_child(list),
+        )
+        .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+
+        // Scroll viewer pulls content past its end when it is dragged.
+        ui.send_message(ScrollPanelMessage::vertical_scroll(
+            panel,
+            MessageDirection::ToWidget,
+            450.0,
+        ));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+        assert_eq!(ui.node(list).actual_local_position().y, -450.0);
+    }
+}
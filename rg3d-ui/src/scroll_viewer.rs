@@ -1,17 +1,48 @@
 use crate::core::algebra::Vector2;
 use crate::{
-    core::pool::Handle,
+    brush::Brush,
+    core::{color::Color, math, math::Rect, pool::Handle},
+    draw::{CommandTexture, Draw, DrawingContext},
     grid::{Column, GridBuilder, Row},
     message::{
-        MessageData, MessageDirection, ScrollBarMessage, ScrollPanelMessage, ScrollViewerMessage,
-        UiMessage, UiMessageData, WidgetMessage,
+        ButtonState, MessageData, MessageDirection, MouseButton, ScrollBarMessage,
+        ScrollPanelMessage, ScrollViewerMessage, UiMessage, UiMessageData, WidgetMessage,
     },
     scroll_bar::ScrollBarBuilder,
     scroll_panel::ScrollPanelBuilder,
+    touch::PAN_THRESHOLD,
     widget::{Widget, WidgetBuilder},
-    BuildContext, Control, NodeHandleMapping, Orientation, UINode, UserInterface,
+    BuildContext, Control, NodeHandleMapping, Orientation, UINode, UserInterface, COLOR_DARK,
 };
-use std::ops::{Deref, DerefMut};
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+    sync::mpsc::Sender,
+};
+
+/// Default size of fading at edges of viewport.
+const DEFAULT_FADE_SIZE: f32 = 16.0;
+
+/// Default fraction of velocity of inertial scrolling that is lost per second.
+const DEFAULT_FRICTION: f32 = 4.0;
+
+/// Inertial scrolling stops when speed (in units per second) drops below this value.
+const MIN_INERTIA_SPEED: f32 = 5.0;
+
+/// Content pulled beyond its bounds moves slower than pointer by this factor.
+const OVERSCROLL_RESISTANCE: f32 = 0.5;
+
+/// How fast overscrolled content springs back, fraction of overscroll removed per second.
+const SPRING_STIFFNESS: f32 = 10.0;
+
+/// Weight of a new velocity sample, velocity is smoothed to ignore jerky movements.
+const VELOCITY_SMOOTHING: f32 = 0.5;
+
+/// Default width of zones along edges of viewport in which dragged item scrolls content.
+const DEFAULT_DRAG_EDGE_MARGIN: f32 = 24.0;
+
+/// Default speed of scrolling (units per second) when dragged item is at the very edge.
+const DEFAULT_DRAG_EDGE_MAX_SPEED: f32 = 600.0;
 
 #[derive(Clone)]
 pub struct ScrollViewer<M: MessageData, C: Control<M, C>> {
@@ -20,6 +51,37 @@ pub struct ScrollViewer<M: MessageData, C: Control<M, C>> {
     pub scroll_panel: Handle<UINode<M, C>>,
     pub v_scroll_bar: Handle<UINode<M, C>>,
     pub h_scroll_bar: Handle<UINode<M, C>>,
+    /// Node that stays above content, it is scrolled only horizontally together with
+    /// content, so columns of a table stay aligned with their header.
+    header: Handle<UINode<M, C>>,
+    /// Panel that holds the header and follows horizontal offset of content.
+    header_panel: Handle<UINode<M, C>>,
+    /// Draw fading at edges of viewport when there is more content in that direction.
+    fade_edges: bool,
+    fade_size: f32,
+    fade_color: Color,
+    scroll_value: Vector2<f32>,
+    scroll_max: Cell<Vector2<f32>>,
+    /// Bounds of content presenter in local coordinates of the scroll viewer.
+    viewport: Cell<Rect<f32>>,
+    /// Keep scrolling with decaying speed after content was released.
+    inertia_enabled: bool,
+    friction: f32,
+    /// Allows to scroll by dragging content with a mouse.
+    drag_scroll_enabled: bool,
+    /// Content is held by a finger or a mouse.
+    held: bool,
+    /// Scroll velocity in units per second, it is measured while content is held.
+    velocity: Vector2<f32>,
+    /// Offset of content that was accumulated since last update, used to measure velocity.
+    frame_offset: Vector2<f32>,
+    /// Distance on which content is pulled beyond its bounds.
+    overscroll: Vector2<f32>,
+    /// Position of mouse press and last position of mouse, while drag scroll is possible.
+    mouse_drag: Option<(Vector2<f32>, Vector2<f32>)>,
+    /// See `set_drag_edge_margin`.
+    drag_edge_margin: f32,
+    drag_edge_max_speed: f32,
 }
 
 crate::define_widget_deref!(ScrollViewer<M, C>);
@@ -38,6 +100,24 @@ impl<M: MessageData, C: Control<M, C>> ScrollViewer<M, C> {
             scroll_panel: content_presenter,
             v_scroll_bar,
             h_scroll_bar,
+            header: Handle::NONE,
+            header_panel: Handle::NONE,
+            fade_edges: false,
+            fade_size: DEFAULT_FADE_SIZE,
+            fade_color: COLOR_DARK,
+            scroll_value: Default::default(),
+            scroll_max: Default::default(),
+            viewport: Default::default(),
+            inertia_enabled: true,
+            friction: DEFAULT_FRICTION,
+            drag_scroll_enabled: false,
+            held: false,
+            velocity: Default::default(),
+            frame_offset: Default::default(),
+            overscroll: Default::default(),
+            mouse_drag: None,
+            drag_edge_margin: DEFAULT_DRAG_EDGE_MARGIN,
+            drag_edge_max_speed: DEFAULT_DRAG_EDGE_MAX_SPEED,
         }
     }
 
@@ -52,6 +132,273 @@ impl<M: MessageData, C: Control<M, C>> ScrollViewer<M, C> {
     pub fn set_content(&mut self, content: Handle<UINode<M, C>>) {
         self.content = content;
     }
+
+    pub fn header(&self) -> Handle<UINode<M, C>> {
+        self.header
+    }
+
+    pub fn set_fade_edges(&mut self, fade_edges: bool) -> &mut Self {
+        self.fade_edges = fade_edges;
+        self
+    }
+
+    pub fn fade_edges(&self) -> bool {
+        self.fade_edges
+    }
+
+    /// Sets size (in units) of fading at edges of viewport.
+    pub fn set_fade_size(&mut self, size: f32) -> &mut Self {
+        self.fade_size = size.max(0.0);
+        self
+    }
+
+    pub fn fade_size(&self) -> f32 {
+        self.fade_size
+    }
+
+    /// Sets color to which content fades, usually it should match background color.
+    pub fn set_fade_color(&mut self, color: Color) -> &mut Self {
+        self.fade_color = color;
+        self
+    }
+
+    pub fn fade_color(&self) -> Color {
+        self.fade_color
+    }
+
+    /// Enables or disables inertial scrolling: when content is released after a fast drag
+    /// it will keep scrolling with decaying speed.
+    pub fn set_inertia_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.inertia_enabled = enabled;
+        if !enabled {
+            self.velocity = Vector2::default();
+        }
+        self
+    }
+
+    pub fn is_inertia_enabled(&self) -> bool {
+        self.inertia_enabled
+    }
+
+    /// Sets fraction of velocity of inertial scrolling that is lost per second.
+    pub fn set_friction(&mut self, friction: f32) -> &mut Self {
+        self.friction = friction.max(0.0);
+        self
+    }
+
+    pub fn friction(&self) -> f32 {
+        self.friction
+    }
+
+    /// Enables or disables scrolling by dragging content with a mouse. Touch panning is
+    /// always enabled.
+    pub fn set_drag_scroll_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.drag_scroll_enabled = enabled;
+        self
+    }
+
+    pub fn is_drag_scroll_enabled(&self) -> bool {
+        self.drag_scroll_enabled
+    }
+
+    /// Sets width of zones along edges of viewport in which an item that is dragged (drag and
+    /// drop) scrolls content in direction of the edge, so it can be dropped beyond visible
+    /// area. Zero disables the scrolling.
+    pub fn set_drag_edge_margin(&mut self, margin: f32) -> &mut Self {
+        self.drag_edge_margin = margin.max(0.0);
+        self
+    }
+
+    pub fn drag_edge_margin(&self) -> f32 {
+        self.drag_edge_margin
+    }
+
+    /// Sets speed of scrolling (units per second) when dragged item is at the very edge of
+    /// viewport, speed decreases linearly to zero at inner border of edge margin.
+    pub fn set_drag_edge_max_speed(&mut self, speed: f32) -> &mut Self {
+        self.drag_edge_max_speed = speed.max(0.0);
+        self
+    }
+
+    pub fn drag_edge_max_speed(&self) -> f32 {
+        self.drag_edge_max_speed
+    }
+
+    /// Returns current velocity of inertial scrolling, content moves in this direction.
+    pub fn velocity(&self) -> Vector2<f32> {
+        self.velocity
+    }
+
+    /// Returns distance on which content is pulled beyond its bounds.
+    pub fn overscroll(&self) -> Vector2<f32> {
+        self.overscroll
+    }
+
+    /// Returns true if content is released but it is still moving (by inertia or springing
+    /// back from overscroll).
+    pub fn is_scrolling_kinetically(&self) -> bool {
+        !self.held && (self.velocity != Vector2::default() || self.overscroll != Vector2::default())
+    }
+
+    pub(in crate) fn begin_drag(&mut self) {
+        self.held = true;
+        self.velocity = Vector2::default();
+        self.frame_offset = Vector2::default();
+    }
+
+    /// Moves content by given offset, so it follows a pointer. Content can be pulled beyond
+    /// its bounds, but with resistance.
+    pub(in crate) fn drag_by(&mut self, sender: &Sender<UiMessage<M, C>>, offset: Vector2<f32>) {
+        self.frame_offset += offset;
+        let max = self.scroll_max.get();
+        let mut position = self.scroll_value + self.overscroll;
+        for i in 0..2 {
+            let resistance = if position[i] < 0.0 || position[i] > max[i] {
+                OVERSCROLL_RESISTANCE
+            } else {
+                1.0
+            };
+            position[i] -= offset[i] * resistance;
+        }
+        self.scroll_to(sender, position);
+    }
+
+    /// Releases content, it will continue scrolling by inertia if `keep_velocity` is set.
+    pub(in crate) fn end_drag(&mut self, keep_velocity: bool) {
+        self.held = false;
+        if !keep_velocity || !self.inertia_enabled {
+            self.velocity = Vector2::default();
+        }
+    }
+
+    /// Stops dragging of content by a finger or a mouse without inertia, content will only
+    /// spring back if it is overscrolled.
+    pub(in crate) fn cancel_drag(&mut self) {
+        self.mouse_drag = None;
+        self.end_drag(false);
+    }
+
+    /// Moves released content by inertia and springs it back if it is overscrolled.
+    pub(in crate) fn update_kinetics(&mut self, sender: &Sender<UiMessage<M, C>>, dt: f32) {
+        if self.held {
+            if dt > 0.0 {
+                let sample = self.frame_offset.scale(1.0 / dt);
+                self.velocity = self.velocity.lerp(&sample, VELOCITY_SMOOTHING);
+            }
+            self.frame_offset = Vector2::default();
+            return;
+        }
+
+        if !self.is_scrolling_kinetically() {
+            return;
+        }
+
+        let mut position = self.scroll_value + self.overscroll;
+        for i in 0..2 {
+            if self.overscroll[i] != 0.0 {
+                self.velocity[i] = 0.0;
+                let overscroll = self.overscroll[i] * (1.0 - (SPRING_STIFFNESS * dt).min(1.0));
+                position[i] = self.scroll_value[i]
+                    + if overscroll.abs() < 0.5 {
+                        0.0
+                    } else {
+                        overscroll
+                    };
+            } else {
+                position[i] -= self.velocity[i] * dt;
+            }
+        }
+
+        self.velocity = self.velocity.scale((1.0 - self.friction * dt).max(0.0));
+        if self.velocity.norm() < MIN_INERTIA_SPEED {
+            self.velocity = Vector2::default();
+        }
+
+        self.scroll_to(sender, position);
+    }
+
+    /// Scrolls content while something is dragged over edge margins of viewport, speed is
+    /// proportional to depth of cursor in a margin.
+    pub(in crate) fn update_drag_edge_scroll(
+        &mut self,
+        sender: &Sender<UiMessage<M, C>>,
+        cursor: Vector2<f32>,
+        dt: f32,
+    ) {
+        if self.held || self.drag_edge_margin <= 0.0 || !self.is_globally_visible() {
+            return;
+        }
+
+        let viewport = self.viewport.get().translate(self.screen_position());
+        if !viewport.contains(cursor) {
+            return;
+        }
+
+        let mut delta = Vector2::default();
+        for i in 0..2 {
+            let margin = self.drag_edge_margin.min(viewport.size[i] * 0.5);
+            if margin <= 0.0 {
+                continue;
+            }
+            let to_start = cursor[i] - viewport.position[i];
+            let to_end = viewport.position[i] + viewport.size[i] - cursor[i];
+            let depth = if to_start < margin {
+                to_start / margin - 1.0
+            } else if to_end < margin {
+                1.0 - to_end / margin
+            } else {
+                0.0
+            };
+            delta[i] = depth * self.drag_edge_max_speed * dt;
+        }
+        if delta == Vector2::default() {
+            return;
+        }
+
+        // Content must not be pulled beyond its bounds.
+        let max = self.scroll_max.get();
+        let mut position = self.scroll_value + delta;
+        for i in 0..2 {
+            position[i] = math::clampf(position[i], 0.0, max[i]);
+        }
+        self.scroll_to(sender, position);
+    }
+
+    /// Scrolls content to given position, parts of position that are out of bounds are
+    /// stored as overscroll.
+    fn scroll_to(&mut self, sender: &Sender<UiMessage<M, C>>, position: Vector2<f32>) {
+        let max = self.scroll_max.get();
+        let axes = [
+            (
+                self.h_scroll_bar,
+                ScrollPanelMessage::horizontal_scroll as fn(_, _, _) -> _,
+            ),
+            (self.v_scroll_bar, ScrollPanelMessage::vertical_scroll),
+        ];
+        for (i, &(scroll_bar, make_message)) in axes.iter().enumerate() {
+            if scroll_bar.is_none() || max[i] <= 0.0 {
+                self.overscroll[i] = 0.0;
+                continue;
+            }
+            let value = math::clampf(position[i], 0.0, max[i]);
+            let overscroll = position[i] - value;
+            if (value - self.scroll_value[i]).abs() > f32::EPSILON
+                || (overscroll - self.overscroll[i]).abs() > f32::EPSILON
+            {
+                self.scroll_value[i] = value;
+                self.overscroll[i] = overscroll;
+                // Scroll bar will be synced by the scroll viewer, see ScrollPanel branch in
+                // handle_routed_message.
+                sender
+                    .send(make_message(
+                        self.scroll_panel,
+                        MessageDirection::ToWidget,
+                        position[i],
+                    ))
+                    .unwrap();
+            }
+        }
+    }
 }
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollViewer<M, C> {
@@ -60,11 +407,27 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollViewer<M, C> {
         node_map.resolve(&mut self.scroll_panel);
         node_map.resolve(&mut self.v_scroll_bar);
         node_map.resolve(&mut self.h_scroll_bar);
+        node_map.resolve(&mut self.header);
+        node_map.resolve(&mut self.header_panel);
     }
 
     fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
         let size = self.widget.arrange_override(ui, final_size);
 
+        if self.scroll_panel.is_some() {
+            // Accumulate offsets of content presenter up to the scroll viewer.
+            let mut position = Vector2::default();
+            let mut handle = self.scroll_panel;
+            while handle.is_some() && handle != self.handle() {
+                let node = ui.node(handle);
+                position += node.actual_local_position();
+                handle = node.parent();
+            }
+            let size = ui.node(self.scroll_panel).actual_size();
+            self.viewport
+                .set(Rect::new(position.x, position.y, size.x, size.y));
+        }
+
         if self.content.is_some() {
             let content_size = ui.node(self.content).desired_size();
             let available_size_for_content = ui.node(self.scroll_panel).desired_size();
@@ -77,16 +440,92 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollViewer<M, C> {
             ));
 
             let y_max = (content_size.y - available_size_for_content.y).max(0.0);
+            self.scroll_max.set(Vector2::new(x_max, y_max));
             ui.send_message(ScrollBarMessage::max_value(
                 self.v_scroll_bar,
                 MessageDirection::ToWidget,
                 y_max,
             ));
+
+            // Click on the track of a scroll bar scrolls by a viewport.
+            let viewport = self.viewport.get();
+            ui.send_message(ScrollBarMessage::page_size(
+                self.h_scroll_bar,
+                MessageDirection::ToWidget,
+                viewport.w(),
+            ));
+            ui.send_message(ScrollBarMessage::page_size(
+                self.v_scroll_bar,
+                MessageDirection::ToWidget,
+                viewport.h(),
+            ));
         }
 
         size
     }
 
+    fn draw_overlay(&self, drawing_context: &mut DrawingContext) {
+        if !self.fade_edges || self.fade_size <= 0.0 {
+            return;
+        }
+
+        let viewport = self.viewport.get();
+        let bounds = Rect::new(
+            self.widget.screen_position().x + viewport.x(),
+            self.widget.screen_position().y + viewport.y(),
+            viewport.w(),
+            viewport.h(),
+        );
+        let max = self.scroll_max.get();
+        let value = self.scroll_value;
+        let opaque = self.fade_color;
+        let transparent = Color::from_rgba(opaque.r, opaque.g, opaque.b, 0);
+        let size_x = self.fade_size.min(bounds.w() * 0.5);
+        let size_y = self.fade_size.min(bounds.h() * 0.5);
+
+        if value.y > f32::EPSILON {
+            drawing_context.push_rect_multicolor(
+                &Rect::new(bounds.x(), bounds.y(), bounds.w(), size_y),
+                [opaque, opaque, transparent, transparent],
+            );
+        }
+        if max.y - value.y > f32::EPSILON {
+            drawing_context.push_rect_multicolor(
+                &Rect::new(
+                    bounds.x(),
+                    bounds.y() + bounds.h() - size_y,
+                    bounds.w(),
+                    size_y,
+                ),
+                [transparent, transparent, opaque, opaque],
+            );
+        }
+        if value.x > f32::EPSILON {
+            drawing_context.push_rect_multicolor(
+                &Rect::new(bounds.x(), bounds.y(), size_x, bounds.h()),
+                [opaque, transparent, transparent, opaque],
+            );
+        }
+        if max.x - value.x > f32::EPSILON {
+            drawing_context.push_rect_multicolor(
+                &Rect::new(
+                    bounds.x() + bounds.w() - size_x,
+                    bounds.y(),
+                    size_x,
+                    bounds.h(),
+                ),
+                [transparent, opaque, opaque, transparent],
+            );
+        }
+
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(Color::WHITE),
+            CommandTexture::None,
+            None,
+        );
+    }
+
     fn handle_routed_message(
         &mut self,
         ui: &mut UserInterface<M, C>,
@@ -95,6 +534,41 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollViewer<M, C> {
         self.widget.handle_routed_message(ui, message);
 
         match &message.data() {
+            UiMessageData::Widget(WidgetMessage::MouseDown { pos, button })
+                if self.drag_scroll_enabled && *button == MouseButton::Left =>
+            {
+                self.mouse_drag = Some((*pos, *pos));
+            }
+            UiMessageData::Widget(WidgetMessage::MouseMove { pos, state }) => {
+                let pos = *pos;
+                if let Some((origin, last)) = self.mouse_drag {
+                    if state.left != ButtonState::Pressed {
+                        self.mouse_drag = None;
+                    } else {
+                        if !self.held && (pos - origin).norm() > PAN_THRESHOLD {
+                            // Steal mouse from pressed widget, so it won't be clicked. The widget
+                            // is notified, so it won't stay pressed either.
+                            ui.cancel_mouse_capture(message.destination());
+                            ui.capture_mouse(self.handle());
+                            self.begin_drag();
+                        }
+                        if self.held {
+                            self.drag_by(&ui.sender, pos - last);
+                            message.set_handled(true);
+                        }
+                        self.mouse_drag = Some((origin, pos));
+                    }
+                }
+            }
+            UiMessageData::Widget(WidgetMessage::MouseUp { button, .. })
+                if *button == MouseButton::Left && self.mouse_drag.is_some() =>
+            {
+                self.mouse_drag = None;
+                if self.held {
+                    ui.release_mouse_capture();
+                    self.end_drag(true);
+                }
+            }
             UiMessageData::Widget(WidgetMessage::MouseWheel { amount, .. }) => {
                 if self.v_scroll_bar.is_some() && !message.handled() {
                     if let UINode::ScrollBar(v_scroll_bar) = ui.node(self.v_scroll_bar) {
@@ -136,7 +610,33 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollViewer<M, C> {
             {
                 match msg {
                     ScrollBarMessage::Value(new_value) => {
+                        if message.destination() == self.v_scroll_bar {
+                            self.scroll_value.y = *new_value;
+                        } else if message.destination() == self.h_scroll_bar {
+                            self.scroll_value.x = *new_value;
+                            if self.header_panel.is_some() {
+                                ui.send_message(ScrollPanelMessage::horizontal_scroll(
+                                    self.header_panel,
+                                    MessageDirection::ToWidget,
+                                    *new_value,
+                                ));
+                            }
+                        }
+
                         if !message.handled() {
+                            // Scrolled by something else, stop kinetic scrolling on that axis.
+                            let axis = if message.destination() == self.v_scroll_bar {
+                                Some(1)
+                            } else if message.destination() == self.h_scroll_bar {
+                                Some(0)
+                            } else {
+                                None
+                            };
+                            if let Some(axis) = axis {
+                                self.velocity[axis] = 0.0;
+                                self.overscroll[axis] = 0.0;
+                            }
+
                             if message.destination() == self.v_scroll_bar
                                 && self.v_scroll_bar.is_some()
                             {
@@ -202,6 +702,25 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollViewer<M, C> {
                                 MessageDirection::ToWidget,
                                 self.scroll_panel,
                             ));
+                            self.content = *content;
+                        }
+                        &ScrollViewerMessage::Header(header) => {
+                            if self.header_panel.is_some() {
+                                if self.header.is_some() {
+                                    ui.send_message(WidgetMessage::remove(
+                                        self.header,
+                                        MessageDirection::ToWidget,
+                                    ));
+                                }
+                                if header.is_some() {
+                                    ui.send_message(WidgetMessage::link(
+                                        header,
+                                        MessageDirection::ToWidget,
+                                        self.header_panel,
+                                    ));
+                                }
+                                self.header = header;
+                            }
                         }
                         &ScrollViewerMessage::BringIntoView(handle) => {
                             // Re-cast message to inner panel.
@@ -211,6 +730,43 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollViewer<M, C> {
                                 handle,
                             ));
                         }
+                        &ScrollViewerMessage::HorizontalScroll(state) => {
+                            // Header must be laid out the same way as content.
+                            for &panel in &[self.scroll_panel, self.header_panel] {
+                                if panel.is_some() {
+                                    ui.send_message(ScrollPanelMessage::horizontal_scroll_allowed(
+                                        panel,
+                                        MessageDirection::ToWidget,
+                                        state,
+                                    ));
+                                }
+                            }
+                        }
+                        &ScrollViewerMessage::VerticalScroll(state) => {
+                            ui.send_message(ScrollPanelMessage::vertical_scroll_allowed(
+                                self.scroll_panel,
+                                MessageDirection::ToWidget,
+                                state,
+                            ));
+                        }
+                        &ScrollViewerMessage::StretchContentWidth(state) => {
+                            for &panel in &[self.scroll_panel, self.header_panel] {
+                                if panel.is_some() {
+                                    ui.send_message(ScrollPanelMessage::stretch_content_width(
+                                        panel,
+                                        MessageDirection::ToWidget,
+                                        state,
+                                    ));
+                                }
+                            }
+                        }
+                        &ScrollViewerMessage::StretchContentHeight(state) => {
+                            ui.send_message(ScrollPanelMessage::stretch_content_height(
+                                self.scroll_panel,
+                                MessageDirection::ToWidget,
+                                state,
+                            ));
+                        }
                     }
                 }
             }
@@ -231,14 +787,33 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for ScrollViewer<M, C> {
         if self.scroll_panel == handle {
             self.scroll_panel = Handle::NONE;
         }
+        if self.header == handle {
+            self.header = Handle::NONE;
+        }
+        if self.header_panel == handle {
+            self.header_panel = Handle::NONE;
+        }
     }
 }
 
 pub struct ScrollViewerBuilder<M: MessageData, C: Control<M, C>> {
     widget_builder: WidgetBuilder<M, C>,
     content: Handle<UINode<M, C>>,
+    header: Handle<UINode<M, C>>,
     h_scroll_bar: Option<Handle<UINode<M, C>>>,
     v_scroll_bar: Option<Handle<UINode<M, C>>>,
+    fade_edges: bool,
+    fade_size: f32,
+    fade_color: Color,
+    inertia_enabled: bool,
+    friction: f32,
+    drag_scroll_enabled: bool,
+    drag_edge_margin: f32,
+    drag_edge_max_speed: f32,
+    horizontal_scroll: bool,
+    vertical_scroll: bool,
+    stretch_content_width: bool,
+    stretch_content_height: bool,
 }
 
 impl<M: MessageData, C: Control<M, C>> ScrollViewerBuilder<M, C> {
@@ -246,16 +821,102 @@ impl<M: MessageData, C: Control<M, C>> ScrollViewerBuilder<M, C> {
         Self {
             widget_builder,
             content: Handle::NONE,
+            header: Handle::NONE,
             h_scroll_bar: None,
             v_scroll_bar: None,
+            fade_edges: false,
+            fade_size: DEFAULT_FADE_SIZE,
+            fade_color: COLOR_DARK,
+            inertia_enabled: true,
+            friction: DEFAULT_FRICTION,
+            drag_scroll_enabled: false,
+            drag_edge_margin: DEFAULT_DRAG_EDGE_MARGIN,
+            drag_edge_max_speed: DEFAULT_DRAG_EDGE_MAX_SPEED,
+            horizontal_scroll: false,
+            vertical_scroll: true,
+            stretch_content_width: true,
+            stretch_content_height: true,
         }
     }
 
+    /// Allows to scroll content horizontally, disabled by default.
+    pub fn with_horizontal_scroll(mut self, horizontal_scroll: bool) -> Self {
+        self.horizontal_scroll = horizontal_scroll;
+        self
+    }
+
+    /// Allows to scroll content vertically, enabled by default.
+    pub fn with_vertical_scroll(mut self, vertical_scroll: bool) -> Self {
+        self.vertical_scroll = vertical_scroll;
+        self
+    }
+
+    /// Stretches content to width of viewport (default), which is common for vertical lists.
+    /// Otherwise content keeps its own width, for example a 2D canvas.
+    pub fn with_stretch_content_width(mut self, stretch: bool) -> Self {
+        self.stretch_content_width = stretch;
+        self
+    }
+
+    /// Stretches content to height of viewport (default), otherwise content keeps its own
+    /// height.
+    pub fn with_stretch_content_height(mut self, stretch: bool) -> Self {
+        self.stretch_content_height = stretch;
+        self
+    }
+
+    pub fn with_inertia(mut self, inertia_enabled: bool) -> Self {
+        self.inertia_enabled = inertia_enabled;
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_drag_scroll(mut self, drag_scroll_enabled: bool) -> Self {
+        self.drag_scroll_enabled = drag_scroll_enabled;
+        self
+    }
+
+    pub fn with_drag_edge_margin(mut self, margin: f32) -> Self {
+        self.drag_edge_margin = margin;
+        self
+    }
+
+    pub fn with_drag_edge_max_speed(mut self, speed: f32) -> Self {
+        self.drag_edge_max_speed = speed;
+        self
+    }
+
+    pub fn with_fade_edges(mut self, fade_edges: bool) -> Self {
+        self.fade_edges = fade_edges;
+        self
+    }
+
+    pub fn with_fade_size(mut self, size: f32) -> Self {
+        self.fade_size = size;
+        self
+    }
+
+    pub fn with_fade_color(mut self, color: Color) -> Self {
+        self.fade_color = color;
+        self
+    }
+
     pub fn with_content(mut self, content: Handle<UINode<M, C>>) -> Self {
         self.content = content;
         self
     }
 
+    /// Sets node that is shown above content and does not scroll vertically, see
+    /// `ScrollViewerMessage::Header`. Row for the header is created only if it is set.
+    pub fn with_header(mut self, header: Handle<UINode<M, C>>) -> Self {
+        self.header = header;
+        self
+    }
+
     pub fn with_vertical_scroll_bar(mut self, v_scroll_bar: Handle<UINode<M, C>>) -> Self {
         self.v_scroll_bar = Some(v_scroll_bar);
         self
@@ -267,27 +928,54 @@ impl<M: MessageData, C: Control<M, C>> ScrollViewerBuilder<M, C> {
     }
 
     pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        // Row of header exists only if there is a header.
+        let content_row = if self.header.is_some() { 1 } else { 0 };
+
         let content_presenter = ScrollPanelBuilder::new(
             WidgetBuilder::new()
                 .with_child(self.content)
-                .on_row(0)
+                .on_row(content_row)
                 .on_column(0),
         )
+        .with_horizontal_scroll_allowed(self.horizontal_scroll)
+        .with_vertical_scroll_allowed(self.vertical_scroll)
+        .with_stretch_content_width(self.stretch_content_width)
+        .with_stretch_content_height(self.stretch_content_height)
         .build(ctx);
 
+        let header_panel = if self.header.is_some() {
+            ScrollPanelBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(self.header)
+                    .on_row(0)
+                    .on_column(0),
+            )
+            .with_horizontal_scroll_allowed(self.horizontal_scroll)
+            .with_vertical_scroll_allowed(false)
+            .with_stretch_content_width(self.stretch_content_width)
+            .build(ctx)
+        } else {
+            Handle::NONE
+        };
+
         let v_scroll_bar = self.v_scroll_bar.unwrap_or_else(|| {
             ScrollBarBuilder::new(WidgetBuilder::new().with_width(22.0))
                 .with_orientation(Orientation::Vertical)
                 .build(ctx)
         });
-        ctx[v_scroll_bar].set_row(0).set_column(1);
+        ctx[v_scroll_bar].set_row(content_row).set_column(1);
 
         let h_scroll_bar = self.h_scroll_bar.unwrap_or_else(|| {
             ScrollBarBuilder::new(WidgetBuilder::new().with_height(22.0))
                 .with_orientation(Orientation::Horizontal)
                 .build(ctx)
         });
-        ctx[h_scroll_bar].set_row(1).set_column(0);
+        ctx[h_scroll_bar].set_row(content_row + 1).set_column(0);
+
+        let mut rows = vec![Row::stretch(), Row::auto()];
+        if header_panel.is_some() {
+            rows.insert(0, Row::auto());
+        }
 
         let sv = ScrollViewer {
             widget: self
@@ -295,12 +983,12 @@ impl<M: MessageData, C: Control<M, C>> ScrollViewerBuilder<M, C> {
                 .with_child(
                     GridBuilder::new(
                         WidgetBuilder::new()
+                            .with_child(header_panel)
                             .with_child(content_presenter)
                             .with_child(h_scroll_bar)
                             .with_child(v_scroll_bar),
                     )
-                    .add_row(Row::stretch())
-                    .add_row(Row::auto())
+                    .add_rows(rows)
                     .add_column(Column::stretch())
                     .add_column(Column::auto())
                     .build(ctx),
@@ -310,7 +998,345 @@ impl<M: MessageData, C: Control<M, C>> ScrollViewerBuilder<M, C> {
             v_scroll_bar,
             h_scroll_bar,
             scroll_panel: content_presenter,
+            header: self.header,
+            header_panel,
+            fade_edges: self.fade_edges,
+            fade_size: self.fade_size.max(0.0),
+            fade_color: self.fade_color,
+            scroll_value: Default::default(),
+            scroll_max: Default::default(),
+            viewport: Default::default(),
+            inertia_enabled: self.inertia_enabled,
+            friction: self.friction.max(0.0),
+            drag_scroll_enabled: self.drag_scroll_enabled,
+            held: false,
+            velocity: Default::default(),
+            frame_offset: Default::default(),
+            overscroll: Default::default(),
+            mouse_drag: None,
+            drag_edge_margin: self.drag_edge_margin.max(0.0),
+            drag_edge_max_speed: self.drag_edge_max_speed.max(0.0),
         };
         ctx.add_node(UINode::ScrollViewer(sv))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        button::ButtonBuilder,
+        core::algebra::Vector2,
+        message::{
+            ButtonMessage, ButtonState, MessageDirection, MouseButton, OsEvent, ScrollBarMessage,
+            UiMessageData, WidgetMessage,
+        },
+        node::StubNode,
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        widget::WidgetBuilder,
+        BuildContext, UINode, UserInterface,
+    };
+
+    #[test]
+    fn header() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+
+        let header = BorderBuilder::new(WidgetBuilder::new().with_width(500.0).with_height(20.0))
+            .build(&mut ui.build_ctx());
+        let content = BorderBuilder::new(WidgetBuilder::new().with_width(500.0).with_height(500.0))
+            .build(&mut ui.build_ctx());
+        // Real scroll bars need a font, so dummies are used, they only pass values to the
+        // scroll viewer.
+        let h_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        let v_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        ScrollViewerBuilder::new(WidgetBuilder::new().with_width(200.0).with_height(200.0))
+            .with_header(header)
+            .with_content(content)
+            .with_horizontal_scroll_bar(h_scroll_bar)
+            .with_vertical_scroll_bar(v_scroll_bar)
+            .with_horizontal_scroll(true)
+            .with_stretch_content_width(false)
+            .with_stretch_content_height(false)
+            .build(&mut ui.build_ctx());
+
+        let update = |ui: &mut UserInterface<(), StubNode>| {
+            for _ in 0..3 {
+                ui.update(screen_size, 0.0);
+                while ui.poll_message().is_some() {}
+            }
+        };
+        update(&mut ui);
+        let header_position = ui.node(header).screen_position();
+        let content_position = ui.node(content).screen_position();
+        assert_eq!(header_position.y + 20.0, content_position.y);
+
+        ui.send_message(ScrollBarMessage::value(
+            h_scroll_bar,
+            MessageDirection::FromWidget,
+            30.0,
+        ));
+        update(&mut ui);
+        assert_eq!(
+            ui.node(header).screen_position(),
+            header_position - Vector2::new(30.0, 0.0)
+        );
+        assert_eq!(
+            ui.node(content).screen_position(),
+            content_position - Vector2::new(30.0, 0.0)
+        );
+
+        ui.send_message(ScrollBarMessage::value(
+            v_scroll_bar,
+            MessageDirection::FromWidget,
+            40.0,
+        ));
+        update(&mut ui);
+        assert_eq!(
+            ui.node(header).screen_position(),
+            header_position - Vector2::new(30.0, 0.0)
+        );
+        assert_eq!(
+            ui.node(content).screen_position(),
+            content_position - Vector2::new(30.0, 40.0)
+        );
+    }
+
+    #[test]
+    fn drag_edge_scroll() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let content = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(100.0)
+                .with_height(1000.0)
+                .with_allow_drag(true),
+        )
+        .build(ctx);
+        let h_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let v_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let scroll_viewer =
+            ScrollViewerBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+                .with_content(content)
+                .with_horizontal_scroll_bar(h_scroll_bar)
+                .with_vertical_scroll_bar(v_scroll_bar)
+                .with_stretch_content_height(false)
+                .with_drag_edge_margin(20.0)
+                .build(ctx);
+
+        let update = |ui: &mut UserInterface<(), StubNode>, dt: f32| {
+            ui.update(screen_size, dt);
+            while ui.poll_message().is_some() {}
+            ui.draw();
+        };
+        let scroll = |ui: &UserInterface<(), StubNode>| {
+            if let UINode::ScrollViewer(scroll_viewer) = ui.node(scroll_viewer) {
+                scroll_viewer.scroll_value.y
+            } else {
+                unreachable!()
+            }
+        };
+        let move_cursor = |ui: &mut UserInterface<(), StubNode>, y: f32| {
+            ui.process_os_event(&OsEvent::CursorMoved {
+                position: Vector2::new(50.0, y),
+            });
+        };
+        update(&mut ui, 0.0);
+        update(&mut ui, 0.0);
+
+        // Start drag and drop of content and bring it to the bottom edge.
+        move_cursor(&mut ui, 50.0);
+        ui.process_os_event(&OsEvent::MouseInput {
+            button: MouseButton::Left,
+            state: ButtonState::Pressed,
+        });
+        move_cursor(&mut ui, 95.0);
+        update(&mut ui, 0.1);
+        update(&mut ui, 0.1);
+        let scrolled = scroll(&ui);
+        assert!(scrolled > 0.0);
+
+        // Cursor left the margin.
+        move_cursor(&mut ui, 50.0);
+        update(&mut ui, 0.1);
+        assert_eq!(scroll(&ui), scrolled);
+    }
+
+    #[test]
+    fn drag_edge_scroll_innermost() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let content = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_height(1000.0)
+                .with_allow_drag(true),
+        )
+        .build(ctx);
+        let scroll_viewer = |ctx: &mut BuildContext<(), StubNode>, content| {
+            let h_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+            let v_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+            ScrollViewerBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+                .with_content(content)
+                .with_horizontal_scroll_bar(h_scroll_bar)
+                .with_vertical_scroll_bar(v_scroll_bar)
+                .with_stretch_content_height(false)
+                .with_drag_edge_margin(20.0)
+                .build(ctx)
+        };
+        let inner = scroll_viewer(ctx, content);
+        let filler = BorderBuilder::new(WidgetBuilder::new().with_height(900.0)).build(ctx);
+        let outer_content =
+            StackPanelBuilder::new(WidgetBuilder::new().with_child(inner).with_child(filler))
+                .build(ctx);
+        let outer = scroll_viewer(ctx, outer_content);
+
+        let update = |ui: &mut UserInterface<(), StubNode>, dt: f32| {
+            ui.update(screen_size, dt);
+            while ui.poll_message().is_some() {}
+            ui.draw();
+        };
+        let scroll = |ui: &UserInterface<(), StubNode>, handle| {
+            if let UINode::ScrollViewer(scroll_viewer) = ui.node(handle) {
+                scroll_viewer.scroll_value.y
+            } else {
+                unreachable!()
+            }
+        };
+        update(&mut ui, 0.0);
+        update(&mut ui, 0.0);
+
+        // Bottom edges of both viewers coincide.
+        for event in [
+            OsEvent::CursorMoved {
+                position: Vector2::new(50.0, 50.0),
+            },
+            OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ButtonState::Pressed,
+            },
+            OsEvent::CursorMoved {
+                position: Vector2::new(50.0, 95.0),
+            },
+        ]
+        .iter()
+        {
+            ui.process_os_event(event);
+        }
+        update(&mut ui, 0.1);
+        update(&mut ui, 0.1);
+        assert!(scroll(&ui, inner) > 0.0);
+        assert_eq!(scroll(&ui, outer), 0.0);
+    }
+
+    #[test]
+    fn drag_scroll_cancels_press() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let button_content = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let button = ButtonBuilder::new(WidgetBuilder::new().with_height(1000.0))
+            .with_content(button_content)
+            .build(ctx);
+        let h_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let v_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let scroll_viewer =
+            ScrollViewerBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+                .with_content(button)
+                .with_horizontal_scroll_bar(h_scroll_bar)
+                .with_vertical_scroll_bar(v_scroll_bar)
+                .with_stretch_content_height(false)
+                .with_drag_scroll(true)
+                .build(ctx);
+        ui.update(screen_size, 0.0);
+        ui.draw();
+
+        let mut clicks = 0;
+        let mut capture_lost = false;
+        for event in [
+            OsEvent::CursorMoved {
+                position: Vector2::new(50.0, 80.0),
+            },
+            OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ButtonState::Pressed,
+            },
+            OsEvent::CursorMoved {
+                position: Vector2::new(50.0, 30.0),
+            },
+            OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ButtonState::Released,
+            },
+        ]
+        .iter()
+        {
+            ui.process_os_event(event);
+            while let Some(message) = ui.poll_message() {
+                match message.data() {
+                    UiMessageData::Button(ButtonMessage::Click) => clicks += 1,
+                    UiMessageData::Widget(WidgetMessage::MouseCaptureLost) => {
+                        capture_lost |= message.destination() != scroll_viewer;
+                    }
+                    _ => (),
+                }
+            }
+        }
+        assert!(capture_lost);
+        assert_eq!(clicks, 0);
+    }
+
+    #[test]
+    fn disabling_input_releases_held_content() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let content = BorderBuilder::new(WidgetBuilder::new().with_height(1000.0)).build(ctx);
+        let h_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let v_scroll_bar = BorderBuilder::new(WidgetBuilder::new()).build(ctx);
+        let scroll_viewer =
+            ScrollViewerBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(100.0))
+                .with_content(content)
+                .with_horizontal_scroll_bar(h_scroll_bar)
+                .with_vertical_scroll_bar(v_scroll_bar)
+                .with_stretch_content_height(false)
+                .with_drag_scroll(true)
+                .build(ctx);
+        ui.update(screen_size, 0.0);
+        ui.draw();
+
+        for event in [
+            OsEvent::CursorMoved {
+                position: Vector2::new(50.0, 80.0),
+            },
+            OsEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ButtonState::Pressed,
+            },
+            OsEvent::CursorMoved {
+                position: Vector2::new(50.0, 30.0),
+            },
+        ]
+        .iter()
+        {
+            ui.process_os_event(event);
+            while ui.poll_message().is_some() {}
+        }
+        let is_held = |ui: &UserInterface<(), StubNode>| match ui.node(scroll_viewer) {
+            UINode::ScrollViewer(scroll_viewer) => scroll_viewer.held,
+            _ => unreachable!(),
+        };
+        assert!(is_held(&ui));
+
+        ui.set_input_enabled(false);
+        assert!(!is_held(&ui));
+        ui.set_input_enabled(true);
+        ui.process_os_event(&OsEvent::CursorMoved {
+            position: Vector2::new(50.0, 10.0),
+        });
+        while ui.poll_message().is_some() {}
+        assert!(!is_held(&ui));
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     core::{math::Rect, pool::Handle, scope_profile},
     message::UiMessage,
     widget::{Widget, WidgetBuilder},
-    BuildContext, Control, Orientation, UINode, UserInterface,
+    BaselineGroup, BuildContext, Control, Orientation, UINode, UserInterface, VerticalAlignment,
 };
 use std::ops::{Deref, DerefMut};
 
@@ -12,6 +12,7 @@ use std::ops::{Deref, DerefMut};
 pub struct StackPanel<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     orientation: Orientation,
+    spacing: f32,
 }
 
 crate::define_widget_deref!(StackPanel<M, C>);
@@ -21,6 +22,7 @@ impl<M: MessageData, C: Control<M, C>> StackPanel<M, C> {
         Self {
             widget,
             orientation: Orientation::Vertical,
+            spacing: 0.0,
         }
     }
 
@@ -34,6 +36,33 @@ impl<M: MessageData, C: Control<M, C>> StackPanel<M, C> {
     pub fn orientation(&self) -> Orientation {
         self.orientation
     }
+
+    /// Sets gap between adjacent visible children along orientation axis. There is no gap
+    /// before first and after last child, invisible children do not produce gaps.
+    pub fn set_spacing(&mut self, spacing: f32) {
+        if self.spacing != spacing {
+            self.spacing = spacing;
+            self.widget.invalidate_layout();
+        }
+    }
+
+    pub fn spacing(&self) -> f32 {
+        self.spacing
+    }
+
+    /// Collects visible children of horizontal panel that are aligned by baseline.
+    fn baseline_group(&self, ui: &UserInterface<M, C>) -> BaselineGroup {
+        let mut group = BaselineGroup::default();
+        if self.orientation == Orientation::Horizontal {
+            for child_handle in self.widget.children() {
+                let child = ui.node(*child_handle);
+                if child.visibility() && child.vertical_alignment() == VerticalAlignment::Baseline {
+                    group.add(child, ui);
+                }
+            }
+        }
+        group
+    }
 }
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for StackPanel<M, C> {
@@ -74,26 +103,35 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for StackPanel<M, C> {
         }
 
         let mut measured_size = Vector2::default();
+        let mut gap = 0.0;
 
         for child_handle in self.widget.children() {
             ui.node(*child_handle).measure(ui, child_constraint);
 
             let child = ui.node(*child_handle);
+            if !child.visibility() {
+                continue;
+            }
             let desired = child.desired_size();
             match self.orientation {
                 Orientation::Vertical => {
                     if desired.x > measured_size.x {
                         measured_size.x = desired.x;
                     }
-                    measured_size.y += desired.y;
+                    measured_size.y += gap + desired.y;
                 }
                 Orientation::Horizontal => {
-                    measured_size.x += desired.x;
+                    measured_size.x += gap + desired.x;
                     if desired.y > measured_size.y {
                         measured_size.y = desired.y;
                     }
                 }
             }
+            gap = self.spacing;
+        }
+
+        if self.orientation == Orientation::Horizontal {
+            measured_size.y = measured_size.y.max(self.baseline_group(ui).height());
         }
 
         measured_size
@@ -110,8 +148,18 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for StackPanel<M, C> {
             Orientation::Horizontal => width = 0.0,
         }
 
+        let mut gap = 0.0;
+        let baseline_group = self.baseline_group(ui);
+
         for child_handle in self.widget.children() {
             let child = ui.node(*child_handle);
+            if child.visibility() {
+                match self.orientation {
+                    Orientation::Vertical => height += gap,
+                    Orientation::Horizontal => width += gap,
+                }
+                gap = self.spacing;
+            }
             match self.orientation {
                 Orientation::Vertical => {
                     let child_bounds = Rect::new(
@@ -125,12 +173,22 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for StackPanel<M, C> {
                     height += child.desired_size().y;
                 }
                 Orientation::Horizontal => {
-                    let child_bounds = Rect::new(
-                        width,
-                        0.0,
-                        child.desired_size().x,
-                        height.max(child.desired_size().y),
-                    );
+                    let child_bounds = if child.vertical_alignment() == VerticalAlignment::Baseline
+                    {
+                        Rect::new(
+                            width,
+                            baseline_group.offset(child, ui),
+                            child.desired_size().x,
+                            child.desired_size().y,
+                        )
+                    } else {
+                        Rect::new(
+                            width,
+                            0.0,
+                            child.desired_size().x,
+                            height.max(child.desired_size().y),
+                        )
+                    };
                     ui.node(*child_handle).arrange(ui, &child_bounds);
                     width += child.desired_size().x;
                     height = height.max(child.desired_size().y);
@@ -162,6 +220,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for StackPanel<M, C> {
 pub struct StackPanelBuilder<M: MessageData, C: Control<M, C>> {
     widget_builder: WidgetBuilder<M, C>,
     orientation: Option<Orientation>,
+    spacing: f32,
 }
 
 impl<M: MessageData, C: Control<M, C>> StackPanelBuilder<M, C> {
@@ -169,6 +228,7 @@ impl<M: MessageData, C: Control<M, C>> StackPanelBuilder<M, C> {
         Self {
             widget_builder,
             orientation: None,
+            spacing: 0.0,
         }
     }
 
@@ -177,12 +237,68 @@ impl<M: MessageData, C: Control<M, C>> StackPanelBuilder<M, C> {
         self
     }
 
+    /// See `StackPanel::set_spacing`.
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
     pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
         let stack_panel = StackPanel {
             widget: self.widget_builder.build(),
             orientation: self.orientation.unwrap_or(Orientation::Vertical),
+            spacing: self.spacing,
         };
 
         ctx.add_node(UINode::StackPanel(stack_panel))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder, core::algebra::Vector2, node::StubNode,
+        stack_panel::StackPanelBuilder, widget::WidgetBuilder, Orientation, UINode, UserInterface,
+    };
+
+    #[test]
+    fn spacing() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let items = (0..4)
+            .map(|i| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(30.0)
+                        .with_height(20.0)
+                        // Invisible item must not add a gap.
+                        .with_visibility(i != 2),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+        let panel = StackPanelBuilder::new(WidgetBuilder::new().with_children(items.iter()))
+            .with_orientation(Orientation::Vertical)
+            .with_spacing(10.0)
+            .build(ctx);
+        ui.update(screen_size, 0.0);
+
+        for &(item, y) in [(items[0], 0.0), (items[1], 30.0), (items[3], 60.0)].iter() {
+            assert_eq!(ui.node(item).actual_local_position(), Vector2::new(0.0, y));
+        }
+        assert_eq!(ui.node(panel).desired_size(), Vector2::new(30.0, 80.0));
+
+        if let UINode::StackPanel(panel) = &mut ui.nodes[panel] {
+            panel.set_spacing(5.0);
+            panel.set_orientation(Orientation::Horizontal);
+        }
+        ui.update(screen_size, 0.0);
+
+        assert_eq!(
+            ui.node(items[3]).actual_local_position(),
+            Vector2::new(70.0, 0.0)
+        );
+        assert_eq!(ui.node(panel).desired_size(), Vector2::new(100.0, 20.0));
+    }
+}
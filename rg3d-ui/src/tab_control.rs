@@ -1,5 +1,6 @@
 use crate::message::{MessageData, MessageDirection};
 use crate::{
+    accessibility::AccessibleRole,
     border::BorderBuilder,
     brush::Brush,
     button::ButtonBuilder,
@@ -26,6 +27,10 @@ pub struct TabControl<M: MessageData, C: Control<M, C>> {
 crate::define_widget_deref!(TabControl<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for TabControl<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::TabList
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         for tab in self.tabs.iter_mut() {
             node_map.resolve(&mut tab.header_button);
@@ -2,10 +2,11 @@ use crate::core::algebra::Vector2;
 use crate::message::MessageData;
 use crate::ttf::SharedFont;
 use crate::{
+    accessibility::AccessibleRole,
     brush::Brush,
-    core::{color::Color, pool::Handle},
-    draw::DrawingContext,
-    formatted_text::{FormattedText, FormattedTextBuilder},
+    core::{color::Color, math::Rect, pool::Handle},
+    draw::{CommandTexture, Draw, DrawingContext},
+    formatted_text::{FormattedText, FormattedTextBuilder, TextRun, TextTrimming, WrapMode},
     message::UiMessage,
     message::{TextMessage, UiMessageData},
     widget::{Widget, WidgetBuilder},
@@ -13,32 +14,85 @@ use crate::{
 };
 use std::{
     cell::RefCell,
-    ops::{Deref, DerefMut},
+    collections::HashMap,
+    ops::{Deref, DerefMut, Range},
 };
 
 #[derive(Clone)]
 pub struct Text<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     formatted_text: RefCell<FormattedText>,
+    /// Sorted ranges of characters that are drawn with colored background.
+    highlights: Vec<Range<usize>>,
+    highlight_brush: Brush,
+    /// Key of the text in localization table of the UI, see `set_localized`.
+    localization_key: Option<String>,
+    /// Key was changed, but text is not resolved yet.
+    localization_pending: bool,
 }
 
 crate::define_widget_deref!(Text<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for Text<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Text
+    }
+
+    fn accessible_name(&self) -> Option<String> {
+        Some(self.text())
+    }
+
+    fn baseline_offset(&self, _ui: &UserInterface<M, C>) -> Option<f32> {
+        Some(self.baseline())
+    }
+
     fn measure_override(
         &self,
-        _: &UserInterface<M, C>,
+        ui: &UserInterface<M, C>,
         available_size: Vector2<f32>,
     ) -> Vector2<f32> {
         self.formatted_text
             .borrow_mut()
             .set_constraint(available_size)
             .set_brush(self.widget.foreground())
+            .set_fallback_fonts(ui.fallback_fonts())
+            .set_raster_scale(ui.scale_factor())
             .build()
     }
 
     fn draw(&self, drawing_context: &mut DrawingContext) {
         let bounds = self.widget.screen_bounds();
+
+        if !self.highlights.is_empty() {
+            let text = self.formatted_text.borrow();
+            // Highlight is split by visual lines, so it works with wrapping too.
+            for line in text.get_lines() {
+                for range in self.highlights.iter() {
+                    let begin = range.start.max(line.begin);
+                    let end = range.end.min(line.end);
+                    if begin < end {
+                        let offset = text.get_range_width(line.begin..begin);
+                        let width = text.get_range_width(begin..end);
+                        drawing_context.push_rect_filled(
+                            &Rect::new(
+                                bounds.x() + line.x_offset + offset,
+                                bounds.y() + line.y_offset,
+                                width,
+                                line.height,
+                            ),
+                            None,
+                        );
+                    }
+                }
+            }
+            drawing_context.commit(
+                self.clip_bounds(),
+                self.highlight_brush.clone(),
+                CommandTexture::None,
+                None,
+            );
+        }
+
         drawing_context.draw_text(
             self.clip_bounds(),
             bounds.position,
@@ -57,30 +111,36 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Text<M, C> {
             if let UiMessageData::Text(msg) = &message.data() {
                 match msg {
                     TextMessage::Text(text) => {
+                        self.set_text(text);
+                    }
+                    TextMessage::Runs(runs) => {
+                        self.localization_key = None;
+                        self.set_runs(runs.clone());
+                    }
+                    TextMessage::Localized(key) => {
+                        let text = ui.localize(key).to_owned();
+                        self.localization_key = Some(key.clone());
+                        self.localization_pending = false;
                         self.formatted_text.borrow_mut().set_text(text);
                         self.invalidate_layout();
                     }
                     &TextMessage::Wrap(wrap) => {
-                        if self.formatted_text.borrow().is_wrap() != wrap {
-                            self.formatted_text.borrow_mut().set_wrap(wrap);
-                            self.invalidate_layout();
-                        }
+                        self.set_wrap(wrap);
+                    }
+                    &TextMessage::WrapMode(mode) => {
+                        self.set_wrap_mode(mode);
+                    }
+                    &TextMessage::Trimming(trimming) => {
+                        self.set_trimming(trimming);
                     }
                     TextMessage::Font(font) => {
-                        self.formatted_text.borrow_mut().set_font(font.clone());
-                        self.invalidate_layout();
+                        self.set_font(font.clone());
                     }
                     &TextMessage::HorizontalAlignment(horizontal_alignment) => {
-                        self.formatted_text
-                            .borrow_mut()
-                            .set_horizontal_alignment(horizontal_alignment);
-                        self.invalidate_layout();
+                        self.set_horizontal_alignment(horizontal_alignment);
                     }
                     &TextMessage::VerticalAlignment(vertical_alignment) => {
-                        self.formatted_text
-                            .borrow_mut()
-                            .set_vertical_alignment(vertical_alignment);
-                        self.invalidate_layout();
+                        self.set_vertical_alignment(vertical_alignment);
                     }
                 }
             }
@@ -97,13 +157,141 @@ impl<M: MessageData, C: Control<M, C>> Text<M, C> {
                     .with_font(crate::DEFAULT_FONT.clone())
                     .build(),
             ),
+            highlights: Default::default(),
+            highlight_brush: Brush::Solid(Color::TRANSPARENT),
+            localization_key: None,
+            localization_pending: false,
+        }
+    }
+
+    /// Sets ranges of characters (`(begin, end)`, end is exclusive) that will be drawn with
+    /// background of given color. Ranges are clamped to length of current text, overlapping
+    /// ranges are merged. Empty list removes every highlight.
+    pub fn set_highlights(&mut self, ranges: Vec<(usize, usize)>, color: Color) -> &mut Self {
+        let len = self.formatted_text.borrow().get_raw_text().len();
+        self.highlights = merge_ranges(ranges, len);
+        self.highlight_brush = Brush::Solid(color);
+        self
+    }
+
+    pub fn highlights(&self) -> &[Range<usize>] {
+        &self.highlights
+    }
+
+    /// Replaces text with a sequence of runs, each run has its own color and weight. Runs
+    /// are measured and wrapped as one continuous text.
+    pub fn set_runs(&mut self, runs: Vec<TextRun>) -> &mut Self {
+        self.formatted_text.borrow_mut().set_runs(&runs);
+        self.invalidate_layout();
+        self
+    }
+
+    /// Makes the text localized: it will be taken from localization table of the UI by given
+    /// key (key itself is shown if there is no such key in the table) and updated each time
+    /// the table changes, see `UserInterface::set_localization`. The text is resolved on next
+    /// update of the UI, `TextMessage::localized` resolves it immediately. Setting plain text
+    /// removes the key.
+    pub fn set_localized(&mut self, key: &str) -> &mut Self {
+        self.localization_key = Some(key.to_owned());
+        self.localization_pending = true;
+        self
+    }
+
+    pub fn localization_key(&self) -> Option<&str> {
+        self.localization_key.as_deref()
+    }
+
+    /// Re-resolves localized text from given table, does nothing if the text is not
+    /// localized.
+    pub(in crate) fn localize(&mut self, table: &HashMap<String, String>) {
+        self.localization_pending = false;
+        if let Some(key) = self.localization_key.as_ref() {
+            let text = table.get(key).unwrap_or(key);
+            if self.formatted_text.borrow().text() != *text {
+                self.formatted_text.borrow_mut().set_text(text);
+                self.invalidate_layout();
+            }
+        }
+    }
+
+    pub(in crate) fn is_localization_pending(&self) -> bool {
+        self.localization_pending
+    }
+
+    // Setters below change content of the text, so they invalidate layout of the node -
+    // the UI re-measures nodes with invalid layout on next update, there is no need to send
+    // any messages.
+
+    /// Sets new plain text, removes localization key and runs.
+    pub fn set_text<P: AsRef<str>>(&mut self, text: P) -> &mut Self {
+        self.localization_key = None;
+        self.formatted_text.borrow_mut().set_text(text);
+        self.invalidate_layout();
+        self
+    }
+
+    pub fn set_wrap(&mut self, wrap: bool) -> &mut Self {
+        if self.formatted_text.borrow().is_wrap() != wrap {
+            self.formatted_text.borrow_mut().set_wrap(wrap);
+            self.invalidate_layout();
         }
+        self
+    }
+
+    /// Sets the way of wrapping, `set_wrap` enables wrapping by letters.
+    pub fn set_wrap_mode(&mut self, mode: WrapMode) -> &mut Self {
+        if self.formatted_text.borrow().wrap_mode() != mode {
+            self.formatted_text.borrow_mut().set_wrap_mode(mode);
+            self.invalidate_layout();
+        }
+        self
+    }
+
+    pub fn set_trimming(&mut self, trimming: TextTrimming) -> &mut Self {
+        if self.formatted_text.borrow().trimming() != trimming {
+            self.formatted_text.borrow_mut().set_trimming(trimming);
+            self.invalidate_layout();
+        }
+        self
+    }
+
+    pub fn set_font(&mut self, font: SharedFont) -> &mut Self {
+        self.formatted_text.borrow_mut().set_font(font);
+        self.invalidate_layout();
+        self
+    }
+
+    pub fn set_vertical_alignment(&mut self, vertical_alignment: VerticalAlignment) -> &mut Self {
+        self.formatted_text
+            .borrow_mut()
+            .set_vertical_alignment(vertical_alignment);
+        self.invalidate_layout();
+        self
+    }
+
+    pub fn set_horizontal_alignment(
+        &mut self,
+        horizontal_alignment: HorizontalAlignment,
+    ) -> &mut Self {
+        self.formatted_text
+            .borrow_mut()
+            .set_horizontal_alignment(horizontal_alignment);
+        self.invalidate_layout();
+        self
     }
 
     pub fn is_wrap(&self) -> bool {
         self.formatted_text.borrow().is_wrap()
     }
 
+    pub fn wrap_mode(&self) -> WrapMode {
+        self.formatted_text.borrow().wrap_mode()
+    }
+
+    pub fn trimming(&self) -> TextTrimming {
+        self.formatted_text.borrow().trimming()
+    }
+
     pub fn text(&self) -> String {
         self.formatted_text.borrow().text()
     }
@@ -116,18 +304,53 @@ impl<M: MessageData, C: Control<M, C>> Text<M, C> {
         self.formatted_text.borrow().vertical_alignment()
     }
 
+    /// Returns distance from top of the text to baseline of its first line. It is based on
+    /// ascent of the font and is valid after measurement.
+    pub fn baseline(&self) -> f32 {
+        self.formatted_text.borrow().baseline()
+    }
+
+    /// Returns index of caret position that is nearest to given point in local coordinates
+    /// of the widget, see `FormattedText::hit_test_char`.
+    pub fn hit_test_char(&self, local_point: Vector2<f32>) -> usize {
+        self.formatted_text.borrow().hit_test_char(local_point)
+    }
+
     pub fn horizontal_alignment(&self) -> HorizontalAlignment {
         self.formatted_text.borrow().horizontal_alignment()
     }
 }
 
+/// Clamps given ranges to `len`, drops empty ones, sorts them and merges overlapping or
+/// adjacent ranges.
+fn merge_ranges(ranges: Vec<(usize, usize)>, len: usize) -> Vec<Range<usize>> {
+    let mut ranges = ranges
+        .into_iter()
+        .map(|(a, b)| a.min(b).min(len)..a.max(b).min(len))
+        .filter(|range| !range.is_empty())
+        .collect::<Vec<_>>();
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
 pub struct TextBuilder<M: MessageData, C: Control<M, C>> {
     widget_builder: WidgetBuilder<M, C>,
     text: Option<String>,
+    runs: Vec<TextRun>,
     font: Option<SharedFont>,
     vertical_text_alignment: VerticalAlignment,
     horizontal_text_alignment: HorizontalAlignment,
-    wrap: bool,
+    wrap: WrapMode,
+    trimming: TextTrimming,
+    localization_key: Option<String>,
 }
 
 impl<M: MessageData, C: Control<M, C>> TextBuilder<M, C> {
@@ -135,10 +358,13 @@ impl<M: MessageData, C: Control<M, C>> TextBuilder<M, C> {
         Self {
             widget_builder,
             text: None,
+            runs: Vec::new(),
             font: None,
             vertical_text_alignment: VerticalAlignment::Top,
             horizontal_text_alignment: HorizontalAlignment::Left,
-            wrap: false,
+            wrap: WrapMode::NoWrap,
+            trimming: TextTrimming::None,
+            localization_key: None,
         }
     }
 
@@ -147,6 +373,19 @@ impl<M: MessageData, C: Control<M, C>> TextBuilder<M, C> {
         self
     }
 
+    /// Sets key of localized text, it overrides text set by `with_text`. See
+    /// `Text::set_localized`.
+    pub fn with_localized<P: AsRef<str>>(mut self, key: P) -> Self {
+        self.localization_key = Some(key.as_ref().to_owned());
+        self
+    }
+
+    /// Sets text as a sequence of styled runs, it overrides text set by `with_text`.
+    pub fn with_runs(mut self, runs: Vec<TextRun>) -> Self {
+        self.runs = runs;
+        self
+    }
+
     pub fn with_font(mut self, font: SharedFont) -> Self {
         self.font = Some(font);
         self
@@ -167,8 +406,23 @@ impl<M: MessageData, C: Control<M, C>> TextBuilder<M, C> {
         self
     }
 
+    /// Enables or disables wrapping by letters, see `with_wrap_mode` for other modes.
     pub fn with_wrap(mut self, wrap: bool) -> Self {
-        self.wrap = wrap;
+        self.wrap = if wrap {
+            WrapMode::Letter
+        } else {
+            WrapMode::NoWrap
+        };
+        self
+    }
+
+    pub fn with_wrap_mode(mut self, mode: WrapMode) -> Self {
+        self.wrap = mode;
+        self
+    }
+
+    pub fn with_trimming(mut self, trimming: TextTrimming) -> Self {
+        self.trimming = trimming;
         self
     }
 
@@ -176,24 +430,37 @@ impl<M: MessageData, C: Control<M, C>> TextBuilder<M, C> {
         let font = if let Some(font) = self.font {
             font
         } else {
-            crate::DEFAULT_FONT.clone()
+            ui.ui.default_font()
         };
 
         if self.widget_builder.foreground.is_none() {
             self.widget_builder.foreground = Some(Brush::Solid(Color::opaque(220, 220, 220)));
         }
 
+        let text = match self.localization_key.as_ref() {
+            Some(key) => ui.ui.localize(key).to_owned(),
+            None => self.text.unwrap_or_default(),
+        };
+
+        let mut formatted_text = FormattedTextBuilder::new()
+            .with_text(text)
+            .with_vertical_alignment(self.vertical_text_alignment)
+            .with_horizontal_alignment(self.horizontal_text_alignment)
+            .with_font(font)
+            .with_wrap_mode(self.wrap)
+            .build();
+        formatted_text.set_trimming(self.trimming);
+        if !self.runs.is_empty() {
+            formatted_text.set_runs(&self.runs);
+        }
+
         let text = Text {
             widget: self.widget_builder.build(),
-            formatted_text: RefCell::new(
-                FormattedTextBuilder::new()
-                    .with_text(self.text.unwrap_or_default())
-                    .with_vertical_alignment(self.vertical_text_alignment)
-                    .with_horizontal_alignment(self.horizontal_text_alignment)
-                    .with_font(font)
-                    .with_wrap(self.wrap)
-                    .build(),
-            ),
+            formatted_text: RefCell::new(formatted_text),
+            highlights: Default::default(),
+            highlight_brush: Brush::Solid(Color::TRANSPARENT),
+            localization_key: self.localization_key,
+            localization_pending: false,
         };
         ui.add_node(UINode::Text(text))
     }
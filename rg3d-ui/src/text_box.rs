@@ -1,5 +1,6 @@
 use crate::draw::Draw;
 use crate::{
+    accessibility::AccessibleRole,
     brush::Brush,
     core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
     draw::{CommandTexture, DrawingContext},
@@ -91,6 +92,212 @@ impl SelectionRange {
 
 pub type FilterCallback = dyn FnMut(char) -> bool;
 
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum CharClass {
+    Whitespace,
+    Punctuation,
+    Word,
+}
+
+fn char_class(code: u32) -> CharClass {
+    match std::char::from_u32(code) {
+        Some(c) if c.is_whitespace() => CharClass::Whitespace,
+        Some(c) if c.is_alphanumeric() || c == '_' => CharClass::Word,
+        _ => CharClass::Punctuation,
+    }
+}
+
+/// Returns index of beginning of a word that is before given position. Whitespaces before
+/// the position are skipped, then every symbol of the same class (word symbols or
+/// punctuation) is skipped.
+pub fn prev_word_boundary(text: &[u32], position: usize) -> usize {
+    let mut position = position.min(text.len());
+    while position > 0 && char_class(text[position - 1]) == CharClass::Whitespace {
+        position -= 1;
+    }
+    if position > 0 {
+        let class = char_class(text[position - 1]);
+        while position > 0 && char_class(text[position - 1]) == class {
+            position -= 1;
+        }
+    }
+    position
+}
+
+/// Returns index of end of a word that is after given position. Whitespaces after the
+/// position are skipped, then every symbol of the same class (word symbols or punctuation)
+/// is skipped.
+pub fn next_word_boundary(text: &[u32], position: usize) -> usize {
+    let mut position = position.min(text.len());
+    while position < text.len() && char_class(text[position]) == CharClass::Whitespace {
+        position += 1;
+    }
+    if position < text.len() {
+        let class = char_class(text[position]);
+        while position < text.len() && char_class(text[position]) == class {
+            position += 1;
+        }
+    }
+    position
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum EditKind {
+    Insert,
+    Remove,
+    /// Symbols were typed over existing ones in overtype mode.
+    Replace,
+}
+
+/// Single change of text that can be undone.
+#[derive(Clone, Debug)]
+struct TextEdit {
+    kind: EditKind,
+    /// Index of first changed symbol.
+    position: usize,
+    text: Vec<u32>,
+    /// Symbols that were replaced by `text`, used only by `EditKind::Replace`.
+    replaced: Vec<u32>,
+    /// Whether next typed symbols can be merged into this edit.
+    open: bool,
+}
+
+impl TextEdit {
+    /// Applies (or reverts) the edit to given text. Returns absolute position of caret after
+    /// the edit, or `None` if the text was changed in some other way and the edit can't be
+    /// applied.
+    fn apply(&self, text: &mut FormattedText, revert: bool) -> Option<usize> {
+        // Every kind of edit removes some symbols and then inserts others at the same place.
+        let (removed, inserted): (usize, &[u32]) = match (self.kind, revert) {
+            (EditKind::Insert, false) | (EditKind::Remove, true) => (0, &self.text),
+            (EditKind::Insert, true) | (EditKind::Remove, false) => (self.text.len(), &[]),
+            (EditKind::Replace, false) => (self.replaced.len(), &self.text),
+            (EditKind::Replace, true) => (self.text.len(), &self.replaced),
+        };
+        if self.position + removed > text.get_raw_text().len() {
+            return None;
+        }
+        if removed > 0 {
+            text.remove_range(self.position..self.position + removed);
+        }
+        for (i, c) in inserted
+            .iter()
+            .filter_map(|&c| std::char::from_u32(c))
+            .enumerate()
+        {
+            text.insert_char(c, self.position + i);
+        }
+        text.build();
+        Some(self.position + inserted.len())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EditHistory {
+    undo: Vec<TextEdit>,
+    redo: Vec<TextEdit>,
+    max_len: usize,
+}
+
+impl EditHistory {
+    fn new(max_len: usize) -> Self {
+        Self {
+            undo: Default::default(),
+            redo: Default::default(),
+            max_len,
+        }
+    }
+
+    /// Records new edit and clears redo stack. If `coalesce` is set, the edit is merged into
+    /// previous one if previous one is open and the edit continues it.
+    fn push(&mut self, kind: EditKind, position: usize, text: Vec<u32>, coalesce: bool) {
+        self.push_edit(TextEdit {
+            kind,
+            position,
+            text,
+            replaced: Vec::new(),
+            open: coalesce,
+        });
+    }
+
+    /// Records replacement of `replaced` symbols by `text`, see `push`.
+    fn push_replace(
+        &mut self,
+        position: usize,
+        replaced: Vec<u32>,
+        text: Vec<u32>,
+        coalesce: bool,
+    ) {
+        self.push_edit(TextEdit {
+            kind: EditKind::Replace,
+            position,
+            text,
+            replaced,
+            open: coalesce,
+        });
+    }
+
+    fn push_edit(&mut self, edit: TextEdit) {
+        if edit.text.is_empty() || self.max_len == 0 {
+            return;
+        }
+
+        self.redo.clear();
+
+        if edit.open {
+            if let Some(last) = self.undo.last_mut() {
+                if last.open
+                    && last.kind == edit.kind
+                    && last.position + last.text.len() == edit.position
+                {
+                    last.text.extend(edit.text);
+                    last.replaced.extend(edit.replaced);
+                    return;
+                }
+            }
+        }
+
+        self.undo.push(edit);
+        self.truncate();
+    }
+
+    /// Forbids merging of next edits into the last one.
+    fn seal(&mut self) {
+        if let Some(last) = self.undo.last_mut() {
+            last.open = false;
+        }
+    }
+
+    fn truncate(&mut self) {
+        if self.undo.len() > self.max_len {
+            let excess = self.undo.len() - self.max_len;
+            self.undo.drain(0..excess);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+/// Returns amount of characters that can be inserted into text of given length after removal
+/// of `removed` characters, so the text does not exceed `max_length`.
+fn capacity(max_length: Option<usize>, length: usize, removed: usize) -> usize {
+    max_length.map_or(usize::MAX, |max_length| {
+        (max_length + removed).saturating_sub(length)
+    })
+}
+
+/// Checks whether given character can be a part of text. Control characters are rejected,
+/// except line breaks of multiline text boxes and tabs of text boxes that accept them.
+fn is_text_char(c: char, multiline: bool, accepts_tab: bool) -> bool {
+    !c.is_control() || (c == '\n' && multiline) || (c == '\t' && accepts_tab)
+}
+
+/// Width of caret of text boxes by default, in logical units.
+pub const DEFAULT_CARET_WIDTH: f32 = 1.0;
+
 #[derive(Clone)]
 pub struct TextBox<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
@@ -102,11 +309,27 @@ pub struct TextBox<M: MessageData, C: Control<M, C>> {
     selection_range: Option<SelectionRange>,
     selecting: bool,
     has_focus: bool,
-    caret_brush: Brush,
+    /// Brush of caret, `None` means foreground brush of the text box.
+    caret_brush: Option<Brush>,
+    /// Width of caret in logical units, so it is scaled together with the UI.
+    caret_width: f32,
+    /// Typed characters replace characters after caret, caret is drawn as a block over the
+    /// character that will be replaced. Toggled by Insert key.
+    overtype: bool,
     selection_brush: Brush,
+    /// Brush of selection when text box is not focused, `selection_brush` is used if not set.
+    inactive_selection_brush: Option<Brush>,
+    /// Draws selection with selection color of the UI instead of own brushes, see
+    /// `UserInterface::set_selection_color`.
+    shared_selection_color: bool,
     filter: Option<Rc<RefCell<FilterCallback>>>,
     commit_mode: TextCommitMode,
     multiline: bool,
+    /// Tab character is inserted as text instead of moving keyboard focus.
+    accepts_tab: bool,
+    history: EditHistory,
+    /// Maximum amount of characters that can be entered, `None` means unlimited.
+    max_length: Option<usize>,
 }
 
 impl<M: MessageData, C: Control<M, C>> Debug for TextBox<M, C> {
@@ -121,6 +344,106 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
     pub fn reset_blink(&mut self) {
         self.caret_visible = true;
         self.blink_timer = 0.0;
+        self.widget.invalidate_visual();
+    }
+
+    /// Sets brush of caret, `None` makes caret to be drawn with foreground brush (color of
+    /// text). Explicit brush is useful to keep caret visible on any background.
+    pub fn set_caret_brush(&mut self, brush: Option<Brush>) {
+        self.caret_brush = brush;
+    }
+
+    pub fn caret_brush(&self) -> Option<&Brush> {
+        self.caret_brush.as_ref()
+    }
+
+    /// Sets width of caret in logical units, `DEFAULT_CARET_WIDTH` by default.
+    pub fn set_caret_width(&mut self, width: f32) {
+        self.caret_width = width.max(0.0);
+    }
+
+    pub fn caret_width(&self) -> f32 {
+        self.caret_width
+    }
+
+    /// Enables or disables overtype mode, in which typed characters replace existing ones.
+    pub fn set_overtype(&mut self, overtype: bool) {
+        self.overtype = overtype;
+    }
+
+    pub fn is_overtype(&self) -> bool {
+        self.overtype
+    }
+
+    /// Makes Tab key insert tab character instead of moving keyboard focus to next widget,
+    /// disabled by default. Useful for code editors.
+    pub fn set_accepts_tab(&mut self, accepts_tab: bool) {
+        self.accepts_tab = accepts_tab;
+    }
+
+    pub fn is_accepts_tab(&self) -> bool {
+        self.accepts_tab
+    }
+
+    /// Sets maximum amount of characters (Unicode scalar values, not bytes) that can be entered,
+    /// `None` removes the limit. Typed characters beyond the limit are rejected, pasted text is
+    /// truncated to fit. The limit is applied to input only, existing text and text set by
+    /// `TextBoxMessage::Text` are kept as is.
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+    }
+
+    pub fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+
+    /// Returns amount of characters in the text box.
+    pub fn length(&self) -> usize {
+        self.get_text_len()
+    }
+
+    /// Returns amount of characters that can be entered until the limit is reached, `None` if
+    /// there is no limit. Together with `length` it could be used for "45/100" counters.
+    pub fn remaining(&self) -> Option<usize> {
+        self.max_length
+            .map(|max_length| max_length.saturating_sub(self.length()))
+    }
+
+    /// Returns amount of characters that can be inserted after removal of given amount of them.
+    fn capacity(&self, removed: usize) -> usize {
+        capacity(self.max_length, self.length(), removed)
+    }
+
+    fn selection_len(&self, selection: SelectionRange) -> usize {
+        let selection = selection.normalized();
+        match (
+            self.get_absolute_position(selection.begin),
+            self.get_absolute_position(selection.end),
+        ) {
+            (Some(begin), Some(end)) => end.saturating_sub(begin),
+            _ => 0,
+        }
+    }
+
+    /// Returns position (relative to the text box) and height of caret and width of the
+    /// character after caret (width of a space if there is no character or it is a line break).
+    fn caret_geometry(&self) -> Option<(Vector2<f32>, f32, f32)> {
+        let text = self.formatted_text.borrow();
+        let height = text.get_font()?.0.lock().unwrap().height();
+
+        // Advances are taken from the fallback chain, so caret matches rendered glyphs.
+        let mut caret_pos = Vector2::default();
+        let mut next_char_width = text.symbol_advance(' ' as u32);
+        if let Some(line) = text.get_lines().get(self.caret_position.line) {
+            caret_pos += Vector2::new(line.x_offset, line.y_offset);
+            let caret_index = (line.begin + self.caret_position.offset).min(line.end);
+            caret_pos.x += text.get_range_width(line.begin..caret_index);
+            if caret_index < line.end && text.get_raw_text()[caret_index] != '\n' as u32 {
+                next_char_width = text.get_range_width(caret_index..caret_index + 1);
+            }
+        }
+
+        Some((caret_pos, height, next_char_width))
     }
 
     pub fn move_caret_x(
@@ -231,6 +554,82 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
         }
     }
 
+    /// Moves caret to beginning of previous word or to end of next word.
+    pub fn move_caret_by_word(&mut self, direction: HorizontalDirection, select: bool) {
+        let target = match self.word_boundary(direction) {
+            Some(target) => target,
+            None => return,
+        };
+
+        if select {
+            if self.selection_range.is_none() {
+                self.selection_range = Some(SelectionRange {
+                    begin: self.caret_position,
+                    end: self.caret_position,
+                });
+            }
+        } else {
+            self.selection_range = None;
+        }
+
+        self.reset_blink();
+        self.caret_position = target;
+
+        if let Some(selection_range) = self.selection_range.as_mut() {
+            if select {
+                selection_range.end = self.caret_position;
+            }
+        }
+    }
+
+    /// Returns position of a word boundary next to the caret in given direction.
+    fn word_boundary(&self, direction: HorizontalDirection) -> Option<Position> {
+        let position = self.get_absolute_position(self.caret_position)?;
+        let text = self.formatted_text.borrow();
+        let raw_text = text.get_raw_text();
+        let target = match direction {
+            HorizontalDirection::Left => prev_word_boundary(raw_text, position),
+            HorizontalDirection::Right => next_word_boundary(raw_text, position),
+        };
+        drop(text);
+        self.position_from_absolute(target)
+    }
+
+    /// Converts index of a symbol in the text into position (line and offset in the line).
+    pub fn position_from_absolute(&self, index: usize) -> Option<Position> {
+        let text = self.formatted_text.borrow();
+        let lines = text.get_lines();
+        lines
+            .iter()
+            .position(|line| index >= line.begin && index <= line.end)
+            .map(|line| Position {
+                line,
+                offset: index - lines[line].begin,
+            })
+            .or_else(|| {
+                lines.last().map(|last| Position {
+                    line: lines.len() - 1,
+                    offset: last.len(),
+                })
+            })
+    }
+
+    /// Removes text between the caret and a word boundary in given direction.
+    fn remove_word(&mut self, direction: HorizontalDirection, ui: &UserInterface<M, C>) {
+        if let Some(boundary) = self.word_boundary(direction) {
+            if boundary != self.caret_position {
+                self.remove_range(
+                    ui,
+                    SelectionRange {
+                        begin: self.caret_position,
+                        end: boundary,
+                    },
+                );
+                self.reset_blink();
+            }
+        }
+    }
+
     pub fn get_absolute_position(&self, position: Position) -> Option<usize> {
         if let Some(line) = self.formatted_text.borrow().get_lines().get(position.line) {
             Some(line.begin + cmp::min(position.offset, line.len()))
@@ -241,12 +640,38 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
 
     /// Inserts given character at current caret position.
     fn insert_char(&mut self, c: char, ui: &UserInterface<M, C>) {
-        if !c.is_control() {
+        if is_text_char(c, self.multiline, self.accepts_tab) {
             let position = self.get_absolute_position(self.caret_position).unwrap_or(0);
+            let replaced = if self.overtype && self.selection_range.is_none() {
+                self.formatted_text
+                    .borrow()
+                    .get_raw_text()
+                    .get(position)
+                    .cloned()
+                    .filter(|&code| code != '\n' as u32)
+            } else {
+                None
+            };
+            // Replaced character frees space for the new one.
+            if self.capacity(replaced.map_or(0, |_| 1)) == 0 {
+                return;
+            }
+            if replaced.is_some() {
+                self.formatted_text.borrow_mut().remove_at(position);
+            }
             self.formatted_text
                 .borrow_mut()
                 .insert_char(c, position)
                 .build();
+            // Typing is merged into one undo unit until a whitespace.
+            let coalesce = !c.is_whitespace();
+            if let Some(replaced) = replaced {
+                self.history
+                    .push_replace(position, vec![replaced], vec![c as u32], coalesce);
+            } else {
+                self.history
+                    .push(EditKind::Insert, position, vec![c as u32], coalesce);
+            }
             self.move_caret_x(1, HorizontalDirection::Right, false);
             ui.send_message(TextBoxMessage::text(
                 self.handle,
@@ -256,6 +681,52 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
         }
     }
 
+    /// Returns only those characters of given string that are accepted by the filter. Line
+    /// breaks are not passed to the filter, like Enter key of a multiline text box.
+    fn filter_str(&self, str: &str) -> String {
+        str.chars()
+            .filter(|&c| {
+                is_text_char(c, self.multiline, self.accepts_tab)
+                    && if c == '\n' {
+                        true
+                    } else if let Some(filter) = self.filter.as_ref() {
+                        let filter = &mut *filter.borrow_mut();
+                        filter(c)
+                    } else {
+                        true
+                    }
+            })
+            .collect()
+    }
+
+    /// Inserts given string at current caret position. Unlike `insert_char` it sends only
+    /// one text message for the whole string.
+    fn insert_str(&mut self, str: &str, ui: &UserInterface<M, C>) {
+        let position = self.get_absolute_position(self.caret_position).unwrap_or(0);
+        let mut count = 0;
+        for c in str.chars() {
+            self.formatted_text
+                .borrow_mut()
+                .insert_char(c, position + count);
+            count += 1;
+        }
+        if count > 0 {
+            self.formatted_text.borrow_mut().build();
+            self.history.push(
+                EditKind::Insert,
+                position,
+                str.chars().map(|c| c as u32).collect(),
+                false,
+            );
+            self.move_caret_x(count, HorizontalDirection::Right, false);
+            ui.send_message(TextBoxMessage::text(
+                self.handle,
+                MessageDirection::ToWidget,
+                self.formatted_text.borrow().text(),
+            ));
+        }
+    }
+
     pub fn get_text_len(&self) -> usize {
         self.formatted_text.borrow_mut().get_raw_text().len()
     }
@@ -278,8 +749,11 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
                         position
                     }
                 };
+                let removed = self.formatted_text.borrow().get_raw_text()[position];
                 self.formatted_text.borrow_mut().remove_at(position);
                 self.formatted_text.borrow_mut().build();
+                self.history
+                    .push(EditKind::Remove, position, vec![removed], false);
 
                 ui.send_message(TextBoxMessage::text(
                     self.handle(),
@@ -295,63 +769,116 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
     }
 
     fn remove_range(&mut self, ui: &UserInterface<M, C>, selection: SelectionRange) {
+        if self.erase_range(selection) {
+            ui.send_message(TextBoxMessage::text(
+                self.handle(),
+                MessageDirection::ToWidget,
+                self.formatted_text.borrow().text(),
+            ));
+        }
+    }
+
+    /// Removes given range of text without notifying anyone, returns true if text has changed.
+    fn erase_range(&mut self, selection: SelectionRange) -> bool {
         let selection = selection.normalized();
         if let Some(begin) = self.get_absolute_position(selection.begin) {
             if let Some(end) = self.get_absolute_position(selection.end) {
+                let removed = self.formatted_text.borrow().get_raw_text()[begin..end].to_vec();
+                self.history.push(EditKind::Remove, begin, removed, false);
                 self.formatted_text.borrow_mut().remove_range(begin..end);
                 self.formatted_text.borrow_mut().build();
+                self.caret_position = selection.begin;
+                return true;
+            }
+        }
+        false
+    }
 
-                ui.send_message(TextBoxMessage::text(
-                    self.handle(),
-                    MessageDirection::ToWidget,
-                    self.formatted_text.borrow().text(),
-                ));
+    /// Applies (or reverts) given edit and moves caret to the end of changed text,
+    /// returns false if the edit does not fit current text.
+    fn apply_edit(&mut self, edit: &TextEdit, revert: bool) -> bool {
+        let caret = match edit.apply(&mut self.formatted_text.borrow_mut(), revert) {
+            Some(caret) => caret,
+            None => return false,
+        };
+        if let Some(position) = self.position_from_absolute(caret) {
+            self.caret_position = position;
+        }
+        self.selection_range = None;
+        self.reset_blink();
+        self.invalidate_layout();
+        true
+    }
 
-                self.caret_position = selection.begin;
+    /// Reverts last edit of the text, returns true if there was anything to undo. This method
+    /// changes text silently, use `TextBoxMessage::Undo` to also notify listeners.
+    pub fn undo(&mut self) -> bool {
+        if let Some(mut edit) = self.history.undo.pop() {
+            if self.apply_edit(&edit, true) {
+                edit.open = false;
+                self.history.redo.push(edit);
+                return true;
             }
+            // Text was changed in some other way, so history is not valid anymore.
+            self.history.clear();
         }
+        false
     }
 
-    pub fn screen_pos_to_text_pos(&self, screen_pos: Vector2<f32>) -> Option<Position> {
-        let caret_pos = self.widget.screen_position;
-        if let Some(font) = self.formatted_text.borrow().get_font() {
-            let font = font.0.lock().unwrap();
-            for (line_index, line) in self.formatted_text.borrow().get_lines().iter().enumerate() {
-                let line_bounds = Rect::new(
-                    caret_pos.x + line.x_offset,
-                    caret_pos.y + line.y_offset,
-                    line.width,
-                    font.ascender(),
-                );
-                if line_bounds.contains(screen_pos) {
-                    let mut x = line_bounds.x();
-                    // Check each character in line.
-                    for (offset, index) in (line.begin..line.end).enumerate() {
-                        let symbol = self.formatted_text.borrow().get_raw_text()[index];
-                        let (width, height, advance) = if let Some(glyph) = font.glyph(symbol) {
-                            (
-                                glyph.bitmap_width as f32,
-                                glyph.bitmap_height as f32,
-                                glyph.advance,
-                            )
-                        } else {
-                            // Stub
-                            let h = font.height();
-                            (h, h, h)
-                        };
-                        let char_bounds = Rect::new(x, line_bounds.y(), width, height);
-                        if char_bounds.contains(screen_pos) {
-                            return Some(Position {
-                                line: line_index,
-                                offset,
-                            });
-                        }
-                        x += advance;
-                    }
-                }
+    /// Re-applies last undone edit, returns true if there was anything to redo. This method
+    /// changes text silently, use `TextBoxMessage::Redo` to also notify listeners.
+    pub fn redo(&mut self) -> bool {
+        if let Some(edit) = self.history.redo.pop() {
+            if self.apply_edit(&edit, false) {
+                self.history.undo.push(edit);
+                return true;
             }
+            self.history.clear();
         }
-        None
+        false
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Sets maximum amount of undo steps, the oldest steps are discarded. Zero disables
+    /// history completely.
+    pub fn set_max_history_len(&mut self, max_len: usize) -> &mut Self {
+        self.history.max_len = max_len;
+        self.history.truncate();
+        if max_len == 0 {
+            self.history.clear();
+        }
+        self
+    }
+
+    pub fn max_history_len(&self) -> usize {
+        self.history.max_len
+    }
+
+    /// Performs undo or redo and notifies listeners about new text.
+    fn undo_redo(&mut self, undo: bool, ui: &UserInterface<M, C>) {
+        let changed = if undo { self.undo() } else { self.redo() };
+        if changed {
+            ui.send_message(TextBoxMessage::text(
+                self.handle,
+                MessageDirection::ToWidget,
+                self.formatted_text.borrow().text(),
+            ));
+        }
+    }
+
+    /// Returns caret position that is nearest to given point in screen coordinates, see
+    /// `FormattedText::hit_test_char`.
+    pub fn screen_pos_to_text_pos(&self, screen_pos: Vector2<f32>) -> Option<Position> {
+        let local_pos = screen_pos - self.widget.screen_position;
+        let index = self.formatted_text.borrow().hit_test_char(local_pos);
+        self.position_from_absolute(index)
     }
 
     pub fn text(&self) -> String {
@@ -360,6 +887,7 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
 
     pub fn set_wrap(&mut self, wrap: bool) -> &mut Self {
         self.formatted_text.borrow_mut().set_wrap(wrap);
+        self.invalidate_layout();
         self
     }
 
@@ -369,6 +897,7 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
 
     pub fn set_font(&mut self, font: SharedFont) -> &mut Self {
         self.formatted_text.borrow_mut().set_font(font);
+        self.invalidate_layout();
         self
     }
 
@@ -380,6 +909,7 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
         self.formatted_text
             .borrow_mut()
             .set_vertical_alignment(valign);
+        self.invalidate_layout();
         self
     }
 
@@ -391,6 +921,7 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
         self.formatted_text
             .borrow_mut()
             .set_horizontal_alignment(halign);
+        self.invalidate_layout();
         self
     }
 
@@ -400,14 +931,24 @@ impl<M: MessageData, C: Control<M, C>> TextBox<M, C> {
 }
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for TextBox<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::TextBox
+    }
+
+    fn accessible_value(&self) -> Option<String> {
+        Some(self.text())
+    }
+
     fn measure_override(
         &self,
-        _: &UserInterface<M, C>,
+        ui: &UserInterface<M, C>,
         available_size: Vector2<f32>,
     ) -> Vector2<f32> {
         self.formatted_text
             .borrow_mut()
             .set_constraint(available_size)
+            .set_fallback_fonts(ui.fallback_fonts())
+            .set_raster_scale(ui.scale_factor())
             .build()
     }
 
@@ -488,47 +1029,51 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TextBox<M, C> {
                 }
             }
         }
+        let selection_brush = if self.shared_selection_color {
+            Brush::Solid(drawing_context.selection_color(self.has_focus))
+        } else if self.has_focus {
+            self.selection_brush.clone()
+        } else {
+            self.inactive_selection_brush
+                .clone()
+                .unwrap_or_else(|| self.selection_brush.clone())
+        };
         drawing_context.commit(
             self.clip_bounds(),
-            self.selection_brush.clone(),
+            selection_brush,
             CommandTexture::None,
             None,
         );
 
         let screen_position = bounds.position;
-        drawing_context.draw_text(bounds, screen_position, &self.formatted_text.borrow());
-
-        if self.caret_visible {
-            let text = self.formatted_text.borrow();
 
-            if let Some(font) = text.get_font() {
-                let mut caret_pos = screen_position;
+        let caret = if self.caret_visible {
+            self.caret_geometry()
+        } else {
+            None
+        };
+        let caret_brush = self
+            .caret_brush
+            .clone()
+            .unwrap_or_else(|| self.widget.foreground());
 
-                let font = font.0.lock().unwrap();
-                if let Some(line) = text.get_lines().get(self.caret_position.line) {
-                    let text = text.get_raw_text();
-                    caret_pos += Vector2::new(line.x_offset, line.y_offset);
-                    for (offset, char_index) in (line.begin..line.end).enumerate() {
-                        if offset >= self.caret_position.offset {
-                            break;
-                        }
-                        if let Some(glyph) = font.glyph(text[char_index]) {
-                            caret_pos.x += glyph.advance;
-                        } else {
-                            caret_pos.x += font.height();
-                        }
-                    }
-                }
+        drawing_context.draw_text(bounds, screen_position, &self.formatted_text.borrow());
 
-                let caret_bounds = Rect::new(caret_pos.x, caret_pos.y, 2.0, font.height());
-                drawing_context.push_rect_filled(&caret_bounds, None);
-                drawing_context.commit(
-                    self.clip_bounds(),
-                    self.caret_brush.clone(),
-                    CommandTexture::None,
-                    None,
-                );
-            }
+        if let Some((position, height, next_char_width)) = caret {
+            let position = screen_position + position;
+            let (width, brush) = if self.overtype {
+                // Block caret covers the character that will be replaced, it is semi-transparent
+                // so the character stays visible.
+                (
+                    next_char_width,
+                    caret_brush.tinted(Color::from_rgba(255, 255, 255, 128)),
+                )
+            } else {
+                (self.caret_width, caret_brush)
+            };
+            drawing_context
+                .push_rect_filled(&Rect::new(position.x, position.y, width, height), None);
+            drawing_context.commit(self.clip_bounds(), brush, CommandTexture::None, None);
         }
     }
 
@@ -538,9 +1083,11 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TextBox<M, C> {
             if self.blink_timer >= self.blink_interval {
                 self.blink_timer = 0.0;
                 self.caret_visible = !self.caret_visible;
+                self.widget.invalidate_visual();
             }
-        } else {
+        } else if self.caret_visible {
             self.caret_visible = false;
+            self.widget.invalidate_visual();
         }
     }
 
@@ -563,7 +1110,11 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TextBox<M, C> {
                         } else {
                             true
                         };
-                        if insert {
+                        // Selection is replaced by the character, so it frees space too.
+                        let selected = self
+                            .selection_range
+                            .map_or(0, |range| self.selection_len(range));
+                        if insert && self.capacity(selected) > 0 {
                             if let Some(range) = self.selection_range {
                                 self.remove_range(ui, range);
                                 self.selection_range = None;
@@ -571,131 +1122,193 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TextBox<M, C> {
                             self.insert_char(symbol, ui);
                         }
                     }
-                    WidgetMessage::KeyDown(code) => match code {
-                        KeyCode::Up => {
-                            self.move_caret_y(
-                                1,
-                                VerticalDirection::Up,
-                                ui.keyboard_modifiers().shift,
-                            );
-                        }
-                        KeyCode::Down => {
-                            self.move_caret_y(
-                                1,
-                                VerticalDirection::Down,
-                                ui.keyboard_modifiers().shift,
-                            );
-                        }
-                        KeyCode::Right => {
-                            self.move_caret_x(
-                                1,
-                                HorizontalDirection::Right,
-                                ui.keyboard_modifiers().shift,
-                            );
-                        }
-                        KeyCode::Left => {
-                            self.move_caret_x(
-                                1,
-                                HorizontalDirection::Left,
-                                ui.keyboard_modifiers().shift,
-                            );
+                    WidgetMessage::KeyDown(code) => {
+                        if matches!(
+                            code,
+                            KeyCode::Up
+                                | KeyCode::Down
+                                | KeyCode::Left
+                                | KeyCode::Right
+                                | KeyCode::Home
+                                | KeyCode::End
+                        ) {
+                            // Caret jump finishes current undo unit.
+                            self.history.seal();
                         }
-                        KeyCode::Delete => {
-                            if let Some(range) = self.selection_range {
-                                self.remove_range(ui, range);
-                                self.selection_range = None;
-                            } else {
-                                self.remove_char(HorizontalDirection::Right, ui);
+                        match code {
+                            KeyCode::Z | KeyCode::Y if ui.keyboard_modifiers().control => {
+                                let undo = *code == KeyCode::Z && !ui.keyboard_modifiers().shift;
+                                self.undo_redo(undo, ui);
                             }
-                        }
-                        KeyCode::NumpadEnter | KeyCode::Return => {
-                            if self.multiline {
-                                self.insert_char('\n', ui);
-                            } else if self.commit_mode == TextCommitMode::LostFocusPlusEnter {
-                                ui.send_message(TextBoxMessage::text(
-                                    self.handle,
-                                    MessageDirection::FromWidget,
-                                    self.text(),
-                                ));
-                                self.has_focus = false;
+                            KeyCode::Up => {
+                                self.move_caret_y(
+                                    1,
+                                    VerticalDirection::Up,
+                                    ui.keyboard_modifiers().shift,
+                                );
                             }
-                        }
-                        KeyCode::Backspace => {
-                            if let Some(range) = self.selection_range {
-                                self.remove_range(ui, range);
-                                self.selection_range = None;
-                            } else {
-                                self.remove_char(HorizontalDirection::Left, ui);
+                            KeyCode::Down => {
+                                self.move_caret_y(
+                                    1,
+                                    VerticalDirection::Down,
+                                    ui.keyboard_modifiers().shift,
+                                );
                             }
-                        }
-                        KeyCode::End => {
-                            let text = self.formatted_text.borrow();
-                            if ui.keyboard_modifiers().control {
-                                let line = &text.get_lines()[self.caret_position.line];
-                                self.caret_position.line = text.get_lines().len() - 1;
-                                self.caret_position.offset = line.end - line.begin;
-                                self.selection_range = None;
-                            } else if ui.keyboard_modifiers().shift {
-                                let line = &text.get_lines()[self.caret_position.line];
-                                let prev_position = self.caret_position;
-                                self.caret_position.offset = line.end - line.begin;
-                                self.selection_range = Some(SelectionRange {
-                                    begin: prev_position,
-                                    end: Position {
-                                        line: self.caret_position.line,
-                                        offset: self.caret_position.offset - 1,
-                                    },
-                                });
-                            } else {
-                                let line = &text.get_lines()[self.caret_position.line];
-                                self.caret_position.offset = line.end - line.begin;
-                                self.selection_range = None;
+                            KeyCode::Right => {
+                                if ui.keyboard_modifiers().control {
+                                    self.move_caret_by_word(
+                                        HorizontalDirection::Right,
+                                        ui.keyboard_modifiers().shift,
+                                    );
+                                } else {
+                                    self.move_caret_x(
+                                        1,
+                                        HorizontalDirection::Right,
+                                        ui.keyboard_modifiers().shift,
+                                    );
+                                }
                             }
-                        }
-                        KeyCode::Home => {
-                            if ui.keyboard_modifiers().control {
-                                self.caret_position.line = 0;
-                                self.caret_position.offset = 0;
-                                self.selection_range = None;
-                            } else if ui.keyboard_modifiers().shift {
-                                let prev_position = self.caret_position;
-                                self.caret_position.line = 0;
-                                self.caret_position.offset = 0;
-                                self.selection_range = Some(SelectionRange {
-                                    begin: self.caret_position,
-                                    end: Position {
-                                        line: prev_position.line,
-                                        offset: prev_position.offset.saturating_sub(1),
-                                    },
-                                });
-                            } else {
-                                self.caret_position.offset = 0;
-                                self.selection_range = None;
+                            KeyCode::Left => {
+                                if ui.keyboard_modifiers().control {
+                                    self.move_caret_by_word(
+                                        HorizontalDirection::Left,
+                                        ui.keyboard_modifiers().shift,
+                                    );
+                                } else {
+                                    self.move_caret_x(
+                                        1,
+                                        HorizontalDirection::Left,
+                                        ui.keyboard_modifiers().shift,
+                                    );
+                                }
                             }
-                        }
-                        KeyCode::A if ui.keyboard_modifiers().control => {
-                            let text = self.formatted_text.borrow();
-                            if let Some(last_line) = &text.get_lines().last() {
-                                self.selection_range = Some(SelectionRange {
-                                    begin: Position { line: 0, offset: 0 },
-                                    end: Position {
-                                        line: text.get_lines().len() - 1,
-                                        offset: last_line.end - last_line.begin,
-                                    },
-                                });
+                            KeyCode::Insert
+                                if !ui.keyboard_modifiers().control
+                                    && !ui.keyboard_modifiers().shift =>
+                            {
+                                self.overtype = !self.overtype;
+                                self.reset_blink();
+                            }
+                            KeyCode::Delete => {
+                                if let Some(range) = self.selection_range {
+                                    self.remove_range(ui, range);
+                                    self.selection_range = None;
+                                } else if ui.keyboard_modifiers().control {
+                                    self.remove_word(HorizontalDirection::Right, ui);
+                                } else {
+                                    self.remove_char(HorizontalDirection::Right, ui);
+                                }
+                            }
+                            KeyCode::NumpadEnter | KeyCode::Return => {
+                                if self.multiline {
+                                    self.insert_char('\n', ui);
+                                } else if self.commit_mode == TextCommitMode::LostFocusPlusEnter {
+                                    ui.send_message(TextBoxMessage::text(
+                                        self.handle,
+                                        MessageDirection::FromWidget,
+                                        self.text(),
+                                    ));
+                                    self.has_focus = false;
+                                }
+                            }
+                            KeyCode::Backspace => {
+                                if let Some(range) = self.selection_range {
+                                    self.remove_range(ui, range);
+                                    self.selection_range = None;
+                                } else if ui.keyboard_modifiers().control {
+                                    self.remove_word(HorizontalDirection::Left, ui);
+                                } else {
+                                    self.remove_char(HorizontalDirection::Left, ui);
+                                }
+                            }
+                            KeyCode::End => {
+                                let text = self.formatted_text.borrow();
+                                if ui.keyboard_modifiers().control {
+                                    let line = &text.get_lines()[self.caret_position.line];
+                                    self.caret_position.line = text.get_lines().len() - 1;
+                                    self.caret_position.offset = line.end - line.begin;
+                                    self.selection_range = None;
+                                } else if ui.keyboard_modifiers().shift {
+                                    let line = &text.get_lines()[self.caret_position.line];
+                                    let prev_position = self.caret_position;
+                                    self.caret_position.offset = line.end - line.begin;
+                                    self.selection_range = Some(SelectionRange {
+                                        begin: prev_position,
+                                        end: Position {
+                                            line: self.caret_position.line,
+                                            offset: self.caret_position.offset - 1,
+                                        },
+                                    });
+                                } else {
+                                    let line = &text.get_lines()[self.caret_position.line];
+                                    self.caret_position.offset = line.end - line.begin;
+                                    self.selection_range = None;
+                                }
+                            }
+                            KeyCode::Home => {
+                                if ui.keyboard_modifiers().control {
+                                    self.caret_position.line = 0;
+                                    self.caret_position.offset = 0;
+                                    self.selection_range = None;
+                                } else if ui.keyboard_modifiers().shift {
+                                    let prev_position = self.caret_position;
+                                    self.caret_position.line = 0;
+                                    self.caret_position.offset = 0;
+                                    self.selection_range = Some(SelectionRange {
+                                        begin: self.caret_position,
+                                        end: Position {
+                                            line: prev_position.line,
+                                            offset: prev_position.offset.saturating_sub(1),
+                                        },
+                                    });
+                                } else {
+                                    self.caret_position.offset = 0;
+                                    self.selection_range = None;
+                                }
                             }
+                            KeyCode::A if ui.keyboard_modifiers().control => {
+                                let text = self.formatted_text.borrow();
+                                if let Some(last_line) = &text.get_lines().last() {
+                                    self.selection_range = Some(SelectionRange {
+                                        begin: Position { line: 0, offset: 0 },
+                                        end: Position {
+                                            line: text.get_lines().len() - 1,
+                                            offset: last_line.end - last_line.begin,
+                                        },
+                                    });
+                                }
+                            }
+                            _ => (),
                         }
-                        _ => (),
-                    },
+                    }
                     WidgetMessage::GotFocus => {
                         self.reset_blink();
                         self.selection_range = None;
                         self.has_focus = true;
+
+                        let bounds = self.widget.screen_bounds();
+                        let left_top = ui.logical_to_physical(bounds.position);
+                        let right_bottom = ui.logical_to_physical(bounds.right_bottom_corner());
+                        ui.send_message(WidgetMessage::show_soft_keyboard(
+                            self.handle,
+                            MessageDirection::FromWidget,
+                            Rect::new(
+                                left_top.x,
+                                left_top.y,
+                                right_bottom.x - left_top.x,
+                                right_bottom.y - left_top.y,
+                            ),
+                        ));
                     }
                     WidgetMessage::LostFocus => {
                         self.selection_range = None;
                         self.has_focus = false;
 
+                        ui.send_message(WidgetMessage::hide_soft_keyboard(
+                            self.handle,
+                            MessageDirection::FromWidget,
+                        ));
+
                         if self.commit_mode == TextCommitMode::LostFocus
                             || self.commit_mode == TextCommitMode::LostFocusPlusEnter
                         {
@@ -711,6 +1324,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TextBox<M, C> {
                             self.selection_range = None;
                             self.selecting = true;
                             self.has_focus = true;
+                            self.history.seal();
 
                             if let Some(position) = self.screen_pos_to_text_pos(*pos) {
                                 self.caret_position = position;
@@ -728,14 +1342,8 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TextBox<M, C> {
                         if self.selecting {
                             if let Some(position) = self.screen_pos_to_text_pos(*pos) {
                                 if let Some(ref mut sel_range) = self.selection_range {
-                                    if position.offset > sel_range.begin.offset {
-                                        sel_range.end = Position {
-                                            line: position.line,
-                                            offset: position.offset + 1,
-                                        };
-                                    } else {
-                                        sel_range.end = position;
-                                    }
+                                    sel_range.end = position;
+                                    self.caret_position = position;
                                 }
                             }
                         }
@@ -745,6 +1353,9 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TextBox<M, C> {
 
                         ui.release_mouse_capture();
                     }
+                    WidgetMessage::MouseCaptureLost => {
+                        self.selecting = false;
+                    }
                     _ => {}
                 },
                 UiMessageData::TextBox(TextBoxMessage::Text(new_text))
@@ -764,6 +1375,10 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TextBox<M, C> {
                         }
                     }
                     if !equals {
+                        // Text was replaced from outside, so recorded edits do not match it.
+                        if self.text() != *new_text {
+                            self.history.clear();
+                        }
                         self.formatted_text.borrow_mut().set_text(new_text);
                         self.invalidate_layout();
 
@@ -772,6 +1387,51 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TextBox<M, C> {
                         }
                     }
                 }
+                UiMessageData::TextBox(TextBoxMessage::Undo)
+                    if message.direction() == MessageDirection::ToWidget =>
+                {
+                    self.undo_redo(true, ui);
+                }
+                UiMessageData::TextBox(TextBoxMessage::Redo)
+                    if message.direction() == MessageDirection::ToWidget =>
+                {
+                    self.undo_redo(false, ui);
+                }
+                UiMessageData::TextBox(TextBoxMessage::CaretBrush(brush))
+                    if message.direction() == MessageDirection::ToWidget =>
+                {
+                    self.set_caret_brush(brush.clone());
+                }
+                UiMessageData::TextBox(TextBoxMessage::CaretWidth(width))
+                    if message.direction() == MessageDirection::ToWidget =>
+                {
+                    self.set_caret_width(*width);
+                }
+                UiMessageData::TextBox(TextBoxMessage::MaxLength(max_length))
+                    if message.direction() == MessageDirection::ToWidget =>
+                {
+                    self.set_max_length(*max_length);
+                }
+                UiMessageData::TextBox(TextBoxMessage::Paste(text))
+                    if message.direction() == MessageDirection::ToWidget && !message.handled() =>
+                {
+                    // Paste is rejected entirely if no character passed the filter, otherwise
+                    // it is truncated to fit the max length.
+                    let selected = self
+                        .selection_range
+                        .map_or(0, |range| self.selection_len(range));
+                    let text = self
+                        .filter_str(text)
+                        .chars()
+                        .take(self.capacity(selected))
+                        .collect::<String>();
+                    if !text.is_empty() {
+                        if let Some(range) = self.selection_range.take() {
+                            self.erase_range(range);
+                        }
+                        self.insert_str(&text, ui);
+                    }
+                }
                 _ => {}
             }
         }
@@ -782,14 +1442,20 @@ pub struct TextBoxBuilder<M: MessageData, C: Control<M, C>> {
     widget_builder: WidgetBuilder<M, C>,
     font: Option<SharedFont>,
     text: String,
-    caret_brush: Brush,
+    caret_brush: Option<Brush>,
+    caret_width: f32,
     selection_brush: Brush,
+    inactive_selection_brush: Option<Brush>,
+    shared_selection_color: bool,
     filter: Option<Rc<RefCell<FilterCallback>>>,
     vertical_alignment: VerticalAlignment,
     horizontal_alignment: HorizontalAlignment,
     wrap: bool,
     commit_mode: TextCommitMode,
     multiline: bool,
+    accepts_tab: bool,
+    max_history_len: usize,
+    max_length: Option<usize>,
 }
 
 impl<M: MessageData, C: Control<M, C>> TextBoxBuilder<M, C> {
@@ -798,14 +1464,20 @@ impl<M: MessageData, C: Control<M, C>> TextBoxBuilder<M, C> {
             widget_builder,
             font: None,
             text: "".to_owned(),
-            caret_brush: Brush::Solid(Color::WHITE),
+            caret_brush: None,
+            caret_width: DEFAULT_CARET_WIDTH,
             selection_brush: Brush::Solid(Color::opaque(80, 118, 178)),
+            inactive_selection_brush: None,
+            shared_selection_color: false,
             filter: None,
             vertical_alignment: VerticalAlignment::Top,
             horizontal_alignment: HorizontalAlignment::Left,
             wrap: false,
             commit_mode: TextCommitMode::LostFocusPlusEnter,
             multiline: false,
+            accepts_tab: false,
+            max_history_len: 100,
+            max_length: None,
         }
     }
 
@@ -819,8 +1491,20 @@ impl<M: MessageData, C: Control<M, C>> TextBoxBuilder<M, C> {
         self
     }
 
+    /// See `TextBox::set_caret_brush`.
     pub fn with_caret_brush(mut self, brush: Brush) -> Self {
-        self.caret_brush = brush;
+        self.caret_brush = Some(brush);
+        self
+    }
+
+    /// Sets solid color of caret, see `TextBox::set_caret_brush`.
+    pub fn with_caret_color(self, color: Color) -> Self {
+        self.with_caret_brush(Brush::Solid(color))
+    }
+
+    /// See `TextBox::set_caret_width`.
+    pub fn with_caret_width(mut self, width: f32) -> Self {
+        self.caret_width = width.max(0.0);
         self
     }
 
@@ -829,6 +1513,19 @@ impl<M: MessageData, C: Control<M, C>> TextBoxBuilder<M, C> {
         self
     }
 
+    /// Sets brush of selection when text box is not focused.
+    pub fn with_inactive_selection_brush(mut self, brush: Brush) -> Self {
+        self.inactive_selection_brush = Some(brush);
+        self
+    }
+
+    /// Makes selection drawn with selection color of the UI (active when the text box is
+    /// focused, inactive otherwise) instead of selection brushes. Disabled by default.
+    pub fn with_shared_selection_color(mut self, shared: bool) -> Self {
+        self.shared_selection_color = shared;
+        self
+    }
+
     pub fn with_filter(mut self, filter: Rc<RefCell<FilterCallback>>) -> Self {
         self.filter = Some(filter);
         self
@@ -859,6 +1556,24 @@ impl<M: MessageData, C: Control<M, C>> TextBoxBuilder<M, C> {
         self
     }
 
+    /// See `TextBox::set_accepts_tab`.
+    pub fn with_accepts_tab(mut self, accepts_tab: bool) -> Self {
+        self.accepts_tab = accepts_tab;
+        self
+    }
+
+    /// Sets maximum amount of undo steps, zero disables undo.
+    pub fn with_max_history_len(mut self, max_len: usize) -> Self {
+        self.max_history_len = max_len;
+        self
+    }
+
+    /// See `TextBox::set_max_length`.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
     pub fn build(mut self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
         if self.widget_builder.foreground.is_none() {
             self.widget_builder.foreground = Some(BRUSH_TEXT);
@@ -869,6 +1584,9 @@ impl<M: MessageData, C: Control<M, C>> TextBoxBuilder<M, C> {
         if self.widget_builder.cursor.is_none() {
             self.widget_builder.cursor = Some(CursorIcon::Text);
         }
+        if self.widget_builder.tab_stop.is_none() {
+            self.widget_builder.tab_stop = Some(true);
+        }
 
         let text_box = TextBox {
             widget: self.widget_builder.build(),
@@ -879,7 +1597,7 @@ impl<M: MessageData, C: Control<M, C>> TextBoxBuilder<M, C> {
             formatted_text: RefCell::new(
                 FormattedTextBuilder::new()
                     .with_text(self.text)
-                    .with_font(self.font.unwrap_or_else(|| crate::DEFAULT_FONT.clone()))
+                    .with_font(self.font.unwrap_or_else(|| ctx.ui.default_font()))
                     .with_horizontal_alignment(self.horizontal_alignment)
                     .with_vertical_alignment(self.vertical_alignment)
                     .with_wrap(self.wrap)
@@ -888,13 +1606,88 @@ impl<M: MessageData, C: Control<M, C>> TextBoxBuilder<M, C> {
             selection_range: None,
             selecting: false,
             selection_brush: self.selection_brush,
+            inactive_selection_brush: self.inactive_selection_brush,
+            shared_selection_color: self.shared_selection_color,
             caret_brush: self.caret_brush,
+            caret_width: self.caret_width,
+            overtype: false,
             has_focus: false,
             filter: self.filter,
             commit_mode: self.commit_mode,
             multiline: self.multiline,
+            accepts_tab: self.accepts_tab,
+            history: EditHistory::new(self.max_history_len),
+            max_length: self.max_length,
         };
 
         ctx.add_node(UINode::TextBox(text_box))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        formatted_text::FormattedTextBuilder,
+        text_box::{capacity, is_text_char, EditHistory, EditKind},
+    };
+
+    #[test]
+    fn pasted_line_breaks() {
+        let filter = |text: &str, multiline, accepts_tab| {
+            text.chars()
+                .filter(|&c| is_text_char(c, multiline, accepts_tab))
+                .collect::<String>()
+        };
+        assert_eq!(filter("a\r\nb\tc", false, false), "abc");
+        assert_eq!(filter("a\r\nb\tc", true, false), "a\nbc");
+        assert_eq!(filter("a\r\nb\tc", true, true), "a\nb\tc");
+    }
+
+    #[test]
+    fn max_length_counts_characters() {
+        // 3 characters, but 8 bytes.
+        let text = FormattedTextBuilder::new()
+            .with_text("ñé😀".to_owned())
+            .build();
+        let length = text.get_raw_text().len();
+        assert_eq!(length, 3);
+
+        assert_eq!(capacity(Some(4), length, 0), 1);
+        let pasted = "日本語"
+            .chars()
+            .take(capacity(Some(4), length, 0))
+            .collect::<String>();
+        assert_eq!(pasted, "日");
+
+        // Full text accepts characters only in place of removed ones.
+        assert_eq!(capacity(Some(4), 4, 0), 0);
+        assert_eq!(capacity(Some(4), 4, 2), 2);
+        // Limit lowered below current length.
+        assert_eq!(capacity(Some(2), 4, 1), 0);
+        assert_eq!(capacity(None, 4, 0), usize::MAX);
+    }
+
+    #[test]
+    fn overtype_replaces_in_one_edit() {
+        let mut text = FormattedTextBuilder::new()
+            .with_text("xyz".to_owned())
+            .build();
+        let mut history = EditHistory::new(10);
+        // Typing "ab" in overtype mode at the beginning.
+        for (i, c) in "ab".chars().enumerate() {
+            let replaced = text.get_raw_text()[i];
+            text.remove_at(i);
+            text.insert_char(c, i).build();
+            history.push_replace(i, vec![replaced], vec![c as u32], true);
+        }
+        assert_eq!(text.text(), "abz");
+        assert_eq!(history.undo.len(), 1);
+
+        let edit = history.undo.pop().unwrap();
+        assert_eq!(edit.kind, EditKind::Replace);
+        assert_eq!(edit.apply(&mut text, true), Some(2));
+        assert_eq!(text.text(), "xyz");
+        assert_eq!(edit.apply(&mut text, false), Some(2));
+        assert_eq!(text.text(), "abz");
+    }
+}
@@ -0,0 +1,140 @@
+//! Touch input support and simple gesture recognition.
+//!
+//! # Mapping to mouse events
+//!
+//! Only the first finger (primary touch) is translated into mouse events, so every existing
+//! widget works with touch screens without any changes:
+//!
+//! - `TouchPhase::Started` - cursor is moved to the touch position and left mouse button is
+//!   pressed.
+//! - `TouchPhase::Moved` - cursor is moved to the touch position.
+//! - `TouchPhase::Ended` - left mouse button is released.
+//! - `TouchPhase::Cancelled` - same as `Ended`, but no tap will be recognized.
+//!
+//! Other fingers are tracked, but they do not produce mouse events.
+//!
+//! # Gestures
+//!
+//! - Tap - primary touch was released shortly after it was started and it has not moved
+//!   far. `WidgetMessage::Tap` is sent to a widget under the finger in addition to usual
+//!   mouse events.
+//! - Long press - primary touch was held for some time without moving. `WidgetMessage::LongPress`
+//!   is sent to a widget under the finger.
+//! - Pan - primary touch has moved far enough over a scroll viewer. Mouse events are stopped
+//!   and the scroll viewer is scrolled by the finger instead. When the finger is released the
+//!   scroll viewer continues scrolling with decaying speed (inertia), see
+//!   `ScrollViewer::set_inertia_enabled`.
+
+use crate::{
+    core::{algebra::Vector2, pool::Handle},
+    message::MessageData,
+    Control, UINode,
+};
+use std::collections::HashMap;
+
+/// Distance (in logical units) that a finger must pass to start panning.
+pub const PAN_THRESHOLD: f32 = 10.0;
+
+/// Time (in seconds) that a finger must be held to trigger long press.
+pub const LONG_PRESS_TIME: f32 = 0.6;
+
+/// Maximum duration (in seconds) of a touch that is considered as tap.
+pub const TAP_TIME: f32 = 0.3;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TouchPoint {
+    pub start_position: Vector2<f32>,
+    pub position: Vector2<f32>,
+    /// Time when touch has started, see `TouchState::time`.
+    pub start_time: f32,
+}
+
+impl TouchPoint {
+    pub fn distance(&self) -> f32 {
+        (self.position - self.start_position).norm()
+    }
+}
+
+/// State of every active touch and of current gesture.
+pub struct TouchState<M: MessageData, C: Control<M, C>> {
+    pub(in crate) touches: HashMap<u64, TouchPoint>,
+    /// Finger that is translated into mouse events.
+    pub(in crate) primary: Option<u64>,
+    /// Scroll viewer that is currently scrolled by the primary finger.
+    pub(in crate) pan_target: Handle<UINode<M, C>>,
+    pub(in crate) is_panning: bool,
+    pub(in crate) long_press_fired: bool,
+    /// Total time accumulated from updates of the UI.
+    pub(in crate) time: f32,
+}
+
+impl<M: MessageData, C: Control<M, C>> Default for TouchState<M, C> {
+    fn default() -> Self {
+        Self {
+            touches: Default::default(),
+            primary: None,
+            pan_target: Handle::NONE,
+            is_panning: false,
+            long_press_fired: false,
+            time: 0.0,
+        }
+    }
+}
+
+impl<M: MessageData, C: Control<M, C>> TouchState<M, C> {
+    /// Returns every active touch.
+    pub fn touches(&self) -> &HashMap<u64, TouchPoint> {
+        &self.touches
+    }
+
+    pub fn primary_touch(&self) -> Option<&TouchPoint> {
+        self.primary.and_then(|id| self.touches.get(&id))
+    }
+
+    pub fn is_panning(&self) -> bool {
+        self.is_panning
+    }
+
+    pub(in crate) fn begin(&mut self, id: u64, position: Vector2<f32>) -> bool {
+        self.touches.insert(
+            id,
+            TouchPoint {
+                start_position: position,
+                position,
+                start_time: self.time,
+            },
+        );
+        if self.primary.is_none() {
+            self.primary = Some(id);
+            self.is_panning = false;
+            self.long_press_fired = false;
+            self.pan_target = Handle::NONE;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Moves touch and returns movement delta, if the touch is primary.
+    pub(in crate) fn move_to(&mut self, id: u64, position: Vector2<f32>) -> Option<Vector2<f32>> {
+        let touch = self.touches.get_mut(&id)?;
+        let delta = position - touch.position;
+        touch.position = position;
+        if self.primary == Some(id) {
+            Some(delta)
+        } else {
+            None
+        }
+    }
+
+    /// Removes touch and returns it, if it was primary.
+    pub(in crate) fn end(&mut self, id: u64) -> Option<TouchPoint> {
+        let touch = self.touches.remove(&id)?;
+        if self.primary == Some(id) {
+            self.primary = None;
+            Some(touch)
+        } else {
+            None
+        }
+    }
+}
@@ -1,18 +1,20 @@
 use crate::core::algebra::Vector2;
 use crate::{
+    accessibility::AccessibleRole,
     border::BorderBuilder,
     brush::Brush,
     button::ButtonBuilder,
     core::{color::Color, pool::Handle},
     decorator::DecoratorBuilder,
     grid::{Column, GridBuilder, Row},
+    items_control::ItemsControl,
     message::{
-        ButtonMessage, DecoratorMessage, MessageData, MessageDirection, TextMessage, TreeMessage,
-        TreeRootMessage, UiMessage, UiMessageData, WidgetMessage,
+        ButtonMessage, DecoratorMessage, KeyCode, MessageData, MessageDirection, TextMessage,
+        TreeMessage, TreeRootMessage, UiMessage, UiMessageData, WidgetMessage,
     },
     node::UINode,
     stack_panel::StackPanelBuilder,
-    widget::{Widget, WidgetBuilder},
+    widget::{NavMode, Widget, WidgetBuilder},
     BuildContext, Control, NodeHandleMapping, Thickness, UserInterface, BRUSH_DARK, BRUSH_DARKEST,
     BRUSH_LIGHT,
 };
@@ -23,10 +25,9 @@ pub struct Tree<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     expander: Handle<UINode<M, C>>,
     content: Handle<UINode<M, C>>,
-    panel: Handle<UINode<M, C>>,
     is_expanded: bool,
     background: Handle<UINode<M, C>>,
-    items: Vec<Handle<UINode<M, C>>>,
+    items: ItemsControl<M, C>,
     is_selected: bool,
     always_show_expander: bool,
 }
@@ -34,11 +35,15 @@ pub struct Tree<M: MessageData, C: Control<M, C>> {
 crate::define_widget_deref!(Tree<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for Tree<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::TreeItem
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         node_map.resolve(&mut self.content);
         node_map.resolve(&mut self.expander);
-        node_map.resolve(&mut self.panel);
         node_map.resolve(&mut self.background);
+        self.items.resolve(node_map);
     }
 
     fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
@@ -110,7 +115,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Tree<M, C> {
                         &TreeMessage::Expand(expand) => {
                             self.is_expanded = expand;
                             ui.send_message(WidgetMessage::visibility(
-                                self.panel,
+                                self.items.items_panel(),
                                 MessageDirection::ToWidget,
                                 self.is_expanded,
                             ));
@@ -125,38 +130,18 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Tree<M, C> {
                             }
                         }
                         &TreeMessage::AddItem(item) => {
-                            ui.send_message(WidgetMessage::link(
-                                item,
-                                MessageDirection::ToWidget,
-                                self.panel,
-                            ));
-
-                            self.items.push(item);
+                            self.items.add_item(ui, item);
                         }
                         &TreeMessage::RemoveItem(item) => {
-                            if let Some(pos) = self.items.iter().position(|&i| i == item) {
-                                ui.send_message(WidgetMessage::remove(
-                                    item,
-                                    MessageDirection::ToWidget,
-                                ));
-                                self.items.remove(pos);
+                            if let Some(index) = self.items.index_of(item) {
+                                self.items.remove_item(ui, index);
                             }
                         }
                         TreeMessage::SetItems(items) => {
-                            for &item in self.items.iter() {
-                                ui.send_message(WidgetMessage::remove(
-                                    item,
-                                    MessageDirection::ToWidget,
-                                ));
-                            }
+                            self.items.clear(ui);
                             for &item in items {
-                                ui.send_message(WidgetMessage::link(
-                                    item,
-                                    MessageDirection::ToWidget,
-                                    self.panel,
-                                ));
+                                self.items.add_item(ui, item);
                             }
-                            self.items = items.clone();
                         }
                         &TreeMessage::Select(state) => {
                             if self.is_selected != state.0 {
@@ -182,12 +167,10 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Tree<M, C> {
         if self.content == handle {
             self.content = Default::default();
         }
-        if self.panel == handle {
-            self.panel = Default::default();
-        }
         if self.background == handle {
             self.background = Default::default();
         }
+        self.items.remove_ref(handle);
     }
 }
 
@@ -201,7 +184,7 @@ impl<M: MessageData, C: Control<M, C>> Tree<M, C> {
     }
 
     pub fn items(&self) -> &[Handle<UINode<M, C>>] {
-        &self.items
+        self.items.items()
     }
 
     /// Adds new item to given tree. This method is meant to be used only on widget build stage,
@@ -212,8 +195,8 @@ impl<M: MessageData, C: Control<M, C>> Tree<M, C> {
         ctx: &mut BuildContext<M, C>,
     ) {
         if let UINode::Tree(tree) = &mut ctx[tree] {
-            tree.items.push(item);
-            let panel = tree.panel;
+            tree.items.push_item(item, item);
+            let panel = tree.items.items_panel();
             ctx.link(item, panel);
         }
     }
@@ -343,11 +326,10 @@ impl<M: MessageData, C: Control<M, C>> TreeBuilder<M, C> {
                 .with_child(grid)
                 .build(),
             content: self.content,
-            panel,
             is_expanded: self.is_expanded,
             expander,
             background: item_background,
-            items: self.items,
+            items: ItemsControl::new(panel, self.items),
             is_selected: false,
             always_show_expander: self.always_show_expander,
         }
@@ -362,16 +344,22 @@ impl<M: MessageData, C: Control<M, C>> TreeBuilder<M, C> {
 #[derive(Debug, Clone)]
 pub struct TreeRoot<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
-    panel: Handle<UINode<M, C>>,
-    items: Vec<Handle<UINode<M, C>>>,
+    items: ItemsControl<M, C>,
     selected: Vec<Handle<UINode<M, C>>>,
+    /// Whether the tree or any of its descendants is focused, selection of unfocused tree is
+    /// drawn with inactive selection color.
+    has_focus: bool,
 }
 
 crate::define_widget_deref!(TreeRoot<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for TreeRoot<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Tree
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
-        node_map.resolve(&mut self.panel);
+        self.items.resolve(node_map);
         node_map.resolve_slice(&mut self.selected);
     }
 
@@ -382,44 +370,48 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TreeRoot<M, C> {
     ) {
         self.widget.handle_routed_message(ui, message);
 
+        // Focus messages of descendants bubble up here.
+        match &message.data() {
+            UiMessageData::Widget(WidgetMessage::GotFocus) if !self.has_focus => {
+                self.has_focus = true;
+                self.sync_selection_activity(ui);
+            }
+            UiMessageData::Widget(WidgetMessage::LostFocus) if self.has_focus => {
+                self.has_focus = false;
+                self.sync_selection_activity(ui);
+            }
+            // The tree is a single tab stop, arrow keys move selection inside it.
+            UiMessageData::Widget(WidgetMessage::KeyDown(key))
+                if message.destination() == self.handle()
+                    && !message.handled()
+                    && self.move_selection_by_arrow(ui, *key) =>
+            {
+                message.set_handled(true);
+            }
+            _ => (),
+        }
+
         if let UiMessageData::TreeRoot(msg) = &message.data() {
             if message.destination() == self.handle()
                 && message.direction() == MessageDirection::ToWidget
             {
                 match msg {
                     &TreeRootMessage::AddItem(item) => {
-                        ui.send_message(WidgetMessage::link(
-                            item,
-                            MessageDirection::ToWidget,
-                            self.panel,
-                        ));
-
-                        self.items.push(item);
+                        self.items.add_item(ui, item);
                     }
                     &TreeRootMessage::RemoveItem(item) => {
-                        if let Some(pos) = self.items.iter().position(|&i| i == item) {
-                            ui.send_message(WidgetMessage::remove(
-                                item,
-                                MessageDirection::ToWidget,
-                            ));
-                            self.items.remove(pos);
+                        if let Some(index) = self.items.index_of(item) {
+                            self.items.remove_item(ui, index);
                         }
                     }
                     TreeRootMessage::Items(items) => {
-                        for &item in self.items.iter() {
-                            ui.send_message(WidgetMessage::remove(
-                                item,
-                                MessageDirection::ToWidget,
-                            ));
-                        }
+                        self.items.clear(ui);
                         for &item in items {
-                            ui.send_message(WidgetMessage::link(
-                                item,
-                                MessageDirection::ToWidget,
-                                self.panel,
-                            ));
+                            self.items.add_item(ui, item);
                         }
-                        self.items = items.to_vec();
+                    }
+                    &TreeRootMessage::Placeholder(placeholder) => {
+                        self.items.set_placeholder(ui, self.handle(), placeholder);
                     }
                     TreeRootMessage::Selected(selected) => {
                         if &self.selected != selected {
@@ -451,9 +443,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TreeRoot<M, C> {
     }
 
     fn remove_ref(&mut self, handle: Handle<UINode<M, C>>) {
-        if self.panel == handle {
-            self.panel = Default::default();
-        }
+        self.items.remove_ref(handle);
         if let Some(position) = self.selected.iter().position(|&s| s == handle) {
             self.selected.remove(position);
         }
@@ -462,13 +452,91 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for TreeRoot<M, C> {
 
 impl<M: MessageData, C: Control<M, C>> TreeRoot<M, C> {
     pub fn items(&self) -> &[Handle<UINode<M, C>>] {
+        self.items.items()
+    }
+
+    pub fn items_control(&self) -> &ItemsControl<M, C> {
         &self.items
     }
+
+    pub fn placeholder(&self) -> Handle<UINode<M, C>> {
+        self.items.placeholder()
+    }
+
+    /// Returns trees that are shown (all their ancestors are expanded) in their order.
+    fn visible_items(&self, ui: &UserInterface<M, C>) -> Vec<Handle<UINode<M, C>>> {
+        let mut items = Vec::new();
+        let mut stack = self.items.items().iter().rev().cloned().collect::<Vec<_>>();
+        while let Some(handle) = stack.pop() {
+            if let UINode::Tree(tree) = ui.node(handle) {
+                items.push(handle);
+                if tree.is_expanded {
+                    stack.extend(tree.items().iter().rev());
+                }
+            }
+        }
+        items
+    }
+
+    /// Selects previous or next shown tree by Up and Down keys, it is done while the tree is
+    /// focused. Returns true if the key was used.
+    fn move_selection_by_arrow(&self, ui: &UserInterface<M, C>, key: KeyCode) -> bool {
+        let mode = self.keyboard_navigation();
+        let forward = match key {
+            _ if mode == NavMode::None => return false,
+            KeyCode::Up => false,
+            KeyCode::Down => true,
+            _ => return false,
+        };
+
+        let items = self.visible_items(ui);
+        if items.is_empty() {
+            return true;
+        }
+        let last = items.len() - 1;
+        let current = self
+            .selected
+            .last()
+            .and_then(|selected| items.iter().position(|item| item == selected));
+        let index = match current {
+            None if forward => 0,
+            None => last,
+            Some(index) if forward && index < last => index + 1,
+            Some(index) if !forward && index > 0 => index - 1,
+            Some(_) if mode == NavMode::Cycle && forward => 0,
+            Some(_) if mode == NavMode::Cycle => last,
+            Some(index) => index,
+        };
+        if current != Some(index) {
+            ui.send_message(TreeRootMessage::select(
+                self.handle(),
+                MessageDirection::ToWidget,
+                vec![items[index]],
+            ));
+        }
+        true
+    }
+
+    fn sync_selection_activity(&self, ui: &UserInterface<M, C>) {
+        let mut stack = self.children().to_vec();
+        while let Some(handle) = stack.pop() {
+            let node = ui.node(handle);
+            if let UINode::Tree(tree) = node {
+                ui.send_message(DecoratorMessage::selection_active(
+                    tree.background,
+                    MessageDirection::ToWidget,
+                    self.has_focus,
+                ));
+            }
+            stack.extend_from_slice(node.children());
+        }
+    }
 }
 
 pub struct TreeRootBuilder<M: MessageData, C: Control<M, C>> {
     widget_builder: WidgetBuilder<M, C>,
     items: Vec<Handle<UINode<M, C>>>,
+    placeholder: Handle<UINode<M, C>>,
 }
 
 impl<M: MessageData, C: Control<M, C>> TreeRootBuilder<M, C> {
@@ -476,25 +544,99 @@ impl<M: MessageData, C: Control<M, C>> TreeRootBuilder<M, C> {
         Self {
             widget_builder,
             items: Default::default(),
+            placeholder: Handle::NONE,
         }
     }
 
+    /// Sets a node (like "No results" text) that is shown in the center of the tree only while
+    /// the tree has no items.
+    pub fn with_placeholder(mut self, placeholder: Handle<UINode<M, C>>) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
     pub fn with_items(mut self, items: Vec<Handle<UINode<M, C>>>) -> Self {
         self.items = items;
         self
     }
 
-    pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+    pub fn build(mut self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
+        if self.widget_builder.keyboard_navigation.is_none() {
+            self.widget_builder.keyboard_navigation = Some(NavMode::Contained);
+        }
+        if self.widget_builder.tab_stop.is_none() {
+            self.widget_builder.tab_stop = Some(true);
+        }
+
         let panel = StackPanelBuilder::new(WidgetBuilder::new().with_children(self.items.iter()))
             .build(ctx);
 
         let tree = TreeRoot {
-            widget: self.widget_builder.with_child(panel).build(),
-            panel,
-            items: self.items,
+            widget: self
+                .widget_builder
+                .with_child(panel)
+                .with_child(self.placeholder)
+                .build(),
+            items: ItemsControl::new(panel, self.items).with_placeholder(ctx, self.placeholder),
             selected: Default::default(),
+            has_focus: false,
         };
 
         ctx.add_node(UINode::TreeRoot(tree))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder,
+        core::algebra::Vector2,
+        message::{MessageDirection, TreeRootMessage, WidgetMessage},
+        node::StubNode,
+        tree::TreeRootBuilder,
+        widget::WidgetBuilder,
+        UserInterface,
+    };
+
+    #[test]
+    fn placeholder() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let placeholder = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        let tree = TreeRootBuilder::new(WidgetBuilder::new())
+            .with_placeholder(placeholder)
+            .build(&mut ui.build_ctx());
+        assert!(ui.node(placeholder).visibility());
+        assert!(!ui.node(placeholder).is_hit_test_visible());
+
+        let item = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        ui.send_message(TreeRootMessage::add_item(
+            tree,
+            MessageDirection::ToWidget,
+            item,
+        ));
+        while ui.poll_message().is_some() {}
+        assert!(!ui.node(placeholder).visibility());
+
+        ui.send_message(TreeRootMessage::remove_item(
+            tree,
+            MessageDirection::ToWidget,
+            item,
+        ));
+        while ui.poll_message().is_some() {}
+        assert!(ui.node(placeholder).visibility());
+
+        // Item removed directly, bypassing the tree.
+        let item = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        ui.send_message(TreeRootMessage::add_item(
+            tree,
+            MessageDirection::ToWidget,
+            item,
+        ));
+        while ui.poll_message().is_some() {}
+        assert!(!ui.node(placeholder).visibility());
+        ui.send_message(WidgetMessage::remove(item, MessageDirection::ToWidget));
+        while ui.poll_message().is_some() {}
+        assert!(ui.node(placeholder).visibility());
+    }
+}
@@ -22,11 +22,17 @@ struct Polygon {
     points: Vec<Point>,
 }
 
+/// Metrics of a glyph are in logical units, bitmap of a glyph is in pixels and can be larger
+/// than logical size if font is rasterized with a scale (see `Font::set_raster_scale`).
 #[derive(Debug)]
 pub struct FontGlyph {
     pub top: f32,
     pub left: f32,
     pub advance: f32,
+    /// Logical width of glyph bitmap.
+    pub width: f32,
+    /// Logical height of glyph bitmap.
+    pub height: f32,
     pub tex_coords: [Vector2<f32>; 4],
     pub bitmap_width: usize,
     pub bitmap_height: usize,
@@ -34,6 +40,9 @@ pub struct FontGlyph {
 }
 
 pub struct Font {
+    source: fontdue::Font,
+    char_set: Vec<Range<u32>>,
+    raster_scale: f32,
     height: f32,
     glyphs: Vec<FontGlyph>,
     ascender: f32,
@@ -79,6 +88,9 @@ impl Font {
         let font_metrics = fontdue_font.horizontal_line_metrics(height).unwrap();
 
         let mut font = Font {
+            source: fontdue_font,
+            char_set: char_set.to_vec(),
+            raster_scale: 1.0,
             height,
             glyphs: Vec::new(),
             ascender: font_metrics.ascent,
@@ -89,31 +101,64 @@ impl Font {
             texture: None,
         };
 
+        font.rasterize();
+
+        Ok(font)
+    }
+
+    /// Rasterizes every glyph of char set at `height * raster_scale` pixels and packs them
+    /// into new atlas. Metrics of glyphs are kept in logical units.
+    fn rasterize(&mut self) {
+        self.glyphs.clear();
+        self.char_map.clear();
+
+        let k = 1.0 / self.raster_scale;
         let mut index = 0;
-        for range in char_set {
+        for range in self.char_set.iter() {
             for unicode in range.start..range.end {
                 if let Some(character) = std::char::from_u32(unicode) {
-                    let (metrics, bitmap) = fontdue_font.rasterize(character, height);
+                    let (metrics, bitmap) = self
+                        .source
+                        .rasterize(character, self.height * self.raster_scale);
 
-                    font.glyphs.push(FontGlyph {
-                        left: metrics.xmin as f32,
-                        top: metrics.ymin as f32,
+                    self.glyphs.push(FontGlyph {
+                        left: metrics.xmin as f32 * k,
+                        top: metrics.ymin as f32 * k,
                         pixels: bitmap,
-                        advance: metrics.advance_width,
+                        advance: metrics.advance_width * k,
+                        width: metrics.width as f32 * k,
+                        height: metrics.height as f32 * k,
                         tex_coords: Default::default(),
                         bitmap_width: metrics.width,
                         bitmap_height: metrics.height,
                     });
 
-                    font.char_map.insert(unicode, index);
+                    self.char_map.insert(unicode, index);
                     index += 1;
                 }
             }
         }
 
-        font.pack();
+        self.pack();
 
-        Ok(font)
+        // Atlas has changed, renderer must re-upload it.
+        self.texture = None;
+    }
+
+    /// Sets scale at which glyphs are rasterized, it should match scale factor of the UI so
+    /// text will stay sharp on high-DPI displays. Metrics of glyphs stay the same, only atlas
+    /// resolution is changed. Every text that uses the font must be re-built after this.
+    pub fn set_raster_scale(&mut self, scale: f32) {
+        let scale = scale.max(f32::EPSILON);
+        if (self.raster_scale - scale).abs() > f32::EPSILON {
+            self.raster_scale = scale;
+            self.rasterize();
+        }
+    }
+
+    #[inline]
+    pub fn raster_scale(&self) -> f32 {
+        self.raster_scale
     }
 
     pub fn from_file<P: AsRef<Path>>(
@@ -1,11 +1,13 @@
 use crate::{
     core::algebra::Vector2,
     core::pool::Handle,
+    grid::{Column, GridBuilder, Row},
     message::MessageData,
     node::UINode,
+    text::TextBuilder,
     vector_image::{Primitive, VectorImageBuilder},
     widget::WidgetBuilder,
-    BuildContext, Control, HorizontalAlignment, VerticalAlignment, BRUSH_BRIGHT,
+    BuildContext, Control, HorizontalAlignment, Thickness, VerticalAlignment, BRUSH_BRIGHT,
 };
 
 pub enum ArrowDirection {
@@ -58,3 +60,41 @@ pub fn make_arrow<M: MessageData, C: Control<M, C>>(
     }])
     .build(ctx)
 }
+
+/// Creates "Label : Editor" row - a grid with auto-sized column for the label and stretched
+/// column for the `editor`, both are vertically centered. Rows that use the same
+/// `shared_size_group` will have label columns of the same width, so editors of all rows
+/// of a property panel are aligned.
+pub fn make_labeled_row<M: MessageData, C: Control<M, C>>(
+    ctx: &mut BuildContext<M, C>,
+    label_text: &str,
+    editor: Handle<UINode<M, C>>,
+    shared_size_group: Option<&str>,
+) -> Handle<UINode<M, C>> {
+    let label = TextBuilder::new(
+        WidgetBuilder::new()
+            .on_column(0)
+            .with_margin(Thickness::right(4.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(label_text)
+    .build(ctx);
+
+    if editor.is_some() {
+        ctx[editor]
+            .set_row(0)
+            .set_column(1)
+            .set_vertical_alignment(VerticalAlignment::Center);
+    }
+
+    let label_column = match shared_size_group {
+        Some(group) => Column::auto().with_shared_size_group(group),
+        None => Column::auto(),
+    };
+
+    GridBuilder::new(WidgetBuilder::new().with_child(label).with_child(editor))
+        .add_row(Row::auto())
+        .add_column(label_column)
+        .add_column(Column::stretch())
+        .build(ctx)
+}
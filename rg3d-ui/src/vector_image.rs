@@ -1,5 +1,6 @@
 use crate::draw::Draw;
 use crate::{
+    accessibility::AccessibleRole,
     core::{algebra::Vector2, color::Color, math::Vector2Ext, pool::Handle},
     draw::{CommandTexture, DrawingContext},
     message::{MessageData, UiMessage},
@@ -76,6 +77,10 @@ pub struct VectorImage<M: MessageData, C: Control<M, C>> {
 crate::define_widget_deref!(VectorImage<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for VectorImage<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Image
+    }
+
     fn measure_override(
         &self,
         _ui: &UserInterface<M, C>,
@@ -120,6 +125,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for VectorImage<M, C> {
                         bounds.position + *begin,
                         bounds.position + *end,
                         *thickness,
+                        Color::WHITE,
                     );
                 }
                 Primitive::Circle {
@@ -1,9 +1,14 @@
 use crate::{
     brush::Brush,
-    core::{algebra::Vector2, math::Rect, pool::Handle},
+    core::{
+        algebra::{Matrix3, Vector2},
+        color::Color,
+        math::Rect,
+        pool::Handle,
+    },
     message::{CursorIcon, MessageData, MessageDirection, UiMessage, UiMessageData, WidgetMessage},
-    Control, HorizontalAlignment, Thickness, UINode, UserInterface, VerticalAlignment,
-    BRUSH_FOREGROUND, BRUSH_PRIMARY,
+    Control, HorizontalAlignment, MeasureCache, Thickness, UINode, UserInterface,
+    VerticalAlignment, BRUSH_FOREGROUND, BRUSH_PRIMARY,
 };
 use std::{
     any::Any,
@@ -12,6 +17,33 @@ use std::{
     rc::Rc,
 };
 
+/// Defines where tooltip of a widget will be shown. If tooltip does not fit on screen at
+/// desired side, it will be flipped to opposite side.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum TooltipPlacement {
+    /// Near the cursor, with small offset so the tooltip won't overlap the cursor.
+    #[default]
+    Cursor,
+    Above,
+    Below,
+    Right,
+}
+
+/// Defines how arrow keys move keyboard focus among focusable descendants of a container.
+/// Direction of navigation is defined by orientation of the container (or its items panel):
+/// Up/Down keys are used for vertical containers and Left/Right for horizontal. Item containers
+/// (list view, tree) are single tab stops, their arrow keys move selection among items instead.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum NavMode {
+    /// Arrow keys are not handled by the container.
+    #[default]
+    None,
+    /// Focus moves to adjacent item and stops at the ends.
+    Contained,
+    /// Focus moves to adjacent item and wraps around at the ends.
+    Cycle,
+}
+
 #[derive(Debug, Clone)]
 pub struct Widget<M: MessageData, C: Control<M, C>> {
     pub(in crate) handle: Handle<UINode<M, C>>,
@@ -34,6 +66,10 @@ pub struct Widget<M: MessageData, C: Control<M, C>> {
     row: usize,
     /// Index of column to which this node belongs
     column: usize,
+    /// Attached properties of flex panel, see `FlexPanel`.
+    flex_grow: f32,
+    flex_shrink: f32,
+    flex_basis: Option<f32>,
     /// Vertical alignment
     vertical_alignment: VerticalAlignment,
     /// Horizontal alignment
@@ -49,21 +85,49 @@ pub struct Widget<M: MessageData, C: Control<M, C>> {
     pub(in crate) command_indices: RefCell<Vec<usize>>,
     pub(in crate) is_mouse_directly_over: bool,
     hit_test_visibility: bool,
+    /// Widget itself is never picked, but its descendants are, see `set_click_through`.
+    click_through: bool,
     z_index: usize,
     allow_drag: bool,
     allow_drop: bool,
     pub user_data: Option<Rc<dyn Any>>,
     draw_on_top: bool,
+    render_layer: u8,
+    /// Whether the widget can receive keyboard focus by Tab navigation.
+    tab_stop: bool,
+    /// Tab and arrow navigation does not cross bounds of a focus scope.
+    focus_scope: bool,
+    keyboard_navigation: NavMode,
+    /// Any widget (sub-tree) that will be shown when mouse hovers the widget.
+    tooltip: Handle<UINode<M, C>>,
+    tooltip_placement: TooltipPlacement,
+    /// Overrides label of the widget in accessibility tree.
+    accessible_label: Option<String>,
     marker: PhantomData<M>,
     enabled: bool,
     cursor: Option<CursorIcon>,
+    /// Hides OS cursor while the widget captures mouse, see `UserInterface::is_cursor_visible`.
+    hides_cursor: bool,
     opacity: f32,
+    /// Color that multiplies colors of the widget, see `set_tint`.
+    tint: Color,
+    /// Visual transform around center of the widget, see `set_render_transform`.
+    render_transform: Matrix3<f32>,
+    /// Extends region in which the widget can be picked, see `set_hit_test_padding`.
+    hit_test_padding: Thickness,
+    /// Repeated value change notifications are dropped, see `set_event_coalescing`.
+    event_coalescing: bool,
+    /// Desired size that replaces result of `measure_override`, see
+    /// `set_measure_override_size`.
+    measure_override_size: Option<Vector2<f32>>,
 
     /// Layout. Interior mutability is a must here because layout performed in
     /// a series of recursive calls.
     pub(in crate) measure_valid: Cell<bool>,
     pub(in crate) arrange_valid: Cell<bool>,
-    pub(in crate) prev_measure: Cell<Vector2<f32>>,
+    /// Star-sized grids measure children twice per layout, so results of both passes must
+    /// be kept.
+    pub(in crate) measure_cache: Cell<MeasureCache>,
     pub(in crate) prev_arrange: Cell<Rect<f32>>,
     /// Desired size of the node after Measure pass.
     pub(in crate) desired_size: Cell<Vector2<f32>>,
@@ -73,9 +137,19 @@ pub struct Widget<M: MessageData, C: Control<M, C>> {
     pub(in crate) actual_size: Cell<Vector2<f32>>,
     pub(in crate) prev_global_visibility: bool,
     pub(in crate) clip_bounds: Cell<Rect<f32>>,
+    /// Screen bounds of the node at last draw, `None` if it was not visible.
+    pub(in crate) drawn_bounds: Cell<Option<Rect<f32>>>,
+    /// Node was changed since last draw, see `UserInterface::dirty_region`.
+    pub(in crate) visual_changed: Cell<bool>,
+    /// Property that is inherited by descendants (opacity, tint, render transform) was changed
+    /// since last draw, so the whole sub-tree is changed.
+    pub(in crate) subtree_visual_changed: Cell<bool>,
 }
 
 impl<M: MessageData, C: Control<M, C>> Widget<M, C> {
+    /// Returns handle of the node that owns the widget. Every built-in and user widget gets it
+    /// when its node is added to the UI, so widgets can invalidate, capture mouse or focus
+    /// themselves by sending messages to this handle.
     pub fn handle(&self) -> Handle<UINode<M, C>> {
         self.handle
     }
@@ -129,6 +203,15 @@ impl<M: MessageData, C: Control<M, C>> Widget<M, C> {
     pub fn invalidate_layout(&self) {
         self.measure_valid.set(false);
         self.arrange_valid.set(false);
+        self.visual_changed.set(true);
+    }
+
+    /// Marks the node as changed, so its bounds will be in the dirty region of the UI (see
+    /// `UserInterface::dirty_region`). Must be called by widgets that change their look
+    /// without layout invalidation or messages, for example in `update`.
+    #[inline]
+    pub fn invalidate_visual(&self) {
+        self.visual_changed.set(true);
     }
 
     pub fn is_hit_test_visible(&self) -> bool {
@@ -195,6 +278,15 @@ impl<M: MessageData, C: Control<M, C>> Widget<M, C> {
         self
     }
 
+    /// Makes width of the widget automatic (NaN), so it will be defined by desired size of its
+    /// content, and invalidates layout.
+    #[inline]
+    pub fn reset_width(&mut self) -> &mut Self {
+        self.width = f32::NAN;
+        self.invalidate_layout();
+        self
+    }
+
     #[inline]
     pub fn width(&self) -> f32 {
         self.width
@@ -204,12 +296,129 @@ impl<M: MessageData, C: Control<M, C>> Widget<M, C> {
         self.draw_on_top
     }
 
+    #[inline]
+    pub fn set_draw_on_top(&mut self, draw_on_top: bool) -> &mut Self {
+        self.draw_on_top = draw_on_top;
+        self
+    }
+
+    /// Sets render layer of the widget, see `WidgetBuilder::with_render_layer`.
+    #[inline]
+    pub fn set_render_layer(&mut self, render_layer: u8) -> &mut Self {
+        self.render_layer = render_layer;
+        self
+    }
+
+    #[inline]
+    pub fn render_layer(&self) -> u8 {
+        self.render_layer
+    }
+
+    #[inline]
+    pub fn set_hit_test_visibility(&mut self, visibility: bool) -> &mut Self {
+        self.hit_test_visibility = visibility;
+        self
+    }
+
+    #[inline]
+    pub fn is_click_through(&self) -> bool {
+        self.click_through
+    }
+
+    /// Makes the widget transparent for mouse: clicks on the widget itself (its background,
+    /// etc.) will pass through it, but its descendants remain hit-testable. Unlike hit test
+    /// visibility, it does not affect descendants. It is useful for full-screen containers of
+    /// HUDs on top of game scene.
+    #[inline]
+    pub fn set_click_through(&mut self, click_through: bool) -> &mut Self {
+        self.click_through = click_through;
+        self
+    }
+
+    #[inline]
+    pub fn tooltip(&self) -> Handle<UINode<M, C>> {
+        self.tooltip
+    }
+
+    #[inline]
+    pub(in crate) fn set_tooltip(&mut self, tooltip: Handle<UINode<M, C>>) -> &mut Self {
+        self.tooltip = tooltip;
+        self
+    }
+
+    #[inline]
+    pub fn tooltip_placement(&self) -> TooltipPlacement {
+        self.tooltip_placement
+    }
+
+    #[inline]
+    pub fn set_tooltip_placement(&mut self, placement: TooltipPlacement) -> &mut Self {
+        self.tooltip_placement = placement;
+        self
+    }
+
+    #[inline]
+    pub fn accessible_label(&self) -> Option<&str> {
+        self.accessible_label.as_deref()
+    }
+
+    #[inline]
+    pub fn set_accessible_label(&mut self, label: Option<String>) -> &mut Self {
+        self.accessible_label = label;
+        self
+    }
+
+    #[inline]
+    pub fn is_tab_stop(&self) -> bool {
+        self.tab_stop
+    }
+
+    #[inline]
+    pub fn set_tab_stop(&mut self, tab_stop: bool) -> &mut Self {
+        self.tab_stop = tab_stop;
+        self
+    }
+
+    #[inline]
+    pub fn is_focus_scope(&self) -> bool {
+        self.focus_scope
+    }
+
+    /// Makes the widget a focus scope: Tab and arrow navigation inside the widget will cycle
+    /// within it and never leave it, tab stops of nested focus scopes are not reachable from
+    /// outside.
+    #[inline]
+    pub fn set_focus_scope(&mut self, focus_scope: bool) -> &mut Self {
+        self.focus_scope = focus_scope;
+        self
+    }
+
+    #[inline]
+    pub fn keyboard_navigation(&self) -> NavMode {
+        self.keyboard_navigation
+    }
+
+    #[inline]
+    pub fn set_keyboard_navigation(&mut self, mode: NavMode) -> &mut Self {
+        self.keyboard_navigation = mode;
+        self
+    }
+
     #[inline]
     pub fn set_height(&mut self, height: f32) -> &mut Self {
         self.height = height.max(self.min_size.y).min(self.max_size.y);
         self
     }
 
+    /// Makes height of the widget automatic (NaN), so it will be defined by desired size of its
+    /// content, and invalidates layout.
+    #[inline]
+    pub fn reset_height(&mut self) -> &mut Self {
+        self.height = f32::NAN;
+        self.invalidate_layout();
+        self
+    }
+
     #[inline]
     pub fn height(&self) -> f32 {
         self.height
@@ -247,6 +456,21 @@ impl<M: MessageData, C: Control<M, C>> Widget<M, C> {
         self.children.clear();
     }
 
+    /// Moves child to given position in children list, index is clamped to valid range.
+    /// Returns true if order of children has changed.
+    pub(in crate) fn set_child_index(&mut self, child: Handle<UINode<M, C>>, index: usize) -> bool {
+        if let Some(i) = self.children.iter().position(|h| *h == child) {
+            let index = index.min(self.children.len() - 1);
+            if i != index {
+                self.children.remove(i);
+                self.children.insert(index, child);
+                self.invalidate_layout();
+                return true;
+            }
+        }
+        false
+    }
+
     #[inline]
     pub(in crate) fn remove_child(&mut self, child: Handle<UINode<M, C>>) {
         if let Some(i) = self.children.iter().position(|h| *h == child) {
@@ -281,6 +505,46 @@ impl<M: MessageData, C: Control<M, C>> Widget<M, C> {
         self.row
     }
 
+    /// Sets share of free space of a flex panel that is given to the node, zero (default)
+    /// means that the node does not grow.
+    #[inline]
+    pub fn set_flex_grow(&mut self, grow: f32) -> &mut Self {
+        self.flex_grow = grow.max(0.0);
+        self
+    }
+
+    #[inline]
+    pub fn flex_grow(&self) -> f32 {
+        self.flex_grow
+    }
+
+    /// Sets how much the node shrinks (relative to other nodes and weighted by basis) when
+    /// content of a flex panel overflows it. Default is one, zero means that the node does not
+    /// shrink.
+    #[inline]
+    pub fn set_flex_shrink(&mut self, shrink: f32) -> &mut Self {
+        self.flex_shrink = shrink.max(0.0);
+        self
+    }
+
+    #[inline]
+    pub fn flex_shrink(&self) -> f32 {
+        self.flex_shrink
+    }
+
+    /// Sets initial size of the node in a flex panel along its orientation, before free space
+    /// is distributed. `None` (default) means desired size of the node.
+    #[inline]
+    pub fn set_flex_basis(&mut self, basis: Option<f32>) -> &mut Self {
+        self.flex_basis = basis;
+        self
+    }
+
+    #[inline]
+    pub fn flex_basis(&self) -> Option<f32> {
+        self.flex_basis
+    }
+
     #[inline]
     pub fn desired_size(&self) -> Vector2<f32> {
         self.desired_size.get()
@@ -355,6 +619,9 @@ impl<M: MessageData, C: Control<M, C>> Widget<M, C> {
             if let UiMessageData::Widget(msg) = &msg.data() {
                 match msg {
                     &WidgetMessage::Opacity(opacity) => self.opacity = opacity,
+                    &WidgetMessage::Tint(tint) => self.tint = tint,
+                    &WidgetMessage::RenderLayer(render_layer) => self.render_layer = render_layer,
+                    &WidgetMessage::RenderTransform(transform) => self.render_transform = transform,
                     WidgetMessage::Background(background) => self.background = background.clone(),
                     WidgetMessage::Foreground(foreground) => self.foreground = foreground.clone(),
                     WidgetMessage::Name(name) => self.name = name.clone(),
@@ -433,6 +700,12 @@ impl<M: MessageData, C: Control<M, C>> Widget<M, C> {
                     &WidgetMessage::Cursor(icon) => {
                         self.cursor = icon;
                     }
+                    &WidgetMessage::Tooltip(tooltip) => {
+                        self.set_tooltip(tooltip);
+                    }
+                    &WidgetMessage::TooltipPlacement(placement) => {
+                        self.tooltip_placement = placement;
+                    }
                     _ => (),
                 }
             }
@@ -578,6 +851,16 @@ impl<M: MessageData, C: Control<M, C>> Widget<M, C> {
         self.cursor
     }
 
+    #[inline]
+    pub fn set_hides_cursor(&mut self, hides: bool) {
+        self.hides_cursor = hides;
+    }
+
+    #[inline]
+    pub fn hides_cursor(&self) -> bool {
+        self.hides_cursor
+    }
+
     #[inline]
     pub fn user_data_ref<T: 'static>(&self) -> &T {
         self.user_data
@@ -594,6 +877,89 @@ impl<M: MessageData, C: Control<M, C>> Widget<M, C> {
     pub fn opacity(&self) -> f32 {
         self.opacity
     }
+
+    pub fn set_opacity(&mut self, opacity: f32) -> &mut Self {
+        if self.opacity != opacity {
+            self.opacity = opacity;
+            self.subtree_visual_changed.set(true);
+        }
+        self
+    }
+
+    pub fn tint(&self) -> Color {
+        self.tint
+    }
+
+    /// Sets color that multiplies colors of everything the widget draws, white means no
+    /// tint. Alpha of the tint is multiplied too, so it combines with opacity. Tint applies
+    /// to descendants only if tint inheritance is enabled, see
+    /// `UserInterface::set_tint_inheritance`.
+    pub fn set_tint(&mut self, tint: Color) -> &mut Self {
+        if self.tint != tint {
+            self.tint = tint;
+            self.subtree_visual_changed.set(true);
+        }
+        self
+    }
+
+    pub fn render_transform(&self) -> &Matrix3<f32> {
+        &self.render_transform
+    }
+
+    /// Sets transform that is applied to the widget and its descendants when they're drawn.
+    /// Transform is relative to center of the widget, so pure rotation rotates the widget
+    /// around its center. It does not affect layout and hit testing, both still use
+    /// untransformed bounds.
+    pub fn set_render_transform(&mut self, transform: Matrix3<f32>) -> &mut Self {
+        if self.render_transform != transform {
+            self.render_transform = transform;
+            self.subtree_visual_changed.set(true);
+        }
+        self
+    }
+
+    pub fn hit_test_padding(&self) -> Thickness {
+        self.hit_test_padding
+    }
+
+    /// Expands region in which the widget is picked by cursor or touch beyond its bounds,
+    /// it makes thin widgets easier to grab by finger. Expanded region is still clipped by
+    /// parents and it does not affect layout and drawing. Zero by default.
+    pub fn set_hit_test_padding(&mut self, padding: Thickness) -> &mut Self {
+        self.hit_test_padding = padding;
+        self
+    }
+
+    pub fn measure_override_size(&self) -> Option<Vector2<f32>> {
+        self.measure_override_size
+    }
+
+    /// Forces the widget to use given size as result of `measure_override` instead of
+    /// measuring content, it allows to skip measurement of deep static sub-trees when their
+    /// size is known. Children are still measured (with the forced size as available size)
+    /// to be arranged, but their desired sizes do not affect the widget, so children that are
+    /// larger than the forced size will be clipped. Explicit width, height and size limits
+    /// are still applied on top of the forced size. `None` by default.
+    pub fn set_measure_override_size(&mut self, size: Option<Vector2<f32>>) -> &mut Self {
+        if self.measure_override_size != size {
+            self.measure_override_size = size;
+            self.invalidate_layout();
+        }
+        self
+    }
+
+    pub fn is_event_coalescing_enabled(&self) -> bool {
+        self.event_coalescing
+    }
+
+    /// Enables or disables dropping of value change notifications (see
+    /// `UiMessageData::is_value_change`) that repeat the value of previous notification from
+    /// the widget. Disabled by default, so listeners receive every notification; enable it
+    /// for widgets whose notifications are expensive to handle.
+    pub fn set_event_coalescing(&mut self, enabled: bool) -> &mut Self {
+        self.event_coalescing = enabled;
+        self
+    }
 }
 
 #[macro_export]
@@ -628,18 +994,35 @@ pub struct WidgetBuilder<M: MessageData, C: Control<M, C>> {
     pub foreground: Option<Brush>,
     pub row: usize,
     pub column: usize,
+    pub flex_grow: f32,
+    pub flex_shrink: f32,
+    pub flex_basis: Option<f32>,
     pub margin: Thickness,
     pub children: Vec<Handle<UINode<M, C>>>,
     pub is_hit_test_visible: bool,
+    pub click_through: bool,
     pub visibility: bool,
     pub z_index: usize,
     pub allow_drag: bool,
     pub allow_drop: bool,
     pub user_data: Option<Rc<dyn Any>>,
     pub draw_on_top: bool,
+    pub render_layer: u8,
+    pub tab_stop: Option<bool>,
+    pub focus_scope: bool,
+    pub keyboard_navigation: Option<NavMode>,
+    pub tooltip: Handle<UINode<M, C>>,
+    pub tooltip_placement: TooltipPlacement,
+    pub accessible_label: Option<String>,
     pub enabled: bool,
     pub cursor: Option<CursorIcon>,
+    pub hides_cursor: bool,
     pub opacity: f32,
+    pub tint: Color,
+    pub render_transform: Matrix3<f32>,
+    pub hit_test_padding: Thickness,
+    pub event_coalescing: bool,
+    pub measure_override_size: Option<Vector2<f32>>,
 }
 
 impl<M: MessageData, C: Control<M, C>> Default for WidgetBuilder<M, C> {
@@ -662,19 +1045,36 @@ impl<M: MessageData, C: Control<M, C>> WidgetBuilder<M, C> {
             foreground: None,
             row: 0,
             column: 0,
+            flex_grow: 0.0,
+            flex_shrink: 1.0,
+            flex_basis: None,
             margin: Thickness::zero(),
             desired_position: Vector2::default(),
             children: Vec::new(),
             is_hit_test_visible: true,
+            click_through: false,
             visibility: true,
             z_index: 0,
             allow_drag: false,
             allow_drop: false,
             user_data: None,
             draw_on_top: false,
+            render_layer: 0,
+            tab_stop: None,
+            focus_scope: false,
+            keyboard_navigation: None,
+            tooltip: Handle::NONE,
+            tooltip_placement: TooltipPlacement::Cursor,
+            accessible_label: None,
             enabled: true,
             cursor: None,
+            hides_cursor: false,
             opacity: 1.0,
+            tint: Color::WHITE,
+            render_transform: Matrix3::identity(),
+            hit_test_padding: Thickness::zero(),
+            measure_override_size: None,
+            event_coalescing: false,
         }
     }
 
@@ -733,11 +1133,62 @@ impl<M: MessageData, C: Control<M, C>> WidgetBuilder<M, C> {
         self
     }
 
+    /// See `Widget::set_flex_grow`.
+    pub fn with_flex_grow(mut self, grow: f32) -> Self {
+        self.flex_grow = grow;
+        self
+    }
+
+    /// See `Widget::set_flex_shrink`.
+    pub fn with_flex_shrink(mut self, shrink: f32) -> Self {
+        self.flex_shrink = shrink;
+        self
+    }
+
+    /// See `Widget::set_flex_basis`.
+    pub fn with_flex_basis(mut self, basis: f32) -> Self {
+        self.flex_basis = Some(basis);
+        self
+    }
+
     pub fn with_margin(mut self, margin: Thickness) -> Self {
         self.margin = margin;
         self
     }
 
+    /// Same as `on_row(row).on_column(column)`.
+    pub fn in_cell(self, row: usize, column: usize) -> Self {
+        self.on_row(row).on_column(column)
+    }
+
+    /// Sets same margin for every side.
+    pub fn with_margin_uniform(self, margin: f32) -> Self {
+        self.with_margin(Thickness::uniform(margin))
+    }
+
+    /// Sets margin for each side separately, in the same order as fields of `Thickness`.
+    pub fn with_margin_ltrb(self, left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        self.with_margin(Thickness {
+            left,
+            top,
+            right,
+            bottom,
+        })
+    }
+
+    /// Centers the widget on both axes. It overrides alignments that were set before and can
+    /// be overridden by the alignments set after.
+    pub fn centered(self) -> Self {
+        self.with_horizontal_alignment(HorizontalAlignment::Center)
+            .with_vertical_alignment(VerticalAlignment::Center)
+    }
+
+    /// Stretches the widget on both axes, it is the default alignment.
+    pub fn stretch(self) -> Self {
+        self.with_horizontal_alignment(HorizontalAlignment::Stretch)
+            .with_vertical_alignment(VerticalAlignment::Stretch)
+    }
+
     pub fn with_desired_position(mut self, desired_position: Vector2<f32>) -> Self {
         self.desired_position = desired_position;
         self
@@ -760,6 +1211,15 @@ impl<M: MessageData, C: Control<M, C>> WidgetBuilder<M, C> {
         self
     }
 
+    /// Sets render layer of the widget. Unlike z-index, which orders only siblings, layers span
+    /// whole tree: sub-trees of higher layers are drawn after (and picked before) everything of
+    /// lower layers regardless of their position in the tree. Layer `0` (default) means that
+    /// widget stays in the layer of its parent.
+    pub fn with_render_layer(mut self, render_layer: u8) -> Self {
+        self.render_layer = render_layer;
+        self
+    }
+
     pub fn with_children<'a, I: IntoIterator<Item = &'a Handle<UINode<M, C>>>>(
         mut self,
         children: I,
@@ -782,11 +1242,50 @@ impl<M: MessageData, C: Control<M, C>> WidgetBuilder<M, C> {
         self
     }
 
+    pub fn with_click_through(mut self, click_through: bool) -> Self {
+        self.click_through = click_through;
+        self
+    }
+
     pub fn with_visibility(mut self, visibility: bool) -> Self {
         self.visibility = visibility;
         self
     }
 
+    /// Sets a widget that will be shown as tooltip. It could be any widget, not just
+    /// a text. Tooltip will be hidden until mouse hovers the widget.
+    pub fn with_tooltip(mut self, tooltip: Handle<UINode<M, C>>) -> Self {
+        self.tooltip = tooltip;
+        self
+    }
+
+    pub fn with_tooltip_placement(mut self, placement: TooltipPlacement) -> Self {
+        self.tooltip_placement = placement;
+        self
+    }
+
+    /// Sets label that will be used in accessibility tree instead of label provided by
+    /// the widget itself.
+    pub fn with_accessible_label<S: AsRef<str>>(mut self, label: S) -> Self {
+        self.accessible_label = Some(label.as_ref().to_owned());
+        self
+    }
+
+    pub fn with_keyboard_navigation(mut self, mode: NavMode) -> Self {
+        self.keyboard_navigation = Some(mode);
+        self
+    }
+
+    pub fn with_tab_stop(mut self, tab_stop: bool) -> Self {
+        self.tab_stop = Some(tab_stop);
+        self
+    }
+
+    pub fn with_focus_scope(mut self, focus_scope: bool) -> Self {
+        self.focus_scope = focus_scope;
+        self
+    }
+
     pub fn with_allow_drop(mut self, allow_drop: bool) -> Self {
         self.allow_drop = allow_drop;
         self
@@ -807,11 +1306,45 @@ impl<M: MessageData, C: Control<M, C>> WidgetBuilder<M, C> {
         self
     }
 
+    /// Hides OS cursor while the widget captures mouse, for example while the cursor is used
+    /// to drag a value.
+    pub fn with_hides_cursor(mut self, hides: bool) -> Self {
+        self.hides_cursor = hides;
+        self
+    }
+
     pub fn with_opacity(mut self, opacity: f32) -> Self {
         self.opacity = opacity;
         self
     }
 
+    /// See `Widget::set_tint`.
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    pub fn with_render_transform(mut self, transform: Matrix3<f32>) -> Self {
+        self.render_transform = transform;
+        self
+    }
+
+    pub fn with_hit_test_padding(mut self, padding: Thickness) -> Self {
+        self.hit_test_padding = padding;
+        self
+    }
+
+    /// See `Widget::set_event_coalescing`.
+    pub fn with_event_coalescing(mut self, enabled: bool) -> Self {
+        self.event_coalescing = enabled;
+        self
+    }
+
+    pub fn with_measure_override_size(mut self, size: Vector2<f32>) -> Self {
+        self.measure_override_size = Some(size);
+        self
+    }
+
     pub fn build(self) -> Widget<M, C> {
         Widget {
             handle: Default::default(),
@@ -831,6 +1364,9 @@ impl<M: MessageData, C: Control<M, C>> WidgetBuilder<M, C> {
             foreground: self.foreground.unwrap_or_else(|| BRUSH_FOREGROUND.clone()),
             row: self.row,
             column: self.column,
+            flex_grow: self.flex_grow.max(0.0),
+            flex_shrink: self.flex_shrink.max(0.0),
+            flex_basis: self.flex_basis,
             vertical_alignment: self.vertical_alignment,
             horizontal_alignment: self.horizontal_alignment,
             margin: self.margin,
@@ -843,19 +1379,36 @@ impl<M: MessageData, C: Control<M, C>> WidgetBuilder<M, C> {
             is_mouse_directly_over: false,
             measure_valid: Cell::new(false),
             arrange_valid: Cell::new(false),
+            drawn_bounds: Cell::new(None),
+            visual_changed: Cell::new(true),
+            subtree_visual_changed: Cell::new(false),
             hit_test_visibility: self.is_hit_test_visible,
-            prev_measure: Default::default(),
+            click_through: self.click_through,
+            measure_cache: Default::default(),
             prev_arrange: Default::default(),
             z_index: self.z_index,
             allow_drag: self.allow_drag,
             allow_drop: self.allow_drop,
             user_data: self.user_data.clone(),
             draw_on_top: self.draw_on_top,
+            render_layer: self.render_layer,
+            tab_stop: self.tab_stop.unwrap_or(false),
+            focus_scope: self.focus_scope,
+            keyboard_navigation: self.keyboard_navigation.unwrap_or_default(),
+            tooltip: self.tooltip,
+            tooltip_placement: self.tooltip_placement,
+            accessible_label: self.accessible_label.clone(),
             marker: PhantomData,
             enabled: self.enabled,
             cursor: self.cursor,
+            hides_cursor: self.hides_cursor,
             clip_bounds: Cell::new(Default::default()),
             opacity: self.opacity,
+            tint: self.tint,
+            render_transform: self.render_transform,
+            hit_test_padding: self.hit_test_padding,
+            event_coalescing: self.event_coalescing,
+            measure_override_size: self.measure_override_size,
         }
     }
 }
@@ -1,9 +1,11 @@
 use crate::{
+    accessibility::AccessibleRole,
     border::BorderBuilder,
     brush::{Brush, GradientPoint},
     button::ButtonBuilder,
     core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
     decorator::DecoratorBuilder,
+    draw::{Backdrop, CommandTexture, Draw, DrawingContext},
     grid::{Column, GridBuilder, Row},
     message::{
         ButtonMessage, CursorIcon, MessageData, MessageDirection, TextMessage, UiMessage,
@@ -35,18 +37,47 @@ pub struct Window<M: MessageData, C: Control<M, C>> {
     can_minimize: bool,
     can_close: bool,
     can_resize: bool,
+    is_modal: bool,
+    size_to_content: SizeToContent,
     header: Handle<UINode<M, C>>,
     minimize_button: Handle<UINode<M, C>>,
     close_button: Handle<UINode<M, C>>,
     drag_delta: Vector2<f32>,
     content: Handle<UINode<M, C>>,
     grips: RefCell<[Grip; 8]>,
+    /// See `set_resize_border_thickness`.
+    resize_border_thickness: f32,
     title: Handle<UINode<M, C>>,
     title_grid: Handle<UINode<M, C>>,
+    backdrop: Backdrop,
 }
 
-const GRIP_SIZE: f32 = 6.0;
-const CORNER_GRIP_SIZE: f32 = GRIP_SIZE * 2.0;
+/// Defines on which axes a window will be sized to the desired size of its content.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SizeToContent {
+    /// Window uses its explicit size.
+    Manual,
+    Width,
+    Height,
+    WidthAndHeight,
+}
+
+impl SizeToContent {
+    pub fn affects_width(self) -> bool {
+        matches!(self, SizeToContent::Width | SizeToContent::WidthAndHeight)
+    }
+
+    pub fn affects_height(self) -> bool {
+        matches!(self, SizeToContent::Height | SizeToContent::WidthAndHeight)
+    }
+}
+
+/// Default thickness of resize zone along edges of a window.
+pub const DEFAULT_RESIZE_BORDER_THICKNESS: f32 = 6.0;
+
+/// Resize zones extend outside of a resizable window by this distance, so thin edges of the
+/// window are easier to grab by finger.
+const GRIP_HIT_TEST_PADDING: f32 = 4.0;
 
 #[derive(Copy, Clone, Debug)]
 enum GripKind {
@@ -79,9 +110,51 @@ impl Grip {
     }
 }
 
+/// Calculates local bounds of grips of a window of given size, in order of `GripKind`.
+/// Corner grips are twice as large as the border to improve usability, they overlap edge
+/// grips and have priority over them. Grips extend outside of the window by `padding`.
+fn grip_bounds(size: Vector2<f32>, thickness: f32, padding: f32) -> [Rect<f32>; 8] {
+    let corner = thickness * 2.0;
+    let outer = corner + padding;
+    [
+        Rect::new(-padding, -padding, outer, outer),
+        Rect::new(size.x - corner, -padding, outer, outer),
+        Rect::new(size.x - corner, size.y - corner, outer, outer),
+        Rect::new(-padding, size.y - corner, outer, outer),
+        Rect::new(
+            -padding,
+            thickness,
+            thickness + padding,
+            size.y - thickness * 2.0,
+        ),
+        Rect::new(
+            thickness,
+            -padding,
+            size.x - thickness * 2.0,
+            thickness + padding,
+        ),
+        Rect::new(
+            size.x - thickness,
+            thickness,
+            thickness + padding,
+            size.y - thickness * 2.0,
+        ),
+        Rect::new(
+            thickness,
+            size.y - thickness,
+            size.x - thickness * 2.0,
+            thickness + padding,
+        ),
+    ]
+}
+
 crate::define_widget_deref!(Window<M, C>);
 
 impl<M: MessageData, C: Control<M, C>> Control<M, C> for Window<M, C> {
+    fn accessible_role(&self) -> AccessibleRole {
+        AccessibleRole::Window
+    }
+
     fn resolve(&mut self, node_map: &NodeHandleMapping<M, C>) {
         node_map.resolve(&mut self.header);
         node_map.resolve(&mut self.minimize_button);
@@ -91,50 +164,59 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Window<M, C> {
         node_map.resolve(&mut self.content);
     }
 
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        drawing_context.draw_backdrop(self.backdrop, &self.screen_bounds(), self.clip_bounds());
+        if self.widget.hit_test_padding() != Thickness::zero() {
+            // Invisible geometry makes the window pickable, so resize zones outside of it
+            // (hit-test padding) can be grabbed.
+            drawing_context.push_rect_filled(&self.screen_bounds(), None);
+            drawing_context.commit(
+                self.clip_bounds(),
+                Brush::Solid(Color::TRANSPARENT),
+                CommandTexture::None,
+                None,
+            );
+        }
+    }
+
+    fn measure_override(
+        &self,
+        ui: &UserInterface<M, C>,
+        available_size: Vector2<f32>,
+    ) -> Vector2<f32> {
+        // Window that is sized to its content must not go outside of the screen.
+        let screen_size = ui.screen_size();
+        let mut available_size = available_size;
+        if self.size_to_content.affects_width() {
+            available_size.x = available_size.x.min(screen_size.x);
+        }
+        if self.size_to_content.affects_height() {
+            available_size.y = available_size.y.min(screen_size.y);
+        }
+
+        let mut desired_size = self.widget.measure_override(ui, available_size);
+        if self.size_to_content.affects_width() {
+            desired_size.x = desired_size.x.min(screen_size.x);
+        }
+        if self.size_to_content.affects_height() {
+            desired_size.y = desired_size.y.min(screen_size.y);
+        }
+        desired_size
+    }
+
     fn arrange_override(&self, ui: &UserInterface<M, C>, final_size: Vector2<f32>) -> Vector2<f32> {
         let size = self.widget.arrange_override(ui, final_size);
 
-        let mut grips = self.grips.borrow_mut();
-
-        // Adjust grips.
-        grips[GripKind::Left as usize].bounds =
-            Rect::new(0.0, GRIP_SIZE, GRIP_SIZE, final_size.y - GRIP_SIZE * 2.0);
-        grips[GripKind::Top as usize].bounds =
-            Rect::new(GRIP_SIZE, 0.0, final_size.x - GRIP_SIZE * 2.0, GRIP_SIZE);
-        grips[GripKind::Right as usize].bounds = Rect::new(
-            final_size.x - GRIP_SIZE,
-            GRIP_SIZE,
-            GRIP_SIZE,
-            final_size.y - GRIP_SIZE * 2.0,
-        );
-        grips[GripKind::Bottom as usize].bounds = Rect::new(
-            GRIP_SIZE,
-            final_size.y - GRIP_SIZE,
-            final_size.x - GRIP_SIZE * 2.0,
-            GRIP_SIZE,
-        );
-
-        // Corners have different size to improve usability.
-        grips[GripKind::LeftTopCorner as usize].bounds =
-            Rect::new(0.0, 0.0, CORNER_GRIP_SIZE, CORNER_GRIP_SIZE);
-        grips[GripKind::RightTopCorner as usize].bounds = Rect::new(
-            final_size.x - GRIP_SIZE,
-            0.0,
-            CORNER_GRIP_SIZE,
-            CORNER_GRIP_SIZE,
-        );
-        grips[GripKind::RightBottomCorner as usize].bounds = Rect::new(
-            final_size.x - CORNER_GRIP_SIZE,
-            final_size.y - CORNER_GRIP_SIZE,
-            CORNER_GRIP_SIZE,
-            CORNER_GRIP_SIZE,
-        );
-        grips[GripKind::LeftBottomCorner as usize].bounds = Rect::new(
-            0.0,
-            final_size.y - CORNER_GRIP_SIZE,
-            CORNER_GRIP_SIZE,
-            CORNER_GRIP_SIZE,
-        );
+        for (grip, bounds) in self.grips.borrow_mut().iter_mut().zip(
+            grip_bounds(
+                final_size,
+                self.resize_border_thickness,
+                self.hit_test_padding().left,
+            )
+            .iter(),
+        ) {
+            grip.bounds = *bounds;
+        }
 
         size
     }
@@ -148,27 +230,34 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Window<M, C> {
 
         match &message.data() {
             UiMessageData::Widget(msg) => {
+                // Click anywhere in a window activates it, but only if there is no other window
+                // in between (nested windows activates themselves).
+                if let WidgetMessage::MouseDown { .. } = msg {
+                    if ui.find_by_criteria_up(message.destination(), |n| {
+                        matches!(n, UINode::Window(_))
+                    }) == self.handle()
+                    {
+                        ui.send_message(WindowMessage::activate(
+                            self.handle(),
+                            MessageDirection::ToWidget,
+                        ));
+                    }
+                }
+
                 // Grip interaction have higher priority than other actions.
                 if self.can_resize {
                     match msg {
-                        &WidgetMessage::MouseDown { pos, .. } => {
-                            ui.send_message(WidgetMessage::topmost(
-                                self.handle(),
-                                MessageDirection::ToWidget,
-                            ));
-
-                            // Check grips.
-                            for grip in self.grips.borrow_mut().iter_mut() {
-                                let offset = self.screen_position;
-                                let screen_bounds = grip.bounds.translate(offset);
-                                if screen_bounds.contains(pos) {
-                                    grip.is_dragging = true;
-                                    self.initial_position = self.actual_local_position();
-                                    self.initial_size = self.actual_size();
-                                    self.mouse_click_pos = pos;
-                                    ui.capture_mouse(self.handle());
-                                    break;
-                                }
+                        // Content that handled the click (scroll bar near the edge, etc.) has
+                        // priority over resizing.
+                        &WidgetMessage::MouseDown { pos, .. } if !message.handled() => {
+                            if let Some(index) = self.grip_at(pos) {
+                                // Manual resizing overrides sizing to content.
+                                self.size_to_content = SizeToContent::Manual;
+                                self.grips.borrow_mut()[index].is_dragging = true;
+                                self.initial_position = self.actual_local_position();
+                                self.initial_size = self.actual_size();
+                                self.mouse_click_pos = pos;
+                                ui.capture_mouse(self.handle());
                             }
                         }
                         WidgetMessage::MouseUp { .. } => {
@@ -180,16 +269,17 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Window<M, C> {
                                 }
                             }
                         }
+                        WidgetMessage::MouseCaptureLost => {
+                            for grip in self.grips.borrow_mut().iter_mut() {
+                                grip.is_dragging = false;
+                            }
+                        }
                         &WidgetMessage::MouseMove { pos, .. } => {
-                            let mut new_cursor = None;
+                            let new_cursor = self
+                                .grip_at(pos)
+                                .map(|index| self.grips.borrow()[index].cursor);
 
                             for grip in self.grips.borrow().iter() {
-                                let offset = self.screen_position;
-                                let screen_bounds = grip.bounds.translate(offset);
-                                if screen_bounds.contains(pos) {
-                                    new_cursor = Some(grip.cursor);
-                                }
-
                                 if grip.is_dragging {
                                     let delta = self.mouse_click_pos - pos;
                                     let (dx, dy, dw, dh) = match grip.kind {
@@ -256,7 +346,7 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Window<M, C> {
                             ));
                             message.set_handled(true);
                         }
-                        WidgetMessage::MouseUp { .. } => {
+                        WidgetMessage::MouseUp { .. } | WidgetMessage::MouseCaptureLost => {
                             ui.send_message(WindowMessage::move_end(
                                 self.handle,
                                 MessageDirection::ToWidget,
@@ -264,7 +354,8 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Window<M, C> {
                             message.set_handled(true);
                         }
                         WidgetMessage::MouseMove { pos, .. } => {
-                            if self.is_dragging {
+                            // Small movement of cursor after click must not move the window.
+                            if self.is_dragging && ui.is_drag_threshold_exceeded() {
                                 self.drag_delta = *pos - self.mouse_click_pos;
                                 let new_pos = self.initial_position + self.drag_delta;
                                 ui.send_message(WindowMessage::move_to(
@@ -278,9 +369,19 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Window<M, C> {
                         _ => (),
                     }
                 }
-                if let WidgetMessage::Unlink = msg {
-                    if message.destination() == self.handle() {
-                        self.initial_position = self.screen_position;
+                if message.destination() == self.handle() {
+                    match msg {
+                        WidgetMessage::Unlink => {
+                            self.initial_position = self.screen_position;
+                        }
+                        // Explicit size is ignored on axes that are sized to content.
+                        WidgetMessage::Width(_) if self.size_to_content.affects_width() => {
+                            self.widget.reset_width();
+                        }
+                        WidgetMessage::Height(_) if self.size_to_content.affects_height() => {
+                            self.widget.reset_height();
+                        }
+                        _ => (),
                     }
                 }
             }
@@ -343,6 +444,9 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Window<M, C> {
                                     handle: self.handle(),
                                     stop: true,
                                 });
+                                self.set_focus_scope(true);
+                                ui.push_focus_scope(self.handle());
+                                self.is_modal = true;
                             }
                         }
                         WindowMessage::Close => {
@@ -353,8 +457,32 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Window<M, C> {
                                     false,
                                 ));
                                 ui.remove_picking_restriction(self.handle());
+                                ui.remove_focus_scope(self.handle());
+                                if self.is_modal {
+                                    self.set_focus_scope(false);
+                                }
+                                self.is_modal = false;
+                            }
+                        }
+                        &WindowMessage::Backdrop(backdrop) => {
+                            if self.backdrop != backdrop {
+                                self.backdrop = backdrop;
+                                ui.send_message(message.reverse());
+                            }
+                        }
+                        &WindowMessage::SizeToContent(size_to_content) => {
+                            if self.size_to_content != size_to_content {
+                                self.set_size_to_content(size_to_content);
+                                ui.send_message(message.reverse());
                             }
                         }
+                        WindowMessage::Activate => {
+                            ui.send_message(WidgetMessage::topmost(
+                                self.handle(),
+                                MessageDirection::ToWidget,
+                            ));
+                            ui.send_message(message.reverse());
+                        }
                         &WindowMessage::Minimize(minimized) => {
                             if self.minimized != minimized {
                                 self.minimized = minimized;
@@ -396,7 +524,13 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for Window<M, C> {
                         }
                         &WindowMessage::CanResize(value) => {
                             if self.can_resize != value {
-                                self.can_resize = value;
+                                self.set_can_resize(value);
+                                ui.send_message(message.reverse());
+                            }
+                        }
+                        &WindowMessage::ResizeBorderThickness(thickness) => {
+                            if self.resize_border_thickness != thickness {
+                                self.set_resize_border_thickness(thickness);
                                 ui.send_message(message.reverse());
                             }
                         }
@@ -519,11 +653,73 @@ impl<M: MessageData, C: Control<M, C>> Window<M, C> {
 
     pub fn set_can_resize(&mut self, value: bool) {
         self.can_resize = value;
+        self.update_grip_padding();
     }
 
     pub fn can_resize(&self) -> bool {
         self.can_resize
     }
+
+    /// Sets how close (in units) to an edge of the window the cursor must be to resize the
+    /// window instead of interacting with its content or moving it by the title bar. Corner
+    /// zones are twice as large. Zero disables resizing by mouse.
+    pub fn set_resize_border_thickness(&mut self, thickness: f32) {
+        self.resize_border_thickness = thickness.max(0.0);
+        self.update_grip_padding();
+        self.widget.invalidate_layout();
+    }
+
+    /// Resize zones of a resizable window extend outside of it, see `GRIP_HIT_TEST_PADDING`.
+    fn update_grip_padding(&mut self) {
+        let padding = if self.can_resize && self.resize_border_thickness > 0.0 {
+            Thickness::uniform(GRIP_HIT_TEST_PADDING)
+        } else {
+            Thickness::zero()
+        };
+        self.widget.set_hit_test_padding(padding);
+        self.widget.invalidate_layout();
+    }
+
+    pub fn resize_border_thickness(&self) -> f32 {
+        self.resize_border_thickness
+    }
+
+    /// Returns index of the grip under given screen position, corners have priority.
+    fn grip_at(&self, pos: Vector2<f32>) -> Option<usize> {
+        if self.resize_border_thickness <= 0.0 {
+            return None;
+        }
+        self.grips
+            .borrow()
+            .iter()
+            .position(|grip| grip.bounds.translate(self.screen_position).contains(pos))
+    }
+
+    /// Sets the axes on which window will be sized to desired size of its content. Explicit
+    /// size on such axes is discarded.
+    pub fn set_size_to_content(&mut self, size_to_content: SizeToContent) {
+        self.size_to_content = size_to_content;
+        if size_to_content.affects_width() {
+            self.widget.reset_width();
+        }
+        if size_to_content.affects_height() {
+            self.widget.reset_height();
+        }
+        self.widget.invalidate_layout();
+    }
+
+    pub fn size_to_content(&self) -> SizeToContent {
+        self.size_to_content
+    }
+
+    /// Returns true if window was opened in modal mode.
+    pub fn is_modal(&self) -> bool {
+        self.is_modal
+    }
+
+    pub fn backdrop(&self) -> Backdrop {
+        self.backdrop
+    }
 }
 
 pub struct WindowBuilder<M: MessageData, C: Control<M, C>> {
@@ -538,6 +734,9 @@ pub struct WindowBuilder<M: MessageData, C: Control<M, C>> {
     // Warning: Any dependant builders must take this into account!
     pub modal: bool,
     pub can_resize: bool,
+    pub resize_border_thickness: f32,
+    pub size_to_content: SizeToContent,
+    pub backdrop: Backdrop,
 }
 
 /// Window title can be either text or node.
@@ -657,6 +856,9 @@ impl<'a, M: MessageData, C: Control<M, C>> WindowBuilder<M, C> {
             minimize_button: None,
             modal: false,
             can_resize: true,
+            resize_border_thickness: DEFAULT_RESIZE_BORDER_THICKNESS,
+            size_to_content: SizeToContent::Manual,
+            backdrop: Backdrop::None,
         }
     }
 
@@ -705,6 +907,23 @@ impl<'a, M: MessageData, C: Control<M, C>> WindowBuilder<M, C> {
         self
     }
 
+    pub fn with_resize_border_thickness(mut self, thickness: f32) -> Self {
+        self.resize_border_thickness = thickness;
+        self
+    }
+
+    pub fn with_size_to_content(mut self, size_to_content: SizeToContent) -> Self {
+        self.size_to_content = size_to_content;
+        self
+    }
+
+    /// Sets backdrop that is drawn behind the window, the window should have translucent
+    /// background to make the backdrop visible.
+    pub fn with_backdrop(mut self, backdrop: Backdrop) -> Self {
+        self.backdrop = backdrop;
+        self
+    }
+
     pub fn build_window(self, ctx: &mut BuildContext<M, C>) -> Window<M, C> {
         let minimize_button;
         let close_button;
@@ -783,7 +1002,7 @@ impl<'a, M: MessageData, C: Control<M, C>> WindowBuilder<M, C> {
         if self.content.is_some() {
             ctx[self.content].set_row(1);
         }
-        Window {
+        let mut window = Window {
             widget: self
                 .widget_builder
                 .with_visibility(self.open)
@@ -815,6 +1034,8 @@ impl<'a, M: MessageData, C: Control<M, C>> WindowBuilder<M, C> {
             can_minimize: self.can_minimize,
             can_close: self.can_close,
             can_resize: self.can_resize,
+            is_modal: self.modal && self.open,
+            size_to_content: SizeToContent::Manual,
             header,
             minimize_button,
             close_button,
@@ -831,9 +1052,14 @@ impl<'a, M: MessageData, C: Control<M, C>> WindowBuilder<M, C> {
                 Grip::new(GripKind::Right, CursorIcon::EResize),
                 Grip::new(GripKind::Bottom, CursorIcon::SResize),
             ]),
+            resize_border_thickness: self.resize_border_thickness.max(0.0),
             title,
             title_grid,
-        }
+            backdrop: self.backdrop,
+        };
+        window.set_size_to_content(self.size_to_content);
+        window.update_grip_padding();
+        window
     }
 
     pub fn build(self, ctx: &mut BuildContext<M, C>) -> Handle<UINode<M, C>> {
@@ -846,8 +1072,56 @@ impl<'a, M: MessageData, C: Control<M, C>> WindowBuilder<M, C> {
         if modal && open {
             ctx.ui
                 .push_picking_restriction(RestrictionEntry { handle, stop: true });
+            ctx.ui.push_focus_scope(handle);
         }
 
         handle
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        window::{grip_bounds, GripKind},
+    };
+
+    #[test]
+    fn resize_zones() {
+        let bounds = grip_bounds(Vector2::new(200.0, 100.0), 6.0, 4.0);
+        let grip_at = |x: f32, y: f32| {
+            bounds
+                .iter()
+                .position(|b| b.contains(Vector2::new(x, y)))
+                .map(|index| index as u32)
+        };
+
+        for &(x, y, kind) in [
+            (1.0, 1.0, GripKind::LeftTopCorner),
+            (199.0, 1.0, GripKind::RightTopCorner),
+            (199.0, 99.0, GripKind::RightBottomCorner),
+            (1.0, 99.0, GripKind::LeftBottomCorner),
+            (1.0, 50.0, GripKind::Left),
+            (100.0, 1.0, GripKind::Top),
+            (199.0, 50.0, GripKind::Right),
+            (100.0, 99.0, GripKind::Bottom),
+        ]
+        .iter()
+        {
+            assert_eq!(grip_at(x, y), Some(kind as u32));
+        }
+
+        // Resize zones extend outside of the window by the padding.
+        assert_eq!(grip_at(-3.0, -3.0), Some(GripKind::LeftTopCorner as u32));
+        assert_eq!(grip_at(-3.0, 50.0), Some(GripKind::Left as u32));
+        assert_eq!(
+            grip_at(203.0, 103.0),
+            Some(GripKind::RightBottomCorner as u32)
+        );
+        assert_eq!(grip_at(-5.0, 50.0), None);
+
+        // Title bar right below the top border moves the window, interior is not a grip.
+        assert_eq!(grip_at(100.0, 10.0), None);
+        assert_eq!(grip_at(100.0, 50.0), None);
+    }
+}
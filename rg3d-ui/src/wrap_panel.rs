@@ -17,6 +17,8 @@ use std::{
 pub struct WrapPanel<M: MessageData, C: Control<M, C>> {
     widget: Widget<M, C>,
     orientation: Orientation,
+    item_spacing: f32,
+    line_spacing: f32,
     lines: RefCell<Vec<Line>>,
 }
 
@@ -27,6 +29,8 @@ impl<M: MessageData, C: Control<M, C>> WrapPanel<M, C> {
         Self {
             widget,
             orientation: Orientation::Vertical,
+            item_spacing: 0.0,
+            line_spacing: 0.0,
             lines: Default::default(),
         }
     }
@@ -41,6 +45,32 @@ impl<M: MessageData, C: Control<M, C>> WrapPanel<M, C> {
     pub fn orientation(&self) -> Orientation {
         self.orientation
     }
+
+    /// Sets gap between adjacent items of a line. The gap is taken into account when
+    /// deciding whether an item fits the line, so an item that does not fit because of the
+    /// gap goes to the next line.
+    pub fn set_item_spacing(&mut self, spacing: f32) {
+        if self.item_spacing != spacing {
+            self.item_spacing = spacing;
+            self.widget.invalidate_layout();
+        }
+    }
+
+    pub fn item_spacing(&self) -> f32 {
+        self.item_spacing
+    }
+
+    /// Sets gap between adjacent lines (rows or columns depending on orientation).
+    pub fn set_line_spacing(&mut self, spacing: f32) {
+        if self.line_spacing != spacing {
+            self.line_spacing = spacing;
+            self.widget.invalidate_layout();
+        }
+    }
+
+    pub fn line_spacing(&self) -> f32 {
+        self.line_spacing
+    }
 }
 
 #[derive(Clone)]
@@ -66,43 +96,57 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for WrapPanel<M, C> {
     ) -> Vector2<f32> {
         let mut measured_size: Vector2<f32> = Vector2::default();
         let mut line_size = Vector2::default();
+        let mut line_empty = true;
+        let mut lines = 0;
         for child_handle in self.widget.children() {
             let child = ui.node(*child_handle);
             child.measure(ui, available_size);
+            if !child.visibility() {
+                continue;
+            }
             let desired = child.desired_size();
+            let gap = if line_empty { 0.0 } else { self.item_spacing };
             match self.orientation {
                 Orientation::Vertical => {
-                    if line_size.y + desired.y > available_size.y {
+                    if !line_empty && line_size.y + gap + desired.y > available_size.y {
                         // Commit column.
                         measured_size.y = measured_size.y.max(line_size.y);
                         measured_size.x += line_size.x;
                         line_size = Vector2::default();
+                        lines += 1;
+                        line_size.y = desired.y;
+                    } else {
+                        line_size.y += gap + desired.y;
                     }
                     line_size.x = line_size.x.max(desired.x);
-                    line_size.y += desired.y;
                 }
                 Orientation::Horizontal => {
-                    if line_size.x + desired.x > available_size.x {
+                    if !line_empty && line_size.x + gap + desired.x > available_size.x {
                         // Commit row.
                         measured_size.x = measured_size.x.max(line_size.x);
                         measured_size.y += line_size.y;
                         line_size = Vector2::default();
+                        lines += 1;
+                        line_size.x = desired.x;
+                    } else {
+                        line_size.x += gap + desired.x;
                     }
-                    line_size.x += desired.x;
                     line_size.y = line_size.y.max(desired.y);
                 }
             }
+            line_empty = false;
         }
 
         // Commit rest.
+        let line_gaps = lines as f32 * self.line_spacing;
         match self.orientation {
             Orientation::Vertical => {
                 measured_size.y = measured_size.y.max(line_size.y);
-                measured_size.x += line_size.x;
+                measured_size.x += line_size.x + line_gaps;
             }
             Orientation::Horizontal => {
                 measured_size.x = measured_size.x.max(line_size.x);
-                measured_size.y += line_size.y;
+                measured_size.y += line_size.y + line_gaps;
             }
         }
 
@@ -114,16 +158,22 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for WrapPanel<M, C> {
         let mut lines = self.lines.borrow_mut();
         lines.clear();
         let mut line = Line::default();
+        let mut line_empty = true;
         for child_handle in self.widget.children() {
             let child = ui.node(*child_handle);
+            if !child.visibility() {
+                line.children.end += 1;
+                continue;
+            }
             let desired = child.desired_size();
+            let gap = if line_empty { 0.0 } else { self.item_spacing };
             match self.orientation {
                 Orientation::Vertical => {
-                    if line.bounds.h() + desired.y > final_size.y {
+                    if !line_empty && line.bounds.h() + gap + desired.y > final_size.y {
                         // Commit column.
                         lines.push(line.clone());
                         // Advance column.
-                        line.bounds.position.x += line.bounds.w();
+                        line.bounds.position.x += line.bounds.w() + self.line_spacing;
                         line.bounds.position.y = 0.0;
                         line.bounds.size.x = desired.x;
                         line.bounds.size.y = desired.y;
@@ -131,30 +181,31 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for WrapPanel<M, C> {
                         line.children.start = line.children.end;
                         line.children.end = line.children.start + 1;
                     } else {
-                        line.bounds.size.y += desired.y;
+                        line.bounds.size.y += gap + desired.y;
                         line.bounds.size.x = line.bounds.w().max(desired.x);
                         line.children.end += 1;
                     }
                 }
                 Orientation::Horizontal => {
-                    if line.bounds.w() + desired.x > final_size.x {
+                    if !line_empty && line.bounds.w() + gap + desired.x > final_size.x {
                         // Commit row.
                         lines.push(line.clone());
                         // Advance row.
                         line.bounds.position.x = 0.0;
-                        line.bounds.position.y += line.bounds.h();
+                        line.bounds.position.y += line.bounds.h() + self.line_spacing;
                         line.bounds.size.x = desired.x;
                         line.bounds.size.y = desired.y;
                         // Reset children.
                         line.children.start = line.children.end;
                         line.children.end = line.children.start + 1;
                     } else {
-                        line.bounds.size.x += desired.x;
+                        line.bounds.size.x += gap + desired.x;
                         line.bounds.size.y = line.bounds.h().max(desired.y);
                         line.children.end += 1;
                     }
                 }
             }
+            line_empty = false;
         }
 
         // Commit rest.
@@ -162,12 +213,20 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for WrapPanel<M, C> {
 
         // Second pass - arrange children of lines.
         let mut full_size = Vector2::default();
-        for line in lines.iter() {
+        for (i, line) in lines.iter().enumerate() {
             let mut cursor = line.bounds.position;
+            let mut gap = 0.0;
             for child_index in line.children.clone() {
                 let child_handle = self.children()[child_index];
                 let child = ui.node(child_handle);
                 let desired = child.desired_size();
+                if child.visibility() {
+                    match self.orientation {
+                        Orientation::Vertical => cursor.y += gap,
+                        Orientation::Horizontal => cursor.x += gap,
+                    }
+                    gap = self.item_spacing;
+                }
                 match self.orientation {
                     Orientation::Vertical => {
                         let child_bounds =
@@ -183,14 +242,15 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for WrapPanel<M, C> {
                     }
                 }
             }
+            let line_gap = if i > 0 { self.line_spacing } else { 0.0 };
             match self.orientation {
                 Orientation::Vertical => {
-                    full_size.x += line.bounds.w();
+                    full_size.x += line_gap + line.bounds.w();
                     full_size.y = final_size.y.max(line.bounds.h());
                 }
                 Orientation::Horizontal => {
                     full_size.x = final_size.x.max(line.bounds.w());
-                    full_size.y += line.bounds.h();
+                    full_size.y += line_gap + line.bounds.h();
                 }
             }
         }
@@ -210,6 +270,8 @@ impl<M: MessageData, C: Control<M, C>> Control<M, C> for WrapPanel<M, C> {
 pub struct WrapPanelBuilder<M: MessageData, C: Control<M, C>> {
     widget_builder: WidgetBuilder<M, C>,
     orientation: Option<Orientation>,
+    item_spacing: f32,
+    line_spacing: f32,
 }
 
 impl<M: MessageData, C: Control<M, C>> WrapPanelBuilder<M, C> {
@@ -217,6 +279,8 @@ impl<M: MessageData, C: Control<M, C>> WrapPanelBuilder<M, C> {
         Self {
             widget_builder,
             orientation: None,
+            item_spacing: 0.0,
+            line_spacing: 0.0,
         }
     }
 
@@ -225,10 +289,24 @@ impl<M: MessageData, C: Control<M, C>> WrapPanelBuilder<M, C> {
         self
     }
 
+    /// See `WrapPanel::set_item_spacing`.
+    pub fn with_item_spacing(mut self, spacing: f32) -> Self {
+        self.item_spacing = spacing;
+        self
+    }
+
+    /// See `WrapPanel::set_line_spacing`.
+    pub fn with_line_spacing(mut self, spacing: f32) -> Self {
+        self.line_spacing = spacing;
+        self
+    }
+
     pub fn build_node(self) -> UINode<M, C> {
         let stack_panel = WrapPanel {
             widget: self.widget_builder.build(),
             orientation: self.orientation.unwrap_or(Orientation::Vertical),
+            item_spacing: self.item_spacing,
+            line_spacing: self.line_spacing,
             lines: Default::default(),
         };
 
@@ -239,3 +317,57 @@ impl<M: MessageData, C: Control<M, C>> WrapPanelBuilder<M, C> {
         ui.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        border::BorderBuilder, core::algebra::Vector2, node::StubNode, widget::WidgetBuilder,
+        wrap_panel::WrapPanelBuilder, Orientation, UINode, UserInterface,
+    };
+
+    #[test]
+    fn spacing() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::<(), StubNode>::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let items = (0..3)
+            .map(|_| {
+                BorderBuilder::new(WidgetBuilder::new().with_width(45.0).with_height(20.0))
+                    .build(ctx)
+            })
+            .collect::<Vec<_>>();
+        // Two items with the gap take 100 units, so the second one must wrap.
+        let panel = WrapPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(95.0)
+                .with_children(items.iter()),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .with_item_spacing(10.0)
+        .with_line_spacing(5.0)
+        .build(ctx);
+        ui.update(screen_size, 0.0);
+
+        for (i, &item) in items.iter().enumerate() {
+            assert_eq!(
+                ui.node(item).actual_local_position(),
+                Vector2::new(0.0, i as f32 * 25.0)
+            );
+        }
+        assert_eq!(ui.node(panel).desired_size(), Vector2::new(95.0, 70.0));
+
+        if let UINode::WrapPanel(panel) = &mut ui.nodes[panel] {
+            panel.set_item_spacing(5.0);
+        }
+        ui.update(screen_size, 0.0);
+
+        assert_eq!(
+            ui.node(items[1]).actual_local_position(),
+            Vector2::new(50.0, 0.0)
+        );
+        assert_eq!(
+            ui.node(items[2]).actual_local_position(),
+            Vector2::new(0.0, 25.0)
+        );
+    }
+}
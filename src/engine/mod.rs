@@ -7,7 +7,9 @@ pub mod error;
 pub mod resource_manager;
 
 use crate::core::algebra::Vector2;
-use crate::resource::texture::TextureKind;
+use crate::gui::draw::SharedTexture;
+use crate::resource::texture::{Texture, TextureKind, TexturePixelKind, TextureState};
+use crate::resource::ResourceState;
 use crate::utils::log::{Log, MessageKind};
 use crate::{
     core::visitor::{Visit, VisitResult, Visitor},
@@ -48,6 +50,8 @@ pub struct Engine<M: MessageData, C: Control<M, C>> {
     /// for such statistics, probably it is best to make separate structure to hold all
     /// such data.
     pub ui_time: Duration,
+    /// Cursor visibility that was applied to the window last time.
+    cursor_visible: bool,
 }
 
 impl<M: MessageData, C: Control<M, C>> Engine<M, C> {
@@ -89,16 +93,21 @@ impl<M: MessageData, C: Control<M, C>> Engine<M, C> {
         let client_size = context.window().inner_size();
         let sound_engine = SoundEngine::new();
 
+        let mut user_interface = UserInterface::new(Vector2::new(
+            client_size.width as f32,
+            client_size.height as f32,
+        ));
+        user_interface.set_texture_size_provider(Some(Box::new(ui_texture_size)));
+        user_interface.set_texture_alpha_provider(Some(Box::new(ui_texture_alpha)));
+
         Ok(Self {
             renderer: Renderer::new(&mut context, client_size.into())?,
             resource_manager: ResourceManager::new(),
             scenes: SceneContainer::new(sound_engine.clone()),
             sound_engine,
-            user_interface: UserInterface::new(Vector2::new(
-                client_size.width as f32,
-                client_size.height as f32,
-            )),
+            user_interface,
             ui_time: Default::default(),
+            cursor_visible: true,
             context,
         })
     }
@@ -134,6 +143,12 @@ impl<M: MessageData, C: Control<M, C>> Engine<M, C> {
         let time = time::Instant::now();
         self.user_interface.update(window_size, dt);
         self.ui_time = time::Instant::now() - time;
+
+        let cursor_visible = self.user_interface.is_cursor_visible();
+        if self.cursor_visible != cursor_visible {
+            self.cursor_visible = cursor_visible;
+            self.context.window().set_cursor_visible(cursor_visible);
+        }
     }
 
     /// Performs rendering of single frame, must be called from your game loop, otherwise you won't
@@ -150,6 +165,60 @@ impl<M: MessageData, C: Control<M, C>> Engine<M, C> {
     }
 }
 
+/// Returns size of a texture used by the UI, size is unknown while the texture is loading.
+fn ui_texture_size(texture: &SharedTexture) -> Option<Vector2<f32>> {
+    let texture = Texture::from(texture.0.clone().downcast::<Mutex<TextureState>>().ok()?);
+    let state = texture.try_acquire_state()?;
+    if let ResourceState::Ok(data) = &*state {
+        if let TextureKind::Rectangle { width, height } = data.kind {
+            return Some(Vector2::new(width as f32, height as f32));
+        }
+    }
+    None
+}
+
+/// Returns alpha of a pixel of a texture used by the UI, it is read from first mip level of
+/// the texture. Textures without alpha channel are opaque, alpha of compressed textures is
+/// unknown.
+fn ui_texture_alpha(texture: &SharedTexture, tex_coord: Vector2<f32>) -> Option<f32> {
+    let texture = Texture::from(texture.0.clone().downcast::<Mutex<TextureState>>().ok()?);
+    let state = texture.try_acquire_state()?;
+    if let ResourceState::Ok(data) = &*state {
+        if let TextureKind::Rectangle { width, height } = data.kind {
+            if width == 0 || height == 0 {
+                return None;
+            }
+            let x = ((tex_coord.x.max(0.0).min(1.0) * width as f32) as u32).min(width - 1);
+            let y = ((tex_coord.y.max(0.0).min(1.0) * height as f32) as u32).min(height - 1);
+            let pixel = (y * width + x) as usize;
+            return match data.pixel_kind {
+                TexturePixelKind::RGBA8 | TexturePixelKind::BGRA8 => data
+                    .bytes
+                    .get(pixel * 4 + 3)
+                    .map(|&alpha| alpha as f32 / 255.0),
+                TexturePixelKind::RGBA16 => {
+                    let offset = pixel * 8 + 6;
+                    data.bytes.get(offset..offset + 2).map(|alpha| {
+                        u16::from_le_bytes([alpha[0], alpha[1]]) as f32 / u16::MAX as f32
+                    })
+                }
+                TexturePixelKind::R8
+                | TexturePixelKind::RGB8
+                | TexturePixelKind::RG8
+                | TexturePixelKind::R16
+                | TexturePixelKind::RG16
+                | TexturePixelKind::BGR8
+                | TexturePixelKind::RGB16
+                | TexturePixelKind::DXT1RGB => Some(1.0),
+                TexturePixelKind::DXT1RGBA
+                | TexturePixelKind::DXT3RGBA
+                | TexturePixelKind::DXT5RGBA => None,
+            };
+        }
+    }
+    None
+}
+
 impl<M: MessageData, C: Control<M, C>> Visit for Engine<M, C> {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         visitor.enter_region(name)?;
@@ -0,0 +1,253 @@
+//! Blurred backdrop of UI panels, see `gui::draw::Backdrop::Blur`.
+//!
+//! Region behind a panel is copied out of the frame buffer the UI is rendered into and
+//! then blurred by separable gaussian blur in two passes. Result is sampled by the UI
+//! shader in screen space, so it matches the pixels it was taken from.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        math::Rect,
+        scope_profile,
+    },
+    renderer::{
+        error::RendererError,
+        framework::{
+            framebuffer::{
+                Attachment, AttachmentKind, CullFace, DrawParameters, FrameBuffer, FrameBufferTrait,
+            },
+            gl,
+            gpu_program::{GpuProgram, UniformLocation, UniformValue},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::{ColorMask, PipelineState},
+        },
+        surface::SurfaceSharedData,
+        GeometryCache, RenderPassStatistics,
+    },
+};
+use std::{cell::RefCell, rc::Rc};
+
+struct Shader {
+    program: GpuProgram,
+    world_view_projection_matrix: UniformLocation,
+    input_texture: UniformLocation,
+    direction: UniformLocation,
+    radius: UniformLocation,
+    uv_max: UniformLocation,
+}
+
+impl Shader {
+    fn new() -> Result<Self, RendererError> {
+        let fragment_source = include_str!("shaders/backdrop_blur_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source("BackdropBlurShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            world_view_projection_matrix: program.uniform_location("worldViewProjection")?,
+            input_texture: program.uniform_location("inputTexture")?,
+            direction: program.uniform_location("direction")?,
+            radius: program.uniform_location("radius")?,
+            uv_max: program.uniform_location("uvMax")?,
+            program,
+        })
+    }
+}
+
+/// Pair of frame buffers of the same size, blur passes are ping-ponged between them.
+struct Targets {
+    capture: FrameBuffer,
+    intermediate: FrameBuffer,
+    width: usize,
+    height: usize,
+}
+
+fn make_target(
+    state: &mut PipelineState,
+    width: usize,
+    height: usize,
+) -> Result<FrameBuffer, RendererError> {
+    let kind = GpuTextureKind::Rectangle { width, height };
+    let mut texture = GpuTexture::new(
+        state,
+        kind,
+        PixelKind::RGBA8,
+        MinificationFilter::Linear,
+        MagnificationFilter::Linear,
+        1,
+        None,
+    )?;
+    texture
+        .bind_mut(state, 0)
+        .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+        .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
+    FrameBuffer::new(
+        state,
+        None,
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture: Rc::new(RefCell::new(texture)),
+        }],
+    )
+}
+
+pub(in crate) struct BackdropRenderer {
+    shader: Shader,
+    quad: SurfaceSharedData,
+    // Created on first use and grown to the largest region requested so far.
+    targets: Option<Targets>,
+}
+
+/// Blurred copy of a region of a frame buffer.
+pub(in crate) struct BlurredBackdrop {
+    pub texture: Rc<RefCell<GpuTexture>>,
+    /// Size of the texture in pixels, blurred region occupies its bottom left corner.
+    pub texture_size: Vector2<f32>,
+    pub statistics: RenderPassStatistics,
+}
+
+impl BackdropRenderer {
+    pub fn new() -> Result<Self, RendererError> {
+        Ok(Self {
+            shader: Shader::new()?,
+            quad: SurfaceSharedData::make_unit_xy_quad(),
+            targets: None,
+        })
+    }
+
+    /// Copies `region` (in pixels, with origin at lower left corner of frame buffer, just as
+    /// OpenGL wants) of frame buffer `source` and blurs it with given radius (in pixels).
+    /// Scissor test must be disabled by caller, otherwise only a part of region is blurred.
+    pub(in crate) fn render(
+        &mut self,
+        state: &mut PipelineState,
+        geom_cache: &mut GeometryCache,
+        source: u32,
+        region: Rect<i32>,
+        radius: f32,
+    ) -> Result<BlurredBackdrop, RendererError> {
+        scope_profile!();
+
+        let width = region.w() as usize;
+        let height = region.h() as usize;
+
+        let needs_resize = match self.targets.as_ref() {
+            Some(targets) => targets.width < width || targets.height < height,
+            None => true,
+        };
+        if needs_resize {
+            let (width, height) = match self.targets.as_ref() {
+                Some(targets) => (targets.width.max(width), targets.height.max(height)),
+                None => (width, height),
+            };
+            self.targets = Some(Targets {
+                capture: make_target(state, width, height)?,
+                intermediate: make_target(state, width, height)?,
+                width,
+                height,
+            });
+        }
+        let targets = self.targets.as_mut().unwrap();
+
+        let capture_texture = targets.capture.color_attachments()[0].texture.clone();
+        let intermediate_texture = targets.intermediate.color_attachments()[0].texture.clone();
+
+        state.set_framebuffer(source);
+        capture_texture.borrow().bind(state, 0);
+        unsafe {
+            // Texture unit is switched only when binding has changed, make sure the copy
+            // goes into the capture texture.
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::CopyTexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                0,
+                0,
+                region.x(),
+                region.y(),
+                region.w(),
+                region.h(),
+            );
+        }
+
+        let texture_size = Vector2::new(targets.width as f32, targets.height as f32);
+        let uv_max = Vector2::new(
+            width as f32 / texture_size.x,
+            height as f32 / texture_size.y,
+        );
+
+        let viewport = Rect::new(0, 0, region.w(), region.h());
+        let wvp = Matrix4::new_orthographic(
+            0.0,
+            viewport.w() as f32,
+            viewport.h() as f32,
+            0.0,
+            -1.0,
+            1.0,
+        ) * Matrix4::new_nonuniform_scaling(&Vector3::new(
+            viewport.w() as f32,
+            viewport.h() as f32,
+            0.0,
+        ));
+
+        let params = DrawParameters {
+            cull_face: CullFace::Back,
+            culling: false,
+            color_write: ColorMask::all(true),
+            depth_write: false,
+            stencil_test: false,
+            depth_test: false,
+            blend: false,
+        };
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let passes = [
+            (
+                &mut targets.intermediate,
+                capture_texture.clone(),
+                Vector2::new(1.0, 0.0),
+            ),
+            (
+                &mut targets.capture,
+                intermediate_texture,
+                Vector2::new(0.0, 1.0),
+            ),
+        ];
+        for (target, input, direction) in passes {
+            statistics += target.draw(
+                geom_cache.get(state, &self.quad),
+                state,
+                viewport,
+                &self.shader.program,
+                &params,
+                &[
+                    (
+                        self.shader.world_view_projection_matrix,
+                        UniformValue::Matrix4(wvp),
+                    ),
+                    (
+                        self.shader.input_texture,
+                        UniformValue::Sampler {
+                            index: 0,
+                            texture: input,
+                        },
+                    ),
+                    (self.shader.direction, UniformValue::Vector2(direction)),
+                    (self.shader.radius, UniformValue::Float(radius)),
+                    (self.shader.uv_max, UniformValue::Vector2(uv_max)),
+                ],
+            );
+        }
+
+        Ok(BlurredBackdrop {
+            texture: capture_texture,
+            texture_size,
+            statistics,
+        })
+    }
+}
@@ -18,6 +18,7 @@ pub mod surface;
 #[allow(unsafe_code)]
 mod framework;
 
+mod backdrop;
 mod batch;
 mod blur;
 mod deferred_light_renderer;
@@ -42,7 +43,7 @@ use crate::{
         scope_profile,
     },
     engine::resource_manager::TimedEntry,
-    gui::{draw::DrawingContext, message::MessageData, Control, UserInterface},
+    gui::{draw::DrawingContext, message::MessageData, node::UINode, Control, UserInterface},
     renderer::{
         batch::{BatchStorage, InstanceData},
         debug_renderer::DebugRenderer,
@@ -73,7 +74,7 @@ use crate::{
         particle_system_renderer::{ParticleSystemRenderContext, ParticleSystemRenderer},
         sprite_renderer::{SpriteRenderContext, SpriteRenderer},
         surface::SurfaceSharedData,
-        ui_renderer::{UiRenderContext, UiRenderer},
+        ui_renderer::{set_ui_scissor_box, UiRenderContext, UiRenderer},
     },
     resource::texture::{Texture, TextureKind, TextureState},
     scene::{node::Node, Scene, SceneContainer},
@@ -904,13 +905,69 @@ impl Renderer {
 
     /// Renders given UI into specified render target. This method is especially useful if you need
     /// to have off-screen UIs (like interactive touch-screen in Doom 3, Dead Space, etc).
+    /// Previous content of the render target is kept, only changed area of the UI (see
+    /// `UserInterface::dirty_region`) is redrawn.
     pub fn render_ui_to_texture<M: MessageData, C: Control<M, C>>(
         &mut self,
         render_target: Texture,
         ui: &mut UserInterface<M, C>,
     ) -> Result<(), RendererError> {
-        let new_width = ui.screen_size().x as usize;
-        let new_height = ui.screen_size().y as usize;
+        let frame_size = ui.screen_size();
+        ui.draw();
+        let region = if self.texture_cache.map.contains_key(&render_target.key()) {
+            match ui.dirty_region() {
+                Some(region) => Some(region),
+                // Nothing has changed, the texture is up to date.
+                None => return Ok(()),
+            }
+        } else {
+            None
+        };
+        self.render_drawing_context_to_texture(
+            render_target,
+            frame_size,
+            ui.get_drawing_context(),
+            region,
+        )
+    }
+
+    /// Renders sub-tree of given UI into specified render target. Sub-tree is laid out in
+    /// a rectangle of given size (in pixels), see `UserInterface::draw_subtree` for details.
+    /// Render target is updated only if sub-tree has changed since last call, returns true
+    /// if render target was updated. The texture is not returned, sub-tree is rendered into
+    /// the given render target, so the caller keeps it and can use it right away.
+    pub fn render_ui_subtree_to_texture<M: MessageData, C: Control<M, C>>(
+        &mut self,
+        render_target: Texture,
+        ui: &mut UserInterface<M, C>,
+        root: Handle<UINode<M, C>>,
+        size: Vector2<f32>,
+    ) -> Result<bool, RendererError> {
+        if !self.texture_cache.map.contains_key(&render_target.key()) {
+            // Texture was never rendered (or it was removed from the cache), so it must be
+            // rendered even if sub-tree has not changed.
+            ui.invalidate_subtree(root);
+        }
+        match ui.draw_subtree(root, size) {
+            Some(drawing_context) => {
+                self.render_drawing_context_to_texture(render_target, size, drawing_context, None)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Renders drawing context into given render target, `region` limits rendering to given area
+    /// and keeps the rest of the target, `None` means that the whole target is redrawn.
+    fn render_drawing_context_to_texture(
+        &mut self,
+        render_target: Texture,
+        frame_size: Vector2<f32>,
+        drawing_context: &DrawingContext,
+        mut region: Option<Rect<f32>>,
+    ) -> Result<(), RendererError> {
+        let new_width = frame_size.x as usize;
+        let new_height = frame_size.y as usize;
 
         // Create or reuse existing frame buffer.
         let frame_buffer = match self.ui_frame_buffers.entry(render_target.key()) {
@@ -920,7 +977,9 @@ impl Renderer {
                 let color_texture_kind = frame.texture.borrow().kind();
                 if let GpuTextureKind::Rectangle { width, height } = color_texture_kind {
                     if width != new_width || height != new_height {
-                        *frame_buffer = make_ui_frame_buffer(ui.screen_size(), &mut self.state)?;
+                        *frame_buffer = make_ui_frame_buffer(frame_size, &mut self.state)?;
+                        // Content of new frame buffer is undefined.
+                        region = None;
                     }
                 } else {
                     panic!("ui can be rendered only in rectangle texture!")
@@ -928,12 +987,18 @@ impl Renderer {
                 frame_buffer
             }
             Entry::Vacant(entry) => {
-                entry.insert(make_ui_frame_buffer(ui.screen_size(), &mut self.state)?)
+                region = None;
+                entry.insert(make_ui_frame_buffer(frame_size, &mut self.state)?)
             }
         };
 
         let viewport = Rect::new(0, 0, new_width as i32, new_height as i32);
 
+        // Clear is limited by scissor box, so only the region is cleared.
+        if let Some(region) = region {
+            self.state.set_scissor_test(true);
+            set_ui_scissor_box(&mut self.state, viewport, region);
+        }
         frame_buffer.clear(
             &mut self.state,
             viewport,
@@ -941,16 +1006,19 @@ impl Renderer {
             Some(0.0),
             Some(0),
         );
+        self.state.set_scissor_test(false);
 
         self.statistics += self.ui_renderer.render(UiRenderContext {
             state: &mut self.state,
             viewport,
             frame_buffer,
-            frame_width: ui.screen_size().x,
-            frame_height: ui.screen_size().y,
-            drawing_context: &ui.draw(),
+            frame_width: frame_size.x,
+            frame_height: frame_size.y,
+            drawing_context,
             white_dummy: self.white_dummy.clone(),
             texture_cache: &mut self.texture_cache,
+            geometry_cache: &mut self.geometry_cache,
+            region,
         })?;
 
         // Finally register texture in the cache so it will become available as texture in deferred/forward
@@ -1214,6 +1282,9 @@ impl Renderer {
             drawing_context,
             white_dummy: self.white_dummy.clone(),
             texture_cache: &mut self.texture_cache,
+            geometry_cache: &mut self.geometry_cache,
+            // Scene is redrawn every frame, so the UI must be redrawn entirely too.
+            region: None,
         })?;
 
         Ok(())
@@ -0,0 +1,44 @@
+// Separable gaussian blur of region behind UI panels, it is done in two passes:
+// horizontal and vertical.
+
+#version 330 core
+
+#define MAX_SAMPLES 32
+
+uniform sampler2D inputTexture;
+// Direction of a pass, (1, 0) or (0, 1).
+uniform vec2 direction;
+// Radius of blur in texels.
+uniform float radius;
+// Texture could be bigger than blurred region, samples are clamped to the region.
+uniform vec2 uvMax;
+
+out vec4 fragColor;
+
+in vec2 texCoord;
+
+void main()
+{
+    vec2 texelSize = 1.0 / vec2(textureSize(inputTexture, 0));
+    vec2 minUv = 0.5 * texelSize;
+    vec2 maxUv = uvMax - 0.5 * texelSize;
+    vec2 uv = texCoord * uvMax;
+
+    // Large radii are sampled sparsely, linear filtering smooths the gaps.
+    int count = int(min(ceil(radius), float(MAX_SAMPLES)));
+    float stepSize = count > 0 ? radius / float(count) : 0.0;
+    float sigma = max(radius * 0.5, 0.001);
+
+    vec4 sum = texture(inputTexture, clamp(uv, minUv, maxUv));
+    float weightSum = 1.0;
+    for (int i = 1; i <= count; ++i)
+    {
+        float offset = float(i) * stepSize;
+        float weight = exp(-0.5 * offset * offset / (sigma * sigma));
+        vec2 delta = direction * texelSize * offset;
+        sum += weight * texture(inputTexture, clamp(uv + delta, minUv, maxUv));
+        sum += weight * texture(inputTexture, clamp(uv - delta, minUv, maxUv));
+        weightSum += 2.0 * weight;
+    }
+    fragColor = sum / weightSum;
+}
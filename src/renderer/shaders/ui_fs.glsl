@@ -18,14 +18,19 @@ uniform vec2 gradientOrigin;
 // End point of linear gradient in normalized coordinates.
 uniform vec2 gradientEnd;
 
-uniform vec2 resolution;
 uniform vec2 boundsMin;
 uniform vec2 boundsMax;
 
+// Blurred backdrop is sampled in screen space, origin and size are in pixels of frame buffer.
+uniform bool isBackdrop;
+uniform vec2 backdropOrigin;
+uniform vec2 backdropSize;
+
 out vec4 fragColor;
 
 in vec2 texCoord;
 in vec4 color;
+in vec2 localPosition;
 
 float project_point(vec2 a, vec2 b, vec2 p) {
     vec2 ab = b - a;
@@ -47,7 +52,8 @@ int find_stop_index(float t) {
 void main()
 {
     vec2 size = vec2(boundsMax.x - boundsMin.x, boundsMax.y - boundsMin.y);
-    vec2 localPosition = (vec2(gl_FragCoord.x, resolution.y - gl_FragCoord.y) - boundsMin) / size;
+    // Bounds and position are both taken before render transform of the command.
+    vec2 normalizedPosition = (localPosition - boundsMin) / size;
 
     if (brushType == 0) {
         // Solid color
@@ -58,10 +64,10 @@ void main()
 
         if (brushType == 1) {
             // Linear gradient
-            t = project_point(gradientOrigin, gradientEnd, localPosition);
+            t = project_point(gradientOrigin, gradientEnd, normalizedPosition);
         } else if (brushType == 2) {
             // Radial gradient
-            t = clamp(length(localPosition - gradientOrigin), 0.0, 1.0);
+            t = clamp(length(normalizedPosition - gradientOrigin), 0.0, 1.0);
         }
 
         int current = find_stop_index(t);
@@ -75,6 +81,10 @@ void main()
     {
         fragColor.a *= texture(diffuseTexture, texCoord).r;
     }
+    else if (isBackdrop)
+    {
+        fragColor *= texture(diffuseTexture, (gl_FragCoord.xy - backdropOrigin) / backdropSize);
+    }
     else
     {
         fragColor *= texture(diffuseTexture, texCoord);
@@ -8,10 +8,13 @@ uniform mat4 worldViewProjection;
 
 out vec2 texCoord;
 out vec4 color;
+// Position before render transform, so gradients follow transformed geometry.
+out vec2 localPosition;
 
 void main()
 {
     texCoord = vertexTexCoord;
     color = vertexColor;
+    localPosition = vertexPosition.xy;
     gl_Position = worldViewProjection * vec4(vertexPosition, 1.0);
 }
\ No newline at end of file
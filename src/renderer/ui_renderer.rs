@@ -7,9 +7,10 @@ use crate::{
     },
     gui::{
         brush::Brush,
-        draw::{CommandTexture, DrawingContext, SharedTexture},
+        draw::{transform_bounds, CommandTexture, DrawingContext, SharedTexture},
     },
     renderer::{
+        backdrop::BackdropRenderer,
         error::RendererError,
         framework::{
             framebuffer::{CullFace, DrawParameters, DrawPartContext, FrameBufferTrait},
@@ -22,7 +23,7 @@ use crate::{
             gpu_texture::GpuTexture,
             state::{ColorMask, PipelineState, StencilFunc, StencilOp},
         },
-        RenderPassStatistics, TextureCache,
+        GeometryCache, RenderPassStatistics, TextureCache,
     },
     resource::texture::{Texture, TextureData, TextureKind, TexturePixelKind, TextureState},
 };
@@ -44,10 +45,12 @@ struct UiShader {
     gradient_stops: UniformLocation,
     gradient_origin: UniformLocation,
     gradient_end: UniformLocation,
-    resolution: UniformLocation,
     bounds_min: UniformLocation,
     bounds_max: UniformLocation,
     opacity: UniformLocation,
+    is_backdrop: UniformLocation,
+    backdrop_origin: UniformLocation,
+    backdrop_size: UniformLocation,
 }
 
 impl UiShader {
@@ -68,8 +71,10 @@ impl UiShader {
             gradient_end: program.uniform_location("gradientEnd")?,
             bounds_min: program.uniform_location("boundsMin")?,
             bounds_max: program.uniform_location("boundsMax")?,
-            resolution: program.uniform_location("resolution")?,
             opacity: program.uniform_location("opacity")?,
+            is_backdrop: program.uniform_location("isBackdrop")?,
+            backdrop_origin: program.uniform_location("backdropOrigin")?,
+            backdrop_size: program.uniform_location("backdropSize")?,
             program,
         })
     }
@@ -79,6 +84,7 @@ pub struct UiRenderer {
     shader: UiShader,
     geometry_buffer: GeometryBuffer,
     clipping_geometry_buffer: GeometryBuffer,
+    backdrop: BackdropRenderer,
 }
 
 pub(in crate) struct UiRenderContext<'a, 'b, 'c> {
@@ -90,6 +96,25 @@ pub(in crate) struct UiRenderContext<'a, 'b, 'c> {
     pub drawing_context: &'c DrawingContext,
     pub white_dummy: Rc<RefCell<GpuTexture>>,
     pub texture_cache: &'a mut TextureCache,
+    pub geometry_cache: &'a mut GeometryCache,
+    /// Limits rendering to given area (in pixels), see `UserInterface::dirty_region`.
+    pub region: Option<Rect<f32>>,
+}
+
+/// Sets scissor box to given rectangle, which is in UI coordinates with origin at top left
+/// corner of the viewport.
+pub(in crate) fn set_ui_scissor_box(state: &mut PipelineState, viewport: Rect<i32>, rect: Rect<f32>) {
+    let x = rect.position.x.floor();
+    let y = rect.position.y.floor();
+    let w = (rect.position.x + rect.size.x).ceil() - x;
+    let h = (rect.position.y + rect.size.y).ceil() - y;
+    state.set_scissor_box(
+        x as i32,
+        // Because OpenGL is was designed for mathematicians, it has origin at lower left corner.
+        viewport.size.y - (y + h) as i32,
+        w as i32,
+        h as i32,
+    );
 }
 
 impl UiRenderer {
@@ -141,6 +166,7 @@ impl UiRenderer {
             geometry_buffer,
             clipping_geometry_buffer,
             shader: UiShader::new()?,
+            backdrop: BackdropRenderer::new()?,
         })
     }
 
@@ -159,6 +185,8 @@ impl UiRenderer {
             drawing_context,
             white_dummy,
             texture_cache,
+            geometry_cache,
+            region,
         } = args;
 
         let mut statistics = RenderPassStatistics::default();
@@ -176,22 +204,97 @@ impl UiRenderer {
         state.set_scissor_test(true);
 
         for cmd in drawing_context.get_commands() {
+            // Custom commands must be handled by custom renderers.
+            if cmd.custom.is_some() {
+                continue;
+            }
+
+            // Render transform of the command is a 2D affine transform in homogeneous
+            // coordinates, expand it to 3D.
+            let t = &cmd.transform;
+            let transform = Matrix4::new(
+                t[(0, 0)],
+                t[(0, 1)],
+                0.0,
+                t[(0, 2)],
+                t[(1, 0)],
+                t[(1, 1)],
+                0.0,
+                t[(1, 2)],
+                0.0,
+                0.0,
+                1.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                1.0,
+            );
+            let wvp = ortho * transform;
+
             let mut diffuse_texture = white_dummy.clone();
             let mut is_font_texture = false;
+            let mut is_backdrop = false;
+            let mut backdrop_origin = Vector2::default();
+            let mut backdrop_size = Vector2::new(1.0, 1.0);
+
+            // Scissor box is axis-aligned, so for rotated commands it is only a rough clip,
+            // exact clipping is done by clipping geometry.
+            let mut clip_bounds = transform_bounds(&cmd.transform, &cmd.clip_bounds);
+            if let Some(region) = region {
+                let min = clip_bounds.position.sup(&region.position);
+                let max = clip_bounds
+                    .right_bottom_corner()
+                    .inf(&region.right_bottom_corner());
+                if max.x <= min.x || max.y <= min.y {
+                    continue;
+                }
+                clip_bounds = Rect::new(min.x, min.y, max.x - min.x, max.y - min.y);
+            }
 
-            let mut clip_bounds = cmd.clip_bounds;
-            clip_bounds.position.x = clip_bounds.position.x.floor();
-            clip_bounds.position.y = clip_bounds.position.y.floor();
-            clip_bounds.size.x = clip_bounds.size.x.ceil();
-            clip_bounds.size.y = clip_bounds.size.y.ceil();
-
-            state.set_scissor_box(
-                clip_bounds.position.x as i32,
-                // Because OpenGL is was designed for mathematicians, it has origin at lower left corner.
-                viewport.size.y - (clip_bounds.position.y + clip_bounds.size.y) as i32,
-                clip_bounds.size.x as i32,
-                clip_bounds.size.y as i32,
-            );
+            // Backdrop must be captured before anything of the command is drawn, blur pass
+            // uses its own render targets so it is done before scissor box and stencil are set.
+            if let CommandTexture::Backdrop { blur_radius } = cmd.texture {
+                let inflate = blur_radius.ceil();
+                let bounds = transform_bounds(&cmd.transform, &cmd.bounds);
+                let x = (bounds.position.x - inflate).floor().max(0.0);
+                let y = (bounds.position.y - inflate).floor().max(0.0);
+                let right = (bounds.position.x + bounds.size.x + inflate)
+                    .ceil()
+                    .min(viewport.w() as f32);
+                let bottom = (bounds.position.y + bounds.size.y + inflate)
+                    .ceil()
+                    .min(viewport.h() as f32);
+                if right <= x || bottom <= y {
+                    continue;
+                }
+                let (x, y, w, h) = (x as i32, y as i32, (right - x) as i32, (bottom - y) as i32);
+                // OpenGL has origin at lower left corner.
+                let region = Rect::new(
+                    viewport.x() + x,
+                    viewport.y() + viewport.h() - (y + h),
+                    w,
+                    h,
+                );
+
+                state.set_scissor_test(false);
+                let blurred = self.backdrop.render(
+                    state,
+                    geometry_cache,
+                    backbuffer.id(),
+                    region,
+                    blur_radius,
+                )?;
+                state.set_scissor_test(true);
+
+                statistics += blurred.statistics;
+                diffuse_texture = blurred.texture;
+                is_backdrop = true;
+                backdrop_origin = Vector2::new(region.x() as f32, region.y() as f32);
+                backdrop_size = blurred.texture_size;
+            }
+
+            set_ui_scissor_box(state, viewport, clip_bounds);
 
             let mut stencil_test = false;
 
@@ -236,7 +339,8 @@ impl UiRenderer {
                         depth_test: false,
                         blend: false,
                     },
-                    &[(self.shader.wvp_matrix, UniformValue::Matrix4(ortho))],
+                    // Clipping geometry is not transformed, just like the geometry of the command.
+                    &[(self.shader.wvp_matrix, UniformValue::Matrix4(wvp))],
                 );
 
                 // Make sure main geometry will be drawn only on marked pixels.
@@ -302,11 +406,7 @@ impl UiRenderer {
                         texture: diffuse_texture,
                     },
                 ),
-                (self.shader.wvp_matrix, UniformValue::Matrix4(ortho)),
-                (
-                    self.shader.resolution,
-                    UniformValue::Vector2(Vector2::new(frame_width, frame_height)),
-                ),
+                (self.shader.wvp_matrix, UniformValue::Matrix4(wvp)),
                 (
                     self.shader.bounds_min,
                     UniformValue::Vector2(cmd.bounds.position),
@@ -396,6 +496,15 @@ impl UiRenderer {
                     }),
                 ),
                 (self.shader.opacity, UniformValue::Float(cmd.opacity)),
+                (self.shader.is_backdrop, UniformValue::Bool(is_backdrop)),
+                (
+                    self.shader.backdrop_origin,
+                    UniformValue::Vector2(backdrop_origin),
+                ),
+                (
+                    self.shader.backdrop_size,
+                    UniformValue::Vector2(backdrop_size),
+                ),
             ];
 
             let params = DrawParameters {
@@ -11,10 +11,12 @@ pub mod uvgen;
 
 use crate::core::algebra::Vector2;
 use crate::{
-    event::{ElementState, ModifiersState, MouseScrollDelta, VirtualKeyCode, WindowEvent},
+    event::{
+        ElementState, ModifiersState, MouseScrollDelta, TouchPhase, VirtualKeyCode, WindowEvent,
+    },
     gui::{
         draw,
-        message::{ButtonState, KeyCode, KeyboardModifiers, OsEvent},
+        message::{ButtonState, KeyCode, KeyboardModifiers, OsEvent, TouchPhase as UiTouchPhase},
     },
     resource::texture::Texture,
 };
@@ -249,6 +251,16 @@ pub fn translate_state(state: ElementState) -> ButtonState {
     }
 }
 
+/// Translates touch phase to rg3d-ui touch phase.
+pub fn translate_touch_phase(phase: TouchPhase) -> UiTouchPhase {
+    match phase {
+        TouchPhase::Started => UiTouchPhase::Started,
+        TouchPhase::Moved => UiTouchPhase::Moved,
+        TouchPhase::Ended => UiTouchPhase::Ended,
+        TouchPhase::Cancelled => UiTouchPhase::Cancelled,
+    }
+}
+
 /// Translates window event to rg3d-ui event.
 pub fn translate_event(event: &WindowEvent) -> Option<OsEvent> {
     match event {
@@ -279,6 +291,12 @@ pub fn translate_event(event: &WindowEvent) -> Option<OsEvent> {
         &WindowEvent::ModifiersChanged(modifiers) => Some(OsEvent::KeyboardModifiers(
             translate_keyboard_modifiers(modifiers),
         )),
+        WindowEvent::Touch(touch) => Some(OsEvent::Touch {
+            id: touch.id,
+            phase: translate_touch_phase(touch.phase),
+            position: Vector2::new(touch.location.x as f32, touch.location.y as f32),
+        }),
+        &WindowEvent::Focused(focused) => Some(OsEvent::WindowFocused(focused)),
         _ => None,
     }
 }